@@ -0,0 +1,11 @@
+-- Staff accounts with hashed passwords and fine-grained roles
+CREATE TABLE IF NOT EXISTS users (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    username VARCHAR(255) NOT NULL UNIQUE,
+    display_name TEXT,
+    password_hash TEXT NOT NULL,
+    role VARCHAR(32) NOT NULL,
+    created_at TEXT DEFAULT CAST(CURRENT_TIMESTAMP AS TEXT),
+    updated_at TEXT DEFAULT CAST(CURRENT_TIMESTAMP AS TEXT),
+    last_login_at TEXT
+);
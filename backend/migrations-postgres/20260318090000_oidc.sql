@@ -0,0 +1,17 @@
+-- OpenID Connect single sign-on for staff accounts
+ALTER TABLE users ADD COLUMN oidc_subject TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_oidc_subject ON users (oidc_subject);
+
+CREATE TABLE IF NOT EXISTS oidc_login_requests (
+    id TEXT PRIMARY KEY,
+    nonce TEXT NOT NULL,
+    code_verifier TEXT NOT NULL,
+    redirect_uri TEXT NOT NULL,
+    return_to TEXT NOT NULL,
+    created_at_epoch BIGINT NOT NULL,
+    expires_at_epoch BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_oidc_login_requests_expires_at
+    ON oidc_login_requests (expires_at_epoch);
@@ -0,0 +1,14 @@
+-- Personal API tokens for automation; only a SHA-256 hash of each token is stored
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    name TEXT NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    token_prefix TEXT NOT NULL,
+    scopes TEXT NOT NULL,
+    expires_at_epoch BIGINT,
+    last_used_at TEXT,
+    created_at TEXT DEFAULT CAST(CURRENT_TIMESTAMP AS TEXT)
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_tokens_user_id ON api_tokens (user_id);
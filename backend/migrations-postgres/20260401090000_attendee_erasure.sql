@@ -0,0 +1,2 @@
+-- Marks attendees whose personal data was erased on request
+ALTER TABLE attendees ADD COLUMN erased_at TEXT;
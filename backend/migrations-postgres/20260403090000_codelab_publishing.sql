@@ -0,0 +1,3 @@
+-- Revision learners see; NULL until the codelab is first published, when learners see the working copy
+ALTER TABLE codelabs ADD COLUMN published_revision_id VARCHAR(255);
+ALTER TABLE codelabs ADD COLUMN published_at TEXT;
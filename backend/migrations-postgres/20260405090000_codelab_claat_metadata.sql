@@ -0,0 +1,3 @@
+-- Metadata carried by claat markdown: codelab categories and per-step durations
+ALTER TABLE codelabs ADD COLUMN categories TEXT;
+ALTER TABLE steps ADD COLUMN duration_minutes INTEGER;
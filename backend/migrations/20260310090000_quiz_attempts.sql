@@ -0,0 +1,23 @@
+-- Keep every quiz attempt instead of replacing earlier submissions
+CREATE TABLE IF NOT EXISTS quiz_attempts (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    codelab_id VARCHAR(255) NOT NULL,
+    attendee_id VARCHAR(255) NOT NULL,
+    attempt_number INTEGER NOT NULL,
+    total_questions INTEGER NOT NULL,
+    correct_count INTEGER NOT NULL,
+    score REAL NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (codelab_id) REFERENCES codelabs(id) ON DELETE CASCADE,
+    FOREIGN KEY (attendee_id) REFERENCES attendees(id) ON DELETE CASCADE,
+    UNIQUE (attendee_id, attempt_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_quiz_attempts_codelab
+    ON quiz_attempts (codelab_id);
+
+ALTER TABLE quiz_submissions ADD COLUMN attempt_id VARCHAR(255) REFERENCES quiz_attempts(id) ON DELETE CASCADE;
+
+-- Attempt limits and pass threshold per codelab (0 attempts = unlimited)
+ALTER TABLE codelabs ADD COLUMN quiz_max_attempts INTEGER DEFAULT 0;
+ALTER TABLE codelabs ADD COLUMN quiz_pass_percentage INTEGER DEFAULT 100;
@@ -0,0 +1,16 @@
+-- Signed completion certificates with a stable identifier and revocation state
+CREATE TABLE IF NOT EXISTS certificates (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    attendee_id VARCHAR(255) NOT NULL UNIQUE,
+    codelab_id VARCHAR(255) NOT NULL,
+    key_id VARCHAR(255) NOT NULL,
+    signed_token TEXT NOT NULL,
+    issued_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TEXT,
+    revocation_reason TEXT,
+    FOREIGN KEY (codelab_id) REFERENCES codelabs(id) ON DELETE CASCADE,
+    FOREIGN KEY (attendee_id) REFERENCES attendees(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_certificates_codelab
+    ON certificates (codelab_id);
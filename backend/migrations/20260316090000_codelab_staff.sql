@@ -0,0 +1,11 @@
+-- Per-codelab staff assignments scoping non-owner staff roles
+CREATE TABLE IF NOT EXISTS codelab_staff (
+    codelab_id VARCHAR(255) NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (codelab_id, user_id),
+    FOREIGN KEY (codelab_id) REFERENCES codelabs(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_codelab_staff_user ON codelab_staff(user_id);
@@ -0,0 +1,18 @@
+-- Registry of issued session tokens so individual sessions can be revoked
+CREATE TABLE IF NOT EXISTS auth_sessions (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    role VARCHAR(32) NOT NULL,
+    staff_role VARCHAR(32),
+    codelab_id VARCHAR(255),
+    ip TEXT,
+    user_agent TEXT,
+    issued_at_epoch BIGINT NOT NULL,
+    expires_at_epoch BIGINT NOT NULL,
+    revoked_at_epoch BIGINT,
+    revoked_by TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_sessions_subject ON auth_sessions (subject);
+CREATE INDEX IF NOT EXISTS idx_auth_sessions_codelab_id ON auth_sessions (codelab_id);
+CREATE INDEX IF NOT EXISTS idx_auth_sessions_expires_at ON auth_sessions (expires_at_epoch);
@@ -0,0 +1,28 @@
+-- TOTP second factor for staff password sign-in
+CREATE TABLE IF NOT EXISTS staff_totp (
+    subject VARCHAR(255) PRIMARY KEY NOT NULL,
+    secret TEXT NOT NULL,
+    enabled_at_epoch BIGINT,
+    last_used_step BIGINT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS staff_recovery_codes (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at_epoch BIGINT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_staff_recovery_codes_subject ON staff_recovery_codes (subject);
+
+-- Password-verified logins waiting for the second factor
+CREATE TABLE IF NOT EXISTS mfa_login_challenges (
+    id VARCHAR(64) PRIMARY KEY NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at_epoch BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_mfa_login_challenges_expires_at ON mfa_login_challenges (expires_at_epoch);
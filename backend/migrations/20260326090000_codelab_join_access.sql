@@ -0,0 +1,23 @@
+-- Optional join passcode per codelab, encrypted like attendee codes
+CREATE TABLE IF NOT EXISTS codelab_passcodes (
+    codelab_id VARCHAR(255) PRIMARY KEY NOT NULL,
+    passcode TEXT NOT NULL,
+    updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Expiring, usage-limited invite links; only a SHA-256 hash of each token is stored
+CREATE TABLE IF NOT EXISTS codelab_invites (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    codelab_id VARCHAR(255) NOT NULL,
+    label TEXT,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    token_prefix TEXT NOT NULL,
+    max_uses INTEGER,
+    use_count INTEGER NOT NULL DEFAULT 0,
+    expires_at_epoch BIGINT,
+    revoked_at_epoch BIGINT,
+    created_by VARCHAR(255) NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_codelab_invites_codelab_id ON codelab_invites (codelab_id);
@@ -0,0 +1,16 @@
+-- Seat limit (0 = unlimited) and registration window per codelab, as epoch seconds
+ALTER TABLE codelabs ADD COLUMN max_attendees INTEGER DEFAULT 0;
+ALTER TABLE codelabs ADD COLUMN registration_opens_at BIGINT;
+ALTER TABLE codelabs ADD COLUMN registration_closes_at BIGINT;
+
+-- Learners who registered after the codelab filled up, in arrival order
+CREATE TABLE IF NOT EXISTS codelab_waitlist (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    codelab_id VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    code VARCHAR(255) NOT NULL,
+    email TEXT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_codelab_waitlist_codelab_id ON codelab_waitlist (codelab_id);
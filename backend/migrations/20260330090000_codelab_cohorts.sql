@@ -0,0 +1,20 @@
+-- Scheduled runs of a codelab; bounds are epoch seconds
+CREATE TABLE IF NOT EXISTS codelab_cohorts (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    codelab_id VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    starts_at BIGINT,
+    ends_at BIGINT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_codelab_cohorts_codelab_id ON codelab_cohorts (codelab_id);
+
+-- Live data belongs to the cohort the attendee joined; NULL for codelabs run without cohorts
+ALTER TABLE attendees ADD COLUMN cohort_id VARCHAR(255);
+ALTER TABLE codelab_waitlist ADD COLUMN cohort_id VARCHAR(255);
+ALTER TABLE chat_messages ADD COLUMN cohort_id VARCHAR(255);
+ALTER TABLE help_requests ADD COLUMN cohort_id VARCHAR(255);
+ALTER TABLE feedback ADD COLUMN cohort_id VARCHAR(255);
+
+CREATE INDEX IF NOT EXISTS idx_attendees_cohort_id ON attendees (cohort_id);
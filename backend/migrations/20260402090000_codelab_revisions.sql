@@ -0,0 +1,12 @@
+-- Snapshots of a codelab's guide and steps taken on every content save
+CREATE TABLE IF NOT EXISTS codelab_revisions (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    codelab_id VARCHAR(255) NOT NULL,
+    revision_number INTEGER NOT NULL,
+    guide_markdown TEXT,
+    steps TEXT NOT NULL,
+    author_id VARCHAR(255),
+    source VARCHAR(32) NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (codelab_id, revision_number)
+);
@@ -0,0 +1,3 @@
+-- Bumped on every write so editors can detect concurrent changes with If-Match
+ALTER TABLE codelabs ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE codelabs ADD COLUMN steps_version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,11 @@
+-- One waitlist entry per nickname and codelab; keep the earliest of any duplicates
+DELETE FROM codelab_waitlist
+WHERE EXISTS (
+    SELECT 1 FROM codelab_waitlist AS earlier
+    WHERE earlier.codelab_id = codelab_waitlist.codelab_id
+      AND earlier.name = codelab_waitlist.name
+      AND (earlier.created_at < codelab_waitlist.created_at
+           OR (earlier.created_at = codelab_waitlist.created_at AND earlier.id < codelab_waitlist.id))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_codelab_waitlist_codelab_name ON codelab_waitlist (codelab_id, name);
@@ -71,6 +71,9 @@ pub struct CliAuthExchangeResponse {
     pub sub: String,
     /// Issued role, currently `admin`.
     pub role: String,
+    /// Staff role granted to the session, such as `owner` or `ta`.
+    #[serde(default)]
+    pub staff_role: Option<String>,
     /// Optional codelab scope.
     pub codelab_id: Option<String>,
     /// Expiration timestamp in epoch seconds.
@@ -86,6 +89,9 @@ pub struct CliAuthApproveRequest {
     /// Optional admin password when no browser session exists.
     #[serde(default)]
     pub admin_pw: String,
+    /// Authenticator or recovery code for accounts with two-factor enabled.
+    #[serde(default)]
+    pub otp_code: String,
 }
 
 /// Minimal approval response sent back to the browser page.
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// Two-factor state of the signed-in staff member.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaStatusResponse {
+    /// Whether sign-in requires a second factor.
+    pub enabled: bool,
+    /// Whether an enrollment is waiting for its first code.
+    pub pending: bool,
+    /// Unused recovery codes left.
+    pub recovery_codes_remaining: i64,
+}
+
+/// Secret and provisioning URI returned when enrollment starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotpEnrollmentResponse {
+    /// Base32 shared secret for manual entry.
+    pub secret: String,
+    /// `otpauth://` URI to render as a QR code.
+    pub provisioning_uri: String,
+}
+
+/// Payload carrying a TOTP or recovery code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaCodeRequest {
+    /// Six-digit authenticator code, or a recovery code where accepted.
+    pub code: String,
+}
+
+/// Freshly issued recovery codes, shown only once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryCodesResponse {
+    /// Single-use codes in `xxxxx-xxxxx` form.
+    pub recovery_codes: Vec<String>,
+}
+
+/// Second step of a password login for accounts with two-factor enabled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaLoginRequest {
+    /// Challenge token returned by `POST /api/login`.
+    pub mfa_token: String,
+    /// Authenticator or recovery code.
+    pub code: String,
+}
+
+/// Body of `POST /api/login` when the account still needs its second factor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaChallengeResponse {
+    /// Always `mfa_required`.
+    pub status: String,
+    /// Token to send with the code to `POST /api/login/mfa`.
+    pub mfa_token: String,
+    /// Seconds until the challenge expires.
+    pub expires_in: i64,
+}
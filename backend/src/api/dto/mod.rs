@@ -10,6 +10,12 @@ pub mod audit;
 pub mod cli;
 /// Code-server and workspace management DTOs.
 pub mod codeserver;
+/// Two-factor enrollment and login DTOs.
+pub mod mfa;
+/// OpenID Connect single sign-on DTOs.
+pub mod oidc;
+/// Session registry filters and revocation results.
+pub mod sessions;
 
 #[doc(inline)]
 pub use admin::*;
@@ -21,3 +27,9 @@ pub use audit::*;
 pub use cli::*;
 #[doc(inline)]
 pub use codeserver::*;
+#[doc(inline)]
+pub use mfa::*;
+#[doc(inline)]
+pub use oidc::*;
+#[doc(inline)]
+pub use sessions::*;
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// Single sign-on availability advertised to the login page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcStatusResponse {
+    /// Whether an OpenID Connect provider is configured.
+    pub enabled: bool,
+    /// Provider label for the login button, when enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_name: Option<String>,
+}
+
+/// Query parameters accepted when starting a single sign-on login.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OidcLoginQuery {
+    /// Same-origin path to open after a successful login.
+    pub return_to: Option<String>,
+}
+
+/// Query parameters returned by the identity provider to the callback.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OidcCallbackQuery {
+    /// Authorization code to exchange for tokens.
+    pub code: Option<String>,
+    /// Opaque value that binds the callback to a login attempt.
+    pub state: Option<String>,
+    /// OAuth error code when the provider refused the request.
+    pub error: Option<String>,
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// Filters selecting registered sessions by owner or codelab.
+///
+/// Used as query parameters when listing and as the body of bulk revocation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionFilterQuery {
+    /// Session subject: a staff user id, `ADMIN_ID`, or an attendee id.
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Codelab whose attendee sessions should match.
+    #[serde(default)]
+    pub codelab_id: Option<String>,
+}
+
+/// Number of sessions revoked by a bulk request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRevokeResponse {
+    /// Sessions that were active and are now revoked.
+    pub revoked: u64,
+}
@@ -1,20 +1,27 @@
-use crate::api::dto::SettingsPayload;
+use crate::api::dto::{MfaLoginRequest, SettingsPayload};
 use crate::domain::models::LoginPayload;
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
+use crate::infrastructure::db_models::UserRow;
+use crate::infrastructure::mfa::{
+    claim_mfa_challenge, create_mfa_challenge, finish_mfa_challenge, totp_enabled,
+    verify_second_factor, MFA_CHALLENGE_TTL_SECONDS,
+};
+use crate::infrastructure::sessions::{register_session, revoke_session};
 use crate::middleware::auth::{
-    build_csrf_cookie, build_session_cookie, clear_cookie, now_epoch_seconds, AuthSession, Role,
-    SessionClaims,
+    build_csrf_cookie, build_session_cookie, clear_cookie, now_epoch_seconds, AuthSession,
+    Permission, Role, SessionClaims, StaffRole,
 };
 use crate::middleware::request_info::RequestInfo;
 use crate::middleware::security::ensure_csrf_cookie;
-use crate::utils::crypto::decrypt_with_password;
+use crate::utils::crypto::{decrypt_with_password, verify_password};
 use crate::utils::error::{bad_request, internal_error, unauthorized};
 use axum::{extract::State, http::StatusCode, Json};
 use axum_extra::extract::cookie::CookieJar;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use subtle::ConstantTimeEq;
+use uuid::Uuid;
 
 pub(crate) fn validate_admin_credentials(state: &AppState, admin_id: &str, admin_pw: &str) -> bool {
     let id_ok = admin_id.as_bytes().ct_eq(state.admin_id.as_bytes()).into();
@@ -22,21 +29,110 @@ pub(crate) fn validate_admin_credentials(state: &AppState, admin_id: &str, admin
     id_ok && pw_ok
 }
 
-pub(crate) fn issue_admin_session(
+/// Authenticated staff member resolved from credentials or a stored subject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StaffIdentity {
+    /// Session subject: the configured `ADMIN_ID` or a `users.id`.
+    pub sub: String,
+    /// Staff role granted to the session.
+    pub role: StaffRole,
+}
+
+/// Checks credentials against the configured owner account and the `users` table.
+///
+/// The `ADMIN_ID`/`ADMIN_PW` pair always authenticates as an owner so an
+/// instance can never lock itself out of user management.
+pub(crate) async fn authenticate_staff(
+    state: &AppState,
+    username: &str,
+    password: &str,
+) -> Result<Option<StaffIdentity>, sqlx::Error> {
+    if validate_admin_credentials(state, username, password) {
+        return Ok(Some(StaffIdentity {
+            sub: state.admin_id.clone(),
+            role: StaffRole::Owner,
+        }));
+    }
+
+    let Some(user) =
+        sqlx::query_as::<_, UserRow>(&state.q("SELECT * FROM users WHERE username = ?"))
+            .bind(username)
+            .fetch_optional(&state.pool)
+            .await?
+    else {
+        return Ok(None);
+    };
+    let Some(role) = StaffRole::parse(&user.role) else {
+        return Ok(None);
+    };
+
+    let candidate = password.to_string();
+    let stored = user.password_hash.clone();
+    let valid = tokio::task::spawn_blocking(move || verify_password(&candidate, &stored))
+        .await
+        .unwrap_or(false);
+    if !valid {
+        return Ok(None);
+    }
+
+    sqlx::query(
+        &state.q("UPDATE users SET last_login_at = CAST(CURRENT_TIMESTAMP AS TEXT) WHERE id = ?"),
+    )
+    .bind(&user.id)
+    .execute(&state.pool)
+    .await?;
+
+    Ok(Some(StaffIdentity { sub: user.id, role }))
+}
+
+/// Looks up the current staff role for a session subject.
+///
+/// Returns `None` when the user has been deleted or holds an unknown role.
+pub(crate) async fn resolve_staff_identity(
+    state: &AppState,
+    sub: &str,
+) -> Result<Option<StaffIdentity>, sqlx::Error> {
+    if sub == state.admin_id {
+        return Ok(Some(StaffIdentity {
+            sub: sub.to_string(),
+            role: StaffRole::Owner,
+        }));
+    }
+    let role: Option<(String,)> = sqlx::query_as(&state.q("SELECT role FROM users WHERE id = ?"))
+        .bind(sub)
+        .fetch_optional(&state.pool)
+        .await?;
+    Ok(role
+        .and_then(|(role,)| StaffRole::parse(&role))
+        .map(|role| StaffIdentity {
+            sub: sub.to_string(),
+            role,
+        }))
+}
+
+pub(crate) async fn issue_admin_session(
     state: &AppState,
     jar: CookieJar,
-) -> Result<(CookieJar, SessionClaims, String), jsonwebtoken::errors::Error> {
+    identity: &StaffIdentity,
+    info: &RequestInfo,
+) -> Result<(CookieJar, SessionClaims, String), (StatusCode, String)> {
     let now = now_epoch_seconds();
     let claims = SessionClaims {
-        sub: state.admin_id.clone(),
+        sub: identity.sub.clone(),
         role: Role::Admin.as_str().to_string(),
         codelab_id: None,
+        staff_role: Some(identity.role.as_str().to_string()),
+        scopes: None,
+        jti: Some(Uuid::new_v4().to_string()),
         iss: state.auth.issuer.clone(),
         aud: state.auth.audience.clone(),
         iat: now,
         exp: now + state.auth.admin_ttl.as_secs() as usize,
     };
-    let token = state.auth.issue_token(&claims)?;
+    let token = state.auth.issue_token(&claims).map_err(internal_error)?;
+    register_session(state, &claims, Some(&info.ip), info.user_agent.as_deref())
+        .await
+        .map_err(internal_error)?;
     let csrf_token = crate::middleware::auth::generate_csrf_token();
 
     let jar = jar
@@ -54,7 +150,7 @@ pub(crate) fn issue_admin_session(
     Ok((jar, claims, token))
 }
 
-/// Authenticates the built-in administrator and issues session cookies.
+/// Authenticates the built-in administrator or a staff user and issues session cookies.
 pub async fn login(
     State(state): State<Arc<AppState>>,
     jar: CookieJar,
@@ -65,7 +161,10 @@ pub async fn login(
         return Err(bad_request("admin_id and admin_pw are required"));
     }
 
-    if !validate_admin_credentials(&state, &payload.admin_id, &payload.admin_pw) {
+    let identity = authenticate_staff(&state, payload.admin_id.trim(), &payload.admin_pw)
+        .await
+        .map_err(internal_error)?;
+    let Some(identity) = identity else {
         record_audit(
             &state,
             AuditEntry {
@@ -81,21 +180,103 @@ pub async fn login(
         )
         .await;
         return Err(unauthorized());
+    };
+
+    if totp_enabled(&state, &identity.sub)
+        .await
+        .map_err(internal_error)?
+    {
+        let mfa_token = create_mfa_challenge(&state, &identity.sub)
+            .await
+            .map_err(internal_error)?;
+        return Ok((
+            jar,
+            Json(serde_json::json!({
+                "status": "mfa_required",
+                "mfa_token": mfa_token,
+                "expires_in": MFA_CHALLENGE_TTL_SECONDS,
+            })),
+        ));
     }
 
-    let (jar, _claims, token) = issue_admin_session(&state, jar).map_err(internal_error)?;
+    let (jar, _claims, token) = issue_admin_session(&state, jar, &identity, &info).await?;
 
     record_audit(
         &state,
         AuditEntry {
             action: "admin_login_success".to_string(),
             actor_type: "admin".to_string(),
-            actor_id: Some(state.admin_id.clone()),
+            actor_id: Some(identity.sub),
             target_id: None,
             codelab_id: None,
             ip: Some(info.ip),
             user_agent: info.user_agent,
-            metadata: None,
+            metadata: Some(serde_json::json!({ "role": identity.role.as_str() })),
+        },
+    )
+    .await;
+
+    Ok((
+        jar,
+        Json(serde_json::json!({ "status": "ok", "token": token })),
+    ))
+}
+
+/// Completes a password login with a TOTP or recovery code and issues session cookies.
+pub async fn login_mfa(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+    info: RequestInfo,
+    Json(payload): Json<MfaLoginRequest>,
+) -> Result<(CookieJar, Json<serde_json::Value>), (StatusCode, String)> {
+    let subject = claim_mfa_challenge(&state, payload.mfa_token.trim())
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(unauthorized)?;
+    let factor = verify_second_factor(&state, &subject, &payload.code)
+        .await
+        .map_err(internal_error)?;
+    let Some(factor) = factor else {
+        record_audit(
+            &state,
+            AuditEntry {
+                action: "admin_login_failed".to_string(),
+                actor_type: "admin".to_string(),
+                actor_id: Some(subject),
+                target_id: None,
+                codelab_id: None,
+                ip: Some(info.ip),
+                user_agent: info.user_agent,
+                metadata: Some(serde_json::json!({ "reason": "invalid_second_factor" })),
+            },
+        )
+        .await;
+        return Err(unauthorized());
+    };
+    finish_mfa_challenge(&state, payload.mfa_token.trim())
+        .await
+        .map_err(internal_error)?;
+
+    let identity = resolve_staff_identity(&state, &subject)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(unauthorized)?;
+    let (jar, _claims, token) = issue_admin_session(&state, jar, &identity, &info).await?;
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "admin_login_success".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(identity.sub),
+            target_id: None,
+            codelab_id: None,
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({
+                "role": identity.role.as_str(),
+                "factor": factor.as_str(),
+            })),
         },
     )
     .await;
@@ -113,7 +294,7 @@ pub async fn update_settings(
     info: RequestInfo,
     Json(payload): Json<SettingsPayload>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    session.require_admin()?;
+    let admin = session.require_permission(Permission::ManageSystem)?;
     let mut api_key = payload.gemini_api_key.trim().to_string();
     if api_key.len() > 4096 {
         return Err(bad_request("API key too long"));
@@ -147,7 +328,7 @@ pub async fn update_settings(
         AuditEntry {
             action: "admin_settings_update".to_string(),
             actor_type: "admin".to_string(),
-            actor_id: Some(state.admin_id.clone()),
+            actor_id: Some(admin.sub),
             target_id: None,
             codelab_id: None,
             ip: Some(info.ip),
@@ -258,7 +439,7 @@ pub async fn check_updates(
     State(_state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<UpdateCheckResponse>, (StatusCode, String)> {
-    session.require_admin()?;
+    session.require_permission(Permission::ManageSystem)?;
 
     let frontend_current = std::env::var("FRONTEND_IMAGE_TAG").ok();
     let backend_current = std::env::var("BACKEND_IMAGE_TAG").ok();
@@ -275,11 +456,23 @@ pub async fn check_updates(
     }))
 }
 
-/// Clears the admin session cookie and, when appropriate, the CSRF cookie.
+/// Revokes the current admin session and clears its cookie and, when
+/// appropriate, the CSRF cookie.
 pub async fn logout(
     State(state): State<Arc<AppState>>,
     jar: CookieJar,
+    session: AuthSession,
 ) -> Result<(CookieJar, StatusCode), (StatusCode, String)> {
+    if let Some(SessionClaims {
+        sub,
+        jti: Some(jti),
+        ..
+    }) = &session.admin_claims
+    {
+        revoke_session(&state, jti, sub)
+            .await
+            .map_err(internal_error)?;
+    }
     let jar = jar.remove(clear_cookie(&state.auth.cookie_name));
     let jar = if jar.get(&state.auth.attendee_cookie_name).is_some() {
         jar
@@ -302,11 +495,19 @@ pub async fn get_session(
         state.auth.attendee_ttl
     };
     let jar = ensure_csrf_cookie(jar, &state, max_age);
+    let staff_role = claims.staff_role();
+    let permissions: Vec<&str> = Permission::ALL
+        .iter()
+        .filter(|permission| claims.has_permission(**permission))
+        .map(|permission| permission.as_str())
+        .collect();
     Ok((
         jar,
         Json(serde_json::json!({
             "sub": claims.sub,
             "role": claims.role,
+            "staff_role": staff_role.map(|role| role.as_str()),
+            "permissions": permissions,
             "codelab_id": claims.codelab_id,
             "exp": claims.exp,
         })),
@@ -5,7 +5,7 @@ use crate::domain::models::{
 };
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
-use crate::middleware::auth::AuthSession;
+use crate::middleware::auth::{staff_can_access_codelab, AuthSession, Permission};
 use crate::middleware::request_info::RequestInfo;
 use crate::utils::crypto::decrypt_with_password;
 use crate::utils::error::{bad_request, forbidden, internal_error};
@@ -27,8 +27,11 @@ pub async fn proxy_gemini_stream(
     info: RequestInfo,
     Json(payload): Json<AiRequest>,
 ) -> impl IntoResponse {
-    // Allow both admin and attendees to use AI
+    // Allow both admin and attendees to use AI; API tokens have no scope for it.
     let (user_id, user_type, _user_name) = if let Ok(admin) = session.require_admin() {
+        if admin.is_api_token() {
+            return forbidden().into_response();
+        }
         (admin.sub, "admin".to_string(), "Admin".to_string())
     } else if let Ok(attendee) = session.require_attendee() {
         // For attendees, verify they belong to the codelab if codelab_id is provided.
@@ -221,6 +224,13 @@ pub async fn save_ai_conversation(
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     // Get user information
     let (user_id, user_type, user_name) = if let Ok(admin) = session.require_admin() {
+        if admin.is_api_token()
+            || !staff_can_access_codelab(&state, &admin, &payload.codelab_id)
+                .await
+                .map_err(internal_error)?
+        {
+            return Err(forbidden());
+        }
         (admin.sub, "admin".to_string(), "Admin".to_string())
     } else if let Ok(attendee) = session.require_attendee() {
         // Verify attendee belongs to the codelab
@@ -268,7 +278,9 @@ pub async fn get_ai_conversations(
     session: AuthSession,
 ) -> Result<Json<Vec<AiConversation>>, (StatusCode, String)> {
     // Only admin can view all AI conversations
-    session.require_admin()?;
+    session
+        .require_codelab_permission(&state, &codelab_id, Permission::RunSessions)
+        .await?;
 
     let conversations = sqlx::query_as::<_, AiConversation>(
         &state.q("SELECT id, codelab_id, user_id, user_type, user_name, step_number, question, answer, model, usage_metadata, CAST(created_at AS TEXT) as created_at FROM ai_conversations WHERE codelab_id = ? ORDER BY created_at DESC"),
@@ -287,7 +299,7 @@ pub async fn create_ai_thread(
     session: AuthSession,
     Json(payload): Json<CreateAiThreadPayload>,
 ) -> Result<Json<AiThread>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session.require_interactive_admin()?;
     let thread_id = uuid::Uuid::new_v4().to_string();
 
     let thread = sqlx::query_as::<_, AiThread>(&state.q(
@@ -310,7 +322,7 @@ pub async fn get_ai_threads(
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<Vec<AiThread>>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session.require_interactive_admin()?;
 
     let threads = sqlx::query_as::<_, AiThread>(&state.q(
         "SELECT id, title, user_id, user_type, codelab_id, CAST(created_at AS TEXT) as created_at, CAST(updated_at AS TEXT) as updated_at FROM ai_threads WHERE user_id = ? AND user_type = 'admin' ORDER BY updated_at DESC"
@@ -329,7 +341,7 @@ pub async fn delete_ai_thread(
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session.require_interactive_admin()?;
 
     sqlx::query(&state.q("DELETE FROM ai_threads WHERE id = ? AND user_id = ?"))
         .bind(&thread_id)
@@ -348,7 +360,7 @@ pub async fn add_ai_message(
     session: AuthSession,
     Json(payload): Json<AddAiMessagePayload>,
 ) -> Result<Json<AiMessage>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session.require_interactive_admin()?;
 
     // Verify thread belongs to user
     let thread_exists = sqlx::query_scalar::<_, i64>(
@@ -397,7 +409,7 @@ pub async fn get_ai_messages(
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<Vec<AiMessage>>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session.require_interactive_admin()?;
 
     // Verify thread belongs to user
     let thread_exists = sqlx::query_scalar::<_, i64>(
@@ -0,0 +1,281 @@
+use crate::domain::models::{ApiToken, CreateApiTokenPayload, CreatedApiToken};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::db_models::ApiTokenRow;
+use crate::middleware::auth::{
+    hash_api_token, now_epoch_seconds, AuthSession, Permission, API_TOKEN_PREFIX,
+};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::{bad_request, internal_error};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const MAX_TOKEN_NAME_LEN: usize = 128;
+const MAX_TOKEN_LIFETIME_DAYS: u32 = 3650;
+const TOKEN_SECRET_LEN: usize = 40;
+const TOKEN_PREFIX_LEN: usize = 12;
+
+const API_TOKEN_COLUMNS: &str =
+    "id, user_id, name, token_hash, token_prefix, scopes, expires_at_epoch, last_used_at, created_at";
+
+impl From<ApiTokenRow> for ApiToken {
+    fn from(row: ApiTokenRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            name: row.name,
+            token_prefix: row.token_prefix,
+            scopes: row
+                .scopes
+                .split(',')
+                .filter(|scope| !scope.is_empty())
+                .map(str::to_string)
+                .collect(),
+            expires_at: row.expires_at_epoch,
+            last_used_at: row.last_used_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// Lists API tokens. Owners see every token; other staff see their own.
+///
+/// Like the other token endpoints, requires a browser or CLI session.
+pub async fn list_api_tokens(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<Vec<ApiToken>>, (StatusCode, String)> {
+    let admin = session.require_interactive_admin()?;
+    let rows = if admin.has_permission(Permission::ManageUsers) {
+        sqlx::query_as::<_, ApiTokenRow>(&state.q(&format!(
+            "SELECT {API_TOKEN_COLUMNS} FROM api_tokens ORDER BY created_at DESC"
+        )))
+        .fetch_all(&state.pool)
+        .await
+    } else {
+        sqlx::query_as::<_, ApiTokenRow>(&state.q(&format!(
+            "SELECT {API_TOKEN_COLUMNS} FROM api_tokens WHERE user_id = ? ORDER BY created_at DESC"
+        )))
+        .bind(&admin.sub)
+        .fetch_all(&state.pool)
+        .await
+    }
+    .map_err(internal_error)?;
+    Ok(Json(rows.into_iter().map(ApiToken::from).collect()))
+}
+
+/// Creates an API token for the signed-in staff member.
+///
+/// Requires a browser or CLI session; API tokens cannot mint further tokens.
+/// The plaintext token appears only in this response.
+pub async fn create_api_token(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<CreateApiTokenPayload>,
+) -> Result<(StatusCode, Json<CreatedApiToken>), (StatusCode, String)> {
+    let admin = session.require_interactive_admin()?;
+    let name = validate_token_name(&payload.name)?;
+    let scopes = resolve_scopes(payload.scopes.as_deref(), |permission| {
+        admin.has_permission(permission)
+    })?;
+    let expires_at = match payload.expires_in_days {
+        None => None,
+        Some(days) if (1..=MAX_TOKEN_LIFETIME_DAYS).contains(&days) => {
+            Some(now_epoch_seconds() as i64 + i64::from(days) * 24 * 60 * 60)
+        }
+        Some(_) => {
+            return Err(bad_request(
+                "expires_in_days must be between 1 and 3650 when provided",
+            ))
+        }
+    };
+
+    let id = Uuid::new_v4().to_string();
+    let token = generate_api_token();
+    let token_prefix: String = token.chars().take(TOKEN_PREFIX_LEN).collect();
+    sqlx::query(&state.q(
+        "INSERT INTO api_tokens (id, user_id, name, token_hash, token_prefix, scopes, expires_at_epoch) VALUES (?, ?, ?, ?, ?, ?, ?)",
+    ))
+    .bind(&id)
+    .bind(&admin.sub)
+    .bind(&name)
+    .bind(hash_api_token(&token))
+    .bind(&token_prefix)
+    .bind(scopes.join(","))
+    .bind(expires_at)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "api_token_create".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub.clone()),
+            target_id: Some(id.clone()),
+            codelab_id: None,
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({
+                "name": name,
+                "scopes": scopes,
+                "expires_at": expires_at,
+            })),
+        },
+    )
+    .await;
+
+    let metadata = fetch_token(&state, &id).await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiToken { token, metadata }),
+    ))
+}
+
+/// Revokes an API token. Staff may revoke their own tokens; owners any token.
+pub async fn revoke_api_token(
+    Path(token_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session.require_interactive_admin()?;
+    let token = fetch_token(&state, &token_id).await?;
+    if token.user_id != admin.sub && !admin.has_permission(Permission::ManageUsers) {
+        return Err((StatusCode::NOT_FOUND, "API token not found".to_string()));
+    }
+
+    sqlx::query(&state.q("DELETE FROM api_tokens WHERE id = ?"))
+        .bind(&token_id)
+        .execute(&state.pool)
+        .await
+        .map_err(internal_error)?;
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "api_token_revoke".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(token_id),
+            codelab_id: None,
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({
+                "name": token.name,
+                "owner": token.user_id,
+            })),
+        },
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn fetch_token(state: &AppState, token_id: &str) -> Result<ApiToken, (StatusCode, String)> {
+    sqlx::query_as::<_, ApiTokenRow>(&state.q(&format!(
+        "SELECT {API_TOKEN_COLUMNS} FROM api_tokens WHERE id = ?"
+    )))
+    .bind(token_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .map(ApiToken::from)
+    .ok_or((StatusCode::NOT_FOUND, "API token not found".to_string()))
+}
+
+fn generate_api_token() -> String {
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_SECRET_LEN)
+        .map(char::from)
+        .collect();
+    format!("{API_TOKEN_PREFIX}{secret}")
+}
+
+fn validate_token_name(value: &str) -> Result<String, (StatusCode, String)> {
+    let name = value.trim();
+    if name.is_empty() {
+        return Err(bad_request("name is required"));
+    }
+    if name.chars().count() > MAX_TOKEN_NAME_LEN {
+        return Err(bad_request("name is too long"));
+    }
+    Ok(name.to_string())
+}
+
+/// Validates requested scopes against what the caller holds.
+///
+/// Omitted scopes default to every permission the caller has. The result is
+/// de-duplicated and kept in [`Permission::ALL`] order.
+fn resolve_scopes(
+    requested: Option<&[String]>,
+    granted: impl Fn(Permission) -> bool,
+) -> Result<Vec<&'static str>, (StatusCode, String)> {
+    let Some(requested) = requested else {
+        return Ok(Permission::ALL
+            .into_iter()
+            .filter(|permission| granted(*permission))
+            .map(|permission| permission.as_str())
+            .collect());
+    };
+    if requested.is_empty() {
+        return Err(bad_request("scopes must not be empty"));
+    }
+    let mut selected = Vec::new();
+    for scope in requested {
+        let permission = Permission::parse(scope.trim())
+            .ok_or_else(|| bad_request(&format!("unknown scope: {}", scope.trim())))?;
+        if !granted(permission) {
+            return Err(bad_request(&format!(
+                "your role does not grant the {} scope",
+                permission.as_str()
+            )));
+        }
+        selected.push(permission);
+    }
+    Ok(Permission::ALL
+        .into_iter()
+        .filter(|permission| selected.contains(permission))
+        .map(|permission| permission.as_str())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::auth::StaffRole;
+
+    #[test]
+    fn resolve_scopes_defaults_and_validates() {
+        let facilitator = |permission| StaffRole::Facilitator.grants(permission);
+        assert_eq!(
+            resolve_scopes(None, facilitator).unwrap(),
+            vec!["run_sessions", "assist_learners"]
+        );
+        let requested = vec!["assist_learners".to_string(), " run_sessions ".to_string()];
+        assert_eq!(
+            resolve_scopes(Some(&requested), facilitator).unwrap(),
+            vec!["run_sessions", "assist_learners"]
+        );
+        assert!(resolve_scopes(Some(&[]), facilitator).is_err());
+        assert!(resolve_scopes(Some(&["edit_content".to_string()]), facilitator).is_err());
+        assert!(resolve_scopes(Some(&["bogus".to_string()]), facilitator).is_err());
+    }
+
+    #[test]
+    fn generated_tokens_carry_prefix() {
+        let token = generate_api_token();
+        assert!(token.starts_with(API_TOKEN_PREFIX));
+        assert_eq!(token.len(), API_TOKEN_PREFIX.len() + TOKEN_SECRET_LEN);
+        assert_ne!(token, generate_api_token());
+    }
+}
@@ -1,19 +1,29 @@
+use crate::api::handlers::certificates::issue_certificate;
+use crate::api::handlers::cohorts::{
+    active_cohort, attendee_cohort, fetch_cohort, listing_scope, room_key, CohortQuery,
+};
+use crate::api::handlers::quizzes::has_passing_quiz_attempt;
+use crate::api::handlers::waitlist::{enqueue_waitlist, find_waitlist_entry, waitlist_status};
 use crate::domain::models::{
-    Attendee, CertificateInfo, Codelab, HelpRequest, HelpRequestPayload, RegistrationPayload,
+    Attendee, Codelab, Cohort, CompletionRequirementsError, HelpRequest, HelpRequestPayload,
+    RegistrationPayload,
 };
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
+use crate::infrastructure::join_access::{consume_invite, resolve_join_grant, JoinGrant};
+use crate::infrastructure::sessions::register_session;
 use crate::middleware::auth::{
-    build_attendee_session_cookie, build_csrf_cookie, now_epoch_seconds, AuthSession, Role,
-    SessionClaims,
+    build_attendee_session_cookie, build_csrf_cookie, now_epoch_seconds, AuthSession, Permission,
+    Role, SessionClaims,
 };
 use crate::middleware::request_info::RequestInfo;
 use crate::utils::crypto::{decrypt_with_password, encrypt_with_password};
 use crate::utils::error::{bad_request, forbidden, internal_error};
 use crate::utils::validation::validate_registration;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
+    response::{IntoResponse, Response},
     Json,
 };
 use axum_extra::extract::cookie::CookieJar;
@@ -22,7 +32,49 @@ use sqlx;
 use std::sync::Arc;
 use uuid;
 
+/// Rejects new registrations outside the codelab's registration window.
+fn ensure_registration_open(codelab: &Codelab) -> Result<(), (StatusCode, String)> {
+    let now = now_epoch_seconds() as i64;
+    if codelab
+        .registration_opens_at
+        .is_some_and(|opens_at| now < opens_at)
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Registration has not opened yet".to_string(),
+        ));
+    }
+    if codelab
+        .registration_closes_at
+        .is_some_and(|closes_at| now >= closes_at)
+    {
+        return Err((StatusCode::FORBIDDEN, "Registration is closed".to_string()));
+    }
+    Ok(())
+}
+
+/// Picks the cohort a new attendee joins: the requested one, or else the one running now.
+async fn resolve_registration_cohort(
+    state: &AppState,
+    codelab_id: &str,
+    requested: Option<&str>,
+) -> Result<Option<Cohort>, (StatusCode, String)> {
+    match requested.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(cohort_id) => fetch_cohort(state, codelab_id, cohort_id)
+            .await
+            .map_err(internal_error)?
+            .map(Some)
+            .ok_or_else(|| bad_request("unknown cohort_id")),
+        None => active_cohort(state, codelab_id)
+            .await
+            .map_err(internal_error),
+    }
+}
+
 /// Registers or rejoins an attendee for a codelab and issues attendee cookies.
+///
+/// When the codelab is full the learner is waitlisted instead: the response is
+/// `202 Accepted` with a [`WaitlistStatus`] and no session cookies.
 pub async fn register_attendee(
     Path(id): Path<String>,
     State(state): State<Arc<AppState>>,
@@ -30,9 +82,8 @@ pub async fn register_attendee(
     jar: CookieJar,
     info: RequestInfo,
     Json(payload): Json<RegistrationPayload>,
-) -> Result<(CookieJar, Json<crate::domain::models::AttendeePublic>), (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
     validate_registration(&payload)?;
-    // Check if codelab is public
     let codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
         .bind(&id)
         .fetch_optional(&state.pool)
@@ -40,15 +91,25 @@ pub async fn register_attendee(
         .map_err(internal_error)?
         .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))?;
 
-    let is_admin = session
-        .claims
-        .as_ref()
-        .map(|claims| claims.role == "admin")
-        .unwrap_or(false);
-
-    if codelab.is_public == 0 && !is_admin {
-        return Err(forbidden());
-    }
+    // Only staff with access to this codelab may skip the join and capacity checks.
+    let is_admin = session.is_codelab_staff(&state, &id).await?;
+
+    // Private codelabs admit attendees holding an invite or the passcode;
+    // existing attendees may rejoin with their own code.
+    let needs_grant = codelab.is_public == 0 && !is_admin;
+    let grant = if needs_grant {
+        resolve_join_grant(
+            &state,
+            &id,
+            payload.invite_token.as_deref(),
+            payload.passcode.as_deref(),
+        )
+        .await
+        .map_err(internal_error)?
+    } else {
+        None
+    };
+    let denied = needs_grant && grant.is_none();
 
     // Check for duplicate name in the same codelab
     let existing = sqlx::query_as::<_, Attendee>(
@@ -66,6 +127,9 @@ pub async fn register_attendee(
             .map(|stored| stored == payload.code)
             .unwrap_or(false);
         if !code_matches {
+            if denied {
+                return Err(forbidden());
+            }
             return Err((StatusCode::CONFLICT, "Nickname already taken".to_string()));
         }
 
@@ -82,21 +146,138 @@ pub async fn register_attendee(
         is_rejoin = true;
         existing_attendee
     } else {
+        if denied {
+            return Err(forbidden());
+        }
+        // Waitlisted learners check their place with the same nickname and code.
+        if let Some(entry) = find_waitlist_entry(&state, &id, &payload.name)
+            .await
+            .map_err(internal_error)?
+        {
+            let code_matches = decrypt_with_password(&entry.code, &state.admin_pw)
+                .map(|stored| stored == payload.code)
+                .unwrap_or(false);
+            if !code_matches {
+                return Err((StatusCode::CONFLICT, "Nickname already taken".to_string()));
+            }
+            let status = waitlist_status(&state, &entry)
+                .await
+                .map_err(internal_error)?;
+            return Ok((StatusCode::ACCEPTED, Json(status)).into_response());
+        }
+        if !is_admin {
+            ensure_registration_open(&codelab)?;
+        }
+        let cohort = resolve_registration_cohort(&state, &id, payload.cohort_id.as_deref()).await?;
+        if !is_admin
+            && cohort
+                .as_ref()
+                .and_then(|cohort| cohort.ends_at)
+                .is_some_and(|ends_at| now_epoch_seconds() as i64 >= ends_at)
+        {
+            return Err((StatusCode::FORBIDDEN, "Cohort has ended".to_string()));
+        }
+        let cohort_id = cohort.map(|cohort| cohort.id);
+
         let attendee_id = uuid::Uuid::new_v4().to_string();
         let encrypted_code = encrypt_with_password(&payload.code, &state.admin_pw)
             .map_err(|err| internal_error(err))?;
 
-        sqlx::query(&state.q(
-            "INSERT INTO attendees (id, codelab_id, name, code, email, current_step) VALUES (?, ?, ?, ?, ?, 1)",
-        ))
-        .bind(&attendee_id)
-        .bind(&id)
-        .bind(&payload.name)
-        .bind(&encrypted_code)
-        .bind(&payload.email)
-        .execute(&state.pool)
-        .await
-        .map_err(internal_error)?;
+        let mut tx = state.pool.begin().await.map_err(internal_error)?;
+        let admitted = if codelab.max_attendees > 0 && !is_admin {
+            // Writing the codelab row locks it until commit, so concurrent
+            // registrations count seats one at a time on every backend.
+            sqlx::query(&state.q("UPDATE codelabs SET max_attendees = max_attendees WHERE id = ?"))
+                .bind(&id)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal_error)?;
+            // Seats go to the waitlist first, so new learners cannot jump the queue.
+            sqlx::query(&state.q(
+                "INSERT INTO attendees (id, codelab_id, name, code, email, current_step, cohort_id) SELECT ?, ?, ?, ?, ?, 1, ? WHERE (SELECT COUNT(*) FROM attendees WHERE codelab_id = ?) < ? AND NOT EXISTS (SELECT 1 FROM codelab_waitlist WHERE codelab_id = ?)",
+            ))
+            .bind(&attendee_id)
+            .bind(&id)
+            .bind(&payload.name)
+            .bind(&encrypted_code)
+            .bind(&payload.email)
+            .bind(&cohort_id)
+            .bind(&id)
+            .bind(i64::from(codelab.max_attendees))
+            .bind(&id)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?
+            .rows_affected()
+                > 0
+        } else {
+            sqlx::query(&state.q(
+                "INSERT INTO attendees (id, codelab_id, name, code, email, current_step, cohort_id) VALUES (?, ?, ?, ?, ?, 1, ?)",
+            ))
+            .bind(&attendee_id)
+            .bind(&id)
+            .bind(&payload.name)
+            .bind(&encrypted_code)
+            .bind(&payload.email)
+            .bind(&cohort_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+            true
+        };
+
+        if !admitted {
+            let entry = enqueue_waitlist(
+                &state,
+                &mut tx,
+                &id,
+                &payload.name,
+                &encrypted_code,
+                payload.email.as_deref(),
+                cohort_id.as_deref(),
+            )
+            .await
+            .map_err(|err| {
+                // A concurrent registration queued the same nickname first.
+                if err
+                    .as_database_error()
+                    .is_some_and(|db_err| db_err.is_unique_violation())
+                {
+                    (StatusCode::CONFLICT, "Nickname already taken".to_string())
+                } else {
+                    internal_error(err)
+                }
+            })?;
+            tx.commit().await.map_err(internal_error)?;
+            let status = waitlist_status(&state, &entry)
+                .await
+                .map_err(internal_error)?;
+            record_audit(
+                &state,
+                AuditEntry {
+                    action: "attendee_waitlist".to_string(),
+                    actor_type: "attendee".to_string(),
+                    actor_id: Some(entry.id),
+                    target_id: None,
+                    codelab_id: Some(id.clone()),
+                    ip: Some(info.ip),
+                    user_agent: info.user_agent,
+                    metadata: Some(serde_json::json!({ "position": status.position })),
+                },
+            )
+            .await;
+            return Ok((StatusCode::ACCEPTED, Json(status)).into_response());
+        }
+        // The invite is only used up when the learner actually gets a seat.
+        if let Some(JoinGrant::Invite(invite_id)) = &grant {
+            if !consume_invite(&state, &mut tx, invite_id)
+                .await
+                .map_err(internal_error)?
+            {
+                return Err(forbidden());
+            }
+        }
+        tx.commit().await.map_err(internal_error)?;
 
         sqlx::query_as::<_, Attendee>(&state.q("SELECT * FROM attendees WHERE id = ?"))
             .bind(&attendee_id)
@@ -111,12 +292,18 @@ pub async fn register_attendee(
         sub: attendee_id.clone(),
         role: Role::Attendee.as_str().to_string(),
         codelab_id: Some(id.clone()),
+        staff_role: None,
+        scopes: None,
+        jti: Some(uuid::Uuid::new_v4().to_string()),
         iss: state.auth.issuer.clone(),
         aud: state.auth.audience.clone(),
         iat: now,
         exp: now + state.auth.attendee_ttl.as_secs() as usize,
     };
     let token = state.auth.issue_token(&claims).map_err(internal_error)?;
+    register_session(&state, &claims, Some(&info.ip), info.user_agent.as_deref())
+        .await
+        .map_err(internal_error)?;
     let csrf_token = crate::middleware::auth::generate_csrf_token();
     let jar = jar
         .add(build_attendee_session_cookie(
@@ -144,7 +331,9 @@ pub async fn register_attendee(
             codelab_id: Some(id.clone()),
             ip: Some(info.ip),
             user_agent: info.user_agent,
-            metadata: None,
+            metadata: grant
+                .as_ref()
+                .map(|grant| serde_json::json!({ "via": grant.as_str() })),
         },
     )
     .await;
@@ -155,37 +344,36 @@ pub async fn register_attendee(
         p
     };
 
-    Ok((jar, Json(attendee_public)))
+    Ok((jar, Json(attendee_public)).into_response())
 }
 
 /// Lists attendees for a codelab, with different visibility for admins and attendees.
 pub async fn get_attendees(
     Path(id): Path<String>,
+    Query(query): Query<CohortQuery>,
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<Vec<Attendee>>, (StatusCode, String)> {
     // Allow both admin and attendees of this codelab to view attendees
     let is_admin = session
-        .claims
-        .as_ref()
-        .map(|claims| claims.role == "admin")
-        .unwrap_or(false);
-
-    if !is_admin {
-        // If not admin, must be an attendee of this codelab
-        let attendee = session.require_attendee()?;
-        if attendee.codelab_id.as_deref() != Some(id.as_str()) {
-            return Err(forbidden());
-        }
+        .codelab_staff_claims(&state, &id, Permission::AssistLearners)
+        .await?
+        .is_some();
+    // Attendees only see their own cohort's roster.
+    let scope = listing_scope(&state, &session, &id, is_admin, query.cohort_id).await?;
+
+    let sql = state.q(&format!(
+        "SELECT * FROM attendees WHERE codelab_id = ?{} ORDER BY created_at DESC",
+        scope.condition("cohort_id")
+    ));
+    let mut listing = sqlx::query_as::<_, Attendee>(&sql).bind(&id);
+    if let Some(cohort_id) = scope.bind_value() {
+        listing = listing.bind(cohort_id);
     }
-
-    let mut attendees = sqlx::query_as::<_, Attendee>(
-        &state.q("SELECT * FROM attendees WHERE codelab_id = ? ORDER BY created_at DESC"),
-    )
-    .bind(&id)
-    .fetch_all(&state.pool)
-    .await
-    .map_err(internal_error)?;
+    let mut attendees = listing
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
 
     // Only decrypt codes for admins
     if is_admin {
@@ -246,22 +434,26 @@ pub async fn request_help(
     }
 
     let attendee_id = attendee.sub;
+    let cohort_id = attendee_cohort(&state, &attendee_id)
+        .await
+        .map_err(internal_error)?;
 
     let help_id = uuid::Uuid::new_v4().to_string();
 
     sqlx::query(&state.q(
-        "INSERT INTO help_requests (id, codelab_id, attendee_id, step_number) VALUES (?, ?, ?, ?)",
+        "INSERT INTO help_requests (id, codelab_id, attendee_id, step_number, cohort_id) VALUES (?, ?, ?, ?, ?)",
     ))
     .bind(&help_id)
     .bind(&id)
     .bind(&attendee_id)
     .bind(payload.step_number)
+    .bind(&cohort_id)
     .execute(&state.pool)
     .await
     .map_err(internal_error)?;
 
-    // Notify via WebSocket if possible
-    if let Some(res) = state.channels.get(&id) {
+    // Notify the attendee's cohort room via WebSocket if possible
+    if let Some(res) = state.channels.get(&room_key(&id, cohort_id.as_deref())) {
         let msg = serde_json::json!({
             "type": "help_request",
             "attendee_id": attendee_id,
@@ -292,34 +484,33 @@ pub async fn request_help(
 /// Lists pending help requests visible to the current actor.
 pub async fn get_help_requests(
     Path(id): Path<String>,
+    Query(query): Query<CohortQuery>,
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<Vec<HelpRequest>>, (StatusCode, String)> {
     // Allow both admin and attendees of this codelab to view help requests
     let is_admin = session
-        .claims
-        .as_ref()
-        .map(|claims| claims.role == "admin")
-        .unwrap_or(false);
-
-    if !is_admin {
-        // If not admin, must be an attendee of this codelab
-        let attendee = session.require_attendee()?;
-        if attendee.codelab_id.as_deref() != Some(id.as_str()) {
-            return Err(forbidden());
-        }
-    }
+        .codelab_staff_claims(&state, &id, Permission::AssistLearners)
+        .await?
+        .is_some();
+    // Attendees only see their own cohort's help queue.
+    let scope = listing_scope(&state, &session, &id, is_admin, query.cohort_id).await?;
 
-    let requests = sqlx::query_as::<_, HelpRequest>(&state.q(
+    let sql = state.q(&format!(
         "SELECT hr.*, a.name as attendee_name FROM help_requests hr
          JOIN attendees a ON hr.attendee_id = a.id
-         WHERE hr.codelab_id = ? AND hr.status = 'pending'
+         WHERE hr.codelab_id = ? AND hr.status = 'pending'{}
          ORDER BY hr.created_at DESC",
-    ))
-    .bind(&id)
-    .fetch_all(&state.pool)
-    .await
-    .map_err(internal_error)?;
+        scope.condition("hr.cohort_id")
+    ));
+    let mut listing = sqlx::query_as::<_, HelpRequest>(&sql).bind(&id);
+    if let Some(cohort_id) = scope.bind_value() {
+        listing = listing.bind(cohort_id);
+    }
+    let requests = listing
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
 
     Ok(Json(requests))
 }
@@ -327,16 +518,21 @@ pub async fn get_help_requests(
 /// Marks a help request as resolved.
 pub async fn resolve_help_request(
     State(state): State<Arc<AppState>>,
-    Path((_id, help_id)): Path<(String, String)>,
+    Path((id, help_id)): Path<(String, String)>,
     session: AuthSession,
     info: RequestInfo,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
-    sqlx::query(&state.q("UPDATE help_requests SET status = 'resolved' WHERE id = ?"))
-        .bind(help_id)
-        .execute(&state.pool)
-        .await
-        .map_err(internal_error)?;
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::AssistLearners)
+        .await?;
+    sqlx::query(
+        &state.q("UPDATE help_requests SET status = 'resolved' WHERE id = ? AND codelab_id = ?"),
+    )
+    .bind(help_id)
+    .bind(&id)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?;
 
     record_audit(
         &state,
@@ -345,7 +541,7 @@ pub async fn resolve_help_request(
             actor_type: "admin".to_string(),
             actor_id: Some(admin.sub),
             target_id: None,
-            codelab_id: None,
+            codelab_id: Some(id),
             ip: Some(info.ip),
             user_agent: info.user_agent,
             metadata: None,
@@ -357,19 +553,21 @@ pub async fn resolve_help_request(
 }
 
 /// Marks the authenticated attendee as having completed the codelab.
+///
+/// Responds with `400` and a [`CompletionRequirementsError`] body listing every
+/// unmet requirement when the codelab's completion rules are not satisfied.
 pub async fn complete_codelab(
     Path(id): Path<String>,
     State(state): State<Arc<AppState>>,
     session: AuthSession,
     info: RequestInfo,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
     let attendee = session.require_attendee()?;
     if attendee.codelab_id.as_deref() != Some(id.as_str()) {
         return Err(forbidden());
     }
     let attendee_id = attendee.sub;
 
-    // Check if submission is required
     let codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
         .bind(&id)
         .fetch_optional(&state.pool)
@@ -377,21 +575,16 @@ pub async fn complete_codelab(
         .map_err(internal_error)?
         .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))?;
 
-    let submission_count = if codelab.require_submission != 0 {
-        let row: (i64,) = sqlx::query_as(
-            &state.q("SELECT COUNT(*) FROM submissions WHERE attendee_id = ? AND codelab_id = ?"),
+    let unmet = unmet_completion_requirements(&state, &codelab, &attendee_id).await?;
+    if !unmet.is_empty() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(CompletionRequirementsError {
+                error: "REQUIREMENTS_NOT_MET".to_string(),
+                unmet,
+            }),
         )
-        .bind(&attendee_id)
-        .bind(&id)
-        .fetch_one(&state.pool)
-        .await
-        .map_err(internal_error)?;
-        row.0
-    } else {
-        1
-    };
-    if submission_count == 0 {
-        return Err((StatusCode::BAD_REQUEST, "SUBMISSION_REQUIRED".to_string()));
+            .into_response());
     }
 
     sqlx::query(&state.q("UPDATE attendees SET is_completed = 1, completed_at = CAST(CURRENT_TIMESTAMP AS TEXT) WHERE id = ? AND codelab_id = ?"))
@@ -416,36 +609,61 @@ pub async fn complete_codelab(
     )
     .await;
 
-    Ok(Json(serde_json::json!({ "status": "ok" })))
+    issue_certificate(&state, &attendee_id).await?;
+
+    Ok(Json(serde_json::json!({ "status": "ok" })).into_response())
 }
 
-/// Returns certificate metadata for a completed attendee.
-pub async fn get_certificate(
-    Path(attendee_id): Path<String>,
-    State(state): State<Arc<AppState>>,
-) -> Result<Json<CertificateInfo>, (StatusCode, String)> {
-    let row: Option<(String, String, String, String, String, i32)> = sqlx::query_as(&state.q(
-        "SELECT a.name as attendee_name, c.title as codelab_title, c.author, 
-                COALESCE(a.completed_at, ''), c.id as codelab_id, a.is_completed
-         FROM attendees a 
-         JOIN codelabs c ON a.codelab_id = c.id 
-         WHERE a.id = ?",
-    ))
-    .bind(&attendee_id)
-    .fetch_optional(&state.pool)
-    .await
-    .map_err(internal_error)?;
+/// Evaluates the codelab's completion rules for an attendee.
+///
+/// Returns the codes of unmet requirements (`QUIZ_REQUIRED`, `FEEDBACK_REQUIRED`,
+/// `SUBMISSION_REQUIRED`); an empty list means the attendee may complete.
+pub(crate) async fn unmet_completion_requirements(
+    state: &AppState,
+    codelab: &Codelab,
+    attendee_id: &str,
+) -> Result<Vec<String>, (StatusCode, String)> {
+    let mut unmet = Vec::new();
+
+    if codelab.require_quiz != 0 {
+        let quiz_count: (i64,) =
+            sqlx::query_as(&state.q("SELECT COUNT(*) FROM quizzes WHERE codelab_id = ?"))
+                .bind(&codelab.id)
+                .fetch_one(&state.pool)
+                .await
+                .map_err(internal_error)?;
+        if quiz_count.0 > 0 && !has_passing_quiz_attempt(state, codelab, attendee_id).await? {
+            unmet.push("QUIZ_REQUIRED".to_string());
+        }
+    }
 
-    match row {
-        Some(r) if r.5 == 1 => Ok(Json(CertificateInfo {
-            attendee_name: r.0,
-            codelab_title: r.1,
-            codelab_id: r.4,
-            author: r.2,
-            completed_at: r.3,
-            verification_url: format!("/verify/{}", attendee_id),
-        })),
-        Some(_) => Err((StatusCode::FORBIDDEN, "REQUIREMENTS_NOT_MET".to_string())),
-        None => Err((StatusCode::NOT_FOUND, "Certificate not found".to_string())),
+    if codelab.require_feedback != 0 {
+        let feedback_count: (i64,) = sqlx::query_as(
+            &state.q("SELECT COUNT(*) FROM feedback WHERE attendee_id = ? AND codelab_id = ?"),
+        )
+        .bind(attendee_id)
+        .bind(&codelab.id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(internal_error)?;
+        if feedback_count.0 == 0 {
+            unmet.push("FEEDBACK_REQUIRED".to_string());
+        }
     }
+
+    if codelab.require_submission != 0 {
+        let submission_count: (i64,) = sqlx::query_as(
+            &state.q("SELECT COUNT(*) FROM submissions WHERE attendee_id = ? AND codelab_id = ?"),
+        )
+        .bind(attendee_id)
+        .bind(&codelab.id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(internal_error)?;
+        if submission_count.0 == 0 {
+            unmet.push("SUBMISSION_REQUIRED".to_string());
+        }
+    }
+
+    Ok(unmet)
 }
@@ -1,7 +1,7 @@
 use crate::api::dto::AuditLogQuery;
 use crate::infrastructure::database::AppState;
 use crate::infrastructure::db_models::AuditLog;
-use crate::middleware::auth::AuthSession;
+use crate::middleware::auth::{AuthSession, Permission};
 use crate::utils::error::internal_error;
 use axum::{
     extract::{Query, State},
@@ -16,7 +16,7 @@ pub async fn get_audit_logs(
     session: AuthSession,
     Query(params): Query<AuditLogQuery>,
 ) -> Result<Json<Vec<AuditLog>>, (StatusCode, String)> {
-    session.require_admin()?;
+    session.require_permission(Permission::ManageSystem)?;
 
     let limit = params.limit.unwrap_or(100).min(1000);
     let offset = params.offset.unwrap_or(0);
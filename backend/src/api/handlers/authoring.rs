@@ -0,0 +1,258 @@
+use crate::api::handlers::cohorts::ensure_codelab_exists;
+use crate::api::handlers::websocket::WsQuery;
+use crate::infrastructure::authoring::{
+    authoring_channel, open_authoring_room, presence_payload, save_authoring_room,
+    snapshot_payload, AuthoringEditor, AuthoringRoom,
+};
+use crate::infrastructure::database::AppState;
+use crate::middleware::auth::{
+    staff_can_access_codelab, verify_session_token, AuthSession, Permission, Role, SessionClaims,
+};
+use crate::utils::error::{forbidden, internal_error, unauthorized};
+use crate::utils::ot::TextOperation;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    response::{IntoResponse, Response},
+};
+use futures_util::{sink::SinkExt, stream::StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Messages editors send over the authoring socket.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AuthoringClientMessage {
+    /// An edit of a step made against the given revision.
+    Operation {
+        step_id: String,
+        revision: u64,
+        operation: TextOperation,
+    },
+    /// The sender's cursor or selection within a step.
+    Cursor {
+        step_id: String,
+        revision: u64,
+        position: usize,
+        #[serde(default)]
+        selection_end: Option<usize>,
+    },
+    /// Asks for the current documents after the client lost track.
+    Resync,
+}
+
+/// Upgrades a staff request into the shared step editing channel of a codelab.
+///
+/// Only staff who may edit the codelab's content can join.
+pub async fn authoring_ws_handler(
+    Path(id): Path<String>,
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WsQuery>,
+    session: AuthSession,
+) -> Response {
+    let claims = match (session.admin_claims, query.token.as_deref()) {
+        (Some(claims), _) => Some(claims),
+        (None, Some(token)) => match verify_session_token(&state, token).await {
+            Ok(verified) => verified.filter(|claims| claims.role == Role::Admin.as_str()),
+            Err(err) => return internal_error(err).into_response(),
+        },
+        (None, None) => None,
+    };
+    let Some(claims) = claims else {
+        return unauthorized().into_response();
+    };
+    if !claims.has_permission(Permission::EditContent) {
+        return forbidden().into_response();
+    }
+    match staff_can_access_codelab(&state, &claims, &id).await {
+        Ok(true) => {}
+        Ok(false) => return forbidden().into_response(),
+        Err(err) => return internal_error(err).into_response(),
+    }
+    if let Err(err) = ensure_codelab_exists(&state, &id).await {
+        return err.into_response();
+    }
+    let name: Option<String> = match sqlx::query_scalar(
+        &state.q("SELECT COALESCE(display_name, username) FROM users WHERE id = ?"),
+    )
+    .bind(&claims.sub)
+    .fetch_optional(&state.pool)
+    .await
+    {
+        Ok(name) => name,
+        Err(err) => return internal_error(err).into_response(),
+    };
+    let name = name.unwrap_or_else(|| claims.sub.clone());
+    ws.on_upgrade(move |socket| handle_authoring_socket(socket, id, state, claims, name))
+}
+
+fn send_direct(tx: &tokio::sync::mpsc::UnboundedSender<Message>, payload: String) {
+    let _ = tx.send(Message::Text(payload.into()));
+}
+
+fn lock(room: &Mutex<AuthoringRoom>) -> std::sync::MutexGuard<'_, AuthoringRoom> {
+    room.lock().expect("authoring room lock")
+}
+
+async fn handle_authoring_socket(
+    socket: WebSocket,
+    codelab_id: String,
+    state: Arc<AppState>,
+    claims: SessionClaims,
+    name: String,
+) {
+    let room = match open_authoring_room(&state, &codelab_id).await {
+        Ok(room) => room,
+        Err(err) => {
+            tracing::warn!("opening authoring room for codelab {codelab_id} failed: {err}");
+            return;
+        }
+    };
+    let (mut sender, mut receiver) = socket.split();
+    let (tx_ws, mut rx_ws) = tokio::sync::mpsc::unbounded_channel::<Message>();
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let channel = authoring_channel(&state, &codelab_id);
+
+    // Subscribe before taking the snapshot so no operation falls in between;
+    // clients skip operations at or below the revision they already have.
+    let mut rx_broadcast = channel.subscribe();
+    {
+        let mut room = lock(&room);
+        room.join(AuthoringEditor {
+            session_id: session_id.clone(),
+            user_id: claims.sub.clone(),
+            name: name.clone(),
+            step_id: None,
+            position: None,
+            selection_end: None,
+        });
+        send_direct(
+            &tx_ws,
+            json!({
+                "type": "authoring_ready",
+                "session_id": session_id,
+                "steps": room.snapshot(),
+                "editors": room.editors(),
+            })
+            .to_string(),
+        );
+        let _ = channel.send(presence_payload(&room));
+    }
+
+    let send_room = room.clone();
+    let mut send_task = tokio::spawn(async move {
+        loop {
+            let outbound = tokio::select! {
+                received = rx_broadcast.recv() => match received {
+                    Ok(msg) => Message::Text(msg.into()),
+                    // Missed operations cannot be replayed, so start the client over.
+                    Err(RecvError::Lagged(_)) => {
+                        Message::Text(snapshot_payload(&lock(&send_room)).into())
+                    }
+                    Err(RecvError::Closed) => return,
+                },
+                Some(msg) = rx_ws.recv() => msg,
+            };
+            if sender.send(outbound).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    let recv_room = room.clone();
+    let recv_session_id = session_id.clone();
+    let recv_channel = channel.clone();
+    let user_id = claims.sub.clone();
+    let mut recv_task = tokio::spawn(async move {
+        while let Some(Ok(message)) = receiver.next().await {
+            let Message::Text(text) = message else {
+                continue;
+            };
+            let mut room = lock(&recv_room);
+            let Ok(message) = serde_json::from_str::<AuthoringClientMessage>(&text) else {
+                // The sender may be waiting for an acknowledgement that will never come.
+                send_direct(
+                    &tx_ws,
+                    json!({ "type": "authoring_error", "message": "invalid message" }).to_string(),
+                );
+                send_direct(&tx_ws, snapshot_payload(&room));
+                continue;
+            };
+            match message {
+                AuthoringClientMessage::Operation {
+                    step_id,
+                    revision,
+                    operation,
+                } => match room.apply_operation(&step_id, revision, operation, &user_id) {
+                    Ok((operation, revision)) => {
+                        let payload = json!({
+                            "type": "authoring_operation",
+                            "session_id": recv_session_id,
+                            "user_id": user_id,
+                            "step_id": step_id,
+                            "revision": revision,
+                            "operation": operation,
+                        })
+                        .to_string();
+                        let _ = recv_channel.send(payload);
+                    }
+                    Err(err) => {
+                        send_direct(
+                            &tx_ws,
+                            json!({
+                                "type": "authoring_error",
+                                "step_id": step_id,
+                                "message": err.to_string(),
+                            })
+                            .to_string(),
+                        );
+                        send_direct(&tx_ws, snapshot_payload(&room));
+                    }
+                },
+                AuthoringClientMessage::Cursor {
+                    step_id,
+                    revision,
+                    position,
+                    selection_end,
+                } => {
+                    if let Ok(editor) = room.move_cursor(
+                        &recv_session_id,
+                        &step_id,
+                        revision,
+                        position,
+                        selection_end,
+                    ) {
+                        let mut payload = json!(editor);
+                        payload["type"] = json!("authoring_cursor");
+                        let _ = recv_channel.send(payload.to_string());
+                    }
+                }
+                AuthoringClientMessage::Resync => send_direct(&tx_ws, snapshot_payload(&room)),
+            }
+        }
+    });
+
+    tokio::select! {
+        _ = (&mut send_task) => {},
+        _ = (&mut recv_task) => {},
+    }
+    send_task.abort();
+    recv_task.abort();
+
+    let is_empty = {
+        let mut room = lock(&room);
+        room.leave(&session_id);
+        let _ = channel.send(presence_payload(&room));
+        room.editors().is_empty()
+    };
+    if is_empty {
+        if let Err(err) = save_authoring_room(&state, &codelab_id, &room).await {
+            tracing::warn!("saving authoring room for codelab {codelab_id} failed: {err}");
+        }
+    }
+}
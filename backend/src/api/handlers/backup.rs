@@ -1,12 +1,16 @@
 use crate::domain::models::{
-    AiConversation, AiMessage, AiThread, Attendee, ChatMessageRow, Codelab, Feedback, HelpRequest,
-    InlineCommentMessage, InlineCommentThread, Material, Quiz, QuizSubmission, Step, Submission,
+    AiConversation, AiMessage, AiThread, Attendee, Certificate, ChatMessageRow, Codelab, Cohort,
+    Feedback, HelpRequest, InlineCommentMessage, InlineCommentThread, Material, Quiz, QuizAttempt,
+    QuizSubmission, Step, Submission,
 };
 use crate::domain::services::codeserver::CodeServerManager;
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
-use crate::infrastructure::db_models::AuditLog;
-use crate::middleware::auth::AuthSession;
+use crate::infrastructure::db_models::{
+    ApiTokenRow, AuditLog, CodelabInviteRow, CodelabPasscodeRow, CodelabRevisionRow,
+    CodelabStaffRow, CodelabWaitlistRow, StaffRecoveryCodeRow, StaffTotpRow, UserRow,
+};
+use crate::middleware::auth::{AuthSession, Permission};
 use crate::middleware::request_info::RequestInfo;
 use crate::utils::error::{bad_request, internal_error};
 use axum::{
@@ -55,6 +59,39 @@ struct BackupData {
     inline_comment_threads: Vec<InlineCommentThread>,
     #[serde(default)]
     inline_comment_messages: Vec<InlineCommentMessage>,
+    #[serde(default)]
+    quiz_attempts: Vec<QuizAttempt>,
+    #[serde(default)]
+    certificates: Vec<Certificate>,
+    /// Staff accounts; `None` for backups taken before accounts existed, in
+    /// which case restore keeps the current accounts.
+    #[serde(default)]
+    users: Option<Vec<UserRow>>,
+    #[serde(default)]
+    codelab_staff: Vec<CodelabStaffRow>,
+    /// Hashed personal API tokens; `None` keeps the current tokens on restore.
+    #[serde(default)]
+    api_tokens: Option<Vec<ApiTokenRow>>,
+    /// Encrypted TOTP secrets; `None` keeps the current enrollments on restore.
+    #[serde(default)]
+    staff_totp: Option<Vec<StaffTotpRow>>,
+    #[serde(default)]
+    staff_recovery_codes: Option<Vec<StaffRecoveryCodeRow>>,
+    /// Encrypted join passcodes, keyed by codelab.
+    #[serde(default)]
+    codelab_passcodes: Vec<CodelabPasscodeRow>,
+    /// Hashed invite tokens with their usage counters.
+    #[serde(default)]
+    codelab_invites: Vec<CodelabInviteRow>,
+    /// Waitlisted learners with their encrypted join codes.
+    #[serde(default)]
+    codelab_waitlist: Vec<CodelabWaitlistRow>,
+    /// Scheduled runs of each codelab.
+    #[serde(default)]
+    codelab_cohorts: Vec<Cohort>,
+    /// Snapshots of codelab guides and steps.
+    #[serde(default)]
+    codelab_revisions: Vec<CodelabRevisionRow>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -86,6 +123,16 @@ pub struct BackupSummary {
     ai_messages: usize,
     inline_comment_threads: usize,
     inline_comment_messages: usize,
+    quiz_attempts: usize,
+    certificates: usize,
+    users: usize,
+    codelab_staff: usize,
+    api_tokens: usize,
+    staff_totp: usize,
+    codelab_invites: usize,
+    codelab_waitlist: usize,
+    codelab_cohorts: usize,
+    codelab_revisions: usize,
     uploads_files: usize,
     workspaces_files: usize,
 }
@@ -230,7 +277,7 @@ pub async fn export_backup(
     session: AuthSession,
     info: RequestInfo,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    session.require_admin()?;
+    let admin = session.require_permission(Permission::ManageSystem)?;
 
     let codelabs = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs"))
         .fetch_all(&state.pool)
@@ -245,7 +292,7 @@ pub async fn export_backup(
         .await
         .map_err(internal_error)?;
     let help_requests = sqlx::query_as::<_, HelpRequest>(&state.q(
-        "SELECT hr.id, hr.codelab_id, hr.attendee_id, COALESCE(a.name, '') AS attendee_name, hr.step_number, hr.status, hr.created_at FROM help_requests hr LEFT JOIN attendees a ON a.id = hr.attendee_id",
+        "SELECT hr.id, hr.codelab_id, hr.attendee_id, COALESCE(a.name, '') AS attendee_name, hr.step_number, hr.status, hr.cohort_id, hr.created_at FROM help_requests hr LEFT JOIN attendees a ON a.id = hr.attendee_id",
     ))
         .fetch_all(&state.pool)
         .await
@@ -272,6 +319,60 @@ pub async fn export_backup(
             .fetch_all(&state.pool)
             .await
             .map_err(internal_error)?;
+    let quiz_attempts = sqlx::query_as::<_, QuizAttempt>(&state.q("SELECT * FROM quiz_attempts"))
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    let certificates = sqlx::query_as::<_, Certificate>(&state.q("SELECT * FROM certificates"))
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    let users = sqlx::query_as::<_, UserRow>(&state.q("SELECT * FROM users"))
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    let codelab_staff =
+        sqlx::query_as::<_, CodelabStaffRow>(&state.q("SELECT * FROM codelab_staff"))
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let api_tokens = sqlx::query_as::<_, ApiTokenRow>(&state.q("SELECT * FROM api_tokens"))
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    let staff_totp = sqlx::query_as::<_, StaffTotpRow>(&state.q("SELECT * FROM staff_totp"))
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    let staff_recovery_codes =
+        sqlx::query_as::<_, StaffRecoveryCodeRow>(&state.q("SELECT * FROM staff_recovery_codes"))
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let codelab_passcodes =
+        sqlx::query_as::<_, CodelabPasscodeRow>(&state.q("SELECT * FROM codelab_passcodes"))
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let codelab_invites =
+        sqlx::query_as::<_, CodelabInviteRow>(&state.q("SELECT * FROM codelab_invites"))
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let codelab_waitlist =
+        sqlx::query_as::<_, CodelabWaitlistRow>(&state.q("SELECT * FROM codelab_waitlist"))
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let codelab_cohorts = sqlx::query_as::<_, Cohort>(&state.q("SELECT * FROM codelab_cohorts"))
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    let codelab_revisions =
+        sqlx::query_as::<_, CodelabRevisionRow>(&state.q("SELECT * FROM codelab_revisions"))
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
     let submissions = sqlx::query_as::<_, Submission>(&state.q(
         "SELECT id, codelab_id, attendee_id, file_path, file_name, file_size, submission_type, link_url, CAST(created_at AS TEXT) AS created_at FROM submissions",
     ))
@@ -339,6 +440,18 @@ pub async fn export_backup(
             ai_messages,
             inline_comment_threads,
             inline_comment_messages,
+            quiz_attempts,
+            certificates,
+            users: Some(users),
+            codelab_staff,
+            api_tokens: Some(api_tokens),
+            staff_totp: Some(staff_totp),
+            staff_recovery_codes: Some(staff_recovery_codes),
+            codelab_passcodes,
+            codelab_invites,
+            codelab_waitlist,
+            codelab_cohorts,
+            codelab_revisions,
         },
     };
 
@@ -366,7 +479,7 @@ pub async fn export_backup(
         AuditEntry {
             action: "backup_export".to_string(),
             actor_type: "admin".to_string(),
-            actor_id: Some(state.admin_id.clone()),
+            actor_id: Some(admin.sub),
             target_id: None,
             codelab_id: None,
             ip: Some(info.ip),
@@ -396,7 +509,7 @@ pub async fn restore_backup(
     info: RequestInfo,
     mut multipart: Multipart,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    session.require_admin()?;
+    let admin = session.require_permission(Permission::ManageSystem)?;
 
     let mut zip_data = Vec::new();
     while let Some(field) = multipart.next_field().await.map_err(map_multipart_error)? {
@@ -439,6 +552,34 @@ pub async fn restore_backup(
     let mut tx = state.pool.begin().await.map_err(internal_error)?;
 
     // Clear existing data (children first)
+    sqlx::query(&state.q("DELETE FROM codelab_staff"))
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM codelab_passcodes"))
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM codelab_invites"))
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM codelab_waitlist"))
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM codelab_cohorts"))
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM codelab_revisions"))
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM certificates"))
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
     sqlx::query(&state.q("DELETE FROM ai_messages"))
         .execute(&mut *tx)
         .await
@@ -455,6 +596,10 @@ pub async fn restore_backup(
         .execute(&mut *tx)
         .await
         .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM quiz_attempts"))
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
     sqlx::query(&state.q("DELETE FROM quizzes"))
         .execute(&mut *tx)
         .await
@@ -510,7 +655,7 @@ pub async fn restore_backup(
 
     // Restore data
     for row in &payload.data.codelabs {
-        sqlx::query(&state.q("INSERT INTO codelabs (id, title, description, author, is_public, quiz_enabled, require_quiz, require_feedback, guide_markdown, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"))
+        sqlx::query(&state.q("INSERT INTO codelabs (id, title, description, author, is_public, quiz_enabled, require_quiz, require_feedback, require_submission, quiz_max_attempts, quiz_pass_percentage, max_attendees, registration_opens_at, registration_closes_at, guide_markdown, created_at, published_revision_id, published_at, version, steps_version, categories) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"))
             .bind(&row.id)
             .bind(&row.title)
             .bind(&row.description)
@@ -519,27 +664,40 @@ pub async fn restore_backup(
             .bind(row.quiz_enabled)
             .bind(row.require_quiz)
             .bind(row.require_feedback)
+            .bind(row.require_submission)
+            .bind(row.quiz_max_attempts)
+            .bind(row.quiz_pass_percentage)
+            .bind(row.max_attendees)
+            .bind(row.registration_opens_at)
+            .bind(row.registration_closes_at)
             .bind(&row.guide_markdown)
             .bind(&row.created_at)
+            .bind(&row.published_revision_id)
+            .bind(&row.published_at)
+            // Backups taken before versioning carry no versions.
+            .bind(row.version.max(1))
+            .bind(row.steps_version.max(1))
+            .bind(&row.categories)
             .execute(&mut *tx)
             .await
             .map_err(internal_error)?;
     }
 
     for row in &payload.data.steps {
-        sqlx::query(&state.q("INSERT INTO steps (id, codelab_id, step_number, title, content_markdown) VALUES (?, ?, ?, ?, ?)"))
+        sqlx::query(&state.q("INSERT INTO steps (id, codelab_id, step_number, title, content_markdown, duration_minutes) VALUES (?, ?, ?, ?, ?, ?)"))
             .bind(&row.id)
             .bind(&row.codelab_id)
             .bind(row.step_number)
             .bind(&row.title)
             .bind(&row.content_markdown)
+            .bind(row.duration_minutes)
             .execute(&mut *tx)
             .await
             .map_err(internal_error)?;
     }
 
     for row in &payload.data.attendees {
-        sqlx::query(&state.q("INSERT INTO attendees (id, codelab_id, name, code, email, current_step, is_completed, completed_at, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"))
+        sqlx::query(&state.q("INSERT INTO attendees (id, codelab_id, name, code, email, current_step, is_completed, completed_at, cohort_id, erased_at, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"))
             .bind(&row.id)
             .bind(&row.codelab_id)
             .bind(&row.name)
@@ -548,6 +706,8 @@ pub async fn restore_backup(
             .bind(row.current_step)
             .bind(row.is_completed)
             .bind(&row.completed_at)
+            .bind(&row.cohort_id)
+            .bind(&row.erased_at)
             .bind(&row.created_at)
             .execute(&mut *tx)
             .await
@@ -555,12 +715,13 @@ pub async fn restore_backup(
     }
 
     for row in &payload.data.help_requests {
-        sqlx::query(&state.q("INSERT INTO help_requests (id, codelab_id, attendee_id, step_number, status, created_at) VALUES (?, ?, ?, ?, ?, ?)"))
+        sqlx::query(&state.q("INSERT INTO help_requests (id, codelab_id, attendee_id, step_number, status, cohort_id, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)"))
             .bind(&row.id)
             .bind(&row.codelab_id)
             .bind(&row.attendee_id)
             .bind(row.step_number)
             .bind(&row.status)
+            .bind(&row.cohort_id)
             .bind(&row.created_at)
             .execute(&mut *tx)
             .await
@@ -568,13 +729,14 @@ pub async fn restore_backup(
     }
 
     for row in &payload.data.chat_messages {
-        sqlx::query(&state.q("INSERT INTO chat_messages (id, codelab_id, sender_name, message, msg_type, target_id, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)"))
+        sqlx::query(&state.q("INSERT INTO chat_messages (id, codelab_id, sender_name, message, msg_type, target_id, cohort_id, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"))
             .bind(&row.id)
             .bind(&row.codelab_id)
             .bind(&row.sender_name)
             .bind(&row.message)
             .bind(&row.msg_type)
             .bind(&row.target_id)
+            .bind(&row.cohort_id)
             .bind(&row.created_at)
             .execute(&mut *tx)
             .await
@@ -615,13 +777,14 @@ pub async fn restore_backup(
     }
 
     for row in &payload.data.feedback {
-        sqlx::query(&state.q("INSERT INTO feedback (id, codelab_id, attendee_id, difficulty, satisfaction, comment, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)"))
+        sqlx::query(&state.q("INSERT INTO feedback (id, codelab_id, attendee_id, difficulty, satisfaction, comment, cohort_id, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"))
             .bind(&row.id)
             .bind(&row.codelab_id)
             .bind(&row.attendee_id)
             .bind(&row.difficulty)
             .bind(&row.satisfaction)
             .bind(&row.comment)
+            .bind(&row.cohort_id)
             .bind(&row.created_at)
             .execute(&mut *tx)
             .await
@@ -643,13 +806,29 @@ pub async fn restore_backup(
     }
 
     for row in &payload.data.quizzes {
-        sqlx::query(&state.q("INSERT INTO quizzes (id, codelab_id, question, quiz_type, options, correct_answer, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)"))
+        sqlx::query(&state.q("INSERT INTO quizzes (id, codelab_id, question, quiz_type, options, correct_answer, correct_answers, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"))
             .bind(&row.id)
             .bind(&row.codelab_id)
             .bind(&row.question)
             .bind(&row.quiz_type)
             .bind(&row.options)
             .bind(row.correct_answer)
+            .bind(&row.correct_answers)
+            .bind(&row.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    for row in &payload.data.quiz_attempts {
+        sqlx::query(&state.q("INSERT INTO quiz_attempts (id, codelab_id, attendee_id, attempt_number, total_questions, correct_count, score, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"))
+            .bind(&row.id)
+            .bind(&row.codelab_id)
+            .bind(&row.attendee_id)
+            .bind(row.attempt_number)
+            .bind(row.total_questions)
+            .bind(row.correct_count)
+            .bind(row.score)
             .bind(&row.created_at)
             .execute(&mut *tx)
             .await
@@ -657,19 +836,197 @@ pub async fn restore_backup(
     }
 
     for row in &payload.data.quiz_submissions {
-        sqlx::query(&state.q("INSERT INTO quiz_submissions (id, codelab_id, attendee_id, quiz_id, answer, is_correct, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)"))
+        sqlx::query(&state.q("INSERT INTO quiz_submissions (id, codelab_id, attendee_id, quiz_id, answer, is_correct, attempt_id, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"))
             .bind(&row.id)
             .bind(&row.codelab_id)
             .bind(&row.attendee_id)
             .bind(&row.quiz_id)
             .bind(&row.answer)
             .bind(row.is_correct)
+            .bind(&row.attempt_id)
+            .bind(&row.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    if let Some(users) = &payload.data.users {
+        sqlx::query(&state.q("DELETE FROM users"))
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+        for row in users {
+            sqlx::query(&state.q("INSERT INTO users (id, username, display_name, password_hash, role, created_at, updated_at, last_login_at, oidc_subject) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"))
+                .bind(&row.id)
+                .bind(&row.username)
+                .bind(&row.display_name)
+                .bind(&row.password_hash)
+                .bind(&row.role)
+                .bind(&row.created_at)
+                .bind(&row.updated_at)
+                .bind(&row.last_login_at)
+                .bind(&row.oidc_subject)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal_error)?;
+        }
+    }
+
+    if let Some(api_tokens) = &payload.data.api_tokens {
+        sqlx::query(&state.q("DELETE FROM api_tokens"))
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+        for row in api_tokens {
+            sqlx::query(&state.q("INSERT INTO api_tokens (id, user_id, name, token_hash, token_prefix, scopes, expires_at_epoch, last_used_at, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"))
+                .bind(&row.id)
+                .bind(&row.user_id)
+                .bind(&row.name)
+                .bind(&row.token_hash)
+                .bind(&row.token_prefix)
+                .bind(&row.scopes)
+                .bind(row.expires_at_epoch)
+                .bind(&row.last_used_at)
+                .bind(&row.created_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal_error)?;
+        }
+    }
+
+    if let Some(staff_totp) = &payload.data.staff_totp {
+        sqlx::query(&state.q("DELETE FROM staff_recovery_codes"))
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+        sqlx::query(&state.q("DELETE FROM staff_totp"))
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+        for row in staff_totp {
+            sqlx::query(&state.q("INSERT INTO staff_totp (subject, secret, enabled_at_epoch, last_used_step, created_at) VALUES (?, ?, ?, ?, ?)"))
+                .bind(&row.subject)
+                .bind(&row.secret)
+                .bind(row.enabled_at_epoch)
+                .bind(row.last_used_step)
+                .bind(&row.created_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal_error)?;
+        }
+        for row in payload.data.staff_recovery_codes.iter().flatten() {
+            sqlx::query(&state.q("INSERT INTO staff_recovery_codes (id, subject, code_hash, used_at_epoch, created_at) VALUES (?, ?, ?, ?, ?)"))
+                .bind(&row.id)
+                .bind(&row.subject)
+                .bind(&row.code_hash)
+                .bind(row.used_at_epoch)
+                .bind(&row.created_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal_error)?;
+        }
+    }
+
+    for row in &payload.data.codelab_staff {
+        sqlx::query(
+            &state
+                .q("INSERT INTO codelab_staff (codelab_id, user_id, created_at) VALUES (?, ?, ?)"),
+        )
+        .bind(&row.codelab_id)
+        .bind(&row.user_id)
+        .bind(&row.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+
+    for row in &payload.data.codelab_passcodes {
+        sqlx::query(&state.q(
+            "INSERT INTO codelab_passcodes (codelab_id, passcode, updated_at) VALUES (?, ?, ?)",
+        ))
+        .bind(&row.codelab_id)
+        .bind(&row.passcode)
+        .bind(&row.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+
+    for row in &payload.data.codelab_invites {
+        sqlx::query(&state.q("INSERT INTO codelab_invites (id, codelab_id, label, token_hash, token_prefix, max_uses, use_count, expires_at_epoch, revoked_at_epoch, created_by, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"))
+            .bind(&row.id)
+            .bind(&row.codelab_id)
+            .bind(&row.label)
+            .bind(&row.token_hash)
+            .bind(&row.token_prefix)
+            .bind(row.max_uses)
+            .bind(row.use_count)
+            .bind(row.expires_at_epoch)
+            .bind(row.revoked_at_epoch)
+            .bind(&row.created_by)
+            .bind(&row.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    for row in &payload.data.codelab_waitlist {
+        sqlx::query(&state.q("INSERT INTO codelab_waitlist (id, codelab_id, name, code, email, cohort_id, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)"))
+            .bind(&row.id)
+            .bind(&row.codelab_id)
+            .bind(&row.name)
+            .bind(&row.code)
+            .bind(&row.email)
+            .bind(&row.cohort_id)
+            .bind(&row.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    for row in &payload.data.codelab_cohorts {
+        sqlx::query(&state.q("INSERT INTO codelab_cohorts (id, codelab_id, name, starts_at, ends_at, created_at) VALUES (?, ?, ?, ?, ?, ?)"))
+            .bind(&row.id)
+            .bind(&row.codelab_id)
+            .bind(&row.name)
+            .bind(row.starts_at)
+            .bind(row.ends_at)
+            .bind(&row.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    for row in &payload.data.codelab_revisions {
+        sqlx::query(&state.q("INSERT INTO codelab_revisions (id, codelab_id, revision_number, guide_markdown, steps, author_id, source, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"))
+            .bind(&row.id)
+            .bind(&row.codelab_id)
+            .bind(row.revision_number)
+            .bind(&row.guide_markdown)
+            .bind(&row.steps)
+            .bind(&row.author_id)
+            .bind(&row.source)
             .bind(&row.created_at)
             .execute(&mut *tx)
             .await
             .map_err(internal_error)?;
     }
 
+    for row in &payload.data.certificates {
+        sqlx::query(&state.q("INSERT INTO certificates (id, attendee_id, codelab_id, key_id, signed_token, issued_at, revoked_at, revocation_reason) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"))
+            .bind(&row.id)
+            .bind(&row.attendee_id)
+            .bind(&row.codelab_id)
+            .bind(&row.key_id)
+            .bind(&row.signed_token)
+            .bind(&row.issued_at)
+            .bind(&row.revoked_at)
+            .bind(&row.revocation_reason)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+    }
+
     for row in &payload.data.submissions {
         let submission_type = row.submission_type.clone();
         sqlx::query(&state.q("INSERT INTO submissions (id, codelab_id, attendee_id, file_path, file_name, file_size, submission_type, link_url, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"))
@@ -777,7 +1134,7 @@ pub async fn restore_backup(
         AuditEntry {
             action: "backup_restore".to_string(),
             actor_type: "admin".to_string(),
-            actor_id: Some(state.admin_id.clone()),
+            actor_id: Some(admin.sub),
             target_id: None,
             codelab_id: None,
             ip: Some(info.ip),
@@ -796,7 +1153,7 @@ pub async fn inspect_backup(
     session: AuthSession,
     mut multipart: Multipart,
 ) -> Result<axum::Json<BackupSummary>, (StatusCode, String)> {
-    session.require_admin()?;
+    session.require_permission(Permission::ManageSystem)?;
 
     let mut zip_data = Vec::new();
     while let Some(field) = multipart.next_field().await.map_err(map_multipart_error)? {
@@ -869,6 +1226,16 @@ pub async fn inspect_backup(
         ai_messages: payload.data.ai_messages.len(),
         inline_comment_threads: payload.data.inline_comment_threads.len(),
         inline_comment_messages: payload.data.inline_comment_messages.len(),
+        quiz_attempts: payload.data.quiz_attempts.len(),
+        certificates: payload.data.certificates.len(),
+        users: payload.data.users.as_ref().map(Vec::len).unwrap_or(0),
+        codelab_staff: payload.data.codelab_staff.len(),
+        api_tokens: payload.data.api_tokens.as_ref().map(Vec::len).unwrap_or(0),
+        staff_totp: payload.data.staff_totp.as_ref().map(Vec::len).unwrap_or(0),
+        codelab_invites: payload.data.codelab_invites.len(),
+        codelab_waitlist: payload.data.codelab_waitlist.len(),
+        codelab_cohorts: payload.data.codelab_cohorts.len(),
+        codelab_revisions: payload.data.codelab_revisions.len(),
         uploads_files,
         workspaces_files,
     };
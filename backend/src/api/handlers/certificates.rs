@@ -0,0 +1,548 @@
+use crate::domain::models::{
+    Certificate, CertificateClaims, CertificateInfo, CertificateVerification, Codelab,
+    CredentialVerification, RevokeCertificatePayload,
+};
+use crate::domain::services::certificate_pdf::{
+    parse_stored_timestamp, render_certificate_pdf, CertificateDocument,
+};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::middleware::auth::{
+    now_epoch_seconds, staff_can_access_codelab, AuthSession, Permission,
+};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::{bad_request, forbidden, internal_error};
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const MAX_REVOCATION_REASON_LEN: usize = 500;
+const VC_CONTEXT_V2: &str = "https://www.w3.org/ns/credentials/v2";
+const OB_CONTEXT_V3: &str = "https://purl.imsglobal.org/spec/ob/v3p0/context-3.0.3.json";
+const MULTIKEY_CONTEXT: &str = "https://w3id.org/security/multikey/v1";
+
+/// Returns certificate metadata for a completed attendee, issuing the signed certificate on first use.
+pub async fn get_certificate(
+    Path(attendee_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CertificateInfo>, (StatusCode, String)> {
+    let is_completed: Option<(i32,)> =
+        sqlx::query_as(&state.q("SELECT is_completed FROM attendees WHERE id = ?"))
+            .bind(&attendee_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(internal_error)?;
+
+    match is_completed {
+        Some((1,)) => {}
+        Some(_) => return Err((StatusCode::FORBIDDEN, "REQUIREMENTS_NOT_MET".to_string())),
+        None => return Err((StatusCode::NOT_FOUND, "Certificate not found".to_string())),
+    }
+
+    let certificate = issue_certificate(&state, &attendee_id)
+        .await?
+        .ok_or((StatusCode::NOT_FOUND, "Certificate not found".to_string()))?;
+    let claims = verified_claims(&state, &certificate)?;
+
+    Ok(Json(CertificateInfo {
+        attendee_name: claims.attendee_name,
+        codelab_title: claims.codelab_title,
+        codelab_id: claims.codelab_id,
+        author: claims.author,
+        completed_at: claims.completed_at,
+        verification_url: format!("/api/certificates/verify/{}", certificate.id),
+        revoked: certificate.revoked_at.is_some(),
+        certificate_id: certificate.id,
+        signed_certificate: certificate.signed_token,
+    }))
+}
+
+/// Verifies a certificate's signature and revocation status. Public endpoint.
+pub async fn verify_certificate(
+    Path(cert_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CertificateVerification>, (StatusCode, String)> {
+    let certificate = fetch_certificate(&state, &cert_id).await?;
+    Ok(Json(build_verification(&state, certificate)))
+}
+
+/// Revokes a certificate so verification reports it as invalid.
+pub async fn revoke_certificate(
+    Path(cert_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<RevokeCertificatePayload>,
+) -> Result<Json<CertificateVerification>, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::RunSessions)?;
+    let reason = payload
+        .reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > MAX_REVOCATION_REASON_LEN)
+    {
+        return Err(bad_request("reason is too long"));
+    }
+
+    let certificate = fetch_certificate(&state, &cert_id).await?;
+    if !staff_can_access_codelab(&state, &admin, &certificate.codelab_id)
+        .await
+        .map_err(internal_error)?
+    {
+        return Err(forbidden());
+    }
+    if certificate.revoked_at.is_none() {
+        sqlx::query(&state.q(
+            "UPDATE certificates SET revoked_at = CAST(CURRENT_TIMESTAMP AS TEXT), revocation_reason = ? WHERE id = ? AND revoked_at IS NULL",
+        ))
+        .bind(&reason)
+        .bind(&cert_id)
+        .execute(&state.pool)
+        .await
+        .map_err(internal_error)?;
+
+        record_audit(
+            &state,
+            AuditEntry {
+                action: "certificate_revoke".to_string(),
+                actor_type: "admin".to_string(),
+                actor_id: Some(admin.sub),
+                target_id: Some(cert_id.clone()),
+                codelab_id: Some(certificate.codelab_id.clone()),
+                ip: Some(info.ip),
+                user_agent: info.user_agent,
+                metadata: reason
+                    .as_ref()
+                    .map(|reason| serde_json::json!({ "reason": reason })),
+            },
+        )
+        .await;
+    }
+
+    let certificate = fetch_certificate(&state, &cert_id).await?;
+    Ok(Json(build_verification(&state, certificate)))
+}
+
+/// Publishes the certificate signing keys as a JSON Web Key Set, the active key first.
+pub async fn get_certificate_keys(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    let keys: Vec<Value> = state
+        .certificate_signer
+        .verification_keys()
+        .iter()
+        .map(|key| {
+            json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "alg": "EdDSA",
+                "use": "sig",
+                "kid": key.key_id(),
+                "x": key.public_key_base64url(),
+            })
+        })
+        .collect();
+    Json(json!({ "keys": keys }))
+}
+
+/// Exports a completed attendee's certificate as a signed Open Badges 3.0 credential.
+///
+/// The credential carries an `eddsa-jcs-2022` Data Integrity proof whose
+/// verification method is published by [`get_credential_issuer`].
+pub async fn get_certificate_credential(
+    Path(attendee_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let origin = credential_origin(&state)?;
+    let (certificate, claims) = load_active_certificate(&state, &attendee_id).await?;
+    let codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
+        .bind(&claims.codelab_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))?;
+
+    let issuer_id = format!("{origin}/api/certificates/issuer");
+    let issued_at = DateTime::<Utc>::from_timestamp(claims.iat as i64, 0)
+        .unwrap_or_else(Utc::now)
+        .to_rfc3339_opts(SecondsFormat::Secs, true);
+    let valid_from = parse_timestamp(&claims.completed_at).unwrap_or_else(|| issued_at.clone());
+
+    let mut credential = json!({
+        "@context": [VC_CONTEXT_V2, OB_CONTEXT_V3],
+        "id": format!("urn:uuid:{}", certificate.id),
+        "type": ["VerifiableCredential", "OpenBadgeCredential"],
+        "name": format!("{} completion", claims.codelab_title),
+        "issuer": {
+            "id": issuer_id,
+            "type": ["Profile"],
+            "name": state.credential_issuer.name,
+            "url": origin,
+        },
+        "validFrom": valid_from,
+        "credentialSubject": {
+            "type": ["AchievementSubject"],
+            "identifier": [{
+                "type": "IdentityObject",
+                "identityHash": claims.attendee_name,
+                "identityType": "name",
+                "hashed": false,
+            }],
+            "achievement": {
+                "id": format!("{origin}/api/codelabs/{}#achievement", codelab.id),
+                "type": ["Achievement"],
+                "achievementType": "Course",
+                "name": claims.codelab_title,
+                "description": achievement_description(&codelab),
+                "criteria": { "narrative": achievement_criteria(&codelab) },
+                "creator": {
+                    "id": issuer_id,
+                    "type": ["Profile"],
+                    "name": state.credential_issuer.name,
+                },
+            },
+        },
+    });
+    state
+        .certificate_signer
+        .attach_data_integrity_proof(
+            &mut credential,
+            &format!("{issuer_id}#{}", state.certificate_signer.key_id()),
+            &issued_at,
+        )
+        .map_err(internal_error)?;
+
+    Ok(Json(credential))
+}
+
+/// Renders a completed attendee's certificate as a PDF using the configured template.
+pub async fn get_certificate_pdf(
+    Path(attendee_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let (certificate, claims) = load_active_certificate(&state, &attendee_id).await?;
+    let document = CertificateDocument {
+        verification_url: format!(
+            "{}/api/certificates/verify/{}",
+            public_origin(&state, &headers),
+            certificate.id
+        ),
+        certificate_id: certificate.id,
+        attendee_name: claims.attendee_name,
+        codelab_title: claims.codelab_title,
+        author: claims.author,
+        completed_at: claims.completed_at,
+        issuer_name: state.credential_issuer.name.clone(),
+    };
+    let filename = format!("certificate_{}.pdf", document.certificate_id);
+
+    let template = state.certificate_template.clone();
+    let pdf = tokio::task::spawn_blocking(move || render_certificate_pdf(&template, &document))
+        .await
+        .map_err(internal_error)?
+        .map_err(internal_error)?;
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/pdf"),
+    );
+    response_headers.insert(
+        header::CONTENT_DISPOSITION,
+        header::HeaderValue::from_str(&format!("inline; filename=\"{filename}\""))
+            .map_err(internal_error)?,
+    );
+    Ok((response_headers, pdf).into_response())
+}
+
+/// Publishes the Open Badges issuer profile and its Multikey verification methods.
+pub async fn get_credential_issuer(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let origin = credential_origin(&state)?;
+    let issuer_id = format!("{origin}/api/certificates/issuer");
+    let keys = state.certificate_signer.verification_keys();
+    let key_ids: Vec<String> = keys
+        .iter()
+        .map(|key| format!("{issuer_id}#{}", key.key_id()))
+        .collect();
+    let methods: Vec<Value> = keys
+        .iter()
+        .zip(&key_ids)
+        .map(|(key, key_id)| {
+            json!({
+                "id": key_id,
+                "type": "Multikey",
+                "controller": issuer_id,
+                "publicKeyMultibase": key.public_key_multibase(),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "@context": [VC_CONTEXT_V2, OB_CONTEXT_V3, MULTIKEY_CONTEXT],
+        "id": issuer_id,
+        "type": ["Profile"],
+        "name": state.credential_issuer.name,
+        "url": origin,
+        "verificationMethod": methods,
+        "assertionMethod": key_ids,
+    })))
+}
+
+/// Verifies an Open Badges credential exported by this server. Public endpoint.
+///
+/// The credential is valid when its issuer is this server, its Data Integrity
+/// proof verifies against a published issuer key, and the certificate it was
+/// issued for exists and is not revoked.
+pub async fn verify_credential(
+    State(state): State<Arc<AppState>>,
+    Json(credential): Json<Value>,
+) -> Result<Json<CredentialVerification>, (StatusCode, String)> {
+    let origin = credential_origin(&state)?;
+    let issuer_id = format!("{origin}/api/certificates/issuer");
+    let issued_here = credential.pointer("/issuer/id").and_then(Value::as_str)
+        == Some(issuer_id.as_str())
+        && credential
+            .pointer("/proof/verificationMethod")
+            .and_then(Value::as_str)
+            .is_some_and(|method| method.starts_with(&format!("{issuer_id}#")));
+    let proof_valid = issued_here
+        && state
+            .certificate_signer
+            .verify_data_integrity_proof(&credential);
+
+    let certificate = match credential
+        .get("id")
+        .and_then(Value::as_str)
+        .and_then(|id| id.strip_prefix("urn:uuid:"))
+        .filter(|_| proof_valid)
+    {
+        Some(cert_id) => {
+            sqlx::query_as::<_, Certificate>(&state.q("SELECT * FROM certificates WHERE id = ?"))
+                .bind(cert_id)
+                .fetch_optional(&state.pool)
+                .await
+                .map_err(internal_error)?
+        }
+        None => None,
+    };
+    let revoked = certificate
+        .as_ref()
+        .is_some_and(|certificate| certificate.revoked_at.is_some());
+
+    Ok(Json(CredentialVerification {
+        valid: proof_valid && certificate.is_some() && !revoked,
+        proof_valid,
+        revoked,
+        certificate_id: certificate.map(|certificate| certificate.id),
+    }))
+}
+
+/// Returns the attendee's certificate, signing and storing a new one if none exists.
+///
+/// Returns `None` when the attendee is unknown or has not completed the codelab.
+pub(crate) async fn issue_certificate(
+    state: &AppState,
+    attendee_id: &str,
+) -> Result<Option<Certificate>, (StatusCode, String)> {
+    if let Some(existing) = find_certificate_for_attendee(state, attendee_id).await? {
+        return Ok(Some(existing));
+    }
+
+    let row: Option<(String, String, String, String, String)> = sqlx::query_as(&state.q(
+        "SELECT a.name, c.id, c.title, c.author, COALESCE(a.completed_at, '')
+         FROM attendees a
+         JOIN codelabs c ON a.codelab_id = c.id
+         WHERE a.id = ? AND a.is_completed = 1",
+    ))
+    .bind(attendee_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let Some((attendee_name, codelab_id, codelab_title, author, completed_at)) = row else {
+        return Ok(None);
+    };
+
+    let claims = CertificateClaims {
+        iss: state.auth.issuer.clone(),
+        jti: Uuid::new_v4().to_string(),
+        sub: attendee_id.to_string(),
+        iat: now_epoch_seconds(),
+        codelab_id,
+        codelab_title,
+        attendee_name,
+        author,
+        completed_at,
+    };
+    let signed_token = state
+        .certificate_signer
+        .sign(&claims)
+        .map_err(internal_error)?;
+
+    let inserted = sqlx::query(&state.q(
+        "INSERT INTO certificates (id, attendee_id, codelab_id, key_id, signed_token) VALUES (?, ?, ?, ?, ?)",
+    ))
+    .bind(&claims.jti)
+    .bind(attendee_id)
+    .bind(&claims.codelab_id)
+    .bind(state.certificate_signer.key_id())
+    .bind(&signed_token)
+    .execute(&state.pool)
+    .await;
+
+    if let Err(err) = inserted {
+        // A concurrent request may have issued the certificate first.
+        return match find_certificate_for_attendee(state, attendee_id).await? {
+            Some(existing) => Ok(Some(existing)),
+            None => Err(internal_error(err)),
+        };
+    }
+
+    find_certificate_for_attendee(state, attendee_id).await
+}
+
+/// Issues (if needed) and returns an unrevoked certificate with its verified claims.
+async fn load_active_certificate(
+    state: &AppState,
+    attendee_id: &str,
+) -> Result<(Certificate, CertificateClaims), (StatusCode, String)> {
+    let certificate = issue_certificate(state, attendee_id)
+        .await?
+        .ok_or((StatusCode::NOT_FOUND, "Certificate not found".to_string()))?;
+    if certificate.revoked_at.is_some() {
+        return Err((StatusCode::GONE, "CERTIFICATE_REVOKED".to_string()));
+    }
+    let claims = verified_claims(state, &certificate)?;
+    Ok((certificate, claims))
+}
+
+/// Returns the claims of a stored certificate, or `409` when no key in the
+/// keyring verifies its signature, for example after its key was dropped.
+fn verified_claims(
+    state: &AppState,
+    certificate: &Certificate,
+) -> Result<CertificateClaims, (StatusCode, String)> {
+    state
+        .certificate_signer
+        .verify::<CertificateClaims>(&certificate.signed_token)
+        .ok_or((
+            StatusCode::CONFLICT,
+            "CERTIFICATE_SIGNATURE_INVALID".to_string(),
+        ))
+}
+
+async fn find_certificate_for_attendee(
+    state: &AppState,
+    attendee_id: &str,
+) -> Result<Option<Certificate>, (StatusCode, String)> {
+    sqlx::query_as::<_, Certificate>(&state.q("SELECT * FROM certificates WHERE attendee_id = ?"))
+        .bind(attendee_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)
+}
+
+async fn fetch_certificate(
+    state: &AppState,
+    cert_id: &str,
+) -> Result<Certificate, (StatusCode, String)> {
+    sqlx::query_as::<_, Certificate>(&state.q("SELECT * FROM certificates WHERE id = ?"))
+        .bind(cert_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Certificate not found".to_string()))
+}
+
+fn build_verification(state: &AppState, certificate: Certificate) -> CertificateVerification {
+    let claims = state
+        .certificate_signer
+        .verify::<CertificateClaims>(&certificate.signed_token)
+        .filter(|claims| claims.jti == certificate.id && claims.sub == certificate.attendee_id);
+    let signature_valid = claims.is_some();
+    let revoked = certificate.revoked_at.is_some();
+
+    CertificateVerification {
+        valid: signature_valid && !revoked,
+        signature_valid,
+        revoked,
+        revoked_at: certificate.revoked_at,
+        revocation_reason: certificate.revocation_reason,
+        key_id: certificate.key_id,
+        claims,
+        signed_certificate: certificate.signed_token,
+        certificate_id: certificate.id,
+    }
+}
+
+/// Returns the configured `PUBLIC_URL` that credential issuer and achievement
+/// URLs are built from.
+///
+/// Credentials are signed, so their issuer identity must not come from a
+/// client-controlled `Host` header; without `PUBLIC_URL` they are not issued.
+fn credential_origin(state: &AppState) -> Result<String, (StatusCode, String)> {
+    state.credential_issuer.public_url.clone().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "PUBLIC_URL_REQUIRED".to_string(),
+    ))
+}
+
+/// Returns the configured public origin, or one derived from the request's `Host` header.
+pub(crate) fn public_origin(state: &AppState, headers: &HeaderMap) -> String {
+    if let Some(public_url) = &state.credential_issuer.public_url {
+        return public_url.clone();
+    }
+    let host = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("localhost");
+    let forwarded_proto = headers
+        .get("x-forwarded-proto")
+        .and_then(|value| value.to_str().ok())
+        .filter(|_| state.trust_proxy);
+    let scheme = match forwarded_proto {
+        Some(proto) if proto.eq_ignore_ascii_case("https") => "https",
+        _ => "http",
+    };
+    format!("{scheme}://{host}")
+}
+
+/// Converts a stored timestamp (SQL `CURRENT_TIMESTAMP` or RFC 3339) to an XSD `dateTime`.
+fn parse_timestamp(value: &str) -> Option<String> {
+    parse_stored_timestamp(value).map(|parsed| parsed.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn achievement_description(codelab: &Codelab) -> String {
+    let description = codelab.description.trim();
+    if description.is_empty() {
+        format!("Completed the \"{}\" codelab.", codelab.title)
+    } else {
+        description.to_string()
+    }
+}
+
+fn achievement_criteria(codelab: &Codelab) -> String {
+    let mut requirements = vec!["Finish every step of the codelab".to_string()];
+    if codelab.require_quiz != 0 {
+        requirements.push(format!(
+            "pass the quiz with a score of at least {}%",
+            codelab.quiz_pass_percentage
+        ));
+    }
+    if codelab.require_feedback != 0 {
+        requirements.push("submit feedback".to_string());
+    }
+    if codelab.require_submission != 0 {
+        requirements.push("upload a submission".to_string());
+    }
+    format!("{}.", requirements.join(", "))
+}
@@ -3,13 +3,18 @@ use crate::api::dto::{
     CliAuthPollQuery, CliAuthPollResponse, CliAuthStartResponse, CliRuntimeCapabilities,
     CliRuntimeInfo,
 };
-use crate::api::handlers::admin::{issue_admin_session, validate_admin_credentials};
+use crate::api::handlers::admin::{
+    authenticate_staff, issue_admin_session, resolve_staff_identity,
+};
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
+use crate::infrastructure::mfa::{
+    claim_password_approval_attempt, finish_password_approval, totp_enabled, verify_second_factor,
+};
 use crate::middleware::auth::{now_epoch_seconds, AuthSession};
 use crate::middleware::request_info::RequestInfo;
 use crate::middleware::security::ensure_csrf_cookie;
-use crate::utils::error::{bad_request, internal_error, unauthorized};
+use crate::utils::error::{bad_request, forbidden, internal_error, unauthorized};
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
@@ -30,6 +35,7 @@ struct CliAuthRequestRow {
     status: String,
     poll_token_hash: String,
     expires_at_epoch: i64,
+    approved_by: Option<String>,
 }
 
 /// Returns runtime metadata used by the standalone CLI to probe server support.
@@ -101,6 +107,7 @@ pub async fn exchange_cli_auth(
     State(state): State<Arc<AppState>>,
     Path(request_id): Path<String>,
     jar: CookieJar,
+    info: RequestInfo,
     Json(payload): Json<CliAuthExchangeRequest>,
 ) -> Result<(CookieJar, Json<CliAuthExchangeResponse>), (StatusCode, String)> {
     let challenge = load_cli_auth_request(&state, &request_id)
@@ -133,12 +140,20 @@ pub async fn exchange_cli_auth(
         return Err(bad_request("CLI auth request has already been exchanged"));
     }
 
-    let (jar, claims, _token) = issue_admin_session(&state, jar).map_err(internal_error)?;
+    let approved_by = challenge
+        .approved_by
+        .unwrap_or_else(|| state.admin_id.clone());
+    let identity = resolve_staff_identity(&state, &approved_by)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(unauthorized)?;
+    let (jar, claims, _token) = issue_admin_session(&state, jar, &identity, &info).await?;
     Ok((
         jar,
         Json(CliAuthExchangeResponse {
             sub: claims.sub,
             role: claims.role,
+            staff_role: claims.staff_role,
             codelab_id: claims.codelab_id,
             exp: claims.exp,
         }),
@@ -173,13 +188,21 @@ pub async fn approve_cli_auth(
     }
 
     let actor_id = if let Ok(claims) = session.require_admin() {
+        // A CLI session carries the owner's full role, so an API token may
+        // not approve one and escape its scopes and expiry.
+        if claims.is_api_token() {
+            return Err(forbidden());
+        }
         claims.sub
     } else {
         if payload.admin_id.trim().is_empty() || payload.admin_pw.trim().is_empty() {
             return Err(bad_request("admin_id and admin_pw are required"));
         }
 
-        if !validate_admin_credentials(&state, payload.admin_id.trim(), payload.admin_pw.trim()) {
+        let identity = authenticate_staff(&state, payload.admin_id.trim(), payload.admin_pw.trim())
+            .await
+            .map_err(internal_error)?;
+        let Some(identity) = identity else {
             record_audit(
                 &state,
                 AuditEntry {
@@ -195,9 +218,53 @@ pub async fn approve_cli_auth(
             )
             .await;
             return Err(unauthorized());
+        };
+
+        if totp_enabled(&state, &identity.sub)
+            .await
+            .map_err(internal_error)?
+        {
+            if payload.otp_code.trim().is_empty() {
+                return Err((
+                    StatusCode::UNAUTHORIZED,
+                    "Two-factor code required".to_string(),
+                ));
+            }
+            if !claim_password_approval_attempt(&state, &identity.sub)
+                .await
+                .map_err(internal_error)?
+            {
+                return Err((
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "Too many two-factor attempts; try again later".to_string(),
+                ));
+            }
+            let factor = verify_second_factor(&state, &identity.sub, &payload.otp_code)
+                .await
+                .map_err(internal_error)?;
+            if factor.is_none() {
+                record_audit(
+                    &state,
+                    AuditEntry {
+                        action: "cli_auth_failed".to_string(),
+                        actor_type: "admin".to_string(),
+                        actor_id: Some(identity.sub),
+                        target_id: Some(request_id.clone()),
+                        codelab_id: None,
+                        ip: Some(info.ip),
+                        user_agent: info.user_agent,
+                        metadata: Some(serde_json::json!({ "reason": "invalid_second_factor" })),
+                    },
+                )
+                .await;
+                return Err(unauthorized());
+            }
+            finish_password_approval(&state, &identity.sub)
+                .await
+                .map_err(internal_error)?;
         }
 
-        state.admin_id.clone()
+        identity.sub
     };
 
     let updated = sqlx::query(&state.q("UPDATE cli_auth_requests
@@ -249,7 +316,7 @@ pub async fn cli_auth_page(
             } else {
                 challenge.status
             };
-            let has_admin_session = session.require_admin().is_ok();
+            let has_admin_session = session.require_interactive_admin().is_ok();
             let jar = if has_admin_session {
                 ensure_csrf_cookie(jar, &state, state.auth.admin_ttl)
             } else {
@@ -263,13 +330,20 @@ pub async fn cli_auth_page(
                     &status,
                     challenge.expires_at_epoch,
                     has_admin_session,
+                    state.oidc.as_ref().map(|cfg| cfg.provider_name.as_str()),
                 )),
             )
         }
         Ok(None) => (
             StatusCode::NOT_FOUND,
             jar,
-            Html(render_cli_auth_page(&request_id, "missing", now, false)),
+            Html(render_cli_auth_page(
+                &request_id,
+                "missing",
+                now,
+                false,
+                None,
+            )),
         ),
         Err(error) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -279,6 +353,7 @@ pub async fn cli_auth_page(
                 &format!("error:{error}"),
                 now,
                 false,
+                None,
             )),
         ),
     };
@@ -329,8 +404,8 @@ async fn load_cli_auth_request(
     state: &AppState,
     request_id: &str,
 ) -> Result<Option<CliAuthRequestRow>, sqlx::Error> {
-    let row = sqlx::query_as::<_, (String, String, i64)>(&state.q(
-        "SELECT status, poll_token_hash, expires_at_epoch
+    let row = sqlx::query_as::<_, (String, String, i64, Option<String>)>(&state.q(
+        "SELECT status, poll_token_hash, expires_at_epoch, approved_by
          FROM cli_auth_requests
          WHERE id = ?",
     ))
@@ -339,10 +414,11 @@ async fn load_cli_auth_request(
     .await?;
 
     Ok(row.map(
-        |(status, poll_token_hash, expires_at_epoch)| CliAuthRequestRow {
+        |(status, poll_token_hash, expires_at_epoch, approved_by)| CliAuthRequestRow {
             status,
             poll_token_hash,
             expires_at_epoch,
+            approved_by,
         },
     ))
 }
@@ -352,6 +428,7 @@ fn render_cli_auth_page(
     status: &str,
     expires_at_epoch: i64,
     has_admin_session: bool,
+    sso_provider: Option<&str>,
 ) -> String {
     let title = match status {
         "approved" => "CLI login approved",
@@ -385,6 +462,18 @@ fn render_cli_auth_page(
         ""
     };
     let action_disabled = if status == "pending" { "" } else { "disabled" };
+    let sso_link = match sso_provider {
+        Some(provider) => {
+            let return_to: String =
+                url::form_urlencoded::byte_serialize(format!("/cli/auth/{request_id}").as_bytes())
+                    .collect();
+            format!(
+                r#"<a class="sso" href="/api/auth/oidc/login?return_to={return_to}">Sign in with {}</a>"#,
+                escape_html(provider)
+            )
+        }
+        None => String::new(),
+    };
 
     format!(
         r#"<!doctype html>
@@ -470,6 +559,18 @@ fn render_cli_auth_page(
       background: #ece7dd;
       color: var(--ink);
     }}
+    .sso {{
+      display: block;
+      margin-top: 10px;
+      padding: 13px 16px;
+      border-radius: 14px;
+      text-align: center;
+      font-size: 15px;
+      font-weight: 700;
+      text-decoration: none;
+      background: #ece7dd;
+      color: var(--ink);
+    }}
     .hidden {{ display: none; }}
     .status {{
       margin-top: 14px;
@@ -495,7 +596,10 @@ fn render_cli_auth_page(
       <input id="admin_id" autocomplete="username" {action_disabled}>
       <label for="admin_pw">Password</label>
       <input id="admin_pw" type="password" autocomplete="current-password" {action_disabled}>
+      <label for="otp_code">Authentication code (if two-factor is enabled)</label>
+      <input id="otp_code" inputmode="numeric" autocomplete="one-time-code" {action_disabled}>
       <button id="login-button" {action_disabled}>Sign In And Approve</button>
+      {sso_link}
     </section>
 
     <section id="approve-panel" class="{approve_hidden}">
@@ -551,7 +655,8 @@ fn render_cli_auth_page(
       document.getElementById("login-button")?.addEventListener("click", async () => {{
         const adminId = document.getElementById("admin_id").value;
         const adminPw = document.getElementById("admin_pw").value;
-        await approve({{ admin_id: adminId, admin_pw: adminPw }});
+        const otpCode = document.getElementById("otp_code").value;
+        await approve({{ admin_id: adminId, admin_pw: adminPw, otp_code: otpCode }});
       }});
       document.getElementById("approve-button")?.addEventListener("click", async () => {{
         await approve({{}});
@@ -563,6 +668,14 @@ fn render_cli_auth_page(
     )
 }
 
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -706,4 +819,14 @@ mod tests {
         assert_eq!(exchange.role, "admin");
         assert_eq!(exchange.sub, "admin");
     }
+
+    #[test]
+    fn auth_page_links_to_sso_when_configured() {
+        let page = render_cli_auth_page("req-1", "pending", 0, false, Some("Acme <SSO>"));
+        assert!(page.contains("/api/auth/oidc/login?return_to=%2Fcli%2Fauth%2Freq-1"));
+        assert!(page.contains("Sign in with Acme &lt;SSO&gt;"));
+
+        let page = render_cli_auth_page("req-1", "pending", 0, false, None);
+        assert!(!page.contains("/api/auth/oidc/login"));
+    }
 }
@@ -1,45 +1,83 @@
-use crate::domain::models::{ChatMessageRow, Codelab, CreateCodelab, Step, UpdateStepsPayload};
+use crate::api::handlers::cohorts::{listing_scope, CohortQuery};
+use crate::api::handlers::users::assign_staff_to_codelab;
+use crate::domain::models::{
+    default_quiz_pass_percentage, ChatMessageRow, Codelab, CreateCodelab, Material, RevisionStep,
+    Step, UpdateStepsPayload, UpdateStepsResponse,
+};
+use crate::domain::services::static_site::{render_static_site, site_upload_path};
 use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::authoring::reload_authoring_room;
 use crate::infrastructure::database::AppState;
-use crate::middleware::auth::AuthSession;
+use crate::infrastructure::progress::{learner_step_ids, remap_attendee_progress};
+use crate::infrastructure::retention::upload_disk_path;
+use crate::infrastructure::revisions::{
+    load_revision_content, record_baseline_revision, record_revision, REVISION_SOURCE_GUIDE,
+    REVISION_SOURCE_STEPS,
+};
+use crate::middleware::auth::{AuthSession, Permission, SessionClaims, StaffRole};
 use crate::middleware::request_info::RequestInfo;
+use crate::utils::claat::{parse_claat, render_claat, ClaatDocument, ClaatStep};
 use crate::utils::error::{bad_request, forbidden, internal_error, unauthorized};
-use crate::utils::validation::{validate_codelab, validate_steps};
+use crate::utils::etag::{parse_if_match, version_conflict, version_etag};
+use crate::utils::validation::{
+    validate_codelab, validate_step_duration, validate_steps, MAX_STEPS,
+};
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{header, HeaderMap, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Json,
 };
 use axum_extra::extract::Multipart;
+use serde::Deserialize;
 use serde_json;
-use sqlx;
+use sqlx::{self, AnyConnection};
+use std::collections::HashMap;
 use std::io::{Cursor, Read, Write};
 use std::sync::Arc;
 use uuid;
 use zip;
 
+/// Query string choosing which version of a codelab's content to read.
+#[derive(Debug, Default, Deserialize)]
+pub struct CodelabContentQuery {
+    /// `draft` or `published`; only editors can read the draft, which they get by default.
+    pub version: Option<String>,
+}
+
 /// Lists codelabs visible to the current session.
 pub async fn list_codelabs(
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<Vec<Codelab>>, (StatusCode, String)> {
-    let is_admin = session
-        .claims
-        .as_ref()
-        .map(|claims| claims.role == "admin")
-        .unwrap_or(false);
-
-    let query = if is_admin {
-        "SELECT * FROM codelabs"
-    } else {
-        "SELECT * FROM codelabs WHERE is_public = 1"
-    };
-
-    let codelabs = sqlx::query_as::<_, Codelab>(&state.q(query))
-        .fetch_all(&state.pool)
-        .await
-        .map_err(internal_error)?;
+    let staff = session
+        .require_admin()
+        .ok()
+        .and_then(|claims| claims.staff_role().map(|role| (claims.sub, role)));
+
+    // Owners see every codelab; other staff see public codelabs plus the
+    // private ones they are assigned to.
+    let codelabs = match staff {
+        Some((_, StaffRole::Owner)) => {
+            sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs"))
+                .fetch_all(&state.pool)
+                .await
+        }
+        Some((user_id, _)) => {
+            sqlx::query_as::<_, Codelab>(&state.q(
+                "SELECT * FROM codelabs WHERE is_public = 1 OR id IN (SELECT codelab_id FROM codelab_staff WHERE user_id = ?)",
+            ))
+            .bind(user_id)
+            .fetch_all(&state.pool)
+            .await
+        }
+        None => {
+            sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE is_public = 1"))
+                .fetch_all(&state.pool)
+                .await
+        }
+    }
+    .map_err(internal_error)?;
 
     Ok(Json(codelabs))
 }
@@ -49,18 +87,43 @@ pub async fn get_codelab(
     Path(id): Path<String>,
     State(state): State<Arc<AppState>>,
     session: AuthSession,
+    Query(query): Query<CodelabContentQuery>,
 ) -> Result<Json<(Codelab, Vec<Step>)>, (StatusCode, String)> {
-    let codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
+    let mut codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
         .bind(&id)
         .fetch_optional(&state.pool)
         .await
         .map_err(internal_error)?
         .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))?;
 
-    if !can_access_codelab(&codelab, &session) {
+    if !can_access_codelab(&codelab, &session) && !session.is_codelab_staff(&state, &id).await? {
         return Err(forbidden());
     }
 
+    // Editors work on the draft; everyone else reads the published snapshot
+    // once there is one.
+    let wants_published = match query.version.as_deref() {
+        None | Some("draft") => session
+            .codelab_staff_claims(&state, &id, Permission::EditContent)
+            .await?
+            .is_none(),
+        Some("published") => true,
+        Some(_) => return Err(bad_request("version must be draft or published")),
+    };
+    if let Some(revision_id) = codelab
+        .published_revision_id
+        .clone()
+        .filter(|_| wants_published)
+    {
+        let (guide, steps) = load_revision_content(&state, &revision_id)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| internal_error("published revision missing"))?;
+        codelab.guide_markdown = guide;
+        let steps = revision_steps(&id, steps);
+        return Ok(Json((codelab, steps)));
+    }
+
     let steps = sqlx::query_as::<_, Step>(
         &state.q("SELECT * FROM steps WHERE codelab_id = ? ORDER BY step_number"),
     )
@@ -72,6 +135,22 @@ pub async fn get_codelab(
     Ok(Json((codelab, steps)))
 }
 
+/// Numbers a revision's steps as stored steps of `codelab_id`.
+fn revision_steps(codelab_id: &str, steps: Vec<RevisionStep>) -> Vec<Step> {
+    steps
+        .into_iter()
+        .enumerate()
+        .map(|(i, step)| Step {
+            id: step.id,
+            codelab_id: codelab_id.to_string(),
+            step_number: (i + 1) as i32,
+            title: step.title,
+            content_markdown: step.content_markdown,
+            duration_minutes: step.duration_minutes,
+        })
+        .collect()
+}
+
 /// Creates a new codelab.
 pub async fn create_codelab(
     State(state): State<Arc<AppState>>,
@@ -79,7 +158,7 @@ pub async fn create_codelab(
     info: RequestInfo,
     Json(payload): Json<CreateCodelab>,
 ) -> Result<Json<Codelab>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session.require_permission(Permission::EditContent)?;
     validate_codelab(&payload)?;
     let id = uuid::Uuid::new_v4().to_string();
     let is_public = payload.is_public.unwrap_or(true);
@@ -87,8 +166,15 @@ pub async fn create_codelab(
     let require_quiz = payload.require_quiz.unwrap_or(false);
     let require_feedback = payload.require_feedback.unwrap_or(false);
     let require_submission = payload.require_submission.unwrap_or(false);
-
-    sqlx::query(&state.q("INSERT INTO codelabs (id, title, description, author, is_public, quiz_enabled, require_quiz, require_feedback, require_submission, guide_markdown) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"))
+    let quiz_max_attempts = payload.quiz_max_attempts.unwrap_or(0);
+    let quiz_pass_percentage = payload
+        .quiz_pass_percentage
+        .unwrap_or_else(default_quiz_pass_percentage);
+    let max_attendees = payload.max_attendees.unwrap_or(0);
+    let registration_opens_at = payload.registration_opens_at.filter(|value| *value > 0);
+    let registration_closes_at = payload.registration_closes_at.filter(|value| *value > 0);
+
+    sqlx::query(&state.q("INSERT INTO codelabs (id, title, description, author, is_public, quiz_enabled, require_quiz, require_feedback, require_submission, quiz_max_attempts, quiz_pass_percentage, max_attendees, registration_opens_at, registration_closes_at, guide_markdown) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"))
         .bind(&id)
         .bind(&payload.title)
         .bind(&payload.description)
@@ -98,6 +184,11 @@ pub async fn create_codelab(
         .bind(require_quiz as i32)
         .bind(require_feedback as i32)
         .bind(require_submission as i32)
+        .bind(quiz_max_attempts)
+        .bind(quiz_pass_percentage)
+        .bind(max_attendees)
+        .bind(registration_opens_at)
+        .bind(registration_closes_at)
         .bind(&payload.guide_markdown)
         .execute(&state.pool)
         .await
@@ -108,6 +199,7 @@ pub async fn create_codelab(
         .fetch_one(&state.pool)
         .await
         .map_err(internal_error)?;
+    assign_creator(&state, &admin, &id).await?;
 
     record_audit(
         &state,
@@ -134,7 +226,9 @@ pub async fn copy_codelab(
     session: AuthSession,
     info: RequestInfo,
 ) -> Result<Json<Codelab>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
     let codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
         .bind(&id)
         .fetch_optional(&state.pool)
@@ -155,7 +249,7 @@ pub async fn copy_codelab(
     let new_id = uuid::Uuid::new_v4().to_string();
     let new_title = format!("{} (Copy)", codelab.title);
 
-    sqlx::query(&state.q("INSERT INTO codelabs (id, title, description, author, is_public, quiz_enabled, require_quiz, require_feedback, guide_markdown) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"))
+    sqlx::query(&state.q("INSERT INTO codelabs (id, title, description, author, is_public, quiz_enabled, require_quiz, require_feedback, require_submission, quiz_max_attempts, quiz_pass_percentage, max_attendees, guide_markdown, categories) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"))
         .bind(&new_id)
         .bind(&new_title)
         .bind(&codelab.description)
@@ -165,7 +259,11 @@ pub async fn copy_codelab(
         .bind(codelab.require_quiz)
         .bind(codelab.require_feedback)
         .bind(codelab.require_submission)
+        .bind(codelab.quiz_max_attempts)
+        .bind(codelab.quiz_pass_percentage)
+        .bind(codelab.max_attendees)
         .bind(&codelab.guide_markdown)
+        .bind(&codelab.categories)
         .execute(&mut *tx)
         .await
         .map_err(internal_error)?;
@@ -173,19 +271,21 @@ pub async fn copy_codelab(
     for step in steps {
         let step_id = uuid::Uuid::new_v4().to_string();
         sqlx::query(
-            &state.q("INSERT INTO steps (id, codelab_id, step_number, title, content_markdown) VALUES (?, ?, ?, ?, ?)"),
+            &state.q("INSERT INTO steps (id, codelab_id, step_number, title, content_markdown, duration_minutes) VALUES (?, ?, ?, ?, ?, ?)"),
         )
         .bind(&step_id)
         .bind(&new_id)
         .bind(step.step_number)
         .bind(&step.title)
         .bind(&step.content_markdown)
+        .bind(step.duration_minutes)
         .execute(&mut *tx)
         .await
         .map_err(internal_error)?;
     }
 
     tx.commit().await.map_err(internal_error)?;
+    assign_creator(&state, &admin, &new_id).await?;
 
     let new_codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
         .bind(&new_id)
@@ -212,22 +312,51 @@ pub async fn copy_codelab(
 }
 
 /// Updates editable metadata for a codelab.
+///
+/// With `If-Match`, the update is rejected with `409 Conflict` unless the
+/// codelab is still at that version.
 pub async fn update_codelab_info(
     Path(id): Path<String>,
     State(state): State<Arc<AppState>>,
     session: AuthSession,
     info: RequestInfo,
+    headers: HeaderMap,
     Json(payload): Json<CreateCodelab>,
-) -> Result<Json<Codelab>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+) -> Result<Response, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
     validate_codelab(&payload)?;
+    let expected_version = parse_if_match(&headers)?;
     let is_public = payload.is_public.unwrap_or(true);
     let quiz_enabled = payload.quiz_enabled.unwrap_or(false);
     let require_quiz = payload.require_quiz.unwrap_or(false);
     let require_feedback = payload.require_feedback.unwrap_or(false);
     let require_submission = payload.require_submission.unwrap_or(false);
 
-    sqlx::query(&state.q("UPDATE codelabs SET title = ?, description = ?, author = ?, is_public = ?, quiz_enabled = ?, require_quiz = ?, require_feedback = ?, require_submission = ?, guide_markdown = ? WHERE id = ?"))
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let (previous_guide, current_version): (Option<String>, i32) =
+        sqlx::query_as(&state.q("SELECT guide_markdown, version FROM codelabs WHERE id = ?"))
+            .bind(&id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(internal_error)?
+            .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))?;
+    if expected_version.is_some_and(|expected| expected != current_version) {
+        return Ok(codelab_conflict(current_version));
+    }
+    let guide_changed = previous_guide.as_deref().unwrap_or_default()
+        != payload.guide_markdown.as_deref().unwrap_or_default();
+    if guide_changed {
+        record_baseline_revision(&state, &mut tx, &id)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    // Quiz attempt and registration settings keep their stored values when
+    // omitted so older clients that do not know about them cannot reset them.
+    // A window bound of `0` clears it.
+    let updated = sqlx::query(&state.q("UPDATE codelabs SET title = ?, description = ?, author = ?, is_public = ?, quiz_enabled = ?, require_quiz = ?, require_feedback = ?, require_submission = ?, quiz_max_attempts = COALESCE(?, quiz_max_attempts), quiz_pass_percentage = COALESCE(?, quiz_pass_percentage), max_attendees = COALESCE(?, max_attendees), registration_opens_at = CASE WHEN ? IS NULL THEN registration_opens_at ELSE NULLIF(?, 0) END, registration_closes_at = CASE WHEN ? IS NULL THEN registration_closes_at ELSE NULLIF(?, 0) END, guide_markdown = ?, version = version + 1 WHERE id = ? AND version = ?"))
         .bind(&payload.title)
         .bind(&payload.description)
         .bind(&payload.author)
@@ -236,11 +365,40 @@ pub async fn update_codelab_info(
         .bind(require_quiz as i32)
         .bind(require_feedback as i32)
         .bind(require_submission as i32)
+        .bind(payload.quiz_max_attempts)
+        .bind(payload.quiz_pass_percentage)
+        .bind(payload.max_attendees)
+        .bind(payload.registration_opens_at)
+        .bind(payload.registration_opens_at)
+        .bind(payload.registration_closes_at)
+        .bind(payload.registration_closes_at)
         .bind(&payload.guide_markdown)
         .bind(&id)
-        .execute(&state.pool)
+        .bind(current_version)
+        .execute(&mut *tx)
         .await
         .map_err(internal_error)?;
+    if updated.rows_affected() == 0 {
+        // Another writer got in between the read and the update.
+        let latest: i32 = sqlx::query_scalar(&state.q("SELECT version FROM codelabs WHERE id = ?"))
+            .bind(&id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+        return Ok(codelab_conflict(latest));
+    }
+    if guide_changed {
+        record_revision(
+            &state,
+            &mut tx,
+            &id,
+            Some(&admin.sub),
+            REVISION_SOURCE_GUIDE,
+        )
+        .await
+        .map_err(internal_error)?;
+    }
+    tx.commit().await.map_err(internal_error)?;
 
     let codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
         .bind(&id)
@@ -263,20 +421,104 @@ pub async fn update_codelab_info(
     )
     .await;
 
-    Ok(Json(codelab))
+    Ok((
+        [(header::ETAG, version_etag(codelab.version))],
+        Json(codelab),
+    )
+        .into_response())
+}
+
+fn codelab_conflict(current_version: i32) -> Response {
+    version_conflict(
+        "Codelab was changed by someone else; reload it and try again",
+        current_version,
+    )
+}
+
+fn steps_conflict(current_version: i32) -> Response {
+    version_conflict(
+        "Steps were changed by someone else; reload them and try again",
+        current_version,
+    )
+}
+
+/// Bumps the `steps_version` of a codelab on `conn` and returns the version
+/// the change is based on.
+///
+/// Fails with `409 Conflict` when `expected` is given and the step set is at
+/// another version, or when a concurrent write bumped it first.
+pub(crate) async fn bump_steps_version(
+    state: &AppState,
+    conn: &mut AnyConnection,
+    id: &str,
+    expected: Option<i32>,
+) -> Result<i32, Response> {
+    let current_version: i32 =
+        sqlx::query_scalar(&state.q("SELECT steps_version FROM codelabs WHERE id = ?"))
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|err| internal_error(err).into_response())?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "Codelab not found").into_response())?;
+    if expected.is_some_and(|expected| expected != current_version) {
+        return Err(steps_conflict(current_version));
+    }
+    let bumped = sqlx::query(&state.q(
+        "UPDATE codelabs SET steps_version = steps_version + 1 WHERE id = ? AND steps_version = ?",
+    ))
+    .bind(id)
+    .bind(current_version)
+    .execute(&mut *conn)
+    .await
+    .map_err(|err| internal_error(err).into_response())?;
+    if bumped.rows_affected() == 0 {
+        let latest: i32 =
+            sqlx::query_scalar(&state.q("SELECT steps_version FROM codelabs WHERE id = ?"))
+                .bind(id)
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|err| internal_error(err).into_response())?;
+        return Err(steps_conflict(latest));
+    }
+    Ok(current_version)
 }
 
 /// Replaces the ordered step set for a codelab.
+///
+/// With `If-Match`, the write is rejected with `409 Conflict` unless the step
+/// set is still at that version.
 pub async fn update_codelab_steps(
     Path(id): Path<String>,
     State(state): State<Arc<AppState>>,
     session: AuthSession,
     info: RequestInfo,
+    headers: HeaderMap,
     Json(payload): Json<UpdateStepsPayload>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+) -> Result<Response, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
     validate_steps(&payload)?;
+    let expected_version = parse_if_match(&headers)?;
     let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let current_version = match bump_steps_version(&state, &mut tx, &id, expected_version).await {
+        Ok(version) => version,
+        Err(response) => return Ok(response),
+    };
+    let learner_steps = learner_step_ids(&state, &mut tx, &id)
+        .await
+        .map_err(internal_error)?;
+    record_baseline_revision(&state, &mut tx, &id)
+        .await
+        .map_err(internal_error)?;
+    let stored_durations: HashMap<String, Option<i32>> =
+        sqlx::query_as(&state.q("SELECT id, duration_minutes FROM steps WHERE codelab_id = ?"))
+            .bind(&id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(internal_error)?
+            .into_iter()
+            .collect();
 
     // Delete existing steps
     sqlx::query(&state.q("DELETE FROM steps WHERE codelab_id = ?"))
@@ -294,20 +536,39 @@ pub async fn update_codelab_steps(
             .filter(|value| !value.is_empty())
             .map(|value| value.to_string())
             .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let duration_minutes = step
+            .duration_minutes
+            .or_else(|| stored_durations.get(&step_id).copied().flatten());
         sqlx::query(
-            &state.q("INSERT INTO steps (id, codelab_id, step_number, title, content_markdown) VALUES (?, ?, ?, ?, ?)"),
+            &state.q("INSERT INTO steps (id, codelab_id, step_number, title, content_markdown, duration_minutes) VALUES (?, ?, ?, ?, ?, ?)"),
         )
         .bind(&step_id)
         .bind(&id)
         .bind((i + 1) as i32)
         .bind(&step.title)
         .bind(&step.content_markdown)
+        .bind(duration_minutes)
         .execute(&mut *tx)
         .await
         .map_err(internal_error)?;
     }
 
+    remap_attendee_progress(&state, &mut tx, &id, &learner_steps)
+        .await
+        .map_err(internal_error)?;
+    let revision_id = record_revision(
+        &state,
+        &mut tx,
+        &id,
+        Some(&admin.sub),
+        REVISION_SOURCE_STEPS,
+    )
+    .await
+    .map_err(internal_error)?;
     tx.commit().await.map_err(internal_error)?;
+    if let Err(err) = reload_authoring_room(&state, &id).await {
+        tracing::warn!("reloading authoring room for codelab {id} failed: {err}");
+    }
 
     record_audit(
         &state,
@@ -319,23 +580,48 @@ pub async fn update_codelab_steps(
             codelab_id: Some(id),
             ip: Some(info.ip),
             user_agent: info.user_agent,
-            metadata: None,
+            metadata: Some(serde_json::json!({ "revision_id": revision_id })),
         },
     )
     .await;
 
-    Ok(Json(serde_json::json!({ "status": "ok" })))
+    let steps_version = current_version + 1;
+    Ok((
+        [(header::ETAG, version_etag(steps_version))],
+        Json(UpdateStepsResponse {
+            status: "ok".to_string(),
+            revision_id,
+            steps_version,
+        }),
+    )
+        .into_response())
+}
+
+/// Query string choosing the file format of a codelab export.
+#[derive(Debug, Default, Deserialize)]
+pub struct CodelabExportQuery {
+    /// `zip`, the default, `claat` for a single claat markdown file, or `html`
+    /// for a zipped static site of the learner-facing content.
+    pub format: Option<String>,
 }
 
-/// Exports a codelab definition as a zip archive.
+/// Exports a codelab definition as a zip archive, as claat markdown, or as a
+/// static HTML site.
 pub async fn export_codelab(
     Path(id): Path<String>,
+    Query(query): Query<CodelabExportQuery>,
     State(state): State<Arc<AppState>>,
     session: AuthSession,
     info: RequestInfo,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let admin = session.require_admin()?;
-    let codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    let format = query.format.as_deref().unwrap_or("zip");
+    if !matches!(format, "zip" | "claat" | "html") {
+        return Err(bad_request("format must be zip, claat or html"));
+    }
+    let mut codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
         .bind(&id)
         .fetch_optional(&state.pool)
         .await
@@ -350,6 +636,82 @@ pub async fn export_codelab(
     .await
     .map_err(internal_error)?;
 
+    let (content_type, filename, buf) = if format == "claat" {
+        let markdown = render_claat(&claat_document(&codelab, steps));
+        (
+            "text/markdown; charset=utf-8",
+            format!("codelab_{}.md", id),
+            markdown.into_bytes(),
+        )
+    } else if format == "html" {
+        // The site is what learners see: the published revision once there
+        // is one, otherwise the draft.
+        let steps = match codelab.published_revision_id.clone() {
+            Some(revision_id) => {
+                let (guide, steps) = load_revision_content(&state, &revision_id)
+                    .await
+                    .map_err(internal_error)?
+                    .ok_or_else(|| internal_error("published revision missing"))?;
+                codelab.guide_markdown = guide;
+                revision_steps(&id, steps)
+            }
+            None => steps,
+        };
+        let materials = sqlx::query_as::<_, Material>(
+            &state.q("SELECT * FROM materials WHERE codelab_id = ? ORDER BY created_at ASC"),
+        )
+        .bind(&id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
+        // Highlighting and reading uploads from disk would stall the runtime.
+        let archive =
+            tokio::task::spawn_blocking(move || static_site_archive(&codelab, &steps, &materials))
+                .await
+                .map_err(internal_error)??;
+        (
+            "application/zip",
+            format!("codelab_{}_site.zip", id),
+            archive,
+        )
+    } else {
+        (
+            "application/zip",
+            format!("codelab_{}.zip", id),
+            codelab_archive(&codelab, steps)?,
+        )
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static(content_type),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        header::HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)).unwrap(),
+    );
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "codelab_export".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(id.clone()),
+            codelab_id: Some(id),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({ "format": format })),
+        },
+    )
+    .await;
+
+    Ok((headers, buf))
+}
+
+/// Packs a codelab's metadata, steps, and guide into a zip archive.
+fn codelab_archive(codelab: &Codelab, steps: Vec<Step>) -> Result<Vec<u8>, (StatusCode, String)> {
     let mut buf = Vec::new();
     let mut zip = zip::ZipWriter::new(Cursor::new(&mut buf));
     let options =
@@ -358,7 +720,7 @@ pub async fn export_codelab(
     // Add metadata
     zip.start_file("codelab.json", options)
         .map_err(internal_error)?;
-    let metadata = serde_json::to_string_pretty(&codelab).map_err(internal_error)?;
+    let metadata = serde_json::to_string_pretty(codelab).map_err(internal_error)?;
     zip.write_all(metadata.as_bytes()).map_err(internal_error)?;
 
     // Add steps
@@ -384,72 +746,82 @@ pub async fn export_codelab(
     }
 
     zip.finish().map_err(internal_error)?;
+    Ok(buf)
+}
 
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        header::CONTENT_TYPE,
-        header::HeaderValue::from_static("application/zip"),
-    );
-    headers.insert(
-        header::CONTENT_DISPOSITION,
-        header::HeaderValue::from_str(&format!("attachment; filename=\"codelab_{}.zip\"", id))
-            .unwrap(),
-    );
+/// Renders a codelab as a static site and packs it with the uploaded files
+/// its pages link to. Uploads missing from disk are left out.
+fn static_site_archive(
+    codelab: &Codelab,
+    steps: &[Step],
+    materials: &[Material],
+) -> Result<Vec<u8>, (StatusCode, String)> {
+    let site = render_static_site(codelab, steps, materials);
+    let mut buf = Vec::new();
+    let mut zip = zip::ZipWriter::new(Cursor::new(&mut buf));
+    let options =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
 
-    record_audit(
-        &state,
-        AuditEntry {
-            action: "codelab_export".to_string(),
-            actor_type: "admin".to_string(),
-            actor_id: Some(admin.sub),
-            target_id: Some(id.clone()),
-            codelab_id: Some(id),
-            ip: Some(info.ip),
-            user_agent: info.user_agent,
-            metadata: None,
-        },
-    )
-    .await;
+    for (path, contents) in &site.files {
+        zip.start_file(path.as_str(), options)
+            .map_err(internal_error)?;
+        zip.write_all(contents.as_bytes()).map_err(internal_error)?;
+    }
+    for upload in &site.uploads {
+        let Some(data) = upload_disk_path(upload).and_then(|path| std::fs::read(path).ok()) else {
+            tracing::warn!("static site export skipped missing upload {upload}");
+            continue;
+        };
+        zip.start_file(site_upload_path(upload), options)
+            .map_err(internal_error)?;
+        zip.write_all(&data).map_err(internal_error)?;
+    }
 
-    Ok((headers, buf))
+    zip.finish().map_err(internal_error)?;
+    Ok(buf)
 }
 
-/// Imports a codelab definition from an uploaded zip archive.
-pub async fn import_codelab(
-    State(state): State<Arc<AppState>>,
-    session: AuthSession,
-    info: RequestInfo,
-    mut multipart: Multipart,
-) -> Result<Json<Codelab>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
-    let mut zip_data = Vec::new();
-    while let Some(field) = multipart
-        .next_field()
-        .await
-        .map_err(|e: axum_extra::extract::multipart::MultipartError| bad_request(&e.to_string()))?
-    {
-        if field.name() == Some("file") {
-            zip_data = field
-                .bytes()
-                .await
-                .map_err(|e| bad_request(&e.to_string()))?
-                .to_vec();
-            if zip_data.len() > 20 * 1024 * 1024 {
-                return Err(bad_request("import file too large"));
-            }
-            break;
-        }
+/// Maps a codelab onto claat metadata and steps. claat has no place for the
+/// preparation guide or codelab settings, so those are left out.
+fn claat_document(codelab: &Codelab, steps: Vec<Step>) -> ClaatDocument {
+    let status = if codelab.published_revision_id.is_some() {
+        "Published"
+    } else {
+        "Draft"
+    };
+    ClaatDocument {
+        title: codelab.title.clone(),
+        summary: Some(codelab.description.clone()),
+        authors: Some(codelab.author.clone()),
+        categories: codelab.categories.clone(),
+        id: Some(codelab.id.clone()),
+        status: Some(status.to_string()),
+        steps: steps
+            .into_iter()
+            .map(|step| ClaatStep {
+                title: step.title,
+                duration_minutes: step.duration_minutes,
+                content_markdown: step.content_markdown,
+            })
+            .collect(),
     }
+}
 
-    if zip_data.is_empty() {
-        return Err(bad_request("No file uploaded"));
-    }
+/// Codelab read from an uploaded file, before it is stored.
+struct ImportedCodelab {
+    details: CreateCodelab,
+    categories: Option<String>,
+    /// `(step_number, title, content_markdown, duration_minutes)` per step.
+    steps: Vec<(i32, String, String, Option<i32>)>,
+}
 
+/// Reads a codelab from a zip archive made by [`export_codelab`].
+fn read_codelab_archive(data: Vec<u8>) -> Result<ImportedCodelab, (StatusCode, String)> {
     let mut archive =
-        zip::ZipArchive::new(Cursor::new(zip_data)).map_err(|e| bad_request(&e.to_string()))?;
+        zip::ZipArchive::new(Cursor::new(data)).map_err(|e| bad_request(&e.to_string()))?;
 
     let mut codelab: Option<Codelab> = None;
-    let mut steps_content: Vec<(i32, String, String)> = Vec::new();
+    let mut steps_content = Vec::new();
 
     for i in 0..archive.len() {
         let mut file = archive
@@ -471,63 +843,183 @@ pub async fn import_codelab(
             if parts.len() >= 3 {
                 let step_num: i32 = parts[1].parse().unwrap_or(0);
                 let title = parts[2..].join("_").replace(".md", "").replace("_", " ");
-                steps_content.push((step_num, title, contents));
+                steps_content.push((step_num, title, contents, None));
             }
         }
     }
 
-    let mut codelab = codelab.ok_or_else(|| bad_request("Missing codelab.json"))?;
-    let create = CreateCodelab {
-        title: codelab.title.clone(),
-        description: codelab.description.clone(),
-        author: codelab.author.clone(),
-        is_public: Some(codelab.is_public != 0),
-        quiz_enabled: Some(codelab.quiz_enabled != 0),
-        require_quiz: Some(codelab.require_quiz != 0),
-        require_feedback: Some(codelab.require_feedback != 0),
-        require_submission: Some(codelab.require_submission != 0), // Added this line
-        guide_markdown: codelab.guide_markdown.clone(),
+    let codelab = codelab.ok_or_else(|| bad_request("Missing codelab.json"))?;
+    steps_content.sort_by_key(|s| s.0);
+    Ok(ImportedCodelab {
+        details: CreateCodelab {
+            title: codelab.title,
+            description: codelab.description,
+            author: codelab.author,
+            is_public: Some(codelab.is_public != 0),
+            quiz_enabled: Some(codelab.quiz_enabled != 0),
+            require_quiz: Some(codelab.require_quiz != 0),
+            require_feedback: Some(codelab.require_feedback != 0),
+            require_submission: Some(codelab.require_submission != 0),
+            quiz_max_attempts: Some(codelab.quiz_max_attempts),
+            quiz_pass_percentage: Some(codelab.quiz_pass_percentage),
+            max_attendees: Some(codelab.max_attendees),
+            registration_opens_at: None,
+            registration_closes_at: None,
+            guide_markdown: codelab.guide_markdown,
+        },
+        categories: codelab.categories,
+        steps: steps_content,
+    })
+}
+
+/// Reads a codelab from claat markdown. The summary falls back to the title
+/// and the author to `default_author` when the metadata leaves them out.
+fn read_claat_codelab(
+    data: &[u8],
+    default_author: &str,
+) -> Result<ImportedCodelab, (StatusCode, String)> {
+    let text = std::str::from_utf8(data)
+        .map_err(|_| bad_request("import file must be a zip archive or claat markdown"))?;
+    let document = parse_claat(text).map_err(|err| bad_request(&err))?;
+    Ok(ImportedCodelab {
+        details: CreateCodelab {
+            description: document.summary.unwrap_or_else(|| document.title.clone()),
+            title: document.title,
+            author: document
+                .authors
+                .unwrap_or_else(|| default_author.to_string()),
+            is_public: None,
+            quiz_enabled: None,
+            require_quiz: None,
+            require_feedback: None,
+            require_submission: None,
+            quiz_max_attempts: None,
+            quiz_pass_percentage: None,
+            max_attendees: None,
+            registration_opens_at: None,
+            registration_closes_at: None,
+            guide_markdown: None,
+        },
+        categories: document.categories,
+        steps: document
+            .steps
+            .into_iter()
+            .enumerate()
+            .map(|(i, step)| {
+                (
+                    (i + 1) as i32,
+                    step.title,
+                    step.content_markdown,
+                    step.duration_minutes,
+                )
+            })
+            .collect(),
+    })
+}
+
+/// Imports a codelab from an uploaded zip archive or claat markdown file.
+pub async fn import_codelab(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    mut multipart: Multipart,
+) -> Result<Json<Codelab>, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::EditContent)?;
+    let mut file_data = Vec::new();
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e: axum_extra::extract::multipart::MultipartError| bad_request(&e.to_string()))?
+    {
+        if field.name() == Some("file") {
+            file_data = field
+                .bytes()
+                .await
+                .map_err(|e| bad_request(&e.to_string()))?
+                .to_vec();
+            if file_data.len() > 20 * 1024 * 1024 {
+                return Err(bad_request("import file too large"));
+            }
+            break;
+        }
+    }
+
+    if file_data.is_empty() {
+        return Err(bad_request("No file uploaded"));
+    }
+
+    // Zip archives start with a local file header; anything else is claat.
+    let format = if file_data.starts_with(b"PK\x03\x04") {
+        "zip"
+    } else {
+        "claat"
+    };
+    let imported = if format == "zip" {
+        read_codelab_archive(file_data)?
+    } else {
+        read_claat_codelab(&file_data, &admin.sub)?
     };
+    let create = imported.details;
     validate_codelab(&create)?;
-    codelab.id = uuid::Uuid::new_v4().to_string(); // New ID for imported codelab
+    if imported.steps.len() > MAX_STEPS {
+        return Err(bad_request("steps exceed limit"));
+    }
+    for (_, title, content, duration_minutes) in &imported.steps {
+        if title.len() > 200 || content.len() > 50_000 {
+            return Err(bad_request("step content too large"));
+        }
+        validate_step_duration(*duration_minutes)?;
+    }
+    let id = uuid::Uuid::new_v4().to_string(); // New ID for imported codelab
 
     let mut tx = state.pool.begin().await.map_err(internal_error)?;
 
-    sqlx::query(&state.q("INSERT INTO codelabs (id, title, description, author, is_public, quiz_enabled, require_quiz, require_feedback, guide_markdown) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"))
-        .bind(&codelab.id)
-        .bind(&codelab.title)
-        .bind(&codelab.description)
-        .bind(&codelab.author)
-        .bind(codelab.is_public)
-        .bind(codelab.quiz_enabled)
-        .bind(codelab.require_quiz)
-        .bind(codelab.require_feedback)
-        .bind(&codelab.guide_markdown)
+    sqlx::query(&state.q("INSERT INTO codelabs (id, title, description, author, is_public, quiz_enabled, require_quiz, require_feedback, require_submission, quiz_max_attempts, quiz_pass_percentage, max_attendees, guide_markdown, categories) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"))
+        .bind(&id)
+        .bind(&create.title)
+        .bind(&create.description)
+        .bind(&create.author)
+        .bind(create.is_public.unwrap_or(true) as i32)
+        .bind(create.quiz_enabled.unwrap_or(false) as i32)
+        .bind(create.require_quiz.unwrap_or(false) as i32)
+        .bind(create.require_feedback.unwrap_or(false) as i32)
+        .bind(create.require_submission.unwrap_or(false) as i32)
+        .bind(create.quiz_max_attempts.unwrap_or(0))
+        .bind(
+            create
+                .quiz_pass_percentage
+                .unwrap_or_else(default_quiz_pass_percentage),
+        )
+        .bind(create.max_attendees.unwrap_or(0))
+        .bind(&create.guide_markdown)
+        .bind(&imported.categories)
         .execute(&mut *tx)
         .await
         .map_err(internal_error)?;
 
-    steps_content.sort_by_key(|s| s.0);
-
-    for (step_num, title, content) in steps_content {
-        if title.len() > 200 || content.len() > 50_000 {
-            return Err(bad_request("step content too large"));
-        }
+    for (step_num, title, content, duration_minutes) in imported.steps {
         let step_id = uuid::Uuid::new_v4().to_string();
         sqlx::query(
-            &state.q("INSERT INTO steps (id, codelab_id, step_number, title, content_markdown) VALUES (?, ?, ?, ?, ?)"),
+            &state.q("INSERT INTO steps (id, codelab_id, step_number, title, content_markdown, duration_minutes) VALUES (?, ?, ?, ?, ?, ?)"),
         )
         .bind(&step_id)
-        .bind(&codelab.id)
+        .bind(&id)
         .bind(step_num)
         .bind(&title)
         .bind(&content)
+        .bind(duration_minutes)
         .execute(&mut *tx)
         .await
         .map_err(internal_error)?;
     }
 
+    let codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
+        .bind(&id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(internal_error)?;
     tx.commit().await.map_err(internal_error)?;
+    assign_creator(&state, &admin, &codelab.id).await?;
 
     record_audit(
         &state,
@@ -539,7 +1031,7 @@ pub async fn import_codelab(
             codelab_id: Some(codelab.id.clone()),
             ip: Some(info.ip),
             user_agent: info.user_agent,
-            metadata: None,
+            metadata: Some(serde_json::json!({ "format": format })),
         },
     )
     .await;
@@ -550,10 +1042,11 @@ pub async fn import_codelab(
 /// Returns chat history for a codelab.
 pub async fn get_chat_history(
     Path(id): Path<String>,
+    Query(query): Query<CohortQuery>,
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<Vec<ChatMessageRow>>, (StatusCode, String)> {
-    let claims = session.claims.ok_or_else(unauthorized)?;
+    let claims = session.claims.clone().ok_or_else(unauthorized)?;
     let codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
         .bind(&id)
         .fetch_optional(&state.pool)
@@ -561,22 +1054,30 @@ pub async fn get_chat_history(
         .map_err(internal_error)?
         .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))?;
 
-    if claims.role == "admin" {
-        // allowed
-    } else if claims.role == "attendee" && claims.codelab_id.as_deref() == Some(codelab.id.as_str())
-    {
-        // allowed
-    } else {
+    let is_staff = session
+        .codelab_staff_claims(&state, &codelab.id, Permission::AssistLearners)
+        .await?
+        .is_some();
+    let is_member =
+        claims.role == "attendee" && claims.codelab_id.as_deref() == Some(codelab.id.as_str());
+    if !is_staff && !is_member {
         return Err(forbidden());
     }
-
-    let messages = sqlx::query_as::<_, ChatMessageRow>(
-        &state.q("SELECT * FROM chat_messages WHERE codelab_id = ? ORDER BY created_at ASC"),
-    )
-    .bind(&id)
-    .fetch_all(&state.pool)
-    .await
-    .map_err(internal_error)?;
+    // Attendees only see their own cohort's room.
+    let scope = listing_scope(&state, &session, &id, is_staff, query.cohort_id).await?;
+
+    let sql = state.q(&format!(
+        "SELECT * FROM chat_messages WHERE codelab_id = ?{} ORDER BY created_at ASC",
+        scope.condition("cohort_id")
+    ));
+    let mut listing = sqlx::query_as::<_, ChatMessageRow>(&sql).bind(&id);
+    if let Some(cohort_id) = scope.bind_value() {
+        listing = listing.bind(cohort_id);
+    }
+    let messages = listing
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
 
     Ok(Json(messages))
 }
@@ -588,7 +1089,9 @@ pub async fn delete_codelab(
     session: AuthSession,
     info: RequestInfo,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
     tracing::debug!("Attempting to delete codelab: {}", id);
     let mut tx = state.pool.begin().await.map_err(internal_error)?;
 
@@ -621,6 +1124,13 @@ pub async fn delete_codelab(
         .await
         .map_err(internal_error)?;
 
+    // Delete issued certificates
+    sqlx::query(&state.q("DELETE FROM certificates WHERE codelab_id = ?"))
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
     // Delete quiz submissions and quizzes
     sqlx::query(&state.q("DELETE FROM quiz_submissions WHERE codelab_id = ?"))
         .bind(&id)
@@ -628,6 +1138,12 @@ pub async fn delete_codelab(
         .await
         .map_err(internal_error)?;
 
+    sqlx::query(&state.q("DELETE FROM quiz_attempts WHERE codelab_id = ?"))
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
     sqlx::query(&state.q("DELETE FROM quizzes WHERE codelab_id = ?"))
         .bind(&id)
         .execute(&mut *tx)
@@ -648,6 +1164,46 @@ pub async fn delete_codelab(
         .await
         .map_err(internal_error)?;
 
+    // Delete staff assignments
+    sqlx::query(&state.q("DELETE FROM codelab_staff WHERE codelab_id = ?"))
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    // Delete join passcode and invites
+    sqlx::query(&state.q("DELETE FROM codelab_passcodes WHERE codelab_id = ?"))
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM codelab_invites WHERE codelab_id = ?"))
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    // Delete waitlist entries
+    sqlx::query(&state.q("DELETE FROM codelab_waitlist WHERE codelab_id = ?"))
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    // Delete cohorts
+    sqlx::query(&state.q("DELETE FROM codelab_cohorts WHERE codelab_id = ?"))
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    // Delete revision history
+    sqlx::query(&state.q("DELETE FROM codelab_revisions WHERE codelab_id = ?"))
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
     // Delete codeserver workspaces
     sqlx::query(&state.q("DELETE FROM codeserver_workspaces WHERE codelab_id = ?"))
         .bind(&id)
@@ -727,7 +1283,7 @@ fn can_access_codelab(codelab: &Codelab, session: &AuthSession) -> bool {
         return true;
     }
     match &session.claims {
-        Some(claims) if claims.role == "admin" => true,
+        Some(claims) if claims.staff_role() == Some(StaffRole::Owner) => true,
         Some(claims)
             if claims.role == "attendee"
                 && claims.codelab_id.as_deref() == Some(codelab.id.as_str()) =>
@@ -738,6 +1294,20 @@ fn can_access_codelab(codelab: &Codelab, session: &AuthSession) -> bool {
     }
 }
 
+/// Assigns a newly created codelab to its creator unless they are an owner.
+async fn assign_creator(
+    state: &AppState,
+    admin: &SessionClaims,
+    codelab_id: &str,
+) -> Result<(), (StatusCode, String)> {
+    if admin.staff_role() == Some(StaffRole::Owner) {
+        return Ok(());
+    }
+    assign_staff_to_codelab(state, codelab_id, &admin.sub)
+        .await
+        .map_err(internal_error)
+}
+
 fn sanitize_filename(value: &str) -> String {
     let mut out = String::new();
     for ch in value.chars() {
@@ -818,6 +1388,9 @@ mod tests {
                 sub: "admin".to_string(),
                 role: "admin".to_string(),
                 codelab_id: None,
+                staff_role: None,
+                scopes: None,
+                jti: None,
                 iss: "test".to_string(),
                 aud: "test".to_string(),
                 iat: 0,
@@ -841,6 +1414,9 @@ mod tests {
                 sub: "user".to_string(),
                 role: "attendee".to_string(),
                 codelab_id: Some("123".to_string()),
+                staff_role: None,
+                scopes: None,
+                jti: None,
                 iss: "test".to_string(),
                 aud: "test".to_string(),
                 iat: 0,
@@ -864,6 +1440,9 @@ mod tests {
                 sub: "user".to_string(),
                 role: "attendee".to_string(),
                 codelab_id: Some("456".to_string()),
+                staff_role: None,
+                scopes: None,
+                jti: None,
                 iss: "test".to_string(),
                 aud: "test".to_string(),
                 iat: 0,
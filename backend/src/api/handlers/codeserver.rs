@@ -6,7 +6,7 @@ use crate::domain::services::codeserver::CodeServerManager;
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
 use crate::infrastructure::db_models::WorkspaceRow;
-use crate::middleware::auth::AuthSession;
+use crate::middleware::auth::{AuthSession, Permission};
 use crate::middleware::request_info::RequestInfo;
 use crate::utils::error::{bad_request, internal_error};
 use axum::{
@@ -25,7 +25,9 @@ pub async fn create_codeserver(
     info: RequestInfo,
     Json(payload): Json<CreateCodeServerRequest>,
 ) -> Result<Json<CodeServerInfo>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &payload.codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify codelab exists
     let _codelab = sqlx::query(&state.q("SELECT id FROM codelabs WHERE id = ?"))
@@ -110,7 +112,9 @@ pub async fn create_branch(
     info: RequestInfo,
     Json(payload): Json<CreateBranchRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
@@ -155,7 +159,9 @@ pub async fn download_workspace(
     session: AuthSession,
     info: RequestInfo,
 ) -> Result<Response, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
@@ -206,7 +212,9 @@ pub async fn get_codeserver_info(
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<CodeServerInfo>, (StatusCode, String)> {
-    session.require_admin()?;
+    session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     let workspace = sqlx::query_as::<_, WorkspaceRow>(
         &state.q("SELECT url, structure_type FROM codeserver_workspaces WHERE codelab_id = ?"),
@@ -230,7 +238,9 @@ pub async fn delete_codeserver(
     session: AuthSession,
     info: RequestInfo,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     let _workspace =
         sqlx::query(&state.q("SELECT url FROM codeserver_workspaces WHERE codelab_id = ?"))
@@ -280,7 +290,9 @@ pub async fn create_folder(
     info: RequestInfo,
     Json(payload): Json<CreateFolderRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
@@ -337,7 +349,9 @@ pub async fn list_branches(
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<Vec<String>>, (StatusCode, String)> {
-    session.require_admin()?;
+    session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
@@ -363,7 +377,9 @@ pub async fn list_files(
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<Vec<String>>, (StatusCode, String)> {
-    session.require_admin()?;
+    session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
@@ -390,7 +406,9 @@ pub async fn read_file(
     session: AuthSession,
     axum::extract::Query(query): axum::extract::Query<ReadFileQuery>,
 ) -> Result<String, (StatusCode, String)> {
-    session.require_admin()?;
+    session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
@@ -416,7 +434,9 @@ pub async fn list_folders(
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<Vec<String>>, (StatusCode, String)> {
-    session.require_admin()?;
+    session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
@@ -442,7 +462,9 @@ pub async fn list_folder_files(
     State(state): State<Arc<AppState>>,
     session: AuthSession,
 ) -> Result<Json<Vec<String>>, (StatusCode, String)> {
-    session.require_admin()?;
+    session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
@@ -469,7 +491,9 @@ pub async fn read_folder_file(
     session: AuthSession,
     axum::extract::Query(query): axum::extract::Query<ReadFileQuery>,
 ) -> Result<String, (StatusCode, String)> {
-    session.require_admin()?;
+    session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
@@ -497,7 +521,9 @@ pub async fn update_branch_files(
     info: RequestInfo,
     Json(payload): Json<UpdateWorkspaceFilesRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
@@ -578,7 +604,9 @@ pub async fn update_folder_files(
     info: RequestInfo,
     Json(payload): Json<UpdateWorkspaceFilesRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
 
     // Verify workspace exists
     let _workspace =
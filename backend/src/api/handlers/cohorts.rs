@@ -0,0 +1,394 @@
+use crate::domain::models::{Cohort, CohortSummary, CreateCohort};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::middleware::auth::{now_epoch_seconds, AuthSession, Permission};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::{forbidden, internal_error};
+use crate::utils::validation::validate_cohort;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Query string of listings that staff can narrow to one cohort.
+#[derive(Debug, Default, Deserialize)]
+pub struct CohortQuery {
+    /// Cohort to list; staff see every cohort when omitted.
+    pub cohort_id: Option<String>,
+}
+
+/// Which cohort's live data a request may see.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CohortScope {
+    /// Every row of the codelab, for staff without a filter.
+    All,
+    /// Rows of one cohort.
+    Cohort(String),
+    /// Rows recorded outside any cohort.
+    Unassigned,
+}
+
+impl CohortScope {
+    /// Scope of an attendee, who only sees their own cohort.
+    pub(crate) fn for_attendee(cohort_id: Option<String>) -> Self {
+        cohort_id.map_or(Self::Unassigned, Self::Cohort)
+    }
+
+    /// Returns the SQL condition on `column`, starting with ` AND`, or nothing.
+    pub(crate) fn condition(&self, column: &str) -> String {
+        match self {
+            Self::All => String::new(),
+            Self::Cohort(_) => format!(" AND {column} = ?"),
+            Self::Unassigned => format!(" AND {column} IS NULL"),
+        }
+    }
+
+    /// Returns the value bound by [`Self::condition`], if it binds one.
+    pub(crate) fn bind_value(&self) -> Option<&str> {
+        match self {
+            Self::Cohort(cohort_id) => Some(cohort_id),
+            Self::All | Self::Unassigned => None,
+        }
+    }
+}
+
+/// Returns the key of the websocket room for a codelab cohort.
+pub(crate) fn room_key(codelab_id: &str, cohort_id: Option<&str>) -> String {
+    match cohort_id {
+        Some(cohort_id) => format!("{codelab_id}/{cohort_id}"),
+        None => codelab_id.to_string(),
+    }
+}
+
+/// Finds a cohort of `codelab_id`.
+pub(crate) async fn fetch_cohort(
+    state: &AppState,
+    codelab_id: &str,
+    cohort_id: &str,
+) -> Result<Option<Cohort>, sqlx::Error> {
+    sqlx::query_as::<_, Cohort>(
+        &state.q("SELECT * FROM codelab_cohorts WHERE id = ? AND codelab_id = ?"),
+    )
+    .bind(cohort_id)
+    .bind(codelab_id)
+    .fetch_optional(&state.pool)
+    .await
+}
+
+/// Returns the cohort running right now, preferring the one that started last.
+pub(crate) async fn active_cohort(
+    state: &AppState,
+    codelab_id: &str,
+) -> Result<Option<Cohort>, sqlx::Error> {
+    let now = now_epoch_seconds() as i64;
+    sqlx::query_as::<_, Cohort>(&state.q(
+        "SELECT * FROM codelab_cohorts WHERE codelab_id = ? AND (starts_at IS NULL OR starts_at <= ?) AND (ends_at IS NULL OR ends_at > ?) ORDER BY COALESCE(starts_at, 0) DESC, created_at DESC LIMIT 1",
+    ))
+    .bind(codelab_id)
+    .bind(now)
+    .bind(now)
+    .fetch_optional(&state.pool)
+    .await
+}
+
+/// Returns the cohort an attendee joined.
+pub(crate) async fn attendee_cohort(
+    state: &AppState,
+    attendee_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let row: Option<(Option<String>,)> =
+        sqlx::query_as(&state.q("SELECT cohort_id FROM attendees WHERE id = ?"))
+            .bind(attendee_id)
+            .fetch_optional(&state.pool)
+            .await?;
+    Ok(row.and_then(|(cohort_id,)| cohort_id))
+}
+
+/// Resolves the cohort scope of a live-data listing.
+///
+/// Staff may narrow the listing with `requested`; attendees always see their
+/// own cohort and must belong to the codelab.
+pub(crate) async fn listing_scope(
+    state: &AppState,
+    session: &AuthSession,
+    codelab_id: &str,
+    is_staff: bool,
+    requested: Option<String>,
+) -> Result<CohortScope, (StatusCode, String)> {
+    if is_staff {
+        return Ok(requested
+            .filter(|cohort_id| !cohort_id.is_empty())
+            .map_or(CohortScope::All, CohortScope::Cohort));
+    }
+    let attendee = session.require_attendee()?;
+    if attendee.codelab_id.as_deref() != Some(codelab_id) {
+        return Err(forbidden());
+    }
+    let cohort_id = attendee_cohort(state, &attendee.sub)
+        .await
+        .map_err(internal_error)?;
+    Ok(CohortScope::for_attendee(cohort_id))
+}
+
+async fn count_in_cohort(state: &AppState, sql: &str, cohort_id: &str) -> Result<i64, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as(&state.q(sql))
+        .bind(cohort_id)
+        .fetch_one(&state.pool)
+        .await?;
+    Ok(count)
+}
+
+async fn summarize_cohort(state: &AppState, cohort: Cohort) -> Result<CohortSummary, sqlx::Error> {
+    let attendee_count = count_in_cohort(
+        state,
+        "SELECT COUNT(*) FROM attendees WHERE cohort_id = ?",
+        &cohort.id,
+    )
+    .await?;
+    let completed_count = count_in_cohort(
+        state,
+        "SELECT COUNT(*) FROM attendees WHERE cohort_id = ? AND is_completed = 1",
+        &cohort.id,
+    )
+    .await?;
+    let help_request_count = count_in_cohort(
+        state,
+        "SELECT COUNT(*) FROM help_requests WHERE cohort_id = ?",
+        &cohort.id,
+    )
+    .await?;
+    let (feedback_count, avg_satisfaction, avg_difficulty): (i64, Option<f64>, Option<f64>) =
+        sqlx::query_as(&state.q(
+            "SELECT COUNT(*), AVG(CAST(satisfaction AS REAL)), AVG(CAST(difficulty AS REAL)) FROM feedback WHERE cohort_id = ?",
+        ))
+        .bind(&cohort.id)
+        .fetch_one(&state.pool)
+        .await?;
+    Ok(CohortSummary {
+        cohort,
+        attendee_count,
+        completed_count,
+        help_request_count,
+        feedback_count,
+        avg_satisfaction,
+        avg_difficulty,
+    })
+}
+
+/// Fails with `404` unless the codelab exists.
+pub(crate) async fn ensure_codelab_exists(
+    state: &AppState,
+    id: &str,
+) -> Result<(), (StatusCode, String)> {
+    let exists: Option<(String,)> =
+        sqlx::query_as(&state.q("SELECT id FROM codelabs WHERE id = ?"))
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    exists
+        .map(|_| ())
+        .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))
+}
+
+async fn audit_cohort(
+    state: &AppState,
+    action: &str,
+    actor: String,
+    cohort_id: String,
+    codelab_id: String,
+    info: RequestInfo,
+) {
+    record_audit(
+        state,
+        AuditEntry {
+            action: action.to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(actor),
+            target_id: Some(cohort_id),
+            codelab_id: Some(codelab_id),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: None,
+        },
+    )
+    .await;
+}
+
+/// Lists the cohorts of a codelab in schedule order with per-run numbers.
+pub async fn list_cohorts(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<Vec<CohortSummary>>, (StatusCode, String)> {
+    session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    let cohorts = sqlx::query_as::<_, Cohort>(&state.q(
+        "SELECT * FROM codelab_cohorts WHERE codelab_id = ? ORDER BY COALESCE(starts_at, 0), created_at",
+    ))
+    .bind(&id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let mut summaries = Vec::with_capacity(cohorts.len());
+    for cohort in cohorts {
+        summaries.push(
+            summarize_cohort(&state, cohort)
+                .await
+                .map_err(internal_error)?,
+        );
+    }
+    Ok(Json(summaries))
+}
+
+/// Schedules a new cohort of a codelab.
+pub async fn create_cohort(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<CreateCohort>,
+) -> Result<(StatusCode, Json<Cohort>), (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    ensure_codelab_exists(&state, &id).await?;
+    validate_cohort(&payload)?;
+
+    let cohort_id = Uuid::new_v4().to_string();
+    sqlx::query(&state.q(
+        "INSERT INTO codelab_cohorts (id, codelab_id, name, starts_at, ends_at) VALUES (?, ?, ?, ?, ?)",
+    ))
+    .bind(&cohort_id)
+    .bind(&id)
+    .bind(payload.name.trim())
+    .bind(payload.starts_at)
+    .bind(payload.ends_at)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    audit_cohort(
+        &state,
+        "cohort_create",
+        admin.sub,
+        cohort_id.clone(),
+        id.clone(),
+        info,
+    )
+    .await;
+
+    let cohort = fetch_cohort(&state, &id, &cohort_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| internal_error("cohort missing after insert"))?;
+    Ok((StatusCode::CREATED, Json(cohort)))
+}
+
+/// Renames or reschedules a cohort.
+pub async fn update_cohort(
+    Path((id, cohort_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<CreateCohort>,
+) -> Result<Json<Cohort>, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    validate_cohort(&payload)?;
+    let updated = sqlx::query(&state.q(
+        "UPDATE codelab_cohorts SET name = ?, starts_at = ?, ends_at = ? WHERE id = ? AND codelab_id = ?",
+    ))
+    .bind(payload.name.trim())
+    .bind(payload.starts_at)
+    .bind(payload.ends_at)
+    .bind(&cohort_id)
+    .bind(&id)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    if updated.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Cohort not found".to_string()));
+    }
+
+    audit_cohort(
+        &state,
+        "cohort_update",
+        admin.sub,
+        cohort_id.clone(),
+        id.clone(),
+        info,
+    )
+    .await;
+
+    let cohort = fetch_cohort(&state, &id, &cohort_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Cohort not found".to_string()))?;
+    Ok(Json(cohort))
+}
+
+/// Deletes a cohort that has no attendees yet.
+pub async fn delete_cohort(
+    Path((id, cohort_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    fetch_cohort(&state, &id, &cohort_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Cohort not found".to_string()))?;
+    let attendees = count_in_cohort(
+        &state,
+        "SELECT COUNT(*) FROM attendees WHERE cohort_id = ?",
+        &cohort_id,
+    )
+    .await
+    .map_err(internal_error)?;
+    if attendees > 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            "Cohort has attendees and cannot be deleted".to_string(),
+        ));
+    }
+
+    sqlx::query(&state.q("DELETE FROM codelab_cohorts WHERE id = ? AND codelab_id = ?"))
+        .bind(&cohort_id)
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(internal_error)?;
+
+    audit_cohort(&state, "cohort_delete", admin.sub, cohort_id, id, info).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cohort_scope_builds_conditions_and_room_keys() {
+        assert_eq!(CohortScope::All.condition("a.cohort_id"), "");
+        let scope = CohortScope::for_attendee(Some("c1".to_string()));
+        assert_eq!(scope.condition("cohort_id"), " AND cohort_id = ?");
+        assert_eq!(scope.bind_value(), Some("c1"));
+        let scope = CohortScope::for_attendee(None);
+        assert_eq!(scope.condition("cohort_id"), " AND cohort_id IS NULL");
+        assert_eq!(scope.bind_value(), None);
+
+        assert_eq!(room_key("lab-1", None), "lab-1");
+        assert_eq!(room_key("lab-1", Some("c1")), "lab-1/c1");
+    }
+}
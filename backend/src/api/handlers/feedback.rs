@@ -1,12 +1,13 @@
+use crate::api::handlers::cohorts::{attendee_cohort, CohortQuery, CohortScope};
 use crate::domain::models::{CreateFeedback, Feedback};
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
-use crate::middleware::auth::AuthSession;
+use crate::middleware::auth::{AuthSession, Permission};
 use crate::middleware::request_info::RequestInfo;
 use crate::utils::error::{forbidden, internal_error};
 use crate::utils::validation::validate_feedback;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
 };
@@ -28,14 +29,18 @@ pub async fn submit_feedback(
     validate_feedback(&payload)?;
     let feedback_id = Uuid::new_v4().to_string();
     let attendee_id = attendee.sub.clone();
+    let cohort_id = attendee_cohort(&state, &attendee_id)
+        .await
+        .map_err(internal_error)?;
 
-    let result = sqlx::query(&state.q("INSERT INTO feedback (id, codelab_id, difficulty, satisfaction, comment, attendee_id) VALUES (?, ?, ?, ?, ?, ?)"))
+    let result = sqlx::query(&state.q("INSERT INTO feedback (id, codelab_id, difficulty, satisfaction, comment, attendee_id, cohort_id) VALUES (?, ?, ?, ?, ?, ?, ?)"))
         .bind(&feedback_id)
         .bind(&id)
         .bind(&payload.difficulty)
         .bind(&payload.satisfaction)
         .bind(&payload.comment)
         .bind(&attendee_id)
+        .bind(&cohort_id)
         .execute(&state.pool)
         .await;
 
@@ -81,20 +86,32 @@ fn is_duplicate_feedback_error(err_msg: &str) -> bool {
     .any(|needle| err_msg.contains(needle))
 }
 
-/// Lists feedback rows for a codelab.
+/// Lists feedback rows for a codelab, optionally for one cohort.
 pub async fn get_feedback(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
+    Query(query): Query<CohortQuery>,
     session: AuthSession,
 ) -> Result<Json<Vec<Feedback>>, (StatusCode, String)> {
-    session.require_admin()?;
-    let feedback = sqlx::query_as::<_, Feedback>(
-        &state.q("SELECT * FROM feedback WHERE codelab_id = ? ORDER BY created_at DESC"),
-    )
-    .bind(id)
-    .fetch_all(&state.pool)
-    .await
-    .map_err(internal_error)?;
+    session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    let scope = query
+        .cohort_id
+        .filter(|cohort_id| !cohort_id.is_empty())
+        .map_or(CohortScope::All, CohortScope::Cohort);
+    let sql = state.q(&format!(
+        "SELECT * FROM feedback WHERE codelab_id = ?{} ORDER BY created_at DESC",
+        scope.condition("cohort_id")
+    ));
+    let mut listing = sqlx::query_as::<_, Feedback>(&sql).bind(id);
+    if let Some(cohort_id) = scope.bind_value() {
+        listing = listing.bind(cohort_id);
+    }
+    let feedback = listing
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
 
     Ok(Json(feedback))
 }
@@ -1,9 +1,10 @@
+use crate::api::handlers::websocket::broadcast_to_codelab;
 use crate::domain::models::{
     CreateInlineCommentPayload, InlineCommentMessage, InlineCommentThread,
     InlineCommentThreadWithMessages, ReplyInlineCommentPayload,
 };
 use crate::infrastructure::database::AppState;
-use crate::middleware::auth::AuthSession;
+use crate::middleware::auth::{staff_can_access_codelab, AuthSession};
 use crate::utils::error::{bad_request, forbidden, internal_error};
 use axum::{
     extract::{Path, Query, State},
@@ -376,6 +377,12 @@ async fn authorize_codelab_actor(
     codelab_id: &str,
 ) -> Result<Actor, (StatusCode, String)> {
     if let Ok(admin) = session.require_admin() {
+        if !staff_can_access_codelab(state, &admin, codelab_id)
+            .await
+            .map_err(internal_error)?
+        {
+            return Err(forbidden());
+        }
         return Ok(Actor {
             role: "admin".to_string(),
             id: admin.sub,
@@ -517,15 +524,13 @@ fn broadcast_inline_comment_changed(
     target_type: &str,
     target_step_id: Option<&str>,
 ) {
-    if let Some(channel) = state.channels.get(codelab_id) {
-        let payload = json!({
-            "type": "inline_comment_changed",
-            "target_type": target_type,
-            "target_step_id": target_step_id
-        })
-        .to_string();
-        let _ = channel.send(payload);
-    }
+    let payload = json!({
+        "type": "inline_comment_changed",
+        "target_type": target_type,
+        "target_step_id": target_step_id
+    })
+    .to_string();
+    broadcast_to_codelab(state, codelab_id, &payload);
 }
 
 #[cfg(test)]
@@ -582,6 +587,9 @@ mod tests {
             sub: sub.to_string(),
             role: role.to_string(),
             codelab_id: codelab_id.map(|id| id.to_string()),
+            staff_role: None,
+            scopes: None,
+            jti: None,
             iss: "open-codelabs".to_string(),
             aud: "open-codelabs".to_string(),
             iat: now,
@@ -0,0 +1,332 @@
+use crate::domain::models::{
+    Codelab, CodelabInvite, CodelabJoinInfo, CodelabPasscodePayload, CreateCodelabInvitePayload,
+    CreatedCodelabInvite,
+};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::db_models::CodelabInviteRow;
+use crate::infrastructure::join_access::{
+    find_usable_invite, generate_invite_token, hash_invite_token, load_passcode, store_passcode,
+    INVITE_COLUMNS, INVITE_PREFIX_LEN,
+};
+use crate::middleware::auth::{now_epoch_seconds, AuthSession, Permission};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::{bad_request, forbidden, internal_error};
+use crate::utils::validation::validate_passcode;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const MAX_INVITE_LABEL_LEN: usize = 128;
+const MAX_INVITE_USES: u32 = 10_000;
+const MAX_INVITE_LIFETIME_HOURS: u32 = 24 * 365;
+
+/// Query string of the public join summary.
+#[derive(Debug, Deserialize)]
+pub struct JoinInfoQuery {
+    /// Invite token from an invite link.
+    pub invite: Option<String>,
+}
+
+impl From<CodelabInviteRow> for CodelabInvite {
+    fn from(row: CodelabInviteRow) -> Self {
+        Self {
+            id: row.id,
+            codelab_id: row.codelab_id,
+            label: row.label,
+            token_prefix: row.token_prefix,
+            max_uses: row.max_uses,
+            use_count: row.use_count,
+            expires_at: row.expires_at_epoch,
+            revoked_at: row.revoked_at_epoch,
+            created_by: row.created_by,
+            created_at: row.created_at,
+        }
+    }
+}
+
+async fn fetch_codelab(state: &AppState, id: &str) -> Result<Codelab, (StatusCode, String)> {
+    sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))
+}
+
+async fn audit_join_access(
+    state: &AppState,
+    action: &str,
+    actor: String,
+    target_id: String,
+    codelab_id: String,
+    info: RequestInfo,
+    metadata: Option<serde_json::Value>,
+) {
+    record_audit(
+        state,
+        AuditEntry {
+            action: action.to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(actor),
+            target_id: Some(target_id),
+            codelab_id: Some(codelab_id),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata,
+        },
+    )
+    .await;
+}
+
+/// Returns what the join page needs to know before registration.
+///
+/// Private codelabs are described only when they can be joined with a
+/// passcode or the supplied invite, or to their own staff and attendees.
+pub async fn get_join_info(
+    Path(id): Path<String>,
+    Query(query): Query<JoinInfoQuery>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<CodelabJoinInfo>, (StatusCode, String)> {
+    let codelab = fetch_codelab(&state, &id).await?;
+    let is_public = codelab.is_public != 0;
+    let passcode_required = !is_public
+        && load_passcode(&state, &id)
+            .await
+            .map_err(internal_error)?
+            .is_some();
+    let invite_valid = match query.invite.as_deref().filter(|token| !token.is_empty()) {
+        Some(token) => find_usable_invite(&state, &id, token)
+            .await
+            .map_err(internal_error)?
+            .is_some(),
+        None => false,
+    };
+
+    if !is_public && !passcode_required && !invite_valid {
+        let joined = session
+            .attendee_claims
+            .as_ref()
+            .is_some_and(|claims| claims.codelab_id.as_deref() == Some(id.as_str()));
+        if !joined && !session.is_codelab_staff(&state, &id).await? {
+            return Err(forbidden());
+        }
+    }
+
+    Ok(Json(CodelabJoinInfo {
+        id: codelab.id,
+        title: codelab.title,
+        description: codelab.description,
+        author: codelab.author,
+        is_public,
+        passcode_required,
+        invite_valid,
+    }))
+}
+
+/// Returns the join passcode of a codelab so staff can share it.
+pub async fn get_codelab_passcode(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<CodelabPasscodePayload>, (StatusCode, String)> {
+    session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    fetch_codelab(&state, &id).await?;
+    let passcode = load_passcode(&state, &id).await.map_err(internal_error)?;
+    Ok(Json(CodelabPasscodePayload { passcode }))
+}
+
+/// Sets or clears the join passcode of a codelab.
+pub async fn set_codelab_passcode(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<CodelabPasscodePayload>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    fetch_codelab(&state, &id).await?;
+    let passcode = payload
+        .passcode
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    if let Some(passcode) = passcode {
+        validate_passcode(passcode)?;
+    }
+    store_passcode(&state, &id, passcode)
+        .await
+        .map_err(internal_error)?;
+
+    audit_join_access(
+        &state,
+        "codelab_passcode_update",
+        admin.sub,
+        id.clone(),
+        id,
+        info,
+        Some(serde_json::json!({ "enabled": passcode.is_some() })),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists invite links of a codelab, newest first, without their tokens.
+pub async fn list_codelab_invites(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<Vec<CodelabInvite>>, (StatusCode, String)> {
+    session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    let rows = sqlx::query_as::<_, CodelabInviteRow>(&state.q(&format!(
+        "SELECT {INVITE_COLUMNS} FROM codelab_invites WHERE codelab_id = ? ORDER BY created_at DESC"
+    )))
+    .bind(&id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(rows.into_iter().map(CodelabInvite::from).collect()))
+}
+
+/// Creates an invite link. The token appears only in this response.
+pub async fn create_codelab_invite(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<CreateCodelabInvitePayload>,
+) -> Result<(StatusCode, Json<CreatedCodelabInvite>), (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    fetch_codelab(&state, &id).await?;
+
+    let label = payload
+        .label
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string);
+    if label
+        .as_ref()
+        .is_some_and(|label| label.chars().count() > MAX_INVITE_LABEL_LEN)
+    {
+        return Err(bad_request("label is too long"));
+    }
+    if payload
+        .max_uses
+        .is_some_and(|uses| !(1..=MAX_INVITE_USES).contains(&uses))
+    {
+        return Err(bad_request(
+            "max_uses must be between 1 and 10000 when provided",
+        ));
+    }
+    let expires_at = match payload.expires_in_hours {
+        None => None,
+        Some(hours) if (1..=MAX_INVITE_LIFETIME_HOURS).contains(&hours) => {
+            Some(now_epoch_seconds() as i64 + i64::from(hours) * 60 * 60)
+        }
+        Some(_) => {
+            return Err(bad_request(
+                "expires_in_hours must be between 1 and 8760 when provided",
+            ))
+        }
+    };
+
+    let invite_id = Uuid::new_v4().to_string();
+    let token = generate_invite_token();
+    let token_prefix: String = token.chars().take(INVITE_PREFIX_LEN).collect();
+    sqlx::query(&state.q(
+        "INSERT INTO codelab_invites (id, codelab_id, label, token_hash, token_prefix, max_uses, expires_at_epoch, created_by) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    ))
+    .bind(&invite_id)
+    .bind(&id)
+    .bind(&label)
+    .bind(hash_invite_token(&token))
+    .bind(&token_prefix)
+    .bind(payload.max_uses.map(|uses| uses as i32))
+    .bind(expires_at)
+    .bind(&admin.sub)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    audit_join_access(
+        &state,
+        "codelab_invite_create",
+        admin.sub,
+        invite_id.clone(),
+        id.clone(),
+        info,
+        Some(serde_json::json!({
+            "label": label,
+            "max_uses": payload.max_uses,
+            "expires_at": expires_at,
+        })),
+    )
+    .await;
+
+    let row = sqlx::query_as::<_, CodelabInviteRow>(&state.q(&format!(
+        "SELECT {INVITE_COLUMNS} FROM codelab_invites WHERE id = ?"
+    )))
+    .bind(&invite_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedCodelabInvite {
+            invite_path: format!("/codelabs/{id}/entry?invite={token}"),
+            token,
+            metadata: row.into(),
+        }),
+    ))
+}
+
+/// Revokes an invite link so it no longer admits attendees.
+pub async fn revoke_codelab_invite(
+    Path((id, invite_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    let updated = sqlx::query(&state.q(
+        "UPDATE codelab_invites SET revoked_at_epoch = ? WHERE id = ? AND codelab_id = ? AND revoked_at_epoch IS NULL",
+    ))
+    .bind(now_epoch_seconds() as i64)
+    .bind(&invite_id)
+    .bind(&id)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    if updated.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Invite not found".to_string()));
+    }
+
+    audit_join_access(
+        &state,
+        "codelab_invite_revoke",
+        admin.sub,
+        invite_id,
+        id,
+        info,
+        None,
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,7 +1,7 @@
 use crate::domain::models::{Codelab, CreateMaterial, Material};
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
-use crate::middleware::auth::AuthSession;
+use crate::middleware::auth::{AuthSession, Permission, StaffRole};
 use crate::middleware::request_info::RequestInfo;
 use crate::utils::error::{bad_request, forbidden, internal_error};
 use crate::utils::validation::validate_material;
@@ -29,7 +29,9 @@ pub async fn get_materials(
         .await
         .map_err(internal_error)?
         .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))?;
-    if !can_access_codelab(&codelab, &session) {
+    if !can_access_codelab(&codelab, &session)
+        && !session.is_codelab_staff(&state, &codelab_id).await?
+    {
         return Err(forbidden());
     }
 
@@ -52,7 +54,9 @@ pub async fn add_material(
     info: RequestInfo,
     Json(payload): Json<CreateMaterial>,
 ) -> Result<Json<Material>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
     validate_material(&payload)?;
     let id = Uuid::new_v4().to_string();
 
@@ -98,17 +102,20 @@ pub async fn add_material(
 /// Deletes a material entry from a codelab.
 pub async fn delete_material(
     State(state): State<Arc<AppState>>,
-    Path((_codelab_id, material_id)): Path<(String, String)>,
+    Path((codelab_id, material_id)): Path<(String, String)>,
     session: AuthSession,
     info: RequestInfo,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
     // 만약 파일이라면 물리적 파일도 삭제해야 할까요?
     // 우선 DB 레코드만 삭제하도록 구현하겠습니다.
     // 필요하다면 나중에 파일 삭제 로직을 추가할 수 있습니다.
 
-    sqlx::query(&state.q("DELETE FROM materials WHERE id = ?"))
+    sqlx::query(&state.q("DELETE FROM materials WHERE id = ? AND codelab_id = ?"))
         .bind(material_id)
+        .bind(&codelab_id)
         .execute(&state.pool)
         .await
         .map_err(internal_error)?;
@@ -120,7 +127,7 @@ pub async fn delete_material(
             actor_type: "admin".to_string(),
             actor_id: Some(admin.sub),
             target_id: None,
-            codelab_id: None,
+            codelab_id: Some(codelab_id),
             ip: Some(info.ip),
             user_agent: info.user_agent,
             metadata: None,
@@ -138,7 +145,7 @@ pub async fn upload_material_file(
     info: RequestInfo,
     mut multipart: Multipart,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session.require_permission(Permission::EditContent)?;
     if let Some(field) = multipart.next_field().await.map_err(internal_error)? {
         let filename = sanitize_filename(field.file_name().unwrap_or("file"));
         if filename.is_empty() {
@@ -199,7 +206,7 @@ pub async fn upload_material_file(
 
 fn can_access_codelab(codelab: &Codelab, session: &AuthSession) -> bool {
     match &session.claims {
-        Some(claims) if claims.role == "admin" => true,
+        Some(claims) if claims.staff_role() == Some(StaffRole::Owner) => true,
         Some(claims)
             if claims.role == "attendee"
                 && claims.codelab_id.as_deref() == Some(codelab.id.as_str()) =>
@@ -234,6 +241,9 @@ mod tests {
                 sub: "u1".to_string(),
                 role: role.to_string(),
                 codelab_id: codelab_id.map(|v| v.to_string()),
+                staff_role: None,
+                scopes: None,
+                jti: None,
                 iss: "test".to_string(),
                 aud: "test".to_string(),
                 iat: 0,
@@ -255,8 +265,18 @@ mod tests {
             require_quiz: 0,
             require_feedback: 0,
             require_submission: 0,
+            quiz_max_attempts: 0,
+            quiz_pass_percentage: 100,
+            max_attendees: 0,
+            registration_opens_at: None,
+            registration_closes_at: None,
             guide_markdown: None,
             created_at: None,
+            published_revision_id: None,
+            published_at: None,
+            version: 1,
+            steps_version: 1,
+            categories: None,
         }
     }
 
@@ -0,0 +1,231 @@
+use crate::api::dto::{
+    MfaCodeRequest, MfaStatusResponse, RecoveryCodesResponse, TotpEnrollmentResponse,
+};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::mfa::{
+    begin_totp_enrollment, confirm_totp_enrollment, load_totp, remaining_recovery_codes,
+    remove_totp, replace_recovery_codes, verify_second_factor,
+};
+use crate::infrastructure::sessions::{revoke_sessions, SessionFilter};
+use crate::middleware::auth::{AuthSession, Permission};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::{bad_request, forbidden, internal_error};
+use crate::utils::totp::{generate_totp_secret, provisioning_uri};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+fn invalid_code() -> (StatusCode, String) {
+    (
+        StatusCode::UNAUTHORIZED,
+        "Invalid two-factor code".to_string(),
+    )
+}
+
+async fn audit_mfa(
+    state: &AppState,
+    action: &str,
+    actor: &str,
+    target: &str,
+    info: RequestInfo,
+    metadata: Option<serde_json::Value>,
+) {
+    record_audit(
+        state,
+        AuditEntry {
+            action: action.to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(actor.to_string()),
+            target_id: Some(target.to_string()),
+            codelab_id: None,
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata,
+        },
+    )
+    .await;
+}
+
+/// Reports whether the signed-in staff member has two-factor sign-in enabled.
+pub async fn get_mfa_status(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<MfaStatusResponse>, (StatusCode, String)> {
+    let admin = session.require_admin()?;
+    let row = load_totp(&state, &admin.sub)
+        .await
+        .map_err(internal_error)?;
+    let enabled = row
+        .as_ref()
+        .is_some_and(|row| row.enabled_at_epoch.is_some());
+    let recovery_codes_remaining = if enabled {
+        remaining_recovery_codes(&state, &admin.sub)
+            .await
+            .map_err(internal_error)?
+    } else {
+        0
+    };
+    Ok(Json(MfaStatusResponse {
+        enabled,
+        pending: row.is_some() && !enabled,
+        recovery_codes_remaining,
+    }))
+}
+
+/// Starts TOTP enrollment and returns the secret to load into an authenticator app.
+///
+/// Calling it again before confirmation replaces the pending secret.
+pub async fn start_totp_enrollment(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<TotpEnrollmentResponse>, (StatusCode, String)> {
+    let admin = session.require_interactive_admin()?;
+    let existing = load_totp(&state, &admin.sub)
+        .await
+        .map_err(internal_error)?;
+    if existing.is_some_and(|row| row.enabled_at_epoch.is_some()) {
+        return Err((
+            StatusCode::CONFLICT,
+            "Two-factor authentication is already enabled".to_string(),
+        ));
+    }
+
+    let account = if admin.sub == state.admin_id {
+        state.admin_id.clone()
+    } else {
+        let user: Option<(String, Option<String>)> =
+            sqlx::query_as(&state.q("SELECT username, oidc_subject FROM users WHERE id = ?"))
+                .bind(&admin.sub)
+                .fetch_optional(&state.pool)
+                .await
+                .map_err(internal_error)?;
+        match user {
+            Some((_, Some(_))) => {
+                return Err(bad_request(
+                    "Single sign-on accounts use the identity provider's second factor",
+                ))
+            }
+            Some((username, None)) => username,
+            None => return Err(forbidden()),
+        }
+    };
+
+    let secret = begin_totp_enrollment(&state, &admin.sub, &generate_totp_secret())
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(TotpEnrollmentResponse {
+        provisioning_uri: provisioning_uri(&state.credential_issuer.name, &account, &secret),
+        secret,
+    }))
+}
+
+/// Confirms enrollment with a first authenticator code and issues recovery codes.
+pub async fn confirm_totp(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<MfaCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, (StatusCode, String)> {
+    let admin = session.require_interactive_admin()?;
+    let confirmed = confirm_totp_enrollment(&state, &admin.sub, &payload.code)
+        .await
+        .map_err(internal_error)?;
+    if !confirmed {
+        return Err(invalid_code());
+    }
+    let recovery_codes = replace_recovery_codes(&state, &admin.sub)
+        .await
+        .map_err(internal_error)?;
+    audit_mfa(&state, "mfa_enable", &admin.sub, &admin.sub, info, None).await;
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// Turns off two-factor sign-in after checking a current code.
+pub async fn disable_totp(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<MfaCodeRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session.require_interactive_admin()?;
+    let factor = verify_second_factor(&state, &admin.sub, &payload.code)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(invalid_code)?;
+    remove_totp(&state, &admin.sub)
+        .await
+        .map_err(internal_error)?;
+    audit_mfa(
+        &state,
+        "mfa_disable",
+        &admin.sub,
+        &admin.sub,
+        info,
+        Some(serde_json::json!({ "factor": factor.as_str() })),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Replaces the recovery codes after checking a current code.
+pub async fn regenerate_recovery_codes(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<MfaCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, (StatusCode, String)> {
+    let admin = session.require_interactive_admin()?;
+    let factor = verify_second_factor(&state, &admin.sub, &payload.code)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(invalid_code)?;
+    let recovery_codes = replace_recovery_codes(&state, &admin.sub)
+        .await
+        .map_err(internal_error)?;
+    audit_mfa(
+        &state,
+        "mfa_recovery_codes_regenerate",
+        &admin.sub,
+        &admin.sub,
+        info,
+        Some(serde_json::json!({ "factor": factor.as_str() })),
+    )
+    .await;
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// Clears another staff member's two-factor enrollment so they can sign in
+/// with a password again. Owner only; the user's sessions are revoked.
+pub async fn reset_user_mfa(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageUsers)?;
+    if admin.is_api_token() {
+        return Err(forbidden());
+    }
+    let removed = remove_totp(&state, &user_id)
+        .await
+        .map_err(internal_error)?;
+    if !removed {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Two-factor authentication is not enrolled".to_string(),
+        ));
+    }
+    let filter = SessionFilter {
+        subject: Some(user_id.clone()),
+        codelab_id: None,
+    };
+    revoke_sessions(&state, &filter, &admin.sub)
+        .await
+        .map_err(internal_error)?;
+    audit_mfa(&state, "mfa_reset", &admin.sub, &user_id, info, None).await;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -4,29 +4,61 @@
 pub mod admin;
 /// AI proxying, threaded chat, and conversation persistence.
 pub mod ai;
-/// Attendee registration, progress, help, and certificates.
+/// Personal API token management for automation.
+pub mod api_tokens;
+/// Attendee registration, progress, help, and completion.
 pub mod attendees;
 /// Audit log listing endpoints.
 pub mod audit;
+/// Shared step editing over a staff-only websocket.
+pub mod authoring;
 /// Backup export, restore, and inspection handlers.
 pub mod backup;
+/// Signed certificate issuance, verification, and revocation.
+pub mod certificates;
 /// CLI runtime discovery and browser-auth support handlers.
 pub mod cli;
 /// Codelab CRUD, import/export, and chat history handlers.
 pub mod codelabs;
 /// Workspace and code-server orchestration handlers.
 pub mod codeserver;
+/// Cohorts: scheduled runs of a codelab with isolated live data.
+pub mod cohorts;
 /// Learner feedback submission and listing handlers.
 pub mod feedback;
 /// Inline comment thread handlers for guide and step content.
 pub mod inline_comments;
+/// Codelab join passcodes and invite links.
+pub mod join_access;
 /// Codelab material management handlers.
 pub mod materials;
+/// TOTP enrollment, recovery codes, and owner resets.
+pub mod mfa;
+/// OpenID Connect single sign-on for staff.
+pub mod oidc;
+/// Attendee data export and erasure for privacy requests.
+pub mod privacy;
+/// Draft and published versions of codelab content.
+pub mod publishing;
 /// Quiz listing, updates, submissions, and results handlers.
 pub mod quizzes;
+/// Data retention reports and on-demand pruning.
+pub mod retention;
+/// Codelab revision history, diffs, and rollback.
+pub mod revisions;
+/// Bulk roster imports that pre-register attendees.
+pub mod roster;
+/// Active session listing and revocation handlers.
+pub mod sessions;
+/// Single-step insert, edit, delete, and move handlers.
+pub mod steps;
 /// Learner submission upload and management handlers.
 pub mod submissions;
 /// Standalone asset upload handlers.
 pub mod upload;
+/// Staff account management handlers.
+pub mod users;
+/// Attendee waitlist listing, promotion, and removal.
+pub mod waitlist;
 /// Websocket connection and live messaging handlers.
 pub mod websocket;
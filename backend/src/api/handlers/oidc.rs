@@ -0,0 +1,442 @@
+use crate::api::dto::{OidcCallbackQuery, OidcLoginQuery, OidcStatusResponse};
+use crate::api::handlers::admin::{issue_admin_session, StaffIdentity};
+use crate::api::handlers::certificates::public_origin;
+use crate::domain::services::oidc::{self, OidcIdentity};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::config::OidcConfig;
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::sessions::{revoke_sessions, SessionFilter};
+use crate::middleware::auth::{clear_cookie, now_epoch_seconds, StaffRole};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::{bad_request, forbidden, internal_error, unauthorized};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Redirect,
+    Json,
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+const OIDC_LOGIN_TTL_SECONDS: i64 = 10 * 60;
+const OIDC_CALLBACK_PATH: &str = "/api/auth/oidc/callback";
+const DEFAULT_RETURN_TO: &str = "/admin";
+const MAX_USERNAME_LEN: usize = 64;
+
+struct OidcLoginRequestRow {
+    nonce: String,
+    code_verifier: String,
+    redirect_uri: String,
+    return_to: String,
+    expires_at_epoch: i64,
+}
+
+/// Reports whether single sign-on is configured so the login page can offer it.
+pub async fn get_oidc_status(State(state): State<Arc<AppState>>) -> Json<OidcStatusResponse> {
+    Json(OidcStatusResponse {
+        enabled: state.oidc.is_some(),
+        provider_name: state.oidc.as_ref().map(|cfg| cfg.provider_name.clone()),
+    })
+}
+
+/// Starts an authorization-code login and redirects the browser to the identity provider.
+pub async fn start_oidc_login(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    Query(query): Query<OidcLoginQuery>,
+) -> Result<(CookieJar, Redirect), (StatusCode, String)> {
+    let config = oidc_config(&state)?;
+    let return_to = sanitize_return_to(query.return_to.as_deref());
+    let redirect_uri = config
+        .redirect_url
+        .clone()
+        .unwrap_or_else(|| format!("{}{OIDC_CALLBACK_PATH}", public_origin(&state, &headers)));
+
+    let discovery = oidc::discover(config).await.map_err(provider_error)?;
+    let login_state = oidc::random_token(43);
+    let nonce = oidc::random_token(43);
+    let code_verifier = oidc::generate_code_verifier();
+    let authorize_url = oidc::authorization_url(
+        config,
+        &discovery,
+        &redirect_uri,
+        &login_state,
+        &nonce,
+        &code_verifier,
+    )
+    .map_err(provider_error)?;
+
+    cleanup_oidc_login_requests(&state)
+        .await
+        .map_err(internal_error)?;
+    let now = now_epoch_seconds() as i64;
+    sqlx::query(&state.q(
+        "INSERT INTO oidc_login_requests (id, nonce, code_verifier, redirect_uri, return_to, created_at_epoch, expires_at_epoch)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    ))
+    .bind(hash_state(&login_state))
+    .bind(&nonce)
+    .bind(&code_verifier)
+    .bind(&redirect_uri)
+    .bind(&return_to)
+    .bind(now)
+    .bind(now + OIDC_LOGIN_TTL_SECONDS)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let jar = jar.add(build_state_cookie(&state, login_state));
+    Ok((jar, Redirect::to(&authorize_url)))
+}
+
+/// Completes the authorization-code flow and issues the staff session cookie.
+///
+/// The account is matched on the ID token `sub`, provisioned on first login,
+/// and its role is refreshed from the provider's groups on every login.
+pub async fn complete_oidc_login(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+    info: RequestInfo,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Result<(CookieJar, Redirect), (StatusCode, String)> {
+    let config = oidc_config(&state)?;
+    if let Some(error) = query.error.as_deref() {
+        tracing::warn!("OIDC provider returned error: {}", error);
+        return Err(unauthorized());
+    }
+    let (Some(code), Some(login_state)) = (query.code.as_deref(), query.state.as_deref()) else {
+        return Err(bad_request("code and state are required"));
+    };
+
+    let cookie_name = state_cookie_name(&state);
+    let state_matches = jar
+        .get(&cookie_name)
+        .map(|cookie| bool::from(cookie.value().as_bytes().ct_eq(login_state.as_bytes())))
+        .unwrap_or(false);
+    if !state_matches {
+        return Err(bad_request("Single sign-on state mismatch"));
+    }
+    let jar = jar.remove(clear_cookie(&cookie_name));
+
+    let request = consume_oidc_login_request(&state, &hash_state(login_state))
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| bad_request("Unknown or already used single sign-on request"))?;
+    if request.expires_at_epoch <= now_epoch_seconds() as i64 {
+        return Err(bad_request("Single sign-on request expired"));
+    }
+
+    let discovery = oidc::discover(config).await.map_err(provider_error)?;
+    let id_token = oidc::exchange_code(
+        config,
+        &discovery,
+        code,
+        &request.redirect_uri,
+        &request.code_verifier,
+    )
+    .await
+    .map_err(provider_error)?;
+    let jwks = oidc::fetch_jwks(&discovery).await.map_err(provider_error)?;
+    let identity = oidc::validate_id_token(config, &discovery, &jwks, &id_token, &request.nonce)
+        .map_err(|err| {
+            tracing::warn!("OIDC id_token rejected: {}", err);
+            unauthorized()
+        })?;
+
+    let Some(role) = oidc::map_role(config, &identity.groups) else {
+        // Losing every mapped group also ends sessions from earlier logins.
+        let existing: Option<String> =
+            sqlx::query_scalar(&state.q("SELECT id FROM users WHERE oidc_subject = ?"))
+                .bind(&identity.subject)
+                .fetch_optional(&state.pool)
+                .await
+                .map_err(internal_error)?;
+        let sessions_revoked = match &existing {
+            Some(user_id) => revoke_user_sessions(&state, user_id)
+                .await
+                .map_err(internal_error)?,
+            None => 0,
+        };
+        record_audit(
+            &state,
+            AuditEntry {
+                action: "admin_login_failed".to_string(),
+                actor_type: "admin".to_string(),
+                actor_id: Some(identity.subject),
+                target_id: None,
+                codelab_id: None,
+                ip: Some(info.ip),
+                user_agent: info.user_agent,
+                metadata: Some(serde_json::json!({
+                    "method": "oidc",
+                    "reason": "no_role",
+                    "sessions_revoked": sessions_revoked,
+                })),
+            },
+        )
+        .await;
+        return Err(forbidden());
+    };
+
+    let (user_id, sessions_revoked) = provision_oidc_user(&state, &identity, role)
+        .await
+        .map_err(internal_error)?;
+    let staff = StaffIdentity { sub: user_id, role };
+    let (jar, _claims, _token) = issue_admin_session(&state, jar, &staff, &info).await?;
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "admin_login_success".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(staff.sub),
+            target_id: None,
+            codelab_id: None,
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({
+                "role": role.as_str(),
+                "method": "oidc",
+                "sessions_revoked": sessions_revoked,
+            })),
+        },
+    )
+    .await;
+
+    Ok((jar, Redirect::to(&request.return_to)))
+}
+
+fn oidc_config(state: &AppState) -> Result<&OidcConfig, (StatusCode, String)> {
+    state.oidc.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            "Single sign-on is not configured".to_string(),
+        )
+    })
+}
+
+fn provider_error(error: String) -> (StatusCode, String) {
+    tracing::warn!("OIDC provider error: {}", error);
+    (
+        StatusCode::BAD_GATEWAY,
+        "Identity provider request failed".to_string(),
+    )
+}
+
+/// Accepts only same-origin absolute paths so the callback cannot become an open redirect.
+fn sanitize_return_to(value: Option<&str>) -> String {
+    match value.map(str::trim) {
+        Some(path)
+            if path.starts_with('/')
+                && !path.starts_with("//")
+                && !path.contains('\\')
+                && !path.chars().any(char::is_control) =>
+        {
+            path.to_string()
+        }
+        _ => DEFAULT_RETURN_TO.to_string(),
+    }
+}
+
+fn state_cookie_name(state: &AppState) -> String {
+    let prefix = if state.auth.cookie_secure {
+        "__Host-"
+    } else {
+        ""
+    };
+    format!("{prefix}oc_oidc_state")
+}
+
+/// Binds the login attempt to the browser that started it.
+///
+/// `SameSite=Lax` is required because the callback arrives as a top-level
+/// navigation from the identity provider's origin.
+fn build_state_cookie(state: &AppState, value: String) -> Cookie<'static> {
+    Cookie::build((state_cookie_name(state), value))
+        .path("/")
+        .http_only(true)
+        .secure(state.auth.cookie_secure)
+        .same_site(SameSite::Lax)
+        .max_age(cookie::time::Duration::seconds(OIDC_LOGIN_TTL_SECONDS))
+        .build()
+}
+
+fn hash_state(value: &str) -> String {
+    format!("{:x}", Sha256::digest(value.as_bytes()))
+}
+
+async fn cleanup_oidc_login_requests(state: &AppState) -> Result<(), sqlx::Error> {
+    sqlx::query(&state.q("DELETE FROM oidc_login_requests WHERE expires_at_epoch <= ?"))
+        .bind(now_epoch_seconds() as i64)
+        .execute(&state.pool)
+        .await?;
+    Ok(())
+}
+
+/// Loads and deletes a pending login so each `state` value is accepted once.
+async fn consume_oidc_login_request(
+    state: &AppState,
+    id: &str,
+) -> Result<Option<OidcLoginRequestRow>, sqlx::Error> {
+    let row = sqlx::query_as::<_, (String, String, String, String, i64)>(&state.q(
+        "SELECT nonce, code_verifier, redirect_uri, return_to, expires_at_epoch
+         FROM oidc_login_requests
+         WHERE id = ?",
+    ))
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await?;
+    let Some((nonce, code_verifier, redirect_uri, return_to, expires_at_epoch)) = row else {
+        return Ok(None);
+    };
+
+    let deleted = sqlx::query(&state.q("DELETE FROM oidc_login_requests WHERE id = ?"))
+        .bind(id)
+        .execute(&state.pool)
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(OidcLoginRequestRow {
+        nonce,
+        code_verifier,
+        redirect_uri,
+        return_to,
+        expires_at_epoch,
+    }))
+}
+
+/// Revokes every session of an SSO account whose groups no longer grant the
+/// role its sessions were issued with.
+async fn revoke_user_sessions(state: &AppState, user_id: &str) -> Result<u64, sqlx::Error> {
+    let filter = SessionFilter {
+        subject: Some(user_id.to_string()),
+        codelab_id: None,
+    };
+    revoke_sessions(state, &filter, user_id).await
+}
+
+/// Finds or creates the `users` row for an SSO identity and refreshes its role.
+///
+/// Provisioned accounts get an empty password hash, so they can only sign in
+/// through the identity provider. When the mapped role changed, sessions from
+/// earlier logins are revoked; returns the user id and how many were.
+async fn provision_oidc_user(
+    state: &AppState,
+    identity: &OidcIdentity,
+    role: StaffRole,
+) -> Result<(String, u64), sqlx::Error> {
+    let existing: Option<(String, String)> =
+        sqlx::query_as(&state.q("SELECT id, role FROM users WHERE oidc_subject = ?"))
+            .bind(&identity.subject)
+            .fetch_optional(&state.pool)
+            .await?;
+
+    if let Some((id, stored_role)) = existing {
+        sqlx::query(&state.q("UPDATE users
+             SET role = ?, display_name = COALESCE(?, display_name),
+                 last_login_at = CAST(CURRENT_TIMESTAMP AS TEXT),
+                 updated_at = CAST(CURRENT_TIMESTAMP AS TEXT)
+             WHERE id = ?"))
+        .bind(role.as_str())
+        .bind(&identity.name)
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+        let sessions_revoked = if stored_role != role.as_str() {
+            revoke_user_sessions(state, &id).await?
+        } else {
+            0
+        };
+        return Ok((id, sessions_revoked));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let username = available_username(state, identity).await?;
+    sqlx::query(&state.q(
+        "INSERT INTO users (id, username, display_name, password_hash, role, oidc_subject, last_login_at)
+         VALUES (?, ?, ?, '', ?, ?, CAST(CURRENT_TIMESTAMP AS TEXT))",
+    ))
+    .bind(&id)
+    .bind(&username)
+    .bind(&identity.name)
+    .bind(role.as_str())
+    .bind(&identity.subject)
+    .execute(&state.pool)
+    .await?;
+    Ok((id, 0))
+}
+
+/// Picks a unique username from `preferred_username`, `email`, or the subject.
+async fn available_username(
+    state: &AppState,
+    identity: &OidcIdentity,
+) -> Result<String, sqlx::Error> {
+    let base = identity
+        .preferred_username
+        .clone()
+        .or_else(|| identity.email.clone())
+        .unwrap_or_else(|| format!("oidc-{}", identity.subject));
+    let base: String = base
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() || c.is_control() {
+                '-'
+            } else {
+                c
+            }
+        })
+        .take(MAX_USERNAME_LEN)
+        .collect();
+    let suffix = &hash_state(&identity.subject)[..8];
+    let fallback = format!(
+        "{}-{suffix}",
+        base.chars()
+            .take(MAX_USERNAME_LEN - suffix.len() - 1)
+            .collect::<String>()
+    );
+
+    for candidate in [base, fallback] {
+        if candidate == state.admin_id {
+            continue;
+        }
+        let taken: Option<(String,)> =
+            sqlx::query_as(&state.q("SELECT id FROM users WHERE username = ?"))
+                .bind(&candidate)
+                .fetch_optional(&state.pool)
+                .await?;
+        if taken.is_none() {
+            return Ok(candidate);
+        }
+    }
+    Ok(format!("oidc-{}", Uuid::new_v4().simple()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_return_to_rejects_external_targets() {
+        assert_eq!(sanitize_return_to(Some("/admin/abc")), "/admin/abc");
+        assert_eq!(sanitize_return_to(None), DEFAULT_RETURN_TO);
+        assert_eq!(
+            sanitize_return_to(Some("https://evil.example")),
+            DEFAULT_RETURN_TO
+        );
+        assert_eq!(
+            sanitize_return_to(Some("//evil.example")),
+            DEFAULT_RETURN_TO
+        );
+        assert_eq!(
+            sanitize_return_to(Some("/\\evil.example")),
+            DEFAULT_RETURN_TO
+        );
+        assert_eq!(sanitize_return_to(Some("/a\r\nb")), DEFAULT_RETURN_TO);
+    }
+}
@@ -0,0 +1,444 @@
+use crate::domain::models::{
+    AiConversation, AiMessage, AiThread, Attendee, AttendeeErasure, AttendeePublic, Certificate,
+    ChatMessageRow, Feedback, HelpRequest, InlineCommentMessage, InlineCommentThread, QuizAttempt,
+    QuizSubmission, Submission,
+};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::db_models::{AuditLog, AuthSessionRow};
+use crate::infrastructure::retention::upload_disk_path;
+use crate::infrastructure::sessions::SESSION_COLUMNS;
+use crate::middleware::auth::{now_epoch_seconds, AuthSession, Permission};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::crypto::encrypt_with_password;
+use crate::utils::error::{bad_request, internal_error};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Write};
+use std::path::Path as StdPath;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Matches an attendee's chat rows by codelab, then sender or DM target.
+///
+/// Public messages stored before `sender_id` was recorded for them only carry
+/// the sender's name, which is unique within a codelab. Binds the codelab id,
+/// the attendee id twice and the attendee name.
+const ATTENDEE_CHAT_CLAUSE: &str = "codelab_id = ? AND (sender_id = ? OR target_id = ? OR (msg_type = 'chat' AND sender_id IS NULL AND sender_name = ?))";
+
+/// Query string of the attendee lookup by email address.
+#[derive(Debug, Deserialize)]
+pub struct AttendeeLookupQuery {
+    /// Email address given at registration, matched case-insensitively.
+    pub email: String,
+}
+
+/// Everything stored about one attendee, written as `attendee.json`.
+#[derive(Debug, Serialize)]
+struct AttendeeDataExport {
+    exported_at: String,
+    attendee: AttendeePublic,
+    erased_at: Option<String>,
+    help_requests: Vec<HelpRequest>,
+    chat_messages: Vec<ChatMessageRow>,
+    feedback: Vec<Feedback>,
+    quiz_attempts: Vec<QuizAttempt>,
+    quiz_submissions: Vec<QuizSubmission>,
+    submissions: Vec<Submission>,
+    ai_conversations: Vec<AiConversation>,
+    ai_threads: Vec<AiThread>,
+    ai_messages: Vec<AiMessage>,
+    inline_comment_threads: Vec<InlineCommentThread>,
+    inline_comment_messages: Vec<InlineCommentMessage>,
+    certificates: Vec<Certificate>,
+    sessions: Vec<AuthSessionRow>,
+    audit_logs: Vec<AuditLog>,
+}
+
+async fn fetch_attendee(state: &AppState, id: &str) -> Result<Attendee, (StatusCode, String)> {
+    sqlx::query_as::<_, Attendee>(&state.q("SELECT * FROM attendees WHERE id = ?"))
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Attendee not found".to_string()))
+}
+
+async fn audit_privacy(
+    state: &AppState,
+    action: &str,
+    actor: String,
+    attendee: &Attendee,
+    info: RequestInfo,
+) {
+    record_audit(
+        state,
+        AuditEntry {
+            action: action.to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(actor),
+            target_id: Some(attendee.id.clone()),
+            codelab_id: Some(attendee.codelab_id.clone()),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: None,
+        },
+    )
+    .await;
+}
+
+/// Finds attendee registrations across codelabs by email address.
+pub async fn find_attendees(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    Query(query): Query<AttendeeLookupQuery>,
+) -> Result<Json<Vec<AttendeePublic>>, (StatusCode, String)> {
+    session.require_permission(Permission::ManageSystem)?;
+    let email = query.email.trim();
+    if email.is_empty() {
+        return Err(bad_request("email is required"));
+    }
+
+    let attendees = sqlx::query_as::<_, Attendee>(
+        &state.q("SELECT * FROM attendees WHERE LOWER(email) = LOWER(?) ORDER BY created_at"),
+    )
+    .bind(email)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(attendees.into_iter().map(Into::into).collect()))
+}
+
+/// Exports everything stored about an attendee as a ZIP archive.
+///
+/// The archive holds `attendee.json` with the attendee's rows from every table
+/// and their uploaded submission files under `submissions/`.
+pub async fn export_attendee_data(
+    Path(attendee_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageSystem)?;
+    let attendee = fetch_attendee(&state, &attendee_id).await?;
+
+    let help_requests = sqlx::query_as::<_, HelpRequest>(&state.q(
+        "SELECT hr.id, hr.codelab_id, hr.attendee_id, COALESCE(a.name, '') AS attendee_name, hr.step_number, hr.status, hr.cohort_id, hr.created_at FROM help_requests hr LEFT JOIN attendees a ON a.id = hr.attendee_id WHERE hr.attendee_id = ?",
+    ))
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let chat_messages = sqlx::query_as::<_, ChatMessageRow>(&state.q(&format!(
+        "SELECT * FROM chat_messages WHERE {ATTENDEE_CHAT_CLAUSE} ORDER BY created_at"
+    )))
+    .bind(&attendee.codelab_id)
+    .bind(&attendee_id)
+    .bind(&attendee_id)
+    .bind(&attendee.name)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let feedback =
+        sqlx::query_as::<_, Feedback>(&state.q("SELECT * FROM feedback WHERE attendee_id = ?"))
+            .bind(&attendee_id)
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let quiz_attempts = sqlx::query_as::<_, QuizAttempt>(
+        &state.q("SELECT * FROM quiz_attempts WHERE attendee_id = ? ORDER BY attempt_number"),
+    )
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let quiz_submissions = sqlx::query_as::<_, QuizSubmission>(
+        &state.q("SELECT * FROM quiz_submissions WHERE attendee_id = ?"),
+    )
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let submissions = sqlx::query_as::<_, Submission>(&state.q(
+        "SELECT id, codelab_id, attendee_id, file_path, file_name, file_size, submission_type, link_url, CAST(created_at AS TEXT) AS created_at FROM submissions WHERE attendee_id = ?",
+    ))
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let ai_conversations = sqlx::query_as::<_, AiConversation>(&state.q(
+        "SELECT id, codelab_id, user_id, user_type, user_name, step_number, question, answer, model, usage_metadata, CAST(created_at AS TEXT) AS created_at FROM ai_conversations WHERE user_id = ? AND user_type = 'attendee'",
+    ))
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let ai_threads = sqlx::query_as::<_, AiThread>(&state.q(
+        "SELECT id, title, user_id, user_type, codelab_id, CAST(created_at AS TEXT) AS created_at, CAST(updated_at AS TEXT) AS updated_at FROM ai_threads WHERE user_id = ? AND user_type = 'attendee'",
+    ))
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let ai_messages = sqlx::query_as::<_, AiMessage>(&state.q(
+        "SELECT id, thread_id, role, content, grounding_metadata, usage_metadata, CAST(created_at AS TEXT) AS created_at FROM ai_messages WHERE thread_id IN (SELECT id FROM ai_threads WHERE user_id = ? AND user_type = 'attendee')",
+    ))
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let inline_comment_threads = sqlx::query_as::<_, InlineCommentThread>(&state.q(
+        "SELECT id, codelab_id, anchor_key, target_type, target_step_id, start_offset, end_offset, selected_text, content_hash, created_by_attendee_id, CAST(created_at AS TEXT) AS created_at FROM inline_comment_threads WHERE created_by_attendee_id = ?",
+    ))
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let inline_comment_messages = sqlx::query_as::<_, InlineCommentMessage>(&state.q(
+        "SELECT id, thread_id, codelab_id, author_role, author_id, author_name, message, CAST(created_at AS TEXT) AS created_at FROM inline_comment_messages WHERE author_role = 'attendee' AND author_id = ?",
+    ))
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let certificates = sqlx::query_as::<_, Certificate>(
+        &state.q("SELECT * FROM certificates WHERE attendee_id = ?"),
+    )
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let sessions = sqlx::query_as::<_, AuthSessionRow>(&state.q(&format!(
+        "SELECT {SESSION_COLUMNS} FROM auth_sessions WHERE subject = ? AND role = 'attendee'"
+    )))
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let audit_logs = sqlx::query_as::<_, AuditLog>(
+        &state
+            .q("SELECT * FROM audit_logs WHERE actor_id = ? OR target_id = ? ORDER BY created_at"),
+    )
+    .bind(&attendee_id)
+    .bind(&attendee_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let mut buf = Vec::new();
+    let mut zip = zip::ZipWriter::new(Cursor::new(&mut buf));
+    let options =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for submission in &submissions {
+        if submission.submission_type != "file" {
+            continue;
+        }
+        let Some(disk_path) = upload_disk_path(&submission.file_path) else {
+            continue;
+        };
+        let Ok(bytes) = tokio::fs::read(&disk_path).await else {
+            continue;
+        };
+        let file_name = StdPath::new(&submission.file_name)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("file");
+        zip.start_file(
+            format!("submissions/{}_{file_name}", submission.id),
+            options,
+        )
+        .map_err(internal_error)?;
+        zip.write_all(&bytes).map_err(internal_error)?;
+    }
+
+    let export = AttendeeDataExport {
+        exported_at: Utc::now().to_rfc3339(),
+        erased_at: attendee.erased_at.clone(),
+        attendee: attendee.clone().into(),
+        help_requests,
+        chat_messages,
+        feedback,
+        quiz_attempts,
+        quiz_submissions,
+        submissions,
+        ai_conversations,
+        ai_threads,
+        ai_messages,
+        inline_comment_threads,
+        inline_comment_messages,
+        certificates,
+        sessions,
+        audit_logs,
+    };
+    zip.start_file("attendee.json", options)
+        .map_err(internal_error)?;
+    let document = serde_json::to_string_pretty(&export).map_err(internal_error)?;
+    zip.write_all(document.as_bytes()).map_err(internal_error)?;
+    zip.finish().map_err(internal_error)?;
+
+    audit_privacy(&state, "attendee_data_export", admin.sub, &attendee, info).await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/zip"),
+    );
+    let disposition = format!("attachment; filename=\"attendee_{attendee_id}.zip\"");
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        header::HeaderValue::from_str(&disposition).map_err(internal_error)?,
+    );
+    Ok((headers, buf))
+}
+
+/// Erases an attendee's personal data while keeping aggregate statistics.
+///
+/// Free text, uploads, AI history, certificates, and chat are deleted or
+/// cleared; the attendee row stays under a pseudonym with its progress,
+/// ratings, and quiz scores so codelab and cohort numbers do not change.
+pub async fn erase_attendee_data(
+    Path(attendee_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<Json<AttendeeErasure>, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageSystem)?;
+    let attendee = fetch_attendee(&state, &attendee_id).await?;
+    if attendee.erased_at.is_some() {
+        return Err((
+            StatusCode::CONFLICT,
+            "Attendee data was already erased".to_string(),
+        ));
+    }
+
+    let pseudonym = format!(
+        "Erased attendee {}",
+        attendee_id.chars().take(8).collect::<String>()
+    );
+    // A random code nobody knows keeps the pseudonymized row from being used to sign in.
+    let locked_code = encrypt_with_password(&Uuid::new_v4().to_string(), &state.admin_pw)
+        .map_err(internal_error)?;
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let files: Vec<(String,)> =
+        sqlx::query_as(&state.q(
+            "SELECT file_path FROM submissions WHERE attendee_id = ? AND submission_type = 'file'",
+        ))
+        .bind(&attendee_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    macro_rules! execute {
+        ($sql:expr $(, $value:expr)*) => {
+            sqlx::query(&state.q($sql))
+                $(.bind($value))*
+                .execute(&mut *tx)
+                .await
+                .map_err(internal_error)?
+                .rows_affected()
+        };
+    }
+
+    let chat_messages_deleted = execute!(
+        &format!("DELETE FROM chat_messages WHERE {ATTENDEE_CHAT_CLAUSE}"),
+        &attendee.codelab_id,
+        &attendee_id,
+        &attendee_id,
+        &attendee.name
+    );
+    let waitlist_entries_deleted = execute!(
+        "DELETE FROM codelab_waitlist WHERE codelab_id = ? AND name = ?",
+        &attendee.codelab_id,
+        &attendee.name
+    );
+    let feedback_redacted = execute!(
+        "UPDATE feedback SET comment = NULL WHERE attendee_id = ?",
+        &attendee_id
+    );
+    let quiz_answers_redacted = execute!(
+        "UPDATE quiz_submissions SET answer = '' WHERE attendee_id = ?",
+        &attendee_id
+    );
+    let submissions_deleted = execute!(
+        "DELETE FROM submissions WHERE attendee_id = ?",
+        &attendee_id
+    );
+    execute!(
+        "DELETE FROM ai_messages WHERE thread_id IN (SELECT id FROM ai_threads WHERE user_id = ? AND user_type = 'attendee')",
+        &attendee_id
+    );
+    let ai_threads_deleted = execute!(
+        "DELETE FROM ai_threads WHERE user_id = ? AND user_type = 'attendee'",
+        &attendee_id
+    );
+    let ai_conversations_deleted = execute!(
+        "DELETE FROM ai_conversations WHERE user_id = ? AND user_type = 'attendee'",
+        &attendee_id
+    );
+    let inline_comments_deleted = execute!(
+        "DELETE FROM inline_comment_messages WHERE author_role = 'attendee' AND author_id = ?",
+        &attendee_id
+    );
+    // Threads left without messages are removed, as when the last message is deleted.
+    execute!(
+        "DELETE FROM inline_comment_threads WHERE codelab_id = ? AND NOT EXISTS (SELECT 1 FROM inline_comment_messages m WHERE m.thread_id = inline_comment_threads.id)",
+        &attendee.codelab_id
+    );
+    let certificates_deleted = execute!(
+        "DELETE FROM certificates WHERE attendee_id = ?",
+        &attendee_id
+    );
+    let audit_logs_scrubbed = execute!(
+        "UPDATE audit_logs SET ip = NULL, user_agent = NULL WHERE actor_id = ? OR target_id = ?",
+        &attendee_id,
+        &attendee_id
+    );
+    let sessions_revoked = execute!(
+        "UPDATE auth_sessions SET revoked_at_epoch = COALESCE(revoked_at_epoch, ?), revoked_by = COALESCE(revoked_by, ?), ip = NULL, user_agent = NULL WHERE subject = ? AND role = 'attendee'",
+        now_epoch_seconds() as i64,
+        &admin.sub,
+        &attendee_id
+    );
+    execute!(
+        "UPDATE attendees SET name = ?, email = NULL, code = ?, erased_at = CAST(CURRENT_TIMESTAMP AS TEXT) WHERE id = ?",
+        &pseudonym,
+        &locked_code,
+        &attendee_id
+    );
+    tx.commit().await.map_err(internal_error)?;
+
+    let mut files_deleted = 0;
+    for (file_path,) in files {
+        if let Some(disk_path) = upload_disk_path(&file_path) {
+            if tokio::fs::remove_file(&disk_path).await.is_ok() {
+                files_deleted += 1;
+            }
+        }
+    }
+
+    audit_privacy(&state, "attendee_data_erase", admin.sub, &attendee, info).await;
+
+    Ok(Json(AttendeeErasure {
+        attendee_id,
+        codelab_id: attendee.codelab_id,
+        pseudonym,
+        chat_messages_deleted,
+        waitlist_entries_deleted,
+        feedback_redacted,
+        quiz_answers_redacted,
+        submissions_deleted,
+        files_deleted,
+        ai_records_deleted: ai_threads_deleted + ai_conversations_deleted,
+        inline_comments_deleted,
+        certificates_deleted,
+        audit_logs_scrubbed,
+        sessions_revoked,
+    }))
+}
@@ -0,0 +1,144 @@
+use crate::api::handlers::cohorts::ensure_codelab_exists;
+use crate::api::handlers::websocket::broadcast_to_codelab;
+use crate::domain::models::PublicationStatus;
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::progress::{learner_step_ids, remap_attendee_progress};
+use crate::infrastructure::revisions::{record_revision, REVISION_SOURCE_PUBLISH};
+use crate::middleware::auth::{AuthSession, Permission};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::internal_error;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Reads which revision of a codelab is published and whether the draft moved on.
+pub(crate) async fn publication_status(
+    state: &AppState,
+    codelab_id: &str,
+) -> Result<PublicationStatus, sqlx::Error> {
+    let (published_revision_id, published_at): (Option<String>, Option<String>) = sqlx::query_as(
+        &state.q("SELECT published_revision_id, published_at FROM codelabs WHERE id = ?"),
+    )
+    .bind(codelab_id)
+    .fetch_one(&state.pool)
+    .await?;
+    let published_revision_number: Option<i32> = match &published_revision_id {
+        Some(revision_id) => {
+            sqlx::query_scalar(
+                &state.q("SELECT revision_number FROM codelab_revisions WHERE id = ?"),
+            )
+            .bind(revision_id)
+            .fetch_optional(&state.pool)
+            .await?
+        }
+        None => None,
+    };
+    let latest_revision_number: Option<i32> = sqlx::query_scalar(
+        &state.q("SELECT MAX(revision_number) FROM codelab_revisions WHERE codelab_id = ?"),
+    )
+    .bind(codelab_id)
+    .fetch_one(&state.pool)
+    .await?;
+    let has_unpublished_changes = match (published_revision_number, latest_revision_number) {
+        (Some(published), Some(latest)) => latest > published,
+        _ => false,
+    };
+    Ok(PublicationStatus {
+        codelab_id: codelab_id.to_string(),
+        published_revision_id,
+        published_revision_number,
+        published_at,
+        latest_revision_number,
+        has_unpublished_changes,
+    })
+}
+
+/// Returns the publication status of a codelab.
+pub async fn get_publication(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<PublicationStatus>, (StatusCode, String)> {
+    session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    ensure_codelab_exists(&state, &id).await?;
+    let status = publication_status(&state, &id)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(status))
+}
+
+/// Snapshots the draft steps and guide into the content learners see and
+/// tells connected clients to reload it. Attendees are moved to follow their
+/// step if the published step order changed.
+pub async fn publish_codelab(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<Json<PublicationStatus>, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    ensure_codelab_exists(&state, &id).await?;
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let learner_steps = learner_step_ids(&state, &mut tx, &id)
+        .await
+        .map_err(internal_error)?;
+    let revision_id = record_revision(
+        &state,
+        &mut tx,
+        &id,
+        Some(&admin.sub),
+        REVISION_SOURCE_PUBLISH,
+    )
+    .await
+    .map_err(internal_error)?;
+    sqlx::query(&state.q(
+        "UPDATE codelabs SET published_revision_id = ?, published_at = CAST(CURRENT_TIMESTAMP AS TEXT) WHERE id = ?",
+    ))
+    .bind(&revision_id)
+    .bind(&id)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    remap_attendee_progress(&state, &mut tx, &id, &learner_steps)
+        .await
+        .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    let status = publication_status(&state, &id)
+        .await
+        .map_err(internal_error)?;
+    let payload = json!({
+        "type": "content_updated",
+        "revision_id": revision_id,
+        "revision_number": status.published_revision_number,
+    })
+    .to_string();
+    broadcast_to_codelab(&state, &id, &payload);
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "codelab_publish".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(revision_id),
+            codelab_id: Some(id),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: None,
+        },
+    )
+    .await;
+
+    Ok(Json(status))
+}
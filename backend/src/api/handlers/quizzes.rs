@@ -1,11 +1,12 @@
 use crate::domain::models::{
-    Codelab, CreateQuiz, Quiz, QuizSubmissionPayload, QuizSubmissionWithAttendee,
+    Codelab, CreateQuiz, Quiz, QuizAttempt, QuizGradeResult, QuizSubmissionPayload,
+    QuizSubmissionResult, QuizSubmissionWithAttendee,
 };
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
-use crate::middleware::auth::AuthSession;
+use crate::middleware::auth::{AuthSession, Permission, StaffRole};
 use crate::middleware::request_info::RequestInfo;
-use crate::utils::error::{bad_request, forbidden, internal_error};
+use crate::utils::error::{bad_request, forbidden, internal_error, unauthorized};
 use crate::utils::validation::validate_quiz;
 use axum::{
     extract::{Path, State},
@@ -13,6 +14,7 @@ use axum::{
     response::Json,
 };
 use serde_json;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -28,7 +30,9 @@ pub async fn get_quizzes(
         .await
         .map_err(internal_error)?
         .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))?;
-    if !can_access_codelab(&codelab, &session) {
+    if !can_access_codelab(&codelab, &session)
+        && !session.is_codelab_staff(&state, &codelab_id).await?
+    {
         return Err(forbidden());
     }
 
@@ -51,7 +55,9 @@ pub async fn update_quizzes(
     info: RequestInfo,
     Json(payload): Json<Vec<CreateQuiz>>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    let admin = session.require_admin()?;
+    let admin = session
+        .require_codelab_permission(&state, &codelab_id, Permission::EditContent)
+        .await?;
     // Delete existing quizzes
     sqlx::query(&state.q("DELETE FROM quizzes WHERE codelab_id = ?"))
         .bind(&codelab_id)
@@ -104,13 +110,16 @@ pub async fn update_quizzes(
     Ok(StatusCode::OK)
 }
 
-/// Stores quiz submissions for the authenticated attendee.
+/// Grades quiz submissions for the authenticated attendee and records them as a new attempt.
+///
+/// Correctness is always computed against the stored quiz definition; any
+/// `is_correct` value sent by the client is ignored. Earlier attempts are kept.
 pub async fn submit_quiz(
     State(state): State<Arc<AppState>>,
     Path(codelab_id): Path<String>,
     session: AuthSession,
     Json(payload): Json<QuizSubmissionPayload>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<Json<QuizSubmissionResult>, (StatusCode, String)> {
     let attendee = session.require_attendee()?;
     if attendee.codelab_id.as_deref() != Some(codelab_id.as_str()) {
         return Err(forbidden());
@@ -118,30 +127,219 @@ pub async fn submit_quiz(
     if payload.submissions.is_empty() {
         return Err(bad_request("submissions cannot be empty"));
     }
-    // Delete previous submissions for this attendee in this codelab
-    sqlx::query(&state.q("DELETE FROM quiz_submissions WHERE codelab_id = ? AND attendee_id = ?"))
+
+    let codelab = sqlx::query_as::<_, Codelab>(&state.q("SELECT * FROM codelabs WHERE id = ?"))
         .bind(&codelab_id)
-        .bind(&attendee.sub)
-        .execute(&state.pool)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))?;
+
+    let quizzes = sqlx::query_as::<_, Quiz>(&state.q("SELECT * FROM quizzes WHERE codelab_id = ?"))
+        .bind(&codelab_id)
+        .fetch_all(&state.pool)
         .await
         .map_err(internal_error)?;
+    let quizzes_by_id: HashMap<&str, &Quiz> = quizzes
+        .iter()
+        .map(|quiz| (quiz.id.as_str(), quiz))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut results = Vec::with_capacity(payload.submissions.len());
+    for sub in &payload.submissions {
+        let quiz = quizzes_by_id
+            .get(sub.quiz_id.as_str())
+            .ok_or_else(|| bad_request("unknown quiz_id"))?;
+        if !seen.insert(sub.quiz_id.as_str()) {
+            return Err(bad_request("duplicate quiz_id"));
+        }
+        results.push(QuizGradeResult {
+            quiz_id: sub.quiz_id.clone(),
+            answer: sub.answer.clone(),
+            is_correct: grade_answer(quiz, &sub.answer),
+        });
+    }
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let previous_attempts: i32 = sqlx::query_scalar(&state.q(
+        "SELECT COALESCE(MAX(attempt_number), 0) FROM quiz_attempts WHERE codelab_id = ? AND attendee_id = ?",
+    ))
+    .bind(&codelab_id)
+    .bind(&attendee.sub)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    if codelab.quiz_max_attempts > 0 && previous_attempts >= codelab.quiz_max_attempts {
+        return Err((StatusCode::FORBIDDEN, "QUIZ_ATTEMPTS_EXHAUSTED".to_string()));
+    }
+
+    let attempt_id = Uuid::new_v4().to_string();
+    let attempt_number = previous_attempts + 1;
+    let total = quizzes.len() as i32;
+    let correct = results.iter().filter(|result| result.is_correct).count() as i32;
+    let score = score_percentage(correct, total);
+
+    sqlx::query(&state.q(
+        "INSERT INTO quiz_attempts (id, codelab_id, attendee_id, attempt_number, total_questions, correct_count, score) VALUES (?, ?, ?, ?, ?, ?, ?)",
+    ))
+    .bind(&attempt_id)
+    .bind(&codelab_id)
+    .bind(&attendee.sub)
+    .bind(attempt_number)
+    .bind(total)
+    .bind(correct)
+    .bind(score)
+    .execute(&mut *tx)
+    .await
+    .map_err(|err| {
+        // A concurrent submission by the same attendee took this attempt number.
+        if err
+            .as_database_error()
+            .is_some_and(|db_err| db_err.is_unique_violation())
+        {
+            (StatusCode::CONFLICT, "QUIZ_ATTEMPT_CONFLICT".to_string())
+        } else {
+            internal_error(err)
+        }
+    })?;
 
-    for sub in payload.submissions {
+    for result in &results {
         let id = Uuid::new_v4().to_string();
         sqlx::query(
-            &state.q("INSERT INTO quiz_submissions (id, codelab_id, attendee_id, quiz_id, answer, is_correct) VALUES (?, ?, ?, ?, ?, ?)")
+            &state.q("INSERT INTO quiz_submissions (id, codelab_id, attendee_id, quiz_id, answer, is_correct, attempt_id) VALUES (?, ?, ?, ?, ?, ?, ?)")
         )
         .bind(&id)
         .bind(&codelab_id)
         .bind(&attendee.sub)
-        .bind(&sub.quiz_id)
-        .bind(&sub.answer)
-        .bind(if sub.is_correct { 1 } else { 0 })
-        .execute(&state.pool)
+        .bind(&result.quiz_id)
+        .bind(&result.answer)
+        .bind(if result.is_correct { 1 } else { 0 })
+        .bind(&attempt_id)
+        .execute(&mut *tx)
         .await
         .map_err(internal_error)?;
     }
-    Ok(StatusCode::OK)
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(Json(QuizSubmissionResult {
+        attempt_id,
+        attempt_number,
+        total,
+        correct,
+        score,
+        pass_percentage: codelab.quiz_pass_percentage,
+        passed: is_passing_score(score, codelab.quiz_pass_percentage),
+        remaining_attempts: (codelab.quiz_max_attempts > 0)
+            .then(|| codelab.quiz_max_attempts - attempt_number),
+        results,
+    }))
+}
+
+/// Lists quiz attempts: every attendee's for admins, only their own for attendees.
+pub async fn get_quiz_attempts(
+    State(state): State<Arc<AppState>>,
+    Path(codelab_id): Path<String>,
+    session: AuthSession,
+) -> Result<Json<Vec<QuizAttempt>>, (StatusCode, String)> {
+    let claims = session.claims.clone().ok_or_else(unauthorized)?;
+    let attempts = if session
+        .codelab_staff_claims(&state, &codelab_id, Permission::RunSessions)
+        .await?
+        .is_some()
+    {
+        sqlx::query_as::<_, QuizAttempt>(&state.q(
+            "SELECT * FROM quiz_attempts WHERE codelab_id = ? ORDER BY attendee_id, attempt_number",
+        ))
+        .bind(&codelab_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?
+    } else if claims.role == "attendee" && claims.codelab_id.as_deref() == Some(codelab_id.as_str())
+    {
+        sqlx::query_as::<_, QuizAttempt>(&state.q(
+            "SELECT * FROM quiz_attempts WHERE codelab_id = ? AND attendee_id = ? ORDER BY attempt_number",
+        ))
+        .bind(&codelab_id)
+        .bind(&claims.sub)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?
+    } else {
+        return Err(forbidden());
+    };
+
+    Ok(Json(attempts))
+}
+
+/// Returns whether the attendee has an attempt that meets the codelab's pass threshold.
+pub(crate) async fn has_passing_quiz_attempt(
+    state: &AppState,
+    codelab: &Codelab,
+    attendee_id: &str,
+) -> Result<bool, (StatusCode, String)> {
+    let best_score: Option<f64> = sqlx::query_scalar(
+        &state.q("SELECT MAX(score) FROM quiz_attempts WHERE codelab_id = ? AND attendee_id = ?"),
+    )
+    .bind(&codelab.id)
+    .bind(attendee_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    Ok(best_score.is_some_and(|score| is_passing_score(score, codelab.quiz_pass_percentage)))
+}
+
+/// Returns whether `answer` is correct for `quiz`.
+///
+/// Choice answers are an option index (`"1"`), a JSON array of indices for
+/// multi-select quizzes (`"[0,2]"`), or the option text itself. The selected
+/// set must match the stored correct set exactly. Descriptive answers are
+/// correct when non-blank.
+fn grade_answer(quiz: &Quiz, answer: &str) -> bool {
+    if quiz.quiz_type.as_deref() == Some("descriptive") {
+        return !answer.trim().is_empty();
+    }
+    let Some(selected) = parse_selected_indices(quiz, answer) else {
+        return false;
+    };
+    let expected: HashSet<i32> = correct_answer_set(quiz).into_iter().collect();
+    selected == expected
+}
+
+fn parse_selected_indices(quiz: &Quiz, answer: &str) -> Option<HashSet<i32>> {
+    let answer = answer.trim();
+    if answer.starts_with('[') {
+        let selected = serde_json::from_str::<Vec<i32>>(answer).ok()?;
+        return Some(selected.into_iter().collect());
+    }
+    let index = answer.parse::<i32>().ok().or_else(|| {
+        serde_json::from_str::<Vec<String>>(&quiz.options)
+            .ok()?
+            .iter()
+            .position(|option| option.trim() == answer)
+            .map(|position| position as i32)
+    })?;
+    Some(HashSet::from([index]))
+}
+
+fn correct_answer_set(quiz: &Quiz) -> Vec<i32> {
+    quiz.correct_answers
+        .as_deref()
+        .and_then(|raw| serde_json::from_str::<Vec<i32>>(raw).ok())
+        .filter(|answers| !answers.is_empty())
+        .unwrap_or_else(|| vec![quiz.correct_answer])
+}
+
+fn score_percentage(correct: i32, total: i32) -> f64 {
+    if total <= 0 {
+        return 0.0;
+    }
+    (correct as f64 / total as f64 * 10000.0).round() / 100.0
+}
+
+fn is_passing_score(score: f64, pass_percentage: i32) -> bool {
+    score >= pass_percentage as f64
 }
 
 /// Lists quiz submissions with attendee metadata for admins.
@@ -150,7 +348,9 @@ pub async fn get_quiz_submissions(
     Path(codelab_id): Path<String>,
     session: AuthSession,
 ) -> Result<Json<Vec<QuizSubmissionWithAttendee>>, (StatusCode, String)> {
-    session.require_admin()?;
+    session
+        .require_codelab_permission(&state, &codelab_id, Permission::RunSessions)
+        .await?;
     let submissions = sqlx::query_as::<_, QuizSubmissionWithAttendee>(
         &state.q("SELECT qs.*, a.name as attendee_name FROM quiz_submissions qs JOIN attendees a ON qs.attendee_id = a.id WHERE qs.codelab_id = ? ORDER BY qs.created_at DESC")
     )
@@ -164,7 +364,7 @@ pub async fn get_quiz_submissions(
 
 fn can_access_codelab(codelab: &Codelab, session: &AuthSession) -> bool {
     match &session.claims {
-        Some(claims) if claims.role == "admin" => true,
+        Some(claims) if claims.staff_role() == Some(StaffRole::Owner) => true,
         Some(claims)
             if claims.role == "attendee"
                 && claims.codelab_id.as_deref() == Some(codelab.id.as_str()) =>
@@ -186,6 +386,9 @@ mod tests {
                 sub: "u1".to_string(),
                 role: role.to_string(),
                 codelab_id: codelab_id.map(|v| v.to_string()),
+                staff_role: None,
+                scopes: None,
+                jti: None,
                 iss: "test".to_string(),
                 aud: "test".to_string(),
                 iat: 0,
@@ -207,11 +410,78 @@ mod tests {
             require_quiz: 0,
             require_feedback: 0,
             require_submission: 0,
+            quiz_max_attempts: 0,
+            quiz_pass_percentage: 100,
+            max_attendees: 0,
+            registration_opens_at: None,
+            registration_closes_at: None,
             guide_markdown: None,
             created_at: None,
+            published_revision_id: None,
+            published_at: None,
+            version: 1,
+            steps_version: 1,
+            categories: None,
         }
     }
 
+    fn quiz(quiz_type: &str, correct_answer: i32, correct_answers: Option<&str>) -> Quiz {
+        Quiz {
+            id: "q1".to_string(),
+            codelab_id: "lab-1".to_string(),
+            question: "Which color?".to_string(),
+            quiz_type: Some(quiz_type.to_string()),
+            options: r#"["red","green","blue"]"#.to_string(),
+            correct_answer,
+            correct_answers: correct_answers.map(|v| v.to_string()),
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn grade_answer_handles_single_choice_by_index_or_text() {
+        let q = quiz("multiple_choice", 1, Some("[1]"));
+        assert!(grade_answer(&q, "1"));
+        assert!(grade_answer(&q, " green "));
+        assert!(!grade_answer(&q, "0"));
+        assert!(!grade_answer(&q, "red"));
+        assert!(!grade_answer(&q, ""));
+    }
+
+    #[test]
+    fn grade_answer_requires_exact_set_for_multiple_answers() {
+        let q = quiz("multiple_choice", 0, Some("[0,2]"));
+        assert!(grade_answer(&q, "[2,0]"));
+        assert!(!grade_answer(&q, "[0]"));
+        assert!(!grade_answer(&q, "[0,1,2]"));
+        assert!(!grade_answer(&q, "not-json"));
+
+        let fallback = quiz("multiple_choice", 1, None);
+        assert!(grade_answer(&fallback, "[1]"));
+        assert!(grade_answer(&fallback, "1"));
+    }
+
+    #[test]
+    fn grade_answer_accepts_non_blank_descriptive_answers() {
+        let q = quiz("descriptive", 0, None);
+        assert!(grade_answer(&q, "because"));
+        assert!(!grade_answer(&q, "   "));
+    }
+
+    #[test]
+    fn score_percentage_rounds_to_two_decimals() {
+        assert_eq!(score_percentage(1, 3), 33.33);
+        assert_eq!(score_percentage(2, 2), 100.0);
+        assert_eq!(score_percentage(0, 0), 0.0);
+    }
+
+    #[test]
+    fn is_passing_score_compares_against_threshold() {
+        assert!(is_passing_score(80.0, 80));
+        assert!(!is_passing_score(79.99, 80));
+        assert!(is_passing_score(0.0, 0));
+    }
+
     #[test]
     fn can_access_codelab_respects_role_and_membership() {
         let lab = codelab("lab-1");
@@ -0,0 +1,49 @@
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::retention::{apply_retention, RetentionReport};
+use crate::middleware::auth::{AuthSession, Permission};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::internal_error;
+use axum::{extract::State, http::StatusCode, Json};
+use std::sync::Arc;
+
+/// Reports what a retention pass would delete right now without deleting it.
+pub async fn get_retention_report(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<RetentionReport>, (StatusCode, String)> {
+    session.require_permission(Permission::ManageSystem)?;
+    let report = apply_retention(&state, true)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(report))
+}
+
+/// Runs a retention pass immediately instead of waiting for the background job.
+pub async fn run_retention(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<Json<RetentionReport>, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageSystem)?;
+    let report = apply_retention(&state, false)
+        .await
+        .map_err(internal_error)?;
+    if report.total() > 0 {
+        record_audit(
+            &state,
+            AuditEntry {
+                action: "retention_prune".to_string(),
+                actor_type: "admin".to_string(),
+                actor_id: Some(admin.sub),
+                target_id: None,
+                codelab_id: None,
+                ip: Some(info.ip),
+                user_agent: info.user_agent,
+                metadata: Some(report.audit_metadata()),
+            },
+        )
+        .await;
+    }
+    Ok(Json(report))
+}
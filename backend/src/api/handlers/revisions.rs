@@ -0,0 +1,268 @@
+use crate::api::handlers::cohorts::ensure_codelab_exists;
+use crate::domain::models::{
+    CodelabRevision, CodelabRevisionDiff, CodelabRevisionSummary, RevisionStep,
+};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::authoring::reload_authoring_room;
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::db_models::CodelabRevisionRow;
+use crate::infrastructure::progress::{learner_step_ids, remap_attendee_progress};
+use crate::infrastructure::revisions::{
+    record_revision, render_revision_markdown, REVISION_SOURCE_RESTORE,
+};
+use crate::middleware::auth::{AuthSession, Permission};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::diff::unified_diff;
+use crate::utils::error::internal_error;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+const SUMMARY_SELECT: &str = "SELECT r.id, r.codelab_id, r.revision_number, r.author_id, COALESCE(u.display_name, u.username) AS author_name, r.source, CAST(r.created_at AS TEXT) AS created_at FROM codelab_revisions r LEFT JOIN users u ON u.id = r.author_id";
+
+/// Query string selecting the two revisions to compare.
+#[derive(Debug, Deserialize)]
+pub struct RevisionDiffQuery {
+    /// Revision the diff starts from, by identifier or number.
+    pub from: String,
+    /// Revision the diff leads to, by identifier or number; defaults to the latest.
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+fn revision_not_found() -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, "Revision not found".to_string())
+}
+
+/// Finds a revision of `codelab_id` by identifier, or by number when `key`
+/// is numeric.
+async fn find_revision_row(
+    state: &AppState,
+    codelab_id: &str,
+    key: &str,
+) -> Result<Option<CodelabRevisionRow>, sqlx::Error> {
+    let key = key.trim();
+    match key.parse::<i32>() {
+        Ok(number) => {
+            sqlx::query_as::<_, CodelabRevisionRow>(
+                &state.q(
+                    "SELECT * FROM codelab_revisions WHERE codelab_id = ? AND revision_number = ?",
+                ),
+            )
+            .bind(codelab_id)
+            .bind(number)
+            .fetch_optional(&state.pool)
+            .await
+        }
+        Err(_) => {
+            sqlx::query_as::<_, CodelabRevisionRow>(
+                &state.q("SELECT * FROM codelab_revisions WHERE codelab_id = ? AND id = ?"),
+            )
+            .bind(codelab_id)
+            .bind(key)
+            .fetch_optional(&state.pool)
+            .await
+        }
+    }
+}
+
+async fn fetch_summary(
+    state: &AppState,
+    revision_id: &str,
+) -> Result<CodelabRevisionSummary, sqlx::Error> {
+    sqlx::query_as::<_, CodelabRevisionSummary>(
+        &state.q(&format!("{SUMMARY_SELECT} WHERE r.id = ?")),
+    )
+    .bind(revision_id)
+    .fetch_one(&state.pool)
+    .await
+}
+
+fn decode_steps(row: &CodelabRevisionRow) -> Result<Vec<RevisionStep>, (StatusCode, String)> {
+    serde_json::from_str(&row.steps).map_err(internal_error)
+}
+
+async fn load_revision(
+    state: &AppState,
+    codelab_id: &str,
+    key: &str,
+) -> Result<(CodelabRevisionSummary, CodelabRevisionRow), (StatusCode, String)> {
+    let row = find_revision_row(state, codelab_id, key)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(revision_not_found)?;
+    let summary = fetch_summary(state, &row.id)
+        .await
+        .map_err(internal_error)?;
+    Ok((summary, row))
+}
+
+/// Lists the revisions of a codelab, newest first.
+pub async fn list_revisions(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<Vec<CodelabRevisionSummary>>, (StatusCode, String)> {
+    session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    ensure_codelab_exists(&state, &id).await?;
+    let revisions = sqlx::query_as::<_, CodelabRevisionSummary>(&state.q(&format!(
+        "{SUMMARY_SELECT} WHERE r.codelab_id = ? ORDER BY r.revision_number DESC"
+    )))
+    .bind(&id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(revisions))
+}
+
+/// Returns the guide and steps stored in one revision.
+pub async fn get_revision(
+    Path((id, revision_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<CodelabRevision>, (StatusCode, String)> {
+    session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    let (summary, row) = load_revision(&state, &id, &revision_id).await?;
+    let steps = decode_steps(&row)?;
+    Ok(Json(CodelabRevision {
+        summary,
+        guide_markdown: row.guide_markdown,
+        steps,
+    }))
+}
+
+/// Returns a unified diff between the rendered markdown of two revisions.
+pub async fn diff_revisions(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    Query(query): Query<RevisionDiffQuery>,
+) -> Result<Json<CodelabRevisionDiff>, (StatusCode, String)> {
+    session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    let (from, from_row) = load_revision(&state, &id, &query.from).await?;
+    let to_key = match query.to.filter(|key| !key.trim().is_empty()) {
+        Some(key) => key,
+        None => {
+            let latest: Option<i32> = sqlx::query_scalar(
+                &state.q("SELECT MAX(revision_number) FROM codelab_revisions WHERE codelab_id = ?"),
+            )
+            .bind(&id)
+            .fetch_one(&state.pool)
+            .await
+            .map_err(internal_error)?;
+            latest.ok_or_else(revision_not_found)?.to_string()
+        }
+    };
+    let (to, to_row) = load_revision(&state, &id, &to_key).await?;
+
+    let old = render_revision_markdown(
+        from_row.guide_markdown.as_deref(),
+        &decode_steps(&from_row)?,
+    );
+    let new = render_revision_markdown(to_row.guide_markdown.as_deref(), &decode_steps(&to_row)?);
+    let diff = unified_diff(
+        &old,
+        &new,
+        &format!("revision {}", from.revision_number),
+        &format!("revision {}", to.revision_number),
+    );
+    Ok(Json(CodelabRevisionDiff { from, to, diff }))
+}
+
+/// Replaces a codelab's steps and guide with the content of an earlier
+/// revision, recording the result as a new revision.
+pub async fn restore_revision(
+    Path((id, revision_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<Json<CodelabRevision>, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    let (restored, row) = load_revision(&state, &id, &revision_id).await?;
+    let steps = decode_steps(&row)?;
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let learner_steps = learner_step_ids(&state, &mut tx, &id)
+        .await
+        .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM steps WHERE codelab_id = ?"))
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    for (i, step) in steps.iter().enumerate() {
+        sqlx::query(&state.q(
+            "INSERT INTO steps (id, codelab_id, step_number, title, content_markdown, duration_minutes) VALUES (?, ?, ?, ?, ?, ?)",
+        ))
+        .bind(&step.id)
+        .bind(&id)
+        .bind((i + 1) as i32)
+        .bind(&step.title)
+        .bind(&step.content_markdown)
+        .bind(step.duration_minutes)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+    sqlx::query(&state.q("UPDATE codelabs SET guide_markdown = ?, version = version + 1, steps_version = steps_version + 1 WHERE id = ?"))
+        .bind(&row.guide_markdown)
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    remap_attendee_progress(&state, &mut tx, &id, &learner_steps)
+        .await
+        .map_err(internal_error)?;
+    let new_revision_id = record_revision(
+        &state,
+        &mut tx,
+        &id,
+        Some(&admin.sub),
+        REVISION_SOURCE_RESTORE,
+    )
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+    if let Err(err) = reload_authoring_room(&state, &id).await {
+        tracing::warn!("reloading authoring room for codelab {id} failed: {err}");
+    }
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "codelab_revision_restore".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(new_revision_id.clone()),
+            codelab_id: Some(id),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({
+                "restored_revision_id": restored.id,
+                "restored_revision_number": restored.revision_number,
+            })),
+        },
+    )
+    .await;
+
+    let summary = fetch_summary(&state, &new_revision_id)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(CodelabRevision {
+        summary,
+        guide_markdown: row.guide_markdown,
+        steps,
+    }))
+}
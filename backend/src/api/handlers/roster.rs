@@ -0,0 +1,283 @@
+use crate::api::handlers::cohorts::{ensure_codelab_exists, fetch_cohort};
+use crate::domain::models::{
+    ImportRosterPayload, RosterEntry, RosterImportResult, RosterImportRow,
+};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::join_access::generate_join_code;
+use crate::middleware::auth::{AuthSession, Permission};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::crypto::encrypt_with_password;
+use crate::utils::csv::parse_csv;
+use crate::utils::error::{bad_request, internal_error};
+use crate::utils::validation::validate_roster_entry;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+use url::form_urlencoded::Serializer;
+use uuid::Uuid;
+
+/// Largest roster accepted in one import.
+const MAX_ROSTER_ROWS: usize = 1000;
+
+/// Reads roster rows from CSV text.
+///
+/// A first row whose first cell is `name` is treated as a header and columns
+/// are matched by name; otherwise columns are read as `name,email,code`.
+fn parse_roster_csv(input: &str) -> Result<Vec<RosterEntry>, (StatusCode, String)> {
+    let mut records = parse_csv(input)
+        .map_err(|err| bad_request(&format!("invalid roster CSV: {err}")))?
+        .into_iter();
+    let mut columns = (Some(0), Some(1), Some(2));
+    let mut first = records.next();
+    if let Some(header) = &first {
+        if header
+            .first()
+            .is_some_and(|cell| cell.trim().eq_ignore_ascii_case("name"))
+        {
+            let position = |name: &str| {
+                header
+                    .iter()
+                    .position(|cell| cell.trim().eq_ignore_ascii_case(name))
+            };
+            columns = (position("name"), position("email"), position("code"));
+            first = None;
+        }
+    }
+
+    let cell = |record: &[String], index: Option<usize>| {
+        index
+            .and_then(|index| record.get(index))
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    Ok(first
+        .into_iter()
+        .chain(records)
+        .map(|record| RosterEntry {
+            name: cell(&record, columns.0).unwrap_or_default(),
+            email: cell(&record, columns.1),
+            code: cell(&record, columns.2),
+        })
+        .collect())
+}
+
+/// Returns the entry page path prefilled with a pre-registered learner's sign-in.
+fn join_path(codelab_id: &str, name: &str, code: &str) -> String {
+    let mut serializer = Serializer::new(String::new());
+    serializer.append_pair("name", name);
+    serializer.append_pair("code", code);
+    format!("/codelabs/{codelab_id}/entry?{}", serializer.finish())
+}
+
+/// Pre-registers a roster of attendees from JSON rows or CSV text.
+///
+/// Names follow the registration nickname rules: a name already used by an
+/// attendee, a waitlisted learner, or an earlier row is reported as a
+/// duplicate and skipped. Imports are staff actions and ignore the attendee
+/// limit and registration window.
+pub async fn import_roster(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<ImportRosterPayload>,
+) -> Result<Json<RosterImportResult>, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    ensure_codelab_exists(&state, &id).await?;
+
+    let entries = match payload.csv.as_deref() {
+        Some(_) if !payload.attendees.is_empty() => {
+            return Err(bad_request("send either attendees or csv, not both"));
+        }
+        Some(csv) => parse_roster_csv(csv)?,
+        None => payload.attendees,
+    };
+    if entries.is_empty() {
+        return Err(bad_request("roster is empty"));
+    }
+    if entries.len() > MAX_ROSTER_ROWS {
+        return Err(bad_request("roster is too large"));
+    }
+    let cohort_id = match payload.cohort_id.as_deref() {
+        Some(cohort_id) => Some(
+            fetch_cohort(&state, &id, cohort_id)
+                .await
+                .map_err(internal_error)?
+                .ok_or_else(|| bad_request("unknown cohort_id"))?
+                .id,
+        ),
+        None => None,
+    };
+
+    // Validate the roster and encrypt the join codes before opening the
+    // transaction: PBKDF2 is CPU-bound and runs on the blocking pool.
+    let mut rows = Vec::with_capacity(entries.len());
+    let mut pending = Vec::new();
+    let mut seen = HashSet::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let entry = RosterEntry {
+            name: entry.name.trim().to_string(),
+            email: entry
+                .email
+                .map(|email| email.trim().to_string())
+                .filter(|email| !email.is_empty()),
+            code: entry.code.map(|code| code.trim().to_string()),
+        };
+        let mut row = RosterImportRow {
+            row: index + 1,
+            name: entry.name.clone(),
+            email: entry.email.clone(),
+            status: "invalid".to_string(),
+            attendee_id: None,
+            code: None,
+            join_path: None,
+            error: None,
+        };
+        if let Err((_, message)) = validate_roster_entry(&entry) {
+            row.error = Some(message);
+            rows.push(row);
+            continue;
+        }
+
+        row.status = "duplicate".to_string();
+        if !seen.insert(entry.name.clone()) {
+            row.error = Some("Name appears earlier in the roster".to_string());
+            rows.push(row);
+            continue;
+        }
+        let code = entry.code.clone().unwrap_or_else(generate_join_code);
+        pending.push((rows.len(), entry, code));
+        rows.push(row);
+    }
+
+    let codes: Vec<String> = pending.iter().map(|(_, _, code)| code.clone()).collect();
+    let admin_pw = state.admin_pw.clone();
+    let encrypted_codes = tokio::task::spawn_blocking(move || {
+        codes
+            .iter()
+            .map(|code| encrypt_with_password(code, &admin_pw))
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .await
+    .map_err(internal_error)?
+    .map_err(internal_error)?;
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    for ((row_index, entry, code), encrypted_code) in pending.into_iter().zip(encrypted_codes) {
+        let row = &mut rows[row_index];
+        let existing: Option<(String,)> =
+            sqlx::query_as(&state.q("SELECT id FROM attendees WHERE codelab_id = ? AND name = ?"))
+                .bind(&id)
+                .bind(&entry.name)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(internal_error)?;
+        if let Some((attendee_id,)) = existing {
+            row.attendee_id = Some(attendee_id);
+            row.error = Some("Nickname already taken".to_string());
+            continue;
+        }
+        let waitlisted: Option<(String,)> = sqlx::query_as(
+            &state.q("SELECT id FROM codelab_waitlist WHERE codelab_id = ? AND name = ?"),
+        )
+        .bind(&id)
+        .bind(&entry.name)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+        if waitlisted.is_some() {
+            row.error = Some("Learner is on the waitlist".to_string());
+            continue;
+        }
+
+        let attendee_id = Uuid::new_v4().to_string();
+        sqlx::query(&state.q(
+            "INSERT INTO attendees (id, codelab_id, name, code, email, current_step, cohort_id) VALUES (?, ?, ?, ?, ?, 1, ?)",
+        ))
+        .bind(&attendee_id)
+        .bind(&id)
+        .bind(&entry.name)
+        .bind(&encrypted_code)
+        .bind(&entry.email)
+        .bind(&cohort_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+        row.status = "created".to_string();
+        row.attendee_id = Some(attendee_id);
+        row.join_path = Some(join_path(&id, &entry.name, &code));
+        row.code = Some(code);
+    }
+    tx.commit().await.map_err(internal_error)?;
+
+    let count = |status: &str| rows.iter().filter(|row| row.status == status).count();
+    let result = RosterImportResult {
+        created: count("created"),
+        duplicates: count("duplicate"),
+        invalid: count("invalid"),
+        rows,
+    };
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "attendee_import".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: cohort_id,
+            codelab_id: Some(id),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({
+                "created": result.created,
+                "duplicates": result.duplicates,
+                "invalid": result.invalid,
+            })),
+        },
+    )
+    .await;
+
+    Ok(Json(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_roster_csv_reads_headers_and_positional_columns() {
+        let entries = parse_roster_csv("name,code,email\nAda,ADA-1,ada@example.com\nGrace,,\n")
+            .expect("header csv");
+        assert_eq!(
+            entries,
+            vec![
+                RosterEntry {
+                    name: "Ada".to_string(),
+                    email: Some("ada@example.com".to_string()),
+                    code: Some("ADA-1".to_string()),
+                },
+                RosterEntry {
+                    name: "Grace".to_string(),
+                    email: None,
+                    code: None,
+                },
+            ]
+        );
+
+        let entries = parse_roster_csv("Linus,linus@example.com").expect("positional csv");
+        assert_eq!(entries[0].name, "Linus");
+        assert_eq!(entries[0].email.as_deref(), Some("linus@example.com"));
+
+        assert_eq!(
+            join_path("lab-1", "Ada L", "AB&C"),
+            "/codelabs/lab-1/entry?name=Ada+L&code=AB%26C"
+        );
+    }
+}
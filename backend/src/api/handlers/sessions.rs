@@ -0,0 +1,187 @@
+use crate::api::dto::{SessionFilterQuery, SessionRevokeResponse};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::db_models::AuthSessionRow;
+use crate::infrastructure::sessions::{
+    list_active_sessions, revoke_session, revoke_sessions, SessionFilter,
+};
+use crate::middleware::auth::{clear_cookie, AuthSession, Permission};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::{bad_request, internal_error, unauthorized};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::extract::cookie::CookieJar;
+use std::sync::Arc;
+
+impl SessionFilterQuery {
+    fn into_filter(self) -> SessionFilter {
+        let clean = |value: Option<String>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        SessionFilter {
+            subject: clean(self.user_id),
+            codelab_id: clean(self.codelab_id),
+        }
+    }
+}
+
+/// Lists active sessions, optionally narrowed to one user or codelab.
+pub async fn list_sessions(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    Query(query): Query<SessionFilterQuery>,
+) -> Result<Json<Vec<AuthSessionRow>>, (StatusCode, String)> {
+    session.require_permission(Permission::ManageUsers)?;
+    let sessions = list_active_sessions(&state, &query.into_filter())
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(sessions))
+}
+
+/// Revokes a single session by its `jti`.
+pub async fn delete_session(
+    Path(session_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageUsers)?;
+    let revoked = revoke_session(&state, &session_id, &admin.sub)
+        .await
+        .map_err(internal_error)?;
+    if !revoked {
+        return Err((StatusCode::NOT_FOUND, "Session not found".to_string()));
+    }
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "session_revoke".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(session_id),
+            codelab_id: None,
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: None,
+        },
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Revokes every active session of a user or codelab.
+///
+/// At least one filter is required so a stray request cannot sign out the
+/// whole instance.
+pub async fn revoke_matching_sessions(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<SessionFilterQuery>,
+) -> Result<Json<SessionRevokeResponse>, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageUsers)?;
+    let filter = payload.into_filter();
+    if filter.subject.is_none() && filter.codelab_id.is_none() {
+        return Err(bad_request("user_id or codelab_id is required"));
+    }
+    let revoked = revoke_sessions(&state, &filter, &admin.sub)
+        .await
+        .map_err(internal_error)?;
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "session_revoke".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: filter.subject.clone(),
+            codelab_id: filter.codelab_id.clone(),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({ "revoked": revoked })),
+        },
+    )
+    .await;
+
+    Ok(Json(SessionRevokeResponse { revoked }))
+}
+
+/// Signs the caller out of every device by revoking all of their sessions.
+pub async fn logout_everywhere(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<(CookieJar, Json<SessionRevokeResponse>), (StatusCode, String)> {
+    let subjects: Vec<(String, &str)> = [
+        session
+            .admin_claims
+            .as_ref()
+            .map(|c| (c.sub.clone(), "admin")),
+        session
+            .attendee_claims
+            .as_ref()
+            .map(|c| (c.sub.clone(), "attendee")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if subjects.is_empty() {
+        return Err(unauthorized());
+    }
+
+    let mut revoked = 0;
+    for (subject, actor_type) in subjects {
+        let filter = SessionFilter {
+            subject: Some(subject.clone()),
+            codelab_id: None,
+        };
+        let count = revoke_sessions(&state, &filter, &subject)
+            .await
+            .map_err(internal_error)?;
+        revoked += count;
+        record_audit(
+            &state,
+            AuditEntry {
+                action: "session_revoke".to_string(),
+                actor_type: actor_type.to_string(),
+                actor_id: Some(subject.clone()),
+                target_id: Some(subject),
+                codelab_id: None,
+                ip: Some(info.ip.clone()),
+                user_agent: info.user_agent.clone(),
+                metadata: Some(serde_json::json!({ "revoked": count, "scope": "all" })),
+            },
+        )
+        .await;
+    }
+
+    let jar = jar
+        .remove(clear_cookie(&state.auth.cookie_name))
+        .remove(clear_cookie(&state.auth.attendee_cookie_name))
+        .remove(clear_cookie(&state.auth.csrf_cookie_name));
+    Ok((jar, Json(SessionRevokeResponse { revoked })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_query_trims_and_drops_blank_values() {
+        let filter = SessionFilterQuery {
+            user_id: Some("  user-1 ".to_string()),
+            codelab_id: Some("   ".to_string()),
+        }
+        .into_filter();
+        assert_eq!(filter.subject.as_deref(), Some("user-1"));
+        assert!(filter.codelab_id.is_none());
+    }
+}
@@ -0,0 +1,377 @@
+use crate::api::handlers::codelabs::bump_steps_version;
+use crate::api::handlers::websocket::broadcast_to_codelab;
+use crate::domain::models::{
+    InsertStepPayload, MoveStepPayload, Step, StepChangeResponse, UpdateStepPayload,
+};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::authoring::reload_authoring_room;
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::progress::{learner_step_ids, remap_attendee_progress};
+use crate::infrastructure::revisions::{
+    record_baseline_revision, record_revision, REVISION_SOURCE_STEPS,
+};
+use crate::middleware::auth::{AuthSession, Permission, SessionClaims};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::{bad_request, internal_error};
+use crate::utils::etag::{parse_if_match, version_etag};
+use crate::utils::validation::{validate_step_content, MAX_STEPS};
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use sqlx::{Any, AnyConnection, Transaction};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A step change in progress: the transaction holding the bumped step version
+/// and what learners saw before the change.
+struct StepChange {
+    tx: Transaction<'static, Any>,
+    steps_version: i32,
+    learner_steps: Vec<String>,
+    order: Vec<String>,
+}
+
+/// Opens a transaction for changing the steps of a codelab, honoring
+/// `If-Match` against the step set version.
+///
+/// Fails with the response to send, such as `409 Conflict`.
+async fn begin_step_change(
+    state: &AppState,
+    id: &str,
+    headers: &HeaderMap,
+) -> Result<StepChange, Response> {
+    let expected_version = parse_if_match(headers).map_err(IntoResponse::into_response)?;
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(|err| internal_error(err).into_response())?;
+    let current_version = bump_steps_version(state, &mut tx, id, expected_version).await?;
+    record_baseline_revision(state, &mut tx, id)
+        .await
+        .map_err(|err| internal_error(err).into_response())?;
+    let learner_steps = learner_step_ids(state, &mut tx, id)
+        .await
+        .map_err(|err| internal_error(err).into_response())?;
+    let order: Vec<String> = sqlx::query_scalar(
+        &state.q("SELECT id FROM steps WHERE codelab_id = ? ORDER BY step_number"),
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|err| internal_error(err).into_response())?;
+    Ok(StepChange {
+        tx,
+        steps_version: current_version + 1,
+        learner_steps,
+        order,
+    })
+}
+
+fn step_not_found() -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, "Step not found".to_string())
+}
+
+/// Writes `step_number` for every step of a codelab whose stored number does
+/// not match its position in `order`.
+async fn renumber_steps(
+    state: &AppState,
+    conn: &mut AnyConnection,
+    codelab_id: &str,
+    order: &[String],
+) -> Result<(), sqlx::Error> {
+    let stored: HashMap<String, i32> =
+        sqlx::query_as(&state.q("SELECT id, step_number FROM steps WHERE codelab_id = ?"))
+            .bind(codelab_id)
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .collect();
+    for (index, step_id) in order.iter().enumerate() {
+        let step_number = (index + 1) as i32;
+        if stored.get(step_id) == Some(&step_number) {
+            continue;
+        }
+        sqlx::query(&state.q("UPDATE steps SET step_number = ? WHERE id = ?"))
+            .bind(step_number)
+            .bind(step_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Records the revision, moves attendee progress, commits, and tells editors
+/// and learners about the new step set.
+async fn finish_step_change(
+    state: &AppState,
+    mut change: StepChange,
+    id: &str,
+    step_id: String,
+    admin: SessionClaims,
+    info: RequestInfo,
+    action: &str,
+) -> Result<Response, (StatusCode, String)> {
+    let tx = &mut change.tx;
+    remap_attendee_progress(state, tx, id, &change.learner_steps)
+        .await
+        .map_err(internal_error)?;
+    let revision_id = record_revision(state, tx, id, Some(&admin.sub), REVISION_SOURCE_STEPS)
+        .await
+        .map_err(internal_error)?;
+    let steps = sqlx::query_as::<_, Step>(
+        &state.q("SELECT * FROM steps WHERE codelab_id = ? ORDER BY step_number"),
+    )
+    .bind(id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(internal_error)?;
+    let published: Option<String> =
+        sqlx::query_scalar(&state.q("SELECT published_revision_id FROM codelabs WHERE id = ?"))
+            .bind(id)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(internal_error)?;
+    change.tx.commit().await.map_err(internal_error)?;
+
+    if let Err(err) = reload_authoring_room(state, id).await {
+        tracing::warn!("reloading authoring room for codelab {id} failed: {err}");
+    }
+    // Learners read the draft until the codelab is published.
+    if published.is_none() {
+        let payload = json!({ "type": "content_updated", "revision_id": revision_id }).to_string();
+        broadcast_to_codelab(state, id, &payload);
+    }
+
+    record_audit(
+        state,
+        AuditEntry {
+            action: action.to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(step_id.clone()),
+            codelab_id: Some(id.to_string()),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(json!({ "revision_id": revision_id })),
+        },
+    )
+    .await;
+
+    Ok((
+        [(header::ETAG, version_etag(change.steps_version))],
+        Json(StepChangeResponse {
+            step_id,
+            revision_id,
+            steps_version: change.steps_version,
+            steps,
+        }),
+    )
+        .into_response())
+}
+
+/// Inserts a step at a 1-based position, appending it when none is given.
+///
+/// Later steps shift down and attendees on them move along.
+pub async fn insert_step(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    headers: HeaderMap,
+    Json(payload): Json<InsertStepPayload>,
+) -> Result<Response, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    validate_step_content(&payload.title, &payload.content_markdown)?;
+    let mut change = match begin_step_change(&state, &id, &headers).await {
+        Ok(change) => change,
+        Err(response) => return Ok(response),
+    };
+    if change.order.len() >= MAX_STEPS {
+        return Err(bad_request("steps exceed limit"));
+    }
+    let last = change.order.len() as i32 + 1;
+    let position = payload.position.unwrap_or(last);
+    if !(1..=last).contains(&position) {
+        return Err(bad_request(&format!(
+            "position must be between 1 and {last}"
+        )));
+    }
+
+    let step_id = uuid::Uuid::new_v4().to_string();
+    sqlx::query(&state.q(
+        "INSERT INTO steps (id, codelab_id, step_number, title, content_markdown) VALUES (?, ?, ?, ?, ?)",
+    ))
+    .bind(&step_id)
+    .bind(&id)
+    .bind(position)
+    .bind(&payload.title)
+    .bind(&payload.content_markdown)
+    .execute(&mut *change.tx)
+    .await
+    .map_err(internal_error)?;
+    change
+        .order
+        .insert((position - 1) as usize, step_id.clone());
+    renumber_steps(&state, &mut change.tx, &id, &change.order)
+        .await
+        .map_err(internal_error)?;
+
+    finish_step_change(
+        &state,
+        change,
+        &id,
+        step_id,
+        admin,
+        info,
+        "codelab_step_insert",
+    )
+    .await
+}
+
+/// Replaces the title and markdown of one step, keeping its position.
+pub async fn update_step(
+    Path((id, step_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateStepPayload>,
+) -> Result<Response, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    validate_step_content(&payload.title, &payload.content_markdown)?;
+    let mut change = match begin_step_change(&state, &id, &headers).await {
+        Ok(change) => change,
+        Err(response) => return Ok(response),
+    };
+    if !change.order.contains(&step_id) {
+        return Err(step_not_found());
+    }
+    sqlx::query(
+        &state
+            .q("UPDATE steps SET title = ?, content_markdown = ? WHERE id = ? AND codelab_id = ?"),
+    )
+    .bind(&payload.title)
+    .bind(&payload.content_markdown)
+    .bind(&step_id)
+    .bind(&id)
+    .execute(&mut *change.tx)
+    .await
+    .map_err(internal_error)?;
+
+    finish_step_change(
+        &state,
+        change,
+        &id,
+        step_id,
+        admin,
+        info,
+        "codelab_step_update",
+    )
+    .await
+}
+
+/// Deletes one step and closes the gap it leaves.
+///
+/// Attendees on the deleted step continue with the step that followed it.
+pub async fn delete_step(
+    Path((id, step_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    let mut change = match begin_step_change(&state, &id, &headers).await {
+        Ok(change) => change,
+        Err(response) => return Ok(response),
+    };
+    let Some(index) = change
+        .order
+        .iter()
+        .position(|candidate| *candidate == step_id)
+    else {
+        return Err(step_not_found());
+    };
+    if change.order.len() == 1 {
+        return Err(bad_request("steps cannot be empty"));
+    }
+    sqlx::query(&state.q("DELETE FROM steps WHERE id = ? AND codelab_id = ?"))
+        .bind(&step_id)
+        .bind(&id)
+        .execute(&mut *change.tx)
+        .await
+        .map_err(internal_error)?;
+    change.order.remove(index);
+    renumber_steps(&state, &mut change.tx, &id, &change.order)
+        .await
+        .map_err(internal_error)?;
+
+    finish_step_change(
+        &state,
+        change,
+        &id,
+        step_id,
+        admin,
+        info,
+        "codelab_step_delete",
+    )
+    .await
+}
+
+/// Moves one step to a 1-based position; the steps in between shift by one.
+pub async fn move_step(
+    Path((id, step_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    headers: HeaderMap,
+    Json(payload): Json<MoveStepPayload>,
+) -> Result<Response, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::EditContent)
+        .await?;
+    let mut change = match begin_step_change(&state, &id, &headers).await {
+        Ok(change) => change,
+        Err(response) => return Ok(response),
+    };
+    let Some(index) = change
+        .order
+        .iter()
+        .position(|candidate| *candidate == step_id)
+    else {
+        return Err(step_not_found());
+    };
+    let last = change.order.len() as i32;
+    if !(1..=last).contains(&payload.position) {
+        return Err(bad_request(&format!(
+            "position must be between 1 and {last}"
+        )));
+    }
+    let moved = change.order.remove(index);
+    change.order.insert((payload.position - 1) as usize, moved);
+    renumber_steps(&state, &mut change.tx, &id, &change.order)
+        .await
+        .map_err(internal_error)?;
+
+    finish_step_change(
+        &state,
+        change,
+        &id,
+        step_id,
+        admin,
+        info,
+        "codelab_step_move",
+    )
+    .await
+}
@@ -2,7 +2,7 @@ use crate::domain::models::{CreateSubmissionLink, Submission, SubmissionWithAtte
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
 use crate::infrastructure::db_models::SubmissionWithAttendeeRaw;
-use crate::middleware::auth::AuthSession;
+use crate::middleware::auth::{AuthSession, Permission};
 use crate::middleware::request_info::RequestInfo;
 use crate::utils::error::{bad_request, forbidden, internal_error, unauthorized};
 use axum::{
@@ -249,12 +249,16 @@ pub async fn get_submissions(
     Path(codelab_id): Path<String>,
     session: AuthSession,
 ) -> Result<Json<Vec<SubmissionWithAttendee>>, (StatusCode, String)> {
-    let claims = match session.claims {
+    let claims = match session.claims.clone() {
         Some(claims) => claims,
         None => return Err(unauthorized()),
     };
     tracing::debug!("Fetching submissions for codelab: {}", codelab_id);
-    let (query, bind_attendee) = if claims.role == "admin" {
+    let (query, bind_attendee) = if session
+        .codelab_staff_claims(&state, &codelab_id, Permission::RunSessions)
+        .await?
+        .is_some()
+    {
         (
             r#"
             SELECT 
@@ -363,7 +367,7 @@ pub async fn delete_submission(
     session: AuthSession,
     info: RequestInfo,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    let claims = match session.claims {
+    let claims = match session.claims.clone() {
         Some(claims) => claims,
         None => return Err(unauthorized()),
     };
@@ -378,7 +382,11 @@ pub async fn delete_submission(
     let (file_path, submission_codelab_id, submission_attendee_id, submission_type) =
         submission_row.ok_or((StatusCode::NOT_FOUND, "Submission not found".to_string()))?;
 
-    if claims.role == "admin" {
+    if session
+        .codelab_staff_claims(&state, &submission_codelab_id, Permission::RunSessions)
+        .await?
+        .is_some()
+    {
         // allowed
     } else if claims.role == "attendee"
         && claims.sub == submission_attendee_id
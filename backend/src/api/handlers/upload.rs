@@ -1,8 +1,8 @@
 use crate::infrastructure::audit::{record_audit, AuditEntry};
 use crate::infrastructure::database::AppState;
-use crate::middleware::auth::AuthSession;
+use crate::middleware::auth::{AuthSession, Permission};
 use crate::middleware::request_info::RequestInfo;
-use crate::utils::error::{bad_request, internal_error, unauthorized};
+use crate::utils::error::{bad_request, forbidden, internal_error, unauthorized};
 use axum::{extract::State, http::StatusCode, response::Json};
 use axum_extra::extract::Multipart;
 use image::ImageReader;
@@ -49,6 +49,10 @@ pub async fn upload_image(
     mut multipart: Multipart,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     let (actor_type, actor_id, codelab_id) = if let Ok(admin) = session.require_admin() {
+        // Tokens upload images only as part of editing content.
+        if admin.is_api_token() && !admin.has_permission(Permission::EditContent) {
+            return Err(forbidden());
+        }
         ("admin".to_string(), Some(admin.sub), None)
     } else if let Ok(attendee) = session.require_attendee() {
         (
@@ -0,0 +1,475 @@
+use crate::domain::models::{
+    CodelabStaffMember, CreateStaffUserPayload, StaffUser, UpdateStaffUserPayload,
+};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::sessions::{revoke_sessions, SessionFilter};
+use crate::middleware::auth::{AuthSession, Permission, StaffRole};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::crypto::hash_password;
+use crate::utils::error::{bad_request, internal_error};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const MAX_USERNAME_LEN: usize = 64;
+const MAX_DISPLAY_NAME_LEN: usize = 128;
+const MIN_PASSWORD_LEN: usize = 8;
+const MAX_PASSWORD_LEN: usize = 1024;
+
+const STAFF_USER_COLUMNS: &str =
+    "id, username, display_name, role, created_at, updated_at, last_login_at";
+
+/// Lists staff accounts. Owner only.
+pub async fn list_users(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<Vec<StaffUser>>, (StatusCode, String)> {
+    session.require_permission(Permission::ManageUsers)?;
+    let users = sqlx::query_as::<_, StaffUser>(&state.q(&format!(
+        "SELECT {STAFF_USER_COLUMNS} FROM users ORDER BY username"
+    )))
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(users))
+}
+
+/// Creates a staff account with a hashed password. Owner only.
+pub async fn create_user(
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<CreateStaffUserPayload>,
+) -> Result<(StatusCode, Json<StaffUser>), (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageUsers)?;
+    let username = validate_username(&payload.username)?;
+    if username == state.admin_id {
+        return Err((
+            StatusCode::CONFLICT,
+            "Username is reserved for the built-in administrator".to_string(),
+        ));
+    }
+    validate_password(&payload.password)?;
+    let role = parse_role(&payload.role)?;
+    let display_name = normalize_display_name(payload.display_name)?;
+
+    let existing: Option<(String,)> =
+        sqlx::query_as(&state.q("SELECT id FROM users WHERE username = ?"))
+            .bind(&username)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    if existing.is_some() {
+        return Err((StatusCode::CONFLICT, "Username already exists".to_string()));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let password = payload.password;
+    let password_hash = tokio::task::spawn_blocking(move || hash_password(&password))
+        .await
+        .map_err(internal_error)?;
+    sqlx::query(&state.q(
+        "INSERT INTO users (id, username, display_name, password_hash, role) VALUES (?, ?, ?, ?, ?)",
+    ))
+    .bind(&id)
+    .bind(&username)
+    .bind(&display_name)
+    .bind(&password_hash)
+    .bind(role.as_str())
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "user_create".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(id.clone()),
+            codelab_id: None,
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({ "username": username, "role": role.as_str() })),
+        },
+    )
+    .await;
+
+    let user = fetch_user(&state, &id).await?;
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+/// Updates a staff account's role, display name, or password. Owner only.
+pub async fn update_user(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+    Json(payload): Json<UpdateStaffUserPayload>,
+) -> Result<Json<StaffUser>, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageUsers)?;
+    let current = fetch_user(&state, &user_id).await?;
+
+    let role = payload.role.as_deref().map(parse_role).transpose()?;
+    if user_id == admin.sub && role.is_some_and(|role| role != StaffRole::Owner) {
+        return Err(bad_request("You cannot remove your own owner role"));
+    }
+    let display_name = payload
+        .display_name
+        .map(|value| normalize_display_name(Some(value)))
+        .transpose()?;
+    let password_hash = match payload.password {
+        Some(password) => {
+            validate_password(&password)?;
+            Some(
+                tokio::task::spawn_blocking(move || hash_password(&password))
+                    .await
+                    .map_err(internal_error)?,
+            )
+        }
+        None => None,
+    };
+
+    sqlx::query(&state.q("UPDATE users SET
+            role = COALESCE(?, role),
+            display_name = CASE WHEN ? THEN ? ELSE display_name END,
+            password_hash = COALESCE(?, password_hash),
+            updated_at = CAST(CURRENT_TIMESTAMP AS TEXT)
+         WHERE id = ?"))
+    .bind(role.map(|role| role.as_str()))
+    .bind(display_name.is_some())
+    .bind(display_name.clone().flatten())
+    .bind(&password_hash)
+    .bind(&user_id)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    // Sessions carry the role they were issued with, so a role or password
+    // change must end them for the new credentials to take effect.
+    let role_changed = role.is_some_and(|role| role.as_str() != current.role);
+    let sessions_revoked = if role_changed || password_hash.is_some() {
+        let filter = SessionFilter {
+            subject: Some(user_id.clone()),
+            codelab_id: None,
+        };
+        revoke_sessions(&state, &filter, &admin.sub)
+            .await
+            .map_err(internal_error)?
+    } else {
+        0
+    };
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "user_update".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(user_id.clone()),
+            codelab_id: None,
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({
+                "username": current.username,
+                "previous_role": current.role,
+                "role": role.map(|role| role.as_str()).unwrap_or(current.role.as_str()),
+                "password_changed": password_hash.is_some(),
+                "sessions_revoked": sessions_revoked,
+            })),
+        },
+    )
+    .await;
+
+    Ok(Json(fetch_user(&state, &user_id).await?))
+}
+
+/// Deletes a staff account. Owner only; owners cannot delete themselves.
+pub async fn delete_user(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageUsers)?;
+    if user_id == admin.sub {
+        return Err(bad_request("You cannot delete your own account"));
+    }
+    let user = fetch_user(&state, &user_id).await?;
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM codelab_staff WHERE user_id = ?"))
+        .bind(&user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM api_tokens WHERE user_id = ?"))
+        .bind(&user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    for table in ["staff_recovery_codes", "staff_totp"] {
+        sqlx::query(&state.q(&format!("DELETE FROM {table} WHERE subject = ?")))
+            .bind(&user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+    }
+    sqlx::query(&state.q("DELETE FROM users WHERE id = ?"))
+        .bind(&user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+    let filter = SessionFilter {
+        subject: Some(user_id.clone()),
+        codelab_id: None,
+    };
+    revoke_sessions(&state, &filter, &admin.sub)
+        .await
+        .map_err(internal_error)?;
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "user_delete".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(user_id),
+            codelab_id: None,
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({ "username": user.username, "role": user.role })),
+        },
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists staff assigned to a codelab. Owner only.
+pub async fn list_codelab_staff(
+    Path(codelab_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<Vec<CodelabStaffMember>>, (StatusCode, String)> {
+    session.require_permission(Permission::ManageUsers)?;
+    ensure_codelab_exists(&state, &codelab_id).await?;
+    let members = sqlx::query_as::<_, CodelabStaffMember>(&state.q(
+        "SELECT u.id AS user_id, u.username, u.display_name, u.role, cs.created_at AS assigned_at \
+         FROM codelab_staff cs JOIN users u ON u.id = cs.user_id \
+         WHERE cs.codelab_id = ? ORDER BY u.username",
+    ))
+    .bind(&codelab_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(members))
+}
+
+/// Assigns a staff account to a codelab. Owner only; repeating it is a no-op.
+pub async fn assign_codelab_staff(
+    Path((codelab_id, user_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageUsers)?;
+    ensure_codelab_exists(&state, &codelab_id).await?;
+    let user = fetch_user(&state, &user_id).await?;
+
+    let existing: Option<(String,)> = sqlx::query_as(
+        &state.q("SELECT user_id FROM codelab_staff WHERE codelab_id = ? AND user_id = ?"),
+    )
+    .bind(&codelab_id)
+    .bind(&user_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    if existing.is_some() {
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
+    assign_staff_to_codelab(&state, &codelab_id, &user_id)
+        .await
+        .map_err(internal_error)?;
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "codelab_staff_assign".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(user_id),
+            codelab_id: Some(codelab_id),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({ "username": user.username, "role": user.role })),
+        },
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Removes a staff account from a codelab. Owner only.
+pub async fn unassign_codelab_staff(
+    Path((codelab_id, user_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session.require_permission(Permission::ManageUsers)?;
+    let result =
+        sqlx::query(&state.q("DELETE FROM codelab_staff WHERE codelab_id = ? AND user_id = ?"))
+            .bind(&codelab_id)
+            .bind(&user_id)
+            .execute(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Staff assignment not found".to_string(),
+        ));
+    }
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "codelab_staff_unassign".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(user_id),
+            codelab_id: Some(codelab_id),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: None,
+        },
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Records that `user_id` may work on `codelab_id`.
+///
+/// Used by the assignment endpoint and when a non-owner creates a codelab, so
+/// authors keep access to the content they author.
+pub(crate) async fn assign_staff_to_codelab(
+    state: &AppState,
+    codelab_id: &str,
+    user_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(&state.q("INSERT INTO codelab_staff (codelab_id, user_id) VALUES (?, ?)"))
+        .bind(codelab_id)
+        .bind(user_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(())
+}
+
+async fn ensure_codelab_exists(
+    state: &AppState,
+    codelab_id: &str,
+) -> Result<(), (StatusCode, String)> {
+    let exists: Option<(String,)> =
+        sqlx::query_as(&state.q("SELECT id FROM codelabs WHERE id = ?"))
+            .bind(codelab_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    exists
+        .map(|_| ())
+        .ok_or((StatusCode::NOT_FOUND, "Codelab not found".to_string()))
+}
+
+async fn fetch_user(state: &AppState, user_id: &str) -> Result<StaffUser, (StatusCode, String)> {
+    sqlx::query_as::<_, StaffUser>(&state.q(&format!(
+        "SELECT {STAFF_USER_COLUMNS} FROM users WHERE id = ?"
+    )))
+    .bind(user_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .ok_or((StatusCode::NOT_FOUND, "User not found".to_string()))
+}
+
+fn validate_username(value: &str) -> Result<String, (StatusCode, String)> {
+    let username = value.trim();
+    if username.is_empty() {
+        return Err(bad_request("username is required"));
+    }
+    if username.chars().count() > MAX_USERNAME_LEN {
+        return Err(bad_request("username is too long"));
+    }
+    if username
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err(bad_request("username must not contain whitespace"));
+    }
+    Ok(username.to_string())
+}
+
+fn validate_password(value: &str) -> Result<(), (StatusCode, String)> {
+    let len = value.chars().count();
+    if len < MIN_PASSWORD_LEN {
+        return Err(bad_request("password must be at least 8 characters"));
+    }
+    if len > MAX_PASSWORD_LEN {
+        return Err(bad_request("password is too long"));
+    }
+    Ok(())
+}
+
+fn parse_role(value: &str) -> Result<StaffRole, (StatusCode, String)> {
+    StaffRole::parse(value.trim())
+        .ok_or_else(|| bad_request("role must be one of owner, author, facilitator, ta"))
+}
+
+fn normalize_display_name(value: Option<String>) -> Result<Option<String>, (StatusCode, String)> {
+    let value = value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if value
+        .as_ref()
+        .is_some_and(|value| value.chars().count() > MAX_DISPLAY_NAME_LEN)
+    {
+        return Err(bad_request("display_name is too long"));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validators_accept_and_reject_expected_values() {
+        assert_eq!(validate_username("  alice ").unwrap(), "alice");
+        assert!(validate_username("").is_err());
+        assert!(validate_username("al ice").is_err());
+        assert!(validate_username(&"a".repeat(65)).is_err());
+
+        assert!(validate_password("12345678").is_ok());
+        assert!(validate_password("short").is_err());
+
+        assert_eq!(parse_role(" ta ").unwrap(), StaffRole::Ta);
+        assert!(parse_role("admin").is_err());
+
+        assert_eq!(
+            normalize_display_name(Some("  ".to_string())).unwrap(),
+            None
+        );
+        assert_eq!(
+            normalize_display_name(Some(" Alice ".to_string())).unwrap(),
+            Some("Alice".to_string())
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use crate::domain::models::{Attendee, AttendeePublic, WaitlistEntry, WaitlistStatus};
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::db_models::CodelabWaitlistRow;
+use crate::middleware::auth::{AuthSession, Permission};
+use crate::middleware::request_info::RequestInfo;
+use crate::utils::error::internal_error;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use sqlx::AnyConnection;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Returns the one-based queue position of a waitlist entry.
+pub(crate) async fn waitlist_position(
+    state: &AppState,
+    entry: &CodelabWaitlistRow,
+) -> Result<i64, sqlx::Error> {
+    let (ahead,): (i64,) = sqlx::query_as(&state.q(
+        "SELECT COUNT(*) FROM codelab_waitlist WHERE codelab_id = ? AND (created_at < ? OR (created_at = ? AND id < ?))",
+    ))
+    .bind(&entry.codelab_id)
+    .bind(&entry.created_at)
+    .bind(&entry.created_at)
+    .bind(&entry.id)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(ahead + 1)
+}
+
+/// Finds the waitlist entry registered under `name` for a codelab.
+pub(crate) async fn find_waitlist_entry(
+    state: &AppState,
+    codelab_id: &str,
+    name: &str,
+) -> Result<Option<CodelabWaitlistRow>, sqlx::Error> {
+    sqlx::query_as::<_, CodelabWaitlistRow>(
+        &state.q("SELECT * FROM codelab_waitlist WHERE codelab_id = ? AND name = ?"),
+    )
+    .bind(codelab_id)
+    .bind(name)
+    .fetch_optional(&state.pool)
+    .await
+}
+
+/// Adds a learner to the end of the waitlist on `conn`, usually inside the
+/// registration transaction. `code` must already be encrypted.
+pub(crate) async fn enqueue_waitlist(
+    state: &AppState,
+    conn: &mut AnyConnection,
+    codelab_id: &str,
+    name: &str,
+    code: &str,
+    email: Option<&str>,
+    cohort_id: Option<&str>,
+) -> Result<CodelabWaitlistRow, sqlx::Error> {
+    let id = Uuid::new_v4().to_string();
+    sqlx::query(&state.q(
+        "INSERT INTO codelab_waitlist (id, codelab_id, name, code, email, cohort_id) VALUES (?, ?, ?, ?, ?, ?)",
+    ))
+    .bind(&id)
+    .bind(codelab_id)
+    .bind(name)
+    .bind(code)
+    .bind(email)
+    .bind(cohort_id)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_as::<_, CodelabWaitlistRow>(&state.q("SELECT * FROM codelab_waitlist WHERE id = ?"))
+        .bind(&id)
+        .fetch_one(conn)
+        .await
+}
+
+/// Builds the registration response for a waitlisted learner.
+pub(crate) async fn waitlist_status(
+    state: &AppState,
+    entry: &CodelabWaitlistRow,
+) -> Result<WaitlistStatus, sqlx::Error> {
+    Ok(WaitlistStatus {
+        status: "waitlisted".to_string(),
+        codelab_id: entry.codelab_id.clone(),
+        waitlist_id: entry.id.clone(),
+        position: waitlist_position(state, entry).await?,
+    })
+}
+
+/// Lists waitlisted learners of a codelab in queue order.
+pub async fn list_waitlist(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+) -> Result<Json<Vec<WaitlistEntry>>, (StatusCode, String)> {
+    session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    let rows = sqlx::query_as::<_, CodelabWaitlistRow>(
+        &state.q("SELECT * FROM codelab_waitlist WHERE codelab_id = ? ORDER BY created_at, id"),
+    )
+    .bind(&id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    Ok(Json(
+        rows.into_iter()
+            .enumerate()
+            .map(|(index, row)| WaitlistEntry {
+                id: row.id,
+                codelab_id: row.codelab_id,
+                name: row.name,
+                email: row.email,
+                position: index as i64 + 1,
+                cohort_id: row.cohort_id,
+                created_at: row.created_at,
+            })
+            .collect(),
+    ))
+}
+
+/// Admits a waitlisted learner as an attendee, regardless of the seat limit.
+///
+/// The learner keeps their nickname and code and signs in by registering again.
+pub async fn promote_waitlist_entry(
+    Path((id, entry_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<Json<AttendeePublic>, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let entry = sqlx::query_as::<_, CodelabWaitlistRow>(
+        &state.q("SELECT * FROM codelab_waitlist WHERE id = ? AND codelab_id = ?"),
+    )
+    .bind(&entry_id)
+    .bind(&id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?
+    .ok_or((
+        StatusCode::NOT_FOUND,
+        "Waitlist entry not found".to_string(),
+    ))?;
+
+    let attendee_id = Uuid::new_v4().to_string();
+    sqlx::query(&state.q(
+        "INSERT INTO attendees (id, codelab_id, name, code, email, current_step, cohort_id) VALUES (?, ?, ?, ?, ?, 1, ?)",
+    ))
+    .bind(&attendee_id)
+    .bind(&id)
+    .bind(&entry.name)
+    .bind(&entry.code)
+    .bind(&entry.email)
+    .bind(&entry.cohort_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    sqlx::query(&state.q("DELETE FROM codelab_waitlist WHERE id = ?"))
+        .bind(&entry_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    let attendee = sqlx::query_as::<_, Attendee>(&state.q("SELECT * FROM attendees WHERE id = ?"))
+        .bind(&attendee_id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(internal_error)?;
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "waitlist_promote".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(attendee_id),
+            codelab_id: Some(id),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: Some(serde_json::json!({ "waitlist_id": entry_id })),
+        },
+    )
+    .await;
+
+    Ok(Json(AttendeePublic::from(attendee)))
+}
+
+/// Removes a learner from the waitlist without admitting them.
+pub async fn remove_waitlist_entry(
+    Path((id, entry_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    session: AuthSession,
+    info: RequestInfo,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let admin = session
+        .require_codelab_permission(&state, &id, Permission::RunSessions)
+        .await?;
+    let deleted =
+        sqlx::query(&state.q("DELETE FROM codelab_waitlist WHERE id = ? AND codelab_id = ?"))
+            .bind(&entry_id)
+            .bind(&id)
+            .execute(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    if deleted.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Waitlist entry not found".to_string(),
+        ));
+    }
+
+    record_audit(
+        &state,
+        AuditEntry {
+            action: "waitlist_remove".to_string(),
+            actor_type: "admin".to_string(),
+            actor_id: Some(admin.sub),
+            target_id: Some(entry_id),
+            codelab_id: Some(id),
+            ip: Some(info.ip),
+            user_agent: info.user_agent,
+            metadata: None,
+        },
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,6 +1,9 @@
+use crate::api::handlers::cohorts::{fetch_cohort, room_key};
 use crate::infrastructure::database::AppState;
-use crate::middleware::auth::{AuthSession, Role};
-use crate::utils::error::forbidden;
+use crate::middleware::auth::{
+    staff_can_access_codelab, verify_session_token, AuthSession, Permission, Role,
+};
+use crate::utils::error::{forbidden, internal_error};
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
@@ -28,6 +31,8 @@ pub struct WsQuery {
     pub role_hint: Option<String>,
     /// Optional session token fallback passed via query string.
     pub token: Option<String>,
+    /// Cohort room staff want to join; attendees always join their own cohort.
+    pub cohort_id: Option<String>,
 }
 
 /// Upgrades an authenticated request into the codelab websocket channel.
@@ -45,10 +50,10 @@ pub async fn ws_handler(
     };
 
     // If session claims are missing, try the token from query string
-    let claims = match claims {
-        Some(c) => Some(c),
-        None => query.token.as_ref().and_then(|t| {
-            state.auth.verify_token(t).filter(|c| {
+    let claims = match (claims, query.token.as_deref()) {
+        (Some(c), _) => Some(c),
+        (None, Some(token)) => match verify_session_token(&state, token).await {
+            Ok(verified) => verified.filter(|c| {
                 // Ensure the token role matches the requested role hint if provided
                 match query.role_hint.as_deref() {
                     Some("admin") => c.role == Role::Admin.as_str(),
@@ -58,8 +63,10 @@ pub async fn ws_handler(
                     }
                     _ => true,
                 }
-            })
-        }),
+            }),
+            Err(err) => return internal_error(err).into_response(),
+        },
+        (None, None) => None,
     };
 
     let claims = match claims {
@@ -78,7 +85,33 @@ pub async fn ws_handler(
             return forbidden().into_response();
         }
     }
-    ws.on_upgrade(move |socket| handle_socket(socket, id, state, claims))
+    if claims.role == Role::Admin.as_str() {
+        if !claims.has_permission(Permission::AssistLearners) {
+            return forbidden().into_response();
+        }
+        match staff_can_access_codelab(&state, &claims, &id).await {
+            Ok(true) => {}
+            Ok(false) => return forbidden().into_response(),
+            Err(err) => return internal_error(err).into_response(),
+        }
+    }
+    let staff_cohort = match query.cohort_id.filter(|cohort_id| !cohort_id.is_empty()) {
+        Some(cohort_id) if claims.role == Role::Admin.as_str() => {
+            match fetch_cohort(&state, &id, &cohort_id).await {
+                Ok(Some(_)) => Some(cohort_id),
+                Ok(None) => {
+                    return (
+                        axum::http::StatusCode::NOT_FOUND,
+                        "Cohort not found".to_string(),
+                    )
+                        .into_response()
+                }
+                Err(err) => return internal_error(err).into_response(),
+            }
+        }
+        _ => None,
+    };
+    ws.on_upgrade(move |socket| handle_socket(socket, id, state, claims, staff_cohort))
 }
 
 async fn handle_socket(
@@ -86,31 +119,34 @@ async fn handle_socket(
     codelab_id: String,
     state: Arc<AppState>,
     claims: crate::middleware::auth::SessionClaims,
+    staff_cohort: Option<String>,
 ) {
     let (mut sender, mut receiver) = socket.split();
     let (tx_ws, mut rx_ws) = tokio::sync::mpsc::unbounded_channel::<Message>();
     let session_id = generate_session_id();
 
-    let (user_id, sender_name, role) = if Role::from_str(&claims.role) == Some(Role::Admin) {
-        (
-            "facilitator".to_string(),
-            "Facilitator".to_string(),
-            Role::Admin,
-        )
-    } else {
-        let attendee_id = claims.sub.clone();
-        let name: Option<(String,)> =
-            sqlx::query_as(&state.q("SELECT name FROM attendees WHERE id = ?"))
-                .bind(&attendee_id)
-                .fetch_optional(&state.pool)
-                .await
-                .ok()
-                .flatten();
-        let display_name = name
-            .map(|row| row.0)
-            .unwrap_or_else(|| "Attendee".to_string());
-        (attendee_id, display_name, Role::Attendee)
-    };
+    let (user_id, sender_name, role, cohort_id) =
+        if Role::from_str(&claims.role) == Some(Role::Admin) {
+            (
+                "facilitator".to_string(),
+                "Facilitator".to_string(),
+                Role::Admin,
+                staff_cohort,
+            )
+        } else {
+            let attendee_id = claims.sub.clone();
+            let row: Option<(String, Option<String>)> =
+                sqlx::query_as(&state.q("SELECT name, cohort_id FROM attendees WHERE id = ?"))
+                    .bind(&attendee_id)
+                    .fetch_optional(&state.pool)
+                    .await
+                    .ok()
+                    .flatten();
+            let (display_name, cohort_id) = row.unwrap_or_else(|| ("Attendee".to_string(), None));
+            (attendee_id, display_name, Role::Attendee, cohort_id)
+        };
+    // Each cohort gets its own room; codelabs without cohorts use the codelab room.
+    let room = room_key(&codelab_id, cohort_id.as_deref());
 
     // Add session to the list of sessions for this user (supports multiple tabs)
     let session_key = (codelab_id.clone(), user_id.clone());
@@ -122,7 +158,7 @@ async fn handle_socket(
 
     let tx_broadcast = state
         .channels
-        .entry(codelab_id.clone())
+        .entry(room.clone())
         .or_insert_with(|| {
             let (tx, _) = broadcast::channel(100);
             tx
@@ -132,7 +168,7 @@ async fn handle_socket(
     let mut rx_broadcast = tx_broadcast.subscribe();
 
     // Check if screen sharing is active and notify the new connection
-    if let Some(is_active) = state.active_screen_shares.get(&codelab_id) {
+    if let Some(is_active) = state.active_screen_shares.get(&room) {
         if *is_active {
             let payload = serde_json::json!({
                 "type": "screen_share_status",
@@ -163,6 +199,8 @@ async fn handle_socket(
 
     let state_clone = state.clone();
     let codelab_id_clone = codelab_id.clone();
+    let room_clone = room.clone();
+    let cohort_id_clone = cohort_id.clone();
     let sender_name_clone = sender_name.clone();
     let user_id_clone = user_id.clone();
     let role_clone = role.clone();
@@ -180,11 +218,13 @@ async fn handle_socket(
 
                         // Persist to DB
                         let msg_id = uuid::Uuid::new_v4().to_string();
-                        let _ = sqlx::query(&state_clone.q("INSERT INTO chat_messages (id, codelab_id, sender_name, message, msg_type) VALUES (?, ?, ?, ?, 'chat')"))
+                        let _ = sqlx::query(&state_clone.q("INSERT INTO chat_messages (id, codelab_id, sender_name, message, msg_type, sender_id, cohort_id) VALUES (?, ?, ?, ?, 'chat', ?, ?)"))
                             .bind(&msg_id)
                             .bind(&codelab_id_clone)
                             .bind(&sender_name_clone)
                             .bind(message)
+                            .bind(&user_id_clone)
+                            .bind(&cohort_id_clone)
                             .execute(&state_clone.pool)
                             .await;
 
@@ -206,13 +246,14 @@ async fn handle_socket(
 
                             // Persist to DB with sender_id
                             let msg_id = uuid::Uuid::new_v4().to_string();
-                            let _ = sqlx::query(&state_clone.q("INSERT INTO chat_messages (id, codelab_id, sender_name, message, msg_type, target_id, sender_id) VALUES (?, ?, ?, ?, 'dm', ?, ?)"))
+                            let _ = sqlx::query(&state_clone.q("INSERT INTO chat_messages (id, codelab_id, sender_name, message, msg_type, target_id, sender_id, cohort_id) VALUES (?, ?, ?, ?, 'dm', ?, ?, ?)"))
                                 .bind(&msg_id)
                                 .bind(&codelab_id_clone)
                                 .bind(&sender_name_clone)
                                 .bind(message)
                                 .bind(target_id)
                                 .bind(&user_id_clone)
+                                .bind(&cohort_id_clone)
                                 .execute(&state_clone.pool)
                                 .await;
 
@@ -303,11 +344,11 @@ async fn handle_socket(
                         if status == "facilitator_started" {
                             state_clone
                                 .active_screen_shares
-                                .insert(codelab_id_clone.clone(), true);
+                                .insert(room_clone.clone(), true);
                         } else if status == "facilitator_stopped" {
                             state_clone
                                 .active_screen_shares
-                                .insert(codelab_id_clone.clone(), false);
+                                .insert(room_clone.clone(), false);
                         }
 
                         let payload = serde_json::json!({
@@ -401,6 +442,16 @@ async fn handle_socket(
     }
 }
 
+/// Sends a room-wide event to every cohort room of a codelab.
+pub(crate) fn broadcast_to_codelab(state: &AppState, codelab_id: &str, payload: &str) {
+    let cohort_prefix = format!("{codelab_id}/");
+    for channel in state.channels.iter() {
+        if channel.key() == codelab_id || channel.key().starts_with(&cohort_prefix) {
+            let _ = channel.value().send(payload.to_string());
+        }
+    }
+}
+
 /// Remove a specific session from the sessions map
 fn cleanup_session(state: &Arc<AppState>, codelab_id: &str, user_id: &str, session_id: &str) {
     let key = (codelab_id.to_string(), user_id.to_string());
@@ -2,17 +2,23 @@
 
 use crate::api::handlers::codelabs::get_reference_codelabs;
 use crate::api::handlers::{
-    admin::{check_updates, get_session, login, logout, update_settings},
+    admin::{check_updates, get_session, login, login_mfa, logout, update_settings},
     ai::{
         add_ai_message, create_ai_thread, delete_ai_thread, get_ai_conversations, get_ai_messages,
         get_ai_threads, proxy_gemini_stream, save_ai_conversation,
     },
+    api_tokens::{create_api_token, list_api_tokens, revoke_api_token},
     attendees::{
-        complete_codelab, get_attendees, get_certificate, get_help_requests, register_attendee,
-        request_help, resolve_help_request,
+        complete_codelab, get_attendees, get_help_requests, register_attendee, request_help,
+        resolve_help_request,
     },
     audit::get_audit_logs,
+    authoring::authoring_ws_handler,
     backup::{export_backup, inspect_backup, restore_backup},
+    certificates::{
+        get_certificate, get_certificate_credential, get_certificate_keys, get_certificate_pdf,
+        get_credential_issuer, revoke_certificate, verify_certificate, verify_credential,
+    },
     cli::{
         approve_cli_auth, cli_auth_page, exchange_cli_auth, get_cli_runtime, poll_cli_auth,
         start_cli_auth,
@@ -26,14 +32,36 @@ use crate::api::handlers::{
         get_codeserver_info, list_branches, list_files, list_folder_files, list_folders, read_file,
         read_folder_file, update_branch_files, update_folder_files,
     },
+    cohorts::{create_cohort, delete_cohort, list_cohorts, update_cohort},
     feedback::{get_feedback, submit_feedback},
     inline_comments::{
         create_inline_comment, delete_inline_comment, get_inline_comments, reply_inline_comment,
     },
+    join_access::{
+        create_codelab_invite, get_codelab_passcode, get_join_info, list_codelab_invites,
+        revoke_codelab_invite, set_codelab_passcode,
+    },
     materials::{add_material, delete_material, get_materials, upload_material_file},
-    quizzes::{get_quiz_submissions, get_quizzes, submit_quiz, update_quizzes},
+    mfa::{
+        confirm_totp, disable_totp, get_mfa_status, regenerate_recovery_codes, reset_user_mfa,
+        start_totp_enrollment,
+    },
+    oidc::{complete_oidc_login, get_oidc_status, start_oidc_login},
+    privacy::{erase_attendee_data, export_attendee_data, find_attendees},
+    publishing::{get_publication, publish_codelab},
+    quizzes::{get_quiz_attempts, get_quiz_submissions, get_quizzes, submit_quiz, update_quizzes},
+    retention::{get_retention_report, run_retention},
+    revisions::{diff_revisions, get_revision, list_revisions, restore_revision},
+    roster::import_roster,
+    sessions::{delete_session, list_sessions, logout_everywhere, revoke_matching_sessions},
+    steps::{delete_step, insert_step, move_step, update_step},
     submissions::{delete_submission, get_submissions, submit_file, submit_link},
     upload::upload_image,
+    users::{
+        assign_codelab_staff, create_user, delete_user, list_codelab_staff, list_users,
+        unassign_codelab_staff, update_user,
+    },
+    waitlist::{list_waitlist, promote_waitlist_entry, remove_waitlist_entry},
     websocket::ws_handler,
 };
 use crate::infrastructure::AppState;
@@ -52,8 +80,13 @@ use tower_http::services::ServeDir;
 fn auth_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/api/login", post(login))
+        .route("/api/login/mfa", post(login_mfa))
         .route("/api/logout", post(logout))
+        .route("/api/logout/all", post(logout_everywhere))
         .route("/api/session", get(get_session))
+        .route("/api/auth/oidc", get(get_oidc_status))
+        .route("/api/auth/oidc/login", get(start_oidc_login))
+        .route("/api/auth/oidc/callback", get(complete_oidc_login))
         .route("/api/cli/runtime", get(get_cli_runtime))
         .route("/api/cli/auth/start", post(start_cli_auth))
         .route("/api/cli/auth/poll/{id}", get(poll_cli_auth))
@@ -67,9 +100,48 @@ fn admin_routes() -> Router<Arc<AppState>> {
         .route("/api/admin/settings", post(update_settings))
         .route("/api/admin/updates", get(check_updates))
         .route("/api/admin/audit-logs", get(get_audit_logs))
+        .route("/api/admin/users", get(list_users).post(create_user))
+        .route(
+            "/api/admin/users/{id}",
+            put(update_user).delete(delete_user),
+        )
+        .route("/api/admin/users/{id}/mfa", delete(reset_user_mfa))
+        .route("/api/admin/mfa", get(get_mfa_status))
+        .route(
+            "/api/admin/mfa/totp",
+            post(start_totp_enrollment).delete(disable_totp),
+        )
+        .route("/api/admin/mfa/totp/confirm", post(confirm_totp))
+        .route(
+            "/api/admin/mfa/recovery-codes",
+            post(regenerate_recovery_codes),
+        )
+        .route(
+            "/api/admin/tokens",
+            get(list_api_tokens).post(create_api_token),
+        )
+        .route("/api/admin/tokens/{id}", delete(revoke_api_token))
+        .route("/api/admin/sessions", get(list_sessions))
+        .route("/api/admin/sessions/revoke", post(revoke_matching_sessions))
+        .route("/api/admin/sessions/{id}", delete(delete_session))
         .route("/api/admin/backup/export", get(export_backup))
         .route("/api/admin/backup/inspect", post(inspect_backup))
         .route("/api/admin/backup/restore", post(restore_backup))
+        .route("/api/admin/privacy/attendees", get(find_attendees))
+        .route(
+            "/api/admin/privacy/attendees/{attendee_id}/export",
+            get(export_attendee_data),
+        )
+        .route(
+            "/api/admin/privacy/attendees/{attendee_id}/erase",
+            post(erase_attendee_data),
+        )
+        .route("/api/admin/retention", get(get_retention_report))
+        .route("/api/admin/retention/run", post(run_retention))
+        .route(
+            "/api/admin/certificates/{id}/revoke",
+            post(revoke_certificate),
+        )
 }
 
 fn codelab_routes() -> Router<Arc<AppState>> {
@@ -83,13 +155,78 @@ fn codelab_routes() -> Router<Arc<AppState>> {
                 .delete(delete_codelab),
         )
         .route("/api/codelabs/{id}/copy", post(copy_codelab))
-        .route("/api/codelabs/{id}/steps", put(update_codelab_steps))
+        .route(
+            "/api/codelabs/{id}/steps",
+            put(update_codelab_steps).post(insert_step),
+        )
+        .route(
+            "/api/codelabs/{id}/steps/{step_id}",
+            put(update_step).delete(delete_step),
+        )
+        .route("/api/codelabs/{id}/steps/{step_id}/move", post(move_step))
+        .route("/api/codelabs/{id}/publish", post(publish_codelab))
+        .route("/api/codelabs/{id}/publication", get(get_publication))
+        .route("/api/codelabs/{id}/revisions", get(list_revisions))
+        .route("/api/codelabs/{id}/revisions/diff", get(diff_revisions))
+        .route(
+            "/api/codelabs/{id}/revisions/{revision_id}",
+            get(get_revision),
+        )
+        .route(
+            "/api/codelabs/{id}/revisions/{revision_id}/restore",
+            post(restore_revision),
+        )
         .route("/api/codelabs/{id}/export", get(export_codelab))
         .route("/api/codelabs/import", post(import_codelab))
         .route("/api/codelabs/{id}/register", post(register_attendee))
+        .route("/api/codelabs/{id}/join", get(get_join_info))
+        .route("/api/codelabs/{id}/waitlist", get(list_waitlist))
+        .route(
+            "/api/codelabs/{id}/cohorts",
+            get(list_cohorts).post(create_cohort),
+        )
+        .route(
+            "/api/codelabs/{id}/cohorts/{cohort_id}",
+            put(update_cohort).delete(delete_cohort),
+        )
+        .route(
+            "/api/codelabs/{id}/waitlist/{entry_id}",
+            delete(remove_waitlist_entry),
+        )
+        .route(
+            "/api/codelabs/{id}/waitlist/{entry_id}/promote",
+            post(promote_waitlist_entry),
+        )
+        .route(
+            "/api/codelabs/{id}/passcode",
+            get(get_codelab_passcode).put(set_codelab_passcode),
+        )
+        .route(
+            "/api/codelabs/{id}/invites",
+            get(list_codelab_invites).post(create_codelab_invite),
+        )
+        .route(
+            "/api/codelabs/{id}/invites/{invite_id}",
+            delete(revoke_codelab_invite),
+        )
         .route("/api/codelabs/{id}/complete", post(complete_codelab))
         .route("/api/codelabs/{id}/attendees", get(get_attendees))
+        .route("/api/codelabs/{id}/attendees/import", post(import_roster))
+        .route("/api/codelabs/{id}/staff", get(list_codelab_staff))
+        .route(
+            "/api/codelabs/{id}/staff/{user_id}",
+            put(assign_codelab_staff).delete(unassign_codelab_staff),
+        )
         .route("/api/certificates/{id}", get(get_certificate))
+        .route(
+            "/api/certificates/{id}/credential",
+            get(get_certificate_credential),
+        )
+        .route("/api/certificates/{id}/pdf", get(get_certificate_pdf))
+        .route("/api/certificates/jwks", get(get_certificate_keys))
+        .route("/api/certificates/issuer", get(get_credential_issuer))
+        .route("/api/certificates/verify", post(verify_credential))
+        .route("/api/certificates/verify/{id}", get(verify_certificate))
         .route(
             "/api/codelabs/{id}/help",
             post(request_help).get(get_help_requests),
@@ -115,6 +252,10 @@ fn codelab_routes() -> Router<Arc<AppState>> {
             get(get_quizzes).put(update_quizzes),
         )
         .route("/api/codelabs/{id}/quizzes/submit", post(submit_quiz))
+        .route(
+            "/api/codelabs/{id}/quizzes/attempts",
+            get(get_quiz_attempts),
+        )
         .route(
             "/api/codelabs/{id}/quizzes/submissions",
             get(get_quiz_submissions),
@@ -174,7 +315,9 @@ fn ai_routes() -> Router<Arc<AppState>> {
 }
 
 fn websocket_routes() -> Router<Arc<AppState>> {
-    Router::new().route("/api/ws/{id}", get(ws_handler))
+    Router::new()
+        .route("/api/ws/{id}", get(ws_handler))
+        .route("/api/ws/{id}/authoring", get(authoring_ws_handler))
 }
 
 fn codeserver_routes() -> Router<Arc<AppState>> {
@@ -1,8 +1,11 @@
 use crate::api::dto::{
     AiRequest, CliRuntimeCapabilities, CliRuntimeInfo, CodeServerInfo, CreateCodeServerRequest,
-    UpdateWorkspaceFilesRequest, WorkspaceFile,
+    SessionFilterQuery, UpdateWorkspaceFilesRequest, WorkspaceFile,
+};
+use crate::cli::client::{
+    AdminLogin, ApiClient, AttendeeRegistration, BackupSummary, UpdateCheckSummary,
+    UploadedMaterial,
 };
-use crate::cli::client::{ApiClient, BackupSummary, UpdateCheckSummary, UploadedMaterial};
 use crate::cli::config::{
     default_config_path, default_profile_session_path, load_config, save_config, CliConfig,
     ConnectionProfile, RuntimePreference,
@@ -12,14 +15,21 @@ use crate::cli::session::{
     clear_session, default_session_path, load_session, save_session, SessionSnapshot, StoredSession,
 };
 use crate::domain::models::{
-    AddAiMessagePayload, Codelab, CreateCodelab, CreateInlineCommentPayload, CreateMaterial,
-    CreateQuiz, CreateStep, Material, Quiz, QuizSubmissionPayload, ReplyInlineCommentPayload,
-    SaveAiConversationPayload, Step, UpdateStepsPayload,
+    AddAiMessagePayload, ApiToken, AttendeeErasure, CertificateVerification, Codelab,
+    CodelabInvite, CodelabRevisionSummary, CodelabStaffMember, CohortSummary,
+    CreateApiTokenPayload, CreateCodelab, CreateCodelabInvitePayload, CreateCohort,
+    CreateInlineCommentPayload, CreateMaterial, CreateQuiz, CreateStaffUserPayload, CreateStep,
+    ImportRosterPayload, InsertStepPayload, Material, PublicationStatus, Quiz,
+    QuizSubmissionPayload, RegistrationPayload, ReplyInlineCommentPayload, RosterEntry,
+    RosterImportResult, SaveAiConversationPayload, StaffUser, Step, StepChangeResponse,
+    UpdateStaffUserPayload, UpdateStepPayload, UpdateStepsPayload, WaitlistEntry,
 };
-use crate::infrastructure::db_models::AuditLog;
+use crate::infrastructure::db_models::{AuditLog, AuthSessionRow};
+use crate::infrastructure::retention::RetentionReport;
 use crate::mcp::{serve_stdio, McpServerState};
 use crate::middleware::auth::now_epoch_seconds;
 use crate::utils::crypto::encrypt_with_password;
+use crate::utils::qr::QrCode;
 use anyhow::{anyhow, bail, Context, Result};
 use dialoguer::{theme::ColorfulTheme, Input, MultiSelect, Password, Select};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -37,6 +47,7 @@ struct GlobalOptions {
     session_file: Option<PathBuf>,
     config_file: PathBuf,
     profile: Option<String>,
+    api_token: Option<String>,
     json: bool,
 }
 
@@ -47,6 +58,7 @@ impl Default for GlobalOptions {
             session_file: env::var_os("OPEN_CODELABS_SESSION_FILE").map(PathBuf::from),
             config_file: default_config_path(),
             profile: env::var("OPEN_CODELABS_PROFILE").ok(),
+            api_token: env::var("OPEN_CODELABS_API_TOKEN").ok(),
             json: false,
         }
     }
@@ -73,8 +85,11 @@ enum Command {
     Codelab(CodelabCommand),
     Backup(BackupCommand),
     Audit(AuditCommand),
+    User(UserCommand),
+    Token(TokenCommand),
     Workspace(WorkspaceCommand),
     Attendee(AttendeeCommand),
+    Certificate(CertificateCommand),
     HelpDesk(HelpDeskCommand),
     Feedback(FeedbackCommand),
     Materials(MaterialCommand),
@@ -90,6 +105,7 @@ enum Command {
 struct LoginCommand {
     admin_id: String,
     admin_pw: String,
+    otp: Option<String>,
 }
 
 #[derive(Debug, Default)]
@@ -98,8 +114,18 @@ struct InitCommand;
 #[derive(Debug)]
 enum AuthCommand {
     Login(AuthLoginCommand),
-    Logout,
+    Logout { all: bool },
+    Status,
+    TwoFactor(TwoFactorCommand),
+}
+
+#[derive(Debug)]
+enum TwoFactorCommand {
     Status,
+    Enroll,
+    Confirm { code: String },
+    Disable { code: String },
+    RecoveryCodes { code: String },
 }
 
 #[derive(Debug, Default)]
@@ -281,6 +307,26 @@ enum AdminCommand {
         admin_password: Option<String>,
     },
     Updates,
+    Sessions {
+        filter: SessionFilterQuery,
+    },
+    RevokeSessions {
+        id: Option<String>,
+        filter: SessionFilterQuery,
+    },
+    PrivacyFind {
+        email: String,
+    },
+    PrivacyExport {
+        attendee_id: String,
+        output: Option<PathBuf>,
+    },
+    PrivacyErase {
+        attendee_id: String,
+    },
+    Retention {
+        run: bool,
+    },
 }
 
 #[derive(Debug)]
@@ -294,6 +340,7 @@ enum CodelabCommand {
     Update {
         id: String,
         command: CreateCodelabCommand,
+        expected_version: Option<i32>,
     },
     Delete {
         id: String,
@@ -304,6 +351,11 @@ enum CodelabCommand {
     Export {
         id: String,
         output: Option<PathBuf>,
+        format: ExportFormat,
+    },
+    Build {
+        id: String,
+        out: Option<PathBuf>,
     },
     Import {
         file: PathBuf,
@@ -316,13 +368,106 @@ enum CodelabCommand {
     Push {
         manifest: PathBuf,
         id: Option<String>,
+        publish: bool,
+        force: bool,
     },
     PushSteps {
         id: String,
         file: PathBuf,
+        publish: bool,
+        expected_version: Option<i32>,
+    },
+    StepAdd {
+        id: String,
+        title: String,
+        content_file: PathBuf,
+        position: Option<i32>,
+        expected_version: Option<i32>,
+    },
+    StepUpdate {
+        id: String,
+        step_id: String,
+        title: String,
+        content_file: PathBuf,
+        expected_version: Option<i32>,
+    },
+    StepDelete {
+        id: String,
+        step_id: String,
+        expected_version: Option<i32>,
+    },
+    StepMove {
+        id: String,
+        step_id: String,
+        position: i32,
+        expected_version: Option<i32>,
+    },
+    Publish {
+        id: String,
+    },
+    Revisions {
+        id: String,
+    },
+    RevisionDiff {
+        id: String,
+        from: String,
+        to: Option<String>,
+    },
+    RevisionRestore {
+        id: String,
+        revision: String,
+    },
+    Staff {
+        id: String,
+    },
+    StaffAdd {
+        id: String,
+        user_id: String,
+    },
+    StaffRemove {
+        id: String,
+        user_id: String,
+    },
+    Passcode {
+        id: String,
+        action: PasscodeAction,
+    },
+    Invites {
+        id: String,
+    },
+    InviteCreate {
+        id: String,
+        payload: CreateCodelabInvitePayload,
+    },
+    InviteRevoke {
+        id: String,
+        invite_id: String,
+    },
+    Cohorts {
+        id: String,
+    },
+    CohortCreate {
+        id: String,
+        payload: CreateCohort,
+    },
+    CohortUpdate {
+        id: String,
+        cohort_id: String,
+        payload: CreateCohort,
+    },
+    CohortDelete {
+        id: String,
+        cohort_id: String,
     },
 }
 
+#[derive(Debug, PartialEq, Eq)]
+enum PasscodeAction {
+    Show,
+    Set(String),
+    Clear,
+}
+
 #[derive(Debug)]
 struct CreateCodelabCommand {
     title: String,
@@ -333,6 +478,11 @@ struct CreateCodelabCommand {
     require_quiz: bool,
     require_feedback: bool,
     require_submission: bool,
+    quiz_max_attempts: Option<i32>,
+    quiz_pass_percentage: Option<i32>,
+    max_attendees: Option<i32>,
+    registration_opens_at: Option<i64>,
+    registration_closes_at: Option<i64>,
     guide_file: Option<PathBuf>,
 }
 
@@ -359,6 +509,40 @@ impl ManifestFormat {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Zip,
+    Claat,
+    Html,
+}
+
+impl ExportFormat {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "zip" => Some(Self::Zip),
+            "claat" | "md" => Some(Self::Claat),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::Claat => "claat",
+            Self::Html => "html",
+        }
+    }
+
+    fn default_output(&self, id: &str) -> PathBuf {
+        PathBuf::from(match self {
+            Self::Zip => format!("codelab_{id}.zip"),
+            Self::Claat => format!("codelab_{id}.md"),
+            Self::Html => format!("codelab_{id}_site.zip"),
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct CodelabManifest {
     version: u32,
@@ -373,7 +557,16 @@ struct CodelabManifest {
     require_feedback: bool,
     require_submission: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    quiz_max_attempts: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    quiz_pass_percentage: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     guide_markdown: Option<String>,
+    /// Server versions at pull time; `push` refuses to overwrite newer edits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    codelab_version: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    steps_version: Option<i32>,
     #[serde(default)]
     steps: Vec<CodelabManifestStep>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -418,6 +611,42 @@ enum AuditCommand {
     },
 }
 
+#[derive(Debug)]
+enum UserCommand {
+    List,
+    Create {
+        username: String,
+        password: String,
+        role: String,
+        display_name: Option<String>,
+    },
+    Update {
+        id: String,
+        password: Option<String>,
+        role: Option<String>,
+        display_name: Option<String>,
+    },
+    Delete {
+        id: String,
+    },
+    ResetTwoFactor {
+        id: String,
+    },
+}
+
+#[derive(Debug)]
+enum TokenCommand {
+    List,
+    Create {
+        name: String,
+        scopes: Vec<String>,
+        expires_in_days: Option<u32>,
+    },
+    Revoke {
+        id: String,
+    },
+}
+
 #[derive(Debug)]
 enum WorkspaceCommand {
     Create {
@@ -492,15 +721,51 @@ enum AttendeeCommand {
         name: String,
         code: String,
         email: Option<String>,
+        passcode: Option<String>,
+        invite: Option<String>,
+        cohort_id: Option<String>,
     },
     List {
         codelab_id: String,
+        cohort_id: Option<String>,
     },
     Complete {
         codelab_id: String,
     },
     Certificate {
         attendee_id: Option<String>,
+        pdf: Option<PathBuf>,
+    },
+    Import {
+        codelab_id: String,
+        file: PathBuf,
+        cohort_id: Option<String>,
+    },
+    Waitlist {
+        codelab_id: String,
+    },
+    Promote {
+        codelab_id: String,
+        waitlist_id: String,
+    },
+    WaitlistRemove {
+        codelab_id: String,
+        waitlist_id: String,
+    },
+}
+
+#[derive(Debug)]
+enum CertificateCommand {
+    Verify {
+        cert_id: String,
+    },
+    Revoke {
+        cert_id: String,
+        reason: Option<String>,
+    },
+    Credential {
+        attendee_id: String,
+        output: Option<PathBuf>,
     },
 }
 
@@ -559,6 +824,7 @@ enum QuizCommand {
     Update { codelab_id: String, file: PathBuf },
     Submit { codelab_id: String, file: PathBuf },
     Submissions { codelab_id: String },
+    Attempts { codelab_id: String },
 }
 
 #[derive(Debug)]
@@ -889,9 +1155,26 @@ async fn run(global: GlobalOptions, command: Command) -> Result<()> {
                 None,
             );
             let client = ApiClient::new(base_url, None)?;
-            let session = client
+            let session = match client
                 .login_admin(&command.admin_id, &command.admin_pw)
-                .await?;
+                .await?
+            {
+                AdminLogin::Authenticated(session) => session,
+                AdminLogin::MfaRequired(challenge) => {
+                    let code = match command.otp {
+                        Some(code) => code,
+                        None if interactive_terminal_available() => {
+                            prompt_line("Two-factor code", None)?
+                        }
+                        None => {
+                            bail!("This account requires a two-factor code. Pass --otp <code>.")
+                        }
+                    };
+                    client
+                        .complete_admin_mfa(&challenge.mfa_token, &code)
+                        .await?
+                }
+            };
             save_session(&session_file, &session)?;
 
             if global.json {
@@ -966,57 +1249,15 @@ async fn run(global: GlobalOptions, command: Command) -> Result<()> {
             run_codelab_command(&global, &client, command).await?;
         }
         Command::Backup(command) => {
-            let config = load_config(&global.config_file)?;
-            let active_profile = resolve_active_profile(&global, &config)?;
-            let session_file = resolve_session_file(&global, active_profile.as_ref());
-            let session = load_session(&session_file).with_context(|| {
-                format!(
-                    "No saved session found. Run `{}` login first.",
-                    program_name()
-                )
-            })?;
-            let base_url = resolve_base_url(
-                global.base_url.as_deref(),
-                active_profile.as_ref().map(|(_, profile)| profile),
-                Some(&session),
-            );
-            let client = ApiClient::new(base_url, Some(session))?;
+            let client = load_required_session_client(&global)?;
             run_backup_command(&global, &client, command).await?;
         }
         Command::Audit(command) => {
-            let config = load_config(&global.config_file)?;
-            let active_profile = resolve_active_profile(&global, &config)?;
-            let session_file = resolve_session_file(&global, active_profile.as_ref());
-            let session = load_session(&session_file).with_context(|| {
-                format!(
-                    "No saved session found. Run `{}` login first.",
-                    program_name()
-                )
-            })?;
-            let base_url = resolve_base_url(
-                global.base_url.as_deref(),
-                active_profile.as_ref().map(|(_, profile)| profile),
-                Some(&session),
-            );
-            let client = ApiClient::new(base_url, Some(session))?;
+            let client = load_required_session_client(&global)?;
             run_audit_command(&global, &client, command).await?;
         }
         Command::Workspace(command) => {
-            let config = load_config(&global.config_file)?;
-            let active_profile = resolve_active_profile(&global, &config)?;
-            let session_file = resolve_session_file(&global, active_profile.as_ref());
-            let session = load_session(&session_file).with_context(|| {
-                format!(
-                    "No saved session found. Run `{}` login first.",
-                    program_name()
-                )
-            })?;
-            let base_url = resolve_base_url(
-                global.base_url.as_deref(),
-                active_profile.as_ref().map(|(_, profile)| profile),
-                Some(&session),
-            );
-            let client = ApiClient::new(base_url, Some(session))?;
+            let client = load_required_session_client(&global)?;
             run_workspace_command(&global, &client, command).await?;
         }
         Command::Attendee(command) => {
@@ -1024,6 +1265,18 @@ async fn run(global: GlobalOptions, command: Command) -> Result<()> {
             run_attendee_command(&global, &client, &session_file, session.as_ref(), command)
                 .await?;
         }
+        Command::Certificate(command) => {
+            let client = load_api_client(&global)?;
+            run_certificate_command(&global, &client, command).await?;
+        }
+        Command::User(command) => {
+            let client = load_api_client(&global)?;
+            run_user_command(&global, &client, command).await?;
+        }
+        Command::Token(command) => {
+            let client = load_api_client(&global)?;
+            run_token_command(&global, &client, command).await?;
+        }
         Command::HelpDesk(command) => {
             let client = load_api_client(&global)?;
             run_helpdesk_command(&global, &client, command).await?;
@@ -1148,7 +1401,7 @@ async fn run_auth_command(global: &GlobalOptions, command: AuthCommand) -> Resul
                 }
             }
         }
-        AuthCommand::Logout => {
+        AuthCommand::Logout { all } => {
             let config = load_config(&global.config_file)?;
             let active_profile = resolve_active_profile(global, &config)?;
             let session_file = resolve_session_file(global, active_profile.as_ref());
@@ -1173,13 +1426,28 @@ async fn run_auth_command(global: &GlobalOptions, command: AuthCommand) -> Resul
                 Some(&session),
             );
             let client = ApiClient::new(base_url, Some(session))?;
-            let logout_result = client.logout().await;
+            let logout_result = if all {
+                client
+                    .logout_everywhere()
+                    .await
+                    .map(|response| Some(response.revoked))
+            } else {
+                client.logout().await.map(|_| None)
+            };
             clear_session(&session_file)?;
-            logout_result?;
+            let revoked = logout_result?;
 
             if global.json {
-                print_json(&serde_json::json!({ "status": "ok" }))?;
+                match revoked {
+                    Some(revoked) => {
+                        print_json(&serde_json::json!({ "status": "ok", "revoked": revoked }))?
+                    }
+                    None => print_json(&serde_json::json!({ "status": "ok" }))?,
+                }
             } else {
+                if let Some(revoked) = revoked {
+                    println!("Revoked {revoked} session(s) on all devices");
+                }
                 println!("Logged out and removed {}", session_file.display());
             }
         }
@@ -1191,6 +1459,10 @@ async fn run_auth_command(global: &GlobalOptions, command: AuthCommand) -> Resul
                 print_auth_status(&status);
             }
         }
+        AuthCommand::TwoFactor(command) => {
+            let client = load_api_client(global)?;
+            run_two_factor_command(global, &client, command).await?;
+        }
     }
 
     Ok(())
@@ -1360,6 +1632,74 @@ async fn run_admin_command(global: &GlobalOptions, command: AdminCommand) -> Res
                 print_updates_summary(&updates);
             }
         }
+        AdminCommand::Sessions { filter } => {
+            let sessions = client.list_sessions(&filter).await?;
+            if global.json {
+                print_json(&sessions)?;
+            } else {
+                print_session_list(&sessions);
+            }
+        }
+        AdminCommand::RevokeSessions { id: Some(id), .. } => {
+            client.revoke_session(&id).await?;
+            if global.json {
+                print_json(&serde_json::json!({ "status": "ok", "id": id }))?;
+            } else {
+                println!("Revoked session {id}");
+            }
+        }
+        AdminCommand::RevokeSessions { id: None, filter } => {
+            let response = client.revoke_sessions(&filter).await?;
+            if global.json {
+                print_json(&response)?;
+            } else {
+                println!("Revoked {} session(s)", response.revoked);
+            }
+        }
+        AdminCommand::PrivacyFind { email } => {
+            let attendees = client.find_attendees_by_email(&email).await?;
+            print_json(&attendees)?;
+        }
+        AdminCommand::PrivacyExport {
+            attendee_id,
+            output,
+        } => {
+            let archive = client.export_attendee_data(&attendee_id).await?;
+            let output =
+                output.unwrap_or_else(|| PathBuf::from(format!("attendee_{attendee_id}.zip")));
+            tokio::fs::write(&output, archive)
+                .await
+                .with_context(|| format!("Failed to write {}", output.display()))?;
+
+            if global.json {
+                print_json(&serde_json::json!({
+                    "status": "ok",
+                    "output": output,
+                }))?;
+            } else {
+                println!("Exported attendee data to {}", output.display());
+            }
+        }
+        AdminCommand::PrivacyErase { attendee_id } => {
+            let erasure = client.erase_attendee_data(&attendee_id).await?;
+            if global.json {
+                print_json(&erasure)?;
+            } else {
+                print_attendee_erasure(&erasure);
+            }
+        }
+        AdminCommand::Retention { run } => {
+            let report = if run {
+                client.run_retention().await?
+            } else {
+                client.retention_report().await?
+            };
+            if global.json {
+                print_json(&report)?;
+            } else {
+                print_retention_report(&report);
+            }
+        }
     }
 
     Ok(())
@@ -1410,9 +1750,15 @@ async fn run_codelab_command(
                 println!("author: {}", codelab.author);
             }
         }
-        CodelabCommand::Update { id, command } => {
+        CodelabCommand::Update {
+            id,
+            command,
+            expected_version,
+        } => {
             let payload = build_codelab_payload(command).await?;
-            let codelab = client.update_codelab(&id, &payload).await?;
+            let codelab = client
+                .update_codelab(&id, &payload, expected_version)
+                .await?;
             if global.json {
                 print_json(&codelab)?;
             } else {
@@ -1438,9 +1784,9 @@ async fn run_codelab_command(
                 println!("title: {}", codelab.title);
             }
         }
-        CodelabCommand::Export { id, output } => {
-            let archive = client.export_codelab(&id).await?;
-            let output = output.unwrap_or_else(|| PathBuf::from(format!("codelab_{id}.zip")));
+        CodelabCommand::Export { id, output, format } => {
+            let archive = client.export_codelab(&id, format.as_str()).await?;
+            let output = output.unwrap_or_else(|| format.default_output(&id));
             tokio::fs::write(&output, archive)
                 .await
                 .with_context(|| format!("Failed to write {}", output.display()))?;
@@ -1454,6 +1800,29 @@ async fn run_codelab_command(
                 println!("Exported codelab {id} to {}", output.display());
             }
         }
+        CodelabCommand::Build { id, out } => {
+            let archive = client
+                .export_codelab(&id, ExportFormat::Html.as_str())
+                .await?;
+            let out = out.unwrap_or_else(|| PathBuf::from(format!("codelab-{id}-site")));
+            let mut site = zip::ZipArchive::new(io::Cursor::new(archive))
+                .context("Server returned an invalid site archive")?;
+            let files = site.len();
+            site.extract(&out)
+                .with_context(|| format!("Failed to write {}", out.display()))?;
+
+            if global.json {
+                print_json(&serde_json::json!({
+                    "status": "ok",
+                    "codelab_id": id,
+                    "output_dir": out,
+                    "files": files,
+                }))?;
+            } else {
+                println!("Built codelab {id} site in {}", out.display());
+                println!("open: {}", out.join("index.html").display());
+            }
+        }
         CodelabCommand::Import { file } => {
             let codelab = client.import_codelab(&file).await?;
             if global.json {
@@ -1497,24 +1866,50 @@ async fn run_codelab_command(
                 println!("materials: {}", materials.len());
             }
         }
-        CodelabCommand::Push { manifest, id } => {
+        CodelabCommand::Push {
+            manifest,
+            id,
+            publish,
+            force,
+        } => {
             let manifest_path = resolve_manifest_path(&manifest)?;
-            let loaded = load_codelab_manifest(&manifest_path).await?;
+            let mut loaded = load_codelab_manifest(&manifest_path).await?;
             let target_id = id.or_else(|| loaded.id.clone());
             let create_payload =
                 create_codelab_payload_from_manifest(&loaded, &manifest_path).await?;
             let steps_payload = create_steps_payload_from_manifest(&loaded, &manifest_path).await?;
+            // Versions recorded by `pull` only describe the codelab they were
+            // pulled from.
+            let tracked = !force && target_id.is_some() && target_id == loaded.id;
+            let codelab_version = loaded.codelab_version.filter(|_| tracked);
+            let steps_version = loaded.steps_version.filter(|_| tracked);
 
             let (codelab, operation) = match target_id.as_deref() {
                 Some(codelab_id) => (
-                    client.update_codelab(codelab_id, &create_payload).await?,
+                    client
+                        .update_codelab(codelab_id, &create_payload, codelab_version)
+                        .await?,
                     "updated",
                 ),
                 None => (client.create_codelab(&create_payload).await?, "created"),
             };
-            client.push_steps(&codelab.id, &steps_payload).await?;
+            let steps_update = client
+                .push_steps(&codelab.id, &steps_payload, steps_version)
+                .await?;
+            if loaded.id.as_deref() == Some(codelab.id.as_str())
+                && (loaded.codelab_version.is_some() || loaded.steps_version.is_some())
+            {
+                loaded.codelab_version = Some(codelab.version);
+                loaded.steps_version = Some(steps_update.steps_version);
+                write_codelab_manifest(&manifest_path, &loaded).await?;
+            }
             sync_manifest_quizzes(client, &codelab.id, &loaded.quizzes).await?;
             sync_manifest_materials(client, &codelab.id, &loaded.materials, &manifest_path).await?;
+            let publication = if publish {
+                Some(client.publish_codelab(&codelab.id).await?)
+            } else {
+                None
+            };
 
             if global.json {
                 print_json(&serde_json::json!({
@@ -1525,6 +1920,7 @@ async fn run_codelab_command(
                     "steps": steps_payload.steps.len(),
                     "quizzes": loaded.quizzes.len(),
                     "materials": loaded.materials.len(),
+                    "publication": publication,
                 }))?;
             } else {
                 println!("{} codelab {}", capitalize_first(operation), codelab.id);
@@ -1532,26 +1928,281 @@ async fn run_codelab_command(
                 println!("steps: {}", steps_payload.steps.len());
                 println!("quizzes: {}", loaded.quizzes.len());
                 println!("materials: {}", loaded.materials.len());
+                print_publication(publication.as_ref());
             }
         }
-        CodelabCommand::PushSteps { id, file } => {
+        CodelabCommand::PushSteps {
+            id,
+            file,
+            publish,
+            expected_version,
+        } => {
             let payload = load_steps_payload(&file).await?;
-            client.push_steps(&id, &payload).await?;
+            let steps_update = client.push_steps(&id, &payload, expected_version).await?;
+            let publication = if publish {
+                Some(client.publish_codelab(&id).await?)
+            } else {
+                None
+            };
             if global.json {
                 print_json(&serde_json::json!({
                     "status": "ok",
                     "codelab_id": id,
                     "steps": payload.steps.len(),
+                    "steps_version": steps_update.steps_version,
+                    "publication": publication,
                 }))?;
             } else {
-                println!("Updated {} steps for codelab {id}", payload.steps.len());
+                println!(
+                    "Updated {} draft steps for codelab {id}",
+                    payload.steps.len()
+                );
+                print_publication(publication.as_ref());
             }
         }
-    }
-    Ok(())
-}
-
-async fn build_codelab_payload(command: CreateCodelabCommand) -> Result<CreateCodelab> {
+        CodelabCommand::StepAdd {
+            id,
+            title,
+            content_file,
+            position,
+            expected_version,
+        } => {
+            let payload = InsertStepPayload {
+                title,
+                content_markdown: read_step_markdown(&content_file).await?,
+                position,
+            };
+            let change = client.insert_step(&id, &payload, expected_version).await?;
+            print_step_change(global, &id, "Inserted", &change)?;
+        }
+        CodelabCommand::StepUpdate {
+            id,
+            step_id,
+            title,
+            content_file,
+            expected_version,
+        } => {
+            let payload = UpdateStepPayload {
+                title,
+                content_markdown: read_step_markdown(&content_file).await?,
+            };
+            let change = client
+                .update_step(&id, &step_id, &payload, expected_version)
+                .await?;
+            print_step_change(global, &id, "Updated", &change)?;
+        }
+        CodelabCommand::StepDelete {
+            id,
+            step_id,
+            expected_version,
+        } => {
+            let change = client.delete_step(&id, &step_id, expected_version).await?;
+            print_step_change(global, &id, "Deleted", &change)?;
+        }
+        CodelabCommand::StepMove {
+            id,
+            step_id,
+            position,
+            expected_version,
+        } => {
+            let change = client
+                .move_step(&id, &step_id, position, expected_version)
+                .await?;
+            print_step_change(global, &id, "Moved", &change)?;
+        }
+        CodelabCommand::Publish { id } => {
+            let publication = client.publish_codelab(&id).await?;
+            if global.json {
+                print_json(&publication)?;
+            } else {
+                print_publication(Some(&publication));
+            }
+        }
+        CodelabCommand::Revisions { id } => {
+            let revisions = client.list_revisions(&id).await?;
+            if global.json {
+                print_json(&revisions)?;
+            } else {
+                print_revisions(&revisions);
+            }
+        }
+        CodelabCommand::RevisionDiff { id, from, to } => {
+            let diff = client.diff_revisions(&id, &from, to.as_deref()).await?;
+            if global.json {
+                print_json(&diff)?;
+            } else if diff.diff.is_empty() {
+                println!(
+                    "Revisions {} and {} have the same content",
+                    diff.from.revision_number, diff.to.revision_number
+                );
+            } else {
+                print!("{}", diff.diff);
+            }
+        }
+        CodelabCommand::RevisionRestore { id, revision } => {
+            let restored = client.restore_revision(&id, &revision).await?;
+            if global.json {
+                print_json(&restored)?;
+            } else {
+                println!(
+                    "Restored codelab {id} to revision {revision} as revision {} ({} steps)",
+                    restored.summary.revision_number,
+                    restored.steps.len()
+                );
+            }
+        }
+        CodelabCommand::Staff { id } => {
+            let members = client.list_codelab_staff(&id).await?;
+            if global.json {
+                print_json(&members)?;
+            } else {
+                print_codelab_staff(&members);
+            }
+        }
+        CodelabCommand::StaffAdd { id, user_id } => {
+            client.assign_codelab_staff(&id, &user_id).await?;
+            if global.json {
+                print_json(&serde_json::json!({
+                    "status": "ok",
+                    "codelab_id": id,
+                    "user_id": user_id,
+                }))?;
+            } else {
+                println!("Assigned user {user_id} to codelab {id}");
+            }
+        }
+        CodelabCommand::StaffRemove { id, user_id } => {
+            client.unassign_codelab_staff(&id, &user_id).await?;
+            if global.json {
+                print_json(&serde_json::json!({
+                    "status": "ok",
+                    "codelab_id": id,
+                    "user_id": user_id,
+                }))?;
+            } else {
+                println!("Removed user {user_id} from codelab {id}");
+            }
+        }
+        CodelabCommand::Passcode { id, action } => {
+            let passcode = match action {
+                PasscodeAction::Show => client.get_codelab_passcode(&id).await?.passcode,
+                PasscodeAction::Set(passcode) => {
+                    client.set_codelab_passcode(&id, Some(&passcode)).await?;
+                    Some(passcode)
+                }
+                PasscodeAction::Clear => {
+                    client.set_codelab_passcode(&id, None).await?;
+                    None
+                }
+            };
+            if global.json {
+                print_json(&serde_json::json!({
+                    "codelab_id": id,
+                    "passcode": passcode,
+                }))?;
+            } else {
+                println!("passcode: {}", passcode.as_deref().unwrap_or("(none)"));
+            }
+        }
+        CodelabCommand::Invites { id } => {
+            let invites = client.list_codelab_invites(&id).await?;
+            if global.json {
+                print_json(&invites)?;
+            } else {
+                print_codelab_invites(&invites);
+            }
+        }
+        CodelabCommand::InviteCreate { id, payload } => {
+            let created = client.create_codelab_invite(&id, &payload).await?;
+            if global.json {
+                print_json(&created)?;
+            } else {
+                print_codelab_invites(std::slice::from_ref(&created.metadata));
+                println!("\ntoken: {}", created.token);
+                println!("link: {}", created.invite_path);
+                println!("Store this token now; it cannot be shown again.");
+            }
+        }
+        CodelabCommand::InviteRevoke { id, invite_id } => {
+            client.revoke_codelab_invite(&id, &invite_id).await?;
+            if global.json {
+                print_json(&serde_json::json!({
+                    "status": "ok",
+                    "codelab_id": id,
+                    "invite_id": invite_id,
+                }))?;
+            } else {
+                println!("Revoked invite {invite_id} for codelab {id}");
+            }
+        }
+        CodelabCommand::Cohorts { id } => {
+            let cohorts = client.list_cohorts(&id).await?;
+            if global.json {
+                print_json(&cohorts)?;
+            } else {
+                print_cohorts(&cohorts);
+            }
+        }
+        CodelabCommand::CohortCreate { id, payload } => {
+            let cohort = client.create_cohort(&id, &payload).await?;
+            if global.json {
+                print_json(&cohort)?;
+            } else {
+                println!("Created cohort {} ({})", cohort.name, cohort.id);
+            }
+        }
+        CodelabCommand::CohortUpdate {
+            id,
+            cohort_id,
+            payload,
+        } => {
+            let cohort = client.update_cohort(&id, &cohort_id, &payload).await?;
+            if global.json {
+                print_json(&cohort)?;
+            } else {
+                println!("Updated cohort {} ({})", cohort.name, cohort.id);
+            }
+        }
+        CodelabCommand::CohortDelete { id, cohort_id } => {
+            client.delete_cohort(&id, &cohort_id).await?;
+            if global.json {
+                print_json(&serde_json::json!({
+                    "status": "ok",
+                    "codelab_id": id,
+                    "cohort_id": cohort_id,
+                }))?;
+            } else {
+                println!("Deleted cohort {cohort_id} from codelab {id}");
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn read_step_markdown(path: &PathBuf) -> Result<String> {
+    tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn print_step_change(
+    global: &GlobalOptions,
+    codelab_id: &str,
+    verb: &str,
+    change: &StepChangeResponse,
+) -> Result<()> {
+    if global.json {
+        return print_json(change);
+    }
+    println!(
+        "{verb} step {} in codelab {codelab_id} (steps version {})",
+        change.step_id, change.steps_version
+    );
+    print_steps(&change.steps);
+    Ok(())
+}
+
+async fn build_codelab_payload(command: CreateCodelabCommand) -> Result<CreateCodelab> {
     let guide_markdown = match command.guide_file {
         Some(path) => Some(
             tokio::fs::read_to_string(&path)
@@ -1570,6 +2221,11 @@ async fn build_codelab_payload(command: CreateCodelabCommand) -> Result<CreateCo
         require_quiz: Some(command.require_quiz),
         require_feedback: Some(command.require_feedback),
         require_submission: Some(command.require_submission),
+        quiz_max_attempts: command.quiz_max_attempts,
+        quiz_pass_percentage: command.quiz_pass_percentage,
+        max_attendees: command.max_attendees,
+        registration_opens_at: command.registration_opens_at,
+        registration_closes_at: command.registration_closes_at,
         guide_markdown,
     })
 }
@@ -1599,6 +2255,11 @@ async fn create_codelab_payload_from_manifest(
         require_quiz: Some(manifest.require_quiz),
         require_feedback: Some(manifest.require_feedback),
         require_submission: Some(manifest.require_submission),
+        quiz_max_attempts: manifest.quiz_max_attempts,
+        quiz_pass_percentage: manifest.quiz_pass_percentage,
+        max_attendees: None,
+        registration_opens_at: None,
+        registration_closes_at: None,
         guide_markdown,
     })
 }
@@ -1617,6 +2278,7 @@ async fn create_steps_payload_from_manifest(
             id: step.id.clone(),
             title: step.title.clone(),
             content_markdown,
+            duration_minutes: None,
         });
     }
 
@@ -1833,7 +2495,11 @@ async fn write_codelab_manifest_bundle(
         require_quiz: codelab.require_quiz != 0,
         require_feedback: codelab.require_feedback != 0,
         require_submission: codelab.require_submission != 0,
+        quiz_max_attempts: Some(codelab.quiz_max_attempts),
+        quiz_pass_percentage: Some(codelab.quiz_pass_percentage),
         guide_markdown: guide_path,
+        codelab_version: Some(codelab.version),
+        steps_version: Some(codelab.steps_version),
         steps: manifest_steps,
         quizzes: manifest_quizzes,
         materials: manifest_materials,
@@ -2117,6 +2783,190 @@ async fn run_audit_command(
     Ok(())
 }
 
+async fn run_two_factor_command(
+    global: &GlobalOptions,
+    client: &ApiClient,
+    command: TwoFactorCommand,
+) -> Result<()> {
+    let recovery_codes = match command {
+        TwoFactorCommand::Status => {
+            let status = client.mfa_status().await?;
+            if global.json {
+                print_json(&status)?;
+            } else {
+                println!("enabled: {}", status.enabled);
+                if status.pending {
+                    println!("pending: waiting for `oc auth 2fa confirm --code <code>`");
+                }
+                if status.enabled {
+                    println!(
+                        "recovery_codes_remaining: {}",
+                        status.recovery_codes_remaining
+                    );
+                }
+            }
+            return Ok(());
+        }
+        TwoFactorCommand::Enroll => {
+            let enrollment = client.start_totp_enrollment().await?;
+            if global.json {
+                print_json(&enrollment)?;
+            } else {
+                println!("Scan this QR code with an authenticator app:\n");
+                match QrCode::encode(enrollment.provisioning_uri.as_bytes()) {
+                    Ok(qr) => print_terminal_qr(&qr),
+                    Err(error) => eprintln!("Failed to render QR code: {error}"),
+                }
+                println!("\nsecret: {}", enrollment.secret);
+                println!("uri: {}", enrollment.provisioning_uri);
+                println!("\nThen run `oc auth 2fa confirm --code <code>` to finish.");
+            }
+            return Ok(());
+        }
+        TwoFactorCommand::Confirm { code } => client.confirm_totp(&code).await?,
+        TwoFactorCommand::Disable { code } => {
+            client.disable_totp(&code).await?;
+            if global.json {
+                print_json(&serde_json::json!({ "status": "ok", "enabled": false }))?;
+            } else {
+                println!("Two-factor authentication disabled");
+            }
+            return Ok(());
+        }
+        TwoFactorCommand::RecoveryCodes { code } => client.regenerate_recovery_codes(&code).await?,
+    };
+
+    if global.json {
+        print_json(&recovery_codes)?;
+    } else {
+        println!("Store these recovery codes somewhere safe. Each works once:");
+        for code in &recovery_codes.recovery_codes {
+            println!("  {code}");
+        }
+    }
+    Ok(())
+}
+
+async fn run_user_command(
+    global: &GlobalOptions,
+    client: &ApiClient,
+    command: UserCommand,
+) -> Result<()> {
+    let user = match command {
+        UserCommand::List => {
+            let users = client.list_users().await?;
+            if global.json {
+                print_json(&users)?;
+            } else {
+                print_user_list(&users);
+            }
+            return Ok(());
+        }
+        UserCommand::Create {
+            username,
+            password,
+            role,
+            display_name,
+        } => {
+            client
+                .create_user(&CreateStaffUserPayload {
+                    username,
+                    password,
+                    role,
+                    display_name,
+                })
+                .await?
+        }
+        UserCommand::Update {
+            id,
+            password,
+            role,
+            display_name,
+        } => {
+            client
+                .update_user(
+                    &id,
+                    &UpdateStaffUserPayload {
+                        password,
+                        role,
+                        display_name,
+                    },
+                )
+                .await?
+        }
+        UserCommand::Delete { id } => {
+            client.delete_user(&id).await?;
+            if global.json {
+                print_json(&serde_json::json!({ "status": "ok", "id": id }))?;
+            } else {
+                println!("Deleted user {id}");
+            }
+            return Ok(());
+        }
+        UserCommand::ResetTwoFactor { id } => {
+            client.reset_user_mfa(&id).await?;
+            if global.json {
+                print_json(&serde_json::json!({ "status": "ok", "id": id }))?;
+            } else {
+                println!("Reset two-factor authentication for user {id}");
+            }
+            return Ok(());
+        }
+    };
+    if global.json {
+        print_json(&user)?;
+    } else {
+        print_user_list(std::slice::from_ref(&user));
+    }
+    Ok(())
+}
+
+async fn run_token_command(
+    global: &GlobalOptions,
+    client: &ApiClient,
+    command: TokenCommand,
+) -> Result<()> {
+    match command {
+        TokenCommand::List => {
+            let tokens = client.list_api_tokens().await?;
+            if global.json {
+                print_json(&tokens)?;
+            } else {
+                print_api_token_list(&tokens);
+            }
+        }
+        TokenCommand::Create {
+            name,
+            scopes,
+            expires_in_days,
+        } => {
+            let created = client
+                .create_api_token(&CreateApiTokenPayload {
+                    name,
+                    scopes: (!scopes.is_empty()).then_some(scopes),
+                    expires_in_days,
+                })
+                .await?;
+            if global.json {
+                print_json(&created)?;
+            } else {
+                print_api_token_list(std::slice::from_ref(&created.metadata));
+                println!("\ntoken: {}", created.token);
+                println!("Store this token now; it cannot be shown again.");
+            }
+        }
+        TokenCommand::Revoke { id } => {
+            client.revoke_api_token(&id).await?;
+            if global.json {
+                print_json(&serde_json::json!({ "status": "ok", "id": id }))?;
+            } else {
+                println!("Revoked API token {id}");
+            }
+        }
+    }
+    Ok(())
+}
+
 async fn run_workspace_command(
     global: &GlobalOptions,
     client: &ApiClient,
@@ -2385,10 +3235,35 @@ async fn run_attendee_command(
             name,
             code,
             email,
+            passcode,
+            invite,
+            cohort_id,
         } => {
-            let (stored_session, attendee) = client
-                .register_attendee(&codelab_id, &name, &code, email.as_deref())
-                .await?;
+            let payload = RegistrationPayload {
+                name,
+                code,
+                email,
+                passcode,
+                invite_token: invite,
+                cohort_id,
+            };
+            let registration = client.register_attendee(&codelab_id, &payload).await?;
+            let (stored_session, attendee) = match registration {
+                AttendeeRegistration::Joined(stored_session, attendee) => {
+                    (stored_session, attendee)
+                }
+                AttendeeRegistration::Waitlisted(status) => {
+                    if global.json {
+                        print_json(&status)?;
+                    } else {
+                        println!("Codelab {} is full", status.codelab_id);
+                        println!("waitlist_id: {}", status.waitlist_id);
+                        println!("position: {}", status.position);
+                        println!("Run the same join command again once you have been admitted.");
+                    }
+                    return Ok(());
+                }
+            };
             save_session(session_file, &stored_session)?;
             if global.json {
                 print_json(&serde_json::json!({
@@ -2402,8 +3277,13 @@ async fn run_attendee_command(
                 println!("session_file: {}", session_file.display());
             }
         }
-        AttendeeCommand::List { codelab_id } => {
-            let attendees = client.get_attendees(&codelab_id).await?;
+        AttendeeCommand::List {
+            codelab_id,
+            cohort_id,
+        } => {
+            let attendees = client
+                .get_attendees(&codelab_id, cohort_id.as_deref())
+                .await?;
             if global.json {
                 print_json(&attendees)?;
             } else {
@@ -2418,7 +3298,29 @@ async fn run_attendee_command(
                 println!("Completed codelab {codelab_id}");
             }
         }
-        AttendeeCommand::Certificate { attendee_id } => {
+        AttendeeCommand::Certificate {
+            attendee_id,
+            pdf: Some(output),
+        } => {
+            let attendee_id = resolve_attendee_id(attendee_id, session, "attendee certificate")?;
+            let pdf = client.download_certificate_pdf(&attendee_id).await?;
+            tokio::fs::write(&output, pdf)
+                .await
+                .with_context(|| format!("Failed to write {}", output.display()))?;
+
+            if global.json {
+                print_json(&serde_json::json!({
+                    "status": "ok",
+                    "output": output,
+                }))?;
+            } else {
+                println!("Saved certificate PDF to {}", output.display());
+            }
+        }
+        AttendeeCommand::Certificate {
+            attendee_id,
+            pdf: None,
+        } => {
             let attendee_id = resolve_attendee_id(attendee_id, session, "attendee certificate")?;
             let certificate = client.get_certificate(&attendee_id).await?;
             if global.json {
@@ -2427,33 +3329,133 @@ async fn run_attendee_command(
                 print_json(&certificate)?;
             }
         }
-    }
-
-    Ok(())
-}
-
-async fn run_helpdesk_command(
-    global: &GlobalOptions,
-    client: &ApiClient,
-    command: HelpDeskCommand,
-) -> Result<()> {
-    match command {
-        HelpDeskCommand::Request {
+        AttendeeCommand::Import {
             codelab_id,
-            step_number,
+            file,
+            cohort_id,
         } => {
-            client.request_help(&codelab_id, step_number).await?;
+            let mut payload = load_roster_payload(&file).await?;
+            if cohort_id.is_some() {
+                payload.cohort_id = cohort_id;
+            }
+            let result = client.import_roster(&codelab_id, &payload).await?;
             if global.json {
-                print_json(&serde_json::json!({
-                    "status": "ok",
-                    "codelab_id": codelab_id,
-                    "step_number": step_number,
-                }))?;
+                print_json(&result)?;
             } else {
-                println!("Requested help for {codelab_id} step {step_number}");
+                print_roster_import(&result);
             }
         }
-        HelpDeskCommand::List { codelab_id } => {
+        AttendeeCommand::Waitlist { codelab_id } => {
+            let entries = client.list_waitlist(&codelab_id).await?;
+            if global.json {
+                print_json(&entries)?;
+            } else {
+                print_waitlist(&entries);
+            }
+        }
+        AttendeeCommand::Promote {
+            codelab_id,
+            waitlist_id,
+        } => {
+            let attendee = client
+                .promote_waitlist_entry(&codelab_id, &waitlist_id)
+                .await?;
+            if global.json {
+                print_json(&attendee)?;
+            } else {
+                println!("Admitted {} to codelab {codelab_id}", attendee.name);
+                println!("attendee_id: {}", attendee.id);
+            }
+        }
+        AttendeeCommand::WaitlistRemove {
+            codelab_id,
+            waitlist_id,
+        } => {
+            client
+                .remove_waitlist_entry(&codelab_id, &waitlist_id)
+                .await?;
+            if global.json {
+                print_json(&serde_json::json!({
+                    "status": "ok",
+                    "codelab_id": codelab_id,
+                    "waitlist_id": waitlist_id,
+                }))?;
+            } else {
+                println!("Removed waitlist entry {waitlist_id} from codelab {codelab_id}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_certificate_command(
+    global: &GlobalOptions,
+    client: &ApiClient,
+    command: CertificateCommand,
+) -> Result<()> {
+    let verification = match command {
+        CertificateCommand::Verify { cert_id } => client.verify_certificate(&cert_id).await?,
+        CertificateCommand::Revoke { cert_id, reason } => {
+            client
+                .revoke_certificate(&cert_id, reason.as_deref())
+                .await?
+        }
+        CertificateCommand::Credential {
+            attendee_id,
+            output,
+        } => {
+            let credential = client.get_certificate_credential(&attendee_id).await?;
+            let Some(output) = output else {
+                return print_json(&credential);
+            };
+            let raw = serde_json::to_string_pretty(&credential)
+                .context("Failed to serialize credential")?;
+            tokio::fs::write(&output, raw)
+                .await
+                .with_context(|| format!("Failed to write {}", output.display()))?;
+
+            if global.json {
+                print_json(&serde_json::json!({
+                    "status": "ok",
+                    "output": output,
+                }))?;
+            } else {
+                println!("Saved Open Badges credential to {}", output.display());
+            }
+            return Ok(());
+        }
+    };
+    if global.json {
+        print_json(&verification)?;
+    } else {
+        print_certificate_verification(&verification);
+    }
+    Ok(())
+}
+
+async fn run_helpdesk_command(
+    global: &GlobalOptions,
+    client: &ApiClient,
+    command: HelpDeskCommand,
+) -> Result<()> {
+    match command {
+        HelpDeskCommand::Request {
+            codelab_id,
+            step_number,
+        } => {
+            client.request_help(&codelab_id, step_number).await?;
+            if global.json {
+                print_json(&serde_json::json!({
+                    "status": "ok",
+                    "codelab_id": codelab_id,
+                    "step_number": step_number,
+                }))?;
+            } else {
+                println!("Requested help for {codelab_id} step {step_number}");
+            }
+        }
+        HelpDeskCommand::List { codelab_id } => {
             let requests = client.get_help_requests(&codelab_id).await?;
             if global.json {
                 print_json(&requests)?;
@@ -2606,18 +3608,17 @@ async fn run_quiz_command(
         }
         QuizCommand::Submit { codelab_id, file } => {
             let payload = load_quiz_submission_payload(&file).await?;
-            client.submit_quiz(&codelab_id, &payload).await?;
+            let result = client.submit_quiz(&codelab_id, &payload).await?;
             if global.json {
-                print_json(&serde_json::json!({
-                    "status": "ok",
-                    "codelab_id": codelab_id,
-                    "submissions": payload.submissions.len(),
-                }))?;
+                print_json(&result)?;
             } else {
                 println!(
-                    "Submitted {} quiz answers for {}",
+                    "Submitted {} quiz answers for {}: {}/{} correct ({:.2}%)",
                     payload.submissions.len(),
-                    codelab_id
+                    codelab_id,
+                    result.correct,
+                    result.total,
+                    result.score
                 );
             }
         }
@@ -2629,6 +3630,24 @@ async fn run_quiz_command(
                 print_json(&submissions)?;
             }
         }
+        QuizCommand::Attempts { codelab_id } => {
+            let attempts = client.get_quiz_attempts(&codelab_id).await?;
+            if global.json {
+                print_json(&attempts)?;
+            } else {
+                for attempt in attempts {
+                    println!(
+                        "{}\t#{}\t{}/{}\t{:.2}%\t{}",
+                        attempt.attendee_id,
+                        attempt.attempt_number,
+                        attempt.correct_count,
+                        attempt.total_questions,
+                        attempt.score,
+                        attempt.created_at.unwrap_or_default()
+                    );
+                }
+            }
+        }
     }
 
     Ok(())
@@ -3229,7 +4248,20 @@ async fn run_mcp_command(global: &GlobalOptions, command: McpCommand) -> Result<
                 active_profile.as_ref().map(|(_, profile)| profile),
                 session.as_ref(),
             );
-            let client = ApiClient::new(base_url.clone(), session.clone())?;
+            let client = ApiClient::new(base_url.clone(), session.clone())?
+                .with_api_token(global.api_token.clone());
+            let (session_role, session_subject) = if global.api_token.is_some() {
+                let snapshot = client
+                    .session()
+                    .await
+                    .context("OPEN_CODELABS_API_TOKEN was rejected by the server")?;
+                (Some(snapshot.role), Some(snapshot.sub))
+            } else {
+                (
+                    session.as_ref().and_then(|stored| stored.role.clone()),
+                    session.as_ref().and_then(|stored| stored.sub.clone()),
+                )
+            };
             let profile_name = active_profile.as_ref().map(|(name, _)| name.clone());
             let runtime_preference = active_profile
                 .as_ref()
@@ -3241,8 +4273,8 @@ async fn run_mcp_command(global: &GlobalOptions, command: McpCommand) -> Result<
                 profile_name,
                 base_url,
                 session_file,
-                session_role: session.as_ref().and_then(|stored| stored.role.clone()),
-                session_subject: session.as_ref().and_then(|stored| stored.sub.clone()),
+                session_role,
+                session_subject,
                 runtime_preference,
             };
 
@@ -4755,10 +5787,34 @@ fn load_api_client_with_session(
         active_profile.as_ref().map(|(_, profile)| profile),
         session.as_ref(),
     );
-    let client = ApiClient::new(base_url, session.clone())?;
+    let client =
+        ApiClient::new(base_url, session.clone())?.with_api_token(global.api_token.clone());
     Ok((client, session_file, session))
 }
 
+/// Loads a client for commands that need credentials, failing early when
+/// neither an API token nor a saved session is available.
+fn load_required_session_client(global: &GlobalOptions) -> Result<ApiClient> {
+    if global.api_token.is_some() {
+        return load_api_client(global);
+    }
+    let config = load_config(&global.config_file)?;
+    let active_profile = resolve_active_profile(global, &config)?;
+    let session_file = resolve_session_file(global, active_profile.as_ref());
+    let session = load_session(&session_file).with_context(|| {
+        format!(
+            "No saved session found. Run `{}` login first.",
+            program_name()
+        )
+    })?;
+    let base_url = resolve_base_url(
+        global.base_url.as_deref(),
+        active_profile.as_ref().map(|(_, profile)| profile),
+        Some(&session),
+    );
+    ApiClient::new(base_url, Some(session))
+}
+
 async fn build_auth_status(global: &GlobalOptions) -> Result<AuthStatusOutput> {
     let config = load_config(&global.config_file)?;
     let active_profile = resolve_active_profile(global, &config)?;
@@ -4769,6 +5825,23 @@ async fn build_auth_status(global: &GlobalOptions) -> Result<AuthStatusOutput> {
         None,
     );
 
+    if let Some(api_token) = &global.api_token {
+        let client =
+            ApiClient::new(base_url.clone(), None)?.with_api_token(Some(api_token.clone()));
+        let result = client.session().await;
+        return Ok(AuthStatusOutput {
+            authenticated: result.is_ok(),
+            profile: active_profile.map(|(name, _)| name),
+            base_url,
+            session_file,
+            subject: result.as_ref().ok().map(|snapshot| snapshot.sub.clone()),
+            role: result.as_ref().ok().map(|snapshot| snapshot.role.clone()),
+            codelab_id: None,
+            expires_at: result.as_ref().ok().map(|snapshot| snapshot.exp),
+            error: result.err().map(|error| error.to_string()),
+        });
+    }
+
     if !session_file.exists() {
         return Ok(AuthStatusOutput {
             authenticated: false,
@@ -5254,6 +6327,36 @@ async fn load_steps_payload(path: &Path) -> Result<UpdateStepsPayload> {
     Ok(UpdateStepsPayload { steps })
 }
 
+async fn load_roster_payload(path: &Path) -> Result<ImportRosterPayload> {
+    let raw = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let is_csv = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    if is_csv {
+        return Ok(ImportRosterPayload {
+            csv: Some(raw),
+            ..ImportRosterPayload::default()
+        });
+    }
+
+    let value: Value = serde_json::from_str(&raw)
+        .with_context(|| format!("Failed to parse JSON from {}", path.display()))?;
+    if value.get("attendees").is_some() {
+        return serde_json::from_value(value)
+            .with_context(|| format!("Invalid roster payload in {}", path.display()));
+    }
+
+    let attendees: Vec<RosterEntry> = serde_json::from_value(value)
+        .with_context(|| format!("Invalid roster array in {}", path.display()))?;
+    Ok(ImportRosterPayload {
+        attendees,
+        ..ImportRosterPayload::default()
+    })
+}
+
 async fn load_json_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
     let raw = tokio::fs::read_to_string(path)
         .await
@@ -5465,6 +6568,9 @@ fn empty_capabilities() -> CliRuntimeCapabilities {
 fn print_session(snapshot: &SessionSnapshot) {
     println!("subject: {}", snapshot.sub);
     println!("role: {}", snapshot.role);
+    if let Some(staff_role) = snapshot.staff_role.as_deref() {
+        println!("staff_role: {staff_role}");
+    }
     if let Some(codelab_id) = snapshot.codelab_id.as_deref() {
         println!("codelab_id: {codelab_id}");
     }
@@ -5494,8 +6600,12 @@ fn print_codelab_detail(codelab: &Codelab, steps: &[Step]) {
         if codelab.is_public != 0 { "yes" } else { "no" }
     );
     println!("steps: {}", steps.len());
+    print_steps(steps);
+}
+
+fn print_steps(steps: &[Step]) {
     for step in steps {
-        println!("  {:>2}. {}", step.step_number, step.title);
+        println!("  {:>2}. {} ({})", step.step_number, step.title, step.id);
     }
 }
 
@@ -5640,6 +6750,333 @@ fn print_audit_logs(logs: &[AuditLog]) {
     }
 }
 
+fn print_user_list(users: &[StaffUser]) {
+    println!(
+        "{:<38} {:<24} {:<12} display_name",
+        "id", "username", "role"
+    );
+    println!("{}", "-".repeat(96));
+    for user in users {
+        println!(
+            "{:<38} {:<24} {:<12} {}",
+            user.id,
+            truncate(&user.username, 24),
+            user.role,
+            user.display_name.as_deref().unwrap_or("-")
+        );
+    }
+}
+
+fn print_api_token_list(tokens: &[ApiToken]) {
+    println!(
+        "{:<38} {:<24} {:<14} {:<12} scopes",
+        "id", "name", "prefix", "expires_at"
+    );
+    println!("{}", "-".repeat(112));
+    for token in tokens {
+        println!(
+            "{:<38} {:<24} {:<14} {:<12} {}",
+            token.id,
+            truncate(&token.name, 24),
+            token.token_prefix,
+            token
+                .expires_at
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "never".to_string()),
+            token.scopes.join(",")
+        );
+    }
+}
+
+/// Draws a QR code with half-block characters, two modules per row of text.
+///
+/// Light modules are drawn as blocks so the code scans on dark terminal themes.
+fn print_terminal_qr(qr: &QrCode) {
+    const QUIET_ZONE: usize = 2;
+    let size = qr.size();
+    let dark = |x: usize, y: usize| {
+        x >= QUIET_ZONE
+            && y >= QUIET_ZONE
+            && x - QUIET_ZONE < size
+            && y - QUIET_ZONE < size
+            && qr.is_dark(x - QUIET_ZONE, y - QUIET_ZONE)
+    };
+    let span = size + QUIET_ZONE * 2;
+    for y in (0..span).step_by(2) {
+        let line: String = (0..span)
+            .map(|x| match (dark(x, y), dark(x, y + 1)) {
+                (true, true) => ' ',
+                (true, false) => '▄',
+                (false, true) => '▀',
+                (false, false) => '█',
+            })
+            .collect();
+        println!("{line}");
+    }
+}
+
+fn print_session_list(sessions: &[AuthSessionRow]) {
+    println!(
+        "{:<38} {:<38} {:<9} {:<12} {:<16} expires_at",
+        "id", "subject", "role", "staff_role", "ip"
+    );
+    println!("{}", "-".repeat(128));
+    for session in sessions {
+        println!(
+            "{:<38} {:<38} {:<9} {:<12} {:<16} {}",
+            session.id,
+            truncate(&session.subject, 38),
+            session.role,
+            session.staff_role.as_deref().unwrap_or("-"),
+            session.ip.as_deref().unwrap_or("-"),
+            session.expires_at_epoch
+        );
+    }
+}
+
+fn print_attendee_erasure(erasure: &AttendeeErasure) {
+    println!(
+        "Erased attendee {} as \"{}\"",
+        erasure.attendee_id, erasure.pseudonym
+    );
+    println!("chat messages deleted:   {}", erasure.chat_messages_deleted);
+    println!("feedback redacted:       {}", erasure.feedback_redacted);
+    println!("quiz answers redacted:   {}", erasure.quiz_answers_redacted);
+    println!(
+        "submissions deleted:     {} ({} file(s))",
+        erasure.submissions_deleted, erasure.files_deleted
+    );
+    println!("AI records deleted:      {}", erasure.ai_records_deleted);
+    println!(
+        "inline comments deleted: {}",
+        erasure.inline_comments_deleted
+    );
+    println!("certificates deleted:    {}", erasure.certificates_deleted);
+    println!("audit logs scrubbed:     {}", erasure.audit_logs_scrubbed);
+    println!("sessions revoked:        {}", erasure.sessions_revoked);
+}
+
+fn print_retention_report(report: &RetentionReport) {
+    let days = |value: Option<u32>| value.map_or("keep".to_string(), |days| format!("{days}d"));
+    println!(
+        "policy: chat={} ai={} submissions={} audit={} (every {}h)",
+        days(report.policy.chat_days),
+        days(report.policy.ai_days),
+        days(report.policy.submission_days),
+        days(report.policy.audit_days),
+        report.policy.interval_hours
+    );
+    println!(
+        "{} {} row(s) and file(s) in {} codelab(s)",
+        if report.dry_run {
+            "Would delete"
+        } else {
+            "Deleted"
+        },
+        report.total(),
+        report.codelab_ids.len()
+    );
+    println!("chat messages:    {}", report.chat_messages);
+    println!(
+        "AI records:       {} conversation(s), {} thread(s), {} message(s)",
+        report.ai_conversations, report.ai_threads, report.ai_messages
+    );
+    println!(
+        "submissions:      {} ({} file(s), {} orphaned file(s))",
+        report.submissions, report.submission_files, report.orphaned_files
+    );
+    println!("audit logs:       {}", report.audit_logs);
+}
+
+fn print_codelab_staff(members: &[CodelabStaffMember]) {
+    println!(
+        "{:<38} {:<24} {:<12} assigned_at",
+        "user_id", "username", "role"
+    );
+    println!("{}", "-".repeat(96));
+    for member in members {
+        println!(
+            "{:<38} {:<24} {:<12} {}",
+            member.user_id,
+            truncate(&member.username, 24),
+            member.role,
+            member.assigned_at.as_deref().unwrap_or("-")
+        );
+    }
+}
+
+fn print_publication(publication: Option<&PublicationStatus>) {
+    match publication {
+        Some(publication) => println!(
+            "published: revision {} of codelab {}",
+            publication
+                .published_revision_number
+                .map(|number| number.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            publication.codelab_id
+        ),
+        None => println!("published: no (draft only; run `oc codelab publish` to publish)"),
+    }
+}
+
+fn print_revisions(revisions: &[CodelabRevisionSummary]) {
+    println!(
+        "{:>8} {:<38} {:<14} {:<24} created_at",
+        "revision", "id", "source", "author"
+    );
+    println!("{}", "-".repeat(110));
+    for revision in revisions {
+        let author = revision
+            .author_name
+            .as_deref()
+            .or(revision.author_id.as_deref())
+            .unwrap_or("-");
+        println!(
+            "{:>8} {:<38} {:<14} {:<24} {}",
+            revision.revision_number,
+            revision.id,
+            revision.source,
+            truncate(author, 24),
+            revision.created_at.as_deref().unwrap_or("-")
+        );
+    }
+}
+
+fn print_cohorts(cohorts: &[CohortSummary]) {
+    println!(
+        "{:<38} {:<24} {:<12} {:<12} {:>9} {:>9} {:>6} {:>8} satisfaction",
+        "id", "name", "starts_at", "ends_at", "attendees", "completed", "help", "feedback"
+    );
+    println!("{}", "-".repeat(140));
+    let schedule = |value: Option<i64>| {
+        value
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    for summary in cohorts {
+        println!(
+            "{:<38} {:<24} {:<12} {:<12} {:>9} {:>9} {:>6} {:>8} {}",
+            summary.cohort.id,
+            truncate(&summary.cohort.name, 24),
+            schedule(summary.cohort.starts_at),
+            schedule(summary.cohort.ends_at),
+            summary.attendee_count,
+            summary.completed_count,
+            summary.help_request_count,
+            summary.feedback_count,
+            summary
+                .avg_satisfaction
+                .map(|value| format!("{value:.2}"))
+                .unwrap_or_else(|| "-".to_string())
+        );
+    }
+}
+
+fn print_codelab_invites(invites: &[CodelabInvite]) {
+    println!(
+        "{:<38} {:<24} {:<14} {:<9} {:<12} status",
+        "id", "label", "prefix", "uses", "expires_at"
+    );
+    println!("{}", "-".repeat(112));
+    let now = now_epoch_seconds() as i64;
+    for invite in invites {
+        let uses = match invite.max_uses {
+            Some(max_uses) => format!("{}/{max_uses}", invite.use_count),
+            None => invite.use_count.to_string(),
+        };
+        let status = if invite.revoked_at.is_some() {
+            "revoked"
+        } else if invite
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= now)
+        {
+            "expired"
+        } else if invite
+            .max_uses
+            .is_some_and(|max_uses| invite.use_count >= max_uses)
+        {
+            "used"
+        } else {
+            "active"
+        };
+        println!(
+            "{:<38} {:<24} {:<14} {:<9} {:<12} {}",
+            invite.id,
+            truncate(invite.label.as_deref().unwrap_or("-"), 24),
+            invite.token_prefix,
+            uses,
+            invite
+                .expires_at
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "never".to_string()),
+            status
+        );
+    }
+}
+
+fn print_roster_import(result: &RosterImportResult) {
+    println!(
+        "{:<5} {:<24} {:<10} {:<10} join_path / error",
+        "row", "name", "status", "code"
+    );
+    println!("{}", "-".repeat(118));
+    for row in &result.rows {
+        let detail = row
+            .join_path
+            .as_deref()
+            .or(row.error.as_deref())
+            .unwrap_or("-");
+        println!(
+            "{:<5} {:<24} {:<10} {:<10} {}",
+            row.row,
+            truncate(&row.name, 24),
+            row.status,
+            row.code.as_deref().unwrap_or("-"),
+            detail
+        );
+    }
+    println!(
+        "\ncreated: {}, duplicates: {}, invalid: {}",
+        result.created, result.duplicates, result.invalid
+    );
+}
+
+fn print_waitlist(entries: &[WaitlistEntry]) {
+    println!(
+        "{:<5} {:<38} {:<24} {:<28} created_at",
+        "pos", "id", "name", "email"
+    );
+    println!("{}", "-".repeat(118));
+    for entry in entries {
+        println!(
+            "{:<5} {:<38} {:<24} {:<28} {}",
+            entry.position,
+            entry.id,
+            truncate(&entry.name, 24),
+            truncate(entry.email.as_deref().unwrap_or("-"), 28),
+            entry.created_at.as_deref().unwrap_or("-")
+        );
+    }
+}
+
+fn print_certificate_verification(verification: &CertificateVerification) {
+    println!("certificate_id: {}", verification.certificate_id);
+    println!("valid: {}", verification.valid);
+    println!("signature_valid: {}", verification.signature_valid);
+    println!("revoked: {}", verification.revoked);
+    if let Some(revoked_at) = &verification.revoked_at {
+        println!("revoked_at: {revoked_at}");
+    }
+    if let Some(reason) = &verification.revocation_reason {
+        println!("revocation_reason: {reason}");
+    }
+    if let Some(claims) = &verification.claims {
+        println!("attendee: {}", claims.attendee_name);
+        println!("codelab: {} ({})", claims.codelab_title, claims.codelab_id);
+        println!("completed_at: {}", claims.completed_at);
+    }
+}
+
 fn print_json<T: Serialize>(value: &T) -> Result<()> {
     println!(
         "{}",
@@ -5685,8 +7122,11 @@ fn parse_cli() -> Result<(GlobalOptions, Command)> {
         "codelab" => Command::Codelab(parse_codelab(&mut args)?),
         "backup" => Command::Backup(parse_backup(&mut args)?),
         "audit" => Command::Audit(parse_audit(&mut args)?),
+        "user" | "users" => Command::User(parse_user(&mut args)?),
+        "token" | "tokens" => Command::Token(parse_token(&mut args)?),
         "workspace" => Command::Workspace(parse_workspace(&mut args)?),
         "attendee" => Command::Attendee(parse_attendee(&mut args)?),
+        "certificate" | "certificates" => Command::Certificate(parse_certificate(&mut args)?),
         "help" => {
             if args.peek().is_some() {
                 Command::HelpDesk(parse_helpdesk(&mut args)?)
@@ -5749,6 +7189,68 @@ fn parse_admin(args: &mut Args) -> Result<AdminCommand> {
             })
         }
         "updates" => Ok(AdminCommand::Updates),
+        "sessions" => {
+            let mut filter = SessionFilterQuery::default();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--user-id" => filter.user_id = Some(args.next_required("--user-id")?),
+                    "--codelab-id" => filter.codelab_id = Some(args.next_required("--codelab-id")?),
+                    "-h" | "--help" => return Err(help_error("admin sessions")),
+                    other => bail!("Unknown admin sessions option: {other}"),
+                }
+            }
+            Ok(AdminCommand::Sessions { filter })
+        }
+        "revoke-sessions" => {
+            let mut id = None;
+            let mut filter = SessionFilterQuery::default();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--id" => id = Some(args.next_required("--id")?),
+                    "--user-id" => filter.user_id = Some(args.next_required("--user-id")?),
+                    "--codelab-id" => filter.codelab_id = Some(args.next_required("--codelab-id")?),
+                    "-h" | "--help" => return Err(help_error("admin revoke-sessions")),
+                    other => bail!("Unknown admin revoke-sessions option: {other}"),
+                }
+            }
+            if id.is_none() && filter.user_id.is_none() && filter.codelab_id.is_none() {
+                bail!("Provide --id, --user-id, or --codelab-id");
+            }
+            Ok(AdminCommand::RevokeSessions { id, filter })
+        }
+        "privacy-find" => Ok(AdminCommand::PrivacyFind {
+            email: parse_required_string_flag(args, "--email", "admin privacy-find")?,
+        }),
+        "privacy-export" => {
+            let mut attendee_id = None;
+            let mut output = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--attendee-id" => attendee_id = Some(args.next_required("--attendee-id")?),
+                    "--output" => output = Some(PathBuf::from(args.next_required("--output")?)),
+                    "-h" | "--help" => return Err(help_error("admin privacy-export")),
+                    other => bail!("Unknown admin privacy-export option: {other}"),
+                }
+            }
+            Ok(AdminCommand::PrivacyExport {
+                attendee_id: attendee_id.ok_or_else(|| anyhow!("Missing --attendee-id"))?,
+                output,
+            })
+        }
+        "retention" => {
+            let mut run = false;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--run" => run = true,
+                    "-h" | "--help" => return Err(help_error("admin retention")),
+                    other => bail!("Unknown admin retention option: {other}"),
+                }
+            }
+            Ok(AdminCommand::Retention { run })
+        }
+        "privacy-erase" => Ok(AdminCommand::PrivacyErase {
+            attendee_id: parse_required_string_flag(args, "--attendee-id", "admin privacy-erase")?,
+        }),
         _ => Err(help_error("admin")),
     }
 }
@@ -5777,12 +7279,43 @@ fn parse_auth(args: &mut Args) -> Result<AuthCommand> {
             }
             Ok(AuthCommand::Login(command))
         }
-        "logout" => Ok(AuthCommand::Logout),
+        "logout" => {
+            let mut all = false;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--all" => all = true,
+                    "-h" | "--help" => return Err(help_error("auth logout")),
+                    other => bail!("Unknown auth logout option: {other}"),
+                }
+            }
+            Ok(AuthCommand::Logout { all })
+        }
         "status" => Ok(AuthCommand::Status),
+        "2fa" => Ok(AuthCommand::TwoFactor(parse_two_factor(args)?)),
         _ => Err(help_error("auth")),
     }
 }
 
+fn parse_two_factor(args: &mut Args) -> Result<TwoFactorCommand> {
+    let Some(subcommand) = args.next() else {
+        return Err(help_error("auth 2fa"));
+    };
+    match subcommand.as_str() {
+        "status" => Ok(TwoFactorCommand::Status),
+        "enroll" => Ok(TwoFactorCommand::Enroll),
+        "confirm" => Ok(TwoFactorCommand::Confirm {
+            code: parse_required_string_flag(args, "--code", "auth 2fa confirm")?,
+        }),
+        "disable" => Ok(TwoFactorCommand::Disable {
+            code: parse_required_string_flag(args, "--code", "auth 2fa disable")?,
+        }),
+        "recovery-codes" => Ok(TwoFactorCommand::RecoveryCodes {
+            code: parse_required_string_flag(args, "--code", "auth 2fa recovery-codes")?,
+        }),
+        _ => Err(help_error("auth 2fa")),
+    }
+}
+
 fn extract_global_options(items: Vec<String>, global: &mut GlobalOptions) -> Result<Vec<String>> {
     let mut filtered = Vec::new();
     let mut index = 0;
@@ -6103,12 +7636,14 @@ fn parse_down(args: &mut Args) -> Result<DownCommand> {
 fn parse_login(args: &mut Args) -> Result<LoginCommand> {
     let mut admin_id = env::var("OPEN_CODELABS_ADMIN_ID").ok();
     let mut admin_pw = env::var("OPEN_CODELABS_ADMIN_PW").ok();
+    let mut otp = None;
     let mut interactive = false;
 
     while let Some(arg) = args.next() {
         match arg.as_str() {
             "--admin-id" => admin_id = Some(args.next_required("--admin-id")?),
             "--admin-pw" => admin_pw = Some(args.next_required("--admin-pw")?),
+            "--otp" => otp = Some(args.next_required("--otp")?),
             "--interactive" => interactive = true,
             "-h" | "--help" => return Err(help_error("login")),
             other => bail!("Unknown login option: {other}"),
@@ -6150,6 +7685,7 @@ fn parse_login(args: &mut Args) -> Result<LoginCommand> {
     Ok(LoginCommand {
         admin_id: admin_id.ok_or_else(|| anyhow!("Missing --admin-id"))?,
         admin_pw: admin_pw.ok_or_else(|| anyhow!("Missing --admin-pw"))?,
+        otp,
     })
 }
 
@@ -6173,6 +7709,7 @@ fn parse_codelab(args: &mut Args) -> Result<CodelabCommand> {
             id: parse_required_string_flag(args, "--id", "codelab copy")?,
         }),
         "export" => parse_codelab_export(args),
+        "build" => parse_codelab_build(args),
         "import" => Ok(CodelabCommand::Import {
             file: PathBuf::from(parse_required_string_flag(
                 args,
@@ -6183,6 +7720,119 @@ fn parse_codelab(args: &mut Args) -> Result<CodelabCommand> {
         "pull" => parse_codelab_pull(args),
         "push" => parse_codelab_push(args),
         "push-steps" => parse_push_steps(args),
+        "step-add" => parse_step_add(args),
+        "step-update" => parse_step_update(args),
+        "step-delete" => parse_step_delete(args),
+        "step-move" => parse_step_move(args),
+        "publish" => Ok(CodelabCommand::Publish {
+            id: parse_required_string_flag(args, "--id", "codelab publish")?,
+        }),
+        "staff" => Ok(CodelabCommand::Staff {
+            id: parse_required_string_flag(args, "--id", "codelab staff")?,
+        }),
+        "staff-add" => {
+            let (id, user_id) = parse_codelab_staff_flags(args, "codelab staff-add")?;
+            Ok(CodelabCommand::StaffAdd { id, user_id })
+        }
+        "staff-remove" => {
+            let (id, user_id) = parse_codelab_staff_flags(args, "codelab staff-remove")?;
+            Ok(CodelabCommand::StaffRemove { id, user_id })
+        }
+        "passcode" => parse_codelab_passcode(args),
+        "invites" => Ok(CodelabCommand::Invites {
+            id: parse_required_string_flag(args, "--id", "codelab invites")?,
+        }),
+        "invite-create" => parse_codelab_invite_create(args),
+        "invite-revoke" => {
+            let mut id = None;
+            let mut invite_id = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--id" => id = Some(args.next_required("--id")?),
+                    "--invite-id" => invite_id = Some(args.next_required("--invite-id")?),
+                    "-h" | "--help" => return Err(help_error("codelab invite-revoke")),
+                    other => bail!("Unknown codelab invite-revoke option: {other}"),
+                }
+            }
+            Ok(CodelabCommand::InviteRevoke {
+                id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+                invite_id: invite_id.ok_or_else(|| anyhow!("Missing --invite-id"))?,
+            })
+        }
+        "revisions" => Ok(CodelabCommand::Revisions {
+            id: parse_required_string_flag(args, "--id", "codelab revisions")?,
+        }),
+        "revision-diff" => {
+            let mut id = None;
+            let mut from = None;
+            let mut to = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--id" => id = Some(args.next_required("--id")?),
+                    "--from" => from = Some(args.next_required("--from")?),
+                    "--to" => to = Some(args.next_required("--to")?),
+                    "-h" | "--help" => return Err(help_error("codelab revision-diff")),
+                    other => bail!("Unknown codelab revision-diff option: {other}"),
+                }
+            }
+            Ok(CodelabCommand::RevisionDiff {
+                id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+                from: from.ok_or_else(|| anyhow!("Missing --from"))?,
+                to,
+            })
+        }
+        "revision-restore" => {
+            let mut id = None;
+            let mut revision = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--id" => id = Some(args.next_required("--id")?),
+                    "--revision" => revision = Some(args.next_required("--revision")?),
+                    "-h" | "--help" => return Err(help_error("codelab revision-restore")),
+                    other => bail!("Unknown codelab revision-restore option: {other}"),
+                }
+            }
+            Ok(CodelabCommand::RevisionRestore {
+                id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+                revision: revision.ok_or_else(|| anyhow!("Missing --revision"))?,
+            })
+        }
+        "cohorts" => Ok(CodelabCommand::Cohorts {
+            id: parse_required_string_flag(args, "--id", "codelab cohorts")?,
+        }),
+        "cohort-create" => {
+            let (id, cohort_id, payload) =
+                parse_codelab_cohort_flags(args, "codelab cohort-create")?;
+            if cohort_id.is_some() {
+                bail!("Unknown codelab cohort-create option: --cohort-id");
+            }
+            Ok(CodelabCommand::CohortCreate { id, payload })
+        }
+        "cohort-update" => {
+            let (id, cohort_id, payload) =
+                parse_codelab_cohort_flags(args, "codelab cohort-update")?;
+            Ok(CodelabCommand::CohortUpdate {
+                id,
+                cohort_id: cohort_id.ok_or_else(|| anyhow!("Missing --cohort-id"))?,
+                payload,
+            })
+        }
+        "cohort-delete" => {
+            let mut id = None;
+            let mut cohort_id = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--id" => id = Some(args.next_required("--id")?),
+                    "--cohort-id" => cohort_id = Some(args.next_required("--cohort-id")?),
+                    "-h" | "--help" => return Err(help_error("codelab cohort-delete")),
+                    other => bail!("Unknown codelab cohort-delete option: {other}"),
+                }
+            }
+            Ok(CodelabCommand::CohortDelete {
+                id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+                cohort_id: cohort_id.ok_or_else(|| anyhow!("Missing --cohort-id"))?,
+            })
+        }
         _ => Err(help_error("codelab")),
     }
 }
@@ -6197,6 +7847,11 @@ fn parse_create_codelab(args: &mut Args) -> Result<CodelabCommand> {
     let mut require_quiz = false;
     let mut require_feedback = false;
     let mut require_submission = false;
+    let mut quiz_max_attempts = None;
+    let mut quiz_pass_percentage = None;
+    let mut max_attendees = None;
+    let mut registration_opens_at = None;
+    let mut registration_closes_at = None;
 
     while let Some(arg) = args.next() {
         match arg.as_str() {
@@ -6209,6 +7864,19 @@ fn parse_create_codelab(args: &mut Args) -> Result<CodelabCommand> {
             "--require-quiz" => require_quiz = true,
             "--require-feedback" => require_feedback = true,
             "--require-submission" => require_submission = true,
+            "--quiz-max-attempts" => {
+                quiz_max_attempts = Some(parse_i32_flag(args, "--quiz-max-attempts")?)
+            }
+            "--quiz-pass-percentage" => {
+                quiz_pass_percentage = Some(parse_i32_flag(args, "--quiz-pass-percentage")?)
+            }
+            "--max-attendees" => max_attendees = Some(parse_i32_flag(args, "--max-attendees")?),
+            "--registration-opens" => {
+                registration_opens_at = Some(parse_time_flag(args, "--registration-opens")?)
+            }
+            "--registration-closes" => {
+                registration_closes_at = Some(parse_time_flag(args, "--registration-closes")?)
+            }
             "-h" | "--help" => return Err(help_error("codelab create")),
             other => bail!("Unknown codelab create option: {other}"),
         }
@@ -6223,6 +7891,11 @@ fn parse_create_codelab(args: &mut Args) -> Result<CodelabCommand> {
         require_quiz,
         require_feedback,
         require_submission,
+        quiz_max_attempts,
+        quiz_pass_percentage,
+        max_attendees,
+        registration_opens_at,
+        registration_closes_at,
         guide_file,
     }))
 }
@@ -6238,10 +7911,19 @@ fn parse_update_codelab(args: &mut Args) -> Result<CodelabCommand> {
     let mut require_quiz = false;
     let mut require_feedback = false;
     let mut require_submission = false;
+    let mut quiz_max_attempts = None;
+    let mut quiz_pass_percentage = None;
+    let mut max_attendees = None;
+    let mut registration_opens_at = None;
+    let mut registration_closes_at = None;
+    let mut expected_version = None;
 
     while let Some(arg) = args.next() {
         match arg.as_str() {
             "--id" => id = Some(args.next_required("--id")?),
+            "--expect-version" => {
+                expected_version = Some(parse_i32_flag(args, "--expect-version")?)
+            }
             "--title" => title = Some(args.next_required("--title")?),
             "--description" => description = Some(args.next_required("--description")?),
             "--author" => author = Some(args.next_required("--author")?),
@@ -6251,6 +7933,19 @@ fn parse_update_codelab(args: &mut Args) -> Result<CodelabCommand> {
             "--require-quiz" => require_quiz = true,
             "--require-feedback" => require_feedback = true,
             "--require-submission" => require_submission = true,
+            "--quiz-max-attempts" => {
+                quiz_max_attempts = Some(parse_i32_flag(args, "--quiz-max-attempts")?)
+            }
+            "--quiz-pass-percentage" => {
+                quiz_pass_percentage = Some(parse_i32_flag(args, "--quiz-pass-percentage")?)
+            }
+            "--max-attendees" => max_attendees = Some(parse_i32_flag(args, "--max-attendees")?),
+            "--registration-opens" => {
+                registration_opens_at = Some(parse_time_flag(args, "--registration-opens")?)
+            }
+            "--registration-closes" => {
+                registration_closes_at = Some(parse_time_flag(args, "--registration-closes")?)
+            }
             "-h" | "--help" => return Err(help_error("codelab update")),
             other => bail!("Unknown codelab update option: {other}"),
         }
@@ -6267,19 +7962,31 @@ fn parse_update_codelab(args: &mut Args) -> Result<CodelabCommand> {
             require_quiz,
             require_feedback,
             require_submission,
+            quiz_max_attempts,
+            quiz_pass_percentage,
+            max_attendees,
+            registration_opens_at,
+            registration_closes_at,
             guide_file,
         },
+        expected_version,
     })
 }
 
 fn parse_codelab_export(args: &mut Args) -> Result<CodelabCommand> {
     let mut id = None;
     let mut output = None;
+    let mut format = ExportFormat::Zip;
 
     while let Some(arg) = args.next() {
         match arg.as_str() {
             "--id" => id = Some(args.next_required("--id")?),
             "--output" => output = Some(PathBuf::from(args.next_required("--output")?)),
+            "--format" => {
+                let value = args.next_required("--format")?;
+                format = ExportFormat::parse(&value)
+                    .ok_or_else(|| anyhow!("Invalid export format: {value}"))?;
+            }
             "-h" | "--help" => return Err(help_error("codelab export")),
             other => bail!("Unknown codelab export option: {other}"),
         }
@@ -6288,17 +7995,43 @@ fn parse_codelab_export(args: &mut Args) -> Result<CodelabCommand> {
     Ok(CodelabCommand::Export {
         id: id.ok_or_else(|| anyhow!("Missing --id"))?,
         output,
+        format,
+    })
+}
+
+fn parse_codelab_build(args: &mut Args) -> Result<CodelabCommand> {
+    let mut id = None;
+    let mut out = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--id" => id = Some(args.next_required("--id")?),
+            "--out" => out = Some(PathBuf::from(args.next_required("--out")?)),
+            "-h" | "--help" => return Err(help_error("codelab build")),
+            other => bail!("Unknown codelab build option: {other}"),
+        }
+    }
+
+    Ok(CodelabCommand::Build {
+        id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+        out,
     })
 }
 
 fn parse_push_steps(args: &mut Args) -> Result<CodelabCommand> {
     let mut id = None;
     let mut file = None;
+    let mut publish = false;
+    let mut expected_version = None;
 
     while let Some(arg) = args.next() {
         match arg.as_str() {
             "--id" => id = Some(args.next_required("--id")?),
             "--file" => file = Some(PathBuf::from(args.next_required("--file")?)),
+            "--publish" => publish = true,
+            "--expect-version" => {
+                expected_version = Some(parse_i32_flag(args, "--expect-version")?)
+            }
             "-h" | "--help" => return Err(help_error("codelab push-steps")),
             other => bail!("Unknown codelab push-steps option: {other}"),
         }
@@ -6307,9 +8040,233 @@ fn parse_push_steps(args: &mut Args) -> Result<CodelabCommand> {
     Ok(CodelabCommand::PushSteps {
         id: id.ok_or_else(|| anyhow!("Missing --id"))?,
         file: file.ok_or_else(|| anyhow!("Missing --file"))?,
+        publish,
+        expected_version,
+    })
+}
+
+fn parse_step_add(args: &mut Args) -> Result<CodelabCommand> {
+    let mut id = None;
+    let mut title = None;
+    let mut content_file = None;
+    let mut position = None;
+    let mut expected_version = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--id" => id = Some(args.next_required("--id")?),
+            "--title" => title = Some(args.next_required("--title")?),
+            "--content-file" => {
+                content_file = Some(PathBuf::from(args.next_required("--content-file")?))
+            }
+            "--position" => position = Some(parse_i32_flag(args, "--position")?),
+            "--expect-version" => {
+                expected_version = Some(parse_i32_flag(args, "--expect-version")?)
+            }
+            "-h" | "--help" => return Err(help_error("codelab step-add")),
+            other => bail!("Unknown codelab step-add option: {other}"),
+        }
+    }
+
+    Ok(CodelabCommand::StepAdd {
+        id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+        title: title.ok_or_else(|| anyhow!("Missing --title"))?,
+        content_file: content_file.ok_or_else(|| anyhow!("Missing --content-file"))?,
+        position,
+        expected_version,
+    })
+}
+
+fn parse_step_update(args: &mut Args) -> Result<CodelabCommand> {
+    let mut id = None;
+    let mut step_id = None;
+    let mut title = None;
+    let mut content_file = None;
+    let mut expected_version = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--id" => id = Some(args.next_required("--id")?),
+            "--step-id" => step_id = Some(args.next_required("--step-id")?),
+            "--title" => title = Some(args.next_required("--title")?),
+            "--content-file" => {
+                content_file = Some(PathBuf::from(args.next_required("--content-file")?))
+            }
+            "--expect-version" => {
+                expected_version = Some(parse_i32_flag(args, "--expect-version")?)
+            }
+            "-h" | "--help" => return Err(help_error("codelab step-update")),
+            other => bail!("Unknown codelab step-update option: {other}"),
+        }
+    }
+
+    Ok(CodelabCommand::StepUpdate {
+        id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+        step_id: step_id.ok_or_else(|| anyhow!("Missing --step-id"))?,
+        title: title.ok_or_else(|| anyhow!("Missing --title"))?,
+        content_file: content_file.ok_or_else(|| anyhow!("Missing --content-file"))?,
+        expected_version,
+    })
+}
+
+fn parse_step_delete(args: &mut Args) -> Result<CodelabCommand> {
+    let mut id = None;
+    let mut step_id = None;
+    let mut expected_version = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--id" => id = Some(args.next_required("--id")?),
+            "--step-id" => step_id = Some(args.next_required("--step-id")?),
+            "--expect-version" => {
+                expected_version = Some(parse_i32_flag(args, "--expect-version")?)
+            }
+            "-h" | "--help" => return Err(help_error("codelab step-delete")),
+            other => bail!("Unknown codelab step-delete option: {other}"),
+        }
+    }
+
+    Ok(CodelabCommand::StepDelete {
+        id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+        step_id: step_id.ok_or_else(|| anyhow!("Missing --step-id"))?,
+        expected_version,
+    })
+}
+
+fn parse_step_move(args: &mut Args) -> Result<CodelabCommand> {
+    let mut id = None;
+    let mut step_id = None;
+    let mut position = None;
+    let mut expected_version = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--id" => id = Some(args.next_required("--id")?),
+            "--step-id" => step_id = Some(args.next_required("--step-id")?),
+            "--position" => position = Some(parse_i32_flag(args, "--position")?),
+            "--expect-version" => {
+                expected_version = Some(parse_i32_flag(args, "--expect-version")?)
+            }
+            "-h" | "--help" => return Err(help_error("codelab step-move")),
+            other => bail!("Unknown codelab step-move option: {other}"),
+        }
+    }
+
+    Ok(CodelabCommand::StepMove {
+        id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+        step_id: step_id.ok_or_else(|| anyhow!("Missing --step-id"))?,
+        position: position.ok_or_else(|| anyhow!("Missing --position"))?,
+        expected_version,
     })
 }
 
+fn parse_codelab_staff_flags(args: &mut Args, topic: &str) -> Result<(String, String)> {
+    let mut id = None;
+    let mut user_id = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--id" => id = Some(args.next_required("--id")?),
+            "--user-id" => user_id = Some(args.next_required("--user-id")?),
+            "-h" | "--help" => return Err(help_error(topic)),
+            other => bail!("Unknown {topic} option: {other}"),
+        }
+    }
+
+    Ok((
+        id.ok_or_else(|| anyhow!("Missing --id"))?,
+        user_id.ok_or_else(|| anyhow!("Missing --user-id"))?,
+    ))
+}
+
+fn parse_codelab_passcode(args: &mut Args) -> Result<CodelabCommand> {
+    let mut id = None;
+    let mut action = PasscodeAction::Show;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--id" => id = Some(args.next_required("--id")?),
+            "--set" => action = PasscodeAction::Set(args.next_required("--set")?),
+            "--clear" => action = PasscodeAction::Clear,
+            "-h" | "--help" => return Err(help_error("codelab passcode")),
+            other => bail!("Unknown codelab passcode option: {other}"),
+        }
+    }
+
+    Ok(CodelabCommand::Passcode {
+        id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+        action,
+    })
+}
+
+fn parse_codelab_invite_create(args: &mut Args) -> Result<CodelabCommand> {
+    let mut id = None;
+    let mut payload = CreateCodelabInvitePayload::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--id" => id = Some(args.next_required("--id")?),
+            "--label" => payload.label = Some(args.next_required("--label")?),
+            "--max-uses" => {
+                let value = args.next_required("--max-uses")?;
+                payload.max_uses = Some(
+                    value
+                        .parse::<u32>()
+                        .with_context(|| format!("Invalid --max-uses: {value}"))?,
+                );
+            }
+            "--expires-in-hours" => {
+                let value = args.next_required("--expires-in-hours")?;
+                payload.expires_in_hours = Some(
+                    value
+                        .parse::<u32>()
+                        .with_context(|| format!("Invalid --expires-in-hours: {value}"))?,
+                );
+            }
+            "-h" | "--help" => return Err(help_error("codelab invite-create")),
+            other => bail!("Unknown codelab invite-create option: {other}"),
+        }
+    }
+
+    Ok(CodelabCommand::InviteCreate {
+        id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+        payload,
+    })
+}
+
+fn parse_codelab_cohort_flags(
+    args: &mut Args,
+    help_topic: &str,
+) -> Result<(String, Option<String>, CreateCohort)> {
+    let mut id = None;
+    let mut cohort_id = None;
+    let mut name = None;
+    let mut starts_at = None;
+    let mut ends_at = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--id" => id = Some(args.next_required("--id")?),
+            "--cohort-id" => cohort_id = Some(args.next_required("--cohort-id")?),
+            "--name" => name = Some(args.next_required("--name")?),
+            "--starts" => starts_at = Some(parse_time_flag(args, "--starts")?),
+            "--ends" => ends_at = Some(parse_time_flag(args, "--ends")?),
+            "-h" | "--help" => return Err(help_error(help_topic)),
+            other => bail!("Unknown {help_topic} option: {other}"),
+        }
+    }
+
+    Ok((
+        id.ok_or_else(|| anyhow!("Missing --id"))?,
+        cohort_id,
+        CreateCohort {
+            name: name.ok_or_else(|| anyhow!("Missing --name"))?,
+            starts_at: starts_at.filter(|value| *value > 0),
+            ends_at: ends_at.filter(|value| *value > 0),
+        },
+    ))
+}
+
 fn parse_codelab_pull(args: &mut Args) -> Result<CodelabCommand> {
     let mut id = None;
     let mut output = None;
@@ -6339,11 +8296,15 @@ fn parse_codelab_pull(args: &mut Args) -> Result<CodelabCommand> {
 fn parse_codelab_push(args: &mut Args) -> Result<CodelabCommand> {
     let mut manifest = None;
     let mut id = None;
+    let mut publish = false;
+    let mut force = false;
 
     while let Some(arg) = args.next() {
         match arg.as_str() {
             "--manifest" => manifest = Some(PathBuf::from(args.next_required("--manifest")?)),
             "--id" => id = Some(args.next_required("--id")?),
+            "--publish" => publish = true,
+            "--force" => force = true,
             "-h" | "--help" => return Err(help_error("codelab push")),
             other => bail!("Unknown codelab push option: {other}"),
         }
@@ -6352,6 +8313,8 @@ fn parse_codelab_push(args: &mut Args) -> Result<CodelabCommand> {
     Ok(CodelabCommand::Push {
         manifest: manifest.ok_or_else(|| anyhow!("Missing --manifest"))?,
         id,
+        publish,
+        force,
     })
 }
 
@@ -6390,6 +8353,113 @@ fn parse_backup(args: &mut Args) -> Result<BackupCommand> {
     }
 }
 
+fn parse_user(args: &mut Args) -> Result<UserCommand> {
+    let Some(subcommand) = args.next() else {
+        return Err(help_error("user"));
+    };
+    match subcommand.as_str() {
+        "list" => Ok(UserCommand::List),
+        "create" => {
+            let mut username = None;
+            let mut password = None;
+            let mut role = None;
+            let mut display_name = None;
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--username" => username = Some(args.next_required("--username")?),
+                    "--password" => password = Some(args.next_required("--password")?),
+                    "--role" => role = Some(args.next_required("--role")?),
+                    "--display-name" => display_name = Some(args.next_required("--display-name")?),
+                    "-h" | "--help" => return Err(help_error("user create")),
+                    other => bail!("Unknown user create option: {other}"),
+                }
+            }
+
+            Ok(UserCommand::Create {
+                username: username.ok_or_else(|| anyhow!("Missing --username"))?,
+                password: password
+                    .or_else(|| env::var("OPEN_CODELABS_USER_PASSWORD").ok())
+                    .ok_or_else(|| anyhow!("Missing --password"))?,
+                role: role.ok_or_else(|| anyhow!("Missing --role"))?,
+                display_name,
+            })
+        }
+        "update" => {
+            let mut id = None;
+            let mut password = None;
+            let mut role = None;
+            let mut display_name = None;
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--id" => id = Some(args.next_required("--id")?),
+                    "--password" => password = Some(args.next_required("--password")?),
+                    "--role" => role = Some(args.next_required("--role")?),
+                    "--display-name" => display_name = Some(args.next_required("--display-name")?),
+                    "-h" | "--help" => return Err(help_error("user update")),
+                    other => bail!("Unknown user update option: {other}"),
+                }
+            }
+
+            Ok(UserCommand::Update {
+                id: id.ok_or_else(|| anyhow!("Missing --id"))?,
+                password,
+                role,
+                display_name,
+            })
+        }
+        "delete" => Ok(UserCommand::Delete {
+            id: parse_required_string_flag(args, "--id", "user delete")?,
+        }),
+        "reset-2fa" => Ok(UserCommand::ResetTwoFactor {
+            id: parse_required_string_flag(args, "--id", "user reset-2fa")?,
+        }),
+        _ => Err(help_error("user")),
+    }
+}
+
+fn parse_token(args: &mut Args) -> Result<TokenCommand> {
+    let Some(subcommand) = args.next() else {
+        return Err(help_error("token"));
+    };
+    match subcommand.as_str() {
+        "list" => Ok(TokenCommand::List),
+        "create" => {
+            let mut name = None;
+            let mut scopes = Vec::new();
+            let mut expires_in_days = None;
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--name" => name = Some(args.next_required("--name")?),
+                    "--scope" => scopes.push(args.next_required("--scope")?),
+                    "--expires-in-days" => {
+                        let value = args.next_required("--expires-in-days")?;
+                        expires_in_days = Some(
+                            value
+                                .parse::<u32>()
+                                .with_context(|| format!("Invalid --expires-in-days: {value}"))?,
+                        );
+                    }
+                    "-h" | "--help" => return Err(help_error("token create")),
+                    other => bail!("Unknown token create option: {other}"),
+                }
+            }
+
+            Ok(TokenCommand::Create {
+                name: name.ok_or_else(|| anyhow!("Missing --name"))?,
+                scopes,
+                expires_in_days,
+            })
+        }
+        "revoke" => Ok(TokenCommand::Revoke {
+            id: parse_required_string_flag(args, "--id", "token revoke")?,
+        }),
+        _ => Err(help_error("token")),
+    }
+}
+
 fn parse_audit(args: &mut Args) -> Result<AuditCommand> {
     let Some(subcommand) = args.next() else {
         return Err(help_error("audit"));
@@ -6437,6 +8507,55 @@ fn parse_audit(args: &mut Args) -> Result<AuditCommand> {
     }
 }
 
+fn parse_certificate(args: &mut Args) -> Result<CertificateCommand> {
+    let Some(subcommand) = args.next() else {
+        return Err(help_error("certificate"));
+    };
+
+    match subcommand.as_str() {
+        "verify" => Ok(CertificateCommand::Verify {
+            cert_id: parse_required_string_flag(args, "--cert-id", "certificate verify")?,
+        }),
+        "revoke" => {
+            let mut cert_id = None;
+            let mut reason = None;
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--cert-id" => cert_id = Some(args.next_required("--cert-id")?),
+                    "--reason" => reason = Some(args.next_required("--reason")?),
+                    "-h" | "--help" => return Err(help_error("certificate revoke")),
+                    other => bail!("Unknown certificate revoke option: {other}"),
+                }
+            }
+
+            Ok(CertificateCommand::Revoke {
+                cert_id: cert_id.ok_or_else(|| anyhow!("Missing --cert-id"))?,
+                reason,
+            })
+        }
+        "credential" => {
+            let mut attendee_id = None;
+            let mut output = None;
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--attendee-id" => attendee_id = Some(args.next_required("--attendee-id")?),
+                    "--output" => output = Some(PathBuf::from(args.next_required("--output")?)),
+                    "-h" | "--help" => return Err(help_error("certificate credential")),
+                    other => bail!("Unknown certificate credential option: {other}"),
+                }
+            }
+
+            Ok(CertificateCommand::Credential {
+                attendee_id: attendee_id.ok_or_else(|| anyhow!("Missing --attendee-id"))?,
+                output,
+            })
+        }
+        _ => Err(help_error("certificate")),
+    }
+}
+
 fn parse_workspace(args: &mut Args) -> Result<WorkspaceCommand> {
     let Some(subcommand) = args.next() else {
         return Err(help_error("workspace"));
@@ -6709,6 +8828,9 @@ fn parse_attendee(args: &mut Args) -> Result<AttendeeCommand> {
             let mut name = None;
             let mut code = None;
             let mut email = None;
+            let mut passcode = None;
+            let mut invite = None;
+            let mut cohort_id = None;
 
             while let Some(arg) = args.next() {
                 match arg.as_str() {
@@ -6716,6 +8838,9 @@ fn parse_attendee(args: &mut Args) -> Result<AttendeeCommand> {
                     "--name" => name = Some(args.next_required("--name")?),
                     "--code" => code = Some(args.next_required("--code")?),
                     "--email" => email = Some(args.next_required("--email")?),
+                    "--passcode" => passcode = Some(args.next_required("--passcode")?),
+                    "--invite" => invite = Some(args.next_required("--invite")?),
+                    "--cohort-id" => cohort_id = Some(args.next_required("--cohort-id")?),
                     "-h" | "--help" => return Err(help_error("attendee join")),
                     other => bail!("Unknown attendee join option: {other}"),
                 }
@@ -6726,24 +8851,89 @@ fn parse_attendee(args: &mut Args) -> Result<AttendeeCommand> {
                 name: name.ok_or_else(|| anyhow!("Missing --name"))?,
                 code: code.ok_or_else(|| anyhow!("Missing --code"))?,
                 email,
+                passcode,
+                invite,
+                cohort_id,
+            })
+        }
+        "list" => {
+            let mut codelab_id = None;
+            let mut cohort_id = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--codelab-id" => codelab_id = Some(args.next_required("--codelab-id")?),
+                    "--cohort-id" => cohort_id = Some(args.next_required("--cohort-id")?),
+                    "-h" | "--help" => return Err(help_error("attendee list")),
+                    other => bail!("Unknown attendee list option: {other}"),
+                }
+            }
+            Ok(AttendeeCommand::List {
+                codelab_id: codelab_id.ok_or_else(|| anyhow!("Missing --codelab-id"))?,
+                cohort_id,
             })
         }
-        "list" => Ok(AttendeeCommand::List {
-            codelab_id: parse_required_string_flag(args, "--codelab-id", "attendee list")?,
-        }),
         "complete" => Ok(AttendeeCommand::Complete {
             codelab_id: parse_required_string_flag(args, "--codelab-id", "attendee complete")?,
         }),
         "certificate" => {
             let mut attendee_id = None;
+            let mut pdf = None;
             while let Some(arg) = args.next() {
                 match arg.as_str() {
                     "--attendee-id" => attendee_id = Some(args.next_required("--attendee-id")?),
+                    "--pdf" => pdf = Some(PathBuf::from(args.next_required("--pdf")?)),
                     "-h" | "--help" => return Err(help_error("attendee certificate")),
                     other => bail!("Unknown attendee certificate option: {other}"),
                 }
             }
-            Ok(AttendeeCommand::Certificate { attendee_id })
+            Ok(AttendeeCommand::Certificate { attendee_id, pdf })
+        }
+        "import" => {
+            let mut codelab_id = None;
+            let mut file = None;
+            let mut cohort_id = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--codelab-id" => codelab_id = Some(args.next_required("--codelab-id")?),
+                    "--file" => file = Some(PathBuf::from(args.next_required("--file")?)),
+                    "--cohort-id" => cohort_id = Some(args.next_required("--cohort-id")?),
+                    "-h" | "--help" => return Err(help_error("attendee import")),
+                    other => bail!("Unknown attendee import option: {other}"),
+                }
+            }
+            Ok(AttendeeCommand::Import {
+                codelab_id: codelab_id.ok_or_else(|| anyhow!("Missing --codelab-id"))?,
+                file: file.ok_or_else(|| anyhow!("Missing --file"))?,
+                cohort_id,
+            })
+        }
+        "waitlist" => Ok(AttendeeCommand::Waitlist {
+            codelab_id: parse_required_string_flag(args, "--codelab-id", "attendee waitlist")?,
+        }),
+        "promote" | "waitlist-remove" => {
+            let mut codelab_id = None;
+            let mut waitlist_id = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--codelab-id" => codelab_id = Some(args.next_required("--codelab-id")?),
+                    "--waitlist-id" => waitlist_id = Some(args.next_required("--waitlist-id")?),
+                    "-h" | "--help" => return Err(help_error(&format!("attendee {subcommand}"))),
+                    other => bail!("Unknown attendee {subcommand} option: {other}"),
+                }
+            }
+            let codelab_id = codelab_id.ok_or_else(|| anyhow!("Missing --codelab-id"))?;
+            let waitlist_id = waitlist_id.ok_or_else(|| anyhow!("Missing --waitlist-id"))?;
+            if subcommand == "promote" {
+                Ok(AttendeeCommand::Promote {
+                    codelab_id,
+                    waitlist_id,
+                })
+            } else {
+                Ok(AttendeeCommand::WaitlistRemove {
+                    codelab_id,
+                    waitlist_id,
+                })
+            }
         }
         _ => Err(help_error("attendee")),
     }
@@ -6953,6 +9143,9 @@ fn parse_quiz(args: &mut Args) -> Result<QuizCommand> {
         "submissions" => Ok(QuizCommand::Submissions {
             codelab_id: parse_required_string_flag(args, "--codelab-id", "quiz submissions")?,
         }),
+        "attempts" => Ok(QuizCommand::Attempts {
+            codelab_id: parse_required_string_flag(args, "--codelab-id", "quiz attempts")?,
+        }),
         _ => Err(help_error("quiz")),
     }
 }
@@ -7231,6 +9424,29 @@ fn parse_required_string_flag(args: &mut Args, flag: &str, help_topic: &str) ->
     value.ok_or_else(|| anyhow!("Missing {flag}"))
 }
 
+fn parse_i32_flag(args: &mut Args, flag: &str) -> Result<i32> {
+    let value = args.next_required(flag)?;
+    value
+        .parse::<i32>()
+        .with_context(|| format!("Invalid value for {flag}: {value}"))
+}
+
+/// Parses an RFC 3339 timestamp or epoch seconds; `none` maps to `0`, which clears the bound.
+fn parse_time_flag(args: &mut Args, flag: &str) -> Result<i64> {
+    let value = args.next_required(flag)?;
+    if value.eq_ignore_ascii_case("none") {
+        return Ok(0);
+    }
+    if let Ok(epoch) = value.parse::<i64>() {
+        return Ok(epoch);
+    }
+    chrono::DateTime::parse_from_rfc3339(&value)
+        .map(|time| time.timestamp())
+        .with_context(|| {
+            format!("Invalid value for {flag}: {value} (use RFC 3339 or epoch seconds)")
+        })
+}
+
 fn help_error(topic: &str) -> anyhow::Error {
     anyhow::Error::new(HelpRequested {
         topic: topic.to_string(),
@@ -7253,9 +9469,20 @@ fn command_help_lines() -> &'static [&'static str] {
         "init",
         "admin settings [--gemini-api-key <key>] [--admin-password <pw>]",
         "admin updates",
+        "admin sessions [--user-id <id>] [--codelab-id <id>]",
+        "admin revoke-sessions [--id <session-id>] [--user-id <id>] [--codelab-id <id>]",
+        "admin privacy-find --email <email>",
+        "admin privacy-export --attendee-id <id> [--output <path>]",
+        "admin privacy-erase --attendee-id <id>",
+        "admin retention [--run]",
         "auth login [--no-open] [--interactive]",
-        "auth logout",
+        "auth logout [--all]",
         "auth status",
+        "auth 2fa status",
+        "auth 2fa enroll",
+        "auth 2fa confirm --code <code>",
+        "auth 2fa disable --code <code>",
+        "auth 2fa recovery-codes --code <code>",
         "connect add --name <name> --url <url> [--runtime <auto|backend|firebase|supabase>] [--activate] [--interactive]",
         "connect use [--name <name>]",
         "connect list",
@@ -7270,25 +9497,53 @@ fn command_help_lines() -> &'static [&'static str] {
         "logs [--service <name>] [--tail <n>] [--no-follow]",
         "restart [--service <name>]",
         "down [--volumes]",
-        "login [--admin-id <id>] [--admin-pw <pw>] [--interactive]   Legacy direct login",
+        "login [--admin-id <id>] [--admin-pw <pw>] [--otp <code>] [--interactive]   Legacy direct login",
         "logout                                   Legacy alias for auth logout",
         "session                                  Legacy alias for auth status",
         "codelab list",
         "codelab reference",
         "codelab get --id <id>",
-        "codelab create --title <title> --description <desc> --author <author> [--private] [--guide-file <path>] [--quiz-enabled] [--require-quiz] [--require-feedback] [--require-submission]",
-        "codelab update --id <id> --title <title> --description <desc> --author <author> [--private] [--guide-file <path>] [--quiz-enabled] [--require-quiz] [--require-feedback] [--require-submission]",
+        "codelab create --title <title> --description <desc> --author <author> [--private] [--guide-file <path>] [--quiz-enabled] [--require-quiz] [--require-feedback] [--require-submission] [--quiz-max-attempts <n>] [--quiz-pass-percentage <0-100>] [--max-attendees <n>] [--registration-opens <time>] [--registration-closes <time>]",
+        "codelab update --id <id> --title <title> --description <desc> --author <author> [--private] [--guide-file <path>] [--quiz-enabled] [--require-quiz] [--require-feedback] [--require-submission] [--quiz-max-attempts <n>] [--quiz-pass-percentage <0-100>] [--max-attendees <n>] [--registration-opens <time>] [--registration-closes <time>] [--expect-version <n>]",
         "codelab delete --id <id>",
         "codelab copy --id <id>",
-        "codelab export --id <id> [--output <path>]",
-        "codelab import --file <zip>",
+        "codelab export --id <id> [--output <path>] [--format <zip|claat|html>]",
+        "codelab build --id <id> [--out <dir>]",
+        "codelab import --file <zip|md>",
         "codelab pull --id <id> [--output <dir>] [--format <yaml|json>]",
-        "codelab push --manifest <path> [--id <id>]",
-        "codelab push-steps --id <id> --file <json>",
+        "codelab push --manifest <path> [--id <id>] [--publish] [--force]",
+        "codelab push-steps --id <id> --file <json> [--publish] [--expect-version <n>]",
+        "codelab step-add --id <id> --title <title> --content-file <md> [--position <n>] [--expect-version <n>]",
+        "codelab step-update --id <id> --step-id <id> --title <title> --content-file <md> [--expect-version <n>]",
+        "codelab step-delete --id <id> --step-id <id> [--expect-version <n>]",
+        "codelab step-move --id <id> --step-id <id> --position <n> [--expect-version <n>]",
+        "codelab publish --id <id>",
+        "codelab revisions --id <id>",
+        "codelab revision-diff --id <id> --from <revision> [--to <revision>]",
+        "codelab revision-restore --id <id> --revision <revision>",
+        "codelab staff --id <id>",
+        "codelab staff-add --id <id> --user-id <id>",
+        "codelab staff-remove --id <id> --user-id <id>",
+        "codelab passcode --id <id> [--set <passcode> | --clear]",
+        "codelab invites --id <id>",
+        "codelab invite-create --id <id> [--label <text>] [--max-uses <n>] [--expires-in-hours <n>]",
+        "codelab invite-revoke --id <id> --invite-id <id>",
+        "codelab cohorts --id <id>",
+        "codelab cohort-create --id <id> --name <name> [--starts <time>] [--ends <time>]",
+        "codelab cohort-update --id <id> --cohort-id <id> --name <name> [--starts <time>] [--ends <time>]",
+        "codelab cohort-delete --id <id> --cohort-id <id>",
         "backup export [--output <path>]",
         "backup inspect --file <zip>",
         "backup restore --file <zip>",
         "audit logs [--limit <n>] [--offset <n>] [--action <name>] [--codelab-id <id>]",
+        "user list",
+        "user create --username <name> --password <pw> --role <owner|author|facilitator|ta> [--display-name <name>]",
+        "user update --id <id> [--role <owner|author|facilitator|ta>] [--password <pw>] [--display-name <name>]",
+        "user delete --id <id>",
+        "user reset-2fa --id <id>",
+        "token list",
+        "token create --name <name> [--scope <permission>]... [--expires-in-days <n>]",
+        "token revoke --id <id>",
         "workspace create --codelab-id <id> [--structure-type <branch|folder>] [--files-json <path>]",
         "workspace info --codelab-id <id>",
         "workspace download --codelab-id <id> [--output <path>]",
@@ -7303,10 +9558,17 @@ fn command_help_lines() -> &'static [&'static str] {
         "workspace folder-files --codelab-id <id> --folder <name>",
         "workspace folder-read --codelab-id <id> --folder <name> --file <path>",
         "workspace folder-update --codelab-id <id> --folder <name> --files-json <path> [--delete-json <path>]",
-        "attendee join --codelab-id <id> --name <name> --code <code> [--email <email>]",
-        "attendee list --codelab-id <id>",
+        "attendee join --codelab-id <id> --name <name> --code <code> [--email <email>] [--passcode <passcode> | --invite <token>] [--cohort-id <id>]",
+        "attendee list --codelab-id <id> [--cohort-id <id>]",
+        "attendee import --codelab-id <id> --file <roster.csv|roster.json> [--cohort-id <id>]",
         "attendee complete --codelab-id <id>",
-        "attendee certificate [--attendee-id <id>]",
+        "attendee certificate [--attendee-id <id>] [--pdf <path>]",
+        "attendee waitlist --codelab-id <id>",
+        "attendee promote --codelab-id <id> --waitlist-id <id>",
+        "attendee waitlist-remove --codelab-id <id> --waitlist-id <id>",
+        "certificate verify --cert-id <id>",
+        "certificate revoke --cert-id <id> [--reason <text>]",
+        "certificate credential --attendee-id <id> [--output <path>]",
         "help request --codelab-id <id> --step-number <n>",
         "help list --codelab-id <id>",
         "help resolve --codelab-id <id> --help-id <id>",
@@ -7320,6 +9582,7 @@ fn command_help_lines() -> &'static [&'static str] {
         "quiz update --codelab-id <id> --file <json>",
         "quiz submit --codelab-id <id> --file <json>",
         "quiz submissions --codelab-id <id>",
+        "quiz attempts --codelab-id <id>",
         "submission list --codelab-id <id>",
         "submission file --codelab-id <id> [--attendee-id <id>] --file <path>",
         "submission link --codelab-id <id> [--attendee-id <id>] --url <url> [--title <title>]",
@@ -7346,6 +9609,7 @@ fn environment_help_lines() -> &'static [&'static str] {
         "OPEN_CODELABS_BASE_URL",
         "OPEN_CODELABS_ADMIN_ID",
         "OPEN_CODELABS_ADMIN_PW",
+        "OPEN_CODELABS_API_TOKEN",
         "OPEN_CODELABS_CONFIG_FILE",
         "OPEN_CODELABS_PROFILE",
         "OPEN_CODELABS_SESSION_FILE",
@@ -7573,6 +9837,563 @@ mod tests {
         assert_eq!(help.topic, "run");
     }
 
+    #[test]
+    fn parse_token_create_collects_repeated_scopes() {
+        let mut args = Args::new(
+            [
+                "create",
+                "--name",
+                "ci",
+                "--scope",
+                "edit_content",
+                "--scope",
+                "run_sessions",
+                "--expires-in-days",
+                "30",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        match parse_token(&mut args).expect("token create") {
+            TokenCommand::Create {
+                name,
+                scopes,
+                expires_in_days,
+            } => {
+                assert_eq!(name, "ci");
+                assert_eq!(scopes, vec!["edit_content", "run_sessions"]);
+                assert_eq!(expires_in_days, Some(30));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(vec!["create".to_string()]);
+        assert!(parse_token(&mut args).is_err());
+    }
+
+    #[test]
+    fn parse_admin_revoke_sessions_requires_a_target() {
+        let mut args = Args::new(
+            ["revoke-sessions", "--codelab-id", "lab-1"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_admin(&mut args).expect("admin revoke-sessions") {
+            AdminCommand::RevokeSessions { id, filter } => {
+                assert!(id.is_none());
+                assert!(filter.user_id.is_none());
+                assert_eq!(filter.codelab_id.as_deref(), Some("lab-1"));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(vec!["revoke-sessions".to_string()]);
+        assert!(parse_admin(&mut args).is_err());
+
+        let mut args = Args::new(
+            [
+                "privacy-export",
+                "--attendee-id",
+                "att-1",
+                "--output",
+                "ada.zip",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        match parse_admin(&mut args).expect("admin privacy-export") {
+            AdminCommand::PrivacyExport {
+                attendee_id,
+                output,
+            } => {
+                assert_eq!(attendee_id, "att-1");
+                assert_eq!(output, Some(PathBuf::from("ada.zip")));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(vec!["privacy-erase".to_string()]);
+        assert!(parse_admin(&mut args).is_err());
+
+        let mut args = Args::new(vec!["retention".to_string(), "--run".to_string()]);
+        assert!(matches!(
+            parse_admin(&mut args).expect("admin retention"),
+            AdminCommand::Retention { run: true }
+        ));
+
+        let mut args = Args::new(vec!["logout".to_string(), "--all".to_string()]);
+        assert!(matches!(
+            parse_auth(&mut args).expect("auth logout"),
+            AuthCommand::Logout { all: true }
+        ));
+    }
+
+    #[test]
+    fn parse_auth_two_factor_requires_code() {
+        let mut args = Args::new(
+            ["2fa", "confirm", "--code", "123456"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_auth(&mut args).expect("auth 2fa confirm") {
+            AuthCommand::TwoFactor(TwoFactorCommand::Confirm { code }) => {
+                assert_eq!(code, "123456");
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(vec!["2fa".to_string(), "disable".to_string()]);
+        assert!(parse_auth(&mut args).is_err());
+
+        let mut args = Args::new(
+            ["reset-2fa", "--id", "user-1"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        assert!(matches!(
+            parse_user(&mut args).expect("user reset-2fa"),
+            UserCommand::ResetTwoFactor { id } if id == "user-1"
+        ));
+    }
+
+    #[test]
+    fn parse_codelab_join_access_commands() {
+        let mut args = Args::new(
+            ["passcode", "--id", "lab-1", "--set", "open-sesame"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab passcode") {
+            CodelabCommand::Passcode { id, action } => {
+                assert_eq!(id, "lab-1");
+                assert_eq!(action, PasscodeAction::Set("open-sesame".to_string()));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            [
+                "invite-create",
+                "--id",
+                "lab-1",
+                "--label",
+                "team a",
+                "--max-uses",
+                "25",
+                "--expires-in-hours",
+                "48",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab invite-create") {
+            CodelabCommand::InviteCreate { id, payload } => {
+                assert_eq!(id, "lab-1");
+                assert_eq!(payload.label.as_deref(), Some("team a"));
+                assert_eq!(payload.max_uses, Some(25));
+                assert_eq!(payload.expires_in_hours, Some(48));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            ["invite-revoke", "--id", "lab-1"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        assert!(parse_codelab(&mut args).is_err());
+    }
+
+    #[test]
+    fn parse_codelab_revision_commands() {
+        let mut args = Args::new(
+            ["revision-diff", "--id", "lab-1", "--from", "2"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab revision-diff") {
+            CodelabCommand::RevisionDiff { id, from, to } => {
+                assert_eq!(id, "lab-1");
+                assert_eq!(from, "2");
+                assert_eq!(to, None);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            ["revision-restore", "--id", "lab-1"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        assert!(parse_codelab(&mut args).is_err());
+    }
+
+    #[test]
+    fn parse_codelab_publish_commands() {
+        let mut args = Args::new(
+            [
+                "push-steps",
+                "--id",
+                "lab-1",
+                "--file",
+                "steps.json",
+                "--publish",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab push-steps") {
+            CodelabCommand::PushSteps {
+                id,
+                file,
+                publish,
+                expected_version,
+            } => {
+                assert_eq!(id, "lab-1");
+                assert_eq!(file, PathBuf::from("steps.json"));
+                assert!(publish);
+                assert_eq!(expected_version, None);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            ["push", "--manifest", "codelab.yaml"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab push") {
+            CodelabCommand::Push { publish, .. } => assert!(!publish),
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            ["publish", "--id", "lab-1"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab publish") {
+            CodelabCommand::Publish { id } => assert_eq!(id, "lab-1"),
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_codelab_export_format() {
+        let mut args = Args::new(
+            ["export", "--id", "lab-1", "--format", "claat"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab export") {
+            CodelabCommand::Export { id, output, format } => {
+                assert_eq!(id, "lab-1");
+                assert_eq!(output, None);
+                assert_eq!(format, ExportFormat::Claat);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            ["export", "--id", "lab-1", "--format", "pdf"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        assert!(parse_codelab(&mut args).is_err());
+    }
+
+    #[test]
+    fn parse_codelab_build_out_dir() {
+        let mut args = Args::new(
+            ["build", "--id", "lab-1", "--out", "site"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab build") {
+            CodelabCommand::Build { id, out } => {
+                assert_eq!(id, "lab-1");
+                assert_eq!(out, Some(PathBuf::from("site")));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+        assert_eq!(
+            ExportFormat::Html.default_output("lab-1"),
+            PathBuf::from("codelab_lab-1_site.zip")
+        );
+    }
+
+    #[test]
+    fn parse_codelab_step_commands() {
+        let mut args = Args::new(
+            [
+                "step-add",
+                "--id",
+                "lab-1",
+                "--title",
+                "Setup",
+                "--content-file",
+                "setup.md",
+                "--position",
+                "2",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab step-add") {
+            CodelabCommand::StepAdd {
+                id,
+                title,
+                content_file,
+                position,
+                expected_version,
+            } => {
+                assert_eq!(id, "lab-1");
+                assert_eq!(title, "Setup");
+                assert_eq!(content_file, PathBuf::from("setup.md"));
+                assert_eq!(position, Some(2));
+                assert_eq!(expected_version, None);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            [
+                "step-move",
+                "--id",
+                "lab-1",
+                "--step-id",
+                "step-3",
+                "--position",
+                "1",
+                "--expect-version",
+                "4",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab step-move") {
+            CodelabCommand::StepMove {
+                step_id,
+                position,
+                expected_version,
+                ..
+            } => {
+                assert_eq!(step_id, "step-3");
+                assert_eq!(position, 1);
+                assert_eq!(expected_version, Some(4));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            ["step-delete", "--id", "lab-1"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        assert!(parse_codelab(&mut args).is_err());
+    }
+
+    #[test]
+    fn parse_codelab_expected_versions() {
+        let mut args = Args::new(
+            [
+                "update",
+                "--id",
+                "lab-1",
+                "--title",
+                "T",
+                "--description",
+                "D",
+                "--author",
+                "A",
+                "--expect-version",
+                "3",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab update") {
+            CodelabCommand::Update {
+                expected_version, ..
+            } => assert_eq!(expected_version, Some(3)),
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            ["push", "--manifest", "codelab.yaml", "--force"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab push") {
+            CodelabCommand::Push { force, .. } => assert!(force),
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            [
+                "push-steps",
+                "--id",
+                "lab-1",
+                "--file",
+                "steps.json",
+                "--expect-version",
+                "latest",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        assert!(parse_codelab(&mut args).is_err());
+    }
+
+    #[test]
+    fn parse_codelab_cohort_commands() {
+        let mut args = Args::new(
+            [
+                "cohort-create",
+                "--id",
+                "lab-1",
+                "--name",
+                "Morning run",
+                "--starts",
+                "1700000000",
+                "--ends",
+                "none",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        match parse_codelab(&mut args).expect("codelab cohort-create") {
+            CodelabCommand::CohortCreate { id, payload } => {
+                assert_eq!(id, "lab-1");
+                assert_eq!(payload.name, "Morning run");
+                assert_eq!(payload.starts_at, Some(1_700_000_000));
+                assert_eq!(payload.ends_at, None);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            ["cohort-update", "--id", "lab-1", "--name", "Evening run"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        assert!(parse_codelab(&mut args).is_err());
+
+        let mut args = Args::new(
+            ["list", "--codelab-id", "lab-1", "--cohort-id", "cohort-1"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_attendee(&mut args).expect("attendee list") {
+            AttendeeCommand::List {
+                codelab_id,
+                cohort_id,
+            } => {
+                assert_eq!(codelab_id, "lab-1");
+                assert_eq!(cohort_id.as_deref(), Some("cohort-1"));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn attendee_import_reads_csv_and_json_rosters() {
+        let mut args = Args::new(
+            ["import", "--codelab-id", "lab-1", "--file", "roster.csv"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        match parse_attendee(&mut args).expect("attendee import") {
+            AttendeeCommand::Import {
+                codelab_id,
+                file,
+                cohort_id,
+            } => {
+                assert_eq!(codelab_id, "lab-1");
+                assert_eq!(file, PathBuf::from("roster.csv"));
+                assert_eq!(cohort_id, None);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let dir = tempfile::tempdir().expect("temp dir");
+        let csv = dir.path().join("roster.CSV");
+        fs::write(&csv, "name,email\nAda,ada@example.com\n").expect("write csv");
+        let payload = load_roster_payload(&csv).await.expect("csv roster");
+        assert!(payload.attendees.is_empty());
+        assert_eq!(
+            payload.csv.as_deref(),
+            Some("name,email\nAda,ada@example.com\n")
+        );
+
+        let json = dir.path().join("roster.json");
+        fs::write(&json, r#"[{"name": "Ada", "code": "ADA-1"}]"#).expect("write json");
+        let payload = load_roster_payload(&json).await.expect("json roster");
+        assert_eq!(payload.attendees[0].name, "Ada");
+        assert_eq!(payload.attendees[0].code.as_deref(), Some("ADA-1"));
+        assert!(payload.csv.is_none());
+    }
+
+    #[test]
+    fn parse_attendee_waitlist_commands() {
+        let mut args = Args::new(
+            [
+                "promote",
+                "--codelab-id",
+                "lab-1",
+                "--waitlist-id",
+                "wait-1",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        match parse_attendee(&mut args).expect("attendee promote") {
+            AttendeeCommand::Promote {
+                codelab_id,
+                waitlist_id,
+            } => {
+                assert_eq!(codelab_id, "lab-1");
+                assert_eq!(waitlist_id, "wait-1");
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let mut args = Args::new(
+            ["waitlist-remove", "--codelab-id", "lab-1"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        assert!(parse_attendee(&mut args).is_err());
+    }
+
     #[test]
     fn default_run_command_uses_expected_ports() {
         let command = default_run_command();
@@ -3,21 +3,32 @@
 use crate::api::dto::{
     AiRequest, CliAuthExchangeRequest, CliAuthExchangeResponse, CliAuthPollResponse,
     CliAuthStartResponse, CliRuntimeInfo, CodeServerInfo, CreateBranchRequest,
-    CreateCodeServerRequest, CreateFolderRequest, UpdateWorkspaceFilesRequest, WorkspaceFile,
+    CreateCodeServerRequest, CreateFolderRequest, MfaChallengeResponse, MfaCodeRequest,
+    MfaLoginRequest, MfaStatusResponse, RecoveryCodesResponse, SessionFilterQuery,
+    SessionRevokeResponse, TotpEnrollmentResponse, UpdateWorkspaceFilesRequest, WorkspaceFile,
 };
 use crate::cli::session::{SessionSnapshot, StoredSession};
 use crate::domain::models::{
-    AddAiMessagePayload, AiConversation, AiMessage, AiThread, Attendee, AttendeePublic,
-    CertificateInfo, ChatMessageRow, Codelab, CreateCodelab, CreateInlineCommentPayload,
-    CreateMaterial, CreateQuiz, Feedback, HelpRequest, InlineCommentThreadWithMessages,
-    LoginPayload, Material, Quiz, QuizSubmissionPayload, QuizSubmissionWithAttendee,
-    ReplyInlineCommentPayload, SaveAiConversationPayload, Step, Submission, SubmissionWithAttendee,
-    UpdateStepsPayload,
+    AddAiMessagePayload, AiConversation, AiMessage, AiThread, ApiToken, Attendee, AttendeeErasure,
+    AttendeePublic, CertificateInfo, CertificateVerification, ChatMessageRow, Codelab,
+    CodelabInvite, CodelabPasscodePayload, CodelabRevision, CodelabRevisionDiff,
+    CodelabRevisionSummary, CodelabStaffMember, Cohort, CohortSummary, CompletionRequirementsError,
+    CreateApiTokenPayload, CreateCodelab, CreateCodelabInvitePayload, CreateCohort,
+    CreateInlineCommentPayload, CreateMaterial, CreateQuiz, CreateStaffUserPayload,
+    CreatedApiToken, CreatedCodelabInvite, Feedback, HelpRequest, ImportRosterPayload,
+    InlineCommentThreadWithMessages, InsertStepPayload, LoginPayload, Material, MoveStepPayload,
+    PublicationStatus, Quiz, QuizAttempt, QuizSubmissionPayload, QuizSubmissionResult,
+    QuizSubmissionWithAttendee, RegistrationPayload, ReplyInlineCommentPayload, RosterImportResult,
+    SaveAiConversationPayload, StaffUser, Step, StepChangeResponse, Submission,
+    SubmissionWithAttendee, UpdateStaffUserPayload, UpdateStepPayload, UpdateStepsPayload,
+    UpdateStepsResponse, WaitlistEntry, WaitlistStatus,
 };
-use crate::infrastructure::db_models::AuditLog;
+use crate::infrastructure::db_models::{AuditLog, AuthSessionRow};
+use crate::infrastructure::retention::RetentionReport;
+use crate::utils::etag::{version_etag, VersionConflict};
 use anyhow::{anyhow, bail, Context, Result};
 use reqwest::header;
-use reqwest::{Client, Method, Response};
+use reqwest::{Client, Method, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -101,12 +112,31 @@ pub struct UploadedImage {
     pub url: String,
 }
 
+/// Result of the password step of a direct admin login.
+#[derive(Debug)]
+pub enum AdminLogin {
+    /// The password was enough and the session is ready.
+    Authenticated(StoredSession),
+    /// The account has two-factor enabled; finish with [`ApiClient::complete_admin_mfa`].
+    MfaRequired(MfaChallengeResponse),
+}
+
+/// Result of an attendee registration.
+#[derive(Debug)]
+pub enum AttendeeRegistration {
+    /// The attendee joined and the session is ready.
+    Joined(Box<StoredSession>, AttendeePublic),
+    /// The codelab is full; the attendee waits for a seat without a session.
+    Waitlisted(WaitlistStatus),
+}
+
 /// Thin wrapper around the backend HTTP API.
 #[derive(Clone)]
 pub struct ApiClient {
     base_url: String,
     http: Client,
     session: Option<StoredSession>,
+    api_token: Option<String>,
 }
 
 impl ApiClient {
@@ -120,11 +150,20 @@ impl ApiClient {
             base_url: normalize_base_url(&base_url.into()),
             http,
             session,
+            api_token: None,
         })
     }
 
-    /// Authenticates an administrator and returns a persisted session payload.
-    pub async fn login_admin(&self, admin_id: &str, admin_pw: &str) -> Result<StoredSession> {
+    /// Authenticates requests with a personal API token instead of the saved session.
+    pub fn with_api_token(mut self, api_token: Option<String>) -> Self {
+        self.api_token = api_token.filter(|token| !token.trim().is_empty());
+        self
+    }
+
+    /// Authenticates an administrator with a password.
+    ///
+    /// Accounts with two-factor enabled get a challenge instead of a session.
+    pub async fn login_admin(&self, admin_id: &str, admin_pw: &str) -> Result<AdminLogin> {
         let response = self
             .http
             .post(self.url("/api/login"))
@@ -136,15 +175,44 @@ impl ApiClient {
             .await
             .context("Failed to call /api/login")?;
 
+        if response.status().is_success() && !response.headers().contains_key(header::SET_COOKIE) {
+            let challenge = read_json(response, "/api/login").await?;
+            return Ok(AdminLogin::MfaRequired(challenge));
+        }
         let mut session =
             build_session_from_response(response, "/api/login", &self.base_url).await?;
         let snapshot = self.fetch_session_with(&session).await?;
         session.apply_snapshot(&snapshot);
+        Ok(AdminLogin::Authenticated(session))
+    }
+
+    /// Completes a direct admin login with an authenticator or recovery code.
+    pub async fn complete_admin_mfa(&self, mfa_token: &str, code: &str) -> Result<StoredSession> {
+        let response = self
+            .http
+            .post(self.url("/api/login/mfa"))
+            .json(&MfaLoginRequest {
+                mfa_token: mfa_token.to_string(),
+                code: code.to_string(),
+            })
+            .send()
+            .await
+            .context("Failed to call /api/login/mfa")?;
+
+        let mut session =
+            build_session_from_response(response, "/api/login/mfa", &self.base_url).await?;
+        let snapshot = self.fetch_session_with(&session).await?;
+        session.apply_snapshot(&snapshot);
         Ok(session)
     }
 
     /// Returns the latest session claims for the active CLI session.
     pub async fn session(&self) -> Result<SessionSnapshot> {
+        if self.api_token.is_some() {
+            return self
+                .send_authed_json(Method::GET, "/api/session", None)
+                .await;
+        }
         let session = self.require_session()?;
         self.fetch_session_with(session).await
     }
@@ -213,6 +281,7 @@ impl ApiClient {
         session.apply_snapshot(&SessionSnapshot {
             sub: snapshot.sub,
             role: snapshot.role,
+            staff_role: snapshot.staff_role,
             codelab_id: snapshot.codelab_id,
             exp: snapshot.exp,
         });
@@ -229,6 +298,15 @@ impl ApiClient {
         Ok(())
     }
 
+    /// Revokes every session of the signed-in user on all devices.
+    pub async fn logout_everywhere(&self) -> Result<SessionRevokeResponse> {
+        let session = self.require_session()?;
+        let response = self
+            .send_with_session(Method::POST, "/api/logout/all", None, session)
+            .await?;
+        read_json(response, "/api/logout/all").await
+    }
+
     /// Lists codelabs visible to the current session.
     pub async fn list_codelabs(&self) -> Result<Vec<Codelab>> {
         self.send_optional_json(Method::GET, "/api/codelabs", None)
@@ -267,13 +345,24 @@ impl ApiClient {
     }
 
     /// Updates existing codelab metadata.
-    pub async fn update_codelab(&self, id: &str, payload: &CreateCodelab) -> Result<Codelab> {
-        self.send_authed_json(
-            Method::PUT,
-            &format!("/api/codelabs/{id}"),
-            Some(serde_json::to_value(payload).context("serialize update payload")?),
-        )
-        .await
+    ///
+    /// With `if_match`, the update fails if the codelab moved past that version.
+    pub async fn update_codelab(
+        &self,
+        id: &str,
+        payload: &CreateCodelab,
+        if_match: Option<i32>,
+    ) -> Result<Codelab> {
+        let path = format!("/api/codelabs/{id}");
+        let response = self
+            .send_authed_if_match(
+                Method::PUT,
+                &path,
+                Some(serde_json::to_value(payload).context("serialize update payload")?),
+                if_match,
+            )
+            .await?;
+        read_json(response, &path).await
     }
 
     /// Deletes a codelab and all related data.
@@ -292,29 +381,178 @@ impl ApiClient {
     }
 
     /// Uploads a replacement step list from a JSON payload.
-    pub async fn push_steps(&self, id: &str, payload: &UpdateStepsPayload) -> Result<()> {
+    ///
+    /// With `if_match`, the upload fails if the step set moved past that version.
+    pub async fn push_steps(
+        &self,
+        id: &str,
+        payload: &UpdateStepsPayload,
+        if_match: Option<i32>,
+    ) -> Result<UpdateStepsResponse> {
+        let path = format!("/api/codelabs/{id}/steps");
         let response = self
-            .send_authed(
+            .send_authed_if_match(
                 Method::PUT,
-                &format!("/api/codelabs/{id}/steps"),
+                &path,
                 Some(serde_json::to_value(payload).context("serialize steps payload")?),
+                if_match,
             )
             .await?;
-        ensure_success(response, "/api/codelabs/{id}/steps").await?;
-        Ok(())
+        read_json(response, &path).await
+    }
+
+    /// Inserts one step, at `payload.position` or after the last step.
+    pub async fn insert_step(
+        &self,
+        id: &str,
+        payload: &InsertStepPayload,
+        if_match: Option<i32>,
+    ) -> Result<StepChangeResponse> {
+        let path = format!("/api/codelabs/{id}/steps");
+        let response = self
+            .send_authed_if_match(
+                Method::POST,
+                &path,
+                Some(serde_json::to_value(payload).context("serialize step payload")?),
+                if_match,
+            )
+            .await?;
+        read_json(response, &path).await
+    }
+
+    /// Replaces the title and markdown of one step.
+    pub async fn update_step(
+        &self,
+        id: &str,
+        step_id: &str,
+        payload: &UpdateStepPayload,
+        if_match: Option<i32>,
+    ) -> Result<StepChangeResponse> {
+        let path = format!("/api/codelabs/{id}/steps/{step_id}");
+        let response = self
+            .send_authed_if_match(
+                Method::PUT,
+                &path,
+                Some(serde_json::to_value(payload).context("serialize step payload")?),
+                if_match,
+            )
+            .await?;
+        read_json(response, &path).await
+    }
+
+    /// Deletes one step.
+    pub async fn delete_step(
+        &self,
+        id: &str,
+        step_id: &str,
+        if_match: Option<i32>,
+    ) -> Result<StepChangeResponse> {
+        let path = format!("/api/codelabs/{id}/steps/{step_id}");
+        let response = self
+            .send_authed_if_match(Method::DELETE, &path, None, if_match)
+            .await?;
+        read_json(response, &path).await
+    }
+
+    /// Moves one step to a 1-based position.
+    pub async fn move_step(
+        &self,
+        id: &str,
+        step_id: &str,
+        position: i32,
+        if_match: Option<i32>,
+    ) -> Result<StepChangeResponse> {
+        let path = format!("/api/codelabs/{id}/steps/{step_id}/move");
+        let payload = MoveStepPayload { position };
+        let response = self
+            .send_authed_if_match(
+                Method::POST,
+                &path,
+                Some(serde_json::to_value(&payload).context("serialize move payload")?),
+                if_match,
+            )
+            .await?;
+        read_json(response, &path).await
+    }
+
+    /// Publishes the draft steps and guide of a codelab to learners.
+    pub async fn publish_codelab(&self, codelab_id: &str) -> Result<PublicationStatus> {
+        self.send_authed_json(
+            Method::POST,
+            &format!("/api/codelabs/{codelab_id}/publish"),
+            None,
+        )
+        .await
+    }
+
+    /// Lists the content revisions of a codelab, newest first.
+    pub async fn list_revisions(&self, codelab_id: &str) -> Result<Vec<CodelabRevisionSummary>> {
+        self.send_authed_json(
+            Method::GET,
+            &format!("/api/codelabs/{codelab_id}/revisions"),
+            None,
+        )
+        .await
+    }
+
+    /// Diffs two revisions of a codelab; `to` defaults to the latest.
+    pub async fn diff_revisions(
+        &self,
+        codelab_id: &str,
+        from: &str,
+        to: Option<&str>,
+    ) -> Result<CodelabRevisionDiff> {
+        let path = {
+            let mut serializer = Serializer::new(String::new());
+            serializer.append_pair("from", from);
+            if let Some(to) = to {
+                serializer.append_pair("to", to);
+            }
+            format!(
+                "/api/codelabs/{codelab_id}/revisions/diff?{}",
+                serializer.finish()
+            )
+        };
+        self.send_authed_json(Method::GET, &path, None).await
     }
 
-    /// Imports a codelab ZIP archive.
+    /// Restores a codelab's steps and guide to an earlier revision.
+    pub async fn restore_revision(
+        &self,
+        codelab_id: &str,
+        revision: &str,
+    ) -> Result<CodelabRevision> {
+        self.send_authed_json(
+            Method::POST,
+            &format!("/api/codelabs/{codelab_id}/revisions/{revision}/restore"),
+            None,
+        )
+        .await
+    }
+
+    /// Imports a codelab ZIP archive or claat markdown file.
     pub async fn import_codelab(&self, file_path: &Path) -> Result<Codelab> {
-        let form = file_form(file_path, "application/zip").await?;
+        let is_markdown = file_path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("md"));
+        let mime_type = if is_markdown {
+            "text/markdown"
+        } else {
+            "application/zip"
+        };
+        let form = file_form(file_path, mime_type).await?;
         self.send_authed_multipart("/api/codelabs/import", form)
             .await
     }
 
-    /// Exports a codelab ZIP archive.
-    pub async fn export_codelab(&self, id: &str) -> Result<Vec<u8>> {
-        self.send_authed_bytes(Method::GET, &format!("/api/codelabs/{id}/export"), None)
-            .await
+    /// Exports a codelab as a ZIP archive (`zip`) or claat markdown (`claat`).
+    pub async fn export_codelab(&self, id: &str, format: &str) -> Result<Vec<u8>> {
+        self.send_authed_bytes(
+            Method::GET,
+            &format!("/api/codelabs/{id}/export?format={format}"),
+            None,
+        )
+        .await
     }
 
     /// Downloads an uploaded asset by relative API/static path or absolute URL.
@@ -334,43 +572,155 @@ impl ApiClient {
     }
 
     /// Registers or rejoins an attendee and returns the issued attendee session.
+    ///
+    /// Private codelabs additionally need a join `passcode` or an `invite_token`.
     pub async fn register_attendee(
         &self,
         codelab_id: &str,
-        name: &str,
-        code: &str,
-        email: Option<&str>,
-    ) -> Result<(StoredSession, AttendeePublic)> {
+        payload: &RegistrationPayload,
+    ) -> Result<AttendeeRegistration> {
         let path = format!("/api/codelabs/{codelab_id}/register");
         let response = self
             .send_optional(
                 Method::POST,
                 &path,
-                Some(serde_json::json!({
-                    "name": name,
-                    "code": code,
-                    "email": email,
-                })),
+                Some(serde_json::to_value(payload).context("serialize registration payload")?),
             )
             .await?;
+        if response.status() == reqwest::StatusCode::ACCEPTED {
+            let status = read_json(response, &path).await?;
+            return Ok(AttendeeRegistration::Waitlisted(status));
+        }
         let (mut session, attendee) =
             read_session_json::<AttendeePublic>(response, &path, &self.base_url).await?;
         let snapshot = self.fetch_session_with(&session).await?;
         session.apply_snapshot(&snapshot);
-        Ok((session, attendee))
+        Ok(AttendeeRegistration::Joined(Box::new(session), attendee))
+    }
+
+    /// Pre-registers a roster of attendees and returns per-row results.
+    pub async fn import_roster(
+        &self,
+        codelab_id: &str,
+        payload: &ImportRosterPayload,
+    ) -> Result<RosterImportResult> {
+        self.send_authed_json(
+            Method::POST,
+            &format!("/api/codelabs/{codelab_id}/attendees/import"),
+            Some(serde_json::to_value(payload).context("serialize roster payload")?),
+        )
+        .await
+    }
+
+    /// Lists waitlisted learners of a codelab in queue order.
+    pub async fn list_waitlist(&self, codelab_id: &str) -> Result<Vec<WaitlistEntry>> {
+        self.send_authed_json(
+            Method::GET,
+            &format!("/api/codelabs/{codelab_id}/waitlist"),
+            None,
+        )
+        .await
+    }
+
+    /// Admits a waitlisted learner as an attendee.
+    pub async fn promote_waitlist_entry(
+        &self,
+        codelab_id: &str,
+        entry_id: &str,
+    ) -> Result<AttendeePublic> {
+        self.send_authed_json(
+            Method::POST,
+            &format!("/api/codelabs/{codelab_id}/waitlist/{entry_id}/promote"),
+            None,
+        )
+        .await
+    }
+
+    /// Removes a learner from the waitlist.
+    pub async fn remove_waitlist_entry(&self, codelab_id: &str, entry_id: &str) -> Result<()> {
+        let response = self
+            .send_authed(
+                Method::DELETE,
+                &format!("/api/codelabs/{codelab_id}/waitlist/{entry_id}"),
+                None,
+            )
+            .await?;
+        ensure_success(response, "/api/codelabs/{id}/waitlist/{entry_id}").await?;
+        Ok(())
     }
 
     /// Lists attendees for a codelab.
-    pub async fn get_attendees(&self, codelab_id: &str) -> Result<Vec<Attendee>> {
+    pub async fn get_attendees(
+        &self,
+        codelab_id: &str,
+        cohort_id: Option<&str>,
+    ) -> Result<Vec<Attendee>> {
+        let path = match cohort_id {
+            Some(cohort_id) => {
+                let mut serializer = Serializer::new(String::new());
+                serializer.append_pair("cohort_id", cohort_id);
+                format!(
+                    "/api/codelabs/{codelab_id}/attendees?{}",
+                    serializer.finish()
+                )
+            }
+            None => format!("/api/codelabs/{codelab_id}/attendees"),
+        };
+        self.send_authed_json(Method::GET, &path, None).await
+    }
+
+    /// Lists the cohorts of a codelab with their per-run numbers.
+    pub async fn list_cohorts(&self, codelab_id: &str) -> Result<Vec<CohortSummary>> {
         self.send_authed_json(
             Method::GET,
-            &format!("/api/codelabs/{codelab_id}/attendees"),
+            &format!("/api/codelabs/{codelab_id}/cohorts"),
             None,
         )
         .await
     }
 
+    /// Schedules a new cohort of a codelab.
+    pub async fn create_cohort(&self, codelab_id: &str, payload: &CreateCohort) -> Result<Cohort> {
+        self.send_authed_json(
+            Method::POST,
+            &format!("/api/codelabs/{codelab_id}/cohorts"),
+            Some(serde_json::to_value(payload).context("serialize cohort payload")?),
+        )
+        .await
+    }
+
+    /// Renames or reschedules a cohort.
+    pub async fn update_cohort(
+        &self,
+        codelab_id: &str,
+        cohort_id: &str,
+        payload: &CreateCohort,
+    ) -> Result<Cohort> {
+        self.send_authed_json(
+            Method::PUT,
+            &format!("/api/codelabs/{codelab_id}/cohorts/{cohort_id}"),
+            Some(serde_json::to_value(payload).context("serialize cohort payload")?),
+        )
+        .await
+    }
+
+    /// Deletes a cohort that has no attendees.
+    pub async fn delete_cohort(&self, codelab_id: &str, cohort_id: &str) -> Result<()> {
+        let response = self
+            .send_authed(
+                Method::DELETE,
+                &format!("/api/codelabs/{codelab_id}/cohorts/{cohort_id}"),
+                None,
+            )
+            .await?;
+        ensure_success(response, "/api/codelabs/{id}/cohorts/{cohort_id}").await?;
+        Ok(())
+    }
+
     /// Marks the current attendee as completed.
+    ///
+    /// Fails with the list of unmet requirement codes when the codelab's
+    /// completion rules are not satisfied yet.
     pub async fn complete_codelab(&self, codelab_id: &str) -> Result<()> {
         let response = self
             .send_authed(
@@ -379,10 +729,47 @@ impl ApiClient {
                 None,
             )
             .await?;
+        if response.status() == reqwest::StatusCode::BAD_REQUEST {
+            let body = response.text().await.unwrap_or_default();
+            if let Ok(requirements) = serde_json::from_str::<CompletionRequirementsError>(&body) {
+                bail!(
+                    "Completion requirements not met: {}",
+                    requirements.unmet.join(", ")
+                );
+            }
+            bail!(
+                "/api/codelabs/{{id}}/complete failed: HTTP 400 body={}",
+                truncate(&body, 200)
+            );
+        }
         ensure_success(response, "/api/codelabs/{id}/complete").await?;
         Ok(())
     }
 
+    /// Checks a certificate's signature and revocation status.
+    pub async fn verify_certificate(&self, cert_id: &str) -> Result<CertificateVerification> {
+        self.send_optional_json(
+            Method::GET,
+            &format!("/api/certificates/verify/{cert_id}"),
+            None,
+        )
+        .await
+    }
+
+    /// Revokes a certificate as an administrator.
+    pub async fn revoke_certificate(
+        &self,
+        cert_id: &str,
+        reason: Option<&str>,
+    ) -> Result<CertificateVerification> {
+        self.send_authed_json(
+            Method::POST,
+            &format!("/api/admin/certificates/{cert_id}/revoke"),
+            Some(serde_json::json!({ "reason": reason })),
+        )
+        .await
+    }
+
     /// Fetches a certificate payload for a completed attendee.
     pub async fn get_certificate(&self, attendee_id: &str) -> Result<CertificateInfo> {
         let response = self
@@ -394,6 +781,30 @@ impl ApiClient {
         read_json(response, "/api/certificates/{id}").await
     }
 
+    /// Downloads the server-rendered certificate PDF for a completed attendee.
+    pub async fn download_certificate_pdf(&self, attendee_id: &str) -> Result<Vec<u8>> {
+        let path = format!("/api/certificates/{attendee_id}/pdf");
+        let response = self
+            .http
+            .get(self.url(&path))
+            .send()
+            .await
+            .with_context(|| format!("Failed to call {path}"))?;
+        read_bytes(response, "/api/certificates/{id}/pdf").await
+    }
+
+    /// Fetches the Open Badges 3.0 credential for a completed attendee.
+    pub async fn get_certificate_credential(&self, attendee_id: &str) -> Result<Value> {
+        let path = format!("/api/certificates/{attendee_id}/credential");
+        let response = self
+            .http
+            .get(self.url(&path))
+            .send()
+            .await
+            .with_context(|| format!("Failed to call {path}"))?;
+        read_json(response, "/api/certificates/{id}/credential").await
+    }
+
     /// Creates a help request for the current attendee.
     pub async fn request_help(&self, codelab_id: &str, step_number: i32) -> Result<()> {
         let response = self
@@ -536,21 +947,18 @@ impl ApiClient {
         Ok(())
     }
 
-    /// Submits quiz answers for the current attendee.
+    /// Submits quiz answers for the current attendee and returns the graded result.
     pub async fn submit_quiz(
         &self,
         codelab_id: &str,
         payload: &QuizSubmissionPayload,
-    ) -> Result<()> {
-        let response = self
-            .send_authed(
-                Method::POST,
-                &format!("/api/codelabs/{codelab_id}/quizzes/submit"),
-                Some(serde_json::to_value(payload).context("serialize quiz submission payload")?),
-            )
-            .await?;
-        ensure_success(response, "/api/codelabs/{id}/quizzes/submit").await?;
-        Ok(())
+    ) -> Result<QuizSubmissionResult> {
+        self.send_authed_json(
+            Method::POST,
+            &format!("/api/codelabs/{codelab_id}/quizzes/submit"),
+            Some(serde_json::to_value(payload).context("serialize quiz submission payload")?),
+        )
+        .await
     }
 
     /// Lists quiz submissions for administrators.
@@ -566,6 +974,16 @@ impl ApiClient {
         .await
     }
 
+    /// Lists quiz attempts visible to the current actor.
+    pub async fn get_quiz_attempts(&self, codelab_id: &str) -> Result<Vec<QuizAttempt>> {
+        self.send_authed_json(
+            Method::GET,
+            &format!("/api/codelabs/{codelab_id}/quizzes/attempts"),
+            None,
+        )
+        .await
+    }
+
     /// Lists submissions visible to the current actor.
     pub async fn get_submissions(&self, codelab_id: &str) -> Result<Vec<SubmissionWithAttendee>> {
         self.send_authed_json(
@@ -811,6 +1229,50 @@ impl ApiClient {
             .await
     }
 
+    /// Finds attendee registrations across codelabs by email address.
+    pub async fn find_attendees_by_email(&self, email: &str) -> Result<Vec<AttendeePublic>> {
+        let mut serializer = Serializer::new(String::new());
+        serializer.append_pair("email", email);
+        self.send_authed_json(
+            Method::GET,
+            &format!("/api/admin/privacy/attendees?{}", serializer.finish()),
+            None,
+        )
+        .await
+    }
+
+    /// Downloads everything stored about an attendee as a ZIP archive.
+    pub async fn export_attendee_data(&self, attendee_id: &str) -> Result<Vec<u8>> {
+        self.send_authed_bytes(
+            Method::GET,
+            &format!("/api/admin/privacy/attendees/{attendee_id}/export"),
+            None,
+        )
+        .await
+    }
+
+    /// Erases an attendee's personal data.
+    pub async fn erase_attendee_data(&self, attendee_id: &str) -> Result<AttendeeErasure> {
+        self.send_authed_json(
+            Method::POST,
+            &format!("/api/admin/privacy/attendees/{attendee_id}/erase"),
+            None,
+        )
+        .await
+    }
+
+    /// Reports what a retention pass would delete without deleting anything.
+    pub async fn retention_report(&self) -> Result<RetentionReport> {
+        self.send_authed_json(Method::GET, "/api/admin/retention", None)
+            .await
+    }
+
+    /// Runs a retention pass immediately.
+    pub async fn run_retention(&self) -> Result<RetentionReport> {
+        self.send_authed_json(Method::POST, "/api/admin/retention/run", None)
+            .await
+    }
+
     /// Updates encrypted administrator settings.
     pub async fn save_admin_settings(&self, gemini_api_key: &str) -> Result<()> {
         let response = self
@@ -1079,7 +1541,7 @@ impl ApiClient {
     pub async fn restore_backup(&self, file_path: &Path) -> Result<()> {
         let form = file_form(file_path, "application/zip").await?;
         let response = self
-            .send_with_session_multipart("/api/admin/backup/restore", form, self.require_session()?)
+            .send_authed_multipart_raw("/api/admin/backup/restore", form)
             .await?;
         ensure_success(response, "/api/admin/backup/restore").await?;
         Ok(())
@@ -1115,6 +1577,279 @@ impl ApiClient {
         self.send_authed_json(Method::GET, &path, None).await
     }
 
+    /// Lists staff accounts.
+    pub async fn list_users(&self) -> Result<Vec<StaffUser>> {
+        self.send_authed_json(Method::GET, "/api/admin/users", None)
+            .await
+    }
+
+    /// Creates a staff account.
+    pub async fn create_user(&self, payload: &CreateStaffUserPayload) -> Result<StaffUser> {
+        self.send_authed_json(
+            Method::POST,
+            "/api/admin/users",
+            Some(serde_json::to_value(payload).context("serialize user payload")?),
+        )
+        .await
+    }
+
+    /// Updates a staff account's role, display name, or password.
+    pub async fn update_user(
+        &self,
+        user_id: &str,
+        payload: &UpdateStaffUserPayload,
+    ) -> Result<StaffUser> {
+        self.send_authed_json(
+            Method::PUT,
+            &format!("/api/admin/users/{user_id}"),
+            Some(serde_json::to_value(payload).context("serialize user payload")?),
+        )
+        .await
+    }
+
+    /// Deletes a staff account.
+    pub async fn delete_user(&self, user_id: &str) -> Result<()> {
+        let response = self
+            .send_authed(Method::DELETE, &format!("/api/admin/users/{user_id}"), None)
+            .await?;
+        ensure_success(response, "/api/admin/users/{id}").await?;
+        Ok(())
+    }
+
+    /// Lists staff accounts assigned to a codelab.
+    pub async fn list_codelab_staff(&self, codelab_id: &str) -> Result<Vec<CodelabStaffMember>> {
+        self.send_authed_json(
+            Method::GET,
+            &format!("/api/codelabs/{codelab_id}/staff"),
+            None,
+        )
+        .await
+    }
+
+    /// Assigns a staff account to a codelab.
+    pub async fn assign_codelab_staff(&self, codelab_id: &str, user_id: &str) -> Result<()> {
+        let response = self
+            .send_authed(
+                Method::PUT,
+                &format!("/api/codelabs/{codelab_id}/staff/{user_id}"),
+                None,
+            )
+            .await?;
+        ensure_success(response, "/api/codelabs/{id}/staff/{user_id}").await?;
+        Ok(())
+    }
+
+    /// Removes a staff account from a codelab.
+    pub async fn unassign_codelab_staff(&self, codelab_id: &str, user_id: &str) -> Result<()> {
+        let response = self
+            .send_authed(
+                Method::DELETE,
+                &format!("/api/codelabs/{codelab_id}/staff/{user_id}"),
+                None,
+            )
+            .await?;
+        ensure_success(response, "/api/codelabs/{id}/staff/{user_id}").await?;
+        Ok(())
+    }
+
+    /// Returns the join passcode of a codelab.
+    pub async fn get_codelab_passcode(&self, codelab_id: &str) -> Result<CodelabPasscodePayload> {
+        self.send_authed_json(
+            Method::GET,
+            &format!("/api/codelabs/{codelab_id}/passcode"),
+            None,
+        )
+        .await
+    }
+
+    /// Sets the join passcode of a codelab, or clears it with `None`.
+    pub async fn set_codelab_passcode(
+        &self,
+        codelab_id: &str,
+        passcode: Option<&str>,
+    ) -> Result<()> {
+        let response = self
+            .send_authed(
+                Method::PUT,
+                &format!("/api/codelabs/{codelab_id}/passcode"),
+                Some(serde_json::json!({ "passcode": passcode })),
+            )
+            .await?;
+        ensure_success(response, "/api/codelabs/{id}/passcode").await?;
+        Ok(())
+    }
+
+    /// Lists invite links of a codelab.
+    pub async fn list_codelab_invites(&self, codelab_id: &str) -> Result<Vec<CodelabInvite>> {
+        self.send_authed_json(
+            Method::GET,
+            &format!("/api/codelabs/{codelab_id}/invites"),
+            None,
+        )
+        .await
+    }
+
+    /// Creates an invite link; the token is only returned here.
+    pub async fn create_codelab_invite(
+        &self,
+        codelab_id: &str,
+        payload: &CreateCodelabInvitePayload,
+    ) -> Result<CreatedCodelabInvite> {
+        self.send_authed_json(
+            Method::POST,
+            &format!("/api/codelabs/{codelab_id}/invites"),
+            Some(serde_json::to_value(payload).context("serialize invite payload")?),
+        )
+        .await
+    }
+
+    /// Revokes an invite link.
+    pub async fn revoke_codelab_invite(&self, codelab_id: &str, invite_id: &str) -> Result<()> {
+        let response = self
+            .send_authed(
+                Method::DELETE,
+                &format!("/api/codelabs/{codelab_id}/invites/{invite_id}"),
+                None,
+            )
+            .await?;
+        ensure_success(response, "/api/codelabs/{id}/invites/{invite_id}").await?;
+        Ok(())
+    }
+
+    /// Lists personal API tokens visible to the caller.
+    pub async fn list_api_tokens(&self) -> Result<Vec<ApiToken>> {
+        self.send_authed_json(Method::GET, "/api/admin/tokens", None)
+            .await
+    }
+
+    /// Creates a personal API token; the plaintext is only returned here.
+    pub async fn create_api_token(
+        &self,
+        payload: &CreateApiTokenPayload,
+    ) -> Result<CreatedApiToken> {
+        self.send_authed_json(
+            Method::POST,
+            "/api/admin/tokens",
+            Some(serde_json::to_value(payload).context("serialize token payload")?),
+        )
+        .await
+    }
+
+    /// Revokes a personal API token.
+    pub async fn revoke_api_token(&self, token_id: &str) -> Result<()> {
+        let response = self
+            .send_authed(
+                Method::DELETE,
+                &format!("/api/admin/tokens/{token_id}"),
+                None,
+            )
+            .await?;
+        ensure_success(response, "/api/admin/tokens/{id}").await?;
+        Ok(())
+    }
+
+    /// Lists active sessions, optionally filtered by user or codelab.
+    pub async fn list_sessions(&self, filter: &SessionFilterQuery) -> Result<Vec<AuthSessionRow>> {
+        let mut serializer = Serializer::new(String::new());
+        if let Some(user_id) = &filter.user_id {
+            serializer.append_pair("user_id", user_id);
+        }
+        if let Some(codelab_id) = &filter.codelab_id {
+            serializer.append_pair("codelab_id", codelab_id);
+        }
+        let query = serializer.finish();
+        let mut path = "/api/admin/sessions".to_string();
+        if !query.is_empty() {
+            path.push('?');
+            path.push_str(&query);
+        }
+        self.send_authed_json(Method::GET, &path, None).await
+    }
+
+    /// Revokes a single session by id.
+    pub async fn revoke_session(&self, session_id: &str) -> Result<()> {
+        let response = self
+            .send_authed(
+                Method::DELETE,
+                &format!("/api/admin/sessions/{session_id}"),
+                None,
+            )
+            .await?;
+        ensure_success(response, "/api/admin/sessions/{id}").await?;
+        Ok(())
+    }
+
+    /// Revokes every active session matching the filter.
+    pub async fn revoke_sessions(
+        &self,
+        filter: &SessionFilterQuery,
+    ) -> Result<SessionRevokeResponse> {
+        self.send_authed_json(
+            Method::POST,
+            "/api/admin/sessions/revoke",
+            Some(serde_json::to_value(filter).context("serialize session filter")?),
+        )
+        .await
+    }
+
+    /// Returns the two-factor state of the signed-in staff member.
+    pub async fn mfa_status(&self) -> Result<MfaStatusResponse> {
+        self.send_authed_json(Method::GET, "/api/admin/mfa", None)
+            .await
+    }
+
+    /// Starts TOTP enrollment and returns the secret to add to an authenticator.
+    pub async fn start_totp_enrollment(&self) -> Result<TotpEnrollmentResponse> {
+        self.send_authed_json(Method::POST, "/api/admin/mfa/totp", None)
+            .await
+    }
+
+    /// Confirms TOTP enrollment and returns the initial recovery codes.
+    pub async fn confirm_totp(&self, code: &str) -> Result<RecoveryCodesResponse> {
+        self.send_authed_json(
+            Method::POST,
+            "/api/admin/mfa/totp/confirm",
+            Some(mfa_code_body(code)?),
+        )
+        .await
+    }
+
+    /// Turns off two-factor sign-in for the signed-in staff member.
+    pub async fn disable_totp(&self, code: &str) -> Result<()> {
+        let response = self
+            .send_authed(
+                Method::DELETE,
+                "/api/admin/mfa/totp",
+                Some(mfa_code_body(code)?),
+            )
+            .await?;
+        ensure_success(response, "/api/admin/mfa/totp").await?;
+        Ok(())
+    }
+
+    /// Replaces the recovery codes of the signed-in staff member.
+    pub async fn regenerate_recovery_codes(&self, code: &str) -> Result<RecoveryCodesResponse> {
+        self.send_authed_json(
+            Method::POST,
+            "/api/admin/mfa/recovery-codes",
+            Some(mfa_code_body(code)?),
+        )
+        .await
+    }
+
+    /// Clears another staff member's two-factor enrollment.
+    pub async fn reset_user_mfa(&self, user_id: &str) -> Result<()> {
+        let response = self
+            .send_authed(
+                Method::DELETE,
+                &format!("/api/admin/users/{user_id}/mfa"),
+                None,
+            )
+            .await?;
+        ensure_success(response, "/api/admin/users/{id}/mfa").await?;
+        Ok(())
+    }
+
     fn url(&self, path: &str) -> String {
         format!("{}{}", self.base_url, path)
     }
@@ -1167,18 +1902,38 @@ impl ApiClient {
         path: &str,
         form: reqwest::multipart::Form,
     ) -> Result<T> {
-        let response = self
-            .send_with_session_multipart(path, form, self.require_session()?)
-            .await?;
+        let response = self.send_authed_multipart_raw(path, form).await?;
         read_json(response, path).await
     }
 
+    async fn send_authed_multipart_raw(
+        &self,
+        path: &str,
+        form: reqwest::multipart::Form,
+    ) -> Result<Response> {
+        if let Some(token) = &self.api_token {
+            return self
+                .http
+                .post(self.url(path))
+                .bearer_auth(token)
+                .multipart(form)
+                .send()
+                .await
+                .with_context(|| format!("Request failed: {path}"));
+        }
+        self.send_with_session_multipart(path, form, self.require_session()?)
+            .await
+    }
+
     async fn send_authed(
         &self,
         method: Method,
         path: &str,
         body: Option<Value>,
     ) -> Result<Response> {
+        if let Some(token) = &self.api_token {
+            return self.send_with_api_token(method, path, body, token).await;
+        }
         let session = self.require_session()?;
         self.send_with_session(method, path, body, session).await
     }
@@ -1189,13 +1944,48 @@ impl ApiClient {
         path: &str,
         body: Option<Value>,
     ) -> Result<Response> {
-        if let Some(session) = &self.session {
+        if let Some(token) = &self.api_token {
+            self.send_with_api_token(method, path, body, token).await
+        } else if let Some(session) = &self.session {
             self.send_with_session(method, path, body, session).await
         } else {
             self.send_without_session(method, path, body).await
         }
     }
 
+    /// Sends a write that only applies while the resource is at `if_match`.
+    async fn send_authed_if_match(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+        if_match: Option<i32>,
+    ) -> Result<Response> {
+        let mut request = match &self.api_token {
+            Some(token) => self.api_token_request(method, path, body, token),
+            None => self.session_request(method, path, body, self.require_session()?),
+        };
+        if let Some(version) = if_match {
+            request = request.header(header::IF_MATCH, version_etag(version));
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Request failed: {path}"))?;
+        if response.status() == reqwest::StatusCode::CONFLICT {
+            let conflict: VersionConflict = response
+                .json()
+                .await
+                .with_context(|| format!("Failed to parse conflict response from {path}"))?;
+            bail!(
+                "{path} failed: {} (server is at version {})",
+                conflict.error,
+                conflict.current_version
+            );
+        }
+        Ok(response)
+    }
+
     async fn send_with_session(
         &self,
         method: Method,
@@ -1203,6 +1993,19 @@ impl ApiClient {
         body: Option<Value>,
         session: &StoredSession,
     ) -> Result<Response> {
+        self.session_request(method, path, body, session)
+            .send()
+            .await
+            .with_context(|| format!("Request failed: {path}"))
+    }
+
+    fn session_request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+        session: &StoredSession,
+    ) -> RequestBuilder {
         let mut request = self
             .http
             .request(method.clone(), self.url(path))
@@ -1222,13 +2025,39 @@ impl ApiClient {
                 .header(header::CONTENT_TYPE, "application/json")
                 .json(&body);
         }
-
         request
+    }
+
+    async fn send_with_api_token(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+        token: &str,
+    ) -> Result<Response> {
+        self.api_token_request(method, path, body, token)
             .send()
             .await
             .with_context(|| format!("Request failed: {path}"))
     }
 
+    fn api_token_request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+        token: &str,
+    ) -> RequestBuilder {
+        let mut request = self.http.request(method, self.url(path)).bearer_auth(token);
+
+        if let Some(body) = body {
+            request = request
+                .header(header::CONTENT_TYPE, "application/json")
+                .json(&body);
+        }
+        request
+    }
+
     async fn send_without_session(
         &self,
         method: Method,
@@ -1323,6 +2152,13 @@ async fn read_bytes(response: Response, path: &str) -> Result<Vec<u8>> {
         .with_context(|| format!("Failed to read response body from {path}"))
 }
 
+fn mfa_code_body(code: &str) -> Result<Value> {
+    serde_json::to_value(MfaCodeRequest {
+        code: code.to_string(),
+    })
+    .context("serialize two-factor code")
+}
+
 async fn build_session_from_response(
     response: Response,
     context_path: &str,
@@ -43,6 +43,9 @@ pub struct SessionSnapshot {
     pub sub: String,
     /// Session role such as `admin`.
     pub role: String,
+    /// Staff role for admin sessions, such as `owner` or `ta`.
+    #[serde(default)]
+    pub staff_role: Option<String>,
     /// Optional codelab scope.
     pub codelab_id: Option<String>,
     /// Expiration timestamp in epoch seconds.
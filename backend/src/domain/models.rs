@@ -28,10 +28,46 @@ pub struct Codelab {
     /// Whether a submission is required before completion.
     #[serde(serialize_with = "to_bool", deserialize_with = "from_bool")]
     pub require_submission: i32,
+    /// Maximum quiz attempts per attendee; `0` means unlimited.
+    #[serde(default)]
+    pub quiz_max_attempts: i32,
+    /// Minimum quiz score percentage required to pass.
+    #[serde(default = "default_quiz_pass_percentage")]
+    pub quiz_pass_percentage: i32,
+    /// Seat limit for attendees; `0` means unlimited.
+    #[serde(default)]
+    pub max_attendees: i32,
+    /// Epoch seconds before which new registrations are refused.
+    #[serde(default)]
+    pub registration_opens_at: Option<i64>,
+    /// Epoch seconds from which new registrations are refused.
+    #[serde(default)]
+    pub registration_closes_at: Option<i64>,
     /// Optional guide markdown rendered alongside the steps.
     pub guide_markdown: Option<String>,
     /// Creation timestamp serialized as text.
     pub created_at: Option<String>,
+    /// Revision learners see; `None` until the first publish, while learners
+    /// see the working copy.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub published_revision_id: Option<String>,
+    /// Time of the last publish.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub published_at: Option<String>,
+    /// Version of the settings and guide, bumped on every update.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub version: i32,
+    /// Version of the step set, bumped whenever any step changes.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub steps_version: i32,
+    /// Comma-separated categories, as written in claat metadata.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub categories: Option<String>,
 }
 
 /// Stored quiz row attached to a codelab step or overall lab flow.
@@ -99,6 +135,10 @@ pub struct Step {
     pub title: String,
     /// Markdown body for the step.
     pub content_markdown: String,
+    /// Estimated minutes to complete the step.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub duration_minutes: Option<i32>,
 }
 
 /// Payload used to create or update codelab metadata.
@@ -120,6 +160,21 @@ pub struct CreateCodelab {
     pub require_feedback: Option<bool>,
     /// Whether a submission is required before completion.
     pub require_submission: Option<bool>,
+    /// Maximum quiz attempts per attendee; `0` means unlimited.
+    #[serde(default)]
+    pub quiz_max_attempts: Option<i32>,
+    /// Minimum quiz score percentage required to pass.
+    #[serde(default)]
+    pub quiz_pass_percentage: Option<i32>,
+    /// Seat limit for attendees; `0` means unlimited.
+    #[serde(default)]
+    pub max_attendees: Option<i32>,
+    /// Epoch seconds when registration opens; `0` removes the bound.
+    #[serde(default)]
+    pub registration_opens_at: Option<i64>,
+    /// Epoch seconds when registration closes; `0` removes the bound.
+    #[serde(default)]
+    pub registration_closes_at: Option<i64>,
     /// Optional guide markdown rendered alongside the steps.
     pub guide_markdown: Option<String>,
 }
@@ -127,12 +182,118 @@ pub struct CreateCodelab {
 /// Login payload for the built-in administrator session.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginPayload {
-    /// Submitted administrator identifier.
+    /// Submitted administrator identifier or staff username.
     pub admin_id: String,
     /// Submitted administrator password.
     pub admin_pw: String,
 }
 
+/// Staff account as exposed by the user-management API (never includes the hash).
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct StaffUser {
+    /// Stable user identifier, used as the session subject and audit actor.
+    pub id: String,
+    /// Unique login name.
+    pub username: String,
+    /// Optional human-readable name.
+    pub display_name: Option<String>,
+    /// Serialized staff role (`owner`, `author`, `facilitator`, or `ta`).
+    pub role: String,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+    /// Last modification timestamp serialized as text.
+    pub updated_at: Option<String>,
+    /// Timestamp of the most recent successful login.
+    pub last_login_at: Option<String>,
+}
+
+/// Payload used by owners to create a staff account.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateStaffUserPayload {
+    /// Unique login name.
+    pub username: String,
+    /// Initial password, stored only as a salted hash.
+    pub password: String,
+    /// Staff role to grant.
+    pub role: String,
+    /// Optional human-readable name.
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+/// Partial update applied to an existing staff account.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct UpdateStaffUserPayload {
+    /// Replacement password, when rotating credentials.
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Replacement staff role.
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Replacement display name; an empty string clears it.
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+/// Personal API token metadata; the plaintext is only returned at creation.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiToken {
+    /// Stable token identifier used for revocation.
+    pub id: String,
+    /// Owning staff user identifier.
+    pub user_id: String,
+    /// Human-readable label chosen at creation.
+    pub name: String,
+    /// Leading characters of the token, shown to help identify it.
+    pub token_prefix: String,
+    /// Permission names the token is limited to.
+    pub scopes: Vec<String>,
+    /// Expiry in epoch seconds; `None` never expires.
+    pub expires_at: Option<i64>,
+    /// Timestamp of the most recent authenticated request.
+    pub last_used_at: Option<String>,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+}
+
+/// Payload used by staff to create a personal API token.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateApiTokenPayload {
+    /// Human-readable label, such as the CI pipeline using the token.
+    pub name: String,
+    /// Permission names to grant; defaults to every permission of the caller's role.
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+    /// Lifetime in days; omitted tokens never expire.
+    #[serde(default)]
+    pub expires_in_days: Option<u32>,
+}
+
+/// Newly created API token, including the plaintext value shown exactly once.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatedApiToken {
+    /// Plaintext bearer token.
+    pub token: String,
+    /// Stored token metadata.
+    #[serde(flatten)]
+    pub metadata: ApiToken,
+}
+
+/// Staff account assigned to a specific codelab.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct CodelabStaffMember {
+    /// Assigned staff user identifier.
+    pub user_id: String,
+    /// Login name of the assigned user.
+    pub username: String,
+    /// Optional human-readable name.
+    pub display_name: Option<String>,
+    /// Serialized staff role of the assigned user.
+    pub role: String,
+    /// Assignment timestamp serialized as text.
+    pub assigned_at: Option<String>,
+}
+
 /// Payload used to replace the ordered step list of a codelab.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateStepsPayload {
@@ -140,6 +301,17 @@ pub struct UpdateStepsPayload {
     pub steps: Vec<CreateStep>,
 }
 
+/// Result of replacing the step list of a codelab.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateStepsResponse {
+    /// Always `ok`.
+    pub status: String,
+    /// Revision recorded for the new step set.
+    pub revision_id: String,
+    /// Version of the step set after the update.
+    pub steps_version: i32,
+}
+
 /// Step payload accepted by create/update endpoints.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateStep {
@@ -149,6 +321,51 @@ pub struct CreateStep {
     pub title: String,
     /// Markdown body for the step.
     pub content_markdown: String,
+    /// Estimated minutes to complete the step; an existing step keeps its
+    /// stored duration when this is omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<i32>,
+}
+
+/// Payload inserting a single step into a codelab.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InsertStepPayload {
+    /// Step title.
+    pub title: String,
+    /// Markdown body for the step.
+    pub content_markdown: String,
+    /// 1-based position of the new step; appended when omitted.
+    #[serde(default)]
+    pub position: Option<i32>,
+}
+
+/// Payload replacing the title and body of a single step.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateStepPayload {
+    /// Step title.
+    pub title: String,
+    /// Markdown body for the step.
+    pub content_markdown: String,
+}
+
+/// Payload moving a single step to another position.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveStepPayload {
+    /// 1-based position the step should end up at.
+    pub position: i32,
+}
+
+/// Result of inserting, editing, deleting, or moving a single step.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StepChangeResponse {
+    /// Step that was changed.
+    pub step_id: String,
+    /// Revision recorded for the new step set.
+    pub revision_id: String,
+    /// Version of the step set after the change.
+    pub steps_version: i32,
+    /// Ordered steps after the change.
+    pub steps: Vec<Step>,
 }
 
 /// Learner registration payload for joining a codelab.
@@ -160,10 +377,97 @@ pub struct RegistrationPayload {
     pub code: String,
     /// Optional email address captured during registration.
     pub email: Option<String>,
+    /// Codelab passcode, required to join a private codelab without an invite.
+    #[serde(default)]
+    pub passcode: Option<String>,
+    /// Invite token from an invite link, accepted for private codelabs.
+    #[serde(default)]
+    pub invite_token: Option<String>,
+    /// Cohort to join; defaults to the cohort running right now, if any.
+    #[serde(default)]
+    pub cohort_id: Option<String>,
+}
+
+/// Public summary shown on the join page, including for private codelabs.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CodelabJoinInfo {
+    /// Codelab identifier.
+    pub id: String,
+    /// Display title.
+    pub title: String,
+    /// Short summary of the codelab.
+    pub description: String,
+    /// Author name displayed with the codelab.
+    pub author: String,
+    /// Whether anyone may join without a passcode or invite.
+    pub is_public: bool,
+    /// Whether registration asks for the codelab passcode.
+    pub passcode_required: bool,
+    /// Whether the invite token passed in the query is usable.
+    pub invite_valid: bool,
+}
+
+/// Payload used by staff to set or clear the join passcode.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CodelabPasscodePayload {
+    /// New passcode; `None` or blank removes it.
+    #[serde(default)]
+    pub passcode: Option<String>,
+}
+
+/// Codelab invite metadata without the token.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CodelabInvite {
+    /// Stable invite identifier used for revocation.
+    pub id: String,
+    /// Codelab the invite admits attendees to.
+    pub codelab_id: String,
+    /// Optional label, such as the team the link was sent to.
+    pub label: Option<String>,
+    /// Leading characters of the token, shown to help identify it.
+    pub token_prefix: String,
+    /// Registrations allowed; `None` is unlimited.
+    pub max_uses: Option<i32>,
+    /// Registrations made with the invite so far.
+    pub use_count: i32,
+    /// Expiry in epoch seconds; `None` never expires.
+    pub expires_at: Option<i64>,
+    /// Revocation time in epoch seconds.
+    pub revoked_at: Option<i64>,
+    /// Staff subject that created the invite.
+    pub created_by: String,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+}
+
+/// Payload used by staff to create an invite link.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct CreateCodelabInvitePayload {
+    /// Optional label, such as the team the link was sent to.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Registrations allowed; omitted invites are unlimited.
+    #[serde(default)]
+    pub max_uses: Option<u32>,
+    /// Lifetime in hours; omitted invites never expire.
+    #[serde(default)]
+    pub expires_in_hours: Option<u32>,
+}
+
+/// Newly created invite, including the token shown exactly once.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatedCodelabInvite {
+    /// Plaintext invite token.
+    pub token: String,
+    /// Frontend path that pre-fills the token, relative to the site root.
+    pub invite_path: String,
+    /// Stored invite metadata.
+    #[serde(flatten)]
+    pub metadata: CodelabInvite,
 }
 
 /// Stored attendee record for a codelab registration.
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Attendee {
     /// Attendee identifier.
     pub id: String,
@@ -188,6 +492,14 @@ pub struct Attendee {
     #[serde(default)]
     #[sqlx(default)]
     pub is_sharing_screen: bool,
+    /// Cohort the attendee joined, if the codelab runs in cohorts.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub cohort_id: Option<String>,
+    /// When the attendee's personal data was erased on request.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub erased_at: Option<String>,
 }
 
 /// Public attendee representation returned to clients.
@@ -215,6 +527,262 @@ pub struct AttendeePublic {
     /// Whether this attendee is currently sharing their screen.
     #[serde(default)]
     pub is_sharing_screen: bool,
+    /// Cohort the attendee joined, if the codelab runs in cohorts.
+    #[serde(default)]
+    pub cohort_id: Option<String>,
+}
+
+/// Learner waiting for a seat in a full codelab, as shown to staff.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WaitlistEntry {
+    /// Waitlist entry identifier.
+    pub id: String,
+    /// Related codelab identifier.
+    pub codelab_id: String,
+    /// Learner display name.
+    pub name: String,
+    /// Optional email address captured during registration.
+    pub email: Option<String>,
+    /// One-based place in the queue.
+    pub position: i64,
+    /// Cohort the learner asked to join.
+    #[serde(default)]
+    pub cohort_id: Option<String>,
+    /// Time the learner joined the waitlist.
+    pub created_at: Option<String>,
+}
+
+/// Registration response for learners placed on the waitlist instead of admitted.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WaitlistStatus {
+    /// Always `waitlisted`.
+    pub status: String,
+    /// Related codelab identifier.
+    pub codelab_id: String,
+    /// Waitlist entry identifier.
+    pub waitlist_id: String,
+    /// One-based place in the queue.
+    pub position: i64,
+}
+
+/// Summary of an attendee data erasure.
+///
+/// The attendee row is kept under a pseudonym so completion and cohort
+/// statistics stay intact; the counts report what was removed or redacted.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AttendeeErasure {
+    /// Erased attendee identifier.
+    pub attendee_id: String,
+    /// Related codelab identifier.
+    pub codelab_id: String,
+    /// Pseudonym that replaced the learner's name.
+    pub pseudonym: String,
+    /// Chat messages sent by or addressed to the attendee that were deleted.
+    pub chat_messages_deleted: u64,
+    /// Waitlist entries under the attendee's name, with their email and code, deleted.
+    pub waitlist_entries_deleted: u64,
+    /// Feedback entries whose comment was removed; ratings are kept.
+    pub feedback_redacted: u64,
+    /// Quiz answers cleared; correctness and attempt scores are kept.
+    pub quiz_answers_redacted: u64,
+    /// Submissions deleted.
+    pub submissions_deleted: u64,
+    /// Uploaded submission files removed from disk.
+    pub files_deleted: u64,
+    /// AI conversations and threads deleted.
+    pub ai_records_deleted: u64,
+    /// Inline comment messages deleted.
+    pub inline_comments_deleted: u64,
+    /// Certificates deleted.
+    pub certificates_deleted: u64,
+    /// Audit log rows whose IP address and user agent were cleared.
+    pub audit_logs_scrubbed: u64,
+    /// Sessions revoked and scrubbed.
+    pub sessions_revoked: u64,
+}
+
+/// One learner in a roster import.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct RosterEntry {
+    /// Learner display name.
+    pub name: String,
+    /// Optional email address.
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Join code; generated when omitted.
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+/// Payload used to pre-register a roster of attendees.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ImportRosterPayload {
+    /// Learners to register.
+    #[serde(default)]
+    pub attendees: Vec<RosterEntry>,
+    /// CSV roster with `name,email,code` columns, used instead of `attendees`.
+    #[serde(default)]
+    pub csv: Option<String>,
+    /// Cohort the imported attendees join.
+    #[serde(default)]
+    pub cohort_id: Option<String>,
+}
+
+/// Outcome of importing one roster row.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RosterImportRow {
+    /// One-based position in the submitted roster.
+    pub row: usize,
+    /// Learner display name.
+    pub name: String,
+    /// Learner email address.
+    pub email: Option<String>,
+    /// `created`, `duplicate`, or `invalid`.
+    pub status: String,
+    /// Attendee created or matched by name.
+    pub attendee_id: Option<String>,
+    /// Join code, returned only for created attendees.
+    pub code: Option<String>,
+    /// Entry page path prefilled with the learner's sign-in, for created attendees.
+    pub join_path: Option<String>,
+    /// Why the row was skipped.
+    pub error: Option<String>,
+}
+
+/// Result of a roster import.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RosterImportResult {
+    /// Number of attendees created.
+    pub created: usize,
+    /// Number of rows skipped because the nickname is taken.
+    pub duplicates: usize,
+    /// Number of rows rejected by validation.
+    pub invalid: usize,
+    /// Per-row outcomes in roster order.
+    pub rows: Vec<RosterImportRow>,
+}
+
+/// One scheduled run of a codelab with its own roster and live data.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct Cohort {
+    /// Cohort identifier.
+    pub id: String,
+    /// Related codelab identifier.
+    pub codelab_id: String,
+    /// Display name, such as `2026-04 evening`.
+    pub name: String,
+    /// Scheduled start as epoch seconds.
+    pub starts_at: Option<i64>,
+    /// Scheduled end as epoch seconds.
+    pub ends_at: Option<i64>,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+}
+
+/// Payload used to create or update a cohort.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCohort {
+    /// Display name.
+    pub name: String,
+    /// Scheduled start as epoch seconds.
+    #[serde(default)]
+    pub starts_at: Option<i64>,
+    /// Scheduled end as epoch seconds.
+    #[serde(default)]
+    pub ends_at: Option<i64>,
+}
+
+/// Cohort with the per-run numbers used to compare deliveries of a codelab.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CohortSummary {
+    /// Cohort metadata.
+    #[serde(flatten)]
+    pub cohort: Cohort,
+    /// Registered attendees.
+    pub attendee_count: i64,
+    /// Attendees who completed the codelab.
+    pub completed_count: i64,
+    /// Help requests raised, resolved or not.
+    pub help_request_count: i64,
+    /// Feedback entries submitted.
+    pub feedback_count: i64,
+    /// Mean satisfaction score, when any feedback was given.
+    pub avg_satisfaction: Option<f64>,
+    /// Mean difficulty score, when any feedback was given.
+    pub avg_difficulty: Option<f64>,
+}
+
+/// Step as captured in a codelab revision.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RevisionStep {
+    /// Step identifier, reused when the revision is restored.
+    pub id: String,
+    /// Step title.
+    pub title: String,
+    /// Markdown body for the step.
+    pub content_markdown: String,
+    /// Estimated minutes to complete the step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<i32>,
+}
+
+/// Revision metadata shown in a codelab's history.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct CodelabRevisionSummary {
+    /// Revision identifier.
+    pub id: String,
+    /// Related codelab identifier.
+    pub codelab_id: String,
+    /// 1-based revision counter within the codelab.
+    pub revision_number: i32,
+    /// Staff subject that saved the content; `None` for baselines.
+    pub author_id: Option<String>,
+    /// Display name or username of the author, when known.
+    pub author_name: Option<String>,
+    /// What produced the snapshot: `baseline`, `steps_update`, `guide_update`, or `restore`.
+    pub source: String,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+}
+
+/// Full snapshot of a codelab's guide and steps.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodelabRevision {
+    /// Revision metadata.
+    #[serde(flatten)]
+    pub summary: CodelabRevisionSummary,
+    /// Guide markdown at the time of the snapshot.
+    pub guide_markdown: Option<String>,
+    /// Ordered steps at the time of the snapshot.
+    pub steps: Vec<RevisionStep>,
+}
+
+/// Unified diff between the rendered markdown of two revisions.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodelabRevisionDiff {
+    /// Revision the diff starts from.
+    pub from: CodelabRevisionSummary,
+    /// Revision the diff leads to.
+    pub to: CodelabRevisionSummary,
+    /// Unified diff text; empty when the revisions render identically.
+    pub diff: String,
+}
+
+/// Which version of a codelab's content learners see.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublicationStatus {
+    /// Related codelab identifier.
+    pub codelab_id: String,
+    /// Revision learners see; `None` while the codelab was never published.
+    pub published_revision_id: Option<String>,
+    /// Number of the published revision.
+    pub published_revision_number: Option<i32>,
+    /// Time of the last publish.
+    pub published_at: Option<String>,
+    /// Number of the newest revision of the draft.
+    pub latest_revision_number: Option<i32>,
+    /// Whether the draft changed since it was last published.
+    pub has_unpublished_changes: bool,
 }
 
 impl From<Attendee> for AttendeePublic {
@@ -230,10 +798,20 @@ impl From<Attendee> for AttendeePublic {
             created_at: attendee.created_at,
             token: None,
             is_sharing_screen: attendee.is_sharing_screen,
+            cohort_id: attendee.cohort_id,
         }
     }
 }
 
+/// Error body returned when an attendee has not met a codelab's completion rules.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompletionRequirementsError {
+    /// Always `REQUIREMENTS_NOT_MET`.
+    pub error: String,
+    /// Unmet requirement codes: `QUIZ_REQUIRED`, `FEEDBACK_REQUIRED`, `SUBMISSION_REQUIRED`.
+    pub unmet: Vec<String>,
+}
+
 /// Certificate payload returned for a completed attendee.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CertificateInfo {
@@ -249,6 +827,103 @@ pub struct CertificateInfo {
     pub completed_at: String,
     /// Public verification URL for the certificate.
     pub verification_url: String,
+    /// Stable certificate identifier used for verification.
+    #[serde(default)]
+    pub certificate_id: String,
+    /// Compact EdDSA JWS carrying the signed [`CertificateClaims`].
+    #[serde(default)]
+    pub signed_certificate: String,
+    /// Whether an administrator revoked the certificate.
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+/// Stored certificate issued to a completed attendee.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct Certificate {
+    /// Stable certificate identifier (also the JWS `jti` claim).
+    pub id: String,
+    /// Attendee the certificate was issued to.
+    pub attendee_id: String,
+    /// Related codelab identifier.
+    pub codelab_id: String,
+    /// Identifier of the signing key (JWS `kid` header).
+    pub key_id: String,
+    /// Compact EdDSA JWS carrying the signed [`CertificateClaims`].
+    pub signed_token: String,
+    /// Issue timestamp serialized as text.
+    pub issued_at: Option<String>,
+    /// Revocation timestamp, if the certificate was revoked.
+    pub revoked_at: Option<String>,
+    /// Optional reason recorded when revoking.
+    pub revocation_reason: Option<String>,
+}
+
+/// Claims signed into a certificate JWS.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CertificateClaims {
+    /// Issuer of the certificate (the configured auth issuer).
+    pub iss: String,
+    /// Stable certificate identifier.
+    pub jti: String,
+    /// Attendee identifier the certificate was issued to.
+    pub sub: String,
+    /// Issue timestamp in epoch seconds.
+    pub iat: usize,
+    /// Related codelab identifier.
+    pub codelab_id: String,
+    /// Codelab title at issue time.
+    pub codelab_title: String,
+    /// Learner name at issue time.
+    pub attendee_name: String,
+    /// Codelab author at issue time.
+    pub author: String,
+    /// Completion timestamp recorded for the attendee.
+    pub completed_at: String,
+}
+
+/// Public verification result for a certificate identifier.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CertificateVerification {
+    /// Certificate identifier that was checked.
+    pub certificate_id: String,
+    /// `true` when the signature is valid and the certificate is not revoked.
+    pub valid: bool,
+    /// Whether the stored JWS verifies against the server signing key.
+    pub signature_valid: bool,
+    /// Whether an administrator revoked the certificate.
+    pub revoked: bool,
+    /// Revocation timestamp, if revoked.
+    pub revoked_at: Option<String>,
+    /// Revocation reason, if one was recorded.
+    pub revocation_reason: Option<String>,
+    /// Identifier of the signing key.
+    pub key_id: String,
+    /// Signed claims, present when the signature is valid.
+    pub claims: Option<CertificateClaims>,
+    /// Compact EdDSA JWS so verifiers can check it independently.
+    pub signed_certificate: String,
+}
+
+/// Public verification result for an exported Open Badges credential.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialVerification {
+    /// `true` when the proof is valid and the certificate exists and is not revoked.
+    pub valid: bool,
+    /// Whether the Data Integrity proof verifies against a key of this issuer.
+    pub proof_valid: bool,
+    /// Whether an administrator revoked the certificate.
+    pub revoked: bool,
+    /// Certificate the credential was issued for, when the proof is valid and it is known.
+    pub certificate_id: Option<String>,
+}
+
+/// Payload used by administrators to revoke a certificate.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct RevokeCertificatePayload {
+    /// Optional reason stored with the revocation.
+    #[serde(default)]
+    pub reason: Option<String>,
 }
 
 /// Payload used when an attendee requests help on a specific step.
@@ -273,6 +948,10 @@ pub struct HelpRequest {
     pub step_number: i32,
     /// Request status such as `open` or `resolved`.
     pub status: String,
+    /// Cohort the attendee belongs to.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub cohort_id: Option<String>,
     /// Creation timestamp serialized as text.
     pub created_at: Option<String>,
 }
@@ -294,6 +973,10 @@ pub struct ChatMessageRow {
     pub target_id: Option<String>,
     /// Optional sender identifier when the sender is a known attendee/admin.
     pub sender_id: Option<String>,
+    /// Cohort room the message was sent in.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub cohort_id: Option<String>,
     /// Creation timestamp serialized as text.
     pub created_at: Option<String>,
 }
@@ -412,6 +1095,10 @@ pub struct Feedback {
     pub satisfaction: String,
     /// Optional free-form comment from the learner.
     pub comment: Option<String>,
+    /// Cohort of the attendee who submitted the feedback.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub cohort_id: Option<String>,
     /// Creation timestamp serialized as text.
     pub created_at: Option<String>,
 }
@@ -464,6 +1151,9 @@ pub struct QuizSubmission {
     /// Stored as `0`/`1` in the database, serialized as a boolean in the API.
     #[serde(serialize_with = "to_bool", deserialize_with = "from_bool")]
     pub is_correct: i32,
+    /// Attempt this answer belongs to; `None` for rows recorded before attempt history.
+    #[serde(default)]
+    pub attempt_id: Option<String>,
     /// Creation timestamp serialized as text.
     pub created_at: Option<String>,
 }
@@ -475,7 +1165,8 @@ pub struct CreateQuizSubmission {
     pub quiz_id: String,
     /// Submitted answer payload.
     pub answer: String,
-    /// Whether the submitted answer is correct.
+    /// Client-side correctness hint. Ignored by the server, which grades answers itself.
+    #[serde(default)]
     pub is_correct: bool,
 }
 
@@ -486,6 +1177,61 @@ pub struct QuizSubmissionPayload {
     pub submissions: Vec<CreateQuizSubmission>,
 }
 
+/// Server-side grading outcome for a single quiz answer.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuizGradeResult {
+    /// Related quiz identifier.
+    pub quiz_id: String,
+    /// Submitted answer payload.
+    pub answer: String,
+    /// Whether the answer matched the stored correct answer.
+    pub is_correct: bool,
+}
+
+/// Stored quiz attempt with its computed score.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct QuizAttempt {
+    /// Attempt identifier.
+    pub id: String,
+    /// Related codelab identifier.
+    pub codelab_id: String,
+    /// Attendee who made the attempt.
+    pub attendee_id: String,
+    /// One-based attempt counter per attendee.
+    pub attempt_number: i32,
+    /// Number of quizzes in the codelab when the attempt was graded.
+    pub total_questions: i32,
+    /// Number of correct answers in the attempt.
+    pub correct_count: i32,
+    /// Percentage of correct answers in the range `0..=100`.
+    pub score: f64,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+}
+
+/// Graded result returned after submitting a batch of quiz answers.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuizSubmissionResult {
+    /// Identifier of the stored attempt.
+    pub attempt_id: String,
+    /// One-based attempt counter for the attendee.
+    pub attempt_number: i32,
+    /// Number of quizzes in the codelab; unanswered quizzes count as incorrect.
+    pub total: i32,
+    /// Number of correct answers.
+    pub correct: i32,
+    /// Percentage of correct answers in the range `0..=100`.
+    pub score: f64,
+    /// Minimum percentage required to pass.
+    pub pass_percentage: i32,
+    /// Whether `score` meets `pass_percentage`.
+    pub passed: bool,
+    /// Attempts left after this one; `None` when attempts are unlimited.
+    pub remaining_attempts: Option<i32>,
+    /// Per-question grading results in submission order.
+    pub results: Vec<QuizGradeResult>,
+}
+
 /// Quiz submission joined with attendee metadata for admin views.
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct QuizSubmissionWithAttendee {
@@ -504,6 +1250,9 @@ pub struct QuizSubmissionWithAttendee {
     /// Stored as `0`/`1` in the database, serialized as a boolean in the API.
     #[serde(serialize_with = "to_bool", deserialize_with = "from_bool")]
     pub is_correct: i32,
+    /// Attempt this answer belongs to; `None` for rows recorded before attempt history.
+    #[serde(default)]
+    pub attempt_id: Option<String>,
     /// Creation timestamp serialized as text.
     pub created_at: Option<String>,
 }
@@ -567,6 +1316,11 @@ pub struct CreateSubmissionLink {
     pub title: Option<String>,
 }
 
+/// Default quiz pass threshold used for older rows that predate the field.
+pub fn default_quiz_pass_percentage() -> i32 {
+    100
+}
+
 /// Default submission type used for older rows that predate the field.
 pub fn default_submission_type() -> String {
     "file".to_string()
@@ -696,8 +1450,18 @@ mod tests {
             require_quiz: 0,
             require_feedback: 0,
             require_submission: 0,
+            quiz_max_attempts: 0,
+            quiz_pass_percentage: 100,
+            max_attendees: 0,
+            registration_opens_at: None,
+            registration_closes_at: None,
             guide_markdown: None,
             created_at: Some("2023-01-01".to_string()),
+            published_revision_id: None,
+            published_at: None,
+            version: 1,
+            steps_version: 1,
+            categories: None,
         };
 
         let json = serde_json::to_string(&codelab).unwrap();
@@ -737,6 +1501,8 @@ mod tests {
             completed_at: Some("2026-02-01".to_string()),
             created_at: Some("2026-01-01".to_string()),
             is_sharing_screen: true,
+            cohort_id: Some("k1".to_string()),
+            erased_at: None,
         };
 
         let public: AttendeePublic = attendee.into();
@@ -750,6 +1516,7 @@ mod tests {
         assert_eq!(public.created_at.as_deref(), Some("2026-01-01"));
         assert_eq!(public.token, None);
         assert!(public.is_sharing_screen);
+        assert_eq!(public.cohort_id.as_deref(), Some("k1"));
     }
 
     #[test]
@@ -769,7 +1536,17 @@ mod tests {
             require_quiz: 0,
             require_feedback: 0,
             require_submission: 0,
+            quiz_max_attempts: 0,
+            quiz_pass_percentage: 100,
+            max_attendees: 0,
+            registration_opens_at: None,
+            registration_closes_at: None,
             guide_markdown: None,
+            published_revision_id: None,
+            published_at: None,
+            version: 1,
+            steps_version: 1,
+            categories: None,
             created_at: None,
         };
 
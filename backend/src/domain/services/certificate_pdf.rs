@@ -0,0 +1,514 @@
+//! Server-side rendering of completion certificates as PDF documents.
+
+use crate::utils::pdf::{FontId, ImageId, PdfDocument, Rgb, StandardFont};
+use crate::utils::qr::QrCode;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const TEXT_COLOR: Rgb = Rgb(0.13, 0.13, 0.15);
+const MUTED_COLOR: Rgb = Rgb(0.45, 0.45, 0.5);
+const RULE_COLOR: Rgb = Rgb(0.8, 0.8, 0.82);
+const DEFAULT_ACCENT: Rgb = Rgb(0.259, 0.522, 0.957);
+
+/// Paper size of the rendered certificate; always landscape.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CertificatePageSize {
+    #[default]
+    A4,
+    Letter,
+}
+
+impl CertificatePageSize {
+    fn dimensions(self) -> (f32, f32) {
+        match self {
+            CertificatePageSize::A4 => (841.89, 595.28),
+            CertificatePageSize::Letter => (792.0, 612.0),
+        }
+    }
+}
+
+/// Layout and branding for rendered certificates, loaded from `CERTIFICATE_TEMPLATE`.
+///
+/// Every field is optional in the JSON file. Relative asset paths are resolved
+/// against the template file's directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CertificateTemplate {
+    /// Heading at the top of the page.
+    pub title: String,
+    /// Line under the heading; defaults to the credential issuer name.
+    pub subtitle: Option<String>,
+    /// Text above the attendee name.
+    pub intro_text: String,
+    /// Text between the attendee name and the codelab title.
+    pub completion_text: String,
+    /// Prefix for the codelab author line.
+    pub author_text: String,
+    /// Label above the completion date.
+    pub date_label: String,
+    /// `chrono` format string for the completion date.
+    pub date_format: String,
+    /// Name printed under the signature line.
+    pub signer_name: Option<String>,
+    /// Role printed under the signer name.
+    pub signer_title: Option<String>,
+    /// Logo image drawn above the heading.
+    pub logo_path: Option<PathBuf>,
+    /// Signature image drawn above the signature line.
+    pub signature_path: Option<PathBuf>,
+    /// TrueType font used for all text; needed for non-Latin names.
+    pub font_path: Option<PathBuf>,
+    /// Border and highlight color as `#rrggbb`.
+    pub accent_color: String,
+    /// Paper size.
+    pub page_size: CertificatePageSize,
+    /// Whether to draw a QR code linking to the verification URL.
+    pub show_qr: bool,
+}
+
+impl Default for CertificateTemplate {
+    fn default() -> Self {
+        Self {
+            title: "Certificate of Completion".to_string(),
+            subtitle: None,
+            intro_text: "This is to certify that".to_string(),
+            completion_text: "has successfully completed the hands-on codelab".to_string(),
+            author_text: "Facilitated by".to_string(),
+            date_label: "Completion date".to_string(),
+            date_format: "%B %-d, %Y".to_string(),
+            signer_name: None,
+            signer_title: None,
+            logo_path: None,
+            signature_path: None,
+            font_path: None,
+            accent_color: "#4285F4".to_string(),
+            page_size: CertificatePageSize::A4,
+            show_qr: true,
+        }
+    }
+}
+
+impl CertificateTemplate {
+    /// Loads the template named by `CERTIFICATE_TEMPLATE`, or the built-in default.
+    pub fn from_env() -> Self {
+        let Ok(path) = std::env::var("CERTIFICATE_TEMPLATE") else {
+            return Self::default();
+        };
+        match Self::from_file(Path::new(path.trim())) {
+            Ok(template) => template,
+            Err(err) => {
+                tracing::warn!(
+                    "CERTIFICATE_TEMPLATE ignored ({}); using the default certificate template.",
+                    err
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Reads a JSON template and resolves its asset paths.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        let mut template: Self = serde_json::from_str(&raw)
+            .map_err(|err| format!("invalid template {}: {err}", path.display()))?;
+        let base = path.parent().unwrap_or_else(|| Path::new("."));
+        for asset in [
+            &mut template.logo_path,
+            &mut template.signature_path,
+            &mut template.font_path,
+        ] {
+            if let Some(asset_path) = asset.as_mut() {
+                if asset_path.is_relative() {
+                    *asset_path = base.join(&*asset_path);
+                }
+            }
+        }
+        Ok(template)
+    }
+}
+
+/// Certificate fields printed on the page.
+#[derive(Debug, Clone)]
+pub struct CertificateDocument {
+    /// Certificate identifier printed under the QR code.
+    pub certificate_id: String,
+    /// Attendee display name.
+    pub attendee_name: String,
+    /// Completed codelab title.
+    pub codelab_title: String,
+    /// Codelab author name.
+    pub author: String,
+    /// Completion timestamp as stored.
+    pub completed_at: String,
+    /// Issuer shown when the template has no subtitle.
+    pub issuer_name: String,
+    /// Absolute URL encoded in the QR code.
+    pub verification_url: String,
+}
+
+/// Parses timestamps stored as SQL `CURRENT_TIMESTAMP` text or RFC 3339.
+pub fn parse_stored_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|value| value.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").map(|v| v.and_utc()))
+        .ok()
+}
+
+/// Renders a one-page landscape certificate.
+///
+/// Missing or unreadable template assets are skipped with a warning so a bad
+/// logo path never blocks certificate delivery.
+pub fn render_certificate_pdf(
+    template: &CertificateTemplate,
+    certificate: &CertificateDocument,
+) -> Result<Vec<u8>, String> {
+    let (width, height) = template.page_size.dimensions();
+    let center = width / 2.0;
+    let accent = Rgb::from_hex(&template.accent_color).unwrap_or(DEFAULT_ACCENT);
+
+    let mut doc = PdfDocument::new(width, height);
+    doc.set_title(&format!(
+        "{} - {}",
+        certificate.codelab_title, certificate.attendee_name
+    ));
+    let (regular, bold) = load_fonts(&mut doc, template.font_path.as_deref());
+    for text in [&certificate.attendee_name, &certificate.codelab_title] {
+        if !doc.supports_text(regular, text) {
+            tracing::warn!(
+                "Certificate {} has characters the template font cannot draw; set font_path in the certificate template.",
+                certificate.certificate_id
+            );
+        }
+    }
+
+    doc.stroke_rect(18.0, 18.0, width - 36.0, height - 36.0, 4.0, accent);
+    doc.stroke_rect(30.0, 30.0, width - 60.0, height - 60.0, 0.75, RULE_COLOR);
+
+    let mut title_y = height - 118.0;
+    if let Some((image, image_width, image_height)) =
+        load_image(&mut doc, template.logo_path.as_deref())
+    {
+        let (draw_width, draw_height) = fit_box(image_width, image_height, 200.0, 56.0);
+        let top = height - 56.0;
+        doc.draw_image(
+            image,
+            center - draw_width / 2.0,
+            top - draw_height,
+            draw_width,
+            draw_height,
+        );
+        title_y = top - draw_height - 42.0;
+    }
+
+    let title_size = fit_font_size(&doc, bold, &template.title, 30.0, 16.0, width - 160.0);
+    doc.draw_text_centered(
+        bold,
+        title_size,
+        center,
+        title_y,
+        &template.title,
+        TEXT_COLOR,
+    );
+    let subtitle = template
+        .subtitle
+        .as_deref()
+        .unwrap_or(&certificate.issuer_name);
+    doc.draw_text_centered(regular, 13.0, center, title_y - 26.0, subtitle, MUTED_COLOR);
+
+    let middle = height / 2.0;
+    doc.draw_text_centered(
+        regular,
+        13.0,
+        center,
+        middle + 62.0,
+        &template.intro_text,
+        MUTED_COLOR,
+    );
+    let name_size = fit_font_size(
+        &doc,
+        bold,
+        &certificate.attendee_name,
+        32.0,
+        14.0,
+        width - 200.0,
+    );
+    doc.draw_text_centered(
+        bold,
+        name_size,
+        center,
+        middle + 20.0,
+        &certificate.attendee_name,
+        TEXT_COLOR,
+    );
+    let name_width = doc
+        .text_width(bold, name_size, &certificate.attendee_name)
+        .max(240.0);
+    doc.line(
+        (center - name_width / 2.0 - 24.0, middle + 10.0),
+        (center + name_width / 2.0 + 24.0, middle + 10.0),
+        1.0,
+        RULE_COLOR,
+    );
+    doc.draw_text_centered(
+        regular,
+        13.0,
+        center,
+        middle - 18.0,
+        &template.completion_text,
+        MUTED_COLOR,
+    );
+    let codelab_size = fit_font_size(
+        &doc,
+        bold,
+        &certificate.codelab_title,
+        22.0,
+        11.0,
+        width - 200.0,
+    );
+    doc.draw_text_centered(
+        bold,
+        codelab_size,
+        center,
+        middle - 50.0,
+        &certificate.codelab_title,
+        accent,
+    );
+    if !certificate.author.trim().is_empty() {
+        doc.draw_text_centered(
+            regular,
+            11.0,
+            center,
+            middle - 74.0,
+            &format!("{} {}", template.author_text, certificate.author),
+            MUTED_COLOR,
+        );
+    }
+
+    let baseline = 78.0;
+    let completed = parse_stored_timestamp(&certificate.completed_at)
+        .map(|value| value.format(&template.date_format).to_string())
+        .unwrap_or_else(|| certificate.completed_at.clone());
+    doc.draw_text(
+        regular,
+        9.0,
+        72.0,
+        baseline + 22.0,
+        &template.date_label,
+        MUTED_COLOR,
+    );
+    doc.draw_text(bold, 14.0, 72.0, baseline, &completed, TEXT_COLOR);
+
+    let signature = load_image(&mut doc, template.signature_path.as_deref());
+    if signature.is_some() || template.signer_name.is_some() {
+        if let Some((image, image_width, image_height)) = signature {
+            let (draw_width, draw_height) = fit_box(image_width, image_height, 160.0, 44.0);
+            doc.draw_image(
+                image,
+                center - draw_width / 2.0,
+                baseline + 20.0,
+                draw_width,
+                draw_height,
+            );
+        }
+        doc.line(
+            (center - 90.0, baseline + 16.0),
+            (center + 90.0, baseline + 16.0),
+            0.75,
+            MUTED_COLOR,
+        );
+        if let Some(name) = &template.signer_name {
+            doc.draw_text_centered(bold, 11.0, center, baseline, name, TEXT_COLOR);
+        }
+        if let Some(role) = &template.signer_title {
+            doc.draw_text_centered(regular, 9.0, center, baseline - 14.0, role, MUTED_COLOR);
+        }
+    }
+
+    let right_center = width - 72.0 - 42.0;
+    if template.show_qr {
+        let qr = QrCode::encode(certificate.verification_url.as_bytes())?;
+        draw_qr(&mut doc, &qr, right_center - 42.0, baseline - 6.0, 84.0);
+    }
+    doc.draw_text_centered(
+        regular,
+        6.5,
+        right_center,
+        baseline - 18.0,
+        &certificate.certificate_id,
+        MUTED_COLOR,
+    );
+
+    Ok(doc.finish())
+}
+
+/// Returns (regular, bold) fonts, preferring the template's TrueType font.
+fn load_fonts(doc: &mut PdfDocument, font_path: Option<&Path>) -> (FontId, FontId) {
+    if let Some(path) = font_path {
+        match std::fs::read(path)
+            .map_err(|err| err.to_string())
+            .and_then(|data| doc.add_truetype_font(data))
+        {
+            Ok(font) => return (font, font),
+            Err(err) => tracing::warn!("Certificate font {} ignored: {}", path.display(), err),
+        }
+    }
+    (
+        doc.add_standard_font(StandardFont::Helvetica),
+        doc.add_standard_font(StandardFont::HelveticaBold),
+    )
+}
+
+fn load_image(doc: &mut PdfDocument, path: Option<&Path>) -> Option<(ImageId, u32, u32)> {
+    let path = path?;
+    match std::fs::read(path)
+        .map_err(|err| err.to_string())
+        .and_then(|data| doc.add_image(&data))
+    {
+        Ok(image) => Some(image),
+        Err(err) => {
+            tracing::warn!("Certificate image {} ignored: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// Scales an image to fit inside `max_width` x `max_height`, preserving aspect ratio.
+fn fit_box(width: u32, height: u32, max_width: f32, max_height: f32) -> (f32, f32) {
+    let scale = (max_width / width.max(1) as f32).min(max_height / height.max(1) as f32);
+    (width as f32 * scale, height as f32 * scale)
+}
+
+/// Shrinks the font size until `text` fits in `max_width`, down to `min_size`.
+fn fit_font_size(
+    doc: &PdfDocument,
+    font: FontId,
+    text: &str,
+    max_size: f32,
+    min_size: f32,
+    max_width: f32,
+) -> f32 {
+    let width = doc.text_width(font, max_size, text);
+    if width <= max_width {
+        return max_size;
+    }
+    (max_size * max_width / width).max(min_size)
+}
+
+/// Draws a QR code with its quiet zone inside a `size`-point square.
+fn draw_qr(doc: &mut PdfDocument, qr: &QrCode, x: f32, y: f32, size: f32) {
+    const QUIET_ZONE: usize = 2;
+    let modules = qr.size() + QUIET_ZONE * 2;
+    let module = size / modules as f32;
+    let origin_x = x + QUIET_ZONE as f32 * module;
+    let top = y + size - QUIET_ZONE as f32 * module;
+
+    for row in 0..qr.size() {
+        let mut column = 0;
+        while column < qr.size() {
+            if !qr.is_dark(column, row) {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < qr.size() && qr.is_dark(column, row) {
+                column += 1;
+            }
+            doc.fill_rect(
+                origin_x + start as f32 * module,
+                top - (row + 1) as f32 * module,
+                (column - start) as f32 * module,
+                module,
+                Rgb::BLACK,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> CertificateDocument {
+        CertificateDocument {
+            certificate_id: "0f8e4c1a-7b3d-4e2f-9a6c-5d1b2e3f4a5b".to_string(),
+            attendee_name: "Ada Lovelace".to_string(),
+            codelab_title: "Build a REST API with Rust".to_string(),
+            author: "Grace".to_string(),
+            completed_at: "2026-03-12 09:30:00".to_string(),
+            issuer_name: "Open Codelabs".to_string(),
+            verification_url:
+                "https://codelabs.example.com/api/certificates/verify/0f8e4c1a-7b3d-4e2f-9a6c-5d1b2e3f4a5b"
+                    .to_string(),
+        }
+    }
+
+    #[test]
+    fn renders_pdf_with_default_template() {
+        let pdf = render_certificate_pdf(&CertificateTemplate::default(), &sample()).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+
+        let document = lopdf::Document::load_mem(&pdf).unwrap();
+        let page = document.get_pages()[&1];
+        let content =
+            lopdf::content::Content::decode(&document.get_page_content(page).unwrap()).unwrap();
+        let text: Vec<&[u8]> = content
+            .operations
+            .iter()
+            .filter(|op| op.operator == "Tj")
+            .map(|op| op.operands[0].as_str().unwrap())
+            .collect();
+        assert!(text.contains(&b"Ada Lovelace".as_slice()));
+        assert!(text.contains(&b"Build a REST API with Rust".as_slice()));
+        // The QR code is drawn as filled runs of dark modules.
+        let fills = content
+            .operations
+            .iter()
+            .filter(|op| op.operator == "f")
+            .count();
+        assert!(fills > 50);
+    }
+
+    #[test]
+    fn missing_assets_do_not_fail_rendering() {
+        let template = CertificateTemplate {
+            logo_path: Some(PathBuf::from("/nonexistent/logo.png")),
+            signature_path: Some(PathBuf::from("/nonexistent/signature.png")),
+            font_path: Some(PathBuf::from("/nonexistent/font.ttf")),
+            signer_name: Some("Jane Doe".to_string()),
+            show_qr: false,
+            ..CertificateTemplate::default()
+        };
+        assert!(render_certificate_pdf(&template, &sample()).is_ok());
+    }
+
+    #[test]
+    fn template_file_resolves_relative_assets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("template.json");
+        std::fs::write(
+            &path,
+            r#"{ "title": "Certificate", "logo_path": "logo.png", "font_path": "/fonts/a.ttf", "page_size": "letter" }"#,
+        )
+        .unwrap();
+
+        let template = CertificateTemplate::from_file(&path).unwrap();
+        assert_eq!(template.title, "Certificate");
+        assert_eq!(template.logo_path, Some(dir.path().join("logo.png")));
+        assert_eq!(template.font_path, Some(PathBuf::from("/fonts/a.ttf")));
+        assert_eq!(template.page_size, CertificatePageSize::Letter);
+        assert!(template.show_qr);
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(CertificateTemplate::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn parses_stored_timestamps() {
+        assert!(parse_stored_timestamp("2026-03-12 09:30:00").is_some());
+        assert!(parse_stored_timestamp("2026-03-12T09:30:00+09:00").is_some());
+        assert!(parse_stored_timestamp("yesterday").is_none());
+    }
+}
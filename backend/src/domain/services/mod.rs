@@ -1,7 +1,17 @@
 //! Service helpers for infrastructure-adjacent domain operations.
 
+/// Server-side PDF rendering for completion certificates.
+pub mod certificate_pdf;
 /// Workspace and git orchestration for code-server style flows.
 pub mod codeserver;
+/// OpenID Connect client used for staff single sign-on.
+pub mod oidc;
+/// Static HTML rendering of codelabs for offline copies.
+pub mod static_site;
 
+#[doc(inline)]
+pub use certificate_pdf::*;
 #[doc(inline)]
 pub use codeserver::*;
+#[doc(inline)]
+pub use static_site::*;
@@ -0,0 +1,367 @@
+//! OpenID Connect authorization-code flow used for staff single sign-on.
+
+use crate::infrastructure::config::OidcConfig;
+use crate::middleware::auth::StaffRole;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+const ID_TOKEN_LEEWAY_SECONDS: u64 = 60;
+
+/// Subset of the provider metadata published at `/.well-known/openid-configuration`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcDiscovery {
+    /// Issuer identifier; must match the configured issuer.
+    pub issuer: String,
+    /// Endpoint the browser is redirected to for authentication.
+    pub authorization_endpoint: String,
+    /// Endpoint used to exchange the authorization code for tokens.
+    pub token_endpoint: String,
+    /// Location of the provider's public signing keys.
+    pub jwks_uri: String,
+}
+
+/// Verified ID token claims used to provision the staff account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidcIdentity {
+    /// Provider-scoped subject identifier (`sub`).
+    pub subject: String,
+    /// `preferred_username` claim when present.
+    pub preferred_username: Option<String>,
+    /// `email` claim when present.
+    pub email: Option<String>,
+    /// `name` claim when present.
+    pub name: Option<String>,
+    /// Group names read from the configured groups claim.
+    pub groups: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+/// Returns a random PKCE code verifier (RFC 7636, 64 unreserved characters).
+pub fn generate_code_verifier() -> String {
+    random_token(64)
+}
+
+/// Returns a random opaque value suitable for `state` and `nonce` parameters.
+pub fn random_token(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+/// Derives the `S256` PKCE code challenge for a verifier.
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Fetches provider metadata and checks that it belongs to the configured issuer.
+pub async fn discover(config: &OidcConfig) -> Result<OidcDiscovery, String> {
+    let url = format!("{}/.well-known/openid-configuration", config.issuer_url);
+    let discovery: OidcDiscovery = http_client()?
+        .get(&url)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(|e| format!("discovery request failed: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("invalid discovery document: {e}"))?;
+
+    if discovery.issuer.trim_end_matches('/') != config.issuer_url {
+        return Err(format!(
+            "discovery issuer {} does not match {}",
+            discovery.issuer, config.issuer_url
+        ));
+    }
+    Ok(discovery)
+}
+
+/// Builds the authorization endpoint URL for a new login attempt.
+pub fn authorization_url(
+    config: &OidcConfig,
+    discovery: &OidcDiscovery,
+    redirect_uri: &str,
+    state: &str,
+    nonce: &str,
+    code_verifier: &str,
+) -> Result<String, String> {
+    let mut url = url::Url::parse(&discovery.authorization_endpoint)
+        .map_err(|e| format!("invalid authorization endpoint: {e}"))?;
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &config.client_id)
+        .append_pair("redirect_uri", redirect_uri)
+        .append_pair("scope", &config.scopes)
+        .append_pair("state", state)
+        .append_pair("nonce", nonce)
+        .append_pair("code_challenge", &pkce_challenge(code_verifier))
+        .append_pair("code_challenge_method", "S256");
+    Ok(url.into())
+}
+
+/// Exchanges an authorization code and returns the raw ID token.
+pub async fn exchange_code(
+    config: &OidcConfig,
+    discovery: &OidcDiscovery,
+    code: &str,
+    redirect_uri: &str,
+    code_verifier: &str,
+) -> Result<String, String> {
+    let body = {
+        let mut form = url::form_urlencoded::Serializer::new(String::new());
+        form.append_pair("grant_type", "authorization_code")
+            .append_pair("code", code)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("client_id", &config.client_id)
+            .append_pair("code_verifier", code_verifier);
+        if let Some(secret) = &config.client_secret {
+            form.append_pair("client_secret", secret);
+        }
+        form.finish()
+    };
+
+    let response: TokenResponse = http_client()?
+        .post(&discovery.token_endpoint)
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )
+        .header(reqwest::header::ACCEPT, "application/json")
+        .body(body)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(|e| format!("token request failed: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("invalid token response: {e}"))?;
+
+    response
+        .id_token
+        .ok_or_else(|| "token response did not include an id_token".to_string())
+}
+
+/// Fetches the provider's signing keys.
+pub async fn fetch_jwks(discovery: &OidcDiscovery) -> Result<JwkSet, String> {
+    http_client()?
+        .get(&discovery.jwks_uri)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(|e| format!("jwks request failed: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("invalid jwks document: {e}"))
+}
+
+/// Verifies an ID token's signature, issuer, audience, expiry, and nonce.
+///
+/// Only asymmetric algorithms are accepted so a token can never be validated
+/// with the client secret or a key the provider did not publish.
+pub fn validate_id_token(
+    config: &OidcConfig,
+    discovery: &OidcDiscovery,
+    jwks: &JwkSet,
+    id_token: &str,
+    expected_nonce: &str,
+) -> Result<OidcIdentity, String> {
+    let header = decode_header(id_token).map_err(|e| format!("malformed id_token: {e}"))?;
+    if !is_asymmetric(header.alg) {
+        return Err(format!("unsupported id_token algorithm {:?}", header.alg));
+    }
+    let jwk = match header.kid.as_deref() {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| "id_token signing key not found in jwks".to_string())?;
+    let key = DecodingKey::from_jwk(jwk).map_err(|e| format!("unusable jwk: {e}"))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.leeway = ID_TOKEN_LEEWAY_SECONDS;
+    validation.set_issuer(&[discovery.issuer.as_str()]);
+    validation.set_audience(&[config.client_id.as_str()]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    let claims = decode::<Value>(id_token, &key, &validation)
+        .map_err(|e| format!("id_token rejected: {e}"))?
+        .claims;
+
+    if claims.get("nonce").and_then(Value::as_str) != Some(expected_nonce) {
+        return Err("id_token nonce mismatch".to_string());
+    }
+    let multiple_audiences = claims
+        .get("aud")
+        .and_then(Value::as_array)
+        .is_some_and(|aud| aud.len() > 1);
+    if multiple_audiences
+        && claims.get("azp").and_then(Value::as_str) != Some(config.client_id.as_str())
+    {
+        return Err("id_token azp does not match client_id".to_string());
+    }
+
+    let text = |key: &str| {
+        claims
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let subject = text("sub").ok_or_else(|| "id_token has an empty sub".to_string())?;
+    let groups = match claims.get(&config.groups_claim) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        Some(Value::String(group)) => vec![group.clone()],
+        _ => Vec::new(),
+    };
+
+    Ok(OidcIdentity {
+        subject,
+        preferred_username: text("preferred_username"),
+        email: text("email"),
+        name: text("name"),
+        groups,
+    })
+}
+
+/// Maps provider groups to the most privileged matching staff role.
+///
+/// Falls back to the configured default role; `None` means the user is not
+/// allowed to sign in.
+pub fn map_role(config: &OidcConfig, groups: &[String]) -> Option<StaffRole> {
+    StaffRole::ALL
+        .into_iter()
+        .find(|role| {
+            config
+                .role_mappings
+                .iter()
+                .any(|(group, mapped)| mapped == role && groups.contains(group))
+        })
+        .or(config.default_role)
+}
+
+fn is_asymmetric(alg: Algorithm) -> bool {
+    !matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512)
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| format!("http client error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mappings: Vec<(&str, StaffRole)>, default_role: Option<StaffRole>) -> OidcConfig {
+        OidcConfig {
+            issuer_url: "https://idp.example.com".to_string(),
+            client_id: "codelabs".to_string(),
+            client_secret: None,
+            redirect_url: None,
+            scopes: "openid".to_string(),
+            groups_claim: "groups".to_string(),
+            role_mappings: mappings
+                .into_iter()
+                .map(|(group, role)| (group.to_string(), role))
+                .collect(),
+            default_role,
+            provider_name: "SSO".to_string(),
+        }
+    }
+
+    #[test]
+    fn pkce_challenge_matches_rfc7636_vector() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        let verifier = generate_code_verifier();
+        assert_eq!(verifier.len(), 64);
+        assert_ne!(verifier, generate_code_verifier());
+    }
+
+    #[test]
+    fn map_role_prefers_most_privileged_group() {
+        let cfg = config(
+            vec![("mentors", StaffRole::Ta), ("admins", StaffRole::Owner)],
+            None,
+        );
+        let groups = vec!["mentors".to_string(), "admins".to_string()];
+        assert_eq!(map_role(&cfg, &groups), Some(StaffRole::Owner));
+        assert_eq!(
+            map_role(&cfg, &["mentors".to_string()]),
+            Some(StaffRole::Ta)
+        );
+        assert_eq!(map_role(&cfg, &["others".to_string()]), None);
+
+        let cfg = config(vec![], Some(StaffRole::Facilitator));
+        assert_eq!(map_role(&cfg, &[]), Some(StaffRole::Facilitator));
+    }
+
+    #[test]
+    fn authorization_url_includes_pkce_and_nonce() {
+        let cfg = config(vec![], None);
+        let discovery = OidcDiscovery {
+            issuer: cfg.issuer_url.clone(),
+            authorization_endpoint: "https://idp.example.com/authorize?tenant=a".to_string(),
+            token_endpoint: "https://idp.example.com/token".to_string(),
+            jwks_uri: "https://idp.example.com/jwks".to_string(),
+        };
+        let url = authorization_url(
+            &cfg,
+            &discovery,
+            "https://codelabs.example.com/api/auth/oidc/callback",
+            "state-1",
+            "nonce-1",
+            "verifier",
+        )
+        .expect("url");
+        let parsed = url::Url::parse(&url).expect("parse");
+        let params: std::collections::HashMap<_, _> = parsed.query_pairs().into_owned().collect();
+        assert_eq!(params["tenant"], "a");
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["state"], "state-1");
+        assert_eq!(params["nonce"], "nonce-1");
+        assert_eq!(params["code_challenge"], pkce_challenge("verifier"));
+        assert_eq!(params["code_challenge_method"], "S256");
+    }
+
+    #[test]
+    fn validate_id_token_rejects_symmetric_algorithms() {
+        let cfg = config(vec![], None);
+        let discovery = OidcDiscovery {
+            issuer: cfg.issuer_url.clone(),
+            authorization_endpoint: String::new(),
+            token_endpoint: String::new(),
+            jwks_uri: String::new(),
+        };
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(Algorithm::HS256),
+            &serde_json::json!({ "sub": "u", "iss": cfg.issuer_url, "aud": "codelabs", "exp": 4102444800u64, "nonce": "n" }),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .expect("token");
+        let err = validate_id_token(&cfg, &discovery, &JwkSet { keys: vec![] }, &token, "n")
+            .expect_err("hs256 must be rejected");
+        assert!(err.contains("unsupported"));
+    }
+}
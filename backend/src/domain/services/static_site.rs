@@ -0,0 +1,493 @@
+//! Static HTML rendering of a codelab for offline copies and docs sites.
+//!
+//! The site has an `index.html` with the overview, guide, and materials, one
+//! `step-NN.html` page per step with navigation between them, and
+//! `assets/style.css`. Code blocks are highlighted on the server into CSS
+//! classes, so the pages need no scripts. Links and images that point at
+//! `/uploads/...` files, including `src` and `href` attributes of raw HTML in
+//! the markdown, are rewritten to copies under `assets/uploads/`.
+
+use crate::domain::models::{Codelab, Material, Step};
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use std::collections::BTreeSet;
+use std::sync::OnceLock;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+const HIGHLIGHT_THEME: &str = "InspiredGitHub";
+const UPLOAD_PREFIX: &str = "/uploads/";
+
+const STYLESHEET: &str = r#"*, *::before, *::after { box-sizing: border-box; }
+body { margin: 0; font-family: system-ui, -apple-system, "Segoe UI", sans-serif; line-height: 1.6; color: #1f2328; background: #fff; }
+a { color: #0969da; }
+.site-header { padding: 0.75rem 1.5rem; border-bottom: 1px solid #d0d7de; font-weight: 600; }
+.site-header a { color: inherit; text-decoration: none; }
+.layout { display: flex; align-items: flex-start; max-width: 72rem; margin: 0 auto; }
+.steps { flex: 0 0 16rem; position: sticky; top: 0; max-height: 100vh; overflow-y: auto; padding: 1.5rem 1rem; border-right: 1px solid #d0d7de; }
+.steps ol { margin: 0; padding-left: 1.25rem; }
+.steps li { margin: 0.35rem 0; }
+.steps li.current > a { font-weight: 600; color: inherit; }
+.duration { color: #656d76; font-size: 0.85em; white-space: nowrap; }
+main { flex: 1; min-width: 0; padding: 1.5rem 2rem 3rem; }
+main img { max-width: 100%; }
+pre { padding: 1rem; overflow-x: auto; background: #f6f8fa; border-radius: 6px; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.9em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d0d7de; padding: 0.4rem 0.75rem; }
+.meta { color: #656d76; }
+.pager { display: flex; justify-content: space-between; margin-top: 3rem; padding-top: 1rem; border-top: 1px solid #d0d7de; }
+@media (max-width: 48rem) {
+  .layout { display: block; }
+  .steps { position: static; max-height: none; border-right: 0; border-bottom: 1px solid #d0d7de; }
+  main { padding: 1rem; }
+}
+"#;
+
+/// Generated pages of a codelab and the uploads they point at.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StaticSite {
+    /// `(path, contents)` of every generated file, relative to the site root.
+    pub files: Vec<(String, String)>,
+    /// Stored `/uploads/...` paths the pages link to. Each belongs at
+    /// [`site_upload_path`] in the bundle.
+    pub uploads: BTreeSet<String>,
+}
+
+/// Location inside the site of a stored `/uploads/...` file.
+pub fn site_upload_path(upload: &str) -> String {
+    format!(
+        "assets/uploads/{}",
+        upload.trim_start_matches(UPLOAD_PREFIX)
+    )
+}
+
+fn step_page(index: usize) -> String {
+    format!("step-{:02}.html", index + 1)
+}
+
+/// Renders the codelab overview, guide, materials, and steps as HTML pages.
+pub fn render_static_site(codelab: &Codelab, steps: &[Step], materials: &[Material]) -> StaticSite {
+    let mut uploads = BTreeSet::new();
+    let mut files = Vec::with_capacity(steps.len() + 2);
+
+    let mut overview = format!("<h1>{}</h1>\n", escape_html(&codelab.title));
+    let total_minutes: i32 = steps.iter().filter_map(|step| step.duration_minutes).sum();
+    let mut meta = escape_html(&codelab.author);
+    if total_minutes > 0 {
+        meta.push_str(&format!(" · {total_minutes} min"));
+    }
+    overview.push_str(&format!("<p class=\"meta\">{meta}</p>\n"));
+    overview.push_str(&format!("<p>{}</p>\n", escape_html(&codelab.description)));
+    if let Some(guide) = codelab
+        .guide_markdown
+        .as_deref()
+        .filter(|guide| !guide.trim().is_empty())
+    {
+        overview.push_str("<section class=\"guide\">\n");
+        overview.push_str(&render_markdown(guide, &mut uploads));
+        overview.push_str("</section>\n");
+    }
+    if !materials.is_empty() {
+        overview.push_str("<h2>Materials</h2>\n<ul>\n");
+        for material in materials {
+            let href = match (&material.link_url, &material.file_path) {
+                (Some(url), _) if material.material_type == "link" => Some(url.clone()),
+                (_, Some(path)) => rewrite_url(path, &mut uploads).or(Some(path.clone())),
+                (Some(url), None) => Some(url.clone()),
+                (None, None) => None,
+            };
+            let title = escape_html(&material.title);
+            match href {
+                Some(href) => overview.push_str(&format!(
+                    "<li><a href=\"{}\">{title}</a></li>\n",
+                    escape_html(&href)
+                )),
+                None => overview.push_str(&format!("<li>{title}</li>\n")),
+            }
+        }
+        overview.push_str("</ul>\n");
+    }
+    let start = if steps.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<nav class=\"pager\"><span></span><a href=\"{}\">Start →</a></nav>\n",
+            step_page(0)
+        )
+    };
+    files.push((
+        "index.html".to_string(),
+        page(
+            codelab,
+            steps,
+            None,
+            &codelab.title,
+            &format!("{overview}{start}"),
+        ),
+    ));
+
+    for (index, step) in steps.iter().enumerate() {
+        let mut body = format!("<h1>{}</h1>\n", escape_html(&step.title));
+        if let Some(minutes) = step.duration_minutes {
+            body.push_str(&format!("<p class=\"duration\">{minutes} min</p>\n"));
+        }
+        body.push_str(&render_markdown(&step.content_markdown, &mut uploads));
+        let previous = if index == 0 {
+            "<a href=\"index.html\">← Overview</a>".to_string()
+        } else {
+            format!("<a href=\"{}\">← Previous</a>", step_page(index - 1))
+        };
+        let next = if index + 1 < steps.len() {
+            format!("<a href=\"{}\">Next →</a>", step_page(index + 1))
+        } else {
+            "<span></span>".to_string()
+        };
+        body.push_str(&format!("<nav class=\"pager\">{previous}{next}</nav>\n"));
+        files.push((
+            step_page(index),
+            page(codelab, steps, Some(index), &step.title, &body),
+        ));
+    }
+
+    files.push(("assets/style.css".to_string(), stylesheet()));
+    StaticSite { files, uploads }
+}
+
+fn page(
+    codelab: &Codelab,
+    steps: &[Step],
+    current: Option<usize>,
+    title: &str,
+    body: &str,
+) -> String {
+    let mut nav = String::from("<nav class=\"steps\">\n<ol>\n");
+    for (index, step) in steps.iter().enumerate() {
+        let class = if current == Some(index) {
+            " class=\"current\""
+        } else {
+            ""
+        };
+        let duration = step
+            .duration_minutes
+            .map(|minutes| format!(" <span class=\"duration\">{minutes} min</span>"))
+            .unwrap_or_default();
+        nav.push_str(&format!(
+            "<li{class}><a href=\"{}\">{}</a>{duration}</li>\n",
+            step_page(index),
+            escape_html(&step.title)
+        ));
+    }
+    nav.push_str("</ol>\n</nav>\n");
+    let page_title = if current.is_some() {
+        format!("{} · {}", title, codelab.title)
+    } else {
+        codelab.title.clone()
+    };
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<link rel=\"stylesheet\" href=\"assets/style.css\">\n</head>\n<body>\n<header class=\"site-header\"><a href=\"index.html\">{}</a></header>\n<div class=\"layout\">\n{nav}<main>\n{body}</main>\n</div>\n</body>\n</html>\n",
+        escape_html(&page_title),
+        escape_html(&codelab.title),
+    )
+}
+
+fn stylesheet() -> String {
+    let themes = ThemeSet::load_defaults();
+    let highlight = themes
+        .themes
+        .get(HIGHLIGHT_THEME)
+        .and_then(|theme| css_for_theme_with_class_style(theme, CLASS_STYLE).ok())
+        .unwrap_or_default();
+    format!("{STYLESHEET}\n{highlight}")
+}
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// Renders markdown to HTML with highlighted code blocks, collecting the
+/// uploads it links to.
+fn render_markdown(markdown: &str, uploads: &mut BTreeSet<String>) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    let mut events = Vec::new();
+    let mut code: Option<(String, String)> = None;
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().unwrap_or("").to_string()
+                    }
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code = Some((language, String::new()));
+            }
+            Event::Text(text) if code.is_some() => {
+                if let Some((_, source)) = code.as_mut() {
+                    source.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, source)) = code.take() {
+                    events.push(Event::Html(highlight_code(&language, &source).into()));
+                }
+            }
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => events.push(Event::Start(Tag::Image {
+                link_type,
+                dest_url: rewrite_url(&dest_url, uploads)
+                    .map(CowStr::from)
+                    .unwrap_or(dest_url),
+                title,
+                id,
+            })),
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => events.push(Event::Start(Tag::Link {
+                link_type,
+                dest_url: rewrite_url(&dest_url, uploads)
+                    .map(CowStr::from)
+                    .unwrap_or(dest_url),
+                title,
+                id,
+            })),
+            Event::Html(raw) => events.push(Event::Html(rewrite_html_urls(&raw, uploads).into())),
+            Event::InlineHtml(raw) => {
+                events.push(Event::InlineHtml(rewrite_html_urls(&raw, uploads).into()))
+            }
+            other => events.push(other),
+        }
+    }
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    out
+}
+
+fn highlight_code(language: &str, source: &str) -> String {
+    let syntaxes = syntax_set();
+    let class = if language.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"language-{}\"", escape_html(language))
+    };
+    let syntax = Some(language)
+        .filter(|language| !language.is_empty())
+        .and_then(|language| syntaxes.find_syntax_by_token(language));
+    let Some(syntax) = syntax else {
+        return format!("<pre><code{class}>{}</code></pre>\n", escape_html(source));
+    };
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes, CLASS_STYLE);
+    for line in LinesWithEndings::from(source) {
+        if generator
+            .parse_html_for_line_which_includes_newline(line)
+            .is_err()
+        {
+            return format!("<pre><code{class}>{}</code></pre>\n", escape_html(source));
+        }
+    }
+    format!(
+        "<pre class=\"hl-code\"><code{class}>{}</code></pre>\n",
+        generator.finalize()
+    )
+}
+
+/// Site path for a root-relative link to an uploaded file, recording the
+/// upload so it can be copied into the bundle.
+fn rewrite_url(url: &str, uploads: &mut BTreeSet<String>) -> Option<String> {
+    if !url.starts_with(UPLOAD_PREFIX) {
+        return None;
+    }
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    if path.split('/').any(|part| part == "..") {
+        return None;
+    }
+    uploads.insert(path.to_string());
+    Some(site_upload_path(path))
+}
+
+/// Rewrites the `src` and `href` attributes of raw HTML that point at
+/// uploads, as [`rewrite_url`] does for markdown links and images.
+fn rewrite_html_urls(html: &str, uploads: &mut BTreeSet<String>) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut copied = 0;
+    let mut from = 0;
+    while let Some(offset) = html[from..].find('=') {
+        let equals = from + offset;
+        from = equals + 1;
+        let name_end = html[..equals].trim_end().len();
+        let name_start = html[..name_end]
+            .rfind(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .map_or(0, |position| position + 1);
+        let name = &html[name_start..name_end];
+        if !html[..name_start].ends_with(|c: char| c.is_ascii_whitespace())
+            || !(name.eq_ignore_ascii_case("src") || name.eq_ignore_ascii_case("href"))
+        {
+            continue;
+        }
+        let value_at = html.len() - html[from..].trim_start().len();
+        let (start, end) = match html[value_at..].chars().next() {
+            Some(quote @ ('"' | '\'')) => match html[value_at + 1..].find(quote) {
+                Some(length) => (value_at + 1, value_at + 1 + length),
+                None => break,
+            },
+            Some(_) => {
+                let length = html[value_at..]
+                    .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                    .unwrap_or(html.len() - value_at);
+                (value_at, value_at + length)
+            }
+            None => break,
+        };
+        if let Some(rewritten) = rewrite_url(&html[start..end], uploads) {
+            out.push_str(&html[copied..start]);
+            out.push_str(&rewritten);
+            copied = end;
+        }
+        from = end;
+    }
+    out.push_str(&html[copied..]);
+    out
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codelab() -> Codelab {
+        serde_json::from_value(serde_json::json!({
+            "id": "lab-1",
+            "title": "Rust <Basics>",
+            "description": "Learn Rust",
+            "author": "Jane",
+            "is_public": true,
+            "quiz_enabled": false,
+            "require_quiz": false,
+            "require_feedback": false,
+            "require_submission": false,
+            "guide_markdown": "Install ![logo](/uploads/logo.png) first.",
+            "created_at": null
+        }))
+        .unwrap()
+    }
+
+    fn step(number: i32, title: &str, content: &str, duration: Option<i32>) -> Step {
+        Step {
+            id: format!("s{number}"),
+            codelab_id: "lab-1".to_string(),
+            step_number: number,
+            title: title.to_string(),
+            content_markdown: content.to_string(),
+            duration_minutes: duration,
+        }
+    }
+
+    #[test]
+    fn site_has_an_index_and_linked_step_pages() {
+        let steps = [
+            step(
+                1,
+                "Setup",
+                "Run this:\n\n```rust\nfn main() {}\n```\n",
+                Some(5),
+            ),
+            step(2, "Next", "[slides](/uploads/materials/deck.pdf?x=1)", None),
+        ];
+        let materials = [Material {
+            id: "m1".to_string(),
+            codelab_id: "lab-1".to_string(),
+            title: "Starter".to_string(),
+            material_type: "file".to_string(),
+            link_url: None,
+            file_path: Some("/uploads/materials/starter.zip".to_string()),
+            created_at: None,
+        }];
+        let site = render_static_site(&codelab(), &steps, &materials);
+        let paths: Vec<&str> = site.files.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "index.html",
+                "step-01.html",
+                "step-02.html",
+                "assets/style.css"
+            ]
+        );
+        assert_eq!(
+            site.uploads.iter().map(String::as_str).collect::<Vec<_>>(),
+            [
+                "/uploads/logo.png",
+                "/uploads/materials/deck.pdf",
+                "/uploads/materials/starter.zip"
+            ]
+        );
+
+        let index = &site.files[0].1;
+        assert!(index.contains("<title>Rust &lt;Basics&gt;</title>"));
+        assert!(index.contains("src=\"assets/uploads/logo.png\""));
+        assert!(index.contains("href=\"assets/uploads/materials/starter.zip\""));
+        assert!(index.contains("Jane · 5 min"));
+
+        let first = &site.files[1].1;
+        assert!(first.contains("<li class=\"current\"><a href=\"step-01.html\">Setup</a>"));
+        assert!(first.contains("<pre class=\"hl-code\"><code class=\"language-rust\">"));
+        assert!(first.contains("<span class=\"hl-"));
+        assert!(first.contains("<a href=\"step-02.html\">Next →</a>"));
+        let second = &site.files[2].1;
+        assert!(second.contains("href=\"assets/uploads/materials/deck.pdf\""));
+        assert!(second.contains("<a href=\"step-01.html\">← Previous</a>"));
+        assert!(site.files[3].1.contains(".hl-"));
+    }
+
+    #[test]
+    fn unknown_languages_and_outside_links_are_left_alone() {
+        let mut uploads = BTreeSet::new();
+        let html = render_markdown(
+            "```nosuchlang\na < b\n```\n\n[site](https://example.com/uploads/x) [up](/uploads/../db)",
+            &mut uploads,
+        );
+        assert!(html.contains("<pre><code class=\"language-nosuchlang\">a &lt; b\n</code></pre>"));
+        assert!(html.contains("href=\"https://example.com/uploads/x\""));
+        assert!(html.contains("href=\"/uploads/../db\""));
+        assert!(uploads.is_empty());
+    }
+
+    #[test]
+    fn raw_html_upload_attributes_are_rewritten() {
+        let mut uploads = BTreeSet::new();
+        let html = render_markdown(
+            "<figure>\n<IMG alt=\"a=b\" SRC='/uploads/diagram.png'>\n</figure>\n\n\
+             See <a href=/uploads/notes.pdf>notes</a>, <img data-src=\"/uploads/lazy.png\"> \
+             and <a href=\"/uploads/../db\">this</a>.",
+            &mut uploads,
+        );
+        assert!(html.contains("<IMG alt=\"a=b\" SRC='assets/uploads/diagram.png'>"));
+        assert!(html.contains("<a href=assets/uploads/notes.pdf>"));
+        assert!(html.contains("data-src=\"/uploads/lazy.png\""));
+        assert!(html.contains("href=\"/uploads/../db\""));
+        assert_eq!(
+            uploads.iter().map(String::as_str).collect::<Vec<_>>(),
+            ["/uploads/diagram.png", "/uploads/notes.pdf"]
+        );
+    }
+}
@@ -0,0 +1,801 @@
+//! Shared editing rooms where several staff members write step markdown at once.
+//!
+//! Every codelab with connected editors has one room that holds the markdown
+//! of its steps in memory. Each step document counts revisions, and an
+//! operation sent against an older revision is transformed over the ones its
+//! author had not seen before it is applied. Rooms write edited steps back to
+//! the `steps` table on a fixed interval and when the last editor leaves,
+//! unless the step set changed in the database since the room loaded it. In
+//! that case the room reloads the stored steps and keeps unsaved edits of the
+//! steps whose stored markdown did not change.
+
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::revisions::{
+    record_baseline_revision, record_revision, REVISION_SOURCE_COLLABORATION,
+};
+use crate::utils::ot::{OtError, TextOperation};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Operations kept per step for transforming edits sent against old revisions.
+pub const AUTHORING_HISTORY_LIMIT: usize = 500;
+/// Longest step document an edit may produce, in characters.
+pub const AUTHORING_MAX_DOCUMENT_CHARS: usize = 50_000;
+/// Capacity of the broadcast channel shared by the editors of one room.
+const AUTHORING_CHANNEL_CAPACITY: usize = 1024;
+
+/// Reads how often rooms save edited steps from `AUTHORING_SAVE_INTERVAL_SECS`.
+///
+/// Defaults to five seconds; `0` or an invalid value also uses the default.
+pub fn authoring_save_interval_from_env() -> Duration {
+    let secs = std::env::var("AUTHORING_SAVE_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(5);
+    Duration::from_secs(secs)
+}
+
+/// Key of the broadcast channel in [`AppState::channels`] used by a room.
+///
+/// The suffix keeps authoring traffic out of the learner and cohort rooms.
+pub fn authoring_channel_key(codelab_id: &str) -> String {
+    format!("{codelab_id}#authoring")
+}
+
+/// Current markdown of one step as sent to editors.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthoringDocument {
+    /// Step identifier.
+    pub id: String,
+    /// Step title; titles are not edited through the room.
+    pub title: String,
+    /// Current markdown of the step.
+    pub content_markdown: String,
+    /// Number of operations applied since the room loaded the step.
+    pub revision: u64,
+}
+
+/// Staff member connected to a room, with their cursor when known.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthoringEditor {
+    /// Identifier of the websocket connection.
+    pub session_id: String,
+    /// Staff user identifier.
+    pub user_id: String,
+    /// Name shown to the other editors.
+    pub name: String,
+    /// Step the editor's cursor is in.
+    pub step_id: Option<String>,
+    /// Cursor position in UTF-16 code units.
+    pub position: Option<usize>,
+    /// End of the selection when text is selected.
+    pub selection_end: Option<usize>,
+}
+
+/// Reasons a room rejects an operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthoringError {
+    /// The step is not part of the room.
+    UnknownStep,
+    /// The operation is based on a revision the room no longer keeps.
+    StaleRevision,
+    /// The operation would make the step longer than allowed.
+    DocumentTooLong,
+    /// The operation does not fit the document.
+    Invalid(OtError),
+}
+
+impl fmt::Display for AuthoringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthoringError::UnknownStep => write!(f, "step not found"),
+            AuthoringError::StaleRevision => write!(f, "revision is no longer available"),
+            AuthoringError::DocumentTooLong => write!(
+                f,
+                "step content exceeds {AUTHORING_MAX_DOCUMENT_CHARS} characters"
+            ),
+            AuthoringError::Invalid(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for AuthoringError {}
+
+#[derive(Debug)]
+struct StepDocument {
+    title: String,
+    text: String,
+    /// Markdown stored in `steps` as far as the room knows.
+    base: String,
+    /// Markdown a save in progress is writing.
+    saving: Option<String>,
+    revision: u64,
+    /// Operations that produced revisions `revision - history.len() + 1..=revision`.
+    history: VecDeque<TextOperation>,
+    dirty: bool,
+}
+
+impl StepDocument {
+    fn loaded(title: String, text: String) -> Self {
+        Self {
+            title,
+            base: text.clone(),
+            text,
+            saving: None,
+            revision: 0,
+            history: VecDeque::new(),
+            dirty: false,
+        }
+    }
+
+    /// Whether the room holds edits that are not stored yet.
+    fn has_unsaved_edits(&self) -> bool {
+        self.dirty || self.saving.is_some()
+    }
+
+    /// Operations applied after `revision`, or `None` when some were dropped.
+    fn operations_since(&self, revision: u64) -> Option<impl Iterator<Item = &TextOperation>> {
+        let oldest = self.revision - self.history.len() as u64;
+        if revision > self.revision || revision < oldest {
+            return None;
+        }
+        Some(self.history.iter().skip((revision - oldest) as usize))
+    }
+}
+
+/// In-memory state of the editors and step documents of one codelab.
+#[derive(Debug, Default)]
+pub struct AuthoringRoom {
+    order: Vec<String>,
+    documents: HashMap<String, StepDocument>,
+    editors: Vec<AuthoringEditor>,
+    last_author: Option<String>,
+    /// `codelabs.steps_version` the documents were loaded at or last saved as.
+    steps_version: i32,
+}
+
+impl AuthoringRoom {
+    /// Creates a room from `(id, title, content_markdown)` rows in step order,
+    /// read at `steps_version` of the codelab.
+    pub fn new(steps: Vec<(String, String, String)>, steps_version: i32) -> Self {
+        let mut room = Self::default();
+        room.rebase_steps(steps, steps_version);
+        room
+    }
+
+    /// Replaces the step documents with steps read from the database after
+    /// the step set was rewritten outside the room.
+    ///
+    /// Unsaved edits are kept on top of the reloaded steps when the stored
+    /// markdown of their step did not change, and stay unsaved. Returns the
+    /// ids of the steps whose unsaved edits were dropped because the step
+    /// changed or was removed.
+    pub fn rebase_steps(
+        &mut self,
+        steps: Vec<(String, String, String)>,
+        steps_version: i32,
+    ) -> Vec<String> {
+        let mut previous = std::mem::take(&mut self.documents);
+        let previous_order = std::mem::take(&mut self.order);
+        let mut discarded = Vec::new();
+        self.steps_version = steps_version;
+        for (id, title, stored) in steps {
+            let doc = match previous.remove(&id) {
+                Some(mut doc) if doc.has_unsaved_edits() => {
+                    if doc.base == stored || doc.saving.as_deref() == Some(stored.as_str()) {
+                        doc.title = title;
+                        doc.dirty = doc.text != stored;
+                        doc.base = stored;
+                        doc.saving = None;
+                        doc
+                    } else {
+                        discarded.push(id.clone());
+                        StepDocument::loaded(title, stored)
+                    }
+                }
+                _ => StepDocument::loaded(title, stored),
+            };
+            self.order.push(id.clone());
+            self.documents.insert(id, doc);
+        }
+        discarded.extend(
+            previous_order
+                .into_iter()
+                .filter(|id| previous.get(id).is_some_and(|doc| doc.has_unsaved_edits())),
+        );
+        discarded
+    }
+
+    /// Current documents in step order.
+    pub fn snapshot(&self) -> Vec<AuthoringDocument> {
+        self.order
+            .iter()
+            .filter_map(|id| {
+                self.documents.get(id).map(|doc| AuthoringDocument {
+                    id: id.clone(),
+                    title: doc.title.clone(),
+                    content_markdown: doc.text.clone(),
+                    revision: doc.revision,
+                })
+            })
+            .collect()
+    }
+
+    /// Connected editors in the order they joined.
+    pub fn editors(&self) -> &[AuthoringEditor] {
+        &self.editors
+    }
+
+    /// Adds an editor connection.
+    pub fn join(&mut self, editor: AuthoringEditor) {
+        self.editors.push(editor);
+    }
+
+    /// Removes an editor connection.
+    pub fn leave(&mut self, session_id: &str) {
+        self.editors
+            .retain(|editor| editor.session_id != session_id);
+    }
+
+    /// Returns whether some step has edits that were not saved yet.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.documents.values().any(|doc| doc.has_unsaved_edits())
+    }
+
+    /// Applies an operation `user_id` made against `revision` of a step.
+    ///
+    /// Returns the operation as applied, after transforming it over the
+    /// operations its author had not seen, and the step's new revision.
+    pub fn apply_operation(
+        &mut self,
+        step_id: &str,
+        revision: u64,
+        operation: TextOperation,
+        user_id: &str,
+    ) -> Result<(TextOperation, u64), AuthoringError> {
+        let doc = self
+            .documents
+            .get_mut(step_id)
+            .ok_or(AuthoringError::UnknownStep)?;
+        let mut operation = operation;
+        for concurrent in doc
+            .operations_since(revision)
+            .ok_or(AuthoringError::StaleRevision)?
+        {
+            operation = TextOperation::transform(&operation, concurrent)
+                .map_err(AuthoringError::Invalid)?
+                .0;
+        }
+        let text = operation
+            .apply(&doc.text)
+            .map_err(AuthoringError::Invalid)?;
+        if text.chars().count() > AUTHORING_MAX_DOCUMENT_CHARS {
+            return Err(AuthoringError::DocumentTooLong);
+        }
+        doc.text = text;
+        doc.revision += 1;
+        doc.history.push_back(operation.clone());
+        if doc.history.len() > AUTHORING_HISTORY_LIMIT {
+            doc.history.pop_front();
+        }
+        doc.dirty = true;
+        self.last_author = Some(user_id.to_string());
+
+        // Cursors of the other editors in this step move with the edit.
+        for editor in &mut self.editors {
+            if editor.step_id.as_deref() == Some(step_id) {
+                editor.position = editor.position.map(|p| operation.transform_position(p));
+                editor.selection_end = editor
+                    .selection_end
+                    .map(|p| operation.transform_position(p));
+            }
+        }
+        Ok((operation, doc.revision))
+    }
+
+    /// Records where an editor's cursor is, given against `revision` of the
+    /// step, and returns the editor with the position at the current revision.
+    pub fn move_cursor(
+        &mut self,
+        session_id: &str,
+        step_id: &str,
+        revision: u64,
+        position: usize,
+        selection_end: Option<usize>,
+    ) -> Result<AuthoringEditor, AuthoringError> {
+        let doc = self
+            .documents
+            .get(step_id)
+            .ok_or(AuthoringError::UnknownStep)?;
+        let (mut position, mut selection_end) = (position, selection_end);
+        for concurrent in doc
+            .operations_since(revision)
+            .ok_or(AuthoringError::StaleRevision)?
+        {
+            position = concurrent.transform_position(position);
+            selection_end = selection_end.map(|p| concurrent.transform_position(p));
+        }
+        let editor = self
+            .editors
+            .iter_mut()
+            .find(|editor| editor.session_id == session_id)
+            .ok_or(AuthoringError::UnknownStep)?;
+        editor.step_id = Some(step_id.to_string());
+        editor.position = Some(position);
+        editor.selection_end = selection_end;
+        Ok(editor.clone())
+    }
+
+    /// Step set version of the codelab the documents are based on.
+    pub fn steps_version(&self) -> i32 {
+        self.steps_version
+    }
+
+    /// Marks every edited step as being saved and returns their id and
+    /// markdown, with the staff user who made the latest edit.
+    fn take_unsaved(&mut self) -> (Vec<(String, String)>, Option<String>) {
+        let changed = self
+            .documents
+            .iter_mut()
+            .filter(|(_, doc)| doc.dirty)
+            .map(|(id, doc)| {
+                doc.dirty = false;
+                doc.saving = Some(doc.text.clone());
+                (id.clone(), doc.text.clone())
+            })
+            .collect();
+        (changed, self.last_author.clone())
+    }
+
+    /// Records that the markdown of `saved` steps is now stored.
+    fn finish_save(&mut self, saved: &[(String, String)]) {
+        for (id, content) in saved {
+            if let Some(doc) = self.documents.get_mut(id) {
+                if doc.saving.as_deref() == Some(content.as_str()) {
+                    doc.saving = None;
+                    doc.base = content.clone();
+                }
+            }
+        }
+    }
+
+    /// Marks steps as edited again after saving them did not go through.
+    fn restore_unsaved(&mut self, step_ids: &[String]) {
+        for id in step_ids {
+            if let Some(doc) = self.documents.get_mut(id) {
+                if doc.saving.take().is_some() {
+                    doc.dirty = true;
+                }
+            }
+        }
+    }
+
+    fn remove_step(&mut self, step_id: &str) {
+        self.documents.remove(step_id);
+        self.order.retain(|id| id != step_id);
+    }
+}
+
+/// Returns the broadcast channel shared by the editors of a codelab.
+pub fn authoring_channel(state: &AppState, codelab_id: &str) -> broadcast::Sender<String> {
+    state
+        .channels
+        .entry(authoring_channel_key(codelab_id))
+        .or_insert_with(|| broadcast::channel(AUTHORING_CHANNEL_CAPACITY).0)
+        .clone()
+}
+
+/// Sends an event to every editor of a codelab, if any are connected.
+pub fn broadcast_authoring(state: &AppState, codelab_id: &str, payload: &str) {
+    if let Some(channel) = state.channels.get(&authoring_channel_key(codelab_id)) {
+        let _ = channel.send(payload.to_string());
+    }
+}
+
+/// Payload telling editors the current state of every step.
+pub fn snapshot_payload(room: &AuthoringRoom) -> String {
+    serde_json::json!({
+        "type": "authoring_snapshot",
+        "steps": room.snapshot(),
+    })
+    .to_string()
+}
+
+/// Payload naming the steps whose unsaved edits were dropped by a reload.
+pub fn conflict_payload(step_ids: &[String]) -> String {
+    serde_json::json!({
+        "type": "authoring_conflict",
+        "step_ids": step_ids,
+    })
+    .to_string()
+}
+
+/// Payload listing the connected editors.
+pub fn presence_payload(room: &AuthoringRoom) -> String {
+    serde_json::json!({
+        "type": "authoring_presence",
+        "editors": room.editors(),
+    })
+    .to_string()
+}
+
+/// Loads the steps of a codelab with the step set version they belong to.
+///
+/// The version is read first, so a concurrent rewrite can only make it older
+/// than the steps and the next save reloads instead of overwriting.
+async fn load_steps(
+    state: &AppState,
+    codelab_id: &str,
+) -> Result<(Vec<(String, String, String)>, i32), sqlx::Error> {
+    let steps_version: i32 =
+        sqlx::query_scalar(&state.q("SELECT steps_version FROM codelabs WHERE id = ?"))
+            .bind(codelab_id)
+            .fetch_optional(&state.pool)
+            .await?
+            .unwrap_or_default();
+    let steps = sqlx::query_as(&state.q(
+        "SELECT id, title, content_markdown FROM steps WHERE codelab_id = ? ORDER BY step_number",
+    ))
+    .bind(codelab_id)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok((steps, steps_version))
+}
+
+/// Returns the room of a codelab, loading its steps and starting its save
+/// loop when nobody is editing the codelab yet.
+pub async fn open_authoring_room(
+    state: &Arc<AppState>,
+    codelab_id: &str,
+) -> Result<Arc<Mutex<AuthoringRoom>>, sqlx::Error> {
+    if let Some(room) = state.authoring_rooms.get(codelab_id) {
+        return Ok(room.clone());
+    }
+    let (steps, steps_version) = load_steps(state, codelab_id).await?;
+    let room = match state.authoring_rooms.entry(codelab_id.to_string()) {
+        dashmap::mapref::entry::Entry::Occupied(entry) => entry.get().clone(),
+        dashmap::mapref::entry::Entry::Vacant(entry) => {
+            let room = Arc::new(Mutex::new(AuthoringRoom::new(steps, steps_version)));
+            entry.insert(room.clone());
+            tokio::spawn(run_room_saver(
+                state.clone(),
+                codelab_id.to_string(),
+                room.clone(),
+            ));
+            room
+        }
+    };
+    Ok(room)
+}
+
+/// Writes edited steps of a room to the database as one new revision and
+/// tells the editors the new step set version.
+///
+/// Steps removed from the database in the meantime are dropped from the room.
+/// When the step set version moved since the room loaded its steps, nothing
+/// is written; the room reloads the stored steps, keeps the edits it can, and
+/// tells editors which steps lost theirs.
+pub async fn save_authoring_room(
+    state: &AppState,
+    codelab_id: &str,
+    room: &Mutex<AuthoringRoom>,
+) -> Result<(), sqlx::Error> {
+    let (changed, author, base_version) = {
+        let mut room = room.lock().expect("authoring room lock");
+        let (changed, author) = room.take_unsaved();
+        (changed, author, room.steps_version())
+    };
+    if changed.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<String> = changed.iter().map(|(id, _)| id.clone()).collect();
+    match write_steps(state, codelab_id, base_version, &changed, author.as_deref()).await {
+        Ok(StepsWrite::Conflict) => {
+            tracing::warn!(
+                "step set of codelab {codelab_id} changed outside its authoring room; reloading"
+            );
+            let loaded = load_steps(state, codelab_id).await;
+            let mut room = room.lock().expect("authoring room lock");
+            let (steps, steps_version) = match loaded {
+                Ok(loaded) => loaded,
+                Err(err) => {
+                    room.restore_unsaved(&ids);
+                    return Err(err);
+                }
+            };
+            if room.steps_version() == base_version {
+                let discarded = room.rebase_steps(steps, steps_version);
+                send_reloaded(state, codelab_id, &room, &discarded);
+            } else {
+                // A reload in the meantime already rebased the room.
+                room.restore_unsaved(&ids);
+            }
+            Ok(())
+        }
+        Ok(StepsWrite::Written { missing, saved }) => {
+            let mut room = room.lock().expect("authoring room lock");
+            for id in &missing {
+                room.remove_step(id);
+            }
+            if saved.is_some() {
+                room.finish_save(&changed);
+            }
+            if !missing.is_empty() {
+                let _ = authoring_channel(state, codelab_id).send(snapshot_payload(&room));
+            }
+            if let Some((revision_id, steps_version)) = saved {
+                // A reload in the meantime already moved the room further.
+                if room.steps_version() == base_version {
+                    room.steps_version = steps_version;
+                }
+                let payload = serde_json::json!({
+                    "type": "authoring_saved",
+                    "revision_id": revision_id,
+                    "steps_version": steps_version,
+                })
+                .to_string();
+                let _ = authoring_channel(state, codelab_id).send(payload);
+            }
+            Ok(())
+        }
+        Err(err) => {
+            room.lock()
+                .expect("authoring room lock")
+                .restore_unsaved(&ids);
+            Err(err)
+        }
+    }
+}
+
+/// Result of writing a room's edited steps.
+enum StepsWrite {
+    /// The step set version no longer matches the room; nothing was written.
+    Conflict,
+    /// The steps were written.
+    Written {
+        /// Edited steps that no longer exist.
+        missing: Vec<String>,
+        /// Revision identifier and new step set version, when any step was
+        /// updated.
+        saved: Option<(String, i32)>,
+    },
+}
+
+/// Updates the markdown of `changed` steps and records a revision, provided
+/// the codelab is still at `base_version` of its step set.
+async fn write_steps(
+    state: &AppState,
+    codelab_id: &str,
+    base_version: i32,
+    changed: &[(String, String)],
+    author: Option<&str>,
+) -> Result<StepsWrite, sqlx::Error> {
+    let mut tx = state.pool.begin().await?;
+    // Claiming the next version first also locks the codelab row until commit.
+    let claimed = sqlx::query(&state.q(
+        "UPDATE codelabs SET steps_version = steps_version + 1 WHERE id = ? AND steps_version = ?",
+    ))
+    .bind(codelab_id)
+    .bind(base_version)
+    .execute(&mut *tx)
+    .await?;
+    if claimed.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(StepsWrite::Conflict);
+    }
+    record_baseline_revision(state, &mut tx, codelab_id).await?;
+    let mut missing = Vec::new();
+    for (step_id, content) in changed {
+        let updated = sqlx::query(
+            &state.q("UPDATE steps SET content_markdown = ? WHERE id = ? AND codelab_id = ?"),
+        )
+        .bind(content)
+        .bind(step_id)
+        .bind(codelab_id)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            missing.push(step_id.clone());
+        }
+    }
+    if missing.len() == changed.len() {
+        tx.rollback().await?;
+        return Ok(StepsWrite::Written {
+            missing,
+            saved: None,
+        });
+    }
+    let revision_id = record_revision(
+        state,
+        &mut tx,
+        codelab_id,
+        author,
+        REVISION_SOURCE_COLLABORATION,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(StepsWrite::Written {
+        missing,
+        saved: Some((revision_id, base_version + 1)),
+    })
+}
+
+/// Saves a room on the configured interval and closes it once no editor is
+/// connected and every edit is saved.
+async fn run_room_saver(state: Arc<AppState>, codelab_id: String, room: Arc<Mutex<AuthoringRoom>>) {
+    let mut ticker = tokio::time::interval(state.authoring_save_interval);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        if let Err(err) = save_authoring_room(&state, &codelab_id, &room).await {
+            tracing::warn!("saving authoring room for codelab {codelab_id} failed: {err}");
+        }
+        let closed = state
+            .authoring_rooms
+            .remove_if(&codelab_id, |_, current| {
+                if !Arc::ptr_eq(current, &room) {
+                    return false;
+                }
+                let room = current.lock().expect("authoring room lock");
+                room.editors().is_empty() && !room.has_unsaved_changes()
+            })
+            .is_some();
+        if closed || !state.authoring_rooms.contains_key(&codelab_id) {
+            state
+                .channels
+                .remove_if(&authoring_channel_key(&codelab_id), |_, channel| {
+                    channel.receiver_count() == 0
+                });
+            return;
+        }
+    }
+}
+
+/// Reloads the steps of an open room after the step set was rewritten
+/// through the REST API and sends editors the new documents.
+pub async fn reload_authoring_room(state: &AppState, codelab_id: &str) -> Result<(), sqlx::Error> {
+    let Some(room) = state
+        .authoring_rooms
+        .get(codelab_id)
+        .map(|room| room.clone())
+    else {
+        return Ok(());
+    };
+    let (steps, steps_version) = load_steps(state, codelab_id).await?;
+    let mut room = room.lock().expect("authoring room lock");
+    let discarded = room.rebase_steps(steps, steps_version);
+    send_reloaded(state, codelab_id, &room, &discarded);
+    Ok(())
+}
+
+/// Sends editors the reloaded documents, preceded by the steps that lost
+/// unsaved edits when there are any.
+fn send_reloaded(state: &AppState, codelab_id: &str, room: &AuthoringRoom, discarded: &[String]) {
+    let channel = authoring_channel(state, codelab_id);
+    if !discarded.is_empty() {
+        tracing::warn!(
+            "dropped unsaved edits of {} step(s) in codelab {codelab_id} after a reload",
+            discarded.len()
+        );
+        let _ = channel.send(conflict_payload(discarded));
+    }
+    let _ = channel.send(snapshot_payload(room));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(session_id: &str) -> AuthoringEditor {
+        AuthoringEditor {
+            session_id: session_id.to_string(),
+            user_id: format!("user-{session_id}"),
+            name: session_id.to_string(),
+            step_id: None,
+            position: None,
+            selection_end: None,
+        }
+    }
+
+    #[test]
+    fn concurrent_operations_are_transformed_and_cursors_follow() {
+        let mut room = AuthoringRoom::new(
+            vec![(
+                "s1".to_string(),
+                "Intro".to_string(),
+                "Hello world".to_string(),
+            )],
+            1,
+        );
+        room.join(editor("a"));
+        room.join(editor("b"));
+        room.move_cursor("b", "s1", 0, 11, None).unwrap();
+
+        // Both editors start from revision 0.
+        let first = TextOperation::new().retain(5).insert(",").retain(6);
+        let second = TextOperation::new().retain(11).insert("!");
+        room.apply_operation("s1", 0, first, "user-a").unwrap();
+        let (applied, revision) = room.apply_operation("s1", 0, second, "user-b").unwrap();
+        assert_eq!(revision, 2);
+        assert_eq!(applied, TextOperation::new().retain(12).insert("!"));
+        assert_eq!(room.snapshot()[0].content_markdown, "Hello, world!");
+        assert!(room.has_unsaved_changes());
+
+        let moved = room.move_cursor("a", "s1", 1, 6, Some(7)).unwrap();
+        assert_eq!((moved.position, moved.selection_end), (Some(6), Some(7)));
+        assert_eq!(room.editors()[1].position, Some(13));
+
+        let (changed, author) = room.take_unsaved();
+        assert_eq!(
+            changed,
+            vec![("s1".to_string(), "Hello, world!".to_string())]
+        );
+        assert_eq!(author.as_deref(), Some("user-b"));
+        // Edits count as unsaved until the save finishes.
+        assert!(room.has_unsaved_changes());
+        room.finish_save(&changed);
+        assert!(!room.has_unsaved_changes());
+    }
+
+    #[test]
+    fn reloads_keep_edits_of_steps_that_did_not_change() {
+        let step = |id: &str, text: &str| (id.to_string(), id.to_string(), text.to_string());
+        let mut room = AuthoringRoom::new(
+            vec![step("s1", "one"), step("s2", "two"), step("s3", "three")],
+            1,
+        );
+        for (id, len) in [("s1", 3), ("s2", 3), ("s3", 5)] {
+            let insert = TextOperation::new().retain(len).insert("!");
+            room.apply_operation(id, 0, insert, "u").unwrap();
+        }
+        // A save of s2 is in flight and already reached the database.
+        let (changed, _) = room.take_unsaved();
+        assert_eq!(changed.len(), 3);
+        room.restore_unsaved(&["s1".to_string(), "s3".to_string()]);
+
+        let discarded = room.rebase_steps(vec![step("s1", "uno"), step("s2", "two!")], 2);
+        assert_eq!(discarded, vec!["s1".to_string(), "s3".to_string()]);
+        let texts: Vec<String> = room
+            .snapshot()
+            .into_iter()
+            .map(|doc| doc.content_markdown)
+            .collect();
+        assert_eq!(texts, vec!["uno".to_string(), "two!".to_string()]);
+        assert!(!room.has_unsaved_changes());
+        assert_eq!(room.steps_version(), 2);
+    }
+
+    #[test]
+    fn operations_against_unknown_steps_or_revisions_are_rejected() {
+        let mut room = AuthoringRoom::new(
+            vec![("s1".to_string(), "Intro".to_string(), "abc".to_string())],
+            1,
+        );
+        let insert = TextOperation::new().retain(3).insert("d");
+        assert_eq!(
+            room.apply_operation("missing", 0, insert.clone(), "u"),
+            Err(AuthoringError::UnknownStep)
+        );
+        assert_eq!(
+            room.apply_operation("s1", 4, insert.clone(), "u"),
+            Err(AuthoringError::StaleRevision)
+        );
+        assert!(matches!(
+            room.apply_operation("s1", 0, TextOperation::new().retain(1), "u"),
+            Err(AuthoringError::Invalid(_))
+        ));
+        let too_long = TextOperation::new()
+            .retain(3)
+            .insert(&"x".repeat(AUTHORING_MAX_DOCUMENT_CHARS));
+        assert_eq!(
+            room.apply_operation("s1", 0, too_long, "u"),
+            Err(AuthoringError::DocumentTooLong)
+        );
+        assert!(!room.has_unsaved_changes());
+    }
+}
@@ -1,4 +1,7 @@
+use crate::middleware::auth::StaffRole;
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Runtime configuration loaded once during server startup.
 #[derive(Debug, Clone)]
@@ -32,6 +35,181 @@ impl AppConfig {
     }
 }
 
+/// Issuer profile published in Open Badges credentials.
+#[derive(Debug, Clone)]
+pub struct CredentialIssuerConfig {
+    /// Display name of the issuing organization.
+    pub name: String,
+    /// Public origin used for issuer and achievement URLs, without a trailing slash.
+    ///
+    /// Required for Open Badges credentials, whose signed issuer identity must
+    /// not come from the request's `Host` header; they are not issued when unset.
+    pub public_url: Option<String>,
+}
+
+impl CredentialIssuerConfig {
+    /// Builds [`CredentialIssuerConfig`] from the process environment.
+    ///
+    /// `CERTIFICATE_ISSUER_NAME` defaults to `"Open Codelabs"`. `PUBLIC_URL`
+    /// is optional and should be the externally reachable origin.
+    pub fn from_env() -> Self {
+        let name = std::env::var("CERTIFICATE_ISSUER_NAME")
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| "Open Codelabs".to_string());
+        let public_url = std::env::var("PUBLIC_URL")
+            .ok()
+            .map(|value| value.trim().trim_end_matches('/').to_string())
+            .filter(|value| !value.is_empty());
+
+        Self { name, public_url }
+    }
+}
+
+/// Retention periods for data that would otherwise accumulate forever.
+///
+/// Each period is in days; `None` keeps that data class indefinitely.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Days chat messages are kept after a codelab ends.
+    pub chat_days: Option<u32>,
+    /// Days AI conversations and threads are kept after a codelab ends.
+    pub ai_days: Option<u32>,
+    /// Days submissions and their files are kept after a codelab ends.
+    pub submission_days: Option<u32>,
+    /// Days audit log entries are kept after they are written.
+    pub audit_days: Option<u32>,
+    /// Hours between background retention passes.
+    pub interval_hours: u64,
+}
+
+impl RetentionConfig {
+    /// Builds [`RetentionConfig`] from the process environment.
+    ///
+    /// `RETENTION_CHAT_DAYS`, `RETENTION_AI_DAYS`, `RETENTION_SUBMISSION_DAYS`,
+    /// and `RETENTION_AUDIT_DAYS` are unset by default; `0` or an invalid value
+    /// also keeps data forever. `RETENTION_INTERVAL_HOURS` defaults to `24`.
+    pub fn from_env() -> Self {
+        let days = |key: &str| {
+            let value = std::env::var(key).ok()?;
+            let parsed = value.trim().parse::<u32>().ok();
+            if parsed.is_none() && !value.trim().is_empty() {
+                tracing::warn!("{key} ignored: not a number of days {:?}", value);
+            }
+            parsed.filter(|days| *days > 0)
+        };
+        let interval_hours = std::env::var("RETENTION_INTERVAL_HOURS")
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .filter(|hours| *hours > 0)
+            .unwrap_or(24);
+
+        Self {
+            chat_days: days("RETENTION_CHAT_DAYS"),
+            ai_days: days("RETENTION_AI_DAYS"),
+            submission_days: days("RETENTION_SUBMISSION_DAYS"),
+            audit_days: days("RETENTION_AUDIT_DAYS"),
+            interval_hours,
+        }
+    }
+
+    /// Returns `true` when at least one data class has a retention period.
+    pub fn is_enabled(&self) -> bool {
+        self.chat_days.is_some()
+            || self.ai_days.is_some()
+            || self.submission_days.is_some()
+            || self.audit_days.is_some()
+    }
+
+    /// Time between background retention passes.
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_hours * 60 * 60)
+    }
+}
+
+/// OpenID Connect settings for staff single sign-on.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Issuer URL; discovery is read from `{issuer}/.well-known/openid-configuration`.
+    pub issuer_url: String,
+    /// OAuth client identifier registered with the identity provider.
+    pub client_id: String,
+    /// Client secret for confidential clients; public clients rely on PKCE alone.
+    pub client_secret: Option<String>,
+    /// Callback URL registered with the provider.
+    ///
+    /// When unset, handlers use `PUBLIC_URL` or the request origin followed by
+    /// `/api/auth/oidc/callback`.
+    pub redirect_url: Option<String>,
+    /// Space-separated scopes requested at authorization time.
+    pub scopes: String,
+    /// ID token claim listing the user's groups.
+    pub groups_claim: String,
+    /// Group-to-role mapping evaluated against the groups claim.
+    pub role_mappings: Vec<(String, StaffRole)>,
+    /// Role granted when no group matches; `None` rejects unmapped users.
+    pub default_role: Option<StaffRole>,
+    /// Label shown on the login button.
+    pub provider_name: String,
+}
+
+impl OidcConfig {
+    /// Builds [`OidcConfig`] from the process environment.
+    ///
+    /// Returns `None` unless both `OIDC_ISSUER_URL` and `OIDC_CLIENT_ID` are
+    /// set. `OIDC_ROLE_MAPPING` is a comma-separated `group=role` list such as
+    /// `codelab-admins=owner,mentors=ta`.
+    pub fn from_env() -> Option<Self> {
+        let read = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let issuer_url = read("OIDC_ISSUER_URL")?.trim_end_matches('/').to_string();
+        let client_id = read("OIDC_CLIENT_ID")?;
+        let default_role = read("OIDC_DEFAULT_ROLE").and_then(|value| {
+            let role = StaffRole::parse(&value);
+            if role.is_none() {
+                tracing::warn!("OIDC_DEFAULT_ROLE ignored: unknown role {:?}", value);
+            }
+            role
+        });
+
+        Some(Self {
+            issuer_url,
+            client_id,
+            client_secret: read("OIDC_CLIENT_SECRET"),
+            redirect_url: read("OIDC_REDIRECT_URL"),
+            scopes: read("OIDC_SCOPES").unwrap_or_else(|| "openid profile email".to_string()),
+            groups_claim: read("OIDC_GROUPS_CLAIM").unwrap_or_else(|| "groups".to_string()),
+            role_mappings: parse_role_mappings(&read("OIDC_ROLE_MAPPING").unwrap_or_default()),
+            default_role,
+            provider_name: read("OIDC_PROVIDER_NAME").unwrap_or_else(|| "SSO".to_string()),
+        })
+    }
+}
+
+/// Parses a comma-separated `group=role` list, skipping malformed entries.
+pub fn parse_role_mappings(value: &str) -> Vec<(String, StaffRole)> {
+    value
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .filter_map(|entry| {
+            let parsed = entry.split_once('=').and_then(|(group, role)| {
+                let group = group.trim();
+                let role = StaffRole::parse(role.trim())?;
+                (!group.is_empty()).then(|| (group.to_string(), role))
+            });
+            if parsed.is_none() {
+                tracing::warn!("OIDC_ROLE_MAPPING entry ignored: {:?}", entry.trim());
+            }
+            parsed
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,6 +285,100 @@ mod tests {
         assert!(err.to_string().contains("ADMIN_PW must be set"));
     }
 
+    #[test]
+    fn credential_issuer_from_env_trims_and_defaults() {
+        let _lock = ENV_TEST_LOCK.lock().expect("env test lock");
+        let _name_guard = EnvRestore::new("CERTIFICATE_ISSUER_NAME");
+        let _url_guard = EnvRestore::new("PUBLIC_URL");
+
+        std::env::remove_var("CERTIFICATE_ISSUER_NAME");
+        std::env::set_var("PUBLIC_URL", " https://codelabs.example.com/ ");
+        let cfg = CredentialIssuerConfig::from_env();
+        assert_eq!(cfg.name, "Open Codelabs");
+        assert_eq!(
+            cfg.public_url.as_deref(),
+            Some("https://codelabs.example.com")
+        );
+
+        std::env::set_var("CERTIFICATE_ISSUER_NAME", "GDG Seoul");
+        std::env::set_var("PUBLIC_URL", "");
+        let cfg = CredentialIssuerConfig::from_env();
+        assert_eq!(cfg.name, "GDG Seoul");
+        assert!(cfg.public_url.is_none());
+    }
+
+    #[test]
+    fn oidc_from_env_requires_issuer_and_client() {
+        let _lock = ENV_TEST_LOCK.lock().expect("env test lock");
+        let guards: Vec<EnvRestore> = [
+            "OIDC_ISSUER_URL",
+            "OIDC_CLIENT_ID",
+            "OIDC_ROLE_MAPPING",
+            "OIDC_DEFAULT_ROLE",
+            "OIDC_SCOPES",
+        ]
+        .into_iter()
+        .map(EnvRestore::new)
+        .collect();
+
+        std::env::remove_var("OIDC_ISSUER_URL");
+        std::env::set_var("OIDC_CLIENT_ID", "codelabs");
+        assert!(OidcConfig::from_env().is_none());
+
+        std::env::set_var("OIDC_ISSUER_URL", "https://idp.example.com/realms/acme/");
+        std::env::set_var(
+            "OIDC_ROLE_MAPPING",
+            "admins=owner, mentors = ta,bogus,x=admin",
+        );
+        std::env::set_var("OIDC_DEFAULT_ROLE", "nobody");
+        std::env::remove_var("OIDC_SCOPES");
+        let cfg = OidcConfig::from_env().expect("oidc config");
+        assert_eq!(cfg.issuer_url, "https://idp.example.com/realms/acme");
+        assert_eq!(cfg.scopes, "openid profile email");
+        assert_eq!(cfg.groups_claim, "groups");
+        assert_eq!(
+            cfg.role_mappings,
+            vec![
+                ("admins".to_string(), StaffRole::Owner),
+                ("mentors".to_string(), StaffRole::Ta)
+            ]
+        );
+        assert!(cfg.default_role.is_none());
+        drop(guards);
+    }
+
+    #[test]
+    fn retention_from_env_ignores_zero_and_invalid_periods() {
+        let _lock = ENV_TEST_LOCK.lock().expect("env test lock");
+        let guards: Vec<EnvRestore> = [
+            "RETENTION_CHAT_DAYS",
+            "RETENTION_AI_DAYS",
+            "RETENTION_SUBMISSION_DAYS",
+            "RETENTION_AUDIT_DAYS",
+            "RETENTION_INTERVAL_HOURS",
+        ]
+        .into_iter()
+        .map(EnvRestore::new)
+        .collect();
+
+        std::env::set_var("RETENTION_CHAT_DAYS", " 90 ");
+        std::env::set_var("RETENTION_AI_DAYS", "0");
+        std::env::set_var("RETENTION_SUBMISSION_DAYS", "soon");
+        std::env::remove_var("RETENTION_AUDIT_DAYS");
+        std::env::remove_var("RETENTION_INTERVAL_HOURS");
+        let cfg = RetentionConfig::from_env();
+        assert_eq!(cfg.chat_days, Some(90));
+        assert!(cfg.ai_days.is_none());
+        assert!(cfg.submission_days.is_none());
+        assert!(cfg.audit_days.is_none());
+        assert!(cfg.is_enabled());
+        assert_eq!(cfg.interval(), Duration::from_secs(24 * 60 * 60));
+
+        std::env::remove_var("RETENTION_CHAT_DAYS");
+        assert!(!RetentionConfig::from_env().is_enabled());
+        drop(guards);
+    }
+
     #[test]
     fn env_restore_restores_previous_value() {
         let _lock = ENV_TEST_LOCK.lock().expect("env test lock");
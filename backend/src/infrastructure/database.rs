@@ -4,13 +4,18 @@ use axum::extract::ws::Message;
 use dashmap::DashMap;
 use sqlx::migrate::Migrator;
 use sqlx::AnyPool;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::broadcast;
 
-use crate::infrastructure::AppConfig;
+use crate::domain::services::certificate_pdf::CertificateTemplate;
+use crate::infrastructure::authoring::{authoring_save_interval_from_env, AuthoringRoom};
+use crate::infrastructure::{AppConfig, CredentialIssuerConfig, OidcConfig, RetentionConfig};
 use crate::middleware::auth::AuthConfig;
 use crate::middleware::rate_limit::{RateLimitConfig, RateLimiter};
 use crate::middleware::security::SecurityHeadersConfig;
+use crate::utils::crypto::SecretKeyring;
+use crate::utils::signing::DocumentSigner;
 
 /// Supported database backends for SQL placeholder rewriting and setup.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,6 +43,18 @@ pub struct AppState {
     pub admin_pw: String,
     /// Session and cookie configuration used by auth helpers.
     pub auth: AuthConfig,
+    /// Ed25519 signer used to issue and verify certificates.
+    pub certificate_signer: Arc<DocumentSigner>,
+    /// Keys derived from the auth secrets that encrypt stored secrets.
+    pub secret_keyring: SecretKeyring,
+    /// Issuer profile used when exporting Open Badges credentials.
+    pub credential_issuer: CredentialIssuerConfig,
+    /// Layout and branding for server-rendered certificate PDFs.
+    pub certificate_template: Arc<CertificateTemplate>,
+    /// OpenID Connect provider used for staff single sign-on, when configured.
+    pub oidc: Option<OidcConfig>,
+    /// Retention periods applied by the background pruning job.
+    pub retention: RetentionConfig,
     /// Tunable per-bucket rate-limit settings.
     pub rate_limit_config: RateLimitConfig,
     /// In-memory sliding-window limiter shared by incoming requests.
@@ -60,6 +77,10 @@ pub struct AppState {
     pub active_screen_shares: Arc<DashMap<String, bool>>,
     /// Whether a specific attendee is actively sharing their screen.
     pub attendee_sharing: Arc<DashMap<(String, String), bool>>,
+    /// Shared step editing rooms keyed by codelab id.
+    pub authoring_rooms: Arc<DashMap<String, Arc<Mutex<AuthoringRoom>>>>,
+    /// How often authoring rooms save edited steps.
+    pub authoring_save_interval: Duration,
 }
 
 impl AppState {
@@ -71,12 +92,21 @@ impl AppState {
         admin_pw: String,
         trust_proxy: bool,
     ) -> Self {
+        let auth = AuthConfig::from_env();
+        let certificate_signer = Arc::new(DocumentSigner::from_env(&auth.secrets));
+        let secret_keyring = SecretKeyring::from_secrets(&auth.secrets);
         Self {
             pool,
             db_kind,
             admin_id,
             admin_pw,
-            auth: AuthConfig::from_env(),
+            auth,
+            certificate_signer,
+            secret_keyring,
+            credential_issuer: CredentialIssuerConfig::from_env(),
+            certificate_template: Arc::new(CertificateTemplate::from_env()),
+            oidc: OidcConfig::from_env(),
+            retention: RetentionConfig::from_env(),
             rate_limit_config: RateLimitConfig::from_env(),
             rate_limiter: Arc::new(RateLimiter::new()),
             security_headers: SecurityHeadersConfig::from_env(),
@@ -86,6 +116,8 @@ impl AppState {
             sessions: Arc::new(DashMap::new()),
             active_screen_shares: Arc::new(DashMap::new()),
             attendee_sharing: Arc::new(DashMap::new()),
+            authoring_rooms: Arc::new(DashMap::new()),
+            authoring_save_interval: authoring_save_interval_from_env(),
         }
     }
 
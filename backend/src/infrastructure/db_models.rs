@@ -58,3 +58,193 @@ pub struct WorkspaceRow {
     /// Workspace layout mode such as `branch` or `folder`.
     pub structure_type: String,
 }
+
+/// Full `users` row including the password hash, used for login and backups.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserRow {
+    /// Stable user identifier.
+    pub id: String,
+    /// Unique login name.
+    pub username: String,
+    /// Optional human-readable name.
+    pub display_name: Option<String>,
+    /// Salted PBKDF2 hash produced by `hash_password`.
+    pub password_hash: String,
+    /// Serialized staff role.
+    pub role: String,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+    /// Last modification timestamp serialized as text.
+    pub updated_at: Option<String>,
+    /// Timestamp of the most recent successful login.
+    pub last_login_at: Option<String>,
+    /// OpenID Connect `sub` claim for accounts provisioned through single sign-on.
+    #[serde(default)]
+    pub oidc_subject: Option<String>,
+}
+
+/// `codelab_staff` assignment row included in backups.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CodelabStaffRow {
+    /// Codelab the staff user is assigned to.
+    pub codelab_id: String,
+    /// Assigned staff user identifier.
+    pub user_id: String,
+    /// Assignment timestamp serialized as text.
+    pub created_at: Option<String>,
+}
+
+/// Full `api_tokens` row including the token hash, used for listing and backups.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ApiTokenRow {
+    /// Stable token identifier.
+    pub id: String,
+    /// Owning staff user identifier, or the configured `ADMIN_ID`.
+    pub user_id: String,
+    /// Human-readable label chosen at creation.
+    pub name: String,
+    /// SHA-256 hex digest of the plaintext token.
+    pub token_hash: String,
+    /// Leading characters of the token, shown to help identify it.
+    pub token_prefix: String,
+    /// Comma-separated permission names the token is limited to.
+    pub scopes: String,
+    /// Expiry in epoch seconds; `None` never expires.
+    pub expires_at_epoch: Option<i64>,
+    /// Timestamp of the most recent authenticated request.
+    pub last_used_at: Option<String>,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+}
+
+/// Registered session as listed by the session management endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuthSessionRow {
+    /// Session identifier, matching the token's `jti` claim.
+    pub id: String,
+    /// Session subject: a staff user id, `ADMIN_ID`, or an attendee id.
+    pub subject: String,
+    /// Session role, `admin` or `attendee`.
+    pub role: String,
+    /// Staff role captured at sign-in for admin sessions.
+    pub staff_role: Option<String>,
+    /// Codelab scope for attendee sessions.
+    pub codelab_id: Option<String>,
+    /// Client IP recorded at sign-in.
+    pub ip: Option<String>,
+    /// Client user agent recorded at sign-in.
+    pub user_agent: Option<String>,
+    /// Issue time in epoch seconds.
+    pub issued_at_epoch: i64,
+    /// Expiry in epoch seconds.
+    pub expires_at_epoch: i64,
+}
+
+/// `staff_totp` row holding a staff member's encrypted TOTP secret.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StaffTotpRow {
+    /// Staff subject: a `users.id` or the configured `ADMIN_ID`.
+    pub subject: String,
+    /// Base32 secret encrypted with the admin password.
+    pub secret: String,
+    /// Confirmation time in epoch seconds; `None` while enrollment is pending.
+    pub enabled_at_epoch: Option<i64>,
+    /// Most recently accepted time step, used to reject replayed codes.
+    pub last_used_step: Option<i64>,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+}
+
+/// `staff_recovery_codes` row storing one hashed single-use recovery code.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StaffRecoveryCodeRow {
+    /// Row identifier.
+    pub id: String,
+    /// Staff subject the code belongs to.
+    pub subject: String,
+    /// SHA-256 hex digest of the normalized code.
+    pub code_hash: String,
+    /// Time the code was redeemed in epoch seconds.
+    pub used_at_epoch: Option<i64>,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+}
+
+/// Join passcode of a codelab, encrypted with the admin password.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CodelabPasscodeRow {
+    /// Codelab the passcode unlocks.
+    pub codelab_id: String,
+    /// Encrypted passcode.
+    pub passcode: String,
+    /// Last change timestamp serialized as text.
+    pub updated_at: Option<String>,
+}
+
+/// Full `codelab_invites` row including the token hash, used for listing and backups.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CodelabInviteRow {
+    /// Stable invite identifier.
+    pub id: String,
+    /// Codelab the invite admits attendees to.
+    pub codelab_id: String,
+    /// Optional label, such as the team the link was sent to.
+    pub label: Option<String>,
+    /// SHA-256 hex digest of the plaintext token.
+    pub token_hash: String,
+    /// Leading characters of the token, shown to help identify it.
+    pub token_prefix: String,
+    /// Registrations allowed; `None` is unlimited.
+    pub max_uses: Option<i32>,
+    /// Registrations made with the invite so far.
+    pub use_count: i32,
+    /// Expiry in epoch seconds; `None` never expires.
+    pub expires_at_epoch: Option<i64>,
+    /// Revocation time in epoch seconds.
+    pub revoked_at_epoch: Option<i64>,
+    /// Staff subject that created the invite.
+    pub created_by: String,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+}
+
+/// `codelab_waitlist` row including the encrypted join code.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CodelabWaitlistRow {
+    /// Waitlist entry identifier.
+    pub id: String,
+    /// Codelab the learner is waiting for.
+    pub codelab_id: String,
+    /// Learner display name.
+    pub name: String,
+    /// Join code encrypted with the admin password, moved to `attendees` on promotion.
+    pub code: String,
+    /// Optional email address captured during registration.
+    pub email: Option<String>,
+    /// Cohort the learner asked to join.
+    #[sqlx(default)]
+    pub cohort_id: Option<String>,
+    /// Time the learner joined the waitlist.
+    pub created_at: Option<String>,
+}
+
+/// `codelab_revisions` row with the step snapshot still encoded as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CodelabRevisionRow {
+    /// Revision identifier.
+    pub id: String,
+    /// Codelab the snapshot belongs to.
+    pub codelab_id: String,
+    /// 1-based revision counter within the codelab.
+    pub revision_number: i32,
+    /// Guide markdown at the time of the snapshot.
+    pub guide_markdown: Option<String>,
+    /// JSON array of [`RevisionStep`](crate::domain::models::RevisionStep) values.
+    pub steps: String,
+    /// Staff subject that saved the content; `None` for baselines.
+    pub author_id: Option<String>,
+    /// What produced the snapshot: `baseline`, `steps_update`, `guide_update`, or `restore`.
+    pub source: String,
+    /// Creation timestamp serialized as text.
+    pub created_at: Option<String>,
+}
@@ -0,0 +1,225 @@
+//! Join passcodes and invite links that admit attendees to private codelabs.
+//!
+//! Passcodes are encrypted with the secret keyring so staff can read them back;
+//! invite tokens are stored only as SHA-256 digests.
+
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::db_models::CodelabInviteRow;
+use crate::middleware::auth::now_epoch_seconds;
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+use sqlx::AnyConnection;
+use subtle::ConstantTimeEq;
+
+/// Prefix that marks a string as a codelab invite token.
+pub const INVITE_TOKEN_PREFIX: &str = "oc_inv_";
+const INVITE_SECRET_LEN: usize = 32;
+/// Characters of the token kept in plaintext to identify an invite.
+pub const INVITE_PREFIX_LEN: usize = 12;
+/// Length of generated attendee join codes.
+pub const JOIN_CODE_LEN: usize = 8;
+
+/// Columns selected for [`CodelabInviteRow`].
+pub const INVITE_COLUMNS: &str = "id, codelab_id, label, token_hash, token_prefix, max_uses, use_count, expires_at_epoch, revoked_at_epoch, created_by, created_at";
+
+/// How a private codelab registration was admitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JoinGrant {
+    /// A valid invite token; carries the invite id to consume.
+    Invite(String),
+    /// The codelab passcode.
+    Passcode,
+}
+
+impl JoinGrant {
+    /// Returns the name recorded in audit metadata.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JoinGrant::Invite(_) => "invite",
+            JoinGrant::Passcode => "passcode",
+        }
+    }
+}
+
+/// Generates a new plaintext invite token.
+pub fn generate_invite_token() -> String {
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(INVITE_SECRET_LEN)
+        .map(char::from)
+        .collect();
+    format!("{INVITE_TOKEN_PREFIX}{secret}")
+}
+
+/// Generates an attendee join code for pre-registered learners.
+///
+/// Uses upper-case letters and digits without look-alikes such as `0`/`O`
+/// so codes can be read out or typed from a printout.
+pub fn generate_join_code() -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+    let mut rng = rand::thread_rng();
+    (0..JOIN_CODE_LEN)
+        .map(|_| char::from(ALPHABET[rng.gen_range(0..ALPHABET.len())]))
+        .collect()
+}
+
+/// Returns the SHA-256 hex digest under which an invite token is stored.
+pub fn hash_invite_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.trim().as_bytes()))
+}
+
+/// Returns the decrypted passcode of a codelab, if one is set.
+pub async fn load_passcode(
+    state: &AppState,
+    codelab_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let row: Option<(String,)> =
+        sqlx::query_as(&state.q("SELECT passcode FROM codelab_passcodes WHERE codelab_id = ?"))
+            .bind(codelab_id)
+            .fetch_optional(&state.pool)
+            .await?;
+    let Some((encrypted,)) = row else {
+        return Ok(None);
+    };
+    let passcode = match state.secret_keyring.open(&encrypted, &state.admin_pw) {
+        Ok(passcode) => passcode,
+        Err(err) => {
+            tracing::error!("Passcode for codelab {codelab_id} could not be decrypted: {err}");
+            return Ok(None);
+        }
+    };
+    if !state.secret_keyring.is_current(&encrypted) {
+        // Move passcodes sealed by a retired key or ADMIN_PW onto the active key.
+        let resealed = state
+            .secret_keyring
+            .seal(&passcode)
+            .map_err(|err| sqlx::Error::Protocol(format!("encrypt passcode: {err}")))?;
+        sqlx::query(
+            &state.q(
+                "UPDATE codelab_passcodes SET passcode = ? WHERE codelab_id = ? AND passcode = ?",
+            ),
+        )
+        .bind(&resealed)
+        .bind(codelab_id)
+        .bind(&encrypted)
+        .execute(&state.pool)
+        .await?;
+    }
+    Ok(Some(passcode))
+}
+
+/// Sets the passcode of a codelab, or removes it when `passcode` is `None`.
+pub async fn store_passcode(
+    state: &AppState,
+    codelab_id: &str,
+    passcode: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(&state.q("DELETE FROM codelab_passcodes WHERE codelab_id = ?"))
+        .bind(codelab_id)
+        .execute(&state.pool)
+        .await?;
+    let Some(passcode) = passcode else {
+        return Ok(());
+    };
+    let encrypted = state
+        .secret_keyring
+        .seal(passcode)
+        .map_err(|err| sqlx::Error::Protocol(format!("encrypt passcode: {err}")))?;
+    sqlx::query(&state.q("INSERT INTO codelab_passcodes (codelab_id, passcode) VALUES (?, ?)"))
+        .bind(codelab_id)
+        .bind(&encrypted)
+        .execute(&state.pool)
+        .await?;
+    Ok(())
+}
+
+/// Finds an invite for `codelab_id` that is unrevoked, unexpired, and has uses left.
+pub async fn find_usable_invite(
+    state: &AppState,
+    codelab_id: &str,
+    token: &str,
+) -> Result<Option<CodelabInviteRow>, sqlx::Error> {
+    sqlx::query_as::<_, CodelabInviteRow>(&state.q(&format!(
+        "SELECT {INVITE_COLUMNS} FROM codelab_invites WHERE token_hash = ? AND codelab_id = ? AND revoked_at_epoch IS NULL AND (expires_at_epoch IS NULL OR expires_at_epoch > ?) AND (max_uses IS NULL OR use_count < max_uses)"
+    )))
+    .bind(hash_invite_token(token))
+    .bind(codelab_id)
+    .bind(now_epoch_seconds() as i64)
+    .fetch_optional(&state.pool)
+    .await
+}
+
+/// Checks the credentials of a registration against a private codelab.
+///
+/// An invite token is preferred over the passcode when both are supplied.
+pub async fn resolve_join_grant(
+    state: &AppState,
+    codelab_id: &str,
+    invite_token: Option<&str>,
+    passcode: Option<&str>,
+) -> Result<Option<JoinGrant>, sqlx::Error> {
+    if let Some(token) = invite_token.filter(|token| !token.trim().is_empty()) {
+        if let Some(invite) = find_usable_invite(state, codelab_id, token).await? {
+            return Ok(Some(JoinGrant::Invite(invite.id)));
+        }
+    }
+    let Some(candidate) = passcode.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    let Some(expected) = load_passcode(state, codelab_id).await? else {
+        return Ok(None);
+    };
+    let matches: bool = expected.as_bytes().ct_eq(candidate.as_bytes()).into();
+    Ok(matches.then_some(JoinGrant::Passcode))
+}
+
+/// Records one registration against an invite on `conn`, usually inside the
+/// registration transaction.
+///
+/// Returns `false` when the invite ran out of uses, expired, or was revoked
+/// since it was checked.
+pub async fn consume_invite(
+    state: &AppState,
+    conn: &mut AnyConnection,
+    invite_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let updated = sqlx::query(&state.q(
+        "UPDATE codelab_invites SET use_count = use_count + 1 WHERE id = ? AND revoked_at_epoch IS NULL AND (expires_at_epoch IS NULL OR expires_at_epoch > ?) AND (max_uses IS NULL OR use_count < max_uses)",
+    ))
+    .bind(invite_id)
+    .bind(now_epoch_seconds() as i64)
+    .execute(conn)
+    .await?;
+    Ok(updated.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invite_tokens_hash_without_surrounding_whitespace() {
+        let token = generate_invite_token();
+        assert!(token.starts_with(INVITE_TOKEN_PREFIX));
+        assert_eq!(token.len(), INVITE_TOKEN_PREFIX.len() + INVITE_SECRET_LEN);
+        assert_eq!(
+            hash_invite_token(&token),
+            hash_invite_token(&format!(" {token}\n"))
+        );
+        assert_ne!(
+            hash_invite_token(&token),
+            hash_invite_token(&generate_invite_token())
+        );
+        assert_eq!(JoinGrant::Invite("id".to_string()).as_str(), "invite");
+    }
+
+    #[test]
+    fn join_codes_avoid_look_alike_characters() {
+        let code = generate_join_code();
+        assert_eq!(code.len(), JOIN_CODE_LEN);
+        assert!(code
+            .chars()
+            .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit()));
+        assert!(!code.contains(['0', 'O', '1', 'I']));
+    }
+}
@@ -0,0 +1,394 @@
+//! Storage for staff two-factor authentication.
+//!
+//! TOTP secrets are encrypted with the secret keyring derived from
+//! `AUTH_SECRETS`, recovery codes and login challenges are stored only as
+//! SHA-256 digests.
+
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::db_models::StaffTotpRow;
+use crate::middleware::auth::now_epoch_seconds;
+use crate::utils::totp::{base32_decode, base32_encode, verify_totp};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Seconds a password-verified login may wait for its second factor.
+pub const MFA_CHALLENGE_TTL_SECONDS: i64 = 5 * 60;
+/// Wrong codes accepted per login challenge before it is discarded.
+const MAX_CHALLENGE_ATTEMPTS: i64 = 5;
+/// Recovery codes issued per enrollment.
+pub const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_LEN: usize = 10;
+const CHALLENGE_TOKEN_LEN: usize = 48;
+
+/// Second factor that satisfied a verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecondFactor {
+    /// A code from the authenticator app.
+    Totp,
+    /// A single-use recovery code, now consumed.
+    RecoveryCode,
+}
+
+impl SecondFactor {
+    /// Returns the name recorded in audit metadata.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecondFactor::Totp => "totp",
+            SecondFactor::RecoveryCode => "recovery_code",
+        }
+    }
+}
+
+fn sha256_hex(value: &str) -> String {
+    format!("{:x}", Sha256::digest(value.as_bytes()))
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|ch| ch.is_ascii_alphanumeric())
+        .map(|ch| ch.to_ascii_lowercase())
+        .collect()
+}
+
+fn generate_recovery_code() -> String {
+    let raw: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(RECOVERY_CODE_LEN)
+        .map(|byte| (byte as char).to_ascii_lowercase())
+        .collect();
+    format!(
+        "{}-{}",
+        &raw[..RECOVERY_CODE_LEN / 2],
+        &raw[RECOVERY_CODE_LEN / 2..]
+    )
+}
+
+/// Loads the TOTP enrollment for `subject`, confirmed or pending.
+pub async fn load_totp(
+    state: &AppState,
+    subject: &str,
+) -> Result<Option<StaffTotpRow>, sqlx::Error> {
+    sqlx::query_as::<_, StaffTotpRow>(&state.q(
+        "SELECT subject, secret, enabled_at_epoch, last_used_step, created_at FROM staff_totp WHERE subject = ?",
+    ))
+    .bind(subject)
+    .fetch_optional(&state.pool)
+    .await
+}
+
+/// Returns whether `subject` must present a second factor at sign-in.
+pub async fn totp_enabled(state: &AppState, subject: &str) -> Result<bool, sqlx::Error> {
+    Ok(load_totp(state, subject)
+        .await?
+        .is_some_and(|row| row.enabled_at_epoch.is_some()))
+}
+
+/// Replaces any pending enrollment with a fresh secret and returns it in base32.
+pub async fn begin_totp_enrollment(
+    state: &AppState,
+    subject: &str,
+    secret: &[u8],
+) -> Result<String, sqlx::Error> {
+    let encoded = base32_encode(secret);
+    let encrypted = state
+        .secret_keyring
+        .seal(&encoded)
+        .map_err(|err| sqlx::Error::Protocol(format!("encrypt TOTP secret: {err}")))?;
+    sqlx::query(&state.q("DELETE FROM staff_totp WHERE subject = ? AND enabled_at_epoch IS NULL"))
+        .bind(subject)
+        .execute(&state.pool)
+        .await?;
+    sqlx::query(&state.q("INSERT INTO staff_totp (subject, secret) VALUES (?, ?)"))
+        .bind(subject)
+        .bind(&encrypted)
+        .execute(&state.pool)
+        .await?;
+    Ok(encoded)
+}
+
+/// Checks a TOTP code and records its step so it cannot be replayed.
+async fn accept_totp(
+    state: &AppState,
+    row: &StaffTotpRow,
+    code: &str,
+) -> Result<bool, sqlx::Error> {
+    let encoded = match state.secret_keyring.open(&row.secret, &state.admin_pw) {
+        Ok(encoded) => encoded,
+        Err(err) => {
+            tracing::error!(
+                "TOTP secret for {} could not be decrypted ({err}); was its AUTH_SECRETS entry removed?",
+                row.subject
+            );
+            return Ok(false);
+        }
+    };
+    let Some(secret) = base32_decode(&encoded) else {
+        tracing::error!("TOTP secret for {} is not valid base32", row.subject);
+        return Ok(false);
+    };
+    if !state.secret_keyring.is_current(&row.secret) {
+        // Move secrets sealed by a retired key or ADMIN_PW onto the active key.
+        let resealed = state
+            .secret_keyring
+            .seal(&encoded)
+            .map_err(|err| sqlx::Error::Protocol(format!("encrypt TOTP secret: {err}")))?;
+        sqlx::query(&state.q("UPDATE staff_totp SET secret = ? WHERE subject = ? AND secret = ?"))
+            .bind(&resealed)
+            .bind(&row.subject)
+            .bind(&row.secret)
+            .execute(&state.pool)
+            .await?;
+    }
+    let Some(step) = verify_totp(&secret, code, now_epoch_seconds() as u64) else {
+        return Ok(false);
+    };
+    let step = step as i64;
+    let updated = sqlx::query(&state.q(
+        "UPDATE staff_totp SET last_used_step = ? WHERE subject = ? AND (last_used_step IS NULL OR last_used_step < ?)",
+    ))
+    .bind(step)
+    .bind(&row.subject)
+    .bind(step)
+    .execute(&state.pool)
+    .await?;
+    Ok(updated.rows_affected() > 0)
+}
+
+/// Confirms a pending enrollment with a first valid code.
+///
+/// Returns `false` when nothing is pending or the code is wrong.
+pub async fn confirm_totp_enrollment(
+    state: &AppState,
+    subject: &str,
+    code: &str,
+) -> Result<bool, sqlx::Error> {
+    let Some(row) = load_totp(state, subject).await? else {
+        return Ok(false);
+    };
+    if row.enabled_at_epoch.is_some() || !accept_totp(state, &row, code).await? {
+        return Ok(false);
+    }
+    sqlx::query(&state.q("UPDATE staff_totp SET enabled_at_epoch = ? WHERE subject = ?"))
+        .bind(now_epoch_seconds() as i64)
+        .bind(subject)
+        .execute(&state.pool)
+        .await?;
+    Ok(true)
+}
+
+/// Verifies a TOTP or recovery code for an enrolled subject.
+pub async fn verify_second_factor(
+    state: &AppState,
+    subject: &str,
+    code: &str,
+) -> Result<Option<SecondFactor>, sqlx::Error> {
+    let Some(row) = load_totp(state, subject).await? else {
+        return Ok(None);
+    };
+    if row.enabled_at_epoch.is_none() {
+        return Ok(None);
+    }
+    if accept_totp(state, &row, code).await? {
+        return Ok(Some(SecondFactor::Totp));
+    }
+    let normalized = normalize_recovery_code(code);
+    if normalized.len() != RECOVERY_CODE_LEN {
+        return Ok(None);
+    }
+    let redeemed = sqlx::query(&state.q(
+        "UPDATE staff_recovery_codes SET used_at_epoch = ? WHERE subject = ? AND code_hash = ? AND used_at_epoch IS NULL",
+    ))
+    .bind(now_epoch_seconds() as i64)
+    .bind(subject)
+    .bind(sha256_hex(&normalized))
+    .execute(&state.pool)
+    .await?;
+    Ok((redeemed.rows_affected() > 0).then_some(SecondFactor::RecoveryCode))
+}
+
+/// Issues a new set of recovery codes, invalidating earlier ones.
+pub async fn replace_recovery_codes(
+    state: &AppState,
+    subject: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+    let mut tx = state.pool.begin().await?;
+    sqlx::query(&state.q("DELETE FROM staff_recovery_codes WHERE subject = ?"))
+        .bind(subject)
+        .execute(&mut *tx)
+        .await?;
+    for code in &codes {
+        sqlx::query(
+            &state.q("INSERT INTO staff_recovery_codes (id, subject, code_hash) VALUES (?, ?, ?)"),
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(subject)
+        .bind(sha256_hex(&normalize_recovery_code(code)))
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(codes)
+}
+
+/// Counts recovery codes that have not been redeemed.
+pub async fn remaining_recovery_codes(state: &AppState, subject: &str) -> Result<i64, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as(&state.q(
+        "SELECT COUNT(*) FROM staff_recovery_codes WHERE subject = ? AND used_at_epoch IS NULL",
+    ))
+    .bind(subject)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(count)
+}
+
+/// Removes the TOTP secret and recovery codes for `subject`.
+///
+/// Returns `false` when the subject had no enrollment.
+pub async fn remove_totp(state: &AppState, subject: &str) -> Result<bool, sqlx::Error> {
+    let mut tx = state.pool.begin().await?;
+    sqlx::query(&state.q("DELETE FROM staff_recovery_codes WHERE subject = ?"))
+        .bind(subject)
+        .execute(&mut *tx)
+        .await?;
+    let removed = sqlx::query(&state.q("DELETE FROM staff_totp WHERE subject = ?"))
+        .bind(subject)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(removed.rows_affected() > 0)
+}
+
+/// Records a password-verified login and returns the opaque challenge token.
+pub async fn create_mfa_challenge(state: &AppState, subject: &str) -> Result<String, sqlx::Error> {
+    let now = now_epoch_seconds() as i64;
+    sqlx::query(&state.q("DELETE FROM mfa_login_challenges WHERE expires_at_epoch <= ?"))
+        .bind(now)
+        .execute(&state.pool)
+        .await?;
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(CHALLENGE_TOKEN_LEN)
+        .map(char::from)
+        .collect();
+    sqlx::query(&state.q(
+        "INSERT INTO mfa_login_challenges (id, subject, attempts, expires_at_epoch) VALUES (?, ?, 0, ?)",
+    ))
+    .bind(sha256_hex(&token))
+    .bind(subject)
+    .bind(now + MFA_CHALLENGE_TTL_SECONDS)
+    .execute(&state.pool)
+    .await?;
+    Ok(token)
+}
+
+/// Counts an attempt against a login challenge and returns its subject.
+///
+/// Returns `None` for unknown, expired, or exhausted challenges.
+pub async fn claim_mfa_challenge(
+    state: &AppState,
+    token: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let id = sha256_hex(token);
+    let updated = sqlx::query(&state.q(
+        "UPDATE mfa_login_challenges SET attempts = attempts + 1 WHERE id = ? AND expires_at_epoch > ? AND attempts < ?",
+    ))
+    .bind(&id)
+    .bind(now_epoch_seconds() as i64)
+    .bind(MAX_CHALLENGE_ATTEMPTS)
+    .execute(&state.pool)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Ok(None);
+    }
+    let row: Option<(String,)> =
+        sqlx::query_as(&state.q("SELECT subject FROM mfa_login_challenges WHERE id = ?"))
+            .bind(&id)
+            .fetch_optional(&state.pool)
+            .await?;
+    Ok(row.map(|(subject,)| subject))
+}
+
+/// Challenge row counting second-factor codes sent along with the password.
+///
+/// Login challenge ids are SHA-256 digests, so this id never matches a
+/// token presented to `/api/login/mfa`.
+fn password_approval_challenge_id(subject: &str) -> String {
+    format!("cli:{subject}")
+}
+
+/// Counts a second-factor attempt made in the same request as the password,
+/// as CLI login approval does, and returns whether the code may be checked.
+///
+/// These attempts share one challenge per subject, so however often the
+/// password is sent, at most five codes are tried per challenge lifetime.
+pub async fn claim_password_approval_attempt(
+    state: &AppState,
+    subject: &str,
+) -> Result<bool, sqlx::Error> {
+    let id = password_approval_challenge_id(subject);
+    let now = now_epoch_seconds() as i64;
+    sqlx::query(
+        &state.q("DELETE FROM mfa_login_challenges WHERE id = ? AND expires_at_epoch <= ?"),
+    )
+    .bind(&id)
+    .bind(now)
+    .execute(&state.pool)
+    .await?;
+    sqlx::query(&state.q(
+        "INSERT INTO mfa_login_challenges (id, subject, attempts, expires_at_epoch) SELECT ?, ?, 0, ? WHERE NOT EXISTS (SELECT 1 FROM mfa_login_challenges WHERE id = ?)",
+    ))
+    .bind(&id)
+    .bind(subject)
+    .bind(now + MFA_CHALLENGE_TTL_SECONDS)
+    .bind(&id)
+    .execute(&state.pool)
+    .await?;
+    let updated = sqlx::query(&state.q(
+        "UPDATE mfa_login_challenges SET attempts = attempts + 1 WHERE id = ? AND expires_at_epoch > ? AND attempts < ?",
+    ))
+    .bind(&id)
+    .bind(now)
+    .bind(MAX_CHALLENGE_ATTEMPTS)
+    .execute(&state.pool)
+    .await?;
+    Ok(updated.rows_affected() > 0)
+}
+
+/// Resets the attempt count of [`claim_password_approval_attempt`] after a
+/// code was accepted.
+pub async fn finish_password_approval(state: &AppState, subject: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(&state.q("DELETE FROM mfa_login_challenges WHERE id = ?"))
+        .bind(password_approval_challenge_id(subject))
+        .execute(&state.pool)
+        .await?;
+    Ok(())
+}
+
+/// Deletes a login challenge once it has been completed.
+pub async fn finish_mfa_challenge(state: &AppState, token: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(&state.q("DELETE FROM mfa_login_challenges WHERE id = ?"))
+        .bind(sha256_hex(token))
+        .execute(&state.pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovery_codes_normalize_for_lookup() {
+        let code = generate_recovery_code();
+        assert_eq!(code.len(), RECOVERY_CODE_LEN + 1);
+        assert_eq!(code.chars().nth(RECOVERY_CODE_LEN / 2), Some('-'));
+        let normalized = normalize_recovery_code(&code.to_uppercase());
+        assert_eq!(normalized.len(), RECOVERY_CODE_LEN);
+        assert_eq!(normalized, code.replace('-', ""));
+        assert_eq!(normalize_recovery_code(" ab12c-DE34f "), "ab12cde34f");
+    }
+}
@@ -1,22 +1,53 @@
 //! Runtime infrastructure for the backend.
 //!
 //! The infrastructure layer owns application state, environment-backed
-//! configuration, audit logging helpers, and raw database mapping structs.
+//! configuration, audit logging helpers, the session registry, two-factor
+//! storage, codelab join access, data retention, content revisions, shared
+//! authoring rooms, attendee progress remapping, and raw database mapping
+//! structs.
 
 /// Audit logging helpers.
 pub mod audit;
+/// Shared step editing rooms with operational transformation.
+pub mod authoring;
 /// Environment-backed runtime configuration.
 pub mod config;
 /// Shared application state and database helper functions.
 pub mod database;
 /// Low-level row-mapping structs used by SQL queries.
 pub mod db_models;
+/// Codelab join passcodes and invite links.
+pub mod join_access;
+/// TOTP secrets, recovery codes, and pending second-factor logins.
+pub mod mfa;
+/// Keeping attendees on their step when the step order changes.
+pub mod progress;
+/// Scheduled pruning of chat, AI history, submissions, and audit logs.
+pub mod retention;
+/// Snapshots of codelab guides and steps for history and rollback.
+pub mod revisions;
+/// Registry of issued session tokens used for revocation.
+pub mod sessions;
 
 #[doc(inline)]
 pub use audit::*;
 #[doc(inline)]
+pub use authoring::*;
+#[doc(inline)]
 pub use config::*;
 #[doc(inline)]
 pub use database::*;
 #[doc(inline)]
 pub use db_models::*;
+#[doc(inline)]
+pub use join_access::*;
+#[doc(inline)]
+pub use mfa::*;
+#[doc(inline)]
+pub use progress::*;
+#[doc(inline)]
+pub use retention::*;
+#[doc(inline)]
+pub use revisions::*;
+#[doc(inline)]
+pub use sessions::*;
@@ -0,0 +1,155 @@
+//! Attendee progress across changes to a codelab's step order.
+//!
+//! An attendee's `current_step` is a 1-based position in the steps learners
+//! read: the published snapshot once there is one, the draft otherwise. When
+//! that order changes, attendees are moved so they stay on the step they were
+//! reading, and so do their open help requests. Attendees on a removed step
+//! stay at the same position, which now holds the step that followed it.
+
+use crate::domain::models::RevisionStep;
+use crate::infrastructure::database::AppState;
+use sqlx::AnyConnection;
+
+/// Returns the step identifiers learners see, in order, on `conn`.
+pub async fn learner_step_ids(
+    state: &AppState,
+    conn: &mut AnyConnection,
+    codelab_id: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    let published: Option<(String,)> = sqlx::query_as(&state.q(
+        "SELECT r.steps FROM codelabs c JOIN codelab_revisions r ON r.id = c.published_revision_id WHERE c.id = ?",
+    ))
+    .bind(codelab_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if let Some((steps,)) = published {
+        let steps: Vec<RevisionStep> =
+            serde_json::from_str(&steps).map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+        return Ok(steps.into_iter().map(|step| step.id).collect());
+    }
+    sqlx::query_scalar(&state.q("SELECT id FROM steps WHERE codelab_id = ? ORDER BY step_number"))
+        .bind(codelab_id)
+        .fetch_all(&mut *conn)
+        .await
+}
+
+/// Pairs of `(old, new)` 1-based positions for every position that moves
+/// when the step order changes from `before` to `after`.
+pub fn progress_moves(before: &[String], after: &[String]) -> Vec<(i32, i32)> {
+    let last = after.len().max(1);
+    before
+        .iter()
+        .enumerate()
+        .filter_map(|(index, id)| {
+            let old = index + 1;
+            let new = after
+                .iter()
+                .position(|candidate| candidate == id)
+                .map(|position| position + 1)
+                .unwrap_or(old.min(last));
+            (old != new).then_some((old as i32, new as i32))
+        })
+        .collect()
+}
+
+/// Moves attendees of a codelab to follow their step after the order learners
+/// see changed from `before`, on the transaction that changed it.
+///
+/// Unresolved help requests move with the step they were raised on.
+/// Returns the number of attendees moved.
+pub async fn remap_attendee_progress(
+    state: &AppState,
+    conn: &mut AnyConnection,
+    codelab_id: &str,
+    before: &[String],
+) -> Result<u64, sqlx::Error> {
+    let after = learner_step_ids(state, conn, codelab_id).await?;
+    let moves = progress_moves(before, &after);
+    if moves.is_empty() {
+        return Ok(0);
+    }
+    let moved = remap_step_column(
+        state,
+        conn,
+        "attendees",
+        "current_step",
+        "codelab_id = ?",
+        codelab_id,
+        &moves,
+    )
+    .await?;
+    remap_step_column(
+        state,
+        conn,
+        "help_requests",
+        "step_number",
+        "codelab_id = ? AND status = 'pending'",
+        codelab_id,
+        &moves,
+    )
+    .await?;
+    Ok(moved)
+}
+
+/// Applies `moves` to a step position column of the rows matching `filter`.
+async fn remap_step_column(
+    state: &AppState,
+    conn: &mut AnyConnection,
+    table: &str,
+    column: &str,
+    filter: &str,
+    codelab_id: &str,
+    moves: &[(i32, i32)],
+) -> Result<u64, sqlx::Error> {
+    // One statement so a step moving into a position that is itself moving
+    // is not shifted twice.
+    let cases = " WHEN ? THEN ?".repeat(moves.len());
+    let sql = format!(
+        "UPDATE {table} SET {column} = CASE {column}{cases} ELSE {column} END WHERE {filter}"
+    );
+    let query = state.q(&sql);
+    let mut update = sqlx::query(&query);
+    for &(old, new) in moves {
+        update = update.bind(old).bind(new);
+    }
+    let result = update.bind(codelab_id).execute(&mut *conn).await?;
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn progress_follows_inserted_moved_and_removed_steps() {
+        let before = ids(&["a", "b", "c", "d"]);
+        assert_eq!(
+            progress_moves(&before, &ids(&["a", "new", "b", "c", "d"])),
+            vec![(2, 3), (3, 4), (4, 5)]
+        );
+        assert_eq!(
+            progress_moves(&before, &ids(&["b", "c", "a", "d"])),
+            vec![(1, 3), (2, 1), (3, 2)]
+        );
+        assert_eq!(
+            progress_moves(&before, &ids(&["a", "c", "d"])),
+            vec![(3, 2), (4, 3)]
+        );
+        assert_eq!(
+            progress_moves(&before, &ids(&["a", "b", "c"])),
+            vec![(4, 3)]
+        );
+        assert!(progress_moves(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn replaced_steps_keep_positions_within_the_new_length() {
+        let before = ids(&["a", "b", "c"]);
+        assert_eq!(progress_moves(&before, &ids(&["x", "y"])), vec![(3, 2)]);
+        assert_eq!(progress_moves(&before, &[]), vec![(2, 1), (3, 1)]);
+    }
+}
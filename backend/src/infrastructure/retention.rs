@@ -0,0 +1,401 @@
+//! Retention passes that prune chat, AI history, submissions, and audit logs.
+//!
+//! Codelab-scoped data is kept until the codelab ends, which is the later of
+//! the newest row of that data class and the end of the codelab's last cohort.
+//! Once the configured period has passed since then, that data class is
+//! removed for the whole codelab. Audit log entries expire one by one.
+
+use crate::infrastructure::audit::{record_audit, AuditEntry};
+use crate::infrastructure::config::RetentionConfig;
+use crate::infrastructure::database::AppState;
+use chrono::{Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Directory holding uploaded submission files.
+const SUBMISSION_UPLOAD_DIR: &str = "static/uploads/submissions";
+
+/// Unreferenced uploads younger than this may belong to an upload in progress.
+const ORPHAN_GRACE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Rows and files removed by a retention pass, or counted by a dry run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionReport {
+    /// Whether nothing was deleted and the counts are what a pass would remove.
+    pub dry_run: bool,
+    /// Retention periods the pass applied.
+    pub policy: RetentionConfig,
+    /// Codelabs with at least one data class past its retention period.
+    pub codelab_ids: Vec<String>,
+    /// Chat messages.
+    pub chat_messages: u64,
+    /// Saved AI conversation exchanges.
+    pub ai_conversations: u64,
+    /// AI threads.
+    pub ai_threads: u64,
+    /// Messages inside the removed AI threads.
+    pub ai_messages: u64,
+    /// Submission rows.
+    pub submissions: u64,
+    /// Files of the removed submissions.
+    pub submission_files: u64,
+    /// Uploaded submission files no longer referenced by any submission.
+    pub orphaned_files: u64,
+    /// Audit log entries.
+    pub audit_logs: u64,
+}
+
+impl RetentionReport {
+    /// Total number of rows and files covered by the report.
+    pub fn total(&self) -> u64 {
+        self.chat_messages
+            + self.ai_conversations
+            + self.ai_threads
+            + self.ai_messages
+            + self.submissions
+            + self.submission_files
+            + self.orphaned_files
+            + self.audit_logs
+    }
+
+    /// Counts recorded as metadata of the `retention_prune` audit entry.
+    pub fn audit_metadata(&self) -> serde_json::Value {
+        serde_json::json!({
+            "codelab_ids": self.codelab_ids,
+            "chat_messages": self.chat_messages,
+            "ai_conversations": self.ai_conversations,
+            "ai_threads": self.ai_threads,
+            "ai_messages": self.ai_messages,
+            "submissions": self.submissions,
+            "submission_files": self.submission_files,
+            "orphaned_files": self.orphaned_files,
+            "audit_logs": self.audit_logs,
+        })
+    }
+}
+
+/// Maps a stored `/uploads/...` path to its location on disk.
+pub(crate) fn upload_disk_path(file_path: &str) -> Option<String> {
+    file_path
+        .strip_prefix("/uploads/")
+        .filter(|relative| !relative.split('/').any(|part| part == ".."))
+        .map(|relative| format!("static/uploads/{relative}"))
+}
+
+/// Formats the moment `days` ago like stored `CURRENT_TIMESTAMP` text.
+fn cutoff_text(days: u32) -> String {
+    (Utc::now() - ChronoDuration::days(i64::from(days)))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn cutoff_epoch(days: u32) -> i64 {
+    Utc::now().timestamp() - i64::from(days) * 24 * 60 * 60
+}
+
+/// Returns the newest activity timestamp per codelab in `table`.
+async fn last_activity(
+    state: &AppState,
+    table: &str,
+    column: &str,
+) -> Result<HashMap<String, String>, sqlx::Error> {
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as(&state.q(&format!(
+        "SELECT codelab_id, MAX(CAST({column} AS TEXT)) FROM {table} WHERE codelab_id IS NOT NULL GROUP BY codelab_id"
+    )))
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(codelab_id, at)| at.map(|at| (codelab_id, at)))
+        .collect())
+}
+
+/// Returns codelabs whose data in `activity` ended more than `days` ago.
+///
+/// `activity` holds the newest row per codelab, merged across the tables of
+/// one data class; codelabs with a cohort ending later are kept.
+fn expired_codelabs(
+    activity: HashMap<String, String>,
+    cohort_ends: &HashMap<String, i64>,
+    days: u32,
+) -> Vec<String> {
+    let cutoff = cutoff_text(days);
+    let cutoff_epoch = cutoff_epoch(days);
+    let mut expired: Vec<String> = activity
+        .into_iter()
+        .filter(|(codelab_id, at)| {
+            at.as_str() < cutoff.as_str()
+                && cohort_ends
+                    .get(codelab_id)
+                    .is_none_or(|ends_at| *ends_at < cutoff_epoch)
+        })
+        .map(|(codelab_id, _)| codelab_id)
+        .collect();
+    expired.sort();
+    expired
+}
+
+fn merge_latest(into: &mut HashMap<String, String>, other: HashMap<String, String>) {
+    for (codelab_id, at) in other {
+        let entry = into.entry(codelab_id).or_default();
+        if at > *entry {
+            *entry = at;
+        }
+    }
+}
+
+/// Deletes rows of `table` matching `filter`, or only counts them on a dry run.
+async fn prune(
+    state: &AppState,
+    dry_run: bool,
+    table: &str,
+    filter: &str,
+    value: &str,
+) -> Result<u64, sqlx::Error> {
+    if dry_run {
+        let count: i64 =
+            sqlx::query_scalar(&state.q(&format!("SELECT COUNT(*) FROM {table} WHERE {filter}")))
+                .bind(value)
+                .fetch_one(&state.pool)
+                .await?;
+        return Ok(count as u64);
+    }
+    let result = sqlx::query(&state.q(&format!("DELETE FROM {table} WHERE {filter}")))
+        .bind(value)
+        .execute(&state.pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Removes `path`, or only checks that it exists on a dry run.
+async fn remove_upload(path: &str, dry_run: bool) -> bool {
+    if dry_run {
+        tokio::fs::try_exists(path).await.unwrap_or(false)
+    } else {
+        tokio::fs::remove_file(path).await.is_ok()
+    }
+}
+
+/// Removes submission uploads that no submission row points to anymore.
+async fn prune_orphaned_files(state: &AppState, dry_run: bool) -> Result<u64, sqlx::Error> {
+    let Ok(mut entries) = tokio::fs::read_dir(SUBMISSION_UPLOAD_DIR).await else {
+        return Ok(0);
+    };
+    let referenced: HashSet<String> = sqlx::query_scalar::<_, String>(
+        &state.q("SELECT file_path FROM submissions WHERE submission_type = 'file'"),
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut removed = 0;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        let recent = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_none_or(|age| age < ORPHAN_GRACE);
+        let stored_path = format!(
+            "/uploads/submissions/{}",
+            entry.file_name().to_string_lossy()
+        );
+        if !metadata.is_file() || recent || referenced.contains(&stored_path) {
+            continue;
+        }
+        if remove_upload(&entry.path().to_string_lossy(), dry_run).await {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Applies the configured retention periods once.
+///
+/// With `dry_run` set nothing is deleted and the report counts what a pass
+/// would remove right now.
+pub async fn apply_retention(
+    state: &AppState,
+    dry_run: bool,
+) -> Result<RetentionReport, sqlx::Error> {
+    let policy = state.retention.clone();
+    let mut report = RetentionReport {
+        dry_run,
+        policy: policy.clone(),
+        ..RetentionReport::default()
+    };
+    let mut codelab_ids = HashSet::new();
+    let cohort_ends: HashMap<String, i64> = sqlx::query_as::<_, (String, Option<i64>)>(
+        &state.q("SELECT codelab_id, MAX(ends_at) FROM codelab_cohorts GROUP BY codelab_id"),
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .filter_map(|(codelab_id, ends_at)| ends_at.map(|ends_at| (codelab_id, ends_at)))
+    .collect();
+
+    if let Some(days) = policy.chat_days {
+        let activity = last_activity(state, "chat_messages", "created_at").await?;
+        for codelab_id in expired_codelabs(activity, &cohort_ends, days) {
+            report.chat_messages += prune(
+                state,
+                dry_run,
+                "chat_messages",
+                "codelab_id = ?",
+                &codelab_id,
+            )
+            .await?;
+            codelab_ids.insert(codelab_id);
+        }
+    }
+
+    if let Some(days) = policy.ai_days {
+        let mut activity = last_activity(state, "ai_conversations", "created_at").await?;
+        merge_latest(
+            &mut activity,
+            last_activity(state, "ai_threads", "updated_at").await?,
+        );
+        for codelab_id in expired_codelabs(activity, &cohort_ends, days) {
+            report.ai_messages += prune(
+                state,
+                dry_run,
+                "ai_messages",
+                "thread_id IN (SELECT id FROM ai_threads WHERE codelab_id = ?)",
+                &codelab_id,
+            )
+            .await?;
+            report.ai_threads +=
+                prune(state, dry_run, "ai_threads", "codelab_id = ?", &codelab_id).await?;
+            report.ai_conversations += prune(
+                state,
+                dry_run,
+                "ai_conversations",
+                "codelab_id = ?",
+                &codelab_id,
+            )
+            .await?;
+            codelab_ids.insert(codelab_id);
+        }
+        // Threads outside any codelab expire on their own last update.
+        let cutoff = cutoff_text(days);
+        report.ai_messages += prune(
+            state,
+            dry_run,
+            "ai_messages",
+            "thread_id IN (SELECT id FROM ai_threads WHERE codelab_id IS NULL AND CAST(updated_at AS TEXT) < ?)",
+            &cutoff,
+        )
+        .await?;
+        report.ai_threads += prune(
+            state,
+            dry_run,
+            "ai_threads",
+            "codelab_id IS NULL AND CAST(updated_at AS TEXT) < ?",
+            &cutoff,
+        )
+        .await?;
+    }
+
+    if let Some(days) = policy.submission_days {
+        let activity = last_activity(state, "submissions", "created_at").await?;
+        for codelab_id in expired_codelabs(activity, &cohort_ends, days) {
+            let files: Vec<String> = sqlx::query_scalar(&state.q(
+                "SELECT file_path FROM submissions WHERE codelab_id = ? AND submission_type = 'file'",
+            ))
+            .bind(&codelab_id)
+            .fetch_all(&state.pool)
+            .await?;
+            report.submissions +=
+                prune(state, dry_run, "submissions", "codelab_id = ?", &codelab_id).await?;
+            for file_path in files {
+                if let Some(disk_path) = upload_disk_path(&file_path) {
+                    if remove_upload(&disk_path, dry_run).await {
+                        report.submission_files += 1;
+                    }
+                }
+            }
+            codelab_ids.insert(codelab_id);
+        }
+        report.orphaned_files = prune_orphaned_files(state, dry_run).await?;
+    }
+
+    if let Some(days) = policy.audit_days {
+        report.audit_logs = prune(
+            state,
+            dry_run,
+            "audit_logs",
+            "CAST(created_at AS TEXT) < ?",
+            &cutoff_text(days),
+        )
+        .await?;
+    }
+
+    report.codelab_ids = codelab_ids.into_iter().collect();
+    report.codelab_ids.sort();
+    Ok(report)
+}
+
+/// Runs retention passes on the configured interval for the life of the server.
+pub async fn run_retention_job(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(state.retention.interval());
+    loop {
+        interval.tick().await;
+        match apply_retention(&state, false).await {
+            Ok(report) if report.total() > 0 => {
+                tracing::info!("retention pass removed {} rows and files", report.total());
+                record_audit(
+                    &state,
+                    AuditEntry {
+                        action: "retention_prune".to_string(),
+                        actor_type: "system".to_string(),
+                        actor_id: None,
+                        target_id: None,
+                        codelab_id: None,
+                        ip: None,
+                        user_agent: None,
+                        metadata: Some(report.audit_metadata()),
+                    },
+                )
+                .await;
+            }
+            Ok(_) => {}
+            Err(error) => tracing::warn!("retention pass failed: {}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upload_disk_path_stays_inside_uploads() {
+        assert_eq!(
+            upload_disk_path("/uploads/submissions/a.webp").as_deref(),
+            Some("static/uploads/submissions/a.webp")
+        );
+        assert_eq!(upload_disk_path("https://example.com/a.webp"), None);
+        assert_eq!(upload_disk_path("/uploads/../secrets.db"), None);
+    }
+
+    #[test]
+    fn expired_codelabs_waits_for_the_last_cohort() {
+        let old = "2000-01-01 00:00:00".to_string();
+        let recent = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let activity = HashMap::from([
+            ("quiet".to_string(), old.clone()),
+            ("active".to_string(), recent),
+            ("scheduled".to_string(), old),
+        ]);
+        let cohort_ends = HashMap::from([("scheduled".to_string(), Utc::now().timestamp())]);
+        assert_eq!(
+            expired_codelabs(activity, &cohort_ends, 90),
+            vec!["quiet".to_string()]
+        );
+    }
+}
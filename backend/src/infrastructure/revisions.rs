@@ -0,0 +1,181 @@
+//! Revision history of codelab content.
+//!
+//! Every save of a codelab's steps or guide stores a full snapshot of both, so
+//! any earlier state can be compared against or restored. Codelabs edited
+//! before history existed get a `baseline` snapshot of their old content first.
+//! Publishing also stores a snapshot, which is what learners read. Shared
+//! editing sessions store one each time they save.
+
+use crate::domain::models::RevisionStep;
+use crate::infrastructure::database::AppState;
+use sqlx::AnyConnection;
+
+/// Snapshot of content that existed before the codelab had any history.
+pub const REVISION_SOURCE_BASELINE: &str = "baseline";
+/// Snapshot taken after the step set was replaced.
+pub const REVISION_SOURCE_STEPS: &str = "steps_update";
+/// Snapshot taken after the guide markdown changed.
+pub const REVISION_SOURCE_GUIDE: &str = "guide_update";
+/// Snapshot taken after an earlier revision was restored.
+pub const REVISION_SOURCE_RESTORE: &str = "restore";
+/// Snapshot of the draft made visible to learners.
+pub const REVISION_SOURCE_PUBLISH: &str = "publish";
+/// Snapshot taken when a shared editing session saved step edits.
+pub const REVISION_SOURCE_COLLABORATION: &str = "collaboration";
+
+/// Reads the current guide and ordered steps of a codelab on `conn`.
+pub async fn load_codelab_content(
+    state: &AppState,
+    conn: &mut AnyConnection,
+    codelab_id: &str,
+) -> Result<(Option<String>, Vec<RevisionStep>), sqlx::Error> {
+    let guide: Option<(Option<String>,)> =
+        sqlx::query_as(&state.q("SELECT guide_markdown FROM codelabs WHERE id = ?"))
+            .bind(codelab_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let steps: Vec<(String, String, String, Option<i32>)> = sqlx::query_as(&state.q(
+        "SELECT id, title, content_markdown, duration_minutes FROM steps WHERE codelab_id = ? ORDER BY step_number",
+    ))
+    .bind(codelab_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let steps = steps
+        .into_iter()
+        .map(
+            |(id, title, content_markdown, duration_minutes)| RevisionStep {
+                id,
+                title,
+                content_markdown,
+                duration_minutes,
+            },
+        )
+        .collect();
+    Ok((guide.and_then(|(guide,)| guide), steps))
+}
+
+/// Stores the current content of a codelab as its next revision and returns
+/// the new revision identifier.
+///
+/// Call this on the transaction that changed the content, after the change.
+pub async fn record_revision(
+    state: &AppState,
+    conn: &mut AnyConnection,
+    codelab_id: &str,
+    author_id: Option<&str>,
+    source: &str,
+) -> Result<String, sqlx::Error> {
+    let (guide, steps) = load_codelab_content(state, conn, codelab_id).await?;
+    let steps_json = serde_json::to_string(&steps).unwrap_or_else(|_| "[]".to_string());
+    let previous: i32 =
+        sqlx::query_scalar(&state.q(
+            "SELECT COALESCE(MAX(revision_number), 0) FROM codelab_revisions WHERE codelab_id = ?",
+        ))
+        .bind(codelab_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    let revision_id = uuid::Uuid::new_v4().to_string();
+    sqlx::query(&state.q(
+        "INSERT INTO codelab_revisions (id, codelab_id, revision_number, guide_markdown, steps, author_id, source) VALUES (?, ?, ?, ?, ?, ?, ?)",
+    ))
+    .bind(&revision_id)
+    .bind(codelab_id)
+    .bind(previous + 1)
+    .bind(&guide)
+    .bind(&steps_json)
+    .bind(author_id)
+    .bind(source)
+    .execute(&mut *conn)
+    .await?;
+    Ok(revision_id)
+}
+
+/// Snapshots the content of a codelab that has no revisions yet, so the first
+/// tracked edit can still be rolled back.
+///
+/// Call this on the transaction that changes the content, before the change.
+/// Codelabs without a guide or steps are skipped.
+pub async fn record_baseline_revision(
+    state: &AppState,
+    conn: &mut AnyConnection,
+    codelab_id: &str,
+) -> Result<(), sqlx::Error> {
+    let existing: Option<(String,)> =
+        sqlx::query_as(&state.q("SELECT id FROM codelab_revisions WHERE codelab_id = ? LIMIT 1"))
+            .bind(codelab_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    if existing.is_some() {
+        return Ok(());
+    }
+    let (guide, steps) = load_codelab_content(state, conn, codelab_id).await?;
+    if steps.is_empty() && guide.as_deref().is_none_or(|guide| guide.trim().is_empty()) {
+        return Ok(());
+    }
+    record_revision(state, conn, codelab_id, None, REVISION_SOURCE_BASELINE).await?;
+    Ok(())
+}
+
+/// Returns the guide and steps stored in a revision, if it exists.
+pub async fn load_revision_content(
+    state: &AppState,
+    revision_id: &str,
+) -> Result<Option<(Option<String>, Vec<RevisionStep>)>, sqlx::Error> {
+    let row: Option<(Option<String>, String)> = sqlx::query_as(
+        &state.q("SELECT guide_markdown, steps FROM codelab_revisions WHERE id = ?"),
+    )
+    .bind(revision_id)
+    .fetch_optional(&state.pool)
+    .await?;
+    row.map(|(guide, steps)| {
+        serde_json::from_str(&steps)
+            .map(|steps| (guide, steps))
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))
+    })
+    .transpose()
+}
+
+/// Renders a revision as one markdown document, used for diffs.
+pub fn render_revision_markdown(guide: Option<&str>, steps: &[RevisionStep]) -> String {
+    let mut out = String::new();
+    if let Some(guide) = guide.filter(|guide| !guide.trim().is_empty()) {
+        out.push_str("# Guide\n\n");
+        out.push_str(guide.trim_end());
+        out.push_str("\n\n");
+    }
+    for (i, step) in steps.iter().enumerate() {
+        out.push_str(&format!("## Step {}: {}\n\n", i + 1, step.title));
+        out.push_str(step.content_markdown.trim_end());
+        out.push_str("\n\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revisions_render_guide_and_numbered_steps() {
+        let steps = vec![
+            RevisionStep {
+                id: "s1".to_string(),
+                title: "Setup".to_string(),
+                content_markdown: "Install tools.\n".to_string(),
+                duration_minutes: Some(5),
+            },
+            RevisionStep {
+                id: "s2".to_string(),
+                title: "Run".to_string(),
+                content_markdown: "Run it.".to_string(),
+                duration_minutes: None,
+            },
+        ];
+        assert_eq!(
+            render_revision_markdown(Some("Read first."), &steps),
+            "# Guide\n\nRead first.\n\n## Step 1: Setup\n\nInstall tools.\n\n## Step 2: Run\n\nRun it.\n\n"
+        );
+        assert_eq!(render_revision_markdown(Some("  "), &[]), "");
+    }
+}
@@ -0,0 +1,133 @@
+//! Session registry backed by the `auth_sessions` table.
+//!
+//! Every issued session token carries a `jti` that must be present here and
+//! not revoked, which lets administrators end a single session without
+//! rotating the signing secrets.
+
+use crate::infrastructure::database::AppState;
+use crate::infrastructure::db_models::AuthSessionRow;
+use crate::middleware::auth::{now_epoch_seconds, SessionClaims};
+
+/// Columns selected for [`AuthSessionRow`].
+pub(crate) const SESSION_COLUMNS: &str =
+    "id, subject, role, staff_role, codelab_id, ip, user_agent, issued_at_epoch, expires_at_epoch";
+
+/// Criteria selecting sessions for listing or bulk revocation.
+#[derive(Debug, Clone, Default)]
+pub struct SessionFilter {
+    /// Matches sessions issued to this subject.
+    pub subject: Option<String>,
+    /// Matches sessions scoped to this codelab.
+    pub codelab_id: Option<String>,
+}
+
+impl SessionFilter {
+    fn where_clause(&self, now: i64) -> (String, Vec<String>) {
+        let mut clause = format!("revoked_at_epoch IS NULL AND expires_at_epoch > {now}");
+        let mut binds = Vec::new();
+        if let Some(subject) = &self.subject {
+            clause.push_str(" AND subject = ?");
+            binds.push(subject.clone());
+        }
+        if let Some(codelab_id) = &self.codelab_id {
+            clause.push_str(" AND codelab_id = ?");
+            binds.push(codelab_id.clone());
+        }
+        (clause, binds)
+    }
+}
+
+/// Records a freshly issued session and prunes expired entries.
+///
+/// Claims without a `jti` are ignored.
+pub async fn register_session(
+    state: &AppState,
+    claims: &SessionClaims,
+    ip: Option<&str>,
+    user_agent: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let Some(jti) = &claims.jti else {
+        return Ok(());
+    };
+    sqlx::query(&state.q("DELETE FROM auth_sessions WHERE expires_at_epoch < ?"))
+        .bind(now_epoch_seconds() as i64)
+        .execute(&state.pool)
+        .await?;
+    sqlx::query(&state.q(
+        "INSERT INTO auth_sessions (id, subject, role, staff_role, codelab_id, ip, user_agent, issued_at_epoch, expires_at_epoch) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    ))
+    .bind(jti)
+    .bind(&claims.sub)
+    .bind(&claims.role)
+    .bind(&claims.staff_role)
+    .bind(&claims.codelab_id)
+    .bind(ip)
+    .bind(user_agent)
+    .bind(claims.iat as i64)
+    .bind(claims.exp as i64)
+    .execute(&state.pool)
+    .await?;
+    Ok(())
+}
+
+/// Returns whether `jti` belongs to a registered session that has not been revoked.
+pub async fn is_session_active(state: &AppState, jti: &str) -> Result<bool, sqlx::Error> {
+    let row: Option<(Option<i64>,)> =
+        sqlx::query_as(&state.q("SELECT revoked_at_epoch FROM auth_sessions WHERE id = ?"))
+            .bind(jti)
+            .fetch_optional(&state.pool)
+            .await?;
+    Ok(matches!(row, Some((None,))))
+}
+
+/// Lists unexpired, unrevoked sessions matching `filter`, newest first.
+pub async fn list_active_sessions(
+    state: &AppState,
+    filter: &SessionFilter,
+) -> Result<Vec<AuthSessionRow>, sqlx::Error> {
+    let (clause, binds) = filter.where_clause(now_epoch_seconds() as i64);
+    let sql = format!(
+        "SELECT {SESSION_COLUMNS} FROM auth_sessions WHERE {clause} ORDER BY issued_at_epoch DESC"
+    );
+    let sql = state.q(&sql);
+    let mut query = sqlx::query_as::<_, AuthSessionRow>(&sql);
+    for value in binds {
+        query = query.bind(value);
+    }
+    query.fetch_all(&state.pool).await
+}
+
+/// Revokes one session. Returns `false` when it is unknown or already revoked.
+pub async fn revoke_session(
+    state: &AppState,
+    jti: &str,
+    revoked_by: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(&state.q(
+        "UPDATE auth_sessions SET revoked_at_epoch = ?, revoked_by = ? WHERE id = ? AND revoked_at_epoch IS NULL",
+    ))
+    .bind(now_epoch_seconds() as i64)
+    .bind(revoked_by)
+    .bind(jti)
+    .execute(&state.pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revokes every active session matching `filter` and returns how many were revoked.
+pub async fn revoke_sessions(
+    state: &AppState,
+    filter: &SessionFilter,
+    revoked_by: &str,
+) -> Result<u64, sqlx::Error> {
+    let now = now_epoch_seconds() as i64;
+    let (clause, binds) = filter.where_clause(now);
+    let sql =
+        format!("UPDATE auth_sessions SET revoked_at_epoch = ?, revoked_by = ? WHERE {clause}");
+    let sql = state.q(&sql);
+    let mut query = sqlx::query(&sql).bind(now).bind(revoked_by);
+    for value in binds {
+        query = query.bind(value);
+    }
+    Ok(query.execute(&state.pool).await?.rows_affected())
+}
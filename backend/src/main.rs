@@ -1,5 +1,5 @@
 use backend::infrastructure::{
-    db_kind_from_url, ensure_sqlite_directory, run_migrations, AppConfig,
+    db_kind_from_url, ensure_sqlite_directory, run_migrations, run_retention_job, AppConfig,
 };
 use backend::{create_router, AppState};
 use sqlx::any::AnyPoolOptions;
@@ -45,6 +45,10 @@ async fn main() -> anyhow::Result<()> {
 
     let state = Arc::new(AppState::new_with_config(pool, db_kind, app_config));
 
+    if state.retention.is_enabled() {
+        tokio::spawn(run_retention_job(state.clone()));
+    }
+
     // Build our application with routes
     let app = create_router(state);
 
@@ -1,6 +1,7 @@
 use crate::cli::client::ApiClient;
 use crate::domain::models::{
-    CreateCodelab, CreateMaterial, CreateQuiz, CreateStep, UpdateStepsPayload,
+    CreateCodelab, CreateMaterial, CreateQuiz, CreateStep, InsertStepPayload, UpdateStepPayload,
+    UpdateStepsPayload,
 };
 use anyhow::{Context, Result};
 use rmcp::{
@@ -55,6 +56,24 @@ struct ScopedCodelabParams {
     codelab_id: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+struct RevisionDiffParams {
+    /// Stable codelab identifier.
+    codelab_id: String,
+    /// Revision to diff from, by identifier or number.
+    from: String,
+    /// Revision to diff to, by identifier or number; defaults to the latest.
+    to: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct RestoreRevisionParams {
+    /// Stable codelab identifier.
+    codelab_id: String,
+    /// Revision to restore, by identifier or number.
+    revision: String,
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 struct ResolveHelpRequestParams {
     /// Stable codelab identifier.
@@ -81,6 +100,16 @@ struct CreateCodelabInput {
     require_feedback: Option<bool>,
     /// Whether a submission is required before completion.
     require_submission: Option<bool>,
+    /// Maximum quiz attempts per attendee; 0 means unlimited.
+    quiz_max_attempts: Option<i32>,
+    /// Minimum quiz score percentage required to pass.
+    quiz_pass_percentage: Option<i32>,
+    /// Seat limit for attendees; 0 means unlimited.
+    max_attendees: Option<i32>,
+    /// Epoch seconds when registration opens; 0 removes the bound.
+    registration_opens_at: Option<i64>,
+    /// Epoch seconds when registration closes; 0 removes the bound.
+    registration_closes_at: Option<i64>,
     /// Optional facilitator guide markdown shown beside the steps.
     guide_markdown: Option<String>,
 }
@@ -105,16 +134,83 @@ struct UpdateCodelabInput {
     require_feedback: Option<bool>,
     /// Whether a submission is required before completion.
     require_submission: Option<bool>,
+    /// Maximum quiz attempts per attendee; 0 means unlimited.
+    quiz_max_attempts: Option<i32>,
+    /// Minimum quiz score percentage required to pass.
+    quiz_pass_percentage: Option<i32>,
+    /// Seat limit for attendees; 0 means unlimited.
+    max_attendees: Option<i32>,
+    /// Epoch seconds when registration opens; 0 removes the bound.
+    registration_opens_at: Option<i64>,
+    /// Epoch seconds when registration closes; 0 removes the bound.
+    registration_closes_at: Option<i64>,
     /// Optional facilitator guide markdown shown beside the steps.
     guide_markdown: Option<String>,
+    /// Codelab `version` the edit is based on; the update fails if it changed since.
+    expected_version: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 struct ReplaceStepsInput {
     /// Stable codelab identifier.
     codelab_id: String,
-    /// Ordered steps that should replace the existing step list.
+    /// Ordered steps that should replace the existing draft step list.
     steps: Vec<StepInput>,
+    /// Publish the draft to learners after saving it.
+    #[serde(default)]
+    publish: bool,
+    /// Codelab `steps_version` the edit is based on; the replace fails if the steps changed since.
+    expected_steps_version: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct InsertStepInput {
+    /// Stable codelab identifier.
+    codelab_id: String,
+    /// Step title shown in the workshop UI.
+    title: String,
+    /// Markdown content for the step body.
+    content_markdown: String,
+    /// 1-based position of the new step; appended when omitted.
+    position: Option<i32>,
+    /// Codelab `steps_version` the edit is based on; the insert fails if the steps changed since.
+    expected_steps_version: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct UpdateStepInput {
+    /// Stable codelab identifier.
+    codelab_id: String,
+    /// Identifier of the step to change.
+    step_id: String,
+    /// Step title shown in the workshop UI.
+    title: String,
+    /// Markdown content for the step body.
+    content_markdown: String,
+    /// Codelab `steps_version` the edit is based on; the update fails if the steps changed since.
+    expected_steps_version: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct StepIdParams {
+    /// Stable codelab identifier.
+    codelab_id: String,
+    /// Identifier of the step to delete.
+    step_id: String,
+    /// Codelab `steps_version` the edit is based on; the delete fails if the steps changed since.
+    expected_steps_version: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct MoveStepInput {
+    /// Stable codelab identifier.
+    codelab_id: String,
+    /// Identifier of the step to move.
+    step_id: String,
+    /// 1-based position the step should end up at.
+    position: i32,
+    /// Codelab `steps_version` the edit is based on; the move fails if the steps changed since.
+    expected_steps_version: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -552,7 +648,7 @@ impl OpenCodelabsMcpServer {
         let attendees = self
             .state
             .client
-            .get_attendees(id)
+            .get_attendees(id, None)
             .await
             .map_err(internal_error)?;
         Ok(json!(attendees))
@@ -1027,7 +1123,11 @@ impl OpenCodelabsMcpServer {
         let codelab = self
             .state
             .client
-            .update_codelab(&input.id, &into_update_codelab(&input))
+            .update_codelab(
+                &input.id,
+                &into_update_codelab(&input),
+                input.expected_version,
+            )
             .await
             .map_err(internal_error)?;
         Ok(tool_payload(json!(codelab)))
@@ -1035,7 +1135,7 @@ impl OpenCodelabsMcpServer {
 
     #[tool(
         name = "replace_codelab_steps",
-        description = "Replace the full ordered step list for a codelab. Requires an admin session."
+        description = "Replace the full ordered draft step list for a codelab. Learners keep seeing the published version until `publish` is set or `publish_codelab` is called. Requires an admin session."
     )]
     async fn replace_codelab_steps(
         &self,
@@ -1043,6 +1143,8 @@ impl OpenCodelabsMcpServer {
     ) -> Result<Json<McpToolPayload>, McpError> {
         self.require_admin_session("Replacing codelab steps")?;
         let codelab_id = params.0.codelab_id.clone();
+        let publish = params.0.publish;
+        let expected_steps_version = params.0.expected_steps_version;
         let payload = json!({
             "steps": params
                 .0
@@ -1052,22 +1154,209 @@ impl OpenCodelabsMcpServer {
                     id: step.id,
                     title: step.title,
                     content_markdown: step.content_markdown,
+                    duration_minutes: None,
                 })
                 .collect::<Vec<_>>(),
         });
         let payload: UpdateStepsPayload =
             serde_json::from_value(payload).map_err(internal_error)?;
-        self.state
+        let steps_update = self
+            .state
             .client
-            .push_steps(&codelab_id, &payload)
+            .push_steps(&codelab_id, &payload, expected_steps_version)
             .await
             .map_err(internal_error)?;
+        let publication = if publish {
+            Some(
+                self.state
+                    .client
+                    .publish_codelab(&codelab_id)
+                    .await
+                    .map_err(internal_error)?,
+            )
+        } else {
+            None
+        };
         Ok(tool_payload(json!({
             "status": "ok",
             "codelab_id": codelab_id,
+            "steps_version": steps_update.steps_version,
+            "publication": publication,
         })))
     }
 
+    #[tool(
+        name = "insert_codelab_step",
+        description = "Insert one draft step at a 1-based position, or after the last step. Other step IDs stay the same and attendee progress follows the shifted steps. Requires an admin session."
+    )]
+    async fn insert_codelab_step(
+        &self,
+        params: Parameters<InsertStepInput>,
+    ) -> Result<Json<McpToolPayload>, McpError> {
+        self.require_admin_session("Inserting a codelab step")?;
+        let input = params.0;
+        let payload = InsertStepPayload {
+            title: input.title,
+            content_markdown: input.content_markdown,
+            position: input.position,
+        };
+        let change = self
+            .state
+            .client
+            .insert_step(&input.codelab_id, &payload, input.expected_steps_version)
+            .await
+            .map_err(internal_error)?;
+        Ok(tool_payload(json!(change)))
+    }
+
+    #[tool(
+        name = "update_codelab_step",
+        description = "Replace the title and markdown of one draft step without touching the others. Requires an admin session."
+    )]
+    async fn update_codelab_step(
+        &self,
+        params: Parameters<UpdateStepInput>,
+    ) -> Result<Json<McpToolPayload>, McpError> {
+        self.require_admin_session("Updating a codelab step")?;
+        let input = params.0;
+        let payload = UpdateStepPayload {
+            title: input.title,
+            content_markdown: input.content_markdown,
+        };
+        let change = self
+            .state
+            .client
+            .update_step(
+                &input.codelab_id,
+                &input.step_id,
+                &payload,
+                input.expected_steps_version,
+            )
+            .await
+            .map_err(internal_error)?;
+        Ok(tool_payload(json!(change)))
+    }
+
+    #[tool(
+        name = "delete_codelab_step",
+        description = "Delete one draft step. Attendees on it continue with the step that followed it. Requires an admin session."
+    )]
+    async fn delete_codelab_step(
+        &self,
+        params: Parameters<StepIdParams>,
+    ) -> Result<Json<McpToolPayload>, McpError> {
+        self.require_admin_session("Deleting a codelab step")?;
+        let input = params.0;
+        let change = self
+            .state
+            .client
+            .delete_step(
+                &input.codelab_id,
+                &input.step_id,
+                input.expected_steps_version,
+            )
+            .await
+            .map_err(internal_error)?;
+        Ok(tool_payload(json!(change)))
+    }
+
+    #[tool(
+        name = "move_codelab_step",
+        description = "Move one draft step to a 1-based position; attendee progress follows the moved steps. Requires an admin session."
+    )]
+    async fn move_codelab_step(
+        &self,
+        params: Parameters<MoveStepInput>,
+    ) -> Result<Json<McpToolPayload>, McpError> {
+        self.require_admin_session("Moving a codelab step")?;
+        let input = params.0;
+        let change = self
+            .state
+            .client
+            .move_step(
+                &input.codelab_id,
+                &input.step_id,
+                input.position,
+                input.expected_steps_version,
+            )
+            .await
+            .map_err(internal_error)?;
+        Ok(tool_payload(json!(change)))
+    }
+
+    #[tool(
+        name = "publish_codelab",
+        description = "Publish a codelab's draft guide and steps so learners see them, and notify connected clients. Requires an admin session."
+    )]
+    async fn publish_codelab(
+        &self,
+        params: Parameters<ScopedCodelabParams>,
+    ) -> Result<Json<McpToolPayload>, McpError> {
+        self.require_admin_session("Publishing a codelab")?;
+        let publication = self
+            .state
+            .client
+            .publish_codelab(&params.0.codelab_id)
+            .await
+            .map_err(internal_error)?;
+        Ok(tool_payload(json!(publication)))
+    }
+
+    #[tool(
+        name = "list_codelab_revisions",
+        description = "List saved revisions of a codelab's guide and steps, newest first. Requires an admin session."
+    )]
+    async fn list_codelab_revisions(
+        &self,
+        params: Parameters<ScopedCodelabParams>,
+    ) -> Result<Json<McpToolPayload>, McpError> {
+        self.require_admin_session("Listing codelab revisions")?;
+        let revisions = self
+            .state
+            .client
+            .list_revisions(&params.0.codelab_id)
+            .await
+            .map_err(internal_error)?;
+        Ok(tool_payload(json!(revisions)))
+    }
+
+    #[tool(
+        name = "diff_codelab_revisions",
+        description = "Show a unified markdown diff between two codelab revisions. Requires an admin session."
+    )]
+    async fn diff_codelab_revisions(
+        &self,
+        params: Parameters<RevisionDiffParams>,
+    ) -> Result<Json<McpToolPayload>, McpError> {
+        self.require_admin_session("Diffing codelab revisions")?;
+        let input = params.0;
+        let diff = self
+            .state
+            .client
+            .diff_revisions(&input.codelab_id, &input.from, input.to.as_deref())
+            .await
+            .map_err(internal_error)?;
+        Ok(tool_payload(json!(diff)))
+    }
+
+    #[tool(
+        name = "restore_codelab_revision",
+        description = "Restore a codelab's guide and steps to an earlier revision. Requires an admin session."
+    )]
+    async fn restore_codelab_revision(
+        &self,
+        params: Parameters<RestoreRevisionParams>,
+    ) -> Result<Json<McpToolPayload>, McpError> {
+        self.require_admin_session("Restoring a codelab revision")?;
+        let revision = self
+            .state
+            .client
+            .restore_revision(&params.0.codelab_id, &params.0.revision)
+            .await
+            .map_err(internal_error)?;
+        Ok(tool_payload(json!(revision)))
+    }
+
     #[tool(
         name = "get_codelab_reference",
         description = "Return the built-in Open Codelabs reference payload."
@@ -1421,7 +1710,7 @@ impl OpenCodelabsMcpServer {
         let attendees = self
             .state
             .client
-            .get_attendees(&params.0.codelab_id)
+            .get_attendees(&params.0.codelab_id, None)
             .await
             .map_err(internal_error)?;
         Ok(tool_payload(json!(attendees)))
@@ -1647,6 +1936,11 @@ fn into_create_codelab(input: CreateCodelabInput) -> CreateCodelab {
         require_quiz: input.require_quiz,
         require_feedback: input.require_feedback,
         require_submission: input.require_submission,
+        quiz_max_attempts: input.quiz_max_attempts,
+        quiz_pass_percentage: input.quiz_pass_percentage,
+        max_attendees: input.max_attendees,
+        registration_opens_at: input.registration_opens_at,
+        registration_closes_at: input.registration_closes_at,
         guide_markdown: input.guide_markdown,
     }
 }
@@ -1661,6 +1955,11 @@ fn into_update_codelab(input: &UpdateCodelabInput) -> CreateCodelab {
         require_quiz: input.require_quiz,
         require_feedback: input.require_feedback,
         require_submission: input.require_submission,
+        quiz_max_attempts: input.quiz_max_attempts,
+        quiz_pass_percentage: input.quiz_pass_percentage,
+        max_attendees: input.max_attendees,
+        registration_opens_at: input.registration_opens_at,
+        registration_closes_at: input.registration_closes_at,
         guide_markdown: input.guide_markdown.clone(),
     }
 }
@@ -1,6 +1,7 @@
 //! Authentication helpers, JWT claims, and cookie builders.
 
 use axum::extract::{FromRef, FromRequestParts, State};
+use axum::http::header::AUTHORIZATION;
 use axum::http::request::Parts;
 use axum::http::StatusCode;
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
@@ -8,11 +9,13 @@ use cookie::time::Duration as CookieDuration;
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use rand::{distributions::Alphanumeric, Rng};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::infrastructure::database::AppState;
-use crate::utils::error::{forbidden, unauthorized};
+use crate::infrastructure::sessions::is_session_active;
+use crate::utils::error::{forbidden, internal_error, unauthorized};
 
 /// User roles supported by the built-in session system.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -42,6 +45,111 @@ impl Role {
     }
 }
 
+/// Fine-grained staff roles carried by admin sessions.
+///
+/// Every staff role signs in through the admin session cookie; the role only
+/// narrows which privileged endpoints the session may call.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StaffRole {
+    /// Full access, including user management and instance settings.
+    Owner,
+    /// Creates and edits codelab content, quizzes, materials, and workspaces.
+    Author,
+    /// Runs live sessions: attendees, submissions, feedback, and certificates.
+    Facilitator,
+    /// Teaching assistant limited to help requests and chat.
+    Ta,
+}
+
+impl StaffRole {
+    /// All staff roles in descending order of privilege.
+    pub const ALL: [StaffRole; 4] = [
+        StaffRole::Owner,
+        StaffRole::Author,
+        StaffRole::Facilitator,
+        StaffRole::Ta,
+    ];
+
+    /// Returns the stable string representation stored in claims and the `users` table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StaffRole::Owner => "owner",
+            StaffRole::Author => "author",
+            StaffRole::Facilitator => "facilitator",
+            StaffRole::Ta => "ta",
+        }
+    }
+
+    /// Parses a staff role string from claims, payloads, or persisted rows.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "owner" => Some(StaffRole::Owner),
+            "author" => Some(StaffRole::Author),
+            "facilitator" => Some(StaffRole::Facilitator),
+            "ta" => Some(StaffRole::Ta),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this role is allowed to exercise `permission`.
+    pub fn grants(&self, permission: Permission) -> bool {
+        match self {
+            StaffRole::Owner => true,
+            StaffRole::Author => matches!(permission, Permission::EditContent),
+            StaffRole::Facilitator => matches!(
+                permission,
+                Permission::RunSessions | Permission::AssistLearners
+            ),
+            StaffRole::Ta => matches!(permission, Permission::AssistLearners),
+        }
+    }
+}
+
+/// Privileged capabilities checked by [`AuthSession::require_permission`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Create, update, and delete staff accounts.
+    ManageUsers,
+    /// Instance settings, backups, audit logs, and update checks.
+    ManageSystem,
+    /// Codelab content, quizzes, materials, and workspaces.
+    EditContent,
+    /// Attendee rosters, submissions, feedback, and certificates.
+    RunSessions,
+    /// Help requests, chat, and the live admin websocket.
+    AssistLearners,
+}
+
+impl Permission {
+    /// Returns the stable string representation used in API responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ManageUsers => "manage_users",
+            Permission::ManageSystem => "manage_system",
+            Permission::EditContent => "edit_content",
+            Permission::RunSessions => "run_sessions",
+            Permission::AssistLearners => "assist_learners",
+        }
+    }
+
+    /// Parses a permission name as produced by [`Permission::as_str`].
+    pub fn parse(value: &str) -> Option<Self> {
+        Permission::ALL
+            .into_iter()
+            .find(|permission| permission.as_str() == value)
+    }
+
+    /// All permissions, in the order they are reported to clients.
+    pub const ALL: [Permission; 5] = [
+        Permission::ManageUsers,
+        Permission::ManageSystem,
+        Permission::EditContent,
+        Permission::RunSessions,
+        Permission::AssistLearners,
+    ];
+}
+
 /// JWT claims stored in the admin and attendee session cookies.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionClaims {
@@ -51,6 +159,24 @@ pub struct SessionClaims {
     pub role: String,
     /// Optional codelab scope for attendee sessions.
     pub codelab_id: Option<String>,
+    /// Serialized [`StaffRole`] for admin sessions.
+    ///
+    /// Admin tokens issued before staff roles existed omit this claim and are
+    /// treated as [`StaffRole::Owner`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staff_role: Option<String>,
+    /// Permission names an API token is limited to.
+    ///
+    /// Cookie sessions leave this unset and receive every permission of their
+    /// staff role.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+    /// Session identifier tracked in the `auth_sessions` registry.
+    ///
+    /// Tokens issued before the registry existed omit it and stay valid until
+    /// they expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
     /// Token issuer expected during verification.
     pub iss: String,
     /// Token audience expected during verification.
@@ -61,6 +187,39 @@ pub struct SessionClaims {
     pub exp: usize,
 }
 
+impl SessionClaims {
+    /// Returns the staff role for admin claims, or `None` for attendee claims.
+    pub fn staff_role(&self) -> Option<StaffRole> {
+        if Role::from_str(&self.role) != Some(Role::Admin) {
+            return None;
+        }
+        match self.staff_role.as_deref() {
+            None => Some(StaffRole::Owner),
+            Some(value) => StaffRole::parse(value),
+        }
+    }
+
+    /// Returns whether these claims carry a staff role granting `permission`.
+    ///
+    /// API token claims must additionally list the permission in their scopes.
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        let granted = self
+            .staff_role()
+            .map(|role| role.grants(permission))
+            .unwrap_or(false);
+        granted
+            && self
+                .scopes
+                .as_ref()
+                .is_none_or(|scopes| scopes.iter().any(|scope| scope == permission.as_str()))
+    }
+
+    /// Returns whether these claims were derived from a personal API token.
+    pub fn is_api_token(&self) -> bool {
+        self.scopes.is_some()
+    }
+}
+
 /// Runtime authentication settings derived from environment variables.
 #[derive(Debug, Clone)]
 pub struct AuthConfig {
@@ -195,6 +354,9 @@ impl AuthConfig {
 }
 
 /// Authentication context extracted from request cookies.
+///
+/// An `Authorization: Bearer oc_pat_…` header takes precedence over cookies and
+/// yields scoped admin claims for the token's owner.
 #[derive(Debug, Clone)]
 pub struct AuthSession {
     /// First successfully verified claim set, regardless of role.
@@ -218,6 +380,92 @@ impl AuthSession {
         }
     }
 
+    /// Returns admin claims from a browser or CLI session.
+    ///
+    /// API tokens answer `403`: they are limited to the routes their scopes
+    /// cover and may not reach account, token, or sign-in endpoints.
+    pub fn require_interactive_admin(&self) -> Result<SessionClaims, (StatusCode, String)> {
+        let claims = self.require_admin()?;
+        if claims.is_api_token() {
+            return Err(forbidden());
+        }
+        Ok(claims)
+    }
+
+    /// Returns verified admin claims whose staff role grants `permission`.
+    ///
+    /// Responds with `401` when no admin session is present and `403` when the
+    /// session belongs to an attendee or to a staff role lacking the permission.
+    pub fn require_permission(
+        &self,
+        permission: Permission,
+    ) -> Result<SessionClaims, (StatusCode, String)> {
+        let claims = self.require_admin()?;
+        if claims.has_permission(permission) {
+            Ok(claims)
+        } else {
+            Err(forbidden())
+        }
+    }
+
+    /// Returns admin claims granting `permission` within a single codelab.
+    ///
+    /// Owners reach every codelab; other staff roles must also be assigned to
+    /// the codelab through the `codelab_staff` table.
+    pub async fn require_codelab_permission(
+        &self,
+        state: &AppState,
+        codelab_id: &str,
+        permission: Permission,
+    ) -> Result<SessionClaims, (StatusCode, String)> {
+        let claims = self.require_permission(permission)?;
+        if staff_can_access_codelab(state, &claims, codelab_id)
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(claims)
+        } else {
+            Err(forbidden())
+        }
+    }
+
+    /// Like [`AuthSession::require_codelab_permission`], but yields `None`
+    /// instead of an error so handlers can fall back to the attendee path.
+    pub async fn codelab_staff_claims(
+        &self,
+        state: &AppState,
+        codelab_id: &str,
+        permission: Permission,
+    ) -> Result<Option<SessionClaims>, (StatusCode, String)> {
+        let Ok(claims) = self.require_permission(permission) else {
+            return Ok(None);
+        };
+        if staff_can_access_codelab(state, &claims, codelab_id)
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(Some(claims))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns whether this is a staff session assigned to `codelab_id`.
+    ///
+    /// Owners always qualify; attendee and anonymous sessions never do.
+    pub async fn is_codelab_staff(
+        &self,
+        state: &AppState,
+        codelab_id: &str,
+    ) -> Result<bool, (StatusCode, String)> {
+        let Ok(claims) = self.require_admin() else {
+            return Ok(false);
+        };
+        staff_can_access_codelab(state, &claims, codelab_id)
+            .await
+            .map_err(internal_error)
+    }
+
     /// Returns verified attendee claims or an authorization error.
     pub fn require_attendee(&self) -> Result<SessionClaims, (StatusCode, String)> {
         if let Some(claims) = &self.attendee_claims {
@@ -233,6 +481,31 @@ impl AuthSession {
     }
 }
 
+/// Returns whether staff `claims` may work on `codelab_id`.
+///
+/// Owners are unscoped. Every other staff role only reaches codelabs it has
+/// been assigned to, and attendee claims never pass.
+pub async fn staff_can_access_codelab(
+    state: &AppState,
+    claims: &SessionClaims,
+    codelab_id: &str,
+) -> Result<bool, sqlx::Error> {
+    match claims.staff_role() {
+        Some(StaffRole::Owner) => Ok(true),
+        Some(_) => {
+            let assigned: Option<(String,)> = sqlx::query_as(
+                &state.q("SELECT user_id FROM codelab_staff WHERE codelab_id = ? AND user_id = ?"),
+            )
+            .bind(codelab_id)
+            .bind(&claims.sub)
+            .fetch_optional(&state.pool)
+            .await?;
+            Ok(assigned.is_some())
+        }
+        None => Ok(false),
+    }
+}
+
 impl<S> FromRequestParts<S> for AuthSession
 where
     Arc<AppState>: FromRef<S>,
@@ -245,6 +518,20 @@ where
             .await
             .map_err(|_| unauthorized())?;
 
+        if let Some(token) = bearer_api_token(parts) {
+            let admin_claims = verify_api_token(&state, token)
+                .await
+                .map_err(internal_error)?;
+            if admin_claims.is_none() {
+                eprintln!("AuthSession: API token verification failed");
+            }
+            return Ok(Self {
+                claims: admin_claims.clone(),
+                admin_claims,
+                attendee_claims: None,
+            });
+        }
+
         let jar = CookieJar::from_headers(&parts.headers);
         let admin_token = jar
             .get(&state.auth.cookie_name)
@@ -260,21 +547,31 @@ where
             );
         }
 
-        let admin_claims = admin_token.and_then(|value| {
-            let result = state.auth.verify_token(&value);
-            if result.is_none() {
-                eprintln!("AuthSession: Admin token verification failed");
+        let admin_claims = match admin_token {
+            Some(value) => {
+                let result = verify_session_token(&state, &value)
+                    .await
+                    .map_err(internal_error)?;
+                if result.is_none() {
+                    eprintln!("AuthSession: Admin token verification failed");
+                }
+                result
             }
-            result
-        });
+            None => None,
+        };
 
-        let attendee_claims = attendee_token.and_then(|value| {
-            let result = state.auth.verify_token(&value);
-            if result.is_none() {
-                eprintln!("AuthSession: Attendee token verification failed");
+        let attendee_claims = match attendee_token {
+            Some(value) => {
+                let result = verify_session_token(&state, &value)
+                    .await
+                    .map_err(internal_error)?;
+                if result.is_none() {
+                    eprintln!("AuthSession: Attendee token verification failed");
+                }
+                result
             }
-            result
-        });
+            None => None,
+        };
         let claims = admin_claims.clone().or(attendee_claims.clone());
         Ok(Self {
             claims,
@@ -284,6 +581,109 @@ where
     }
 }
 
+/// Verifies a session JWT and checks its `jti` against the session registry.
+///
+/// Revoked or unregistered sessions yield `None`. Tokens without a `jti`
+/// predate the registry and are accepted until they expire.
+pub async fn verify_session_token(
+    state: &AppState,
+    token: &str,
+) -> Result<Option<SessionClaims>, sqlx::Error> {
+    let Some(claims) = state.auth.verify_token(token) else {
+        return Ok(None);
+    };
+    match &claims.jti {
+        Some(jti) if !is_session_active(state, jti).await? => Ok(None),
+        _ => Ok(Some(claims)),
+    }
+}
+
+/// Prefix that identifies personal API tokens in `Authorization` headers.
+pub const API_TOKEN_PREFIX: &str = "oc_pat_";
+
+/// Returns the SHA-256 hex digest under which an API token is stored.
+pub fn hash_api_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+fn bearer_api_token(parts: &Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| token.starts_with(API_TOKEN_PREFIX))
+}
+
+/// Token id, owner, scopes, expiry, and the owner's `users.role`, if any.
+type ApiTokenLookup = (String, String, String, Option<i64>, Option<String>);
+
+/// Resolves a personal API token into admin claims.
+///
+/// The owner's current staff role is looked up on every request, so demoting
+/// or deleting a user takes effect immediately. Expired and unknown tokens
+/// yield `None`.
+pub async fn verify_api_token(
+    state: &AppState,
+    token: &str,
+) -> Result<Option<SessionClaims>, sqlx::Error> {
+    let row: Option<ApiTokenLookup> = sqlx::query_as(&state.q(
+        "SELECT t.id, t.user_id, t.scopes, t.expires_at_epoch, u.role \
+             FROM api_tokens t LEFT JOIN users u ON u.id = t.user_id \
+             WHERE t.token_hash = ?",
+    ))
+    .bind(hash_api_token(token))
+    .fetch_optional(&state.pool)
+    .await?;
+    let Some((id, user_id, scopes, expires_at, user_role)) = row else {
+        return Ok(None);
+    };
+
+    let now = now_epoch_seconds();
+    if expires_at.is_some_and(|expires_at| expires_at <= now as i64) {
+        return Ok(None);
+    }
+    let staff_role = if user_id == state.admin_id {
+        Some(StaffRole::Owner)
+    } else {
+        user_role.as_deref().and_then(StaffRole::parse)
+    };
+    let Some(staff_role) = staff_role else {
+        return Ok(None);
+    };
+
+    sqlx::query(
+        &state
+            .q("UPDATE api_tokens SET last_used_at = CAST(CURRENT_TIMESTAMP AS TEXT) WHERE id = ?"),
+    )
+    .bind(&id)
+    .execute(&state.pool)
+    .await?;
+
+    Ok(Some(SessionClaims {
+        sub: user_id,
+        role: Role::Admin.as_str().to_string(),
+        codelab_id: None,
+        staff_role: Some(staff_role.as_str().to_string()),
+        scopes: Some(
+            scopes
+                .split(',')
+                .filter(|scope| !scope.is_empty())
+                .map(str::to_string)
+                .collect(),
+        ),
+        jti: None,
+        iss: state.auth.issuer.clone(),
+        aud: state.auth.audience.clone(),
+        iat: now,
+        exp: expires_at
+            .map(|expires_at| expires_at as usize)
+            .unwrap_or(now + state.auth.admin_ttl.as_secs() as usize),
+    }))
+}
+
 /// Builds the HTTP-only admin session cookie for a freshly issued token.
 pub fn build_session_cookie(
     config: &AuthConfig,
@@ -364,6 +764,9 @@ mod tests {
             sub: "user-1".to_string(),
             role: role.as_str().to_string(),
             codelab_id: Some("codelab-1".to_string()),
+            staff_role: None,
+            scopes: None,
+            jti: None,
             iss: config.issuer.clone(),
             aud: config.audience.clone(),
             iat: now,
@@ -394,6 +797,9 @@ mod tests {
             sub: "admin".to_string(),
             role: Role::Admin.as_str().to_string(),
             codelab_id: None,
+            staff_role: None,
+            scopes: None,
+            jti: None,
             iss: config.issuer.clone(),
             aud: config.audience.clone(),
             iat: now,
@@ -413,6 +819,9 @@ mod tests {
             sub: "attendee".to_string(),
             role: Role::Attendee.as_str().to_string(),
             codelab_id: Some("codelab".to_string()),
+            staff_role: None,
+            scopes: None,
+            jti: None,
             iss: config.issuer.clone(),
             aud: config.audience.clone(),
             iat: now,
@@ -560,6 +969,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn staff_roles_grant_expected_permissions() {
+        for role in StaffRole::ALL {
+            assert_eq!(StaffRole::parse(role.as_str()), Some(role));
+        }
+        assert_eq!(StaffRole::parse("admin"), None);
+
+        assert!(Permission::ALL.iter().all(|p| StaffRole::Owner.grants(*p)));
+        assert!(StaffRole::Author.grants(Permission::EditContent));
+        assert!(!StaffRole::Author.grants(Permission::AssistLearners));
+        assert!(StaffRole::Facilitator.grants(Permission::RunSessions));
+        assert!(StaffRole::Facilitator.grants(Permission::AssistLearners));
+        assert!(!StaffRole::Facilitator.grants(Permission::EditContent));
+        assert!(StaffRole::Ta.grants(Permission::AssistLearners));
+        assert!(!StaffRole::Ta.grants(Permission::RunSessions));
+        assert!(!StaffRole::Ta.grants(Permission::ManageUsers));
+    }
+
+    #[test]
+    fn require_permission_checks_staff_role() {
+        let config = test_config();
+        let session_for = |staff_role: Option<&str>| {
+            let mut admin = claims(Role::Admin, &config);
+            admin.staff_role = staff_role.map(str::to_string);
+            AuthSession {
+                claims: Some(admin.clone()),
+                admin_claims: Some(admin),
+                attendee_claims: None,
+            }
+        };
+
+        // Tokens issued before staff roles existed keep full access.
+        let legacy = session_for(None);
+        assert!(legacy.require_permission(Permission::ManageUsers).is_ok());
+
+        let ta = session_for(Some("ta"));
+        assert!(ta.require_permission(Permission::AssistLearners).is_ok());
+        assert_eq!(
+            ta.require_permission(Permission::EditContent)
+                .unwrap_err()
+                .0,
+            StatusCode::FORBIDDEN
+        );
+        assert!(ta.require_admin().is_ok());
+
+        let unknown = session_for(Some("superuser"));
+        assert!(unknown
+            .require_permission(Permission::AssistLearners)
+            .is_err());
+
+        let attendee = claims(Role::Attendee, &config);
+        assert_eq!(attendee.staff_role(), None);
+        let attendee_session = AuthSession {
+            claims: Some(attendee.clone()),
+            admin_claims: None,
+            attendee_claims: Some(attendee),
+        };
+        assert_eq!(
+            attendee_session
+                .require_permission(Permission::AssistLearners)
+                .unwrap_err()
+                .0,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[test]
+    fn api_token_scopes_narrow_role_permissions() {
+        let config = test_config();
+        let mut token_claims = claims(Role::Admin, &config);
+        token_claims.staff_role = Some("facilitator".to_string());
+        token_claims.scopes = Some(vec!["assist_learners".to_string()]);
+        assert!(token_claims.is_api_token());
+        assert!(token_claims.has_permission(Permission::AssistLearners));
+        assert!(!token_claims.has_permission(Permission::RunSessions));
+
+        // A scope the role no longer grants stays ineffective.
+        token_claims.scopes = Some(vec!["edit_content".to_string()]);
+        assert!(!token_claims.has_permission(Permission::EditContent));
+
+        assert_eq!(
+            Permission::parse("run_sessions"),
+            Some(Permission::RunSessions)
+        );
+        assert_eq!(Permission::parse("admin"), None);
+    }
+
     #[test]
     fn cookie_builders_apply_expected_attributes() {
         let config = test_config();
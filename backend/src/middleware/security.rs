@@ -235,7 +235,10 @@ fn classify_rate_limit<'a>(
     method: &Method,
     config: &RateLimitConfig,
 ) -> (&'a str, u32, std::time::Duration) {
-    if path.starts_with("/api/login") {
+    if path.starts_with("/api/login")
+        || path.starts_with("/api/auth/oidc/")
+        || path.starts_with("/api/cli/auth/approve/")
+    {
         return ("login", config.login_limit, config.login_window);
     }
     if path.starts_with("/api/ai/") {
@@ -314,6 +317,18 @@ mod tests {
             classify_rate_limit("/api/login", &Method::POST, &config),
             ("login", 2, Duration::from_secs(2))
         );
+        assert_eq!(
+            classify_rate_limit("/api/auth/oidc/callback", &Method::GET, &config),
+            ("login", 2, Duration::from_secs(2))
+        );
+        assert_eq!(
+            classify_rate_limit("/api/cli/auth/approve/req-1", &Method::POST, &config),
+            ("login", 2, Duration::from_secs(2))
+        );
+        assert_eq!(
+            classify_rate_limit("/api/cli/auth/start", &Method::POST, &config),
+            ("general", 1, Duration::from_secs(1))
+        );
         assert_eq!(
             classify_rate_limit("/api/ai/stream", &Method::POST, &config),
             ("ai", 3, Duration::from_secs(3))
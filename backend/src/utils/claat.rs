@@ -0,0 +1,233 @@
+//! Reader and writer for codelabs in Google claat markdown.
+//!
+//! A claat document starts with `key: value` metadata lines, followed by the
+//! codelab title as a `#` heading. Each `##` heading starts a step, and a
+//! `Duration:` line right below it gives the estimated time as `mm:ss`,
+//! `hh:mm:ss`, or plain minutes.
+
+/// Codelab read from or written to claat markdown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClaatDocument {
+    /// Codelab title from the `#` heading.
+    pub title: String,
+    /// `summary` metadata.
+    pub summary: Option<String>,
+    /// `authors` metadata, also read from `author`.
+    pub authors: Option<String>,
+    /// Comma-separated `categories` metadata.
+    pub categories: Option<String>,
+    /// `id` metadata.
+    pub id: Option<String>,
+    /// `status` metadata such as `Draft` or `Published`.
+    pub status: Option<String>,
+    /// Steps in document order.
+    pub steps: Vec<ClaatStep>,
+}
+
+/// One `##` section of a claat document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClaatStep {
+    /// Step title from the `##` heading.
+    pub title: String,
+    /// Estimated minutes from the `Duration:` line, rounded up.
+    pub duration_minutes: Option<i32>,
+    /// Markdown below the heading and duration.
+    pub content_markdown: String,
+}
+
+/// Parses claat markdown into a codelab.
+///
+/// Headings inside fenced code blocks do not start steps. Unknown metadata
+/// keys and durations that cannot be read are ignored.
+pub fn parse_claat(input: &str) -> Result<ClaatDocument, String> {
+    let input = input.trim_start_matches('\u{feff}');
+    let mut document = ClaatDocument::default();
+    let mut lines = input.lines();
+    let mut has_title = false;
+
+    for line in lines.by_ref() {
+        if let Some(title) = line.strip_prefix("# ") {
+            document.title = title.trim().to_string();
+            has_title = true;
+            break;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = Some(value.trim().to_string()).filter(|value| !value.is_empty());
+        match key.trim().to_ascii_lowercase().as_str() {
+            "summary" => document.summary = value,
+            "author" | "authors" => document.authors = value,
+            "categories" | "category" => document.categories = value,
+            "id" => document.id = value,
+            "status" => document.status = value,
+            _ => {}
+        }
+    }
+    if !has_title || document.title.is_empty() {
+        return Err("missing codelab title".to_string());
+    }
+
+    let mut fence: Option<&str> = None;
+    let mut body: Vec<&str> = Vec::new();
+    for line in lines {
+        let trimmed = line.trim_start();
+        match fence {
+            Some(marker) if trimmed.starts_with(marker) => fence = None,
+            Some(_) => {}
+            None if trimmed.starts_with("```") => fence = Some("```"),
+            None if trimmed.starts_with("~~~") => fence = Some("~~~"),
+            None => {
+                if let Some(title) = line.strip_prefix("## ") {
+                    finish_step(&mut document.steps, &mut body);
+                    document.steps.push(ClaatStep {
+                        title: title.trim().to_string(),
+                        ..ClaatStep::default()
+                    });
+                    continue;
+                }
+                if let Some(step) = document.steps.last_mut() {
+                    let awaiting_duration = step.duration_minutes.is_none()
+                        && body.iter().all(|line| line.trim().is_empty());
+                    if awaiting_duration {
+                        if let Some(value) = duration_value(trimmed) {
+                            step.duration_minutes = parse_duration(value);
+                            continue;
+                        }
+                    }
+                }
+            }
+        }
+        if !document.steps.is_empty() {
+            body.push(line);
+        }
+    }
+    finish_step(&mut document.steps, &mut body);
+
+    if document.steps.is_empty() {
+        return Err("codelab has no steps".to_string());
+    }
+    Ok(document)
+}
+
+fn finish_step(steps: &mut [ClaatStep], body: &mut Vec<&str>) {
+    if let Some(step) = steps.last_mut() {
+        let start = body
+            .iter()
+            .position(|line| !line.trim().is_empty())
+            .unwrap_or(body.len());
+        step.content_markdown = body[start..].join("\n").trim_end().to_string();
+    }
+    body.clear();
+}
+
+fn duration_value(line: &str) -> Option<&str> {
+    let (key, value) = line.split_once(':')?;
+    key.trim()
+        .eq_ignore_ascii_case("duration")
+        .then_some(value.trim())
+}
+
+/// Reads `mm:ss`, `hh:mm:ss`, or plain minutes as whole minutes, rounding
+/// seconds up.
+fn parse_duration(value: &str) -> Option<i32> {
+    let parts = value
+        .split(':')
+        .map(|part| part.trim().parse::<i64>().ok().filter(|part| *part >= 0))
+        .collect::<Option<Vec<_>>>()?;
+    let seconds = match parts.as_slice() {
+        [minutes] => minutes * 60,
+        [minutes, seconds] => minutes * 60 + seconds,
+        [hours, minutes, seconds] => hours * 3600 + minutes * 60 + seconds,
+        _ => return None,
+    };
+    i32::try_from((seconds + 59) / 60).ok()
+}
+
+/// Writes a codelab as claat markdown.
+pub fn render_claat(document: &ClaatDocument) -> String {
+    let mut out = String::new();
+    let metadata = [
+        ("summary", &document.summary),
+        ("id", &document.id),
+        ("categories", &document.categories),
+        ("status", &document.status),
+        ("authors", &document.authors),
+    ];
+    for (key, value) in metadata {
+        if let Some(value) = value.as_deref().map(single_line).filter(|v| !v.is_empty()) {
+            out.push_str(&format!("{key}: {value}\n"));
+        }
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(&format!("# {}\n", single_line(&document.title)));
+    for step in &document.steps {
+        out.push_str(&format!("\n## {}\n", single_line(&step.title)));
+        if let Some(minutes) = step.duration_minutes {
+            out.push_str(&format!("Duration: {minutes}:00\n"));
+        }
+        let content = step.content_markdown.trim_matches('\n').trim_end();
+        if !content.is_empty() {
+            out.push('\n');
+            out.push_str(content);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn single_line(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "summary: Build a web app\nid: web-app\ncategories: Web,Cloud\nenvironments: Web\nstatus: Published\nauthors: Jane Doe\n\n# Your First Web App\n\n## Overview\nDuration: 2:30\n\nWhat you will build.\n\n## Setup\nDuration: 1:05:00\n\n```sh\n## not a step\nnpm install\n```\n\n## Wrap up\n\nDuration: later\n";
+
+    #[test]
+    fn claat_metadata_steps_and_durations_are_read() {
+        let document = parse_claat(SAMPLE).unwrap();
+        assert_eq!(document.title, "Your First Web App");
+        assert_eq!(document.summary.as_deref(), Some("Build a web app"));
+        assert_eq!(document.authors.as_deref(), Some("Jane Doe"));
+        assert_eq!(document.categories.as_deref(), Some("Web,Cloud"));
+        assert_eq!(document.status.as_deref(), Some("Published"));
+        let titles: Vec<&str> = document.steps.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["Overview", "Setup", "Wrap up"]);
+        assert_eq!(document.steps[0].duration_minutes, Some(3));
+        assert_eq!(document.steps[0].content_markdown, "What you will build.");
+        assert_eq!(document.steps[1].duration_minutes, Some(65));
+        assert_eq!(
+            document.steps[1].content_markdown,
+            "```sh\n## not a step\nnpm install\n```"
+        );
+        assert_eq!(document.steps[2].duration_minutes, None);
+        assert_eq!(document.steps[2].content_markdown, "");
+    }
+
+    #[test]
+    fn claat_documents_need_a_title_and_steps() {
+        assert_eq!(
+            parse_claat("summary: x\n\n## Step\nbody").unwrap_err(),
+            "missing codelab title"
+        );
+        assert_eq!(
+            parse_claat("# Title\n\nno steps").unwrap_err(),
+            "codelab has no steps"
+        );
+    }
+
+    #[test]
+    fn rendered_claat_reads_back_the_same() {
+        let mut document = parse_claat(SAMPLE).unwrap();
+        document.steps[2].content_markdown = "Done.".to_string();
+        let rendered = render_claat(&document);
+        assert!(rendered.starts_with("summary: Build a web app\nid: web-app\n"));
+        assert!(rendered.contains("\n## Overview\nDuration: 3:00\n\nWhat you will build.\n"));
+        assert_eq!(parse_claat(&rendered).unwrap(), document);
+    }
+}
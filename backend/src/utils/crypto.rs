@@ -9,7 +9,8 @@ use hmac::{Hmac, Mac};
 use pbkdf2::pbkdf2_hmac;
 use rand::rngs::OsRng;
 use rand::RngCore;
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
+use std::fmt;
 use subtle::ConstantTimeEq;
 
 const SALT_LEN: usize = 16;
@@ -22,6 +23,11 @@ const PBKDF2_ITERS: u32 = 100_000;
 /// Prefix used to version encrypted payload formats.
 pub const ENCRYPTION_PREFIX: &str = "v1:";
 
+/// Scheme identifier stored at the start of password hashes.
+pub const PASSWORD_HASH_SCHEME: &str = "pbkdf2-sha256";
+const PASSWORD_HASH_ITERS: u32 = 210_000;
+const KEYRING_DERIVATION_LABEL: &[u8] = b"open-codelabs secret encryption key";
+
 /// Encrypts plaintext with a password-derived AES-256-CBC key and HMAC tag.
 pub fn encrypt_with_password(plaintext: &str, password: &str) -> Result<String, String> {
     if plaintext.is_empty() {
@@ -103,6 +109,150 @@ pub fn decrypt_with_password(value: &str, password: &str) -> Result<String, Stri
     String::from_utf8(plaintext).map_err(|_| "invalid plaintext".to_string())
 }
 
+/// Encryption keys derived from the auth secrets for secrets stored at rest.
+///
+/// Sealed values are prefixed with the id of the key that encrypted them, so
+/// rotating `AUTH_SECRETS` keeps them readable while the old secret is still
+/// listed. Values from before the keyring carry no key id and were encrypted
+/// with the admin password.
+#[derive(Clone)]
+pub struct SecretKeyring {
+    /// `(key id, key)` pairs, the active key first.
+    keys: Vec<(String, String)>,
+}
+
+impl fmt::Debug for SecretKeyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretKeyring")
+            .field("key_id", &self.key_id())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecretKeyring {
+    /// Derives one key per auth secret; the first secret's key seals new values.
+    pub fn from_secrets(secrets: &[String]) -> Self {
+        let fallback = [String::new()];
+        let secrets = if secrets.is_empty() {
+            &fallback[..]
+        } else {
+            secrets
+        };
+        let mut keys: Vec<(String, String)> = Vec::with_capacity(secrets.len());
+        for secret in secrets {
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+                .expect("HMAC accepts keys of any length");
+            mac.update(KEYRING_DERIVATION_LABEL);
+            let key = STANDARD.encode(mac.finalize().into_bytes());
+            let key_id: String = Sha256::digest(key.as_bytes())[..8]
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect();
+            if !keys.iter().any(|(known, _)| *known == key_id) {
+                keys.push((key_id, key));
+            }
+        }
+        Self { keys }
+    }
+
+    /// Returns the id of the key that seals new values.
+    pub fn key_id(&self) -> &str {
+        &self.keys[0].0
+    }
+
+    /// Encrypts `plaintext` with the active key as `<key id>:v1:...`.
+    pub fn seal(&self, plaintext: &str) -> Result<String, String> {
+        if plaintext.is_empty() {
+            return Ok(String::new());
+        }
+        let (key_id, key) = &self.keys[0];
+        Ok(format!(
+            "{key_id}:{}",
+            encrypt_with_password(plaintext, key)?
+        ))
+    }
+
+    /// Decrypts a value from [`SecretKeyring::seal`], or a value encrypted with
+    /// `legacy_password` by [`encrypt_with_password`] before keys had ids.
+    pub fn open(&self, value: &str, legacy_password: &str) -> Result<String, String> {
+        if value.is_empty() {
+            return Ok(String::new());
+        }
+        if value.starts_with(ENCRYPTION_PREFIX) {
+            return decrypt_with_password(value, legacy_password);
+        }
+        let (key_id, ciphertext) = value
+            .split_once(':')
+            .ok_or_else(|| "unsupported ciphertext".to_string())?;
+        let (_, key) = self
+            .keys
+            .iter()
+            .find(|(known, _)| known == key_id)
+            .ok_or_else(|| format!("unknown encryption key {key_id}"))?;
+        decrypt_with_password(ciphertext, key)
+    }
+
+    /// Returns whether `value` is sealed with the active key and needs no
+    /// re-encryption.
+    pub fn is_current(&self, value: &str) -> bool {
+        value.is_empty()
+            || value
+                .split_once(':')
+                .is_some_and(|(key_id, _)| key_id == self.key_id())
+    }
+}
+
+/// Hashes a password for storage as `pbkdf2-sha256$<iterations>$<salt>$<hash>`.
+pub fn hash_password(password: &str) -> String {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    hash_password_with(password, &salt, PASSWORD_HASH_ITERS)
+}
+
+fn hash_password_with(password: &str, salt: &[u8], iterations: u32) -> String {
+    let mut derived = [0u8; KEY_LEN];
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut derived);
+    format!(
+        "{}${}${}${}",
+        PASSWORD_HASH_SCHEME,
+        iterations,
+        STANDARD.encode(salt),
+        STANDARD.encode(derived)
+    )
+}
+
+/// Checks a password against a hash produced by [`hash_password`] in constant time.
+///
+/// Malformed or unknown-scheme hashes never verify.
+pub fn verify_password(password: &str, stored: &str) -> bool {
+    let mut parts = stored.split('$');
+    let (Some(scheme), Some(iterations), Some(salt), Some(expected), None) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return false;
+    };
+    if scheme != PASSWORD_HASH_SCHEME {
+        return false;
+    }
+    let (Ok(iterations), Ok(salt), Ok(expected)) = (
+        iterations.parse::<u32>(),
+        STANDARD.decode(salt),
+        STANDARD.decode(expected),
+    ) else {
+        return false;
+    };
+    if iterations == 0 || expected.len() != KEY_LEN {
+        return false;
+    }
+    let mut derived = [0u8; KEY_LEN];
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, iterations, &mut derived);
+    derived.ct_eq(expected.as_slice()).into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,4 +308,44 @@ mod tests {
         let err = decrypt_with_password(&short, "pw").expect_err("must fail");
         assert_eq!(err, "invalid ciphertext");
     }
+
+    #[test]
+    fn keyring_opens_values_sealed_by_listed_and_legacy_keys() {
+        let old = SecretKeyring::from_secrets(&["old".to_string()]);
+        let rotated = SecretKeyring::from_secrets(&["new".to_string(), "old".to_string()]);
+        assert_ne!(old.key_id(), rotated.key_id());
+
+        let sealed = old.seal("JBSWY3DPEHPK3PXP").expect("seal");
+        assert!(sealed.starts_with(&format!("{}:v1:", old.key_id())));
+        assert_eq!(rotated.open(&sealed, "pw").unwrap(), "JBSWY3DPEHPK3PXP");
+        assert!(old.is_current(&sealed));
+        assert!(!rotated.is_current(&sealed));
+
+        let legacy = encrypt_with_password("passcode", "admin-pw").expect("encrypt");
+        assert_eq!(rotated.open(&legacy, "admin-pw").unwrap(), "passcode");
+        assert!(!rotated.is_current(&legacy));
+
+        let dropped = SecretKeyring::from_secrets(&["new".to_string()]);
+        assert!(dropped.open(&sealed, "pw").is_err());
+        assert_eq!(dropped.seal("").unwrap(), "");
+        assert!(format!("{dropped:?}").contains(dropped.key_id()));
+    }
+
+    #[test]
+    fn password_hash_round_trip_and_rejections() {
+        let hash = hash_password_with("correct horse", b"0123456789abcdef", 1_000);
+        assert!(hash.starts_with("pbkdf2-sha256$1000$"));
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("wrong horse", &hash));
+        assert!(!verify_password("correct horse", "plain-text"));
+        assert!(!verify_password(
+            "correct horse",
+            &hash.replace("pbkdf2-sha256", "md5")
+        ));
+        assert!(!verify_password("correct horse", &format!("{hash}$extra")));
+
+        let salted = hash_password("correct horse");
+        assert_ne!(salted, hash_password("correct horse"));
+        assert!(verify_password("correct horse", &salted));
+    }
 }
@@ -0,0 +1,91 @@
+//! Minimal RFC 4180 CSV reader for small uploaded tables such as rosters.
+
+/// Splits CSV text into records of fields.
+///
+/// Supports quoted fields with embedded commas, newlines, and doubled quotes,
+/// and both `\n` and `\r\n` line endings. Blank lines are skipped.
+pub fn parse_csv(input: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut field_started = false;
+    let mut chars = input.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(ch),
+            }
+            continue;
+        }
+        match ch {
+            '"' if field.is_empty() && !field_started => {
+                in_quotes = true;
+                field_started = true;
+            }
+            ',' => {
+                record.push(std::mem::take(&mut field));
+                field_started = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                finish_record(&mut records, &mut record, &mut field, field_started);
+                field_started = false;
+            }
+            _ => {
+                field.push(ch);
+                field_started = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err("unterminated quoted field".to_string());
+    }
+    finish_record(&mut records, &mut record, &mut field, field_started);
+    Ok(records)
+}
+
+fn finish_record(
+    records: &mut Vec<Vec<String>>,
+    record: &mut Vec<String>,
+    field: &mut String,
+    field_started: bool,
+) {
+    if record.is_empty() && field.is_empty() && !field_started {
+        return;
+    }
+    record.push(std::mem::take(field));
+    records.push(std::mem::take(record));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_csv_handles_quotes_and_line_endings() {
+        let records = parse_csv(
+            "\u{feff}name,email\r\n\"Lovelace, Ada\",ada@example.com\n\n\"Say \"\"hi\"\"\",\n",
+        )
+        .unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["name".to_string(), "email".to_string()],
+                vec!["Lovelace, Ada".to_string(), "ada@example.com".to_string()],
+                vec!["Say \"hi\"".to_string(), String::new()],
+            ]
+        );
+
+        let records = parse_csv("\"multi\nline\",x").unwrap();
+        assert_eq!(records[0][0], "multi\nline");
+        assert!(parse_csv("\"open,x").is_err());
+    }
+}
@@ -0,0 +1,216 @@
+//! Line-based unified diffs for comparing markdown documents.
+
+/// Number of unchanged lines shown around each change.
+pub const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Largest number of inserted and deleted lines a diff is computed for;
+/// beyond it [`unified_diff`] only reports that the texts differ.
+pub const MAX_DIFF_EDITS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Returns the shortest edit script turning `old` into `new` (Myers, 1986).
+///
+/// Only the diagonals reachable at each cost are kept, so memory grows with
+/// the square of the number of edits rather than with the document size.
+/// Returns `None` once more than `max_edits` edits would be needed.
+fn edit_script(old: &[&str], new: &[&str], max_edits: usize) -> Option<Vec<Edit>> {
+    let n = old.len() as isize;
+    let m = new.len() as isize;
+    let max = n + m;
+    let limit = max.min(max_edits as isize);
+    let offset = max + 1;
+    let mut v = vec![0isize; (2 * max + 3) as usize];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    let mut found = false;
+    'search: for d in 0..=limit {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let idx = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                found = true;
+                break 'search;
+            }
+        }
+    }
+    if !found {
+        return None;
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, row) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        if d == 0 {
+            edits.extend((0..x).map(|_| Edit::Equal));
+            break;
+        }
+        let k = x - y;
+        let at = |k: isize| row[(k + d) as usize];
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        edits.push(if x == prev_x {
+            Edit::Insert
+        } else {
+            Edit::Delete
+        });
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    Some(edits)
+}
+
+/// Renders a unified diff of two texts, compared line by line.
+///
+/// Returns an empty string when the texts have the same lines, and a single
+/// `Files ... differ` line when they need more than [`MAX_DIFF_EDITS`] edits.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let Some(edits) = edit_script(&old_lines, &new_lines, MAX_DIFF_EDITS) else {
+        return format!("Files {old_label} and {new_label} differ\n");
+    };
+
+    // Positions in both texts before each edit.
+    let mut positions = Vec::with_capacity(edits.len() + 1);
+    let (mut o, mut n) = (0usize, 0usize);
+    for edit in &edits {
+        positions.push((o, n));
+        match edit {
+            Edit::Equal => {
+                o += 1;
+                n += 1;
+            }
+            Edit::Delete => o += 1,
+            Edit::Insert => n += 1,
+        }
+    }
+    positions.push((o, n));
+
+    // Group changes whose context windows touch into hunks.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, edit) in edits.iter().enumerate() {
+        if *edit == Edit::Equal {
+            continue;
+        }
+        let start = i.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (i + 1 + DIFF_CONTEXT_LINES).min(edits.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- {old_label}\n+++ {new_label}\n");
+    for (start, end) in hunks {
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        let old_count = old_end - old_start;
+        let new_count = new_end - new_start;
+        // Unified diffs number an empty range after the line it follows.
+        let old_first = if old_count == 0 {
+            old_start
+        } else {
+            old_start + 1
+        };
+        let new_first = if new_count == 0 {
+            new_start
+        } else {
+            new_start + 1
+        };
+        out.push_str(&format!(
+            "@@ -{old_first},{old_count} +{new_first},{new_count} @@\n"
+        ));
+        for (edit, &(o, n)) in edits[start..end].iter().zip(&positions[start..end]) {
+            let (marker, line) = match edit {
+                Edit::Equal => (' ', old_lines[o]),
+                Edit::Delete => ('-', old_lines[o]),
+                Edit::Insert => ('+', new_lines[n]),
+            };
+            out.push(marker);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_texts_have_no_diff() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb", "old", "new"), "");
+        assert_eq!(unified_diff("", "", "old", "new"), "");
+    }
+
+    #[test]
+    fn changes_are_grouped_into_hunks_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n14\n15\n";
+        let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n15\n16\n";
+        let diff = unified_diff(old, new, "revision 1", "revision 2");
+        assert_eq!(
+            diff,
+            "--- revision 1\n+++ revision 2\n\
+             @@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n\
+             @@ -11,5 +11,5 @@\n 11\n 12\n 13\n-14\n 15\n+16\n"
+        );
+    }
+
+    #[test]
+    fn empty_sides_use_zero_length_ranges() {
+        assert_eq!(
+            unified_diff("", "# Title\n", "a", "b"),
+            "--- a\n+++ b\n@@ -0,0 +1,1 @@\n+# Title\n"
+        );
+        assert_eq!(
+            unified_diff("x\ny\n", "", "a", "b"),
+            "--- a\n+++ b\n@@ -1,2 +0,0 @@\n-x\n-y\n"
+        );
+    }
+
+    #[test]
+    fn large_rewrites_only_report_that_texts_differ() {
+        let old: String = (0..MAX_DIFF_EDITS).map(|i| format!("old {i}\n")).collect();
+        let new: String = (0..MAX_DIFF_EDITS).map(|i| format!("new {i}\n")).collect();
+        assert_eq!(
+            unified_diff(&old, &new, "revision 1", "revision 2"),
+            "Files revision 1 and revision 2 differ\n"
+        );
+
+        let appended = format!("{old}tail\n");
+        assert!(unified_diff(&old, &appended, "a", "b").ends_with("+tail\n"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Entity tags for optimistic concurrency on versioned resources.
+//!
+//! Versioned resources answer with `ETag: "<version>"`. Writers send the
+//! version they read back in `If-Match`; a write against a newer version is
+//! rejected with `409 Conflict` carrying the current version.
+
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+/// Body of a `409 Conflict` caused by a stale `If-Match`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionConflict {
+    /// Human-readable explanation.
+    pub error: String,
+    /// Version the resource is at now.
+    pub current_version: i32,
+}
+
+/// Formats a resource version as a strong entity tag.
+pub fn version_etag(version: i32) -> String {
+    format!("\"{version}\"")
+}
+
+/// Reads the version a writer expects from the `If-Match` header.
+///
+/// Returns `None` when the header is missing or `*`. Weak tags and bare
+/// numbers are accepted as well.
+pub fn parse_if_match(headers: &HeaderMap) -> Result<Option<i32>, (StatusCode, String)> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let invalid = || {
+        (
+            StatusCode::BAD_REQUEST,
+            "If-Match must be a version ETag".to_string(),
+        )
+    };
+    let value = value.to_str().map_err(|_| invalid())?.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    let value = value.strip_prefix("W/").unwrap_or(value);
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    value.parse::<i32>().map(Some).map_err(|_| invalid())
+}
+
+/// Builds the `409 Conflict` response for a write against a stale version.
+pub fn version_conflict(message: &str, current_version: i32) -> Response {
+    (
+        StatusCode::CONFLICT,
+        [(header::ETAG, version_etag(current_version))],
+        Json(VersionConflict {
+            error: message.to_string(),
+            current_version,
+        }),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn if_match(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MATCH, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn if_match_accepts_strong_weak_and_bare_versions() {
+        assert_eq!(parse_if_match(&HeaderMap::new()).unwrap(), None);
+        assert_eq!(parse_if_match(&if_match("*")).unwrap(), None);
+        assert_eq!(parse_if_match(&if_match("\"7\"")).unwrap(), Some(7));
+        assert_eq!(parse_if_match(&if_match("W/\"7\"")).unwrap(), Some(7));
+        assert_eq!(parse_if_match(&if_match(" 7 ")).unwrap(), Some(7));
+        assert_eq!(
+            parse_if_match(&if_match("\"abc\"")).unwrap_err().0,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn conflict_carries_current_version() {
+        let response = version_conflict("Codelab was changed", 4);
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response.headers()[header::ETAG], "\"4\"");
+    }
+}
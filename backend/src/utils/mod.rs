@@ -1,15 +1,51 @@
 //! Small reusable utility helpers used across the backend.
 
+/// Google claat markdown import and export of codelabs.
+pub mod claat;
 /// Symmetric encryption helpers for secrets stored at rest.
 pub mod crypto;
+/// Minimal CSV reader for uploaded tables.
+pub mod csv;
+/// Line-based unified diffs for markdown documents.
+pub mod diff;
 /// Common HTTP error response builders.
 pub mod error;
+/// Version entity tags and `If-Match` checks.
+pub mod etag;
+/// Operational transformation of concurrent text edits.
+pub mod ot;
+/// Minimal PDF writer for server-rendered documents.
+pub mod pdf;
+/// QR Code encoder used for links in generated documents.
+pub mod qr;
+/// Ed25519 signing helpers for verifiable documents.
+pub mod signing;
+/// RFC 6238 one-time passwords for two-factor sign-in.
+pub mod totp;
 /// Payload validation helpers shared by handlers.
 pub mod validation;
 
+#[doc(inline)]
+pub use claat::*;
 #[doc(inline)]
 pub use crypto::*;
 #[doc(inline)]
+pub use csv::*;
+#[doc(inline)]
+pub use diff::*;
+#[doc(inline)]
 pub use error::*;
 #[doc(inline)]
+pub use etag::*;
+#[doc(inline)]
+pub use ot::*;
+#[doc(inline)]
+pub use pdf::*;
+#[doc(inline)]
+pub use qr::*;
+#[doc(inline)]
+pub use signing::*;
+#[doc(inline)]
+pub use totp::*;
+#[doc(inline)]
 pub use validation::*;
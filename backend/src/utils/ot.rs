@@ -0,0 +1,378 @@
+//! Operational transformation of plain-text edits.
+//!
+//! Operations use the wire format of ot.js: a JSON array where a positive
+//! number retains that many characters, a negative number deletes that many,
+//! and a string inserts itself. Lengths count UTF-16 code units so positions
+//! match JavaScript strings and browser text selections.
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Reasons an operation cannot be applied or transformed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OtError {
+    /// The operation expects a document of a different length.
+    LengthMismatch {
+        /// Length the operation was built for.
+        expected: usize,
+        /// Length of the document or operation it met.
+        actual: usize,
+    },
+    /// The operation splits a character encoded as a UTF-16 surrogate pair.
+    SplitCharacter,
+}
+
+impl fmt::Display for OtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OtError::LengthMismatch { expected, actual } => write!(
+                f,
+                "operation expects a document of length {expected}, got {actual}"
+            ),
+            OtError::SplitCharacter => write!(f, "operation splits a character"),
+        }
+    }
+}
+
+impl std::error::Error for OtError {}
+
+/// One component of a [`TextOperation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpComponent {
+    /// Keeps the next characters unchanged.
+    Retain(usize),
+    /// Inserts text at the current position.
+    Insert(String),
+    /// Removes the next characters.
+    Delete(usize),
+}
+
+/// Number of UTF-16 code units in `text`.
+pub fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// An edit that turns a document of `base_len` into one of `target_len`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextOperation {
+    components: Vec<OpComponent>,
+    base_len: usize,
+    target_len: usize,
+}
+
+impl TextOperation {
+    /// Creates an empty operation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Length of the document the operation applies to.
+    pub fn base_len(&self) -> usize {
+        self.base_len
+    }
+
+    /// Length of the document the operation produces.
+    pub fn target_len(&self) -> usize {
+        self.target_len
+    }
+
+    /// Keeps the next `n` characters.
+    pub fn retain(mut self, n: usize) -> Self {
+        if n == 0 {
+            return self;
+        }
+        self.base_len += n;
+        self.target_len += n;
+        if let Some(OpComponent::Retain(last)) = self.components.last_mut() {
+            *last += n;
+        } else {
+            self.components.push(OpComponent::Retain(n));
+        }
+        self
+    }
+
+    /// Inserts `text` at the current position.
+    ///
+    /// Inserts are kept before a delete at the same position so equivalent
+    /// operations compare equal.
+    pub fn insert(mut self, text: &str) -> Self {
+        if text.is_empty() {
+            return self;
+        }
+        self.target_len += utf16_len(text);
+        let len = self.components.len();
+        match self.components.as_mut_slice() {
+            [.., OpComponent::Insert(last)] => last.push_str(text),
+            [.., OpComponent::Insert(before), OpComponent::Delete(_)] => before.push_str(text),
+            [.., OpComponent::Delete(_)] => {
+                self.components
+                    .insert(len - 1, OpComponent::Insert(text.to_string()));
+            }
+            _ => self.components.push(OpComponent::Insert(text.to_string())),
+        }
+        self
+    }
+
+    /// Removes the next `n` characters.
+    pub fn delete(mut self, n: usize) -> Self {
+        if n == 0 {
+            return self;
+        }
+        self.base_len += n;
+        if let Some(OpComponent::Delete(last)) = self.components.last_mut() {
+            *last += n;
+        } else {
+            self.components.push(OpComponent::Delete(n));
+        }
+        self
+    }
+
+    /// Applies the operation to `text`.
+    pub fn apply(&self, text: &str) -> Result<String, OtError> {
+        let units: Vec<u16> = text.encode_utf16().collect();
+        if units.len() != self.base_len {
+            return Err(OtError::LengthMismatch {
+                expected: self.base_len,
+                actual: units.len(),
+            });
+        }
+        let mut out: Vec<u16> = Vec::with_capacity(self.target_len);
+        let mut pos = 0;
+        for component in &self.components {
+            match component {
+                OpComponent::Retain(n) => {
+                    out.extend_from_slice(&units[pos..pos + n]);
+                    pos += n;
+                }
+                OpComponent::Insert(inserted) => out.extend(inserted.encode_utf16()),
+                OpComponent::Delete(n) => pos += n,
+            }
+        }
+        String::from_utf16(&out).map_err(|_| OtError::SplitCharacter)
+    }
+
+    /// Moves a cursor position in the base document to the matching position
+    /// after the operation. Text inserted at the cursor ends up before it.
+    pub fn transform_position(&self, position: usize) -> usize {
+        let mut moved = position;
+        let mut index = 0;
+        for component in &self.components {
+            if index > position {
+                break;
+            }
+            match component {
+                OpComponent::Retain(n) => index += n,
+                OpComponent::Insert(text) => moved += utf16_len(text),
+                OpComponent::Delete(n) => {
+                    moved -= (*n).min(position - index);
+                    index += n;
+                }
+            }
+        }
+        moved
+    }
+
+    /// Transforms two concurrent operations on the same document.
+    ///
+    /// Returns `(a', b')` such that applying `a` then `b'` gives the same
+    /// document as applying `b` then `a'`. Inserts at the same position put
+    /// the text of `a` first.
+    pub fn transform(a: &Self, b: &Self) -> Result<(Self, Self), OtError> {
+        if a.base_len != b.base_len {
+            return Err(OtError::LengthMismatch {
+                expected: a.base_len,
+                actual: b.base_len,
+            });
+        }
+        let mut a_prime = Self::new();
+        let mut b_prime = Self::new();
+        let mut a_iter = a.components.iter().cloned();
+        let mut b_iter = b.components.iter().cloned();
+        let mut a_next = a_iter.next();
+        let mut b_next = b_iter.next();
+
+        loop {
+            match (a_next.take(), b_next.take()) {
+                (None, None) => break,
+                (Some(OpComponent::Insert(text)), other) => {
+                    b_prime = b_prime.retain(utf16_len(&text));
+                    a_prime = a_prime.insert(&text);
+                    a_next = a_iter.next();
+                    b_next = other;
+                }
+                (other, Some(OpComponent::Insert(text))) => {
+                    a_prime = a_prime.retain(utf16_len(&text));
+                    b_prime = b_prime.insert(&text);
+                    a_next = other;
+                    b_next = b_iter.next();
+                }
+                (Some(a_comp), Some(b_comp)) => {
+                    let (a_len, b_len) = (component_len(&a_comp), component_len(&b_comp));
+                    let n = a_len.min(b_len);
+                    match (&a_comp, &b_comp) {
+                        (OpComponent::Retain(_), OpComponent::Retain(_)) => {
+                            a_prime = a_prime.retain(n);
+                            b_prime = b_prime.retain(n);
+                        }
+                        (OpComponent::Delete(_), OpComponent::Retain(_)) => {
+                            a_prime = a_prime.delete(n);
+                        }
+                        (OpComponent::Retain(_), OpComponent::Delete(_)) => {
+                            b_prime = b_prime.delete(n);
+                        }
+                        // Both sides removed the same text.
+                        _ => {}
+                    }
+                    a_next = shorten(a_comp, n).or_else(|| a_iter.next());
+                    b_next = shorten(b_comp, n).or_else(|| b_iter.next());
+                }
+                // Equal base lengths mean both sides run out together.
+                (Some(_), None) | (None, Some(_)) => {
+                    unreachable!("operations with equal base lengths")
+                }
+            }
+        }
+        Ok((a_prime, b_prime))
+    }
+}
+
+/// Characters of the base document a retain or delete covers.
+fn component_len(component: &OpComponent) -> usize {
+    match component {
+        OpComponent::Retain(n) | OpComponent::Delete(n) => *n,
+        OpComponent::Insert(_) => 0,
+    }
+}
+
+/// Returns what is left of a retain or delete after consuming `n` characters.
+fn shorten(component: OpComponent, n: usize) -> Option<OpComponent> {
+    match component {
+        OpComponent::Retain(len) if len > n => Some(OpComponent::Retain(len - n)),
+        OpComponent::Delete(len) if len > n => Some(OpComponent::Delete(len - n)),
+        _ => None,
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum WireComponent {
+    Count(i64),
+    Text(String),
+}
+
+impl Serialize for TextOperation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let wire: Vec<WireComponent> = self
+            .components
+            .iter()
+            .map(|component| match component {
+                OpComponent::Retain(n) => WireComponent::Count(*n as i64),
+                OpComponent::Insert(text) => WireComponent::Text(text.clone()),
+                OpComponent::Delete(n) => WireComponent::Count(-(*n as i64)),
+            })
+            .collect();
+        wire.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TextOperation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = Vec::<WireComponent>::deserialize(deserializer)?;
+        let mut operation = TextOperation::new();
+        for component in wire {
+            operation = match component {
+                WireComponent::Count(0) => {
+                    return Err(D::Error::custom("operation components cannot be zero"))
+                }
+                WireComponent::Count(n) if n > 0 => operation.retain(n as usize),
+                WireComponent::Count(n) => operation.delete(n.unsigned_abs() as usize),
+                WireComponent::Text(text) => operation.insert(&text),
+            };
+        }
+        Ok(operation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations_round_trip_the_wire_format() {
+        let operation: TextOperation = serde_json::from_str(r#"[3, "ab", -2, 1]"#).unwrap();
+        assert_eq!(operation.base_len(), 6);
+        assert_eq!(operation.target_len(), 6);
+        assert_eq!(operation.apply("abcdef").unwrap(), "abcabf");
+        assert_eq!(
+            serde_json::to_string(&operation).unwrap(),
+            r#"[3,"ab",-2,1]"#
+        );
+        assert!(serde_json::from_str::<TextOperation>("[0]").is_err());
+        assert_eq!(
+            operation.apply("abc"),
+            Err(OtError::LengthMismatch {
+                expected: 6,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    fn lengths_count_utf16_code_units() {
+        let operation = TextOperation::new().retain(2).insert("!").retain(1);
+        assert_eq!(operation.apply("😀a").unwrap(), "😀!a");
+        let split = TextOperation::new().retain(1).delete(2);
+        assert_eq!(split.apply("😀a"), Err(OtError::SplitCharacter));
+    }
+
+    #[test]
+    fn transformed_operations_converge() {
+        let doc = "Hello world";
+        let cases = [
+            (
+                TextOperation::new().retain(5).insert(",").retain(6),
+                TextOperation::new().retain(6).delete(5).insert("there"),
+            ),
+            (
+                TextOperation::new().retain(2).delete(6).retain(3),
+                TextOperation::new()
+                    .retain(4)
+                    .delete(4)
+                    .insert("XY")
+                    .retain(3),
+            ),
+            (
+                TextOperation::new().insert("A").retain(11),
+                TextOperation::new().insert("B").retain(11),
+            ),
+        ];
+        for (a, b) in cases {
+            let (a_prime, b_prime) = TextOperation::transform(&a, &b).unwrap();
+            let left = b_prime.apply(&a.apply(doc).unwrap()).unwrap();
+            let right = a_prime.apply(&b.apply(doc).unwrap()).unwrap();
+            assert_eq!(left, right);
+        }
+        let (a_prime, _) = TextOperation::transform(
+            &TextOperation::new().insert("A").retain(11),
+            &TextOperation::new().insert("B").retain(11),
+        )
+        .unwrap();
+        assert_eq!(a_prime.apply("BHello world").unwrap(), "ABHello world");
+    }
+
+    #[test]
+    fn positions_follow_inserts_and_deletes() {
+        let operation = TextOperation::new()
+            .retain(2)
+            .insert("xyz")
+            .retain(3)
+            .delete(4)
+            .retain(1);
+        assert_eq!(operation.transform_position(0), 0);
+        assert_eq!(operation.transform_position(2), 5);
+        assert_eq!(operation.transform_position(4), 7);
+        assert_eq!(operation.transform_position(7), 8);
+        assert_eq!(operation.transform_position(10), 9);
+    }
+}
@@ -0,0 +1,688 @@
+//! Single-page PDF writer for server-rendered documents.
+//!
+//! Builds on `pdf-writer` for the file structure, and on `ttf-parser` and
+//! `subsetter` for embedded fonts. Supports the base-14 Helvetica fonts
+//! (WinAnsi text only), an optional embedded TrueType font subset for full
+//! Unicode text, raster images and simple vector shapes. Streams are
+//! Flate-compressed.
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use std::collections::BTreeMap;
+use std::io::Write as _;
+
+/// Largest image edge kept when embedding; bigger images are downscaled.
+const MAX_IMAGE_EDGE: u32 = 1200;
+
+/// Advance widths (1/1000 em) of Helvetica for ASCII 32..=126.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+/// Advance widths (1/1000 em) of Helvetica-Bold for ASCII 32..=126.
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// Fill or stroke color with components in `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb(pub f32, pub f32, pub f32);
+
+impl Rgb {
+    /// Black.
+    pub const BLACK: Rgb = Rgb(0.0, 0.0, 0.0);
+
+    /// Parses `#rrggbb` or `rrggbb`.
+    pub fn from_hex(value: &str) -> Option<Self> {
+        let hex = value.trim().trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |range: std::ops::Range<usize>| {
+            u8::from_str_radix(&hex[range], 16)
+                .ok()
+                .map(|v| v as f32 / 255.0)
+        };
+        Some(Self(channel(0..2)?, channel(2..4)?, channel(4..6)?))
+    }
+}
+
+/// Base-14 fonts available without embedding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StandardFont {
+    Helvetica,
+    HelveticaBold,
+}
+
+impl StandardFont {
+    fn base_name(self) -> &'static str {
+        match self {
+            StandardFont::Helvetica => "Helvetica",
+            StandardFont::HelveticaBold => "Helvetica-Bold",
+        }
+    }
+
+    fn width(self, byte: u8) -> u16 {
+        let table = match self {
+            StandardFont::Helvetica => &HELVETICA_WIDTHS,
+            StandardFont::HelveticaBold => &HELVETICA_BOLD_WIDTHS,
+        };
+        match byte {
+            32..=126 => table[(byte - 32) as usize],
+            0x91 | 0x92 => 222,
+            0x93 | 0x94 => 333,
+            0x95 => 350,
+            0x96 => 556,
+            0x85 | 0x97 => 1000,
+            _ => 556,
+        }
+    }
+}
+
+/// Handle to a font registered on a [`PdfDocument`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FontId(usize);
+
+/// Handle to an image registered on a [`PdfDocument`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageId(usize);
+
+enum PdfFont {
+    Standard(StandardFont),
+    TrueType(Box<TrueTypeFont>),
+}
+
+struct PdfImage {
+    width: u32,
+    height: u32,
+    rgb: Vec<u8>,
+    alpha: Option<Vec<u8>>,
+}
+
+/// A single-page PDF built from drawing commands.
+pub struct PdfDocument {
+    width: f32,
+    height: f32,
+    title: Option<String>,
+    content: Content,
+    fonts: Vec<PdfFont>,
+    images: Vec<PdfImage>,
+}
+
+impl PdfDocument {
+    /// Creates an empty page of the given size in points.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            title: None,
+            content: Content::new(),
+            fonts: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    /// Sets the document title shown by PDF viewers.
+    pub fn set_title(&mut self, title: &str) {
+        self.title = Some(title.to_string());
+    }
+
+    /// Registers a base-14 font.
+    pub fn add_standard_font(&mut self, font: StandardFont) -> FontId {
+        self.fonts.push(PdfFont::Standard(font));
+        FontId(self.fonts.len() - 1)
+    }
+
+    /// Registers a TrueType (`glyf` outline) font; only used glyphs are embedded.
+    pub fn add_truetype_font(&mut self, data: Vec<u8>) -> Result<FontId, String> {
+        let font = TrueTypeFont::parse(data)?;
+        self.fonts.push(PdfFont::TrueType(Box::new(font)));
+        Ok(FontId(self.fonts.len() - 1))
+    }
+
+    /// Decodes a PNG/JPEG/WebP/... image and registers it; returns its pixel size.
+    pub fn add_image(&mut self, encoded: &[u8]) -> Result<(ImageId, u32, u32), String> {
+        let mut decoded = image::load_from_memory(encoded).map_err(|err| err.to_string())?;
+        if decoded.width() > MAX_IMAGE_EDGE || decoded.height() > MAX_IMAGE_EDGE {
+            decoded = decoded.thumbnail(MAX_IMAGE_EDGE, MAX_IMAGE_EDGE);
+        }
+        let rgba = decoded.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let mut rgb = Vec::with_capacity((width * height * 3) as usize);
+        let mut alpha = Vec::with_capacity((width * height) as usize);
+        for pixel in rgba.pixels() {
+            rgb.extend_from_slice(&pixel.0[..3]);
+            alpha.push(pixel.0[3]);
+        }
+        let alpha = alpha.iter().any(|value| *value != 255).then_some(alpha);
+
+        self.images.push(PdfImage {
+            width,
+            height,
+            rgb,
+            alpha,
+        });
+        Ok((ImageId(self.images.len() - 1), width, height))
+    }
+
+    /// Returns whether every character of `text` can be drawn with `font`.
+    pub fn supports_text(&self, font: FontId, text: &str) -> bool {
+        match &self.fonts[font.0] {
+            PdfFont::Standard(_) => text.chars().all(|ch| win_ansi_byte(ch).is_some()),
+            PdfFont::TrueType(font) => text.chars().all(|ch| font.glyph_id(ch) != 0),
+        }
+    }
+
+    /// Measures `text` in points.
+    pub fn text_width(&self, font: FontId, size: f32, text: &str) -> f32 {
+        let units: f32 = match &self.fonts[font.0] {
+            PdfFont::Standard(standard) => text
+                .chars()
+                .map(|ch| standard.width(win_ansi_byte(ch).unwrap_or(b'?')) as f32)
+                .sum(),
+            PdfFont::TrueType(font) => text
+                .chars()
+                .map(|ch| font.advance_per_mille(font.glyph_id(ch)))
+                .sum(),
+        };
+        units * size / 1000.0
+    }
+
+    /// Draws `text` with its baseline starting at (`x`, `y`).
+    pub fn draw_text(&mut self, font: FontId, size: f32, x: f32, y: f32, text: &str, color: Rgb) {
+        let encoded = match &mut self.fonts[font.0] {
+            PdfFont::Standard(_) => text
+                .chars()
+                .map(|ch| win_ansi_byte(ch).unwrap_or(b'?'))
+                .collect(),
+            PdfFont::TrueType(font) => font.encode(text),
+        };
+        let name = font_name(font);
+        self.content
+            .begin_text()
+            .set_font(Name(name.as_bytes()), size)
+            .set_fill_rgb(color.0, color.1, color.2)
+            .next_line(x, y)
+            .show(Str(&encoded))
+            .end_text();
+    }
+
+    /// Draws `text` horizontally centered on `center_x`.
+    pub fn draw_text_centered(
+        &mut self,
+        font: FontId,
+        size: f32,
+        center_x: f32,
+        y: f32,
+        text: &str,
+        color: Rgb,
+    ) {
+        let width = self.text_width(font, size, text);
+        self.draw_text(font, size, center_x - width / 2.0, y, text, color);
+    }
+
+    /// Fills an axis-aligned rectangle whose lower-left corner is (`x`, `y`).
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Rgb) {
+        self.content
+            .set_fill_rgb(color.0, color.1, color.2)
+            .rect(x, y, width, height)
+            .fill_nonzero();
+    }
+
+    /// Strokes an axis-aligned rectangle outline.
+    pub fn stroke_rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        line_width: f32,
+        color: Rgb,
+    ) {
+        self.content
+            .set_stroke_rgb(color.0, color.1, color.2)
+            .set_line_width(line_width)
+            .rect(x, y, width, height)
+            .stroke();
+    }
+
+    /// Strokes a straight line.
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), line_width: f32, color: Rgb) {
+        self.content
+            .set_stroke_rgb(color.0, color.1, color.2)
+            .set_line_width(line_width)
+            .move_to(from.0, from.1)
+            .line_to(to.0, to.1)
+            .stroke();
+    }
+
+    /// Draws a registered image scaled into the given rectangle.
+    pub fn draw_image(&mut self, image: ImageId, x: f32, y: f32, width: f32, height: f32) {
+        let name = image_name(image.0);
+        self.content
+            .save_state()
+            .transform([width, 0.0, 0.0, height, x, y])
+            .x_object(Name(name.as_bytes()))
+            .restore_state();
+    }
+
+    /// Serializes the document.
+    pub fn finish(self) -> Vec<u8> {
+        let mut pdf = Pdf::new();
+        let mut next_ref = Ref::new(1);
+        let catalog = next_ref.bump();
+        let pages = next_ref.bump();
+        let page = next_ref.bump();
+        let content = next_ref.bump();
+
+        pdf.catalog(catalog).pages(pages);
+        pdf.pages(pages).kids([page]).count(1);
+        pdf.stream(content, &deflate(&self.content.finish()))
+            .filter(Filter::FlateDecode);
+
+        let mut font_refs = Vec::with_capacity(self.fonts.len());
+        for font in &self.fonts {
+            let id = next_ref.bump();
+            match font {
+                PdfFont::Standard(standard) => {
+                    pdf.type1_font(id)
+                        .base_font(Name(standard.base_name().as_bytes()))
+                        .encoding_predefined(Name(b"WinAnsiEncoding"));
+                }
+                PdfFont::TrueType(font) => font.write(&mut pdf, id, &mut next_ref),
+            }
+            font_refs.push(id);
+        }
+
+        let mut image_refs = Vec::with_capacity(self.images.len());
+        for image in &self.images {
+            let id = next_ref.bump();
+            let smask = image.alpha.as_ref().map(|alpha| {
+                let smask = next_ref.bump();
+                let compressed = deflate(alpha);
+                let mut xobject = pdf.image_xobject(smask, &compressed);
+                xobject.filter(Filter::FlateDecode);
+                xobject
+                    .width(image.width as i32)
+                    .height(image.height as i32)
+                    .bits_per_component(8);
+                xobject.color_space().device_gray();
+                xobject.finish();
+                smask
+            });
+            let compressed = deflate(&image.rgb);
+            let mut xobject = pdf.image_xobject(id, &compressed);
+            xobject.filter(Filter::FlateDecode);
+            xobject
+                .width(image.width as i32)
+                .height(image.height as i32)
+                .bits_per_component(8);
+            xobject.color_space().device_rgb();
+            if let Some(smask) = smask {
+                xobject.s_mask(smask);
+            }
+            xobject.finish();
+            image_refs.push(id);
+        }
+
+        let mut page_writer = pdf.page(page);
+        page_writer
+            .parent(pages)
+            .media_box(Rect::new(0.0, 0.0, self.width, self.height))
+            .contents(content);
+        let mut resources = page_writer.resources();
+        let mut fonts = resources.fonts();
+        for (index, id) in font_refs.iter().enumerate() {
+            fonts.pair(Name(font_name(FontId(index)).as_bytes()), *id);
+        }
+        fonts.finish();
+        let mut xobjects = resources.x_objects();
+        for (index, id) in image_refs.iter().enumerate() {
+            xobjects.pair(Name(image_name(index).as_bytes()), *id);
+        }
+        xobjects.finish();
+        resources.finish();
+        page_writer.finish();
+
+        if let Some(title) = &self.title {
+            pdf.document_info(next_ref.bump())
+                .title(TextStr(title))
+                .producer(TextStr("Open Codelabs"));
+        }
+        pdf.finish()
+    }
+}
+
+/// TrueType font that records which glyphs are used for subsetting.
+///
+/// Glyph IDs are kept when subsetting, so text is encoded as raw glyph IDs
+/// (`Identity-H`) and the CID-to-GID map is the identity.
+struct TrueTypeFont {
+    data: Vec<u8>,
+    used: BTreeMap<u16, char>,
+}
+
+impl TrueTypeFont {
+    fn parse(data: Vec<u8>) -> Result<Self, String> {
+        let face = ttf_parser::Face::parse(&data, 0)
+            .map_err(|err| format!("invalid TrueType font: {err}"))?;
+        let tables = face.tables();
+        if tables.glyf.is_none() {
+            return Err("only TrueType (glyf) fonts are supported".to_string());
+        }
+        let has_unicode_cmap = tables
+            .cmap
+            .is_some_and(|cmap| cmap.subtables.into_iter().any(|table| table.is_unicode()));
+        if !has_unicode_cmap {
+            return Err("TrueType font has no Unicode cmap".to_string());
+        }
+        Ok(Self {
+            data,
+            used: BTreeMap::new(),
+        })
+    }
+
+    fn face(&self) -> ttf_parser::Face<'_> {
+        ttf_parser::Face::parse(&self.data, 0).expect("font was validated in TrueTypeFont::parse")
+    }
+
+    fn glyph_id(&self, ch: char) -> u16 {
+        self.face().glyph_index(ch).map_or(0, |glyph| glyph.0)
+    }
+
+    fn advance_per_mille(&self, glyph: u16) -> f32 {
+        let face = self.face();
+        let advance = face
+            .glyph_hor_advance(ttf_parser::GlyphId(glyph))
+            .unwrap_or(0);
+        advance as f32 * 1000.0 / face.units_per_em() as f32
+    }
+
+    /// Encodes `text` as big-endian glyph IDs and records the glyphs as used.
+    fn encode(&mut self, text: &str) -> Vec<u8> {
+        let face = self.face();
+        let glyphs: Vec<(u16, char)> = text
+            .chars()
+            .map(|ch| (face.glyph_index(ch).map_or(0, |glyph| glyph.0), ch))
+            .collect();
+        let mut out = Vec::with_capacity(glyphs.len() * 2);
+        for (glyph, ch) in glyphs {
+            self.used.entry(glyph).or_insert(ch);
+            out.extend_from_slice(&glyph.to_be_bytes());
+        }
+        out
+    }
+
+    /// Writes the Type0 font as `id` and its descendants under fresh refs.
+    fn write(&self, pdf: &mut Pdf, id: Ref, next_ref: &mut Ref) {
+        let face = self.face();
+        let scale = |value: i16| value as f32 * 1000.0 / face.units_per_em() as f32;
+        let bbox = face.global_bounding_box();
+        let ascent = scale(face.ascender());
+
+        let mut glyphs: Vec<u16> = self.used.keys().copied().collect();
+        glyphs.insert(0, 0);
+        glyphs.dedup();
+        let subset = subsetter::subset(&self.data, 0, subsetter::Profile::pdf(&glyphs))
+            .unwrap_or_else(|err| {
+                tracing::warn!("Embedding the full font because subsetting failed: {}", err);
+                self.data.clone()
+            });
+
+        let font_file = next_ref.bump();
+        pdf.stream(font_file, &deflate(&subset))
+            .filter(Filter::FlateDecode)
+            .pair(Name(b"Length1"), subset.len() as i32);
+
+        let descriptor = next_ref.bump();
+        pdf.font_descriptor(descriptor)
+            .name(Name(b"OCSubset"))
+            .flags(FontFlags::SYMBOLIC)
+            .bbox(Rect::new(
+                scale(bbox.x_min),
+                scale(bbox.y_min),
+                scale(bbox.x_max),
+                scale(bbox.y_max),
+            ))
+            .italic_angle(0.0)
+            .ascent(ascent)
+            .descent(scale(face.descender()))
+            .cap_height(face.capital_height().map_or(ascent, scale))
+            .stem_v(80.0)
+            .font_file2(font_file);
+
+        let cid_font = next_ref.bump();
+        let mut cid = pdf.cid_font(cid_font);
+        cid.subtype(CidFontType::Type2)
+            .base_font(Name(b"OCSubset"))
+            .system_info(SystemInfo {
+                registry: Str(b"Adobe"),
+                ordering: Str(b"Identity"),
+                supplement: 0,
+            })
+            .font_descriptor(descriptor)
+            .default_width(1000.0)
+            .cid_to_gid_map_predefined(Name(b"Identity"));
+        let mut widths = cid.widths();
+        for glyph in self.used.keys() {
+            widths.consecutive(*glyph, [self.advance_per_mille(*glyph)]);
+        }
+        widths.finish();
+        cid.finish();
+
+        let mut cmap = UnicodeCmap::new(
+            Name(b"Adobe-Identity-UCS"),
+            SystemInfo {
+                registry: Str(b"Adobe"),
+                ordering: Str(b"UCS"),
+                supplement: 0,
+            },
+        );
+        for (glyph, ch) in &self.used {
+            cmap.pair(*glyph, *ch);
+        }
+        let to_unicode = next_ref.bump();
+        pdf.stream(to_unicode, &deflate(&cmap.finish()))
+            .filter(Filter::FlateDecode);
+
+        pdf.type0_font(id)
+            .base_font(Name(b"OCSubset"))
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_font)
+            .to_unicode(to_unicode);
+    }
+}
+
+/// Maps a character to its WinAnsiEncoding byte, if it has one.
+fn win_ansi_byte(ch: char) -> Option<u8> {
+    match ch {
+        ' '..='~' => Some(ch as u8),
+        '\u{a0}'..='\u{ff}' => Some(ch as u32 as u8),
+        '€' => Some(0x80),
+        '…' => Some(0x85),
+        '‘' => Some(0x91),
+        '’' => Some(0x92),
+        '“' => Some(0x93),
+        '”' => Some(0x94),
+        '•' => Some(0x95),
+        '–' => Some(0x96),
+        '—' => Some(0x97),
+        _ => None,
+    }
+}
+
+fn font_name(font: FontId) -> String {
+    format!("F{}", font.0)
+}
+
+fn image_name(index: usize) -> String {
+    format!("Im{index}")
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(data)
+        .expect("writing to an in-memory buffer cannot fail");
+    encoder
+        .finish()
+        .expect("writing to an in-memory buffer cannot fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::Content as ParsedContent;
+    use lopdf::{Document, Object};
+
+    /// Parses `pdf` back and returns it with its single page's decoded content.
+    fn parse(pdf: &[u8]) -> (Document, lopdf::ObjectId, ParsedContent) {
+        let document = Document::load_mem(pdf).expect("generated PDF parses");
+        let pages = document.get_pages();
+        assert_eq!(pages.len(), 1);
+        let page = pages[&1];
+        let content = document.get_page_content(page).unwrap();
+        let content = ParsedContent::decode(&content).unwrap();
+        (document, page, content)
+    }
+
+    fn shown_text(content: &ParsedContent) -> Vec<Vec<u8>> {
+        content
+            .operations
+            .iter()
+            .filter(|op| op.operator == "Tj")
+            .map(|op| op.operands[0].as_str().unwrap().to_vec())
+            .collect()
+    }
+
+    fn page_resource<'a>(
+        document: &'a Document,
+        page: lopdf::ObjectId,
+        kind: &[u8],
+        name: &[u8],
+    ) -> &'a lopdf::Dictionary {
+        let resources = document
+            .get_dictionary(page)
+            .unwrap()
+            .get_deref(b"Resources", document)
+            .and_then(Object::as_dict)
+            .unwrap();
+        let entries = resources
+            .get_deref(kind, document)
+            .and_then(Object::as_dict)
+            .unwrap();
+        match entries.get_deref(name, document).unwrap() {
+            Object::Stream(stream) => &stream.dict,
+            other => other.as_dict().unwrap(),
+        }
+    }
+
+    #[test]
+    fn finish_round_trips_through_a_pdf_parser() {
+        let mut doc = PdfDocument::new(842.0, 595.0);
+        doc.set_title("Certificate");
+        let font = doc.add_standard_font(StandardFont::HelveticaBold);
+        doc.draw_text(font, 12.0, 10.0, 20.0, "Hello (world)", Rgb::BLACK);
+        doc.stroke_rect(10.0, 10.0, 100.0, 50.0, 1.0, Rgb(0.2, 0.4, 1.0));
+        doc.line((0.0, 0.0), (842.0, 595.0), 2.0, Rgb::BLACK);
+        let pdf = doc.finish();
+
+        let (document, page, content) = parse(&pdf);
+        let media_box = document
+            .get_dictionary(page)
+            .unwrap()
+            .get(b"MediaBox")
+            .and_then(Object::as_array)
+            .unwrap()
+            .iter()
+            .map(|value| value.as_float().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(media_box, [0.0, 0.0, 842.0, 595.0]);
+        assert_eq!(shown_text(&content), [b"Hello (world)".to_vec()]);
+        let operators: Vec<&str> = content
+            .operations
+            .iter()
+            .map(|op| op.operator.as_str())
+            .collect();
+        assert!(operators.ends_with(&["RG", "w", "re", "S", "RG", "w", "m", "l", "S"]));
+
+        let font = page_resource(&document, page, b"Font", b"F0");
+        assert_eq!(
+            font.get(b"BaseFont").unwrap().as_name().unwrap(),
+            b"Helvetica-Bold"
+        );
+        let info = document
+            .trailer
+            .get_deref(b"Info", &document)
+            .and_then(Object::as_dict)
+            .unwrap();
+        let title = info.get(b"Title").and_then(Object::as_str).unwrap();
+        assert_eq!(title, b"Certificate");
+    }
+
+    #[test]
+    fn standard_fonts_measure_and_encode_text() {
+        let mut doc = PdfDocument::new(100.0, 100.0);
+        let font = doc.add_standard_font(StandardFont::Helvetica);
+        assert_eq!(doc.text_width(font, 10.0, "AAA"), 20.01);
+        assert!(doc.supports_text(font, "Café – ok"));
+        assert!(!doc.supports_text(font, "홍길동"));
+
+        doc.draw_text(font, 10.0, 0.0, 0.0, r"a(b)\é–한", Rgb::BLACK);
+        let (_, _, content) = parse(&doc.finish());
+        assert_eq!(shown_text(&content), [b"a(b)\\\xe9\x96?".to_vec()]);
+    }
+
+    #[test]
+    fn images_with_alpha_get_soft_masks() {
+        let mut png = Vec::new();
+        let image = image::RgbaImage::from_pixel(4, 2, image::Rgba([255, 0, 0, 128]));
+        image::DynamicImage::ImageRgba8(image)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        let mut doc = PdfDocument::new(100.0, 100.0);
+        let (image, width, height) = doc.add_image(&png).unwrap();
+        assert_eq!((width, height), (4, 2));
+        doc.draw_image(image, 0.0, 0.0, 40.0, 20.0);
+        let pdf = doc.finish();
+
+        let (document, page, content) = parse(&pdf);
+        let xobject = page_resource(&document, page, b"XObject", b"Im0");
+        assert_eq!(xobject.get(b"Width").unwrap().as_i64().unwrap(), 4);
+        let smask = xobject.get(b"SMask").unwrap().as_reference().unwrap();
+        let smask = document.get_object(smask).unwrap().as_stream().unwrap();
+        let mut alpha = Vec::new();
+        std::io::Read::read_to_end(
+            &mut flate2::read::ZlibDecoder::new(smask.content.as_slice()),
+            &mut alpha,
+        )
+        .unwrap();
+        assert_eq!(alpha, vec![128; 8]);
+        assert!(content.operations.iter().any(|op| op.operator == "Do"));
+        assert!(PdfDocument::new(1.0, 1.0)
+            .add_image(b"not an image")
+            .is_err());
+    }
+
+    #[test]
+    fn color_parsing() {
+        assert_eq!(Rgb::from_hex("#ff8000"), Some(Rgb(1.0, 128.0 / 255.0, 0.0)));
+        assert_eq!(Rgb::from_hex("nope"), None);
+    }
+
+    #[test]
+    fn truetype_parse_rejects_non_truetype_data() {
+        assert!(TrueTypeFont::parse(b"OTTO\0\0\0\0".to_vec()).is_err());
+        assert!(TrueTypeFont::parse(vec![0, 1, 0, 0, 0, 1]).is_err());
+    }
+}
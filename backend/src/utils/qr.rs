@@ -0,0 +1,70 @@
+//! QR Code encoding for embedding links in generated documents.
+//!
+//! Thin wrapper over the `qrcode` crate that fixes error correction level M,
+//! which is all the certificate renderer and the CLI need for URLs.
+
+use qrcode::{Color, EcLevel};
+
+/// A square grid of dark (`true`) and light (`false`) modules.
+#[derive(Clone)]
+pub struct QrCode {
+    inner: qrcode::QrCode,
+}
+
+impl QrCode {
+    /// Encodes `data` using the smallest version that fits at level M.
+    pub fn encode(data: &[u8]) -> Result<Self, String> {
+        qrcode::QrCode::with_error_correction_level(data, EcLevel::M)
+            .map(|inner| Self { inner })
+            .map_err(|err| err.to_string())
+    }
+
+    /// Returns the symbol version (1..=40).
+    pub fn version(&self) -> usize {
+        match self.inner.version() {
+            qrcode::Version::Normal(version) | qrcode::Version::Micro(version) => version as usize,
+        }
+    }
+
+    /// Returns the width and height in modules, without the quiet zone.
+    pub fn size(&self) -> usize {
+        self.inner.width()
+    }
+
+    /// Returns whether the module at (`x`, `y`) is dark; (0, 0) is the top-left.
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.inner[(x, y)] == Color::Dark
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finder_at(qr: &QrCode, left: usize, top: usize) -> bool {
+        (0..7).all(|y| {
+            (0..7).all(|x| {
+                let ring = x.min(y).min(6 - x).min(6 - y);
+                qr.is_dark(left + x, top + y) == (ring != 1)
+            })
+        })
+    }
+
+    #[test]
+    fn encode_picks_smallest_version_and_draws_finders() {
+        let qr = QrCode::encode(b"https://codelabs.example.com/verify/cert_1").unwrap();
+        assert_eq!(qr.version(), 3);
+        assert_eq!(qr.size(), 29);
+        let far = qr.size() - 7;
+        assert!(finder_at(&qr, 0, 0));
+        assert!(finder_at(&qr, far, 0));
+        assert!(finder_at(&qr, 0, far));
+        assert!(!finder_at(&qr, far, far));
+    }
+
+    #[test]
+    fn encode_rejects_data_beyond_version_40() {
+        assert_eq!(QrCode::encode(&[b'a'; 2331]).unwrap().version(), 40);
+        assert!(QrCode::encode(&[b'a'; 2332]).is_err());
+    }
+}
@@ -0,0 +1,597 @@
+//! Ed25519 signing helpers for publicly verifiable documents such as certificates.
+//!
+//! Certificates are signed twice over: as a compact JWS for the native
+//! verification endpoint, and as an `eddsa-jcs-2022` Data Integrity proof when
+//! exported as an Open Badges 3.0 credential.
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// DER prefix of a PKCS#8 v1 document wrapping a raw 32-byte Ed25519 seed.
+const PKCS8_ED25519_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+const SEED_LEN: usize = 32;
+const DERIVATION_LABEL: &[u8] = b"open-codelabs certificate signing key";
+/// Multicodec prefix (`ed25519-pub`, varint encoded) used by Multikey documents.
+const MULTICODEC_ED25519_PUB: [u8; 2] = [0xed, 0x01];
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+/// Data Integrity cryptosuite implemented by [`DocumentSigner::attach_data_integrity_proof`].
+pub const DATA_INTEGRITY_CRYPTOSUITE: &str = "eddsa-jcs-2022";
+
+/// Issues and verifies compact EdDSA (Ed25519) JWS documents.
+///
+/// Documents are signed with one active key and verified against a keyring
+/// that also holds earlier keys, so certificates survive key rotation.
+#[derive(Clone)]
+pub struct DocumentSigner {
+    key_id: String,
+    pkcs8: Vec<u8>,
+    public_key: Vec<u8>,
+    retired_keys: Vec<VerificationKey>,
+}
+
+/// Public half of a signing key, identified by its key id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationKey {
+    key_id: String,
+    public_key: Vec<u8>,
+}
+
+impl VerificationKey {
+    /// Wraps a raw 32-byte Ed25519 public key.
+    pub fn from_public_key(public_key: &[u8]) -> Result<Self, String> {
+        if public_key.len() != SEED_LEN {
+            return Err("Ed25519 public key must be 32 bytes".to_string());
+        }
+        Ok(Self {
+            key_id: key_id_for(public_key),
+            public_key: public_key.to_vec(),
+        })
+    }
+
+    /// Returns the key identifier placed in the JWS `kid` header.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Returns the raw public key encoded as unpadded base64url (JWK `x`).
+    pub fn public_key_base64url(&self) -> String {
+        URL_SAFE_NO_PAD.encode(&self.public_key)
+    }
+
+    /// Returns the public key as a Multikey `publicKeyMultibase` value (`z6Mk...`).
+    pub fn public_key_multibase(&self) -> String {
+        let mut bytes = MULTICODEC_ED25519_PUB.to_vec();
+        bytes.extend_from_slice(&self.public_key);
+        format!("z{}", base58btc_encode(&bytes))
+    }
+}
+
+fn key_id_for(public_key: &[u8]) -> String {
+    Sha256::digest(public_key)[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+impl std::fmt::Debug for DocumentSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DocumentSigner")
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DocumentSigner {
+    /// Builds a signer from a raw 32-byte Ed25519 seed.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, String> {
+        if seed.len() != SEED_LEN {
+            return Err("Ed25519 seed must be 32 bytes".to_string());
+        }
+        let mut pkcs8 = PKCS8_ED25519_PREFIX.to_vec();
+        pkcs8.extend_from_slice(seed);
+        let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(&pkcs8)
+            .map_err(|_| "invalid Ed25519 seed".to_string())?;
+        let public_key = key_pair.public_key().as_ref().to_vec();
+
+        Ok(Self {
+            key_id: key_id_for(&public_key),
+            pkcs8,
+            public_key,
+            retired_keys: Vec::new(),
+        })
+    }
+
+    /// Builds the signer from `CERTIFICATE_SIGNING_KEY` (base64 32-byte seed)
+    /// and `CERTIFICATE_RETIRED_KEYS` (comma-separated public keys).
+    ///
+    /// See [`DocumentSigner::from_config`] for how `auth_secrets` are used.
+    pub fn from_env(auth_secrets: &[String]) -> Self {
+        let signing_key = std::env::var("CERTIFICATE_SIGNING_KEY").ok();
+        let retired_keys = std::env::var("CERTIFICATE_RETIRED_KEYS").unwrap_or_default();
+        if signing_key.is_none() {
+            tracing::warn!(
+                "CERTIFICATE_SIGNING_KEY not set; deriving the certificate signing key from the auth secret. Set a dedicated key in production."
+            );
+        }
+        Self::from_config(signing_key.as_deref(), &retired_keys, auth_secrets)
+    }
+
+    /// Builds the signer from a base64 seed, falling back to a key derived from
+    /// the first auth secret.
+    ///
+    /// Keys derived from every auth secret stay in the keyring, so rotating
+    /// `AUTH_SECRETS` or switching to a dedicated key keeps earlier
+    /// certificates verifiable while the old secret is still listed.
+    /// `retired_keys` holds base64 or base64url public keys, as published in
+    /// the JWKS `x` member, for keys that are no longer configured at all.
+    pub fn from_config(
+        signing_key: Option<&str>,
+        retired_keys: &str,
+        auth_secrets: &[String],
+    ) -> Self {
+        let configured = signing_key.and_then(|encoded| {
+            decode_base64(encoded)
+                .ok_or_else(|| "invalid base64".to_string())
+                .and_then(|seed| Self::from_seed(&seed))
+                .inspect_err(|err| {
+                    tracing::warn!(
+                        "CERTIFICATE_SIGNING_KEY ignored ({}); deriving the key from the auth secret.",
+                        err
+                    )
+                })
+                .ok()
+        });
+        let fallback_secret = auth_secrets.first().map(String::as_str).unwrap_or_default();
+        let mut signer = configured.unwrap_or_else(|| Self::derived_from_secret(fallback_secret));
+
+        for secret in auth_secrets {
+            let derived = Self::derived_from_secret(secret);
+            signer.add_verification_key(derived.verification_key());
+        }
+        for encoded in retired_keys
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+        {
+            match decode_base64(encoded)
+                .ok_or_else(|| "invalid base64".to_string())
+                .and_then(|public_key| VerificationKey::from_public_key(&public_key))
+            {
+                Ok(key) => signer.add_verification_key(key),
+                Err(err) => tracing::warn!("CERTIFICATE_RETIRED_KEYS entry ignored ({})", err),
+            }
+        }
+        signer
+    }
+
+    /// Accepts documents signed by `key` in addition to the active key.
+    pub fn add_verification_key(&mut self, key: VerificationKey) {
+        let known = key.key_id == self.key_id
+            || self
+                .retired_keys
+                .iter()
+                .any(|retired| retired.key_id == key.key_id);
+        if !known {
+            self.retired_keys.push(key);
+        }
+    }
+
+    /// Returns the public half of the active signing key.
+    pub fn verification_key(&self) -> VerificationKey {
+        VerificationKey {
+            key_id: self.key_id.clone(),
+            public_key: self.public_key.clone(),
+        }
+    }
+
+    /// Returns every key documents are verified against, the active key first.
+    pub fn verification_keys(&self) -> Vec<VerificationKey> {
+        std::iter::once(self.verification_key())
+            .chain(self.retired_keys.iter().cloned())
+            .collect()
+    }
+
+    fn public_key_for(&self, key_id: &str) -> Option<&[u8]> {
+        if key_id == self.key_id {
+            return Some(&self.public_key);
+        }
+        self.retired_keys
+            .iter()
+            .find(|key| key.key_id == key_id)
+            .map(|key| key.public_key.as_slice())
+    }
+
+    /// Derives a deterministic signer from an arbitrary secret string.
+    pub fn derived_from_secret(secret: &str) -> Self {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(DERIVATION_LABEL);
+        let seed = mac.finalize().into_bytes();
+        Self::from_seed(&seed).expect("derived seed is 32 bytes")
+    }
+
+    /// Returns the key identifier placed in the JWS `kid` header.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Returns the raw public key encoded as unpadded base64url (JWK `x`).
+    pub fn public_key_base64url(&self) -> String {
+        self.verification_key().public_key_base64url()
+    }
+
+    /// Returns the public key as a Multikey `publicKeyMultibase` value (`z6Mk...`).
+    pub fn public_key_multibase(&self) -> String {
+        self.verification_key().public_key_multibase()
+    }
+
+    /// Signs claims as a compact JWS with the `EdDSA` algorithm.
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, jsonwebtoken::errors::Error> {
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(self.key_id.clone());
+        encode(&header, claims, &EncodingKey::from_ed_der(&self.pkcs8))
+    }
+
+    /// Verifies a compact JWS produced by [`DocumentSigner::sign`] and returns its claims.
+    ///
+    /// Tokens signed by a key outside the keyring or with another algorithm
+    /// are rejected.
+    pub fn verify<T: DeserializeOwned>(&self, token: &str) -> Option<T> {
+        let header = decode_header(token).ok()?;
+        if header.alg != Algorithm::EdDSA {
+            return None;
+        }
+        let public_key = self.public_key_for(header.kid.as_deref()?)?;
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        validation.validate_aud = false;
+
+        decode::<T>(token, &DecodingKey::from_ed_der(public_key), &validation)
+            .ok()
+            .map(|data| data.claims)
+    }
+
+    /// Adds an `eddsa-jcs-2022` `DataIntegrityProof` to a JSON-LD document.
+    ///
+    /// Any existing `proof` member is replaced. The proof covers the JCS
+    /// canonical form of both the proof options and the unsecured document.
+    pub fn attach_data_integrity_proof(
+        &self,
+        document: &mut Value,
+        verification_method: &str,
+        created: &str,
+    ) -> Result<(), String> {
+        let object = document
+            .as_object_mut()
+            .ok_or_else(|| "document must be a JSON object".to_string())?;
+        object.remove("proof");
+
+        let mut proof = serde_json::json!({
+            "type": "DataIntegrityProof",
+            "cryptosuite": DATA_INTEGRITY_CRYPTOSUITE,
+            "created": created,
+            "verificationMethod": verification_method,
+            "proofPurpose": "assertionMethod",
+        });
+        let hash_data = data_integrity_hash(document, &proof);
+        let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(&self.pkcs8)
+            .map_err(|_| "invalid signing key".to_string())?;
+        let signature = key_pair.sign(&hash_data);
+        proof["proofValue"] = Value::String(format!("z{}", base58btc_encode(signature.as_ref())));
+
+        if let Some(object) = document.as_object_mut() {
+            object.insert("proof".to_string(), proof);
+        }
+        Ok(())
+    }
+
+    /// Verifies a proof produced by [`DocumentSigner::attach_data_integrity_proof`].
+    ///
+    /// The key is looked up in the keyring by the fragment of the proof's
+    /// `verificationMethod`, which holds the key id.
+    pub fn verify_data_integrity_proof(&self, document: &Value) -> bool {
+        let Some(proof) = document.get("proof") else {
+            return false;
+        };
+        let Some(public_key) = proof
+            .get("verificationMethod")
+            .and_then(Value::as_str)
+            .and_then(|method| method.rsplit_once('#'))
+            .and_then(|(_, key_id)| self.public_key_for(key_id))
+        else {
+            return false;
+        };
+        if proof.get("type").and_then(Value::as_str) != Some("DataIntegrityProof")
+            || proof.get("cryptosuite").and_then(Value::as_str) != Some(DATA_INTEGRITY_CRYPTOSUITE)
+        {
+            return false;
+        }
+        let Some(signature) = proof
+            .get("proofValue")
+            .and_then(Value::as_str)
+            .and_then(|value| value.strip_prefix('z'))
+            .and_then(base58btc_decode)
+        else {
+            return false;
+        };
+
+        let mut unsecured = document.clone();
+        let mut options = proof.clone();
+        if let Some(object) = unsecured.as_object_mut() {
+            object.remove("proof");
+        }
+        if let Some(object) = options.as_object_mut() {
+            object.remove("proofValue");
+        }
+        let hash_data = data_integrity_hash(&unsecured, &options);
+        UnparsedPublicKey::new(&ED25519, public_key)
+            .verify(&hash_data, &signature)
+            .is_ok()
+    }
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.trim();
+    STANDARD
+        .decode(encoded)
+        .ok()
+        .or_else(|| URL_SAFE_NO_PAD.decode(encoded.trim_end_matches('=')).ok())
+}
+
+/// Builds the `eddsa-jcs-2022` hash input: `sha256(proofConfig) || sha256(document)`.
+fn data_integrity_hash(unsecured: &Value, proof_options: &Value) -> Vec<u8> {
+    let mut proof_config = proof_options.clone();
+    if let (Some(context), Some(config)) = (unsecured.get("@context"), proof_config.as_object_mut())
+    {
+        config.insert("@context".to_string(), context.clone());
+    }
+    let mut hash_data = Sha256::digest(canonical_json(&proof_config).as_bytes()).to_vec();
+    hash_data.extend_from_slice(&Sha256::digest(canonical_json(unsecured).as_bytes()));
+    hash_data
+}
+
+/// Serializes JSON using the RFC 8785 JSON Canonicalization Scheme.
+///
+/// Object members are ordered by their UTF-16 code units; strings and integers
+/// already match `serde_json`'s compact output.
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical_json(value, &mut out);
+    out
+}
+
+fn write_canonical_json(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (index, (key, item)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical_json(item, out);
+            }
+            out.push('}');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+fn base58btc_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    // Little-endian base58 digits of the big-endian input.
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for &byte in &bytes[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut out = String::with_capacity(zeros + digits.len());
+    out.extend(std::iter::repeat_n('1', zeros));
+    out.extend(
+        digits
+            .iter()
+            .rev()
+            .map(|digit| BASE58_ALPHABET[*digit as usize] as char),
+    );
+    out
+}
+
+fn base58btc_decode(input: &str) -> Option<Vec<u8>> {
+    let zeros = input.bytes().take_while(|byte| *byte == b'1').count();
+    // Little-endian base256 bytes of the decoded value.
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len());
+    for ch in input.bytes().skip(zeros) {
+        let mut carry = BASE58_ALPHABET.iter().position(|c| *c == ch)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut out = vec![0u8; zeros];
+    out.extend(bytes.iter().rev());
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Sample {
+        sub: String,
+    }
+
+    #[test]
+    fn sign_and_verify_round_trip() {
+        let signer = DocumentSigner::derived_from_secret("secret");
+        let claims = Sample {
+            sub: "attendee-1".to_string(),
+        };
+        let token = signer.sign(&claims).unwrap();
+        assert_eq!(signer.verify::<Sample>(&token), Some(claims));
+    }
+
+    #[test]
+    fn derived_key_is_stable_per_secret() {
+        let first = DocumentSigner::derived_from_secret("secret");
+        let second = DocumentSigner::derived_from_secret("secret");
+        let other = DocumentSigner::derived_from_secret("other");
+        assert_eq!(first.key_id(), second.key_id());
+        assert_eq!(first.public_key_base64url(), second.public_key_base64url());
+        assert_ne!(first.key_id(), other.key_id());
+    }
+
+    #[test]
+    fn verify_rejects_tampered_or_foreign_tokens() {
+        let signer = DocumentSigner::derived_from_secret("secret");
+        let token = signer
+            .sign(&Sample {
+                sub: "attendee-1".to_string(),
+            })
+            .unwrap();
+
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let forged = URL_SAFE_NO_PAD.encode(br#"{"sub":"attendee-2"}"#);
+        parts[1] = &forged;
+        assert!(signer.verify::<Sample>(&parts.join(".")).is_none());
+
+        let other = DocumentSigner::derived_from_secret("other");
+        assert!(other.verify::<Sample>(&token).is_none());
+    }
+
+    #[test]
+    fn keyring_verifies_certificates_from_rotated_secrets_and_retired_keys() {
+        let sample = Sample {
+            sub: "attendee-1".to_string(),
+        };
+        let old_token = DocumentSigner::derived_from_secret("old")
+            .sign(&sample)
+            .unwrap();
+        let retired = DocumentSigner::derived_from_secret("gone");
+        let retired_token = retired.sign(&sample).unwrap();
+
+        let rotated = DocumentSigner::from_config(
+            None,
+            &retired.public_key_base64url(),
+            &["new".to_string(), "old".to_string()],
+        );
+        assert_eq!(
+            rotated.key_id(),
+            DocumentSigner::derived_from_secret("new").key_id()
+        );
+        assert_eq!(rotated.verify::<Sample>(&old_token).as_ref(), Some(&sample));
+        assert_eq!(
+            rotated.verify::<Sample>(&retired_token).as_ref(),
+            Some(&sample)
+        );
+        assert_eq!(rotated.verification_keys().len(), 3);
+
+        let seed = STANDARD.encode([7u8; 32]);
+        let dedicated = DocumentSigner::from_config(Some(&seed), "", &["old".to_string()]);
+        assert_eq!(
+            dedicated.key_id(),
+            DocumentSigner::from_seed(&[7u8; 32]).unwrap().key_id()
+        );
+        assert_eq!(dedicated.verify::<Sample>(&old_token), Some(sample));
+        assert!(dedicated.verify::<Sample>(&retired_token).is_none());
+    }
+
+    #[test]
+    fn canonical_json_sorts_keys_and_compacts() {
+        let value = serde_json::json!({
+            "b": [1, {"z": true, "a": null}],
+            "a": "é\n",
+            "\u{e000}": 1,
+            "\u{1f600}": 2,
+        });
+        assert_eq!(
+            canonical_json(&value),
+            "{\"a\":\"é\\n\",\"b\":[1,{\"a\":null,\"z\":true}],\"\u{1f600}\":2,\"\u{e000}\":1}"
+        );
+    }
+
+    #[test]
+    fn base58btc_round_trips_with_leading_zeros() {
+        assert_eq!(base58btc_encode(b"Hello World!"), "2NEpo7TZRRrLZSi2U");
+        assert_eq!(base58btc_encode(&[0, 0, 1]), "112");
+        assert_eq!(base58btc_decode("112"), Some(vec![0, 0, 1]));
+        assert_eq!(base58btc_decode("0OIl"), None);
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(base58btc_decode(&base58btc_encode(&bytes)), Some(bytes));
+    }
+
+    #[test]
+    fn public_key_multibase_uses_ed25519_multikey_prefix() {
+        let signer = DocumentSigner::derived_from_secret("secret");
+        assert!(signer.public_key_multibase().starts_with("z6Mk"));
+    }
+
+    #[test]
+    fn data_integrity_proof_round_trip_detects_tampering() {
+        let signer = DocumentSigner::derived_from_secret("secret");
+        let mut document = serde_json::json!({
+            "@context": ["https://www.w3.org/ns/credentials/v2"],
+            "type": ["VerifiableCredential"],
+            "credentialSubject": { "name": "Ada" },
+        });
+        let method = format!("https://example.com/issuer#{}", signer.key_id());
+        signer
+            .attach_data_integrity_proof(&mut document, &method, "2026-01-01T00:00:00Z")
+            .unwrap();
+        assert_eq!(document["proof"]["cryptosuite"], DATA_INTEGRITY_CRYPTOSUITE);
+        assert!(signer.verify_data_integrity_proof(&document));
+
+        let mut tampered = document.clone();
+        tampered["credentialSubject"]["name"] = serde_json::json!("Eve");
+        assert!(!signer.verify_data_integrity_proof(&tampered));
+
+        let other = DocumentSigner::derived_from_secret("other");
+        assert!(!other.verify_data_integrity_proof(&document));
+    }
+
+    #[test]
+    fn from_seed_rejects_wrong_length() {
+        assert!(DocumentSigner::from_seed(&[0u8; 16]).is_err());
+    }
+}
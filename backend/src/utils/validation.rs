@@ -4,11 +4,16 @@ use axum::http::StatusCode;
 use url::Url;
 
 use crate::domain::models::{
-    CreateCodelab, CreateFeedback, CreateMaterial, CreateQuiz, CreateStep, RegistrationPayload,
-    UpdateStepsPayload,
+    CreateCodelab, CreateCohort, CreateFeedback, CreateMaterial, CreateQuiz, CreateStep,
+    RegistrationPayload, RosterEntry, UpdateStepsPayload,
 };
 use crate::utils::error::bad_request;
 
+/// Most steps a codelab may have.
+pub const MAX_STEPS: usize = 200;
+/// Longest estimated duration a single step can have, one day.
+pub const MAX_STEP_DURATION_MINUTES: i32 = 24 * 60;
+
 /// Validates a codelab create/update payload before persistence.
 pub fn validate_codelab(payload: &CreateCodelab) -> Result<(), (StatusCode, String)> {
     validate_text(&payload.title, "title", 1, 200)?;
@@ -17,6 +22,33 @@ pub fn validate_codelab(payload: &CreateCodelab) -> Result<(), (StatusCode, Stri
     if let Some(guide) = &payload.guide_markdown {
         validate_text(guide, "guide_markdown", 0, 50_000)?;
     }
+    if let Some(max_attempts) = payload.quiz_max_attempts {
+        if !(0..=100).contains(&max_attempts) {
+            return Err(bad_request("quiz_max_attempts must be between 0 and 100"));
+        }
+    }
+    if let Some(pass_percentage) = payload.quiz_pass_percentage {
+        if !(0..=100).contains(&pass_percentage) {
+            return Err(bad_request(
+                "quiz_pass_percentage must be between 0 and 100",
+            ));
+        }
+    }
+    if let Some(max_attendees) = payload.max_attendees {
+        if !(0..=100_000).contains(&max_attendees) {
+            return Err(bad_request("max_attendees must be between 0 and 100000"));
+        }
+    }
+    let opens_at = payload.registration_opens_at.unwrap_or(0);
+    let closes_at = payload.registration_closes_at.unwrap_or(0);
+    if opens_at < 0 || closes_at < 0 {
+        return Err(bad_request("registration window must use epoch seconds"));
+    }
+    if opens_at > 0 && closes_at > 0 && opens_at >= closes_at {
+        return Err(bad_request(
+            "registration_opens_at must be before registration_closes_at",
+        ));
+    }
     Ok(())
 }
 
@@ -25,7 +57,7 @@ pub fn validate_steps(payload: &UpdateStepsPayload) -> Result<(), (StatusCode, S
     if payload.steps.is_empty() {
         return Err(bad_request("steps cannot be empty"));
     }
-    if payload.steps.len() > 200 {
+    if payload.steps.len() > MAX_STEPS {
         return Err(bad_request("steps exceed limit"));
     }
     for step in &payload.steps {
@@ -36,8 +68,29 @@ pub fn validate_steps(payload: &UpdateStepsPayload) -> Result<(), (StatusCode, S
 
 /// Validates a single codelab step payload.
 pub fn validate_step(step: &CreateStep) -> Result<(), (StatusCode, String)> {
-    validate_text(&step.title, "step title", 1, 200)?;
-    validate_text(&step.content_markdown, "step content", 1, 50_000)?;
+    validate_step_content(&step.title, &step.content_markdown)?;
+    validate_step_duration(step.duration_minutes)
+}
+
+/// Validates an estimated step duration in minutes.
+pub fn validate_step_duration(duration_minutes: Option<i32>) -> Result<(), (StatusCode, String)> {
+    if let Some(minutes) = duration_minutes {
+        if !(0..=MAX_STEP_DURATION_MINUTES).contains(&minutes) {
+            return Err(bad_request(&format!(
+                "duration_minutes must be between 0 and {MAX_STEP_DURATION_MINUTES}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Validates the title and markdown body of a step.
+pub fn validate_step_content(
+    title: &str,
+    content_markdown: &str,
+) -> Result<(), (StatusCode, String)> {
+    validate_text(title, "step title", 1, 200)?;
+    validate_text(content_markdown, "step content", 1, 50_000)?;
     Ok(())
 }
 
@@ -45,6 +98,51 @@ pub fn validate_step(step: &CreateStep) -> Result<(), (StatusCode, String)> {
 pub fn validate_registration(payload: &RegistrationPayload) -> Result<(), (StatusCode, String)> {
     validate_text(&payload.name, "name", 1, 80)?;
     validate_text(&payload.code, "code", 1, 64)?;
+    if let Some(passcode) = &payload.passcode {
+        validate_text(passcode, "passcode", 0, 64)?;
+    }
+    if let Some(invite_token) = &payload.invite_token {
+        validate_text(invite_token, "invite_token", 0, 128)?;
+    }
+    if let Some(cohort_id) = &payload.cohort_id {
+        validate_text(cohort_id, "cohort_id", 0, 255)?;
+    }
+    Ok(())
+}
+
+/// Validates one learner of a roster import with the registration limits.
+pub fn validate_roster_entry(entry: &RosterEntry) -> Result<(), (StatusCode, String)> {
+    validate_text(&entry.name, "name", 1, 80)?;
+    if let Some(email) = &entry.email {
+        validate_text(email, "email", 0, 255)?;
+    }
+    if let Some(code) = &entry.code {
+        validate_text(code, "code", 1, 64)?;
+    }
+    Ok(())
+}
+
+/// Validates a codelab join passcode set by staff.
+pub fn validate_passcode(passcode: &str) -> Result<(), (StatusCode, String)> {
+    if !(4..=64).contains(&passcode.trim().chars().count()) {
+        return Err(bad_request("passcode must be between 4 and 64 characters"));
+    }
+    Ok(())
+}
+
+/// Validates cohort metadata and its schedule.
+pub fn validate_cohort(payload: &CreateCohort) -> Result<(), (StatusCode, String)> {
+    validate_text(&payload.name, "name", 1, 128)?;
+    if payload.starts_at.is_some_and(|value| value < 0)
+        || payload.ends_at.is_some_and(|value| value < 0)
+    {
+        return Err(bad_request("cohort schedule must use epoch seconds"));
+    }
+    if let (Some(starts_at), Some(ends_at)) = (payload.starts_at, payload.ends_at) {
+        if starts_at >= ends_at {
+            return Err(bad_request("starts_at must be before ends_at"));
+        }
+    }
     Ok(())
 }
 
@@ -151,6 +249,11 @@ mod tests {
             require_quiz: Some(false),
             require_feedback: Some(false),
             require_submission: Some(false),
+            quiz_max_attempts: None,
+            quiz_pass_percentage: None,
+            max_attendees: None,
+            registration_opens_at: None,
+            registration_closes_at: None,
             guide_markdown: None,
         };
         assert!(validate_codelab(&payload).is_ok());
@@ -162,6 +265,9 @@ mod tests {
             name: "Alice".to_string(),
             code: "바이브-코드 123".to_string(),
             email: None,
+            passcode: None,
+            invite_token: None,
+            cohort_id: None,
         };
         assert!(validate_registration(&payload).is_ok());
     }
@@ -200,6 +306,11 @@ mod tests {
             require_quiz: Some(false),
             require_feedback: Some(false),
             require_submission: Some(false),
+            quiz_max_attempts: None,
+            quiz_pass_percentage: None,
+            max_attendees: None,
+            registration_opens_at: None,
+            registration_closes_at: None,
             guide_markdown: None,
         };
         assert_eq!(
@@ -219,11 +330,143 @@ mod tests {
             require_quiz: Some(false),
             require_feedback: Some(false),
             require_submission: Some(false),
+            quiz_max_attempts: None,
+            quiz_pass_percentage: None,
+            max_attendees: None,
+            registration_opens_at: None,
+            registration_closes_at: None,
             guide_markdown: Some("# Guide".to_string()),
         };
         assert!(validate_codelab(&payload).is_ok());
     }
 
+    #[test]
+    fn validate_codelab_checks_quiz_attempt_settings() {
+        let mut payload = CreateCodelab {
+            title: "Title".to_string(),
+            description: "Desc".to_string(),
+            author: "Author".to_string(),
+            is_public: Some(true),
+            quiz_enabled: Some(true),
+            require_quiz: Some(true),
+            require_feedback: Some(false),
+            require_submission: Some(false),
+            quiz_max_attempts: Some(3),
+            quiz_pass_percentage: Some(80),
+            max_attendees: None,
+            registration_opens_at: None,
+            registration_closes_at: None,
+            guide_markdown: None,
+        };
+        assert!(validate_codelab(&payload).is_ok());
+
+        payload.quiz_pass_percentage = Some(101);
+        assert_eq!(
+            validate_codelab(&payload).unwrap_err().1,
+            "quiz_pass_percentage must be between 0 and 100"
+        );
+
+        payload.quiz_pass_percentage = Some(80);
+        payload.quiz_max_attempts = Some(-1);
+        assert_eq!(
+            validate_codelab(&payload).unwrap_err().1,
+            "quiz_max_attempts must be between 0 and 100"
+        );
+    }
+
+    #[test]
+    fn validate_codelab_checks_capacity_and_registration_window() {
+        let mut payload = CreateCodelab {
+            title: "Title".to_string(),
+            description: "Desc".to_string(),
+            author: "Author".to_string(),
+            is_public: Some(true),
+            quiz_enabled: None,
+            require_quiz: None,
+            require_feedback: None,
+            require_submission: None,
+            quiz_max_attempts: None,
+            quiz_pass_percentage: None,
+            max_attendees: Some(30),
+            registration_opens_at: Some(1_700_000_000),
+            registration_closes_at: Some(1_700_003_600),
+            guide_markdown: None,
+        };
+        assert!(validate_codelab(&payload).is_ok());
+
+        payload.registration_opens_at = Some(0);
+        assert!(validate_codelab(&payload).is_ok());
+
+        payload.registration_opens_at = Some(1_700_003_600);
+        assert_eq!(
+            validate_codelab(&payload).unwrap_err().1,
+            "registration_opens_at must be before registration_closes_at"
+        );
+
+        payload.registration_opens_at = Some(-1);
+        assert_eq!(
+            validate_codelab(&payload).unwrap_err().1,
+            "registration window must use epoch seconds"
+        );
+
+        payload.registration_opens_at = None;
+        payload.max_attendees = Some(-1);
+        assert_eq!(
+            validate_codelab(&payload).unwrap_err().1,
+            "max_attendees must be between 0 and 100000"
+        );
+    }
+
+    #[test]
+    fn validate_cohort_checks_name_and_schedule() {
+        let mut payload = CreateCohort {
+            name: "April evening".to_string(),
+            starts_at: Some(1_700_000_000),
+            ends_at: Some(1_700_007_200),
+        };
+        assert!(validate_cohort(&payload).is_ok());
+
+        payload.ends_at = Some(1_700_000_000);
+        assert_eq!(
+            validate_cohort(&payload).unwrap_err().1,
+            "starts_at must be before ends_at"
+        );
+
+        payload.ends_at = None;
+        payload.starts_at = Some(-5);
+        assert_eq!(
+            validate_cohort(&payload).unwrap_err().1,
+            "cohort schedule must use epoch seconds"
+        );
+
+        payload.starts_at = None;
+        payload.name = "  ".to_string();
+        assert_eq!(validate_cohort(&payload).unwrap_err().1, "name is required");
+    }
+
+    #[test]
+    fn validate_roster_entry_uses_registration_limits() {
+        let mut entry = RosterEntry {
+            name: "Ada".to_string(),
+            email: Some("ada@example.com".to_string()),
+            code: None,
+        };
+        assert!(validate_roster_entry(&entry).is_ok());
+
+        entry.code = Some(" ".to_string());
+        assert_eq!(
+            validate_roster_entry(&entry).unwrap_err().1,
+            "code is required"
+        );
+
+        entry.code = None;
+        entry.name = "x".repeat(81);
+        assert_eq!(
+            validate_roster_entry(&entry).unwrap_err().1,
+            "name is too long"
+        );
+    }
+
     #[test]
     fn validate_steps_rejects_empty_and_too_many() {
         assert_eq!(
@@ -238,6 +481,7 @@ mod tests {
                 id: None,
                 title: format!("step {i}"),
                 content_markdown: "content".to_string(),
+                duration_minutes: None,
             })
             .collect::<Vec<_>>();
         assert_eq!(
@@ -254,11 +498,21 @@ mod tests {
             id: None,
             title: "Title".to_string(),
             content_markdown: " ".to_string(),
+            duration_minutes: None,
         };
         assert_eq!(
             validate_step(&step).unwrap_err().1,
             "step content is required".to_string()
         );
+        let step = CreateStep {
+            content_markdown: "Body".to_string(),
+            duration_minutes: Some(-5),
+            ..step
+        };
+        assert_eq!(
+            validate_step(&step).unwrap_err().1,
+            "duration_minutes must be between 0 and 1440".to_string()
+        );
     }
 
     #[test]
@@ -268,6 +522,7 @@ mod tests {
                 id: Some("step-1".to_string()),
                 title: "Step 1".to_string(),
                 content_markdown: "Do this".to_string(),
+                duration_minutes: None,
             }],
         };
         assert!(validate_steps(&payload).is_ok());
@@ -279,6 +534,9 @@ mod tests {
             name: "".to_string(),
             code: "abc".to_string(),
             email: None,
+            passcode: None,
+            invite_token: None,
+            cohort_id: None,
         };
         assert_eq!(
             validate_registration(&payload).unwrap_err().1,
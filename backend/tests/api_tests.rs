@@ -5,9 +5,17 @@ use axum::{
 use backend::{
     create_router,
     domain::models::{Codelab, CreateCodelab},
-    infrastructure::run_migrations,
-    middleware::auth::SessionClaims,
-    utils::crypto::encrypt_with_password,
+    infrastructure::{
+        authoring_channel, open_authoring_room, run_migrations, save_authoring_room, OidcConfig,
+        RetentionConfig,
+    },
+    middleware::auth::{SessionClaims, StaffRole},
+    utils::{
+        crypto::encrypt_with_password,
+        ot::TextOperation,
+        signing::DocumentSigner,
+        totp::{base32_decode, totp_code, TOTP_STEP_SECONDS},
+    },
     AppState, DbKind,
 };
 use cookie::Cookie;
@@ -30,6 +38,10 @@ struct TestApp {
 }
 
 async fn setup_test_app() -> TestApp {
+    setup_test_app_with(|_| {}).await
+}
+
+async fn setup_test_app_with(configure: impl FnOnce(&mut AppState)) -> TestApp {
     sqlx::any::install_default_drivers();
     let pool = AnyPoolOptions::new()
         .max_connections(1) // Use 1 connection for in-memory sqlite to avoid issues
@@ -42,13 +54,15 @@ async fn setup_test_app() -> TestApp {
         .await
         .expect("Failed to run migrations");
 
-    let state = Arc::new(AppState::new(
+    let mut state = AppState::new(
         pool,
         DbKind::Sqlite,
         "admin".to_string(),
         "admin123".to_string(),
         false,
-    ));
+    );
+    configure(&mut state);
+    let state = Arc::new(state);
 
     let app = create_router(state.clone());
     TestApp { app, state }
@@ -82,9 +96,18 @@ fn extract_cookies(headers: &HeaderMap) -> (String, HashMap<String, String>) {
 }
 
 async fn login_admin(app: &axum::Router, state: &AppState) -> (String, String) {
+    login_as(app, state, &state.admin_id, &state.admin_pw).await
+}
+
+async fn login_as(
+    app: &axum::Router,
+    state: &AppState,
+    username: &str,
+    password: &str,
+) -> (String, String) {
     let login_payload = json!({
-        "admin_id": state.admin_id.clone(),
-        "admin_pw": state.admin_pw.clone()
+        "admin_id": username,
+        "admin_pw": password
     });
 
     let response = app
@@ -134,6 +157,11 @@ async fn create_codelab_with_options(
         require_quiz: Some(false),
         require_feedback: Some(false),
         require_submission: Some(require_submission),
+        quiz_max_attempts: None,
+        quiz_pass_percentage: None,
+        max_attendees: None,
+        registration_opens_at: None,
+        registration_closes_at: None,
         guide_markdown: Some("# Guide".to_string()),
     };
 
@@ -361,6 +389,38 @@ fn websocket_request(uri: &str, cookie: &str) -> axum::http::Request<()> {
         .unwrap()
 }
 
+async fn send_json(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: &str,
+    csrf: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::COOKIE, cookie);
+    if let Some(csrf) = csrf {
+        builder = builder.header("x-csrf-token", csrf);
+    }
+    let request = match body {
+        Some(body) => builder
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    };
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let value = serde_json::from_slice(&bytes)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).to_string()));
+    (status, value)
+}
+
 #[tokio::test]
 async fn test_full_handler_flow_materials_quizzes_feedback_submissions_attendees_audit() {
     let test_app = setup_test_app().await;
@@ -519,7 +579,7 @@ async fn test_full_handler_flow_materials_quizzes_feedback_submissions_attendees
         "submissions": [
             {
                 "quiz_id": quiz_id,
-                "answer": "4",
+                "answer": "0",
                 "is_correct": true
             }
         ]
@@ -542,6 +602,42 @@ async fn test_full_handler_flow_materials_quizzes_feedback_submissions_attendees
         .await
         .unwrap();
     assert_eq!(submit_quiz_res.status(), StatusCode::OK);
+    let submit_quiz_body = axum::body::to_bytes(submit_quiz_res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let submit_quiz_result: Value = serde_json::from_slice(&submit_quiz_body).unwrap();
+    // Client-reported correctness is ignored; the server grades "0" as wrong.
+    assert_eq!(submit_quiz_result["correct"], 0);
+    assert_eq!(submit_quiz_result["total"], 1);
+    assert_eq!(submit_quiz_result["results"][0]["is_correct"], false);
+
+    let resubmit_quiz_res = test_app
+        .app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/codelabs/{}/quizzes/submit", codelab.id))
+                .header("Content-Type", "application/json")
+                .header(header::COOKIE, attendee_cookie.clone())
+                .header("x-csrf-token", attendee_csrf.clone())
+                .body(Body::from(
+                    serde_json::to_vec(&json!({
+                        "submissions": [{ "quiz_id": quiz_id, "answer": "1" }]
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resubmit_quiz_res.status(), StatusCode::OK);
+    let resubmit_quiz_body = axum::body::to_bytes(resubmit_quiz_res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let resubmit_quiz_result: Value = serde_json::from_slice(&resubmit_quiz_body).unwrap();
+    assert_eq!(resubmit_quiz_result["correct"], 1);
+    assert_eq!(resubmit_quiz_result["score"], 100.0);
 
     let get_quiz_submissions_res = test_app
         .app
@@ -673,6 +769,13 @@ async fn test_full_handler_flow_materials_quizzes_feedback_submissions_attendees
         complete_before_submission_res.status(),
         StatusCode::BAD_REQUEST
     );
+    let complete_before_submission_body =
+        axum::body::to_bytes(complete_before_submission_res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+    let unmet: Value = serde_json::from_slice(&complete_before_submission_body).unwrap();
+    assert_eq!(unmet["error"], "REQUIREMENTS_NOT_MET");
+    assert_eq!(unmet["unmet"], json!(["SUBMISSION_REQUIRED"]));
 
     // submissions: submit link + list + delete
     let submit_link_payload = json!({
@@ -948,6 +1051,11 @@ async fn test_create_and_get_codelab() {
         require_quiz: None,
         require_feedback: None,
         require_submission: None,
+        quiz_max_attempts: None,
+        quiz_pass_percentage: None,
+        max_attendees: None,
+        registration_opens_at: None,
+        registration_closes_at: None,
         guide_markdown: None,
     };
 
@@ -2501,6 +2609,9 @@ async fn test_websocket_live_message_paths() {
         sub: attendee_id.clone(),
         role: "attendee".to_string(),
         codelab_id: Some(codelab.id.clone()),
+        staff_role: None,
+        scopes: None,
+        jti: None,
         iss: test_app.state.auth.issuer.clone(),
         aud: test_app.state.auth.audience.clone(),
         iat: 1,
@@ -2510,6 +2621,9 @@ async fn test_websocket_live_message_paths() {
         sub: test_app.state.admin_id.clone(),
         role: "admin".to_string(),
         codelab_id: None,
+        staff_role: None,
+        scopes: None,
+        jti: None,
         iss: test_app.state.auth.issuer.clone(),
         aud: test_app.state.auth.audience.clone(),
         iat: 1,
@@ -4486,6 +4600,9 @@ async fn test_websocket_query_token_and_extra_message_paths() {
         sub: attendee_id.clone(),
         role: "attendee".to_string(),
         codelab_id: Some(codelab.id.clone()),
+        staff_role: None,
+        scopes: None,
+        jti: None,
         iss: test_app.state.auth.issuer.clone(),
         aud: test_app.state.auth.audience.clone(),
         iat: 1,
@@ -4495,6 +4612,9 @@ async fn test_websocket_query_token_and_extra_message_paths() {
         sub: test_app.state.admin_id.clone(),
         role: "admin".to_string(),
         codelab_id: None,
+        staff_role: None,
+        scopes: None,
+        jti: None,
         iss: test_app.state.auth.issuer.clone(),
         aud: test_app.state.auth.audience.clone(),
         iat: 1,
@@ -5453,3 +5573,4673 @@ async fn test_quiz_feedback_submission_and_codelab_error_paths() {
 
     let _ = other_attendee_id;
 }
+
+#[tokio::test]
+async fn test_quiz_attempt_history_limits_and_pass_threshold() {
+    let test_app = setup_test_app().await;
+    let (admin_cookie, admin_csrf) = login_admin(&test_app.app, &test_app.state).await;
+
+    let (status, codelab) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/codelabs",
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({
+            "title": "Quiz Lab",
+            "description": "Quiz attempts",
+            "author": "Author",
+            "quiz_enabled": true,
+            "require_quiz": true,
+            "quiz_max_attempts": 2,
+            "quiz_pass_percentage": 50
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(codelab["quiz_max_attempts"], 2);
+    assert_eq!(codelab["quiz_pass_percentage"], 50);
+    let codelab_id = codelab["id"].as_str().unwrap().to_string();
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/codelabs/{}/quizzes", codelab_id),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!([
+            { "question": "2+2?", "options": ["3", "4"], "correct_answer": 1 },
+            { "question": "Pick evens", "options": ["1", "2", "4"], "correct_answer": 1, "correct_answers": [1, 2] }
+        ])),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (attendee_cookie, attendee_csrf, _attendee_id) =
+        register_attendee(&test_app.app, &test_app.state, &codelab_id, "Quinn", "code").await;
+
+    let (_, quizzes) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/quizzes", codelab_id),
+        &attendee_cookie,
+        None,
+        None,
+    )
+    .await;
+    let single_id = quizzes[0]["id"].as_str().unwrap().to_string();
+    let multi_id = quizzes[1]["id"].as_str().unwrap().to_string();
+
+    let complete_uri = format!("/api/codelabs/{}/complete", codelab_id);
+    let submit_uri = format!("/api/codelabs/{}/quizzes/submit", codelab_id);
+
+    let (status, body) = send_json(
+        &test_app.app,
+        "POST",
+        &complete_uri,
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["unmet"], json!(["QUIZ_REQUIRED"]));
+
+    let (status, first) = send_json(
+        &test_app.app,
+        "POST",
+        &submit_uri,
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        Some(json!({ "submissions": [
+            { "quiz_id": single_id, "answer": "0", "is_correct": true },
+            { "quiz_id": multi_id, "answer": "[1]", "is_correct": true }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["attempt_number"], 1);
+    assert_eq!(first["correct"], 0);
+    assert_eq!(first["passed"], false);
+    assert_eq!(first["remaining_attempts"], 1);
+
+    let (status, body) = send_json(
+        &test_app.app,
+        "POST",
+        &complete_uri,
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["unmet"], json!(["QUIZ_REQUIRED"]));
+
+    let (status, second) = send_json(
+        &test_app.app,
+        "POST",
+        &submit_uri,
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        Some(json!({ "submissions": [
+            { "quiz_id": single_id, "answer": "0" },
+            { "quiz_id": multi_id, "answer": "[2,1]" }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(second["attempt_number"], 2);
+    assert_eq!(second["score"], 50.0);
+    assert_eq!(second["passed"], true);
+    assert_eq!(second["remaining_attempts"], 0);
+
+    let (status, body) = send_json(
+        &test_app.app,
+        "POST",
+        &submit_uri,
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        Some(json!({ "submissions": [{ "quiz_id": single_id, "answer": "1" }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body, "QUIZ_ATTEMPTS_EXHAUSTED");
+
+    let (status, attempts) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/quizzes/attempts", codelab_id),
+        &attendee_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let attempts = attempts.as_array().unwrap();
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[0]["attempt_number"], 1);
+    assert_eq!(attempts[1]["correct_count"], 1);
+
+    // Earlier submissions are kept alongside the latest attempt.
+    let (status, submissions) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/quizzes/submissions", codelab_id),
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(submissions.as_array().unwrap().len(), 4);
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &complete_uri,
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Updates that omit the quiz settings keep the stored values.
+    let (status, updated) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/codelabs/{}", codelab_id),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({
+            "title": "Quiz Lab",
+            "description": "Quiz attempts",
+            "author": "Author",
+            "require_quiz": true
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["quiz_max_attempts"], 2);
+    assert_eq!(updated["quiz_pass_percentage"], 50);
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/codelabs/{}", codelab_id),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({
+            "title": "Quiz Lab",
+            "description": "Quiz attempts",
+            "author": "Author",
+            "quiz_pass_percentage": 150
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_complete_codelab_reports_all_unmet_requirements() {
+    let test_app = setup_test_app().await;
+    let (admin_cookie, admin_csrf) = login_admin(&test_app.app, &test_app.state).await;
+
+    let (status, codelab) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/codelabs",
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({
+            "title": "Strict Lab",
+            "description": "All requirements",
+            "author": "Author",
+            "quiz_enabled": true,
+            "require_quiz": true,
+            "require_feedback": true,
+            "require_submission": true
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let codelab_id = codelab["id"].as_str().unwrap().to_string();
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/codelabs/{}/quizzes", codelab_id),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!([{
+            "question": "2 + 2?",
+            "quiz_type": "multiple_choice",
+            "options": ["3", "4"],
+            "correct_answer": 1
+        }])),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (attendee_cookie, attendee_csrf, attendee_id) =
+        register_attendee(&test_app.app, &test_app.state, &codelab_id, "Robin", "code").await;
+    let complete_uri = format!("/api/codelabs/{}/complete", codelab_id);
+
+    let (status, body) = send_json(
+        &test_app.app,
+        "POST",
+        &complete_uri,
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "REQUIREMENTS_NOT_MET");
+    assert_eq!(
+        body["unmet"],
+        json!(["QUIZ_REQUIRED", "FEEDBACK_REQUIRED", "SUBMISSION_REQUIRED"])
+    );
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &format!("/api/codelabs/{}/feedback", codelab_id),
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        Some(json!({ "difficulty": "3", "satisfaction": "5", "comment": "Nice" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send_json(
+        &test_app.app,
+        "POST",
+        &complete_uri,
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["unmet"],
+        json!(["QUIZ_REQUIRED", "SUBMISSION_REQUIRED"])
+    );
+
+    let (_, quizzes) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/quizzes", codelab_id),
+        &attendee_cookie,
+        None,
+        None,
+    )
+    .await;
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &format!("/api/codelabs/{}/quizzes/submit", codelab_id),
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        Some(json!({ "submissions": [{ "quiz_id": quizzes[0]["id"], "answer": "1" }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &format!(
+            "/api/codelabs/{}/attendees/{}/submissions/link",
+            codelab_id, attendee_id
+        ),
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        Some(json!({ "url": "https://example.com/repo", "title": "Repo" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send_json(
+        &test_app.app,
+        "POST",
+        &complete_uri,
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+}
+
+#[tokio::test]
+async fn test_signed_certificate_verification_and_revocation() {
+    let test_app = setup_test_app().await;
+    let (admin_cookie, admin_csrf) = login_admin(&test_app.app, &test_app.state).await;
+    let codelab = create_codelab_as_admin(&test_app.app, &admin_cookie, &admin_csrf, false).await;
+    let (attendee_cookie, attendee_csrf, attendee_id) =
+        register_attendee(&test_app.app, &test_app.state, &codelab.id, "Sam", "code").await;
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &format!("/api/codelabs/{}/complete", codelab.id),
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, certificate) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/certificates/{}", attendee_id),
+        "",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let cert_id = certificate["certificate_id"].as_str().unwrap().to_string();
+    let token = certificate["signed_certificate"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(certificate["attendee_name"], "Sam");
+    assert_eq!(certificate["revoked"], false);
+    assert_eq!(
+        certificate["verification_url"],
+        format!("/api/certificates/verify/{}", cert_id)
+    );
+
+    // The certificate ID is stable across fetches.
+    let (_, again) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/certificates/{}", attendee_id),
+        "",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(again["certificate_id"], cert_id.as_str());
+    assert_eq!(again["signed_certificate"], token.as_str());
+
+    // The published key verifies the JWS independently of the server.
+    let (status, jwks) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/certificates/jwks",
+        "",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let jwk = &jwks["keys"][0];
+    assert_eq!(jwk["alg"], "EdDSA");
+    let key = jsonwebtoken::DecodingKey::from_ed_components(jwk["x"].as_str().unwrap()).unwrap();
+    let mut validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::EdDSA);
+    validation.required_spec_claims.clear();
+    validation.validate_exp = false;
+    let decoded = jsonwebtoken::decode::<Value>(&token, &key, &validation).unwrap();
+    assert_eq!(decoded.header.kid.as_deref(), jwk["kid"].as_str());
+    assert_eq!(decoded.claims["jti"], cert_id.as_str());
+    assert_eq!(decoded.claims["sub"], attendee_id.as_str());
+    assert_eq!(decoded.claims["codelab_id"], codelab.id.as_str());
+
+    let verify_uri = format!("/api/certificates/verify/{}", cert_id);
+    let (status, verification) = send_json(&test_app.app, "GET", &verify_uri, "", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(verification["valid"], true);
+    assert_eq!(verification["signature_valid"], true);
+    assert_eq!(verification["revoked"], false);
+    assert_eq!(verification["claims"]["attendee_name"], "Sam");
+
+    // A certificate signed by a key outside the keyring is reported, not a 500.
+    let foreign = DocumentSigner::derived_from_secret("dropped-secret")
+        .sign(&decoded.claims)
+        .unwrap();
+    let swap_token = |signed_token: String| {
+        let state = test_app.state.clone();
+        let cert_id = cert_id.clone();
+        async move {
+            sqlx::query(&state.q("UPDATE certificates SET signed_token = ? WHERE id = ?"))
+                .bind(signed_token)
+                .bind(cert_id)
+                .execute(&state.pool)
+                .await
+                .unwrap();
+        }
+    };
+    swap_token(foreign).await;
+    let (status, body) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/certificates/{}", attendee_id),
+        "",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body, "CERTIFICATE_SIGNATURE_INVALID");
+    let (_, verification) = send_json(&test_app.app, "GET", &verify_uri, "", None, None).await;
+    assert_eq!(verification["signature_valid"], false);
+    swap_token(token.clone()).await;
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/certificates/verify/unknown-cert",
+        "",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let revoke_uri = format!("/api/admin/certificates/{}/revoke", cert_id);
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &revoke_uri,
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        Some(json!({ "reason": "nope" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, revoked) = send_json(
+        &test_app.app,
+        "POST",
+        &revoke_uri,
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "reason": "Issued in error" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(revoked["valid"], false);
+    assert_eq!(revoked["signature_valid"], true);
+    assert_eq!(revoked["revoked"], true);
+    assert_eq!(revoked["revocation_reason"], "Issued in error");
+
+    let (_, verification) = send_json(&test_app.app, "GET", &verify_uri, "", None, None).await;
+    assert_eq!(verification["valid"], false);
+    assert_eq!(verification["revoked"], true);
+
+    let (_, certificate) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/certificates/{}", attendee_id),
+        "",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(certificate["revoked"], true);
+
+    let (_, logs) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/admin/audit-logs?action=certificate_revoke",
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(logs.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_open_badges_credential_export() {
+    let test_app = setup_test_app_with(|state| {
+        state.credential_issuer.public_url = Some("https://codelabs.example.com".to_string());
+    })
+    .await;
+    let (admin_cookie, admin_csrf) = login_admin(&test_app.app, &test_app.state).await;
+    let codelab = create_codelab_as_admin(&test_app.app, &admin_cookie, &admin_csrf, false).await;
+    let (attendee_cookie, attendee_csrf, attendee_id) =
+        register_attendee(&test_app.app, &test_app.state, &codelab.id, "Ada", "code").await;
+    let credential_uri = format!("/api/certificates/{}/credential", attendee_id);
+
+    let (status, _) = send_json(&test_app.app, "GET", &credential_uri, "", None, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &format!("/api/codelabs/{}/complete", codelab.id),
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, credential) =
+        send_json(&test_app.app, "GET", &credential_uri, "", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        credential["type"],
+        json!(["VerifiableCredential", "OpenBadgeCredential"])
+    );
+    assert_eq!(
+        credential["@context"][0],
+        "https://www.w3.org/ns/credentials/v2"
+    );
+    let (_, certificate) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/certificates/{}", attendee_id),
+        "",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(
+        credential["id"],
+        format!(
+            "urn:uuid:{}",
+            certificate["certificate_id"].as_str().unwrap()
+        )
+    );
+    let subject = &credential["credentialSubject"];
+    assert_eq!(subject["identifier"][0]["identityHash"], "Ada");
+    assert_eq!(subject["achievement"]["name"], codelab.title.as_str());
+    assert_eq!(subject["achievement"]["type"], json!(["Achievement"]));
+    assert!(subject["achievement"]["criteria"]["narrative"].is_string());
+    assert_eq!(credential["proof"]["cryptosuite"], "eddsa-jcs-2022");
+    assert_eq!(
+        credential["issuer"]["id"],
+        "https://codelabs.example.com/api/certificates/issuer"
+    );
+
+    let (status, verification) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/certificates/verify",
+        "",
+        None,
+        Some(credential.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(verification["valid"], true);
+    assert_eq!(verification["proof_valid"], true);
+    assert_eq!(
+        verification["certificate_id"],
+        certificate["certificate_id"]
+    );
+
+    // The proof's verification method resolves to the published issuer key.
+    let (status, issuer) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/certificates/issuer",
+        "",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(credential["issuer"]["id"], issuer["id"]);
+    assert_eq!(
+        credential["proof"]["verificationMethod"],
+        issuer["verificationMethod"][0]["id"]
+    );
+    assert_eq!(
+        issuer["verificationMethod"][0]["publicKeyMultibase"],
+        test_app.state.certificate_signer.public_key_multibase()
+    );
+
+    let mut tampered = credential.clone();
+    tampered["credentialSubject"]["identifier"][0]["identityHash"] = json!("Eve");
+    let (_, verification) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/certificates/verify",
+        "",
+        None,
+        Some(tampered),
+    )
+    .await;
+    assert_eq!(verification["valid"], false);
+    assert_eq!(verification["proof_valid"], false);
+    assert!(verification["certificate_id"].is_null());
+
+    // A credential naming another issuer is rejected even if its proof verifies.
+    let mut foreign = credential.clone();
+    foreign["issuer"]["id"] = json!("https://evil.example.com/api/certificates/issuer");
+    let (_, verification) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/certificates/verify",
+        "",
+        None,
+        Some(foreign),
+    )
+    .await;
+    assert_eq!(verification["valid"], false);
+
+    let cert_id = certificate["certificate_id"].as_str().unwrap();
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &format!("/api/admin/certificates/{}/revoke", cert_id),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(&test_app.app, "GET", &credential_uri, "", None, None).await;
+    assert_eq!(status, StatusCode::GONE);
+    let (_, verification) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/certificates/verify",
+        "",
+        None,
+        Some(credential),
+    )
+    .await;
+    assert_eq!(verification["valid"], false);
+    assert_eq!(verification["proof_valid"], true);
+    assert_eq!(verification["revoked"], true);
+}
+
+#[tokio::test]
+async fn test_open_badges_credentials_require_public_url() {
+    let test_app = setup_test_app().await;
+    let (admin_cookie, admin_csrf) = login_admin(&test_app.app, &test_app.state).await;
+    let codelab = create_codelab_as_admin(&test_app.app, &admin_cookie, &admin_csrf, false).await;
+    let (attendee_cookie, attendee_csrf, attendee_id) =
+        register_attendee(&test_app.app, &test_app.state, &codelab.id, "Ada", "code").await;
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &format!("/api/codelabs/{}/complete", codelab.id),
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Without PUBLIC_URL the issuer identity would come from the Host header.
+    for uri in [
+        format!("/api/certificates/{}/credential", attendee_id),
+        "/api/certificates/issuer".to_string(),
+    ] {
+        let (status, body) = send_json(&test_app.app, "GET", &uri, "", None, None).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body, "PUBLIC_URL_REQUIRED");
+    }
+}
+
+#[tokio::test]
+async fn test_certificate_pdf_download() {
+    let test_app = setup_test_app().await;
+    let (admin_cookie, admin_csrf) = login_admin(&test_app.app, &test_app.state).await;
+    let codelab = create_codelab_as_admin(&test_app.app, &admin_cookie, &admin_csrf, false).await;
+    let (attendee_cookie, attendee_csrf, attendee_id) =
+        register_attendee(&test_app.app, &test_app.state, &codelab.id, "Ada", "code").await;
+    let pdf_uri = format!("/api/certificates/{}/pdf", attendee_id);
+
+    let (status, _) = send_json(&test_app.app, "GET", &pdf_uri, "", None, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &format!("/api/codelabs/{}/complete", codelab.id),
+        &attendee_cookie,
+        Some(&attendee_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let pdf_res = test_app
+        .app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(&pdf_uri)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(pdf_res.status(), StatusCode::OK);
+    assert_eq!(
+        pdf_res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/pdf"
+    );
+    let disposition = pdf_res
+        .headers()
+        .get(header::CONTENT_DISPOSITION)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(disposition.starts_with("inline; filename=\"certificate_"));
+    let pdf = axum::body::to_bytes(pdf_res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+    let document = lopdf::Document::load_mem(&pdf).unwrap();
+    assert_eq!(document.get_pages().len(), 1);
+
+    let (_, certificate) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/certificates/{}", attendee_id),
+        "",
+        None,
+        None,
+    )
+    .await;
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &format!(
+            "/api/admin/certificates/{}/revoke",
+            certificate["certificate_id"].as_str().unwrap()
+        ),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(&test_app.app, "GET", &pdf_uri, "", None, None).await;
+    assert_eq!(status, StatusCode::GONE);
+}
+
+#[tokio::test]
+async fn test_staff_accounts_roles_and_audit_actor() {
+    let test_app = setup_test_app().await;
+    let (owner_cookie, owner_csrf) = login_admin(&test_app.app, &test_app.state).await;
+    let codelab = create_codelab_as_admin(&test_app.app, &owner_cookie, &owner_csrf, false).await;
+
+    let (status, ta) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/admin/users",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "username": "ta-kim", "password": "ta-password", "role": "ta" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(ta["role"], "ta");
+    assert!(ta.get("password_hash").is_none());
+    let ta_id = ta["id"].as_str().unwrap().to_string();
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/admin/users",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "username": "ta-kim", "password": "another-pw", "role": "author" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/admin/users",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "username": "boss", "password": "boss-password", "role": "admin" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (ta_cookie, ta_csrf) =
+        login_as(&test_app.app, &test_app.state, "ta-kim", "ta-password").await;
+    let (status, session) =
+        send_json(&test_app.app, "GET", "/api/session", &ta_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(session["sub"], ta_id.as_str());
+    assert_eq!(session["role"], "admin");
+    assert_eq!(session["staff_role"], "ta");
+    assert_eq!(session["permissions"], json!(["assist_learners"]));
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/codelabs/{}/staff/{}", codelab.id, ta_id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    // Assigned TAs can see help requests but cannot edit content or manage users.
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/help", codelab.id),
+        &ta_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/codelabs/{}", codelab.id),
+        &ta_cookie,
+        Some(&ta_csrf),
+        Some(json!({ "title": "Hijacked", "description": "x", "author": "x" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/admin/users",
+        &ta_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/submissions", codelab.id),
+        &ta_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Promote the TA to author: existing sessions are revoked and the role is
+    // re-read at the next login.
+    let (status, updated) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/admin/users/{}", ta_id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "role": "author", "display_name": "Kim" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["role"], "author");
+    assert_eq!(updated["display_name"], "Kim");
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/help", codelab.id),
+        &ta_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (author_cookie, author_csrf) =
+        login_as(&test_app.app, &test_app.state, "ta-kim", "ta-password").await;
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/codelabs/{}", codelab.id),
+        &author_cookie,
+        Some(&author_csrf),
+        Some(json!({
+            "title": "Edited by author",
+            "description": codelab.description,
+            "author": codelab.author
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/help", codelab.id),
+        &author_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, logs) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/admin/audit-logs?action=codelab_update",
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(logs
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|entry| entry["actor_id"] == ta_id.as_str()));
+
+    // A demoted session loses its author access immediately.
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/admin/users/{}", ta_id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "role": "ta" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/codelabs/{}", codelab.id),
+        &author_cookie,
+        Some(&author_csrf),
+        Some(json!({ "title": "Edited after demotion", "description": "x", "author": "x" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Changing the password also revokes the account's sessions; a display
+    // name change does not.
+    let (ta_cookie, _) = login_as(&test_app.app, &test_app.state, "ta-kim", "ta-password").await;
+    let help_uri = format!("/api/codelabs/{}/help", codelab.id);
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/admin/users/{}", ta_id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "display_name": "Kim TA" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(&test_app.app, "GET", &help_uri, &ta_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/admin/users/{}", ta_id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "password": "new-ta-password" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(&test_app.app, "GET", &help_uri, &ta_cookie, None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "DELETE",
+        &format!("/api/admin/users/{}", ta_id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, users) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/admin/users",
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(users, json!([]));
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/login",
+        "",
+        None,
+        Some(json!({ "admin_id": "ta-kim", "admin_pw": "new-ta-password" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_codelab_staff_assignments_scope_access() {
+    let test_app = setup_test_app().await;
+    let (owner_cookie, owner_csrf) = login_admin(&test_app.app, &test_app.state).await;
+    let assigned =
+        create_codelab_with_options(&test_app.app, &owner_cookie, &owner_csrf, false, false).await;
+    let other =
+        create_codelab_with_options(&test_app.app, &owner_cookie, &owner_csrf, false, false).await;
+
+    let (status, facilitator) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/admin/users",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(
+            json!({ "username": "guest-lee", "password": "guest-password", "role": "facilitator" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let facilitator_id = facilitator["id"].as_str().unwrap().to_string();
+    let (guest_cookie, guest_csrf) = login_as(
+        &test_app.app,
+        &test_app.state,
+        "guest-lee",
+        "guest-password",
+    )
+    .await;
+
+    // Unassigned staff see neither private codelab nor its live data.
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/attendees", assigned.id),
+        &guest_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, listed) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/codelabs",
+        &guest_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed, json!([]));
+    // Nor can they join it without an invite or the passcode.
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        &format!("/api/codelabs/{}/register", assigned.id),
+        &guest_cookie,
+        Some(&guest_csrf),
+        Some(json!({ "name": "Lee", "code": "code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/codelabs/{}/staff/{}", assigned.id, facilitator_id),
+        &guest_cookie,
+        Some(&guest_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json(
+        &test_app.app,
+        "PUT",
+        &format!("/api/codelabs/{}/staff/{}", assigned.id, facilitator_id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, staff) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/staff", assigned.id),
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(staff.as_array().unwrap().len(), 1);
+    assert_eq!(staff[0]["user_id"], facilitator_id.as_str());
+    assert_eq!(staff[0]["role"], "facilitator");
+
+    for path in ["attendees", "help", "submissions", "feedback"] {
+        let (status, _) = send_json(
+            &test_app.app,
+            "GET",
+            &format!("/api/codelabs/{}/{path}", assigned.id),
+            &guest_cookie,
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "assigned {path}");
+        let (status, _) = send_json(
+            &test_app.app,
+            "GET",
+            &format!("/api/codelabs/{}/{path}", other.id),
+            &guest_cookie,
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "unassigned {path}");
+    }
+    for (codelab_id, expected) in [
+        (&assigned.id, StatusCode::OK),
+        (&other.id, StatusCode::FORBIDDEN),
+    ] {
+        let (status, _) = send_json(
+            &test_app.app,
+            "POST",
+            "/api/ai/conversations",
+            &guest_cookie,
+            Some(&guest_csrf),
+            Some(json!({
+                "codelab_id": codelab_id,
+                "question": "q",
+                "answer": "a",
+            })),
+        )
+        .await;
+        assert_eq!(status, expected, "AI conversation in {codelab_id}");
+    }
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}", other.id),
+        &guest_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, listed) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/codelabs",
+        &guest_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let listed_ids: Vec<&str> = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|codelab| codelab["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(listed_ids, vec![assigned.id.as_str()]);
+
+    // Authors are assigned to the codelabs they create.
+    let (status, author) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/admin/users",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "username": "author-park", "password": "author-password", "role": "author" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (author_cookie, author_csrf) = login_as(
+        &test_app.app,
+        &test_app.state,
+        "author-park",
+        "author-password",
+    )
+    .await;
+    let authored =
+        create_codelab_with_options(&test_app.app, &author_cookie, &author_csrf, false, false)
+            .await;
+    let (status, staff) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/staff", authored.id),
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(staff[0]["user_id"], author["id"]);
+    let (status, _) = send_json(
+        &test_app.app,
+        "DELETE",
+        &format!("/api/codelabs/{}", other.id),
+        &author_cookie,
+        Some(&author_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send_json(
+        &test_app.app,
+        "DELETE",
+        &format!("/api/codelabs/{}/staff/{}", assigned.id, facilitator_id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_json(
+        &test_app.app,
+        "DELETE",
+        &format!("/api/codelabs/{}/staff/{}", assigned.id, facilitator_id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        &format!("/api/codelabs/{}/attendees", assigned.id),
+        &guest_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, logs) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/admin/audit-logs?action=codelab_staff_assign",
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(logs.as_array().unwrap().len(), 1);
+    assert_eq!(logs[0]["target_id"], facilitator_id.as_str());
+}
+
+#[derive(Default)]
+struct MockIdpState {
+    issuer: String,
+    subject: String,
+    groups: Vec<String>,
+    /// Issued authorization codes mapped to `(nonce, code_challenge, redirect_uri)`.
+    codes: HashMap<String, (String, String, String)>,
+}
+
+type MockIdp = Arc<Mutex<MockIdpState>>;
+
+/// Serves a minimal OpenID provider: discovery, authorize, token, and JWKS.
+async fn start_mock_oidc_provider(listener: tokio::net::TcpListener) -> MockIdp {
+    use axum::{
+        extract::{Form, Query, State},
+        response::{IntoResponse, Redirect},
+        routing::{get, post},
+        Json, Router,
+    };
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use sha2::{Digest, Sha256};
+
+    let signer = Arc::new(DocumentSigner::from_seed(&[7u8; 32]).expect("signer"));
+    let idp: MockIdp = Arc::new(Mutex::new(MockIdpState {
+        issuer: format!("http://{}", listener.local_addr().expect("addr")),
+        ..Default::default()
+    }));
+
+    let discovery = |State((idp, _)): State<(MockIdp, Arc<DocumentSigner>)>| async move {
+        let issuer = idp.lock().unwrap().issuer.clone();
+        Json(json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{issuer}/authorize"),
+            "token_endpoint": format!("{issuer}/token"),
+            "jwks_uri": format!("{issuer}/jwks"),
+        }))
+    };
+    let authorize = |State((idp, _)): State<(MockIdp, Arc<DocumentSigner>)>,
+                     Query(params): Query<HashMap<String, String>>| async move {
+        assert_eq!(params["client_id"], "codelabs");
+        assert_eq!(params["code_challenge_method"], "S256");
+        let code = uuid::Uuid::new_v4().to_string();
+        idp.lock().unwrap().codes.insert(
+            code.clone(),
+            (
+                params["nonce"].clone(),
+                params["code_challenge"].clone(),
+                params["redirect_uri"].clone(),
+            ),
+        );
+        Redirect::to(&format!(
+            "{}?code={code}&state={}",
+            params["redirect_uri"], params["state"]
+        ))
+    };
+    let token = |State((idp, signer)): State<(MockIdp, Arc<DocumentSigner>)>,
+                 Form(form): Form<HashMap<String, String>>| async move {
+        let mut idp = idp.lock().unwrap();
+        let Some((nonce, challenge, redirect_uri)) = idp.codes.remove(&form["code"]) else {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "invalid_grant" })),
+            )
+                .into_response();
+        };
+        let verifier_ok =
+            URL_SAFE_NO_PAD.encode(Sha256::digest(form["code_verifier"].as_bytes())) == challenge;
+        if !verifier_ok || form["redirect_uri"] != redirect_uri {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "invalid_grant" })),
+            )
+                .into_response();
+        }
+        let now = chrono::Utc::now().timestamp();
+        let id_token = signer
+            .sign(&json!({
+                "iss": idp.issuer,
+                "sub": idp.subject,
+                "aud": "codelabs",
+                "iat": now,
+                "exp": now + 300,
+                "nonce": nonce,
+                "preferred_username": "mentor.kim",
+                "name": "Mentor Kim",
+                "groups": idp.groups,
+            }))
+            .expect("sign id_token");
+        Json(json!({ "access_token": "opaque", "token_type": "Bearer", "id_token": id_token }))
+            .into_response()
+    };
+    let jwks = |State((_, signer)): State<(MockIdp, Arc<DocumentSigner>)>| async move {
+        Json(json!({ "keys": [{
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "use": "sig",
+            "alg": "EdDSA",
+            "kid": signer.key_id(),
+            "x": signer.public_key_base64url(),
+        }] }))
+    };
+
+    let router = Router::new()
+        .route("/.well-known/openid-configuration", get(discovery))
+        .route("/authorize", get(authorize))
+        .route("/token", post(token))
+        .route("/jwks", get(jwks))
+        .with_state((idp.clone(), signer));
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.expect("mock idp");
+    });
+    idp
+}
+
+/// Runs the browser side of an SSO login and returns the callback response.
+async fn run_sso_login(
+    test_app: &TestApp,
+    idp: &MockIdp,
+    subject: &str,
+    groups: &[&str],
+) -> (axum::response::Response, String, String) {
+    {
+        let mut idp = idp.lock().unwrap();
+        idp.subject = subject.to_string();
+        idp.groups = groups.iter().map(|group| group.to_string()).collect();
+    }
+
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/auth/oidc/login?return_to=/admin/reports")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let (state_cookie, _) = extract_cookies(response.headers());
+    let authorize_url = response.headers()[header::LOCATION].to_str().unwrap();
+    assert!(authorize_url.contains("code_challenge="));
+
+    let browser = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let authorized = browser.get(authorize_url).send().await.expect("authorize");
+    let callback = url::Url::parse(
+        authorized.headers()[reqwest::header::LOCATION]
+            .to_str()
+            .unwrap(),
+    )
+    .expect("callback url");
+    assert_eq!(callback.path(), "/api/auth/oidc/callback");
+    let callback_uri = format!(
+        "{}?{}",
+        callback.path(),
+        callback.query().unwrap_or_default()
+    );
+
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(&callback_uri)
+                .header(header::COOKIE, &state_cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    (response, state_cookie, callback_uri)
+}
+
+#[tokio::test]
+async fn test_oidc_sso_login_with_mock_provider() {
+    let disabled = setup_test_app().await;
+    let (status, body) = send_json(&disabled.app, "GET", "/api/auth/oidc", "", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["enabled"], false);
+
+    let Some(listener) = bind_local_listener_or_skip().await else {
+        return;
+    };
+    let idp = start_mock_oidc_provider(listener).await;
+    let issuer_url = idp.lock().unwrap().issuer.clone();
+    let test_app = setup_test_app_with(|state| {
+        state.oidc = Some(OidcConfig {
+            issuer_url,
+            client_id: "codelabs".to_string(),
+            client_secret: Some("client-secret".to_string()),
+            redirect_url: None,
+            scopes: "openid profile email".to_string(),
+            groups_claim: "groups".to_string(),
+            role_mappings: vec![
+                ("codelab-admins".to_string(), StaffRole::Owner),
+                ("codelab-tas".to_string(), StaffRole::Ta),
+            ],
+            default_role: None,
+            provider_name: "Mock IdP".to_string(),
+        });
+    })
+    .await;
+
+    let (status, body) = send_json(&test_app.app, "GET", "/api/auth/oidc", "", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["enabled"], true);
+    assert_eq!(body["provider_name"], "Mock IdP");
+
+    let (response, state_cookie, callback_uri) =
+        run_sso_login(&test_app, &idp, "idp-user-1", &["staff", "codelab-tas"]).await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()[header::LOCATION], "/admin/reports");
+    let (session_cookie, cookies) = extract_cookies(response.headers());
+    assert!(cookies.contains_key(&test_app.state.auth.cookie_name));
+    let (status, session) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/session",
+        &session_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(session["role"], "admin");
+    assert_eq!(session["staff_role"], "ta");
+    let user_id = session["sub"].as_str().unwrap().to_string();
+
+    // The state is single-use and must match the browser cookie.
+    let replay = test_app
+        .app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(&callback_uri)
+                .header(header::COOKIE, &state_cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(replay.status(), StatusCode::BAD_REQUEST);
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        &callback_uri,
+        "oc_oidc_state=forged",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Logging in again reuses the account and refreshes the mapped role.
+    let (response, _, _) = run_sso_login(
+        &test_app,
+        &idp,
+        "idp-user-1",
+        &["codelab-admins", "codelab-tas"],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let (owner_cookie, _) = extract_cookies(response.headers());
+    let (_, session) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/session",
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(session["sub"], user_id.as_str());
+    assert_eq!(session["staff_role"], "owner");
+    // The role change ends the session issued with the old role.
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/session",
+        &session_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, users) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/admin/users",
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let users = users.as_array().unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0]["username"], "mentor.kim");
+    assert_eq!(users[0]["display_name"], "Mentor Kim");
+
+    // SSO accounts have no usable password.
+    let (status, _) = send_json(
+        &test_app.app,
+        "POST",
+        "/api/login",
+        "",
+        None,
+        Some(json!({ "admin_id": "mentor.kim", "admin_pw": "anything" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (response, _, _) = run_sso_login(&test_app, &idp, "idp-user-2", &["marketing"]).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(extract_cookies(response.headers())
+        .1
+        .get(&test_app.state.auth.cookie_name)
+        .is_none());
+
+    let (status, logs) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/admin/audit-logs?action=admin_login_success",
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(logs
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|log| log["actor_id"] == user_id.as_str()
+            && log["metadata"]
+                .as_str()
+                .is_some_and(|metadata| metadata.contains("\"oidc\""))));
+
+    // A demotion at the IdP revokes the owner session on the next login.
+    let (response, _, _) = run_sso_login(&test_app, &idp, "idp-user-1", &["codelab-tas"]).await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let (ta_cookie, _) = extract_cookies(response.headers());
+    let (status, _) = send_json(
+        &test_app.app,
+        "GET",
+        "/api/session",
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Losing every mapped group revokes the remaining sessions too.
+    let (response, _, _) = run_sso_login(&test_app, &idp, "idp-user-1", &["marketing"]).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let (status, _) = send_json(&test_app.app, "GET", "/api/session", &ta_cookie, None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+async fn send_bearer(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"));
+    let request = match body {
+        Some(body) => builder
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    };
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let value = serde_json::from_slice(&bytes)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).to_string()));
+    (status, value)
+}
+
+#[tokio::test]
+async fn test_personal_api_tokens_scopes_and_revocation() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (owner_cookie, owner_csrf) = login_admin(app, &test_app.state).await;
+
+    let (status, writer) = send_json(
+        app,
+        "POST",
+        "/api/admin/users",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "username": "writer", "password": "writer-password", "role": "author" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let writer_id = writer["id"].as_str().unwrap().to_string();
+    let (writer_cookie, writer_csrf) =
+        login_as(app, &test_app.state, "writer", "writer-password").await;
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        "/api/admin/tokens",
+        &writer_cookie,
+        Some(&writer_csrf),
+        Some(json!({ "name": "ci", "scopes": ["manage_users"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        "/api/admin/tokens",
+        &writer_cookie,
+        Some(&writer_csrf),
+        Some(json!({ "name": "ci", "expires_in_days": 0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, created) = send_json(
+        app,
+        "POST",
+        "/api/admin/tokens",
+        &writer_cookie,
+        Some(&writer_csrf),
+        Some(json!({ "name": "ci", "expires_in_days": 30 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["scopes"], json!(["edit_content"]));
+    assert_eq!(created["user_id"], writer_id.as_str());
+    assert!(created["expires_at"].as_i64().is_some());
+    let token = created["token"].as_str().unwrap().to_string();
+    let token_id = created["id"].as_str().unwrap().to_string();
+    assert!(token.starts_with("oc_pat_"));
+    assert!(token.starts_with(created["token_prefix"].as_str().unwrap()));
+
+    // Bearer requests need neither cookies nor a CSRF header.
+    let (status, session) = send_bearer(app, "GET", "/api/session", &token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(session["sub"], writer_id.as_str());
+    assert_eq!(session["staff_role"], "author");
+    assert_eq!(session["permissions"], json!(["edit_content"]));
+
+    let (status, codelab) = send_bearer(
+        app,
+        "POST",
+        "/api/codelabs",
+        &token,
+        Some(json!({ "title": "CI Codelab", "description": "d", "author": "ci" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let codelab_id = codelab["id"].as_str().unwrap().to_string();
+    let (status, _) = send_bearer(
+        app,
+        "PUT",
+        &format!("/api/codelabs/{codelab_id}/steps"),
+        &token,
+        Some(json!({ "steps": [{ "title": "Intro", "content_markdown": "Hello" }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Tokens cannot mint further tokens or reach beyond their role.
+    let (status, _) = send_bearer(
+        app,
+        "POST",
+        "/api/admin/tokens",
+        &token,
+        Some(json!({ "name": "escalate" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_bearer(app, "GET", "/api/admin/users", &token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_bearer(app, "GET", "/api/session", "oc_pat_unknown", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Scopes narrow an owner token below the owner's role.
+    let (status, owner_token) = send_json(
+        app,
+        "POST",
+        "/api/admin/tokens",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "name": "reports", "scopes": ["manage_system"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(owner_token["expires_at"].is_null());
+    let owner_token = owner_token["token"].as_str().unwrap().to_string();
+    let (status, _) = send_bearer(app, "GET", "/api/admin/audit-logs", &owner_token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_bearer(app, "GET", "/api/admin/users", &owner_token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // A scoped token cannot approve a CLI login, which would hand out a
+    // session with the owner's full role, nor reach token or AI endpoints.
+    let (status, start) = send_json(app, "POST", "/api/cli/auth/start", "", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_bearer(
+        app,
+        "POST",
+        &format!(
+            "/api/cli/auth/approve/{}",
+            start["request_id"].as_str().unwrap()
+        ),
+        &owner_token,
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    for uri in ["/api/admin/tokens", "/api/ai/threads"] {
+        let (status, _) = send_bearer(app, "GET", uri, &owner_token, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{uri}");
+    }
+
+    let (status, listed) =
+        send_json(app, "GET", "/api/admin/tokens", &writer_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let listed = listed.as_array().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].get("token").is_none());
+    assert!(listed[0].get("token_hash").is_none());
+    assert!(listed[0]["last_used_at"].is_string());
+    let (_, listed) = send_json(app, "GET", "/api/admin/tokens", &owner_cookie, None, None).await;
+    assert_eq!(listed.as_array().unwrap().len(), 2);
+
+    // Demoting the owner of a token takes effect on the next request.
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &format!("/api/admin/users/{writer_id}"),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "role": "ta" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, session) = send_bearer(app, "GET", "/api/session", &token, None).await;
+    assert_eq!(session["permissions"], json!([]));
+    let (status, _) = send_bearer(
+        app,
+        "PUT",
+        &format!("/api/codelabs/{codelab_id}/steps"),
+        &token,
+        Some(json!({ "steps": [] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    sqlx::query("UPDATE api_tokens SET expires_at_epoch = 1 WHERE id = ?")
+        .bind(&token_id)
+        .execute(&test_app.state.pool)
+        .await
+        .unwrap();
+    let (status, _) = send_bearer(app, "GET", "/api/session", &token, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // The role change signed the writer out; their new session can still
+    // revoke their own token.
+    let (writer_cookie, writer_csrf) =
+        login_as(app, &test_app.state, "writer", "writer-password").await;
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("/api/admin/tokens/{token_id}"),
+        &writer_cookie,
+        Some(&writer_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("/api/admin/tokens/{token_id}"),
+        &writer_cookie,
+        Some(&writer_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, logs) = send_json(
+        app,
+        "GET",
+        "/api/admin/audit-logs?action=api_token_create",
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(logs.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_session_revocation_and_logout_everywhere() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let state = &test_app.state;
+    let (owner_cookie, owner_csrf) = login_admin(app, state).await;
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        "/api/admin/users",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "username": "mentor", "password": "mentor-password", "role": "ta" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (laptop_cookie, laptop_csrf) = login_as(app, state, "mentor", "mentor-password").await;
+    let (phone_cookie, _) = login_as(app, state, "mentor", "mentor-password").await;
+    let (status, mentor) = send_json(app, "GET", "/api/session", &laptop_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let mentor_id = mentor["sub"].as_str().unwrap().to_string();
+
+    // Non-owners cannot see or revoke sessions.
+    let (status, _) = send_json(
+        app,
+        "GET",
+        "/api/admin/sessions",
+        &laptop_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, sessions) = send_json(
+        app,
+        "GET",
+        &format!("/api/admin/sessions?user_id={mentor_id}"),
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let sessions = sessions.as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0]["staff_role"], "ta");
+
+    // Revoking one session leaves the other device signed in.
+    let session_id = sessions[0]["id"].as_str().unwrap().to_string();
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("/api/admin/sessions/{session_id}"),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("/api/admin/sessions/{session_id}"),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let laptop_status = send_json(app, "GET", "/api/session", &laptop_cookie, None, None)
+        .await
+        .0;
+    let phone_status = send_json(app, "GET", "/api/session", &phone_cookie, None, None)
+        .await
+        .0;
+    let mut statuses = [laptop_status, phone_status];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::UNAUTHORIZED]);
+    let (active_cookie, active_csrf) = if laptop_status == StatusCode::OK {
+        (laptop_cookie.clone(), laptop_csrf.clone())
+    } else {
+        login_as(app, state, "mentor", "mentor-password").await
+    };
+
+    // Logging out everywhere ends every remaining session of the caller.
+    let (status, body) = send_json(
+        app,
+        "POST",
+        "/api/logout/all",
+        &active_cookie,
+        Some(&active_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["revoked"].as_u64().unwrap() >= 1);
+    for cookie in [&laptop_cookie, &phone_cookie, &active_cookie] {
+        let (status, _) = send_json(app, "GET", "/api/session", cookie, None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+    let (status, _) = send_json(app, "GET", "/api/session", &owner_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // A plain logout revokes the token server-side, so replaying the cookie fails.
+    let (cookie, csrf) = login_as(app, state, "mentor", "mentor-password").await;
+    let (status, _) = send_json(app, "POST", "/api/logout", &cookie, Some(&csrf), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_json(app, "GET", "/api/session", &cookie, None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Attendee sessions can be revoked per codelab.
+    let codelab = create_codelab_as_admin(app, &owner_cookie, &owner_csrf, false).await;
+    let (attendee_cookie, _, _) =
+        register_attendee(app, state, &codelab.id, "Learner", "learner-code").await;
+    let (status, _) = send_json(
+        app,
+        "POST",
+        "/api/admin/sessions/revoke",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = send_json(
+        app,
+        "POST",
+        "/api/admin/sessions/revoke",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "codelab_id": codelab.id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["revoked"], 1);
+    let (status, _) = send_json(app, "GET", "/api/session", &attendee_cookie, None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, logs) = send_json(
+        app,
+        "GET",
+        "/api/admin/audit-logs?action=session_revoke",
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(logs.as_array().unwrap().len(), 3);
+}
+
+fn totp_now(secret_base32: &str, steps_ahead: u64) -> String {
+    let secret = base32_decode(secret_base32).expect("base32 secret");
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    totp_code(&secret, now / TOTP_STEP_SECONDS + steps_ahead)
+}
+
+async fn start_password_login(app: &axum::Router, username: &str, password: &str) -> Value {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/login")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::to_vec(&json!({ "admin_id": username, "admin_pw": password }))
+                        .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(header::SET_COOKIE).is_none());
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+async fn finish_mfa_login(app: &axum::Router, mfa_token: &str, code: &str) -> (StatusCode, String) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/login/mfa")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::to_vec(&json!({ "mfa_token": mfa_token, "code": code })).unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let (cookie_header, _) = extract_cookies(response.headers());
+    (status, cookie_header)
+}
+
+#[tokio::test]
+async fn test_totp_enrollment_login_and_recovery_codes() {
+    // CLI approvals share the login rate limit with the sign-ins below.
+    let test_app = setup_test_app_with(|state| state.rate_limit_config.login_limit = 100).await;
+    let app = &test_app.app;
+    let state = &test_app.state;
+    let (owner_cookie, owner_csrf) = login_admin(app, state).await;
+
+    let (status, user) = send_json(
+        app,
+        "POST",
+        "/api/admin/users",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "username": "mentor", "password": "mentor-password", "role": "author" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let mentor_id = user["id"].as_str().unwrap().to_string();
+    let (cookie, csrf) = login_as(app, state, "mentor", "mentor-password").await;
+
+    let (status, body) = send_json(app, "GET", "/api/admin/mfa", &cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["enabled"], false);
+
+    let (status, enrollment) = send_json(
+        app,
+        "POST",
+        "/api/admin/mfa/totp",
+        &cookie,
+        Some(&csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let secret = enrollment["secret"].as_str().unwrap().to_string();
+    let uri = enrollment["provisioning_uri"].as_str().unwrap();
+    assert!(uri.starts_with("otpauth://totp/"));
+    assert!(uri.contains(&format!("secret={secret}")));
+
+    // Enrollment stays pending until a valid code confirms it.
+    let stale = totp_code(&base32_decode(&secret).unwrap(), 1);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        "/api/admin/mfa/totp/confirm",
+        &cookie,
+        Some(&csrf),
+        Some(json!({ "code": stale })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (_, body) = send_json(app, "GET", "/api/admin/mfa", &cookie, None, None).await;
+    assert_eq!(body["pending"], true);
+    login_as(app, state, "mentor", "mentor-password").await;
+
+    // Secrets are sealed with the keyring; ones sealed with ADMIN_PW before it
+    // still verify and move onto the active key.
+    let stored_secret = || async {
+        sqlx::query_scalar::<_, String>(&state.q("SELECT secret FROM staff_totp WHERE subject = ?"))
+            .bind(&mentor_id)
+            .fetch_one(&state.pool)
+            .await
+            .unwrap()
+    };
+    let key_prefix = format!("{}:", state.secret_keyring.key_id());
+    assert!(stored_secret().await.starts_with(&key_prefix));
+    sqlx::query(&state.q("UPDATE staff_totp SET secret = ? WHERE subject = ?"))
+        .bind(encrypt_with_password(&secret, &state.admin_pw).unwrap())
+        .bind(&mentor_id)
+        .execute(&state.pool)
+        .await
+        .unwrap();
+
+    let (status, body) = send_json(
+        app,
+        "POST",
+        "/api/admin/mfa/totp/confirm",
+        &cookie,
+        Some(&csrf),
+        Some(json!({ "code": totp_now(&secret, 0) })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let recovery_codes: Vec<String> = body["recovery_codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|code| code.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(recovery_codes.len(), 10);
+    assert!(stored_secret().await.starts_with(&key_prefix));
+
+    // The password alone now yields a challenge instead of a session cookie.
+    let challenge = start_password_login(app, "mentor", "mentor-password").await;
+    assert_eq!(challenge["status"], "mfa_required");
+    let mfa_token = challenge["mfa_token"].as_str().unwrap().to_string();
+    let (status, _) = finish_mfa_login(app, &mfa_token, &stale).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let code = totp_now(&secret, 1);
+    let (status, mfa_cookie) = finish_mfa_login(app, &mfa_token, &code).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, session) = send_json(app, "GET", "/api/session", &mfa_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(session["sub"], mentor_id.as_str());
+    let (status, _) = finish_mfa_login(app, &mfa_token, &code).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // A code cannot be replayed, but each recovery code works exactly once.
+    let challenge = start_password_login(app, "mentor", "mentor-password").await;
+    let mfa_token = challenge["mfa_token"].as_str().unwrap().to_string();
+    let (status, _) = finish_mfa_login(app, &mfa_token, &code).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = finish_mfa_login(app, &mfa_token, &recovery_codes[0].to_uppercase()).await;
+    assert_eq!(status, StatusCode::OK);
+    let challenge = start_password_login(app, "mentor", "mentor-password").await;
+    let mfa_token = challenge["mfa_token"].as_str().unwrap().to_string();
+    let (status, _) = finish_mfa_login(app, &mfa_token, &recovery_codes[0]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (_, body) = send_json(app, "GET", "/api/admin/mfa", &cookie, None, None).await;
+    assert_eq!(body["enabled"], true);
+    assert_eq!(body["recovery_codes_remaining"], 9);
+
+    // Browser approval of CLI login also asks for the second factor.
+    let (status, start) = send_json(app, "POST", "/api/cli/auth/start", "", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let approve_uri = format!(
+        "/api/cli/auth/approve/{}",
+        start["request_id"].as_str().unwrap()
+    );
+    let credentials = json!({ "admin_id": "mentor", "admin_pw": "mentor-password" });
+    let (status, _) = send_json(app, "POST", &approve_uri, "", None, Some(credentials)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = send_json(
+        app,
+        "POST",
+        &approve_uri,
+        "",
+        None,
+        Some(json!({
+            "admin_id": "mentor",
+            "admin_pw": "mentor-password",
+            "otp_code": recovery_codes[1],
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "approved");
+
+    // Wrong codes sent with the password count against one shared limit.
+    let (_, start) = send_json(app, "POST", "/api/cli/auth/start", "", None, None).await;
+    let approve_uri = format!(
+        "/api/cli/auth/approve/{}",
+        start["request_id"].as_str().unwrap()
+    );
+    let with_code = |code: &str| json!({ "admin_id": "mentor", "admin_pw": "mentor-password", "otp_code": code });
+    for _ in 0..5 {
+        let (status, _) = send_json(
+            app,
+            "POST",
+            &approve_uri,
+            "",
+            None,
+            Some(with_code("000000")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &approve_uri,
+        "",
+        None,
+        Some(with_code(&recovery_codes[2])),
+    )
+    .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    // An owner can reset a lost authenticator, which also signs the user out.
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("/api/admin/users/{mentor_id}/mfa"),
+        &cookie,
+        Some(&csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("/api/admin/users/{mentor_id}/mfa"),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_json(app, "GET", "/api/session", &mfa_cookie, None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    login_as(app, state, "mentor", "mentor-password").await;
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("/api/admin/users/{mentor_id}/mfa"),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, logs) = send_json(
+        app,
+        "GET",
+        "/api/admin/audit-logs?action=mfa_enable",
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(logs.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_private_codelab_passcode_and_invite_registration() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (owner_cookie, owner_csrf) = login_admin(app, &test_app.state).await;
+    let codelab = create_codelab_with_options(app, &owner_cookie, &owner_csrf, false, false).await;
+    let register_path = format!("/api/codelabs/{}/register", codelab.id);
+    let join_path = format!("/api/codelabs/{}/join", codelab.id);
+
+    // Without a passcode or invite, private codelabs stay closed.
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Ada", "code": "ada-code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json(app, "GET", &join_path, "", None, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &format!("/api/codelabs/{}/passcode", codelab.id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "passcode": "abc" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &format!("/api/codelabs/{}/passcode", codelab.id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "passcode": "open-sesame" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, stored) = send_json(
+        app,
+        "GET",
+        &format!("/api/codelabs/{}/passcode", codelab.id),
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stored["passcode"], "open-sesame");
+
+    // Passcodes sealed with ADMIN_PW before the keyring still work and move
+    // onto the active key when read.
+    let state = &test_app.state;
+    let stored_passcode = || async {
+        sqlx::query_scalar::<_, String>(
+            &state.q("SELECT passcode FROM codelab_passcodes WHERE codelab_id = ?"),
+        )
+        .bind(&codelab.id)
+        .fetch_one(&state.pool)
+        .await
+        .unwrap()
+    };
+    let key_prefix = format!("{}:", state.secret_keyring.key_id());
+    assert!(stored_passcode().await.starts_with(&key_prefix));
+    sqlx::query(&state.q("UPDATE codelab_passcodes SET passcode = ? WHERE codelab_id = ?"))
+        .bind(encrypt_with_password("open-sesame", &state.admin_pw).unwrap())
+        .bind(&codelab.id)
+        .execute(&state.pool)
+        .await
+        .unwrap();
+
+    let (status, info) = send_json(app, "GET", &join_path, "", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(info["is_public"], false);
+    assert_eq!(info["passcode_required"], true);
+    assert_eq!(info["invite_valid"], false);
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Ada", "code": "ada-code", "passcode": "wrong-pass" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Ada", "code": "ada-code", "passcode": "open-sesame" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(stored_passcode().await.starts_with(&key_prefix));
+    // Registered attendees rejoin with their own code alone.
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Ada", "code": "ada-code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Ada", "code": "guess" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Single-use invite.
+    let (status, created) = send_json(
+        app,
+        "POST",
+        &format!("/api/codelabs/{}/invites", codelab.id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "label": "team a", "max_uses": 1, "expires_in_hours": 24 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let token = created["token"].as_str().unwrap().to_string();
+    let invite_id = created["id"].as_str().unwrap().to_string();
+    assert!(created["invite_path"]
+        .as_str()
+        .unwrap()
+        .ends_with(&format!("?invite={token}")));
+    assert!(token.starts_with(created["token_prefix"].as_str().unwrap()));
+
+    let (status, info) = send_json(
+        app,
+        "GET",
+        &format!("{join_path}?invite={token}"),
+        "",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(info["invite_valid"], true);
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Grace", "code": "grace-code", "invite_token": token })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Linus", "code": "linus-code", "invite_token": token })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, invites) = send_json(
+        app,
+        "GET",
+        &format!("/api/codelabs/{}/invites", codelab.id),
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(invites[0]["id"], invite_id.as_str());
+    assert_eq!(invites[0]["use_count"], 1);
+    assert!(invites[0].get("token_hash").is_none());
+
+    // Revoked invites stop admitting attendees.
+    let (status, created) = send_json(
+        app,
+        "POST",
+        &format!("/api/codelabs/{}/invites", codelab.id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let open_token = created["token"].as_str().unwrap().to_string();
+    let open_id = created["id"].as_str().unwrap().to_string();
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("/api/codelabs/{}/invites/{open_id}", codelab.id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Linus", "code": "linus-code", "invite_token": open_token })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("/api/codelabs/{}/invites/{open_id}", codelab.id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("/api/codelabs/{}/invites", codelab.id),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "max_uses": 0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Unassigned staff cannot manage join access.
+    let (status, _) = send_json(
+        app,
+        "POST",
+        "/api/admin/users",
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "username": "ta-kim", "password": "ta-password", "role": "ta" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (ta_cookie, ta_csrf) = login_as(app, &test_app.state, "ta-kim", "ta-password").await;
+    let (status, _) = send_json(
+        app,
+        "GET",
+        &format!("/api/codelabs/{}/passcode", codelab.id),
+        &ta_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("/api/codelabs/{}/invites", codelab.id),
+        &ta_cookie,
+        Some(&ta_csrf),
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_attendee_capacity_waitlist_and_registration_window() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (owner_cookie, owner_csrf) = login_admin(app, &test_app.state).await;
+    let codelab = create_codelab_with_options(app, &owner_cookie, &owner_csrf, true, false).await;
+    let codelab_path = format!("/api/codelabs/{}", codelab.id);
+    let register_path = format!("{codelab_path}/register");
+    let waitlist_path = format!("{codelab_path}/waitlist");
+
+    let (status, updated) = send_json(
+        app,
+        "PUT",
+        &codelab_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({
+            "title": codelab.title,
+            "description": codelab.description,
+            "author": codelab.author,
+            "max_attendees": 1,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["max_attendees"], 1);
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Ada", "code": "ada-code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The seat is taken: the next learner is queued without a session.
+    let (status, queued) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Grace", "code": "grace-code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(queued["status"], "waitlisted");
+    assert_eq!(queued["position"], 1);
+    let waitlist_id = queued["waitlist_id"].as_str().unwrap().to_string();
+
+    let (status, again) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Grace", "code": "grace-code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(again["waitlist_id"], waitlist_id.as_str());
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Grace", "code": "other-code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    // The database rejects a second entry for a queued nickname.
+    let duplicate = sqlx::query(&test_app.state.q(
+        "INSERT INTO codelab_waitlist (id, codelab_id, name, code) VALUES ('wait-dup', ?, 'Grace', 'sealed')",
+    ))
+    .bind(&codelab.id)
+    .execute(&test_app.state.pool)
+    .await;
+    assert!(duplicate
+        .unwrap_err()
+        .as_database_error()
+        .is_some_and(|err| err.is_unique_violation()));
+    let (status, queued) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Linus", "code": "linus-code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(queued["position"], 2);
+    let linus_id = queued["waitlist_id"].as_str().unwrap().to_string();
+
+    let (status, listed) = send_json(app, "GET", &waitlist_path, &owner_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed.as_array().unwrap().len(), 2);
+    assert_eq!(listed[0]["name"], "Grace");
+    assert_eq!(listed[1]["position"], 2);
+    let (status, _) = send_json(app, "GET", &waitlist_path, "", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Promotion admits the learner past the limit; they rejoin with their code.
+    let (status, promoted) = send_json(
+        app,
+        "POST",
+        &format!("{waitlist_path}/{waitlist_id}/promote"),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(promoted["name"], "Grace");
+    let (status, joined) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Grace", "code": "grace-code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(joined["id"], promoted["id"]);
+
+    let remove_path = format!("{waitlist_path}/{linus_id}");
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &remove_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &remove_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Omitted fields keep their values; a past closing time stops new sign-ups.
+    let (status, updated) = send_json(
+        app,
+        "PUT",
+        &codelab_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({
+            "title": codelab.title,
+            "description": codelab.description,
+            "author": codelab.author,
+            "max_attendees": 0,
+            "registration_closes_at": 1_000,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["max_attendees"], 0);
+    assert_eq!(updated["registration_closes_at"], 1_000);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Hedy", "code": "hedy-code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &register_path,
+        "",
+        None,
+        Some(json!({ "name": "Ada", "code": "ada-code" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, updated) = send_json(
+        app,
+        "PUT",
+        &codelab_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({
+            "title": codelab.title,
+            "description": codelab.description,
+            "author": codelab.author,
+            "registration_closes_at": 0,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(updated["registration_closes_at"].is_null());
+}
+
+#[tokio::test]
+async fn test_codelab_cohorts_isolate_live_data() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (owner_cookie, owner_csrf) = login_admin(app, &test_app.state).await;
+    let codelab = create_codelab_with_options(app, &owner_cookie, &owner_csrf, true, false).await;
+    let codelab_path = format!("/api/codelabs/{}", codelab.id);
+    let cohorts_path = format!("{codelab_path}/cohorts");
+    let now = chrono::Utc::now().timestamp();
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &cohorts_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "name": "Backwards", "starts_at": now, "ends_at": now - 60 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, running) = send_json(
+        app,
+        "POST",
+        &cohorts_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "name": "Morning", "starts_at": now - 3_600, "ends_at": now + 3_600 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let running_id = running["id"].as_str().unwrap().to_string();
+    let (status, upcoming) = send_json(
+        app,
+        "POST",
+        &cohorts_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "name": "Evening", "starts_at": now + 7_200 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let upcoming_id = upcoming["id"].as_str().unwrap().to_string();
+
+    // Without an explicit cohort, learners land in the run that is live now.
+    let (ada_cookie, ada_csrf, _) =
+        register_attendee(app, &test_app.state, &codelab.id, "Ada", "ada-code").await;
+    let (status, joined) = send_json(
+        app,
+        "POST",
+        &format!("{codelab_path}/register"),
+        "",
+        None,
+        Some(json!({ "name": "Grace", "code": "grace-code", "cohort_id": upcoming_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(joined["cohort_id"], upcoming_id.as_str());
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("{codelab_path}/register"),
+        "",
+        None,
+        Some(json!({ "name": "Linus", "code": "linus-code", "cohort_id": "missing" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let attendees_path = format!("{codelab_path}/attendees");
+    let (status, roster) = send_json(app, "GET", &attendees_path, &ada_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(roster.as_array().unwrap().len(), 1);
+    assert_eq!(roster[0]["cohort_id"], running_id.as_str());
+    let (status, roster) = send_json(
+        app,
+        "GET",
+        &format!("{attendees_path}?cohort_id={upcoming_id}"),
+        &ada_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(roster[0]["name"], "Ada");
+    let (status, roster) = send_json(app, "GET", &attendees_path, &owner_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(roster.as_array().unwrap().len(), 2);
+    let (status, roster) = send_json(
+        app,
+        "GET",
+        &format!("{attendees_path}?cohort_id={upcoming_id}"),
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(roster.as_array().unwrap().len(), 1);
+    assert_eq!(roster[0]["name"], "Grace");
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("{codelab_path}/help"),
+        &ada_cookie,
+        Some(&ada_csrf),
+        Some(json!({ "step_number": 1 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, help) = send_json(
+        app,
+        "GET",
+        &format!("{codelab_path}/help?cohort_id={upcoming_id}"),
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(help.as_array().unwrap().is_empty());
+
+    let (status, summaries) = send_json(app, "GET", &cohorts_path, &owner_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let summaries = summaries.as_array().unwrap();
+    assert_eq!(summaries.len(), 2);
+    let morning = summaries
+        .iter()
+        .find(|summary| summary["id"] == running_id.as_str())
+        .unwrap();
+    assert_eq!(morning["name"], "Morning");
+    assert_eq!(morning["attendee_count"], 1);
+    assert_eq!(morning["help_request_count"], 1);
+    let (status, _) = send_json(app, "GET", &cohorts_path, &ada_cookie, None, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, renamed) = send_json(
+        app,
+        "PUT",
+        &format!("{cohorts_path}/{upcoming_id}"),
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "name": "Evening (moved)", "starts_at": now + 10_800 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(renamed["name"], "Evening (moved)");
+
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("{cohorts_path}/{running_id}"),
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, empty) = send_json(
+        app,
+        "POST",
+        &cohorts_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "name": "Spare" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let spare_path = format!("{cohorts_path}/{}", empty["id"].as_str().unwrap());
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &spare_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &spare_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_attendee_roster_import_creates_and_reports_duplicates() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (owner_cookie, owner_csrf) = login_admin(app, &test_app.state).await;
+    let codelab = create_codelab_with_options(app, &owner_cookie, &owner_csrf, false, false).await;
+    let codelab_path = format!("/api/codelabs/{}", codelab.id);
+    let import_path = format!("{codelab_path}/attendees/import");
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &import_path,
+        "",
+        None,
+        Some(json!({ "attendees": [{ "name": "Grace" }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, result) = send_json(
+        app,
+        "POST",
+        &import_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "attendees": [{ "name": " Ada ", "code": "ada-code" }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result["created"], 1);
+    assert_eq!(result["rows"][0]["name"], "Ada");
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &import_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({ "attendees": [] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, result) = send_json(
+        app,
+        "POST",
+        &import_path,
+        &owner_cookie,
+        Some(&owner_csrf),
+        Some(json!({
+            "csv": "name,email,code\nGrace,grace@example.com,\nAda,,\nLinus,,LINUS-1\nGrace,,\n,,\n",
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result["created"], 2);
+    assert_eq!(result["duplicates"], 2);
+    assert_eq!(result["invalid"], 1);
+    let rows = result["rows"].as_array().unwrap();
+    assert_eq!(rows[0]["status"], "created");
+    assert_eq!(rows[0]["email"], "grace@example.com");
+    assert_eq!(rows[0]["code"].as_str().unwrap().len(), 8);
+    assert!(rows[0]["join_path"]
+        .as_str()
+        .unwrap()
+        .starts_with(&format!("/codelabs/{}/entry?name=Grace&code=", codelab.id)));
+    assert_eq!(rows[1]["status"], "duplicate");
+    assert!(rows[1]["attendee_id"].is_string());
+    assert!(rows[1]["code"].is_null());
+    assert_eq!(rows[2]["code"], "LINUS-1");
+    assert_eq!(rows[3]["status"], "duplicate");
+    assert_eq!(rows[4]["status"], "invalid");
+    assert_eq!(rows[4]["error"], "name is required");
+
+    // Pre-registered learners sign in to the private codelab with the issued credentials.
+    let grace_code = rows[0]["code"].as_str().unwrap().to_string();
+    let (status, joined) = send_json(
+        app,
+        "POST",
+        &format!("{codelab_path}/register"),
+        "",
+        None,
+        Some(json!({ "name": "Grace", "code": grace_code })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(joined["id"], rows[0]["attendee_id"]);
+
+    let (status, roster) = send_json(
+        app,
+        "GET",
+        &format!("{codelab_path}/attendees"),
+        &owner_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(roster.as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_attendee_data_export_and_erasure() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (admin_cookie, admin_csrf) = login_admin(app, &test_app.state).await;
+    let codelab = create_codelab_as_admin(app, &admin_cookie, &admin_csrf, false).await;
+    let codelab_path = format!("/api/codelabs/{}", codelab.id);
+    let (alice_cookie, alice_csrf, alice_id) =
+        register_attendee(app, &test_app.state, &codelab.id, "Alice", "alice-code").await;
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("{codelab_path}/feedback"),
+        &alice_cookie,
+        Some(&alice_csrf),
+        Some(json!({ "difficulty": "3", "satisfaction": "5", "comment": "I am Alice" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("{codelab_path}/attendees/{alice_id}/submissions/link"),
+        &alice_cookie,
+        Some(&alice_csrf),
+        Some(json!({ "url": "https://example.com/alice", "title": "Alice repo" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    // Public chat rows written before sender_id was recorded only carry the name.
+    for sql in [
+        "INSERT INTO chat_messages (id, codelab_id, sender_name, message, msg_type) VALUES ('chat-1', ?, 'Alice', 'hello', 'chat')",
+        "INSERT INTO chat_messages (id, codelab_id, sender_name, message, msg_type) VALUES ('chat-2', ?, 'Bob', 'hi Alice', 'chat')",
+        "INSERT INTO codelab_waitlist (id, codelab_id, name, code, email) VALUES ('wait-1', ?, 'Alice', 'sealed', 'alice@example.com')",
+    ] {
+        sqlx::query(&test_app.state.q(sql))
+            .bind(&codelab.id)
+            .execute(&test_app.state.pool)
+            .await
+            .unwrap();
+    }
+
+    let (status, _) = send_json(
+        app,
+        "GET",
+        "/api/admin/privacy/attendees?email=ALICE@example.com",
+        &alice_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, found) = send_json(
+        app,
+        "GET",
+        "/api/admin/privacy/attendees?email=ALICE@example.com",
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(found.as_array().unwrap().len(), 1);
+    assert_eq!(found[0]["id"], alice_id.as_str());
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/api/admin/privacy/attendees/{alice_id}/export"))
+                .header(header::COOKIE, &admin_cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/zip");
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let mut archive = zip::ZipArchive::new(IoCursor::new(bytes.to_vec())).unwrap();
+    let export: Value = serde_json::from_reader(archive.by_name("attendee.json").unwrap()).unwrap();
+    assert_eq!(export["attendee"]["name"], "Alice");
+    assert_eq!(export["feedback"][0]["comment"], "I am Alice");
+    assert_eq!(export["chat_messages"].as_array().unwrap().len(), 1);
+    assert_eq!(export["chat_messages"][0]["message"], "hello");
+    assert_eq!(export["submissions"].as_array().unwrap().len(), 1);
+
+    let erase_path = format!("/api/admin/privacy/attendees/{alice_id}/erase");
+    let (status, erasure) = send_json(
+        app,
+        "POST",
+        &erase_path,
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(erasure["chat_messages_deleted"], 1);
+    assert_eq!(erasure["waitlist_entries_deleted"], 1);
+    assert_eq!(erasure["feedback_redacted"], 1);
+    assert_eq!(erasure["submissions_deleted"], 1);
+    assert_eq!(erasure["sessions_revoked"], 1);
+    assert!(erasure["pseudonym"]
+        .as_str()
+        .unwrap()
+        .starts_with("Erased attendee "));
+
+    let (status, feedback) = send_json(
+        app,
+        "GET",
+        &format!("{codelab_path}/feedback"),
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(feedback[0]["satisfaction"], "5");
+    assert!(feedback[0]["comment"].is_null());
+    let remaining: Vec<(String,)> = sqlx::query_as(
+        &test_app
+            .state
+            .q("SELECT id FROM chat_messages WHERE codelab_id = ? UNION ALL SELECT id FROM codelab_waitlist WHERE codelab_id = ?"),
+    )
+    .bind(&codelab.id)
+    .bind(&codelab.id)
+    .fetch_all(&test_app.state.pool)
+    .await
+    .unwrap();
+    assert_eq!(remaining, vec![("chat-2".to_string(),)]);
+    let (status, found) = send_json(
+        app,
+        "GET",
+        "/api/admin/privacy/attendees?email=alice@example.com",
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(found.as_array().unwrap().is_empty());
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("{codelab_path}/attendees/{alice_id}/submissions/link"),
+        &alice_cookie,
+        Some(&alice_csrf),
+        Some(json!({ "url": "https://example.com/again" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &erase_path,
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        "/api/admin/privacy/attendees/missing/erase",
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_retention_report_and_pruning() {
+    let test_app = setup_test_app_with(|state| {
+        state.retention = RetentionConfig {
+            chat_days: Some(90),
+            ai_days: Some(30),
+            submission_days: None,
+            audit_days: Some(365),
+            interval_hours: 24,
+        };
+    })
+    .await;
+    let app = &test_app.app;
+    let pool = &test_app.state.pool;
+    let (admin_cookie, admin_csrf) = login_admin(app, &test_app.state).await;
+    let ended = create_codelab_as_admin(app, &admin_cookie, &admin_csrf, false).await;
+    let running = create_codelab_as_admin(app, &admin_cookie, &admin_csrf, false).await;
+
+    for (id, codelab_id, created_at) in [
+        ("chat-old-1", &ended.id, "2020-01-01 10:00:00"),
+        ("chat-old-2", &ended.id, "2020-01-02 10:00:00"),
+        ("chat-mixed-old", &running.id, "2020-01-01 10:00:00"),
+    ] {
+        sqlx::query(
+            "INSERT INTO chat_messages (id, codelab_id, sender_name, message, msg_type, created_at) VALUES (?, ?, 'Ada', 'hi', 'chat', ?)",
+        )
+        .bind(id)
+        .bind(codelab_id)
+        .bind(created_at)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+    // A recent message keeps the running codelab's whole chat history.
+    sqlx::query(
+        "INSERT INTO chat_messages (id, codelab_id, sender_name, message, msg_type) VALUES ('chat-new', ?, 'Ada', 'hi', 'chat')",
+    )
+    .bind(&running.id)
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO ai_threads (id, title, user_id, user_type, codelab_id, created_at, updated_at) VALUES ('thread-old', 'Old', 'admin', 'admin', ?, '2020-01-01 10:00:00', '2020-01-01 10:00:00')",
+    )
+    .bind(&ended.id)
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO ai_messages (id, thread_id, role, content, created_at) VALUES ('msg-old', 'thread-old', 'user', 'hello', '2020-01-01 10:00:00')",
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO audit_logs (id, action, actor_type, created_at) VALUES ('audit-old', 'codelab_create', 'admin', '2020-01-01 10:00:00')",
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let (status, _) = send_json(app, "GET", "/api/admin/retention", "", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, report) = send_json(
+        app,
+        "GET",
+        "/api/admin/retention",
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["policy"]["chat_days"], 90);
+    assert!(report["policy"]["submission_days"].is_null());
+    assert_eq!(report["chat_messages"], 2);
+    assert_eq!(report["ai_threads"], 1);
+    assert_eq!(report["ai_messages"], 1);
+    assert_eq!(report["audit_logs"], 1);
+    assert_eq!(report["codelab_ids"], json!([ended.id]));
+    let chat_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM chat_messages")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(chat_count, 4);
+
+    let (status, report) = send_json(
+        app,
+        "POST",
+        "/api/admin/retention/run",
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["dry_run"], false);
+    assert_eq!(report["chat_messages"], 2);
+    assert_eq!(report["audit_logs"], 1);
+    let remaining: Vec<String> = sqlx::query_scalar("SELECT id FROM chat_messages ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, vec!["chat-mixed-old", "chat-new"]);
+    let (status, logs) = send_json(
+        app,
+        "GET",
+        "/api/admin/audit-logs?action=retention_prune",
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(logs.as_array().unwrap().len(), 1);
+
+    let (status, report) = send_json(
+        app,
+        "GET",
+        "/api/admin/retention",
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["chat_messages"], 0);
+    assert_eq!(report["ai_threads"], 0);
+    assert_eq!(report["audit_logs"], 0);
+}
+
+#[tokio::test]
+async fn test_codelab_revisions_diff_and_restore() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (admin_cookie, admin_csrf) = login_admin(app, &test_app.state).await;
+    let codelab = create_codelab_as_admin(app, &admin_cookie, &admin_csrf, false).await;
+    let steps_uri = format!("/api/codelabs/{}/steps", codelab.id);
+    let revisions_uri = format!("/api/codelabs/{}/revisions", codelab.id);
+
+    let (status, saved) = send_json(
+        app,
+        "PUT",
+        &steps_uri,
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "steps": [
+            { "id": "step-intro", "title": "Intro", "content_markdown": "Hello\nWorld" },
+            { "id": "step-setup", "title": "Setup", "content_markdown": "Install" }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(saved["revision_id"].is_string());
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &steps_uri,
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "steps": [
+            { "id": "step-intro", "title": "Intro", "content_markdown": "Hello\nEveryone" }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The guide the codelab was created with is kept as a baseline.
+    let (status, _) = send_json(app, "GET", &revisions_uri, "", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, revisions) =
+        send_json(app, "GET", &revisions_uri, &admin_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let revisions = revisions.as_array().unwrap();
+    let sources: Vec<&str> = revisions
+        .iter()
+        .map(|revision| revision["source"].as_str().unwrap())
+        .collect();
+    assert_eq!(sources, vec!["steps_update", "steps_update", "baseline"]);
+    assert_eq!(revisions[0]["revision_number"], 3);
+    assert_eq!(revisions[0]["author_id"], "admin");
+    assert!(revisions[2]["author_id"].is_null());
+
+    let (status, diff) = send_json(
+        app,
+        "GET",
+        &format!("{revisions_uri}/diff?from=2&to=3"),
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(diff["from"]["revision_number"], 2);
+    let diff_text = diff["diff"].as_str().unwrap();
+    assert!(diff_text.starts_with("--- revision 2\n+++ revision 3\n"));
+    assert!(diff_text.contains("-World\n+Everyone\n"));
+    assert!(diff_text.contains("-## Step 2: Setup\n"));
+    let (status, diff) = send_json(
+        app,
+        "GET",
+        &format!("{revisions_uri}/diff?from=3"),
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(diff["to"]["revision_number"], 3);
+    assert_eq!(diff["diff"], "");
+
+    let revision_two = revisions[1]["id"].as_str().unwrap();
+    let (status, revision) = send_json(
+        app,
+        "GET",
+        &format!("{revisions_uri}/{revision_two}"),
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(revision["guide_markdown"], "# Guide");
+    assert_eq!(revision["steps"].as_array().unwrap().len(), 2);
+    let (status, _) = send_json(
+        app,
+        "GET",
+        &format!("{revisions_uri}/missing"),
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, restored) = send_json(
+        app,
+        "POST",
+        &format!("{revisions_uri}/{revision_two}/restore"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(restored["revision_number"], 4);
+    assert_eq!(restored["source"], "restore");
+    let steps: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, content_markdown FROM steps WHERE codelab_id = ? ORDER BY step_number",
+    )
+    .bind(&codelab.id)
+    .fetch_all(&test_app.state.pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        steps,
+        vec![
+            ("step-intro".to_string(), "Hello\nWorld".to_string()),
+            ("step-setup".to_string(), "Install".to_string()),
+        ]
+    );
+    let (status, logs) = send_json(
+        app,
+        "GET",
+        "/api/admin/audit-logs?action=codelab_revision_restore",
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(logs.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_codelab_publish_separates_draft_from_learner_content() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (admin_cookie, admin_csrf) = login_admin(app, &test_app.state).await;
+    let codelab = create_codelab_as_admin(app, &admin_cookie, &admin_csrf, false).await;
+    let codelab_uri = format!("/api/codelabs/{}", codelab.id);
+    let steps_uri = format!("{codelab_uri}/steps");
+    let publication_uri = format!("{codelab_uri}/publication");
+
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &steps_uri,
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "steps": [
+            { "id": "step-intro", "title": "Intro", "content_markdown": "First draft" }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Until the first publish learners read the working copy.
+    let (status, unpublished) = send_json(app, "GET", &codelab_uri, "", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(unpublished[1][0]["content_markdown"], "First draft");
+    let (status, publication) =
+        send_json(app, "GET", &publication_uri, &admin_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(publication["published_revision_id"].is_null());
+    assert_eq!(publication["has_unpublished_changes"], false);
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("{codelab_uri}/publish"),
+        "",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, published) = send_json(
+        app,
+        "POST",
+        &format!("{codelab_uri}/publish"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(published["published_revision_id"].is_string());
+    assert!(published["published_at"].is_string());
+    assert_eq!(
+        published["published_revision_number"],
+        published["latest_revision_number"]
+    );
+
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &steps_uri,
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "steps": [
+            { "id": "step-intro", "title": "Intro", "content_markdown": "Second draft" },
+            { "id": "step-extra", "title": "Extra", "content_markdown": "More" }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, learner_view) = send_json(app, "GET", &codelab_uri, "", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let learner_steps = learner_view[1].as_array().unwrap();
+    assert_eq!(learner_steps.len(), 1);
+    assert_eq!(learner_steps[0]["content_markdown"], "First draft");
+    assert_eq!(learner_view[0]["guide_markdown"], "# Guide");
+
+    let (status, editor_view) =
+        send_json(app, "GET", &codelab_uri, &admin_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(editor_view[1].as_array().unwrap().len(), 2);
+    assert_eq!(editor_view[1][0]["content_markdown"], "Second draft");
+    let (status, editor_published) = send_json(
+        app,
+        "GET",
+        &format!("{codelab_uri}?version=published"),
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(editor_published[1].as_array().unwrap().len(), 1);
+    let (status, _) = send_json(
+        app,
+        "GET",
+        &format!("{codelab_uri}?version=latest"),
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, publication) =
+        send_json(app, "GET", &publication_uri, &admin_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(publication["has_unpublished_changes"], true);
+    let (status, logs) = send_json(
+        app,
+        "GET",
+        "/api/admin/audit-logs?action=codelab_publish",
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(logs.as_array().unwrap().len(), 1);
+}
+
+async fn send_json_if_match(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: &str,
+    csrf: &str,
+    if_match: &str,
+    body: Value,
+) -> (StatusCode, HeaderMap, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::COOKIE, cookie)
+        .header("x-csrf-token", csrf)
+        .header(header::IF_MATCH, if_match)
+        .header("Content-Type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let value = serde_json::from_slice(&bytes)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).to_string()));
+    (status, headers, value)
+}
+
+#[tokio::test]
+async fn test_codelab_and_step_writes_reject_stale_versions() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (admin_cookie, admin_csrf) = login_admin(app, &test_app.state).await;
+    let codelab = create_codelab_as_admin(app, &admin_cookie, &admin_csrf, false).await;
+    assert_eq!(codelab.version, 1);
+    assert_eq!(codelab.steps_version, 1);
+    let codelab_uri = format!("/api/codelabs/{}", codelab.id);
+    let steps_uri = format!("{codelab_uri}/steps");
+    let update = json!({
+        "title": "Renamed",
+        "description": codelab.description,
+        "author": codelab.author,
+        "guide_markdown": "# Guide",
+    });
+
+    let (status, headers, updated) = send_json_if_match(
+        app,
+        "PUT",
+        &codelab_uri,
+        &admin_cookie,
+        &admin_csrf,
+        "\"1\"",
+        update.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["version"], 2);
+    assert_eq!(headers[header::ETAG], "\"2\"");
+
+    // A second editor still holding version 1 is turned away.
+    let (status, headers, conflict) = send_json_if_match(
+        app,
+        "PUT",
+        &codelab_uri,
+        &admin_cookie,
+        &admin_csrf,
+        "\"1\"",
+        update.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(conflict["current_version"], 2);
+    assert_eq!(headers[header::ETAG], "\"2\"");
+    let (status, _, _) = send_json_if_match(
+        app,
+        "PUT",
+        &codelab_uri,
+        &admin_cookie,
+        &admin_csrf,
+        "soon",
+        update.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let steps = json!({ "steps": [
+        { "id": "step-intro", "title": "Intro", "content_markdown": "Hello" }
+    ] });
+    let (status, headers, saved) = send_json_if_match(
+        app,
+        "PUT",
+        &steps_uri,
+        &admin_cookie,
+        &admin_csrf,
+        "W/\"1\"",
+        steps.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(saved["steps_version"], 2);
+    assert_eq!(headers[header::ETAG], "\"2\"");
+    let (status, _, conflict) = send_json_if_match(
+        app,
+        "PUT",
+        &steps_uri,
+        &admin_cookie,
+        &admin_csrf,
+        "\"1\"",
+        steps.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(conflict["current_version"], 2);
+
+    // Writes without If-Match keep last-write-wins and still bump versions.
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &steps_uri,
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(steps),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, current) = send_json(app, "GET", &codelab_uri, &admin_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(current[0]["version"], 2);
+    assert_eq!(current[0]["steps_version"], 3);
+    let (status, _, _) = send_json_if_match(
+        app,
+        "PUT",
+        "/api/codelabs/missing",
+        &admin_cookie,
+        &admin_csrf,
+        "\"1\"",
+        update,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+async fn next_authoring_message<S>(read: &mut S, kind: &str) -> Value
+where
+    S: futures_util::Stream<Item = Result<WsMessage, tokio_tungstenite::tungstenite::Error>>
+        + Unpin,
+{
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(5), read.next())
+            .await
+            .unwrap_or_else(|_| panic!("timed out waiting for {kind}"))
+            .expect("authoring stream ended")
+            .expect("authoring websocket error");
+        if let WsMessage::Text(text) = message {
+            let payload: Value = serde_json::from_str(text.as_ref()).unwrap();
+            if payload["type"] == kind {
+                return payload;
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_authoring_socket_merges_concurrent_step_edits() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (admin_cookie, admin_csrf) = login_admin(app, &test_app.state).await;
+    let codelab = create_codelab_as_admin(app, &admin_cookie, &admin_csrf, false).await;
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &format!("/api/codelabs/{}/steps", codelab.id),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "steps": [
+            { "id": "step-intro", "title": "Intro", "content_markdown": "Hello" }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (attendee_cookie, _, _) = register_attendee(
+        app,
+        &test_app.state,
+        &codelab.id,
+        "Author Attendee",
+        "author-code",
+    )
+    .await;
+
+    let Some(listener) = bind_local_listener_or_skip().await else {
+        return;
+    };
+    let addr = listener.local_addr().unwrap();
+    let ws_app = create_router(test_app.state.clone());
+    let server = tokio::spawn(async move {
+        axum::serve(
+            listener,
+            ws_app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+    let url = format!("ws://{}/api/ws/{}/authoring", addr, codelab.id);
+
+    match connect_async(websocket_request(&url, &attendee_cookie)).await {
+        Err(tokio_tungstenite::tungstenite::Error::Http(res)) => {
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED)
+        }
+        other => panic!("attendee joined the authoring socket: {other:?}"),
+    }
+
+    let (first_ws, _) = connect_async(websocket_request(&url, &admin_cookie))
+        .await
+        .unwrap();
+    let (second_ws, _) = connect_async(websocket_request(&url, &admin_cookie))
+        .await
+        .unwrap();
+    let (mut first_write, mut first_read) = first_ws.split();
+    let (mut second_write, mut second_read) = second_ws.split();
+    let first_ready = next_authoring_message(&mut first_read, "authoring_ready").await;
+    let second_ready = next_authoring_message(&mut second_read, "authoring_ready").await;
+    assert_eq!(first_ready["steps"][0]["content_markdown"], "Hello");
+    assert_eq!(first_ready["steps"][0]["revision"], 0);
+    let second_session = second_ready["session_id"].as_str().unwrap().to_string();
+    // The first editor may see its own join before the second one.
+    let mut presence = next_authoring_message(&mut first_read, "authoring_presence").await;
+    if presence["editors"].as_array().unwrap().len() == 1 {
+        presence = next_authoring_message(&mut first_read, "authoring_presence").await;
+    }
+    assert_eq!(presence["editors"].as_array().unwrap().len(), 2);
+
+    // Both editors change revision 0 before seeing the other's edit.
+    first_write
+        .send(WsMessage::Text(
+            json!({
+                "type": "operation",
+                "step_id": "step-intro",
+                "revision": 0,
+                "operation": [5, " world"],
+            })
+            .to_string()
+            .into(),
+        ))
+        .await
+        .unwrap();
+    second_write
+        .send(WsMessage::Text(
+            json!({
+                "type": "operation",
+                "step_id": "step-intro",
+                "revision": 0,
+                "operation": ["> ", 5],
+            })
+            .to_string()
+            .into(),
+        ))
+        .await
+        .unwrap();
+    let applied = next_authoring_message(&mut first_read, "authoring_operation").await;
+    assert_eq!(applied["revision"], 1);
+    let applied = next_authoring_message(&mut first_read, "authoring_operation").await;
+    assert_eq!(applied["revision"], 2);
+
+    second_write
+        .send(WsMessage::Text(
+            json!({ "type": "cursor", "step_id": "step-intro", "revision": 1, "position": 2 })
+                .to_string()
+                .into(),
+        ))
+        .await
+        .unwrap();
+    let cursor = next_authoring_message(&mut first_read, "authoring_cursor").await;
+    assert_eq!(cursor["session_id"], second_session.as_str());
+    assert_eq!(cursor["step_id"], "step-intro");
+    assert!(cursor["position"].is_u64());
+
+    second_write
+        .send(WsMessage::Text(
+            json!({ "type": "resync" }).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let snapshot = next_authoring_message(&mut second_read, "authoring_snapshot").await;
+    assert_eq!(snapshot["steps"][0]["content_markdown"], "> Hello world");
+    assert_eq!(snapshot["steps"][0]["revision"], 2);
+
+    second_write
+        .send(WsMessage::Text(
+            json!({
+                "type": "operation",
+                "step_id": "step-intro",
+                "revision": 2,
+                "operation": [99],
+            })
+            .to_string()
+            .into(),
+        ))
+        .await
+        .unwrap();
+    let error = next_authoring_message(&mut second_read, "authoring_error").await;
+    assert_eq!(error["step_id"], "step-intro");
+
+    // The room saves when the last editor leaves.
+    first_write.close().await.unwrap();
+    second_write.close().await.unwrap();
+    let mut saved = String::new();
+    for _ in 0..50 {
+        saved = sqlx::query_scalar(
+            &test_app
+                .state
+                .q("SELECT content_markdown FROM steps WHERE id = ?"),
+        )
+        .bind("step-intro")
+        .fetch_one(&test_app.state.pool)
+        .await
+        .unwrap();
+        if saved == "> Hello world" {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(saved, "> Hello world");
+    let source: String = sqlx::query_scalar(&test_app.state.q(
+        "SELECT source FROM codelab_revisions WHERE codelab_id = ? ORDER BY revision_number DESC LIMIT 1",
+    ))
+    .bind(&codelab.id)
+    .fetch_one(&test_app.state.pool)
+    .await
+    .unwrap();
+    assert_eq!(source, "collaboration");
+    let steps_version: i32 = sqlx::query_scalar(
+        &test_app
+            .state
+            .q("SELECT steps_version FROM codelabs WHERE id = ?"),
+    )
+    .bind(&codelab.id)
+    .fetch_one(&test_app.state.pool)
+    .await
+    .unwrap();
+    assert_eq!(steps_version, 3);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_authoring_room_reloads_instead_of_overwriting_newer_steps() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let state = &test_app.state;
+    let (admin_cookie, admin_csrf) = login_admin(app, state).await;
+    let codelab = create_codelab_as_admin(app, &admin_cookie, &admin_csrf, false).await;
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &format!("/api/codelabs/{}/steps", codelab.id),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "steps": [
+            { "id": "step-intro", "title": "Intro", "content_markdown": "Hello" },
+            { "id": "step-next", "title": "Next", "content_markdown": "Later" }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let steps_version = |state: Arc<AppState>, id: String| async move {
+        sqlx::query_scalar::<_, i32>(&state.q("SELECT steps_version FROM codelabs WHERE id = ?"))
+            .bind(id)
+            .fetch_one(&state.pool)
+            .await
+            .unwrap()
+    };
+    let step_content = |state: Arc<AppState>, step_id: &'static str| async move {
+        sqlx::query_scalar::<_, String>(&state.q("SELECT content_markdown FROM steps WHERE id = ?"))
+            .bind(step_id)
+            .fetch_one(&state.pool)
+            .await
+            .unwrap()
+    };
+    let loaded_version = steps_version(state.clone(), codelab.id.clone()).await;
+
+    let room = open_authoring_room(state, &codelab.id).await.unwrap();
+    let mut events = authoring_channel(state, &codelab.id).subscribe();
+    {
+        let mut room = room.lock().unwrap();
+        room.apply_operation(
+            "step-intro",
+            0,
+            TextOperation::new().retain(5).insert(" world"),
+            "admin",
+        )
+        .unwrap();
+        room.apply_operation(
+            "step-next",
+            0,
+            TextOperation::new().retain(5).insert(" on"),
+            "admin",
+        )
+        .unwrap();
+    }
+
+    // Another writer changes the steps without going through the room.
+    sqlx::query(&state.q("UPDATE steps SET content_markdown = ? WHERE id = ?"))
+        .bind("Rewritten")
+        .bind("step-intro")
+        .execute(&state.pool)
+        .await
+        .unwrap();
+    sqlx::query(&state.q("UPDATE codelabs SET steps_version = steps_version + 1 WHERE id = ?"))
+        .bind(&codelab.id)
+        .execute(&state.pool)
+        .await
+        .unwrap();
+
+    save_authoring_room(state, &codelab.id, &room)
+        .await
+        .unwrap();
+    assert_eq!(step_content(state.clone(), "step-intro").await, "Rewritten");
+    assert_eq!(step_content(state.clone(), "step-next").await, "Later");
+    assert_eq!(
+        steps_version(state.clone(), codelab.id.clone()).await,
+        loaded_version + 1
+    );
+    // Editors learn which step lost its edits before the reloaded documents.
+    let conflict: Value = serde_json::from_str(&events.try_recv().unwrap()).unwrap();
+    assert_eq!(conflict["type"], "authoring_conflict");
+    assert_eq!(conflict["step_ids"], json!(["step-intro"]));
+    let snapshot: Value = serde_json::from_str(&events.try_recv().unwrap()).unwrap();
+    assert_eq!(snapshot["type"], "authoring_snapshot");
+    {
+        let mut room = room.lock().unwrap();
+        // The untouched step keeps its edit on top of the reloaded steps.
+        assert!(room.has_unsaved_changes());
+        assert_eq!(room.steps_version(), loaded_version + 1);
+        assert_eq!(room.snapshot()[0].content_markdown, "Rewritten");
+        assert_eq!(room.snapshot()[1].content_markdown, "Later on");
+        room.apply_operation(
+            "step-intro",
+            0,
+            TextOperation::new().retain(9).insert("!"),
+            "admin",
+        )
+        .unwrap();
+    }
+
+    // Edits made on top of the reloaded steps are saved normally.
+    save_authoring_room(state, &codelab.id, &room)
+        .await
+        .unwrap();
+    assert_eq!(
+        step_content(state.clone(), "step-intro").await,
+        "Rewritten!"
+    );
+    assert_eq!(step_content(state.clone(), "step-next").await, "Later on");
+    assert!(!room.lock().unwrap().has_unsaved_changes());
+    assert_eq!(
+        steps_version(state.clone(), codelab.id.clone()).await,
+        loaded_version + 2
+    );
+    assert_eq!(room.lock().unwrap().steps_version(), loaded_version + 2);
+}
+
+async fn attendee_steps(state: &AppState, ids: &[&str]) -> Vec<i32> {
+    let mut steps = Vec::new();
+    for id in ids {
+        let step: i32 =
+            sqlx::query_scalar(&state.q("SELECT current_step FROM attendees WHERE id = ?"))
+                .bind(id)
+                .fetch_one(&state.pool)
+                .await
+                .unwrap();
+        steps.push(step);
+    }
+    steps
+}
+
+async fn help_request_steps(state: &AppState, ids: &[&str]) -> Vec<i32> {
+    let mut steps = Vec::new();
+    for id in ids {
+        let step: i32 =
+            sqlx::query_scalar(&state.q("SELECT step_number FROM help_requests WHERE id = ?"))
+                .bind(id)
+                .fetch_one(&state.pool)
+                .await
+                .unwrap();
+        steps.push(step);
+    }
+    steps
+}
+
+fn step_ids(response: &Value) -> Vec<&str> {
+    response["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|step| step["id"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_single_step_changes_keep_ids_and_attendee_progress() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let state = &test_app.state;
+    let (admin_cookie, admin_csrf) = login_admin(app, state).await;
+    let codelab = create_codelab_as_admin(app, &admin_cookie, &admin_csrf, false).await;
+    let steps_uri = format!("/api/codelabs/{}/steps", codelab.id);
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &steps_uri,
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "steps": [
+            { "id": "step-a", "title": "A", "content_markdown": "a" },
+            { "id": "step-b", "title": "B", "content_markdown": "b" },
+            { "id": "step-c", "title": "C", "content_markdown": "c" }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (ada_cookie, ada_csrf, ada) =
+        register_attendee(app, state, &codelab.id, "Ada", "ada-code").await;
+    let (_, _, grace) = register_attendee(app, state, &codelab.id, "Grace", "grace-code").await;
+    let (_, _, linus) = register_attendee(app, state, &codelab.id, "Linus", "linus-code").await;
+    let attendees = [ada.as_str(), grace.as_str(), linus.as_str()];
+    for (id, step) in attendees.iter().zip([2, 3, 1]) {
+        sqlx::query(&state.q("UPDATE attendees SET current_step = ? WHERE id = ?"))
+            .bind(step)
+            .bind(id)
+            .execute(&state.pool)
+            .await
+            .unwrap();
+    }
+    let help_requests = ["help-open", "help-resolved"];
+    for (id, status) in help_requests.iter().zip(["pending", "resolved"]) {
+        sqlx::query(&state.q(
+            "INSERT INTO help_requests (id, codelab_id, attendee_id, step_number, status) VALUES (?, ?, ?, 3, ?)",
+        ))
+        .bind(id)
+        .bind(&codelab.id)
+        .bind(&grace)
+        .bind(status)
+        .execute(&state.pool)
+        .await
+        .unwrap();
+    }
+
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &steps_uri,
+        &ada_cookie,
+        Some(&ada_csrf),
+        Some(json!({ "title": "New", "content_markdown": "n" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Inserting shifts later steps and the attendees reading them.
+    let (status, inserted) = send_json(
+        app,
+        "POST",
+        &steps_uri,
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "title": "New", "content_markdown": "n", "position": 2 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let new_id = inserted["step_id"].as_str().unwrap().to_string();
+    assert_eq!(
+        step_ids(&inserted),
+        vec!["step-a", new_id.as_str(), "step-b", "step-c"]
+    );
+    assert_eq!(inserted["steps"][1]["step_number"], 2);
+    assert_eq!(inserted["steps_version"], 3);
+    assert_eq!(attendee_steps(state, &attendees).await, vec![3, 4, 1]);
+    // Open help requests follow their step; resolved ones keep their history.
+    assert_eq!(help_request_steps(state, &help_requests).await, vec![4, 3]);
+
+    let (status, _, _) = send_json_if_match(
+        app,
+        "POST",
+        &format!("{steps_uri}/step-c/move"),
+        &admin_cookie,
+        &admin_csrf,
+        "\"2\"",
+        json!({ "position": 1 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, headers, moved) = send_json_if_match(
+        app,
+        "POST",
+        &format!("{steps_uri}/step-c/move"),
+        &admin_cookie,
+        &admin_csrf,
+        "\"3\"",
+        json!({ "position": 1 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[header::ETAG], "\"4\"");
+    assert_eq!(
+        step_ids(&moved),
+        vec!["step-c", "step-a", new_id.as_str(), "step-b"]
+    );
+    assert_eq!(attendee_steps(state, &attendees).await, vec![4, 1, 2]);
+    assert_eq!(help_request_steps(state, &help_requests).await, vec![1, 3]);
+
+    let (status, updated) = send_json(
+        app,
+        "PUT",
+        &format!("{steps_uri}/step-b"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "title": "B2", "content_markdown": "b2" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["steps"][3]["id"], "step-b");
+    assert_eq!(updated["steps"][3]["title"], "B2");
+
+    // Attendees on a deleted step continue with the one that followed it.
+    sqlx::query(&state.q("UPDATE attendees SET current_step = 3 WHERE id = ?"))
+        .bind(&linus)
+        .execute(&state.pool)
+        .await
+        .unwrap();
+    let (status, deleted) = send_json(
+        app,
+        "DELETE",
+        &format!("{steps_uri}/{new_id}"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(step_ids(&deleted), vec!["step-c", "step-a", "step-b"]);
+    assert_eq!(deleted["steps"][2]["step_number"], 3);
+    assert_eq!(attendee_steps(state, &attendees).await, vec![3, 1, 3]);
+
+    let (status, _) = send_json(
+        app,
+        "DELETE",
+        &format!("{steps_uri}/missing"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("{steps_uri}/step-a/move"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "position": 9 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Once published, learners follow the published order until the next publish.
+    let publish_uri = format!("/api/codelabs/{}/publish", codelab.id);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &publish_uri,
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("{steps_uri}/step-b/move"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "position": 1 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(attendee_steps(state, &attendees).await, vec![3, 1, 3]);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &publish_uri,
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(attendee_steps(state, &attendees).await, vec![1, 2, 1]);
+
+    let (status, logs) = send_json(
+        app,
+        "GET",
+        "/api/admin/audit-logs?action=codelab_step_move",
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(logs.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_claat_markdown_import_and_export() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (admin_cookie, admin_csrf) = login_admin(app, &test_app.state).await;
+    let claat = "summary: Ship a web app\nid: web-app\ncategories: Web,Cloud\nstatus: Published\nauthors: Jane Doe\n\n# Your First Web App\n\n## Overview\nDuration: 2:00\n\nWhat you will build.\n\n## Deploy\nDuration: 10:00\n\n```sh\n## not a step\nnpm run deploy\n```\n";
+
+    let boundary = "----claat-import-boundary";
+    let body = build_multipart_file_body(
+        boundary,
+        "file",
+        "web-app.md",
+        "text/markdown",
+        claat.as_bytes(),
+    );
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/codelabs/import")
+                .header(
+                    "Content-Type",
+                    format!("multipart/form-data; boundary={}", boundary),
+                )
+                .header(header::COOKIE, admin_cookie.clone())
+                .header("x-csrf-token", admin_csrf.clone())
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let codelab: Codelab = serde_json::from_slice(&bytes).unwrap();
+    assert_ne!(codelab.id, "web-app");
+    assert_eq!(codelab.title, "Your First Web App");
+    assert_eq!(codelab.description, "Ship a web app");
+    assert_eq!(codelab.author, "Jane Doe");
+    assert_eq!(codelab.categories.as_deref(), Some("Web,Cloud"));
+
+    let codelab_uri = format!("/api/codelabs/{}", codelab.id);
+    let (status, detail) = send_json(app, "GET", &codelab_uri, &admin_cookie, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let steps = detail[1].as_array().unwrap();
+    assert_eq!(steps.len(), 2);
+    assert_eq!(steps[0]["title"], "Overview");
+    assert_eq!(steps[0]["duration_minutes"], 2);
+    assert_eq!(steps[1]["duration_minutes"], 10);
+    assert_eq!(
+        steps[1]["content_markdown"],
+        "```sh\n## not a step\nnpm run deploy\n```"
+    );
+
+    // Editors that only send titles and markdown keep the durations.
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &format!("{codelab_uri}/steps"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "steps": [
+            { "id": steps[1]["id"], "title": "Deploy", "content_markdown": "Deploy it." },
+            { "id": steps[0]["id"], "title": "Overview", "content_markdown": "What you will build." },
+            { "title": "Next steps", "content_markdown": "Keep going.", "duration_minutes": 1 }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, exported) = send_json(
+        app,
+        "GET",
+        &format!("{codelab_uri}/export?format=claat"),
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        exported.as_str().unwrap(),
+        format!(
+            "summary: Ship a web app\nid: {}\ncategories: Web,Cloud\nstatus: Draft\nauthors: Jane Doe\n\n# Your First Web App\n\n## Deploy\nDuration: 10:00\n\nDeploy it.\n\n## Overview\nDuration: 2:00\n\nWhat you will build.\n\n## Next steps\nDuration: 1:00\n\nKeep going.\n",
+            codelab.id
+        )
+    );
+
+    let (status, _) = send_json(
+        app,
+        "GET",
+        &format!("{codelab_uri}/export?format=pdf"),
+        &admin_cookie,
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let body = build_multipart_file_body(
+        boundary,
+        "file",
+        "notes.md",
+        "text/markdown",
+        b"## Step without a title\n",
+    );
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/codelabs/import")
+                .header(
+                    "Content-Type",
+                    format!("multipart/form-data; boundary={}", boundary),
+                )
+                .header(header::COOKIE, admin_cookie.clone())
+                .header("x-csrf-token", admin_csrf.clone())
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_static_site_export_renders_published_content() {
+    let test_app = setup_test_app().await;
+    let app = &test_app.app;
+    let (admin_cookie, admin_csrf) = login_admin(app, &test_app.state).await;
+
+    let (status, codelab) = send_json(
+        app,
+        "POST",
+        "/api/codelabs",
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({
+            "title": "Site Lab",
+            "description": "Offline copy",
+            "author": "Author",
+            "guide_markdown": "Read the [slides](/uploads/materials/missing-deck.pdf)."
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let codelab_id = codelab["id"].as_str().unwrap().to_string();
+    let codelab_uri = format!("/api/codelabs/{codelab_id}");
+
+    let image = format!("site-export-{codelab_id}.png");
+    std::fs::create_dir_all("static/uploads").unwrap();
+    std::fs::write(format!("static/uploads/{image}"), b"png-bytes").unwrap();
+
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &format!("{codelab_uri}/steps"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "steps": [
+            {
+                "title": "Setup",
+                "content_markdown": format!("![diagram](/uploads/{image})\n\n```rust\nfn main() {{}}\n```"),
+                "duration_minutes": 5
+            },
+            { "title": "Finish", "content_markdown": "Done." }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(
+        app,
+        "POST",
+        &format!("{codelab_uri}/publish"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Draft edits after the publish stay out of the site.
+    let (status, _) = send_json(
+        app,
+        "PUT",
+        &format!("{codelab_uri}/steps"),
+        &admin_cookie,
+        Some(&admin_csrf),
+        Some(json!({ "steps": [
+            { "title": "Unpublished", "content_markdown": "Draft only." }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("{codelab_uri}/export?format=html"))
+                .header(header::COOKIE, &admin_cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    std::fs::remove_file(format!("static/uploads/{image}")).unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/zip");
+    assert_eq!(
+        response.headers()[header::CONTENT_DISPOSITION],
+        format!("attachment; filename=\"codelab_{codelab_id}_site.zip\"").as_str()
+    );
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let mut archive = zip::ZipArchive::new(IoCursor::new(bytes.to_vec())).unwrap();
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort_unstable();
+    let copied_image = format!("assets/uploads/{image}");
+    assert_eq!(
+        names,
+        [
+            "assets/style.css",
+            copied_image.as_str(),
+            "index.html",
+            "step-01.html",
+            "step-02.html"
+        ]
+    );
+
+    let mut read = |name: &str| {
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut contents).unwrap();
+        contents
+    };
+    let index = read("index.html");
+    assert!(index.contains("href=\"assets/uploads/materials/missing-deck.pdf\""));
+    assert!(index.contains("<a href=\"step-01.html\">Setup</a>"));
+    assert!(!index.contains("Unpublished"));
+    let setup = read("step-01.html");
+    assert!(setup.contains(&format!("src=\"{copied_image}\"")));
+    assert!(setup.contains("<span class=\"hl-"));
+    assert!(setup.contains("<a href=\"step-02.html\">Next →</a>"));
+    assert_eq!(read(&copied_image), "png-bytes");
+}
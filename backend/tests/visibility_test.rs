@@ -102,6 +102,11 @@ async fn test_codelab_visibility() {
         require_quiz: None,
         require_feedback: None,
         require_submission: None,
+        quiz_max_attempts: None,
+        quiz_pass_percentage: None,
+        max_attendees: None,
+        registration_opens_at: None,
+        registration_closes_at: None,
         guide_markdown: None,
     };
     test_app
@@ -130,6 +135,11 @@ async fn test_codelab_visibility() {
         require_quiz: None,
         require_feedback: None,
         require_submission: None,
+        quiz_max_attempts: None,
+        quiz_pass_percentage: None,
+        max_attendees: None,
+        registration_opens_at: None,
+        registration_closes_at: None,
         guide_markdown: None,
     };
     let res = test_app
@@ -202,6 +202,8 @@ describe("api-backend", () => {
         expect(api.getWsUrl("lab")).toMatch(/\/api\/ws\/lab$/);
         expect(api.getWsUrl("lab", "admin", "tok")).toContain("as=admin");
         expect(api.getWsUrl("lab", "admin", "tok")).toContain("token=tok");
+        expect(api.getAuthoringWsUrl("lab")).toMatch(/\/api\/ws\/lab\/authoring$/);
+        expect(api.getAuthoringWsUrl("lab", "tok")).toMatch(/\/authoring\?token=tok$/);
     });
 
     test("covers success paths across backend API functions", async () => {
@@ -222,6 +224,28 @@ describe("api-backend", () => {
         enqueue(makeTextResponse(204, ""));
         await api.saveSteps("c1", [{ title: "s", content_markdown: "m" }]);
 
+        enqueue(makeTextResponse(204, ""));
+        await api.saveSteps("c9", [{ title: "s", content_markdown: "m" }], 3);
+        expect(new Headers(findCall("/api/codelabs/c9/steps", "PUT").init.headers).get("If-Match")).toBe('"3"');
+
+        const stepChange = { step_id: "s2", revision_id: "r1", steps_version: 4, steps: [] };
+        enqueue(makeJsonResponse(200, stepChange));
+        expect(await api.insertStep("c9", { title: "s", content_markdown: "m", position: 2 }, 3)).toEqual(stepChange);
+        expect(JSON.parse(findCall("/api/codelabs/c9/steps", "POST").init.body).position).toBe(2);
+
+        enqueue(makeJsonResponse(200, stepChange));
+        expect(await api.updateStep("c9", "s2", { title: "s", content_markdown: "m" })).toEqual(stepChange);
+
+        enqueue(makeJsonResponse(200, stepChange));
+        await api.moveStep("c9", "s2", 1, 4);
+        const moveCall = findCall("/api/codelabs/c9/steps/s2/move", "POST");
+        expect(JSON.parse(moveCall.init.body)).toEqual({ position: 1 });
+        expect(new Headers(moveCall.init.headers).get("If-Match")).toBe('"4"');
+
+        enqueue(makeJsonResponse(200, stepChange));
+        await api.deleteStep("c9", "s2");
+        expect(findCall("/api/codelabs/c9/steps/s2", "DELETE")).toBeTruthy();
+
         enqueue(makeTextResponse(204, ""));
         await api.deleteCodelab("c1");
 
@@ -246,6 +270,12 @@ describe("api-backend", () => {
         enqueue(makeBlobResponse(200, "zip-1"));
         await api.exportCodelab("c1");
 
+        enqueue(makeBlobResponse(200, "md-1"));
+        await api.exportCodelab("c1", "claat");
+
+        enqueue(makeBlobResponse(200, "site-1"));
+        await api.exportCodelab("c1", "html");
+
         enqueue(makeJsonResponse(200, { id: "imported" }));
         expect(await api.importCodelab(makeFile("import.zip"))).toEqual({ id: "imported" });
 
@@ -321,6 +351,12 @@ describe("api-backend", () => {
         enqueue(makeTextResponse(204, ""));
         await api.completeCodelab("c1");
 
+        enqueue(makeJsonResponse(400, { error: "REQUIREMENTS_NOT_MET", unmet: ["QUIZ_REQUIRED", "FEEDBACK_REQUIRED"] }));
+        await expect(api.completeCodelab("c1")).rejects.toMatchObject({
+            message: "REQUIREMENTS_NOT_MET",
+            unmet: ["QUIZ_REQUIRED", "FEEDBACK_REQUIRED"],
+        });
+
         enqueue(makeJsonResponse(200, { id: "cert" }));
         expect(await api.getCertificate("a1")).toEqual({ id: "cert" });
 
@@ -330,8 +366,13 @@ describe("api-backend", () => {
         enqueue(makeJsonResponse(200, [{ id: "q1" }]));
         expect(await api.getQuizzes("c1")).toEqual([{ id: "q1" }]);
 
-        enqueue(makeTextResponse(204, ""));
-        await api.submitQuiz("c1", { submissions: [{ quiz_id: "q1", answer: "1", is_correct: true }] });
+        enqueue(makeJsonResponse(200, { total: 1, correct: 1, score: 100, results: [] }));
+        expect(await api.submitQuiz("c1", { submissions: [{ quiz_id: "q1", answer: "1", is_correct: true }] })).toEqual({
+            total: 1,
+            correct: 1,
+            score: 100,
+            results: [],
+        });
 
         enqueue(makeJsonResponse(200, [{ id: "qs1" }]));
         expect(await api.getQuizSubmissions("c1")).toEqual([{ id: "qs1" }]);
@@ -429,6 +470,8 @@ describe("api-backend", () => {
             { run: () => api.createCodelab({ title: "t", description: "d", author: "a" }) },
             { run: () => api.updateCodelab("c1", { title: "t", description: "d", author: "a" }) },
             { run: () => api.saveSteps("c1", [{ title: "x", content_markdown: "y" }]) },
+            { run: () => api.insertStep("c1", { title: "x", content_markdown: "y" }) },
+            { run: () => api.moveStep("c1", "s1", 2) },
             { run: () => api.deleteCodelab("c1") },
             { run: () => api.copyCodelab("c1") },
             { run: () => api.login("id", "pw") },
@@ -522,6 +565,9 @@ describe("api-backend", () => {
         enqueue(makeTextResponse(409, "dup"));
         await expect(api.registerAttendee("c1", "same", "code")).rejects.toThrow("DUPLICATE_NAME");
 
+        enqueue(makeJsonResponse(409, { error: "stale", current_version: 4 }));
+        await expect(api.updateCodelab("c1", { title: "t", description: "d", author: "a" }, 3)).rejects.toThrow("VERSION_CONFLICT");
+
         enqueue(makeTextResponse(500, "registration-error"));
         await expect(api.registerAttendee("c1", "n", "c")).rejects.toThrow("registration-error");
 
@@ -0,0 +1,59 @@
+import { describe, expect, test } from "bun:test";
+import {
+    applyOperation,
+    composeOperations,
+    diffOperation,
+    transformOperations,
+    transformPosition,
+} from "../authoring";
+
+describe("diffOperation", () => {
+    test("replaces the changed range only", () => {
+        expect(diffOperation("Hello world", "Hello, world")).toEqual([5, ",", 6]);
+        expect(diffOperation("abc", "abc")).toEqual([3]);
+        expect(diffOperation("abc", "")).toEqual([-3]);
+    });
+
+    test("never splits surrogate pairs", () => {
+        const op = diffOperation("😀", "😁");
+        expect(op).toEqual(["😁", -2]);
+        expect(applyOperation(op, "😀")).toBe("😁");
+    });
+});
+
+describe("transformOperations", () => {
+    test("concurrent edits converge", () => {
+        const doc = "Hello world";
+        const a = diffOperation(doc, "Hello, world");
+        const b = diffOperation(doc, "Hello world!");
+        const [aPrime, bPrime] = transformOperations(a, b);
+        const left = applyOperation(bPrime, applyOperation(a, doc));
+        const right = applyOperation(aPrime, applyOperation(b, doc));
+        expect(left).toBe(right);
+        expect(left).toBe("Hello, world!");
+    });
+
+    test("inserts at the same position put the first operation first", () => {
+        const [aPrime] = transformOperations(["A", 3], ["B", 3]);
+        expect(applyOperation(aPrime, "Babc")).toBe("ABabc");
+    });
+});
+
+describe("composeOperations", () => {
+    test("matches applying both operations in turn", () => {
+        const doc = "draft text";
+        const a = diffOperation(doc, "draft of text");
+        const b = diffOperation("draft of text", "first draft of text!");
+        expect(applyOperation(composeOperations(a, b), doc)).toBe("first draft of text!");
+    });
+});
+
+describe("transformPosition", () => {
+    test("moves positions past inserts and into deletes", () => {
+        const op = [2, "xyz", 3, -4, 1];
+        expect(transformPosition(op, 0)).toBe(0);
+        expect(transformPosition(op, 2)).toBe(5);
+        expect(transformPosition(op, 7)).toBe(8);
+        expect(transformPosition(op, 10)).toBe(9);
+    });
+});
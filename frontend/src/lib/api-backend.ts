@@ -1,6 +1,6 @@
 import { encryptForBackend, getEncryptionPassword } from './crypto';
-import type { Codelab, Step, Attendee, HelpRequest, ChatMessage, Feedback, Material, CertificateInfo, Quiz, QuizSubmissionPayload, QuizSubmissionWithAttendee, Submission, SubmissionWithAttendee, AiConversation, SaveAiConversationPayload, InlineCommentThread, CreateInlineCommentPayload } from './types';
-export type { Codelab, Step, Attendee, HelpRequest, ChatMessage, Feedback, CertificateInfo, Quiz, QuizSubmissionPayload, QuizSubmissionWithAttendee, Submission, SubmissionWithAttendee, AiConversation, SaveAiConversationPayload, InlineCommentThread, CreateInlineCommentPayload };
+import type { Codelab, Step, Attendee, HelpRequest, ChatMessage, Feedback, Material, CertificateInfo, CompletionRequirement, Quiz, QuizSubmissionPayload, QuizSubmissionResult, QuizSubmissionWithAttendee, Submission, SubmissionWithAttendee, AiConversation, SaveAiConversationPayload, InlineCommentThread, CreateInlineCommentPayload, Cohort, CohortSummary, RosterEntry, RosterImportResult, AttendeeErasure, RetentionReport, CodelabRevision, CodelabRevisionSummary, CodelabRevisionDiff, PublicationStatus, StepChange } from './types';
+export type { Codelab, Step, Attendee, HelpRequest, ChatMessage, Feedback, CertificateInfo, Quiz, QuizSubmissionPayload, QuizSubmissionResult, QuizSubmissionWithAttendee, Submission, SubmissionWithAttendee, AiConversation, SaveAiConversationPayload, InlineCommentThread, CreateInlineCommentPayload };
 
 const isBrowser = () => typeof window !== "undefined" && typeof document !== "undefined";
 
@@ -93,14 +93,22 @@ export async function createCodelab(payload: { title: string; description: strin
     return res.json();
 }
 
-export async function updateCodelab(id: string, payload: { title: string; description: string; author: string; is_public?: boolean, quiz_enabled?: boolean, require_quiz?: boolean, require_feedback?: boolean, guide_markdown?: string }): Promise<Codelab> {
+// Writes carrying the version they were based on are rejected with 409 once
+// someone else has saved a newer one.
+function ifMatchHeaders(version?: number): Record<string, string> {
+    return version === undefined ? {} : { 'If-Match': `"${version}"` };
+}
+
+export async function updateCodelab(id: string, payload: { title: string; description: string; author: string; is_public?: boolean, quiz_enabled?: boolean, require_quiz?: boolean, require_feedback?: boolean, guide_markdown?: string }, version?: number): Promise<Codelab> {
     const res = await apiFetch(`/codelabs/${id}`, {
         method: 'PUT',
         headers: {
             'Content-Type': 'application/json',
+            ...ifMatchHeaders(version),
         },
         body: JSON.stringify(payload),
     });
+    if (res.status === 409) throw new Error('VERSION_CONFLICT');
     if (!res.ok) throw new Error('Failed to update codelab');
     return res.json();
 }
@@ -108,15 +116,118 @@ export async function updateCodelab(id: string, payload: { title: string; descri
 export async function saveSteps(
     codelabId: string,
     steps: { id?: string; title: string; content_markdown: string }[],
+    version?: number,
 ): Promise<void> {
     const res = await apiFetch(`/codelabs/${codelabId}/steps`, {
         method: 'PUT',
-        headers: { 'Content-Type': 'application/json' },
+        headers: { 'Content-Type': 'application/json', ...ifMatchHeaders(version) },
         body: JSON.stringify({ steps })
     });
+    if (res.status === 409) throw new Error('VERSION_CONFLICT');
     if (!res.ok) throw new Error('Failed to update steps');
 }
 
+async function readStepChange(res: Response, action: string): Promise<StepChange> {
+    if (res.status === 409) throw new Error('VERSION_CONFLICT');
+    if (!res.ok) throw new Error(`Failed to ${action} step`);
+    return res.json();
+}
+
+export async function insertStep(
+    codelabId: string,
+    step: { title: string; content_markdown: string; position?: number },
+    version?: number,
+): Promise<StepChange> {
+    const res = await apiFetch(`/codelabs/${codelabId}/steps`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json', ...ifMatchHeaders(version) },
+        body: JSON.stringify(step),
+    });
+    return readStepChange(res, 'insert');
+}
+
+export async function updateStep(
+    codelabId: string,
+    stepId: string,
+    step: { title: string; content_markdown: string },
+    version?: number,
+): Promise<StepChange> {
+    const res = await apiFetch(`/codelabs/${codelabId}/steps/${stepId}`, {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json', ...ifMatchHeaders(version) },
+        body: JSON.stringify(step),
+    });
+    return readStepChange(res, 'update');
+}
+
+export async function deleteStep(codelabId: string, stepId: string, version?: number): Promise<StepChange> {
+    const res = await apiFetch(`/codelabs/${codelabId}/steps/${stepId}`, {
+        method: 'DELETE',
+        headers: ifMatchHeaders(version),
+    });
+    return readStepChange(res, 'delete');
+}
+
+export async function moveStep(
+    codelabId: string,
+    stepId: string,
+    position: number,
+    version?: number,
+): Promise<StepChange> {
+    const res = await apiFetch(`/codelabs/${codelabId}/steps/${stepId}/move`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json', ...ifMatchHeaders(version) },
+        body: JSON.stringify({ position }),
+    });
+    return readStepChange(res, 'move');
+}
+
+export async function getRevisions(codelabId: string): Promise<CodelabRevisionSummary[]> {
+    const res = await apiFetch(`/codelabs/${codelabId}/revisions`);
+    if (!res.ok) throw new Error('Failed to fetch revisions');
+    return res.json();
+}
+
+export async function getRevision(codelabId: string, revisionId: string): Promise<CodelabRevision> {
+    const res = await apiFetch(`/codelabs/${codelabId}/revisions/${revisionId}`);
+    if (!res.ok) throw new Error('Failed to fetch revision');
+    return res.json();
+}
+
+export async function diffRevisions(
+    codelabId: string,
+    from: string,
+    to?: string,
+): Promise<CodelabRevisionDiff> {
+    const params = new URLSearchParams({ from });
+    if (to) params.set('to', to);
+    const res = await apiFetch(`/codelabs/${codelabId}/revisions/diff?${params.toString()}`);
+    if (!res.ok) throw new Error('Failed to diff revisions');
+    return res.json();
+}
+
+export async function restoreRevision(codelabId: string, revisionId: string): Promise<CodelabRevision> {
+    const res = await apiFetch(`/codelabs/${codelabId}/revisions/${revisionId}/restore`, {
+        method: 'POST',
+    });
+    if (!res.ok) throw new Error('Failed to restore revision');
+    return res.json();
+}
+
+export async function getPublication(codelabId: string): Promise<PublicationStatus> {
+    const res = await apiFetch(`/codelabs/${codelabId}/publication`);
+    if (!res.ok) throw new Error('Failed to fetch publication status');
+    return res.json();
+}
+
+export async function publishCodelab(codelabId: string): Promise<PublicationStatus> {
+    const res = await apiFetch(`/codelabs/${codelabId}/publish`, {
+        method: 'POST',
+    });
+    if (!res.ok) throw new Error('Failed to publish codelab');
+    return res.json();
+}
+
 export async function deleteCodelab(codelabId: string): Promise<void> {
     const res = await apiFetch(`/codelabs/${codelabId}`, {
         method: 'DELETE'
@@ -130,7 +241,10 @@ export async function copyCodelab(id: string): Promise<Codelab> {
     return res.json();
 }
 
-export async function login(admin_id: string, admin_pw: string): Promise<{ status: string; token?: string }> {
+export async function login(
+    admin_id: string,
+    admin_pw: string,
+): Promise<{ status: string; token?: string; mfa_token?: string }> {
     const res = await apiFetch(`/login`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
@@ -140,6 +254,16 @@ export async function login(admin_id: string, admin_pw: string): Promise<{ statu
     return res.json();
 }
 
+export async function completeMfaLogin(mfa_token: string, code: string): Promise<{ status: string; token?: string }> {
+    const res = await apiFetch(`/login/mfa`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ mfa_token, code }),
+    });
+    if (!res.ok) throw new Error('Invalid two-factor code');
+    return res.json();
+}
+
 export async function logout(): Promise<void> {
     const res = await apiFetch(`/logout`, { method: 'POST' });
     if (!res.ok) throw new Error('Logout failed');
@@ -152,6 +276,21 @@ export async function getSession(): Promise<{ role: string; sub: string; exp: nu
     return res.json();
 }
 
+export interface SsoStatus {
+    enabled: boolean;
+    provider_name?: string;
+}
+
+export async function getSsoStatus(): Promise<SsoStatus> {
+    const res = await apiFetch(`/auth/oidc`);
+    if (!res.ok) return { enabled: false };
+    return res.json();
+}
+
+export function getSsoLoginUrl(returnTo = '/admin'): string {
+    return `${API_URL}/auth/oidc/login?return_to=${encodeURIComponent(returnTo)}`;
+}
+
 export interface AuditLog {
     id: string;
     action: string;
@@ -206,14 +345,20 @@ export async function saveAdminSettings(payload: { gemini_api_key: string }): Pr
     if (!res.ok) throw new Error('Failed to save settings to server');
 }
 
-export async function exportCodelab(id: string): Promise<void> {
-    const res = await apiFetch(`/codelabs/${id}/export`);
+const CODELAB_EXPORT_FILE_SUFFIX = { zip: '.zip', claat: '.md', html: '_site.zip' } as const;
+
+export async function exportCodelab(
+    id: string,
+    format: keyof typeof CODELAB_EXPORT_FILE_SUFFIX = 'zip',
+): Promise<void> {
+    const query = format === 'zip' ? '' : `?format=${format}`;
+    const res = await apiFetch(`/codelabs/${id}/export${query}`);
     if (!res.ok) throw new Error('Export failed');
     const blob = await res.blob();
     const url = window.URL.createObjectURL(blob);
     const a = document.createElement('a');
     a.href = url;
-    a.download = `codelab_${id}.zip`;
+    a.download = `codelab_${id}${CODELAB_EXPORT_FILE_SUFFIX[format]}`;
     document.body.appendChild(a);
     a.click();
     window.URL.revokeObjectURL(url);
@@ -290,13 +435,86 @@ export async function inspectBackup(file: File): Promise<{
     return res.json();
 }
 
-export async function registerAttendee(codelabId: string, name: string, code: string, email?: string): Promise<Attendee> {
+export async function findAttendeesByEmail(email: string): Promise<Attendee[]> {
+    const res = await apiFetch(`/admin/privacy/attendees?email=${encodeURIComponent(email)}`);
+    if (!res.ok) throw new Error('Failed to look up attendees');
+    return res.json();
+}
+
+export async function exportAttendeeData(attendeeId: string): Promise<void> {
+    const res = await apiFetch(`/admin/privacy/attendees/${attendeeId}/export`);
+    if (!res.ok) throw new Error('Attendee data export failed');
+    const blob = await res.blob();
+    const url = window.URL.createObjectURL(blob);
+    const a = document.createElement('a');
+    a.href = url;
+    a.download = `attendee_${attendeeId}.zip`;
+    document.body.appendChild(a);
+    a.click();
+    window.URL.revokeObjectURL(url);
+}
+
+export async function eraseAttendeeData(attendeeId: string): Promise<AttendeeErasure> {
+    const res = await apiFetch(`/admin/privacy/attendees/${attendeeId}/erase`, {
+        method: 'POST',
+    });
+    if (!res.ok) {
+        const text = await res.text().catch(() => '');
+        throw new Error(text || 'Attendee data erasure failed');
+    }
+    return res.json();
+}
+
+export async function getRetentionReport(): Promise<RetentionReport> {
+    const res = await apiFetch(`/admin/retention`);
+    if (!res.ok) throw new Error('Failed to fetch retention report');
+    return res.json();
+}
+
+export async function runRetention(): Promise<RetentionReport> {
+    const res = await apiFetch(`/admin/retention/run`, { method: 'POST' });
+    if (!res.ok) throw new Error('Retention run failed');
+    return res.json();
+}
+
+export interface JoinAccess {
+    passcode?: string;
+    invite_token?: string;
+    cohort_id?: string;
+}
+
+export interface CodelabJoinInfo {
+    id: string;
+    title: string;
+    description: string;
+    author: string;
+    is_public: boolean;
+    passcode_required: boolean;
+    invite_valid: boolean;
+}
+
+export async function getJoinInfo(codelabId: string, inviteToken?: string): Promise<CodelabJoinInfo> {
+    const query = inviteToken ? `?invite=${encodeURIComponent(inviteToken)}` : '';
+    const res = await apiFetch(`/codelabs/${codelabId}/join${query}`);
+    if (res.status === 403) throw new Error('PRIVATE_CODELAB');
+    if (!res.ok) throw new Error('Codelab not found');
+    return res.json();
+}
+
+export async function registerAttendee(codelabId: string, name: string, code: string, email?: string, access?: JoinAccess): Promise<Attendee> {
     const res = await apiFetch(`/codelabs/${codelabId}/register`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify({ name, code, email }),
+        body: JSON.stringify({ name, code, email, ...access }),
     });
     if (res.status === 409) throw new Error('DUPLICATE_NAME');
+    if (res.status === 202) {
+        // The codelab is full; the learner is queued and gets no session.
+        const status = await res.json();
+        throw Object.assign(new Error('WAITLISTED'), {
+            position: status.position as number,
+        });
+    }
     if (!res.ok) {
         const text = await res.text().catch(() => '');
         throw new Error(text || 'Registration failed');
@@ -329,12 +547,54 @@ export async function resolveHelpRequest(codelabId: string, helpId: string): Pro
     if (!res.ok) throw new Error('Failed to resolve help request');
 }
 
-export async function getAttendees(codelabId: string): Promise<Attendee[]> {
-    const res = await apiFetch(`/codelabs/${codelabId}/attendees`);
+export async function getAttendees(codelabId: string, cohortId?: string): Promise<Attendee[]> {
+    const query = cohortId ? `?cohort_id=${encodeURIComponent(cohortId)}` : '';
+    const res = await apiFetch(`/codelabs/${codelabId}/attendees${query}`);
     if (!res.ok) throw new Error('Failed to fetch attendees');
     return res.json();
 }
 
+export async function importRoster(
+    codelabId: string,
+    payload: { attendees?: RosterEntry[]; csv?: string; cohort_id?: string },
+): Promise<RosterImportResult> {
+    const res = await apiFetch(`/codelabs/${codelabId}/attendees/import`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(payload),
+    });
+    if (!res.ok) {
+        const text = await res.text().catch(() => '');
+        throw new Error(text || 'Failed to import roster');
+    }
+    return res.json();
+}
+
+export async function getCohorts(codelabId: string): Promise<CohortSummary[]> {
+    const res = await apiFetch(`/codelabs/${codelabId}/cohorts`);
+    if (!res.ok) throw new Error('Failed to fetch cohorts');
+    return res.json();
+}
+
+export async function createCohort(
+    codelabId: string,
+    payload: { name: string; starts_at?: number | null; ends_at?: number | null },
+): Promise<Cohort> {
+    const res = await apiFetch(`/codelabs/${codelabId}/cohorts`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(payload),
+    });
+    if (!res.ok) throw new Error('Failed to create cohort');
+    return res.json();
+}
+
+export async function deleteCohort(codelabId: string, cohortId: string): Promise<void> {
+    const res = await apiFetch(`/codelabs/${codelabId}/cohorts/${cohortId}`, { method: 'DELETE' });
+    if (res.status === 409) throw new Error('COHORT_HAS_ATTENDEES');
+    if (!res.ok) throw new Error('Failed to delete cohort');
+}
+
 export async function getChatHistory(codelabId: string): Promise<ChatMessage[]> {
     const res = await apiFetch(`/codelabs/${codelabId}/chat`);
     if (!res.ok) throw new Error('Failed to fetch chat history');
@@ -478,6 +738,14 @@ export async function getUpdateStatus(): Promise<{
 
 export async function completeCodelab(codelabId: string): Promise<void> {
     const res = await apiFetch(`/codelabs/${codelabId}/complete`, { method: 'POST' });
+    if (res.status === 400) {
+        const body = await res.json().catch(() => null);
+        if (Array.isArray(body?.unmet)) {
+            throw Object.assign(new Error('REQUIREMENTS_NOT_MET'), {
+                unmet: body.unmet as CompletionRequirement[],
+            });
+        }
+    }
     if (!res.ok) throw new Error('Failed to complete codelab');
 }
 
@@ -497,6 +765,12 @@ export function getWsUrl(codelabId: string, roleHint?: 'admin' | 'attendee', tok
     return queryString ? `${base}?${queryString}` : base;
 }
 
+export function getAuthoringWsUrl(codelabId: string, token?: string): string {
+    const url = new URL(API_URL.replace('http', 'ws'));
+    const base = `${url.protocol}//${url.host}/api/ws/${codelabId}/authoring`;
+    return token ? `${base}?token=${encodeURIComponent(token)}` : base;
+}
+
 export async function getMaterials(codelabId: string): Promise<Material[]> {
     const res = await apiFetch(`/codelabs/${codelabId}/materials`);
     if (!res.ok) throw new Error('Failed to fetch materials');
@@ -509,13 +783,14 @@ export async function getQuizzes(codelabId: string): Promise<Quiz[]> {
     return res.json();
 }
 
-export async function submitQuiz(codelabId: string, payload: QuizSubmissionPayload): Promise<void> {
+export async function submitQuiz(codelabId: string, payload: QuizSubmissionPayload): Promise<QuizSubmissionResult | void> {
     const res = await apiFetch(`/codelabs/${codelabId}/quizzes/submit`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(payload),
     });
     if (!res.ok) throw new Error('Quiz submission failed');
+    return res.json();
 }
 
 export async function getQuizSubmissions(codelabId: string): Promise<QuizSubmissionWithAttendee[]> {
@@ -4,6 +4,10 @@ export async function returnEmptyList<T = never>(..._args: unknown[]): Promise<T
 
 export async function noOpAsync(..._args: unknown[]): Promise<void> {}
 
+export async function returnSsoDisabled(..._args: unknown[]): Promise<{ enabled: boolean }> {
+    return { enabled: false };
+}
+
 export function backendNoopUnsubscribe(_cb: unknown): () => void {
     return () => {};
 }
@@ -16,15 +16,24 @@ export const createCodelab = backend.createCodelab;
 export const updateCodelab = backend.updateCodelab;
 export const copyCodelab = backend.copyCodelab;
 export const saveSteps = backend.saveSteps;
+export const insertStep = backend.insertStep;
+export const updateStep = backend.updateStep;
+export const deleteStep = backend.deleteStep;
+export const moveStep = backend.moveStep;
+export const publishCodelab = backend.publishCodelab;
 export const deleteCodelab = backend.deleteCodelab;
 export const login = backend.login;
+export const completeMfaLogin = backend.completeMfaLogin;
 export const saveAdminSettings = backend.saveAdminSettings;
 export const loginWithGoogle = throwNotSupportedInBackendMode;
 export const logout = backend.logout;
 export const onAuthChange = backendNoopUnsubscribe;
 export const getSession = backend.getSession;
+export const getSsoStatus = backend.getSsoStatus;
+export const getSsoLoginUrl = backend.getSsoLoginUrl;
 
 export const registerAttendee = backend.registerAttendee;
+export const getJoinInfo = backend.getJoinInfo;
 export const updateAttendeeProgress = noOpAsync;
 export const requestHelp = backend.requestHelp;
 export const getHelpRequests = backend.getHelpRequests;
@@ -60,6 +69,7 @@ export const submitQuiz = backend.submitQuiz;
 export const getQuizSubmissions = backend.getQuizSubmissions;
 
 export const getWsUrl = backend.getWsUrl;
+export const getAuthoringWsUrl = backend.getAuthoringWsUrl;
 export function listenToWsReplacement(
     _codelabId: string,
     _callback: (msg: unknown) => void,
@@ -4,6 +4,7 @@ import {
     alertExportCodelabNotSupported,
     noOpAsync,
     returnEmptyList,
+    returnSsoDisabled,
     throwImportCodelabNotSupported,
     throwInspectBackupNotSupported,
     throwLinkSubmissionNotSupported,
@@ -23,15 +24,24 @@ export const createCodelab = firebase.createCodelab;
 export const updateCodelab = firebase.updateCodelab;
 export const copyCodelab = throwNotSupportedInServerlessMode;
 export const saveSteps = firebase.saveSteps;
+export const insertStep = throwNotSupportedInServerlessMode;
+export const updateStep = throwNotSupportedInServerlessMode;
+export const deleteStep = throwNotSupportedInServerlessMode;
+export const moveStep = throwNotSupportedInServerlessMode;
+export const publishCodelab = throwNotSupportedInServerlessMode;
 export const deleteCodelab = firebase.deleteCodelab;
 export const login = firebase.login;
+export const completeMfaLogin = throwNotSupportedInServerlessMode;
 export const saveAdminSettings = noOpAsync;
 export const loginWithGoogle = firebase.loginWithGoogle;
 export const logout = firebase.logout;
 export const onAuthChange = firebase.onAuthChange;
 export const getSession = firebase.getSession;
+export const getSsoStatus = returnSsoDisabled;
+export const getSsoLoginUrl = (_returnTo?: string) => "";
 
 export const registerAttendee = firebase.registerAttendee;
+export const getJoinInfo = throwNotSupportedInServerlessMode;
 export const updateAttendeeProgress = firebase.updateAttendeeProgress;
 export const requestHelp = firebase.requestHelp;
 export const getHelpRequests = firebase.getHelpRequests;
@@ -67,6 +77,7 @@ export const submitQuiz = noOpAsync;
 export const getQuizSubmissions = returnEmptyList;
 
 export const getWsUrl = () => "";
+export const getAuthoringWsUrl = () => "";
 export const listenToWsReplacement = firebase.listenToWsReplacement;
 
 export const isFirebaseMode = () => true;
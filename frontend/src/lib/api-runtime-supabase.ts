@@ -4,6 +4,7 @@ import {
     alertExportCodelabNotSupported,
     noOpAsync,
     returnEmptyList,
+    returnSsoDisabled,
     throwImportCodelabNotSupported,
     throwInspectBackupNotSupported,
     throwLinkSubmissionNotSupported,
@@ -22,15 +23,24 @@ export const createCodelab = supabase.createCodelab;
 export const updateCodelab = supabase.updateCodelab;
 export const copyCodelab = throwNotSupportedInServerlessMode;
 export const saveSteps = supabase.saveSteps;
+export const insertStep = throwNotSupportedInServerlessMode;
+export const updateStep = throwNotSupportedInServerlessMode;
+export const deleteStep = throwNotSupportedInServerlessMode;
+export const moveStep = throwNotSupportedInServerlessMode;
+export const publishCodelab = throwNotSupportedInServerlessMode;
 export const deleteCodelab = supabase.deleteCodelab;
 export const login = supabase.login;
+export const completeMfaLogin = throwNotSupportedInServerlessMode;
 export const saveAdminSettings = noOpAsync;
 export const loginWithGoogle = supabase.loginWithGoogle;
 export const logout = supabase.logout;
 export const onAuthChange = supabase.onAuthChange;
 export const getSession = supabase.getSession;
+export const getSsoStatus = returnSsoDisabled;
+export const getSsoLoginUrl = (_returnTo?: string) => "";
 
 export const registerAttendee = supabase.registerAttendee;
+export const getJoinInfo = throwNotSupportedInServerlessMode;
 export const updateAttendeeProgress = supabase.updateAttendeeProgress;
 export const requestHelp = supabase.requestHelp;
 export const getHelpRequests = supabase.getHelpRequests;
@@ -66,6 +76,7 @@ export const submitQuiz = supabase.submitQuiz;
 export const getQuizSubmissions = supabase.getQuizSubmissions;
 
 export const getWsUrl = () => "";
+export const getAuthoringWsUrl = () => "";
 export const listenToWsReplacement = supabase.listenToWsReplacement;
 
 export const isFirebaseMode = () => false;
@@ -7,6 +7,7 @@ import {
     backendNoopUnsubscribe,
     noOpAsync,
     returnEmptyList,
+    returnSsoDisabled,
     throwImportCodelabNotSupported,
     throwInspectBackupNotSupported,
     throwLinkSubmissionNotSupported,
@@ -19,9 +20,11 @@ import {
 import type {
     AiConversation,
     Attendee,
+    AuthoringEditor,
     CertificateInfo,
     ChatMessage,
     Codelab,
+    CompletionRequirement,
     CreateInlineCommentPayload,
     Feedback,
     HelpRequest,
@@ -34,6 +37,7 @@ import type {
     QuizSubmissionWithAttendee,
     SaveAiConversationPayload,
     Step,
+    StepChange,
     Submission,
     SubmissionWithAttendee,
 } from "./types";
@@ -41,9 +45,11 @@ import type {
 export type {
     AiConversation,
     Attendee,
+    AuthoringEditor,
     CertificateInfo,
     ChatMessage,
     Codelab,
+    CompletionRequirement,
     CreateInlineCommentPayload,
     Feedback,
     HelpRequest,
@@ -56,6 +62,7 @@ export type {
     QuizSubmissionWithAttendee,
     SaveAiConversationPayload,
     Step,
+    StepChange,
     Submission,
     SubmissionWithAttendee,
 };
@@ -115,6 +122,56 @@ export const saveSteps = selectByMode({
     firebase: firebase.saveSteps,
     supabase: supabase.saveSteps,
 });
+export const insertStep = selectByMode({
+    backend: backend.insertStep,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const updateStep = selectByMode({
+    backend: backend.updateStep,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const deleteStep = selectByMode({
+    backend: backend.deleteStep,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const moveStep = selectByMode({
+    backend: backend.moveStep,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const getRevisions = selectByMode({
+    backend: backend.getRevisions,
+    firebase: returnEmptyList,
+    supabase: returnEmptyList,
+});
+export const getRevision = selectByMode({
+    backend: backend.getRevision,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const diffRevisions = selectByMode({
+    backend: backend.diffRevisions,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const restoreRevision = selectByMode({
+    backend: backend.restoreRevision,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const getPublication = selectByMode({
+    backend: backend.getPublication,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const publishCodelab = selectByMode({
+    backend: backend.publishCodelab,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
 export const deleteCodelab = selectByMode({
     backend: backend.deleteCodelab,
     firebase: firebase.deleteCodelab,
@@ -125,6 +182,11 @@ export const login = selectByMode({
     firebase: firebase.login,
     supabase: supabase.login,
 });
+export const completeMfaLogin = selectByMode({
+    backend: backend.completeMfaLogin,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
 export const saveAdminSettings = selectByMode({
     backend: backend.saveAdminSettings,
     firebase: noOpAsync,
@@ -150,12 +212,28 @@ export const getSession = selectByMode({
     firebase: firebase.getSession,
     supabase: supabase.getSession,
 });
+export const getSsoStatus = selectByMode({
+    backend: backend.getSsoStatus,
+    firebase: returnSsoDisabled,
+    supabase: returnSsoDisabled,
+});
+export const getSsoLoginUrl = selectByMode({
+    backend: backend.getSsoLoginUrl,
+    firebase: (_returnTo?: string) => "",
+    supabase: (_returnTo?: string) => "",
+});
 
 export const registerAttendee = selectByMode({
     backend: backend.registerAttendee,
     firebase: firebase.registerAttendee,
     supabase: supabase.registerAttendee,
 });
+export const getJoinInfo = selectByMode({
+    backend: backend.getJoinInfo,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export type { CodelabJoinInfo, JoinAccess } from "./api-backend";
 export const updateAttendeeProgress = selectByMode({
     backend: noOpAsync,
     firebase: firebase.updateAttendeeProgress,
@@ -181,6 +259,26 @@ export const getAttendees = selectByMode({
     firebase: firebase.getAttendees,
     supabase: supabase.getAttendees,
 });
+export const importRoster = selectByMode({
+    backend: backend.importRoster,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const getCohorts = selectByMode({
+    backend: backend.getCohorts,
+    firebase: returnEmptyList,
+    supabase: returnEmptyList,
+});
+export const createCohort = selectByMode({
+    backend: backend.createCohort,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const deleteCohort = selectByMode({
+    backend: backend.deleteCohort,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
 export const getChatHistory = selectByMode({
     backend: backend.getChatHistory,
     firebase: firebase.getChatHistory,
@@ -308,6 +406,7 @@ export const getQuizSubmissions = selectByMode({
 });
 
 export const getWsUrl = backend.getWsUrl;
+export const getAuthoringWsUrl = backend.getAuthoringWsUrl;
 export const listenToWsReplacement = selectByMode({
     backend: firebase.listenToWsReplacement,
     firebase: firebase.listenToWsReplacement,
@@ -343,6 +442,31 @@ export const inspectBackup = selectByMode({
     firebase: throwInspectBackupNotSupported,
     supabase: throwInspectBackupNotSupported,
 });
+export const findAttendeesByEmail = selectByMode({
+    backend: backend.findAttendeesByEmail,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const exportAttendeeData = selectByMode({
+    backend: backend.exportAttendeeData,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const eraseAttendeeData = selectByMode({
+    backend: backend.eraseAttendeeData,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const getRetentionReport = selectByMode({
+    backend: backend.getRetentionReport,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
+export const runRetention = selectByMode({
+    backend: backend.runRetention,
+    firebase: throwNotSupportedInServerlessMode,
+    supabase: throwNotSupportedInServerlessMode,
+});
 
 export const createCodeServer = selectByMode({
     backend: backend.createCodeServer,
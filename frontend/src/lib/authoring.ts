@@ -0,0 +1,353 @@
+import type { AuthoringDocument, AuthoringEditor } from './types';
+
+/**
+ * Text edit in the ot.js wire format: a positive number keeps that many
+ * characters, a negative number deletes that many, and a string is inserted.
+ * Lengths are JavaScript string lengths (UTF-16 code units), as on the server.
+ */
+export type TextOperation = Array<number | string>;
+
+const isRetain = (c: number | string | undefined): c is number => typeof c === 'number' && c > 0;
+const isDelete = (c: number | string | undefined): c is number => typeof c === 'number' && c < 0;
+const isInsert = (c: number | string | undefined): c is string => typeof c === 'string';
+
+function push(op: TextOperation, component: number | string) {
+    if (component === 0 || component === '') return;
+    const last = op[op.length - 1];
+    if (isRetain(component) && isRetain(last)) {
+        op[op.length - 1] = last + component;
+    } else if (isDelete(component) && isDelete(last)) {
+        op[op.length - 1] = last + component;
+    } else if (isInsert(component) && isInsert(last)) {
+        op[op.length - 1] = last + component;
+    } else if (isInsert(component) && isDelete(last)) {
+        // Keep inserts before deletes at the same position, like the server.
+        const before = op[op.length - 2];
+        if (isInsert(before)) {
+            op[op.length - 2] = before + component;
+        } else {
+            op.splice(op.length - 1, 0, component);
+        }
+    } else {
+        op.push(component);
+    }
+}
+
+/** Builds the operation turning `oldText` into `newText` as one replaced range. */
+export function diffOperation(oldText: string, newText: string): TextOperation {
+    let start = 0;
+    const max = Math.min(oldText.length, newText.length);
+    while (start < max && oldText.charCodeAt(start) === newText.charCodeAt(start)) start++;
+    let end = 0;
+    while (
+        end < max - start &&
+        oldText.charCodeAt(oldText.length - 1 - end) === newText.charCodeAt(newText.length - 1 - end)
+    ) {
+        end++;
+    }
+    // Never split a surrogate pair; lone surrogates cannot be sent as JSON.
+    const isLowSurrogate = (code: number) => code >= 0xdc00 && code <= 0xdfff;
+    if (start > 0 && start < max && isLowSurrogate(oldText.charCodeAt(start))) start--;
+    if (end > 0 && isLowSurrogate(newText.charCodeAt(newText.length - end))) end--;
+    const op: TextOperation = [];
+    push(op, start);
+    push(op, newText.slice(start, newText.length - end));
+    push(op, -(oldText.length - start - end));
+    push(op, end);
+    return op;
+}
+
+export function applyOperation(op: TextOperation, text: string): string {
+    let out = '';
+    let index = 0;
+    for (const component of op) {
+        if (isRetain(component)) {
+            out += text.slice(index, index + component);
+            index += component;
+        } else if (isInsert(component)) {
+            out += component;
+        } else {
+            index -= component;
+        }
+    }
+    if (index !== text.length) throw new Error('operation does not fit the document');
+    return out;
+}
+
+/** Moves a position through an operation; text inserted at it ends up before it. */
+export function transformPosition(op: TextOperation, position: number): number {
+    let moved = position;
+    let index = 0;
+    for (const component of op) {
+        if (index > position) break;
+        if (isRetain(component)) {
+            index += component;
+        } else if (isInsert(component)) {
+            moved += component.length;
+        } else {
+            moved -= Math.min(-component, position - index);
+            index -= component;
+        }
+    }
+    return moved;
+}
+
+/** Returns `[a', b']` so that `b'` after `a` equals `a'` after `b`; `a` inserts first. */
+export function transformOperations(a: TextOperation, b: TextOperation): [TextOperation, TextOperation] {
+    const aPrime: TextOperation = [];
+    const bPrime: TextOperation = [];
+    let i = 0;
+    let j = 0;
+    let ca = a[i++];
+    let cb = b[j++];
+    while (ca !== undefined || cb !== undefined) {
+        if (isInsert(ca)) {
+            push(aPrime, ca);
+            push(bPrime, ca.length);
+            ca = a[i++];
+            continue;
+        }
+        if (isInsert(cb)) {
+            push(aPrime, cb.length);
+            push(bPrime, cb);
+            cb = b[j++];
+            continue;
+        }
+        if (ca === undefined || cb === undefined) throw new Error('operations do not fit');
+        const n = Math.min(Math.abs(ca), Math.abs(cb));
+        if (isRetain(ca) && isRetain(cb)) {
+            push(aPrime, n);
+            push(bPrime, n);
+        } else if (isDelete(ca) && isRetain(cb)) {
+            push(aPrime, -n);
+        } else if (isRetain(ca) && isDelete(cb)) {
+            push(bPrime, -n);
+        }
+        ca = Math.abs(ca) > n ? Math.sign(ca) * (Math.abs(ca) - n) : a[i++];
+        cb = Math.abs(cb) > n ? Math.sign(cb) * (Math.abs(cb) - n) : b[j++];
+    }
+    return [aPrime, bPrime];
+}
+
+/** Returns one operation with the effect of `a` followed by `b`. */
+export function composeOperations(a: TextOperation, b: TextOperation): TextOperation {
+    const out: TextOperation = [];
+    let i = 0;
+    let j = 0;
+    let ca = a[i++];
+    let cb = b[j++];
+    while (ca !== undefined || cb !== undefined) {
+        if (isDelete(ca)) {
+            push(out, ca);
+            ca = a[i++];
+            continue;
+        }
+        if (isInsert(cb)) {
+            push(out, cb);
+            cb = b[j++];
+            continue;
+        }
+        if (ca === undefined || cb === undefined) throw new Error('operations do not fit');
+        if (isInsert(ca)) {
+            const n = Math.min(ca.length, Math.abs(cb));
+            if (isRetain(cb)) push(out, ca.slice(0, n));
+            ca = ca.length > n ? ca.slice(n) : a[i++];
+            cb = Math.abs(cb) > n ? Math.sign(cb) * (Math.abs(cb) - n) : b[j++];
+            continue;
+        }
+        const n = Math.min(ca, Math.abs(cb));
+        push(out, isRetain(cb) ? n : -n);
+        ca = ca > n ? ca - n : a[i++];
+        cb = Math.abs(cb) > n ? Math.sign(cb) * (Math.abs(cb) - n) : b[j++];
+    }
+    return out;
+}
+
+interface StepState {
+    text: string;
+    revision: number;
+    /** Sent and waiting for the server to apply it. */
+    outstanding: TextOperation | null;
+    /** Made while waiting; sent once `outstanding` is applied. */
+    buffer: TextOperation | null;
+}
+
+export interface AuthoringCallbacks {
+    /** The server state replaced the local one; pending local edits were dropped. */
+    onSnapshot: (steps: AuthoringDocument[]) => void;
+    /** Another editor changed a step; `op` maps old positions to new ones. */
+    onRemoteChange: (stepId: string, text: string, op: TextOperation) => void;
+    onEditors: (editors: AuthoringEditor[]) => void;
+    onSaved?: (stepsVersion: number) => void;
+    /** The steps changed elsewhere and the unsaved edits to `stepIds` were discarded. */
+    onConflict?: (stepIds: string[]) => void;
+}
+
+/** Keeps the step markdown of one codelab in sync with the other editors. */
+export class AuthoringClient {
+    sessionId: string | null = null;
+    private ws: WebSocket | null = null;
+    private steps = new Map<string, StepState>();
+    private editors: AuthoringEditor[] = [];
+    private closed = false;
+    private cursorTimer: ReturnType<typeof setTimeout> | null = null;
+    private pendingCursor: { stepId: string; start: number; end: number } | null = null;
+
+    constructor(
+        private url: string,
+        private callbacks: AuthoringCallbacks,
+    ) {
+        this.connect();
+    }
+
+    /** Reports the current markdown of a step; sends the difference if it changed. */
+    update(stepId: string, text: string) {
+        const step = this.steps.get(stepId);
+        if (!step || step.text === text) return;
+        const op = diffOperation(step.text, text);
+        step.text = text;
+        if (!step.outstanding) {
+            step.outstanding = op;
+            this.sendOperation(stepId, step.revision, op);
+        } else {
+            step.buffer = step.buffer ? composeOperations(step.buffer, op) : op;
+        }
+    }
+
+    /** Shares the local cursor; calls are coalesced to a few per second. */
+    moveCursor(stepId: string, start: number, end: number) {
+        this.pendingCursor = { stepId, start, end };
+        if (this.cursorTimer) return;
+        this.cursorTimer = setTimeout(() => {
+            this.cursorTimer = null;
+            const cursor = this.pendingCursor;
+            const step = cursor && this.steps.get(cursor.stepId);
+            if (!cursor || !step || step.outstanding) return;
+            this.send({
+                type: 'cursor',
+                step_id: cursor.stepId,
+                revision: step.revision,
+                position: cursor.start,
+                selection_end: cursor.end !== cursor.start ? cursor.end : null,
+            });
+        }, 150);
+    }
+
+    close() {
+        this.closed = true;
+        if (this.cursorTimer) clearTimeout(this.cursorTimer);
+        this.ws?.close();
+    }
+
+    private connect() {
+        const ws = new WebSocket(this.url);
+        ws.addEventListener('message', (event) => {
+            try {
+                this.handle(JSON.parse(event.data));
+            } catch (e) {
+                console.error('Authoring message error:', e);
+            }
+        });
+        ws.onclose = () => {
+            if (!this.closed) setTimeout(() => this.connect(), 3000);
+        };
+        this.ws = ws;
+    }
+
+    private send(payload: unknown) {
+        if (this.ws?.readyState === WebSocket.OPEN) this.ws.send(JSON.stringify(payload));
+    }
+
+    private sendOperation(stepId: string, revision: number, operation: TextOperation) {
+        this.send({ type: 'operation', step_id: stepId, revision, operation });
+    }
+
+    private handle(data: any) {
+        switch (data.type) {
+            case 'authoring_ready':
+                this.sessionId = data.session_id;
+                this.reset(data.steps);
+                this.setEditors(data.editors);
+                break;
+            case 'authoring_snapshot':
+                this.reset(data.steps);
+                break;
+            case 'authoring_operation':
+                this.receiveOperation(data);
+                break;
+            case 'authoring_presence':
+                this.setEditors(data.editors);
+                break;
+            case 'authoring_cursor':
+                this.setEditors(
+                    this.editors.map((editor) =>
+                        editor.session_id === data.session_id ? { ...editor, ...data } : editor,
+                    ),
+                );
+                break;
+            case 'authoring_saved':
+                this.callbacks.onSaved?.(data.steps_version);
+                break;
+            case 'authoring_conflict':
+                this.callbacks.onConflict?.(data.step_ids);
+                break;
+            case 'authoring_error':
+                console.warn('Authoring edit rejected:', data.message);
+                break;
+        }
+    }
+
+    private reset(documents: AuthoringDocument[]) {
+        this.steps = new Map(
+            documents.map((doc) => [
+                doc.id,
+                { text: doc.content_markdown, revision: doc.revision, outstanding: null, buffer: null },
+            ]),
+        );
+        this.callbacks.onSnapshot(documents);
+    }
+
+    private receiveOperation(data: {
+        session_id: string;
+        step_id: string;
+        revision: number;
+        operation: TextOperation;
+    }) {
+        const step = this.steps.get(data.step_id);
+        // Operations already contained in the snapshot arrive once more after joining.
+        if (!step || data.revision <= step.revision) return;
+        step.revision = data.revision;
+
+        if (data.session_id === this.sessionId) {
+            step.outstanding = step.buffer;
+            step.buffer = null;
+            if (step.outstanding) this.sendOperation(data.step_id, step.revision, step.outstanding);
+            return;
+        }
+
+        let op = data.operation;
+        if (step.outstanding) [step.outstanding, op] = transformOperations(step.outstanding, op);
+        if (step.buffer) [step.buffer, op] = transformOperations(step.buffer, op);
+        step.text = applyOperation(op, step.text);
+        this.setEditors(
+            this.editors.map((editor) =>
+                editor.step_id === data.step_id && editor.position != null
+                    ? {
+                          ...editor,
+                          position: transformPosition(op, editor.position),
+                          selection_end:
+                              editor.selection_end != null
+                                  ? transformPosition(op, editor.selection_end)
+                                  : editor.selection_end,
+                      }
+                    : editor,
+            ),
+        );
+        this.callbacks.onRemoteChange(data.step_id, step.text, op);
+    }
+
+    private setEditors(editors: AuthoringEditor[]) {
+        this.editors = editors;
+        this.callbacks.onEditors(editors.filter((editor) => editor.session_id !== this.sessionId));
+    }
+}
@@ -8,7 +8,17 @@ export interface Codelab {
     require_quiz: boolean;
     require_feedback: boolean;
     require_submission?: boolean;
+    quiz_max_attempts?: number;
+    quiz_pass_percentage?: number;
+    max_attendees?: number;
+    registration_opens_at?: number | null;
+    registration_closes_at?: number | null;
     guide_markdown?: string;
+    published_revision_id?: string | null;
+    published_at?: string | null;
+    version?: number;
+    steps_version?: number;
+    categories?: string | null;
     created_at?: string;
 }
 
@@ -29,6 +39,66 @@ export interface Step {
     step_number: number;
     title: string;
     content_markdown: string;
+    duration_minutes?: number | null;
+}
+
+export interface StepChange {
+    step_id: string;
+    revision_id: string;
+    steps_version: number;
+    steps: Step[];
+}
+
+export interface RevisionStep {
+    id: string;
+    title: string;
+    content_markdown: string;
+}
+
+export interface CodelabRevisionSummary {
+    id: string;
+    codelab_id: string;
+    revision_number: number;
+    author_id?: string | null;
+    author_name?: string | null;
+    source: 'baseline' | 'steps_update' | 'guide_update' | 'restore' | 'publish' | 'collaboration';
+    created_at?: string;
+}
+
+export interface CodelabRevision extends CodelabRevisionSummary {
+    guide_markdown?: string | null;
+    steps: RevisionStep[];
+}
+
+export interface CodelabRevisionDiff {
+    from: CodelabRevisionSummary;
+    to: CodelabRevisionSummary;
+    diff: string;
+}
+
+export interface PublicationStatus {
+    codelab_id: string;
+    published_revision_id?: string | null;
+    published_revision_number?: number | null;
+    published_at?: string | null;
+    latest_revision_number?: number | null;
+    has_unpublished_changes: boolean;
+}
+
+export interface AuthoringDocument {
+    id: string;
+    title: string;
+    content_markdown: string;
+    revision: number;
+}
+
+export interface AuthoringEditor {
+    session_id: string;
+    user_id: string;
+    name: string;
+    step_id?: string | null;
+    position?: number | null;
+    selection_end?: number | null;
 }
 
 export interface Attendee {
@@ -42,6 +112,88 @@ export interface Attendee {
     completed_at?: string;
     created_at?: string;
     token?: string;
+    cohort_id?: string | null;
+}
+
+export interface RosterEntry {
+    name: string;
+    email?: string | null;
+    code?: string | null;
+}
+
+export interface RosterImportRow {
+    row: number;
+    name: string;
+    email?: string | null;
+    status: 'created' | 'duplicate' | 'invalid';
+    attendee_id?: string | null;
+    code?: string | null;
+    join_path?: string | null;
+    error?: string | null;
+}
+
+export interface RosterImportResult {
+    created: number;
+    duplicates: number;
+    invalid: number;
+    rows: RosterImportRow[];
+}
+
+export interface AttendeeErasure {
+    attendee_id: string;
+    codelab_id: string;
+    pseudonym: string;
+    chat_messages_deleted: number;
+    waitlist_entries_deleted: number;
+    feedback_redacted: number;
+    quiz_answers_redacted: number;
+    submissions_deleted: number;
+    files_deleted: number;
+    ai_records_deleted: number;
+    inline_comments_deleted: number;
+    certificates_deleted: number;
+    audit_logs_scrubbed: number;
+    sessions_revoked: number;
+}
+
+export interface RetentionPolicy {
+    chat_days?: number | null;
+    ai_days?: number | null;
+    submission_days?: number | null;
+    audit_days?: number | null;
+    interval_hours: number;
+}
+
+export interface RetentionReport {
+    dry_run: boolean;
+    policy: RetentionPolicy;
+    codelab_ids: string[];
+    chat_messages: number;
+    ai_conversations: number;
+    ai_threads: number;
+    ai_messages: number;
+    submissions: number;
+    submission_files: number;
+    orphaned_files: number;
+    audit_logs: number;
+}
+
+export interface Cohort {
+    id: string;
+    codelab_id: string;
+    name: string;
+    starts_at?: number | null;
+    ends_at?: number | null;
+    created_at?: string;
+}
+
+export interface CohortSummary extends Cohort {
+    attendee_count: number;
+    completed_count: number;
+    help_request_count: number;
+    feedback_count: number;
+    avg_satisfaction?: number | null;
+    avg_difficulty?: number | null;
 }
 
 export interface HelpRequest {
@@ -122,6 +274,9 @@ export interface Material {
     created_at?: string;
 }
 
+/** Completion requirement codes reported by `POST /codelabs/{id}/complete`. */
+export type CompletionRequirement = 'QUIZ_REQUIRED' | 'FEEDBACK_REQUIRED' | 'SUBMISSION_REQUIRED';
+
 export interface CertificateInfo {
     attendee_name: string;
     codelab_title: string;
@@ -129,6 +284,11 @@ export interface CertificateInfo {
     author: string;
     completed_at: string;
     verification_url: string;
+    /** Stable certificate identifier (absent for serverless runtimes). */
+    certificate_id?: string;
+    /** Compact EdDSA JWS that third parties can verify with `/api/certificates/jwks`. */
+    signed_certificate?: string;
+    revoked?: boolean;
 }
 
 export interface QuizSubmission {
@@ -138,6 +298,18 @@ export interface QuizSubmission {
     quiz_id: string;
     answer: string;
     is_correct: boolean;
+    attempt_id?: string | null;
+    created_at?: string;
+}
+
+export interface QuizAttempt {
+    id: string;
+    codelab_id: string;
+    attendee_id: string;
+    attempt_number: number;
+    total_questions: number;
+    correct_count: number;
+    score: number;
     created_at?: string;
 }
 
@@ -153,6 +325,22 @@ export interface QuizSubmissionPayload {
     }[];
 }
 
+export interface QuizSubmissionResult {
+    attempt_id: string;
+    attempt_number: number;
+    total: number;
+    correct: number;
+    score: number;
+    pass_percentage: number;
+    passed: boolean;
+    remaining_attempts: number | null;
+    results: {
+        quiz_id: string;
+        answer: string;
+        is_correct: boolean;
+    }[];
+}
+
 export interface Submission {
     id: string;
     codelab_id: string;